@@ -552,6 +552,7 @@ impl<'a> ComponentComposer<'a> {
                 define_components: !self.config.import_components,
                 export: Some(root_instance),
                 validate: false,
+                dedupe: self.config.dedupe,
             },
             &graph,
         )
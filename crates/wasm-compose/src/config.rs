@@ -105,6 +105,11 @@ pub struct Config {
     #[serde(default)]
     pub disallow_imports: bool,
 
+    /// Whether or not to deduplicate byte-identical core modules and
+    /// components in the composed component.
+    #[serde(default)]
+    pub dedupe: bool,
+
     /// The explicit, transitive dependencies of the root component.
     #[serde(default, deserialize_with = "de::index_map")]
     pub dependencies: IndexMap<String, Dependency>,
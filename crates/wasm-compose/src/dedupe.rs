@@ -0,0 +1,433 @@
+//! Module for deduplicating nested core modules and components.
+//!
+//! Compositions frequently embed the same core module or component more than
+//! once, for example when two dependencies were built against the same adapter.
+//! The pass in this module keeps the first definition of every byte-identical
+//! module or component within a component and rewrites all references to
+//! later copies to point at that first definition.
+
+use crate::encoding::TypeEncoder;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::mem;
+use wasm_encoder::{
+    Alias, Component, ComponentAliasSection, ComponentExportKind, ComponentExportSection,
+    ComponentInstanceSection, ComponentNameSection, ComponentOuterAliasKind, ComponentSection,
+    ComponentSectionId, ComponentTypeRef, ComponentValType, Encode, ExportKind, InstanceSection,
+    Module, ModuleArg, NameMap, RawSection, Section, TypeBounds,
+};
+use wasmparser::{
+    ComponentAlias, ComponentExternalKind, ComponentInstance, ComponentName,
+    ComponentNameSectionReader, ExternalKind, Instance, Parser, Payload,
+};
+
+/// The result of deduplicating a WebAssembly component.
+#[derive(Debug, Clone, Default)]
+pub struct Deduped {
+    /// The bytes of the deduplicated component.
+    pub bytes: Vec<u8>,
+    /// The number of core module definitions that were removed.
+    pub modules_removed: usize,
+    /// The number of component definitions that were removed.
+    pub components_removed: usize,
+    /// The number of bytes saved relative to the input.
+    pub bytes_saved: usize,
+}
+
+/// Removes byte-identical nested core modules and components from the given
+/// WebAssembly component.
+///
+/// Only definitions within the same enclosing component are merged; the first
+/// definition is kept and every instantiation, alias, and export of a later
+/// copy is rewritten to refer to it.
+pub fn dedupe(bytes: &[u8]) -> Result<Deduped> {
+    if !Parser::is_component(bytes) {
+        bail!("input is not a WebAssembly component");
+    }
+
+    let mut deduped = Deduped::default();
+    let mut stack: Vec<Scope> = Vec::new();
+    let mut scope = Scope::default();
+
+    for payload in Parser::new(0).parse_all(bytes) {
+        let payload = payload?;
+        match &payload {
+            Payload::Version { encoding, .. } => {
+                scope.output.extend_from_slice(match encoding {
+                    wasmparser::Encoding::Component => &Component::HEADER,
+                    wasmparser::Encoding::Module => &Module::HEADER,
+                });
+                continue;
+            }
+            Payload::ModuleSection { .. } | Payload::ComponentSection { .. } => {
+                stack.push(mem::take(&mut scope));
+                continue;
+            }
+            Payload::End(_) => {
+                let mut parent = match stack.pop() {
+                    Some(parent) => parent,
+                    None => break,
+                };
+                let child = mem::take(&mut scope.output);
+                if child.starts_with(&Component::HEADER) {
+                    if parent.define_component(child) {
+                        deduped.components_removed += 1;
+                    }
+                } else if parent.define_module(child) {
+                    deduped.modules_removed += 1;
+                }
+                scope = parent;
+                continue;
+            }
+            Payload::ComponentImportSection(reader) => {
+                for import in reader.clone() {
+                    match import?.ty {
+                        wasmparser::ComponentTypeRef::Module(_) => scope.push_module(),
+                        wasmparser::ComponentTypeRef::Component(_) => scope.push_component(),
+                        _ => {}
+                    }
+                }
+            }
+            Payload::InstanceSection(reader) => {
+                let mut section = InstanceSection::new();
+                for instance in reader.clone() {
+                    match instance? {
+                        Instance::Instantiate { module_index, args } => {
+                            section.instantiate(
+                                scope.module(module_index)?,
+                                args.iter()
+                                    .map(|arg| (arg.name, ModuleArg::Instance(arg.index))),
+                            );
+                        }
+                        Instance::FromExports(exports) => {
+                            section.export_items(
+                                exports
+                                    .iter()
+                                    .map(|e| (e.name, export_kind(e.kind), e.index)),
+                            );
+                        }
+                    }
+                }
+                section.append_to_component(&mut scope.output);
+                continue;
+            }
+            Payload::ComponentInstanceSection(reader) => {
+                let mut section = ComponentInstanceSection::new();
+                for instance in reader.clone() {
+                    match instance? {
+                        ComponentInstance::Instantiate {
+                            component_index,
+                            args,
+                        } => {
+                            let args = args
+                                .iter()
+                                .map(|arg| {
+                                    Ok((arg.name, arg.kind, scope.item(arg.kind, arg.index)?))
+                                })
+                                .collect::<Result<Vec<_>>>()?;
+                            section.instantiate(
+                                scope.component(component_index)?,
+                                args.into_iter().map(|(name, kind, index)| {
+                                    (name, component_export_kind(kind), index)
+                                }),
+                            );
+                        }
+                        ComponentInstance::FromExports(exports) => {
+                            let exports = exports
+                                .iter()
+                                .map(|e| Ok((e.name.0, e.kind, scope.item(e.kind, e.index)?)))
+                                .collect::<Result<Vec<_>>>()?;
+                            section.export_items(exports.into_iter().map(|(name, kind, index)| {
+                                (name, component_export_kind(kind), index)
+                            }));
+                        }
+                    }
+                }
+                section.append_to_component(&mut scope.output);
+                continue;
+            }
+            Payload::ComponentAliasSection(reader) => {
+                let mut section = ComponentAliasSection::new();
+                for alias in reader.clone() {
+                    match alias? {
+                        ComponentAlias::InstanceExport {
+                            kind,
+                            instance_index,
+                            name,
+                        } => {
+                            match kind {
+                                ComponentExternalKind::Module => scope.push_module(),
+                                ComponentExternalKind::Component => scope.push_component(),
+                                _ => {}
+                            }
+                            section.alias(Alias::InstanceExport {
+                                instance: instance_index,
+                                kind: component_export_kind(kind),
+                                name,
+                            });
+                        }
+                        ComponentAlias::CoreInstanceExport {
+                            kind,
+                            instance_index,
+                            name,
+                        } => {
+                            section.alias(Alias::CoreInstanceExport {
+                                instance: instance_index,
+                                kind: export_kind(kind),
+                                name,
+                            });
+                        }
+                        ComponentAlias::Outer { kind, count, index } => {
+                            let outer = if count == 0 {
+                                &scope
+                            } else {
+                                match stack.len().checked_sub(count as usize) {
+                                    Some(i) => &stack[i],
+                                    None => bail!("invalid outer alias count of {count}"),
+                                }
+                            };
+                            let (kind, index) = match kind {
+                                wasmparser::ComponentOuterAliasKind::CoreModule => {
+                                    (ComponentOuterAliasKind::CoreModule, outer.module(index)?)
+                                }
+                                wasmparser::ComponentOuterAliasKind::Component => {
+                                    (ComponentOuterAliasKind::Component, outer.component(index)?)
+                                }
+                                wasmparser::ComponentOuterAliasKind::CoreType => {
+                                    (ComponentOuterAliasKind::CoreType, index)
+                                }
+                                wasmparser::ComponentOuterAliasKind::Type => {
+                                    (ComponentOuterAliasKind::Type, index)
+                                }
+                            };
+                            match kind {
+                                ComponentOuterAliasKind::CoreModule => scope.push_module(),
+                                ComponentOuterAliasKind::Component => scope.push_component(),
+                                _ => {}
+                            }
+                            section.alias(Alias::Outer { kind, count, index });
+                        }
+                    }
+                }
+                section.append_to_component(&mut scope.output);
+                continue;
+            }
+            Payload::ComponentExportSection(reader) => {
+                let mut section = ComponentExportSection::new();
+                for export in reader.clone() {
+                    let export = export?;
+                    let index = scope.item(export.kind, export.index)?;
+                    match export.kind {
+                        ComponentExternalKind::Module => scope.push_module(),
+                        ComponentExternalKind::Component => scope.push_component(),
+                        _ => {}
+                    }
+                    section.export(
+                        export.name.0,
+                        component_export_kind(export.kind),
+                        index,
+                        export.ty.map(type_ref),
+                    );
+                }
+                section.append_to_component(&mut scope.output);
+                continue;
+            }
+            Payload::CustomSection(section)
+                if section.name() == "component-name"
+                    && scope.output.starts_with(&Component::HEADER) =>
+            {
+                let reader = ComponentNameSectionReader::new(section.data(), section.data_offset());
+                let names = scope.names(reader)?;
+                names.append_to_component(&mut scope.output);
+                continue;
+            }
+            _ => {}
+        }
+
+        if let Some((id, range)) = payload.as_section() {
+            RawSection {
+                id,
+                data: &bytes[range],
+            }
+            .append_to(&mut scope.output);
+        }
+    }
+
+    deduped.bytes_saved = bytes.len().saturating_sub(scope.output.len());
+    deduped.bytes = scope.output;
+    Ok(deduped)
+}
+
+/// The state of a module or component currently being deduplicated.
+#[derive(Default)]
+struct Scope {
+    /// The output bytes of the module or component.
+    output: Vec<u8>,
+    /// Maps the original core module indexes to the new indexes.
+    modules: Vec<u32>,
+    /// The number of core modules in the output.
+    module_count: u32,
+    /// Maps the original component indexes to the new indexes.
+    components: Vec<u32>,
+    /// The number of components in the output.
+    component_count: u32,
+    /// The core modules defined in this scope, keyed by their bytes.
+    defined_modules: HashMap<Vec<u8>, u32>,
+    /// The components defined in this scope, keyed by their bytes.
+    defined_components: HashMap<Vec<u8>, u32>,
+}
+
+impl Scope {
+    fn push_module(&mut self) {
+        self.modules.push(self.module_count);
+        self.module_count += 1;
+    }
+
+    fn push_component(&mut self) {
+        self.components.push(self.component_count);
+        self.component_count += 1;
+    }
+
+    /// Defines a core module, returning `true` if it was a duplicate.
+    fn define_module(&mut self, bytes: Vec<u8>) -> bool {
+        if let Some(index) = self.defined_modules.get(&bytes) {
+            self.modules.push(*index);
+            return true;
+        }
+
+        self.output.push(ComponentSectionId::CoreModule as u8);
+        bytes.encode(&mut self.output);
+        self.defined_modules.insert(bytes, self.module_count);
+        self.push_module();
+        false
+    }
+
+    /// Defines a component, returning `true` if it was a duplicate.
+    fn define_component(&mut self, bytes: Vec<u8>) -> bool {
+        if let Some(index) = self.defined_components.get(&bytes) {
+            self.components.push(*index);
+            return true;
+        }
+
+        self.output.push(ComponentSectionId::Component as u8);
+        bytes.encode(&mut self.output);
+        self.defined_components.insert(bytes, self.component_count);
+        self.push_component();
+        false
+    }
+
+    fn module(&self, index: u32) -> Result<u32> {
+        match self.modules.get(index as usize) {
+            Some(index) => Ok(*index),
+            None => bail!("core module index {index} is out of bounds"),
+        }
+    }
+
+    fn component(&self, index: u32) -> Result<u32> {
+        match self.components.get(index as usize) {
+            Some(index) => Ok(*index),
+            None => bail!("component index {index} is out of bounds"),
+        }
+    }
+
+    /// Maps an index of the given kind to its new index.
+    fn item(&self, kind: ComponentExternalKind, index: u32) -> Result<u32> {
+        match kind {
+            ComponentExternalKind::Module => self.module(index),
+            ComponentExternalKind::Component => self.component(index),
+            _ => Ok(index),
+        }
+    }
+
+    /// Rewrites the `component-name` section for this scope.
+    ///
+    /// Names of removed duplicates are dropped as the index they referred to
+    /// now names the first definition.
+    fn names(&self, reader: ComponentNameSectionReader) -> Result<ComponentNameSection> {
+        let mut section = ComponentNameSection::new();
+        for name in reader {
+            match name? {
+                ComponentName::Component { name, .. } => section.component(name),
+                ComponentName::CoreFuncs(map) => section.core_funcs(&name_map(map, None)?),
+                ComponentName::CoreGlobals(map) => section.core_globals(&name_map(map, None)?),
+                ComponentName::CoreMemories(map) => section.core_memories(&name_map(map, None)?),
+                ComponentName::CoreTables(map) => section.core_tables(&name_map(map, None)?),
+                ComponentName::CoreModules(map) => {
+                    section.core_modules(&name_map(map, Some(&self.modules))?)
+                }
+                ComponentName::CoreInstances(map) => section.core_instances(&name_map(map, None)?),
+                ComponentName::CoreTypes(map) => section.core_types(&name_map(map, None)?),
+                ComponentName::Types(map) => section.types(&name_map(map, None)?),
+                ComponentName::Instances(map) => section.instances(&name_map(map, None)?),
+                ComponentName::Components(map) => {
+                    section.components(&name_map(map, Some(&self.components))?)
+                }
+                ComponentName::Funcs(map) => section.funcs(&name_map(map, None)?),
+                ComponentName::Values(map) => section.values(&name_map(map, None)?),
+                ComponentName::Unknown { .. } => {}
+            }
+        }
+        Ok(section)
+    }
+}
+
+/// Converts a name map, optionally remapping its indexes.
+///
+/// When remapping, only the first name for each new index is kept.
+fn name_map(map: wasmparser::NameMap, remap: Option<&[u32]>) -> Result<NameMap> {
+    let mut names = NameMap::new();
+    let mut next = 0;
+    for naming in map {
+        let naming = naming?;
+        let index = match remap {
+            Some(remap) => match remap.get(naming.index as usize) {
+                Some(index) if *index >= next => *index,
+                _ => continue,
+            },
+            None => naming.index,
+        };
+        names.append(index, naming.name);
+        next = index + 1;
+    }
+    Ok(names)
+}
+
+fn export_kind(kind: ExternalKind) -> ExportKind {
+    match kind {
+        ExternalKind::Func => ExportKind::Func,
+        ExternalKind::Table => ExportKind::Table,
+        ExternalKind::Memory => ExportKind::Memory,
+        ExternalKind::Global => ExportKind::Global,
+        ExternalKind::Tag => ExportKind::Tag,
+    }
+}
+
+fn component_export_kind(kind: ComponentExternalKind) -> ComponentExportKind {
+    match kind {
+        ComponentExternalKind::Module => ComponentExportKind::Module,
+        ComponentExternalKind::Func => ComponentExportKind::Func,
+        ComponentExternalKind::Value => ComponentExportKind::Value,
+        ComponentExternalKind::Type => ComponentExportKind::Type,
+        ComponentExternalKind::Instance => ComponentExportKind::Instance,
+        ComponentExternalKind::Component => ComponentExportKind::Component,
+    }
+}
+
+fn type_ref(ty: wasmparser::ComponentTypeRef) -> ComponentTypeRef {
+    match ty {
+        wasmparser::ComponentTypeRef::Module(index) => ComponentTypeRef::Module(index),
+        wasmparser::ComponentTypeRef::Func(index) => ComponentTypeRef::Func(index),
+        wasmparser::ComponentTypeRef::Value(ty) => ComponentTypeRef::Value(match ty {
+            wasmparser::ComponentValType::Primitive(ty) => {
+                ComponentValType::Primitive(TypeEncoder::primitive(ty))
+            }
+            wasmparser::ComponentValType::Type(index) => ComponentValType::Type(index),
+        }),
+        wasmparser::ComponentTypeRef::Type(bounds) => ComponentTypeRef::Type(match bounds {
+            wasmparser::TypeBounds::Eq(index) => TypeBounds::Eq(index),
+            wasmparser::TypeBounds::SubResource => TypeBounds::SubResource,
+        }),
+        wasmparser::ComponentTypeRef::Instance(index) => ComponentTypeRef::Instance(index),
+        wasmparser::ComponentTypeRef::Component(index) => ComponentTypeRef::Component(index),
+    }
+}
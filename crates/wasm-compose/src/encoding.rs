@@ -1,7 +1,7 @@
 use crate::graph::{
     type_desc, CompositionGraph, EncodeOptions, ExportIndex, ImportIndex, InstanceId,
 };
-use anyhow::{anyhow, bail, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use heck::ToKebabCase;
 use indexmap::{IndexMap, IndexSet};
 use petgraph::EdgeDirection;
@@ -453,6 +453,9 @@ impl<'a> TypeEncoder<'a> {
                 wasmparser::HeapType::Struct => HeapType::Struct,
                 wasmparser::HeapType::Array => HeapType::Array,
                 wasmparser::HeapType::I31 => HeapType::I31,
+                wasmparser::HeapType::Exn => HeapType::Exn,
+                wasmparser::HeapType::Cont => HeapType::Cont,
+                wasmparser::HeapType::NoCont => HeapType::NoCont,
                 wasmparser::HeapType::Concrete(i) => {
                     HeapType::Concrete(i.as_module_index().unwrap())
                 }
@@ -484,7 +487,7 @@ impl<'a> TypeEncoder<'a> {
         }
     }
 
-    fn primitive(ty: wasmparser::PrimitiveValType) -> PrimitiveValType {
+    pub(crate) fn primitive(ty: wasmparser::PrimitiveValType) -> PrimitiveValType {
         match ty {
             wasmparser::PrimitiveValType::Bool => PrimitiveValType::Bool,
             wasmparser::PrimitiveValType::S8 => PrimitiveValType::S8,
@@ -768,6 +771,10 @@ impl<'a> TypeEncoder<'a> {
                 state.cur.encodable.ty().defined_type().borrow(ty);
                 index
             }
+            wasmparser::types::ComponentDefinedType::Stream { element, end } => {
+                self.stream(state, *element, *end)
+            }
+            wasmparser::types::ComponentDefinedType::Future(ty) => self.future(state, *ty),
         }
     }
 
@@ -860,6 +867,32 @@ impl<'a> TypeEncoder<'a> {
         index
     }
 
+    fn stream(
+        &self,
+        state: &mut TypeState<'a>,
+        element: Option<wasmparser::types::ComponentValType>,
+        end: Option<wasmparser::types::ComponentValType>,
+    ) -> u32 {
+        let element = element.map(|ty| self.component_val_type(state, ty));
+        let end = end.map(|ty| self.component_val_type(state, ty));
+
+        let index = state.cur.encodable.type_count();
+        state.cur.encodable.ty().defined_type().stream(element, end);
+        index
+    }
+
+    fn future(
+        &self,
+        state: &mut TypeState<'a>,
+        ty: Option<wasmparser::types::ComponentValType>,
+    ) -> u32 {
+        let ty = ty.map(|ty| self.component_val_type(state, ty));
+
+        let index = state.cur.encodable.type_count();
+        state.cur.encodable.ty().defined_type().future(ty);
+        index
+    }
+
     fn export(
         &self,
         name: &'a str,
@@ -1347,6 +1380,19 @@ impl DependencyRegistrar<'_, '_> {
                     self.val_type(*err);
                 }
             }
+            types::ComponentDefinedType::Stream { element, end } => {
+                if let Some(element) = element {
+                    self.val_type(*element);
+                }
+                if let Some(end) = end {
+                    self.val_type(*end);
+                }
+            }
+            types::ComponentDefinedType::Future(ty) => {
+                if let Some(ty) = ty {
+                    self.val_type(*ty);
+                }
+            }
         }
     }
 }
@@ -1412,7 +1458,14 @@ impl<'a> CompositionGraphEncoder<'a> {
             self.encode_exports(&mut encoded, id)?;
         }
 
-        Ok(encoded.finish())
+        let bytes = encoded.finish();
+        if self.options.dedupe {
+            return Ok(crate::dedupe::dedupe(&bytes)
+                .context("failed to deduplicate encoded component")?
+                .bytes);
+        }
+
+        Ok(bytes)
     }
 
     fn encode_imports(&mut self, encoded: &mut ComponentBuilder) -> Result<()> {
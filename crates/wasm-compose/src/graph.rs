@@ -444,6 +444,10 @@ pub struct EncodeOptions {
 
     /// Whether or not to validate the encoded output.
     pub validate: bool,
+
+    /// Whether or not to deduplicate byte-identical core modules and
+    /// components in the encoded output.
+    pub dedupe: bool,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1304,6 +1308,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            dedupe: false,
         }) {
             Ok(_) => panic!("graph should not encode"),
             Err(e) => assert_eq!(format!("{e:#}"), "an instantiation of component `b` and its dependencies form a cycle in the instantiation graph"),
@@ -1322,6 +1327,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            dedupe: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?;
@@ -1342,6 +1348,7 @@ mod test {
             define_components: false,
             export: None,
             validate: true,
+            dedupe: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
@@ -1371,6 +1378,7 @@ mod test {
             define_components: true,
             export: None,
             validate: true,
+            dedupe: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
@@ -1434,6 +1442,7 @@ mod test {
             define_components: true,
             export: None,
             validate: true,
+            dedupe: false,
         })?;
 
         let wat = wasmprinter::print_bytes(encoded)?.replace("\r\n", "\n");
@@ -4,5 +4,6 @@
 
 pub mod composer;
 pub mod config;
+pub mod dedupe;
 pub(crate) mod encoding;
 pub mod graph;
@@ -0,0 +1,6 @@
+(component
+  (core module
+    (func (export "f"))
+  )
+  (core instance (instantiate 0))
+)
@@ -0,0 +1,27 @@
+(component
+  (component (;0;)
+    (type (;0;)
+      (instance)
+    )
+    (import "a" (instance (;0;) (type 0)))
+    (type (;1;)
+      (instance)
+    )
+    (import "b" (instance (;1;) (type 1)))
+  )
+  (component (;1;)
+    (core module (;0;)
+      (type (;0;) (func))
+      (func (;0;) (type 0))
+      (export "f" (func 0))
+    )
+    (core instance (;0;) (instantiate 0))
+  )
+  (instance (;0;) (instantiate 1))
+  (instance (;1;) (instantiate 1))
+  (instance (;2;) (instantiate 0
+      (with "a" (instance 1))
+      (with "b" (instance 0))
+    )
+  )
+)
@@ -0,0 +1,4 @@
+(component
+  (import "a" (instance))
+  (import "b" (instance))
+)
@@ -668,6 +668,19 @@ impl ComponentDefinedTypeEncoder<'_> {
         self.0.push(0x68);
         idx.encode(self.0);
     }
+
+    /// Define a `stream` type.
+    pub fn stream(self, element: Option<ComponentValType>, end: Option<ComponentValType>) {
+        self.0.push(0x66);
+        element.encode(self.0);
+        end.encode(self.0);
+    }
+
+    /// Define a `future` type.
+    pub fn future(self, ty: Option<ComponentValType>) {
+        self.0.push(0x65);
+        ty.encode(self.0);
+    }
 }
 
 /// An encoder for the type section of WebAssembly components.
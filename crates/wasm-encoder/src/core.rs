@@ -1,3 +1,5 @@
+mod branch_hints;
+mod builder;
 mod code;
 mod custom;
 mod data;
@@ -16,6 +18,8 @@ mod tables;
 mod tags;
 mod types;
 
+pub use branch_hints::*;
+pub use builder::*;
 pub use code::*;
 pub use custom::*;
 pub use data::*;
@@ -0,0 +1,159 @@
+use std::borrow::Cow;
+
+use crate::{CustomSection, Encode, Section, SectionId};
+
+/// An encoder for the [branch hinting
+/// section](https://github.com/WebAssembly/branch-hinting).
+///
+/// This section is a custom section named `metadata.code.branch_hint` which,
+/// when present, must appear before the code section.
+///
+/// # Example
+///
+/// ```
+/// use wasm_encoder::{BranchHint, BranchHints, Module};
+///
+/// let mut hints = BranchHints::new();
+/// hints.function_hints(
+///     0,
+///     [BranchHint {
+///         branch_func_offset: 7,
+///         branch_hint_value: 1,
+///     }],
+/// );
+///
+/// let mut module = Module::new();
+/// module.section(&hints);
+/// let wasm_bytes = module.finish();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct BranchHints {
+    bytes: Vec<u8>,
+    num_hints: u32,
+}
+
+/// A single branch hint within a function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BranchHint {
+    /// The byte offset, relative to the start of the function body, of the
+    /// branch instruction this hint applies to.
+    pub branch_func_offset: u32,
+    /// The value of the hint: `0` for likely not taken and `1` for likely
+    /// taken.
+    pub branch_hint_value: u32,
+}
+
+impl BranchHints {
+    /// Construct an empty encoder for the branch hints section.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the hints for the function `func`.
+    ///
+    /// Functions must be added in increasing order of their index, and
+    /// `hints` must be in increasing order of their offset.
+    pub fn function_hints<I>(&mut self, func: u32, hints: I)
+    where
+        I: IntoIterator<Item = BranchHint>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.num_hints += 1;
+        func.encode(&mut self.bytes);
+        let hints = hints.into_iter();
+        hints.len().encode(&mut self.bytes);
+        for hint in hints {
+            hint.branch_func_offset.encode(&mut self.bytes);
+            1u32.encode(&mut self.bytes);
+            hint.branch_hint_value.encode(&mut self.bytes);
+        }
+    }
+
+    /// Returns if this is an empty section.
+    pub fn is_empty(&self) -> bool {
+        self.num_hints == 0
+    }
+
+    /// Returns the number of functions that have hints in this section.
+    pub fn len(&self) -> u32 {
+        self.num_hints
+    }
+}
+
+impl Encode for BranchHints {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        let mut data = Vec::new();
+        self.num_hints.encode(&mut data);
+        data.extend(&self.bytes);
+
+        CustomSection {
+            name: "metadata.code.branch_hint".into(),
+            data: Cow::Borrowed(&data),
+        }
+        .encode(sink);
+    }
+}
+
+impl Section for BranchHints {
+    fn id(&self) -> u8 {
+        SectionId::Custom.into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn roundtrip_example() {
+        use crate::{BranchHint, BranchHints, Module};
+        use wasmparser::{BranchHintSectionReader, Parser, Payload};
+
+        let mut hints = BranchHints::new();
+        hints.function_hints(
+            2,
+            [
+                BranchHint {
+                    branch_func_offset: 3,
+                    branch_hint_value: 0,
+                },
+                BranchHint {
+                    branch_func_offset: 10,
+                    branch_hint_value: 1,
+                },
+            ],
+        );
+        let mut module = Module::new();
+        module.section(&hints);
+        let wasm_bytes = module.finish();
+
+        let mut parser = Parser::new(0).parse_all(&wasm_bytes);
+        match parser.next().unwrap().unwrap() {
+            Payload::Version { .. } => {}
+            _ => panic!("unexpected payload"),
+        }
+        match parser.next().unwrap().unwrap() {
+            Payload::CustomSection(c) => {
+                assert_eq!(c.name(), "metadata.code.branch_hint");
+                let mut section = BranchHintSectionReader::new(c.data(), c.data_offset())
+                    .expect("readable as a branch hint section")
+                    .into_iter();
+                let func = section
+                    .next()
+                    .expect("section has an element")
+                    .expect("element is a branch hint function");
+                assert_eq!(func.func, 2);
+                let hints = func
+                    .hints
+                    .into_iter()
+                    .collect::<wasmparser::Result<Vec<_>>>()
+                    .expect("hints are readable");
+                assert_eq!(hints.len(), 2);
+                assert_eq!(hints[0].func_offset, 3);
+                assert!(!hints[0].taken);
+                assert_eq!(hints[1].func_offset, 10);
+                assert!(hints[1].taken);
+                assert!(section.next().is_none());
+            }
+            _ => panic!("unexpected payload"),
+        }
+    }
+}
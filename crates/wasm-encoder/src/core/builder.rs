@@ -0,0 +1,452 @@
+use crate::*;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Convenience type to build a core module incrementally and automatically
+/// keep track of index spaces.
+///
+/// This type is intended to be a wrapper around the [`Module`] encoding type
+/// which is useful for building it up incrementally over time. Items can be
+/// added in any order and are collected into their respective sections, which
+/// are then emitted in the order required by the binary format when the module
+/// is finished. The index of each item added is returned by keeping track of
+/// index spaces internally, and identical function types are only defined
+/// once.
+///
+/// Imports may be added after definitions, with one limitation: imported
+/// items always come before defined items in their index space, and indices
+/// handed out for definitions are never renumbered. An item therefore can't be
+/// imported once an item of the same kind has been defined, and
+/// [`ModuleBuilder::import`] returns an [`ImportAfterDefinitionError`] in that
+/// case. Imports of other kinds may still come after definitions.
+///
+/// # Example
+///
+/// ```
+/// use wasm_encoder::{
+///     EntityType, ExportKind, Function, Instruction, MemoryType, ModuleBuilder, ValType,
+/// };
+///
+/// let mut builder = ModuleBuilder::default();
+///
+/// let ty = builder.func_type([ValType::I32], [ValType::I32]);
+/// let mut body = Function::new([]);
+/// body.instruction(&Instruction::LocalGet(0));
+/// body.instruction(&Instruction::End);
+/// let f = builder.function(ty, &body);
+/// builder.export("f", ExportKind::Func, f);
+///
+/// // Identical function types share the same index.
+/// assert_eq!(builder.func_type([ValType::I32], [ValType::I32]), ty);
+///
+/// // Imports can be added after definitions of other kinds.
+/// let memory = MemoryType {
+///     minimum: 1,
+///     maximum: None,
+///     memory64: false,
+///     shared: false,
+/// };
+/// assert_eq!(builder.import("env", "memory", memory), Ok(0));
+///
+/// // ... but not after a definition of the same kind.
+/// let ty = builder.func_type([], []);
+/// assert!(builder.import("env", "g", EntityType::Function(ty)).is_err());
+///
+/// let wasm = builder.finish();
+/// ```
+#[derive(Debug, Default)]
+pub struct ModuleBuilder {
+    types: TypeSection,
+    imports: ImportSection,
+    functions: FunctionSection,
+    tables: TableSection,
+    memories: MemorySection,
+    tags: TagSection,
+    globals: GlobalSection,
+    exports: ExportSection,
+    start: Option<u32>,
+    elements: ElementSection,
+    code: CodeSection,
+    data: DataSection,
+    custom_sections: Vec<CustomSection<'static>>,
+
+    /// Indices of the function types defined through
+    /// [`ModuleBuilder::func_type`], used to deduplicate them.
+    func_types: HashMap<FuncType, u32>,
+
+    // Index spaces, including both imported and defined items.
+    num_types: u32,
+    num_funcs: u32,
+    num_tables: u32,
+    num_memories: u32,
+    num_tags: u32,
+    num_globals: u32,
+}
+
+impl ModuleBuilder {
+    /// Returns the current number of types.
+    pub fn type_count(&self) -> u32 {
+        self.num_types
+    }
+
+    /// Returns the current number of functions, including imports.
+    pub fn func_count(&self) -> u32 {
+        self.num_funcs
+    }
+
+    /// Returns the current number of tables, including imports.
+    pub fn table_count(&self) -> u32 {
+        self.num_tables
+    }
+
+    /// Returns the current number of memories, including imports.
+    pub fn memory_count(&self) -> u32 {
+        self.num_memories
+    }
+
+    /// Returns the current number of tags, including imports.
+    pub fn tag_count(&self) -> u32 {
+        self.num_tags
+    }
+
+    /// Returns the current number of globals, including imports.
+    pub fn global_count(&self) -> u32 {
+        self.num_globals
+    }
+
+    /// Returns the current number of element segments.
+    pub fn element_count(&self) -> u32 {
+        self.elements.len()
+    }
+
+    /// Returns the current number of data segments.
+    pub fn data_count(&self) -> u32 {
+        self.data.len()
+    }
+
+    /// Returns the index of the function type with the `params` and `results`
+    /// specified, defining it if an identical function type wasn't already
+    /// defined by this method.
+    pub fn func_type<P, R>(&mut self, params: P, results: R) -> u32
+    where
+        P: IntoIterator<Item = ValType>,
+        R: IntoIterator<Item = ValType>,
+    {
+        let ty = FuncType::new(params, results);
+        if let Some(index) = self.func_types.get(&ty) {
+            return *index;
+        }
+        self.types
+            .function(ty.params().iter().copied(), ty.results().iter().copied());
+        let index = inc(&mut self.num_types);
+        self.func_types.insert(ty, index);
+        index
+    }
+
+    /// Defines a new type with an explicit subtype declaration, returning its
+    /// index.
+    ///
+    /// Unlike [`ModuleBuilder::func_type`] this type is always defined, even
+    /// if an identical type already exists.
+    pub fn subtype(&mut self, ty: &SubType) -> u32 {
+        self.types.subtype(ty);
+        inc(&mut self.num_types)
+    }
+
+    /// Defines a new recursion group with the `types` provided, returning the
+    /// index of the first type in the group.
+    pub fn rec<T>(&mut self, types: T) -> u32
+    where
+        T: IntoIterator<Item = SubType>,
+        T::IntoIter: ExactSizeIterator,
+    {
+        let types = types.into_iter();
+        let index = self.num_types;
+        self.num_types += u32::try_from(types.len()).unwrap();
+        self.types.rec(types);
+        index
+    }
+
+    /// Imports a new item into this module with the `module`, `field` and
+    /// `ty` specified, returning its index.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an item of the same kind as `ty` has already been
+    /// defined in this module, since imports come first in each index space.
+    pub fn import(
+        &mut self,
+        module: &str,
+        field: &str,
+        ty: impl Into<EntityType>,
+    ) -> Result<u32, ImportAfterDefinitionError> {
+        let ty = ty.into();
+        let (defined, count, kind) = match ty {
+            EntityType::Function(_) => (self.functions.len(), &mut self.num_funcs, "function"),
+            EntityType::Table(_) => (self.tables.len(), &mut self.num_tables, "table"),
+            EntityType::Memory(_) => (self.memories.len(), &mut self.num_memories, "memory"),
+            EntityType::Global(_) => (self.globals.len(), &mut self.num_globals, "global"),
+            EntityType::Tag(_) => (self.tags.len(), &mut self.num_tags, "tag"),
+        };
+        if defined != 0 {
+            return Err(ImportAfterDefinitionError { kind });
+        }
+        let index = inc(count);
+        self.imports.import(module, field, ty);
+        Ok(index)
+    }
+
+    /// Defines a new function with the type `type_index` and the `body`
+    /// specified, returning its index.
+    pub fn function(&mut self, type_index: u32, body: &Function) -> u32 {
+        self.functions.function(type_index);
+        self.code.function(body);
+        inc(&mut self.num_funcs)
+    }
+
+    /// Defines a new table, returning its index.
+    pub fn table(&mut self, table_type: TableType) -> u32 {
+        self.tables.table(table_type);
+        inc(&mut self.num_tables)
+    }
+
+    /// Defines a new table with an explicit initialization expression,
+    /// returning its index.
+    pub fn table_with_init(&mut self, table_type: TableType, init: &ConstExpr) -> u32 {
+        self.tables.table_with_init(table_type, init);
+        inc(&mut self.num_tables)
+    }
+
+    /// Defines a new memory, returning its index.
+    pub fn memory(&mut self, memory_type: MemoryType) -> u32 {
+        self.memories.memory(memory_type);
+        inc(&mut self.num_memories)
+    }
+
+    /// Defines a new tag, returning its index.
+    pub fn tag(&mut self, tag_type: TagType) -> u32 {
+        self.tags.tag(tag_type);
+        inc(&mut self.num_tags)
+    }
+
+    /// Defines a new global initialized with `init_expr`, returning its index.
+    pub fn global(&mut self, global_type: GlobalType, init_expr: &ConstExpr) -> u32 {
+        self.globals.global(global_type, init_expr);
+        inc(&mut self.num_globals)
+    }
+
+    /// Exports the item of kind `kind` at `index` from this module with the
+    /// `name` specified.
+    pub fn export(&mut self, name: &str, kind: ExportKind, index: u32) {
+        self.exports.export(name, kind, index);
+    }
+
+    /// Sets the start function of this module, replacing any previous one.
+    pub fn start(&mut self, function_index: u32) {
+        self.start = Some(function_index);
+    }
+
+    /// Defines a new element segment, returning its index.
+    pub fn element(&mut self, segment: ElementSegment<'_>) -> u32 {
+        let index = self.elements.len();
+        self.elements.segment(segment);
+        index
+    }
+
+    /// Defines a new data segment, returning its index.
+    pub fn data<D>(&mut self, segment: DataSegment<'_, D>) -> u32
+    where
+        D: IntoIterator<Item = u8>,
+        D::IntoIter: ExactSizeIterator,
+    {
+        let index = self.data.len();
+        self.data.segment(segment);
+        index
+    }
+
+    /// Adds a new custom section to this module.
+    ///
+    /// Custom sections are emitted after all other sections, in the order
+    /// they were added.
+    pub fn custom_section(&mut self, section: &CustomSection<'_>) {
+        self.custom_sections.push(CustomSection {
+            name: section.name.to_string().into(),
+            data: section.data.to_vec().into(),
+        });
+    }
+
+    /// Completes this module, returning the [`Module`] with all sections
+    /// emitted in the order required by the binary format.
+    ///
+    /// A data count section is emitted whenever there are data segments, so
+    /// that functions may refer to them with bulk memory instructions.
+    pub fn into_module(self) -> Module {
+        let mut module = Module::new();
+        if !self.types.is_empty() {
+            module.section(&self.types);
+        }
+        if !self.imports.is_empty() {
+            module.section(&self.imports);
+        }
+        if !self.functions.is_empty() {
+            module.section(&self.functions);
+        }
+        if !self.tables.is_empty() {
+            module.section(&self.tables);
+        }
+        if !self.memories.is_empty() {
+            module.section(&self.memories);
+        }
+        if !self.tags.is_empty() {
+            module.section(&self.tags);
+        }
+        if !self.globals.is_empty() {
+            module.section(&self.globals);
+        }
+        if !self.exports.is_empty() {
+            module.section(&self.exports);
+        }
+        if let Some(function_index) = self.start {
+            module.section(&StartSection { function_index });
+        }
+        if !self.elements.is_empty() {
+            module.section(&self.elements);
+        }
+        if !self.data.is_empty() {
+            module.section(&DataCountSection {
+                count: self.data.len(),
+            });
+        }
+        if !self.code.is_empty() {
+            module.section(&self.code);
+        }
+        if !self.data.is_empty() {
+            module.section(&self.data);
+        }
+        for section in self.custom_sections.iter() {
+            module.section(section);
+        }
+        module
+    }
+
+    /// Completes this module and returns the binary encoding of the entire
+    /// module.
+    pub fn finish(self) -> Vec<u8> {
+        self.into_module().finish()
+    }
+}
+
+/// The error returned by [`ModuleBuilder::import`] when an item of the same
+/// kind has already been defined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImportAfterDefinitionError {
+    kind: &'static str,
+}
+
+impl fmt::Display for ImportAfterDefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = self.kind;
+        write!(f, "cannot import a {kind} after a {kind} has been defined")
+    }
+}
+
+impl std::error::Error for ImportAfterDefinitionError {}
+
+fn inc(idx: &mut u32) -> u32 {
+    let ret = *idx;
+    *idx += 1;
+    ret
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ModuleBuilder;
+    use crate::*;
+    use wasmparser::{Parser, Payload};
+
+    #[test]
+    fn out_of_order_items() {
+        let mut builder = ModuleBuilder::default();
+
+        let mut body = Function::new([]);
+        body.instruction(&Instruction::GlobalGet(0));
+        body.instruction(&Instruction::End);
+        let ty = builder.func_type([], [ValType::I32]);
+        let f = builder.function(ty, &body);
+        builder.export("f", ExportKind::Func, f);
+        builder.custom_section(&CustomSection {
+            name: "hello".into(),
+            data: [1, 2, 3].as_slice().into(),
+        });
+        builder.data(DataSegment {
+            mode: DataSegmentMode::Passive,
+            data: [0u8; 4],
+        });
+        let global_type = GlobalType {
+            val_type: ValType::I32,
+            mutable: false,
+        };
+        assert_eq!(builder.import("env", "g", global_type), Ok(0));
+        assert_eq!(builder.func_type([], [ValType::I32]), ty);
+
+        let ty = builder.func_type([], []);
+        let mut body = Function::new([]);
+        body.instruction(&Instruction::End);
+        let start = builder.function(ty, &body);
+        builder.start(start);
+        assert_eq!(builder.type_count(), 2);
+        assert_eq!(builder.func_count(), 2);
+
+        let wasm = builder.finish();
+        wasmparser::validate(&wasm).unwrap();
+
+        let mut sections = Vec::new();
+        for payload in Parser::new(0).parse_all(&wasm) {
+            let name = match payload.unwrap() {
+                Payload::TypeSection(_) => "type",
+                Payload::ImportSection(_) => "import",
+                Payload::FunctionSection(_) => "function",
+                Payload::ExportSection(_) => "export",
+                Payload::StartSection { .. } => "start",
+                Payload::DataCountSection { .. } => "datacount",
+                Payload::CodeSectionStart { .. } => "code",
+                Payload::DataSection(_) => "data",
+                Payload::CustomSection(_) => "custom",
+                _ => continue,
+            };
+            sections.push(name);
+        }
+        assert_eq!(
+            sections,
+            [
+                "type",
+                "import",
+                "function",
+                "export",
+                "start",
+                "datacount",
+                "code",
+                "data",
+                "custom"
+            ]
+        );
+    }
+
+    #[test]
+    fn import_after_definition() {
+        let memory = MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+        };
+        let mut builder = ModuleBuilder::default();
+        builder.memory(memory);
+        let err = builder.import("env", "memory", memory).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot import a memory after a memory has been defined"
+        );
+        assert_eq!(builder.memory_count(), 1);
+    }
+}
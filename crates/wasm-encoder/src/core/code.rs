@@ -298,6 +298,98 @@ impl Encode for BlockType {
     }
 }
 
+/// A handler clause of a `resume` or `resume_throw` instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Handle {
+    /// Transfers control to the label `label` when the tag `tag` is
+    /// suspended.
+    OnLabel {
+        /// The index of the tag being handled.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Switches to another continuation when the tag `tag` is used by a
+    /// `switch` instruction.
+    OnSwitch {
+        /// The index of the tag being handled.
+        tag: u32,
+    },
+}
+
+impl Encode for Handle {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        match *self {
+            Handle::OnLabel { tag, label } => {
+                sink.push(0x00);
+                tag.encode(sink);
+                label.encode(sink);
+            }
+            Handle::OnSwitch { tag } => {
+                sink.push(0x01);
+                tag.encode(sink);
+            }
+        }
+    }
+}
+
+/// A catch clause of a `try_table` instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Catch {
+    /// Catches exceptions with the tag `tag`, branching to `label` with the
+    /// exception's payload.
+    One {
+        /// The index of the tag being caught.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Like [`Catch::One`], but additionally passes the caught exception as
+    /// an `exnref` to `label`.
+    OneRef {
+        /// The index of the tag being caught.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Catches all exceptions, branching to `label` with no values.
+    All {
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Catches all exceptions, passing the caught exception as an `exnref`
+    /// to `label`.
+    AllRef {
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+}
+
+impl Encode for Catch {
+    fn encode(&self, sink: &mut Vec<u8>) {
+        match *self {
+            Catch::One { tag, label } => {
+                sink.push(0x00);
+                tag.encode(sink);
+                label.encode(sink);
+            }
+            Catch::OneRef { tag, label } => {
+                sink.push(0x01);
+                tag.encode(sink);
+                label.encode(sink);
+            }
+            Catch::All { label } => {
+                sink.push(0x02);
+                label.encode(sink);
+            }
+            Catch::AllRef { label } => {
+                sink.push(0x03);
+                label.encode(sink);
+            }
+        }
+    }
+}
+
 /// WebAssembly instructions.
 #[derive(Clone, Debug)]
 #[non_exhaustive]
@@ -310,6 +402,7 @@ pub enum Instruction<'a> {
     Loop(BlockType),
     If(BlockType),
     Else,
+    TryTable(BlockType, Cow<'a, [Catch]>),
     Try(BlockType),
     Delegate(u32),
     Catch(u32),
@@ -323,11 +416,18 @@ pub enum Instruction<'a> {
     Return,
     Call(u32),
     CallRef(u32),
-    CallIndirect { ty: u32, table: u32 },
+    CallIndirect {
+        ty: u32,
+        table: u32,
+    },
     ReturnCallRef(u32),
     ReturnCall(u32),
-    ReturnCallIndirect { ty: u32, table: u32 },
+    ReturnCallIndirect {
+        ty: u32,
+        table: u32,
+    },
     Throw(u32),
+    ThrowRef,
     Rethrow(u32),
 
     // Parametric instructions.
@@ -367,9 +467,15 @@ pub enum Instruction<'a> {
     I64Store32(MemArg),
     MemorySize(u32),
     MemoryGrow(u32),
-    MemoryInit { mem: u32, data_index: u32 },
+    MemoryInit {
+        mem: u32,
+        data_index: u32,
+    },
     DataDrop(u32),
-    MemoryCopy { src_mem: u32, dst_mem: u32 },
+    MemoryCopy {
+        src_mem: u32,
+        dst_mem: u32,
+    },
     MemoryFill(u32),
     MemoryDiscard(u32),
 
@@ -548,6 +654,16 @@ pub enum Instruction<'a> {
 
     RefTest(RefType),
     RefCast(RefType),
+    BrOnCast {
+        relative_depth: u32,
+        from_ref_type: RefType,
+        to_ref_type: RefType,
+    },
+    BrOnCastFail {
+        relative_depth: u32,
+        from_ref_type: RefType,
+        to_ref_type: RefType,
+    },
     AnyConvertExtern,
     ExternConvertAny,
 
@@ -555,15 +671,42 @@ pub enum Instruction<'a> {
     I31GetS,
     I31GetU,
 
+    // Stack switching instructions.
+    ContNew(u32),
+    ContBind {
+        argument_index: u32,
+        result_index: u32,
+    },
+    Suspend(u32),
+    Resume {
+        cont_type_index: u32,
+        resume_table: Cow<'a, [Handle]>,
+    },
+    ResumeThrow {
+        cont_type_index: u32,
+        tag_index: u32,
+        resume_table: Cow<'a, [Handle]>,
+    },
+    Switch {
+        cont_type_index: u32,
+        tag_index: u32,
+    },
+
     // Bulk memory instructions.
-    TableInit { elem_index: u32, table: u32 },
+    TableInit {
+        elem_index: u32,
+        table: u32,
+    },
     ElemDrop(u32),
     TableFill(u32),
     TableSet(u32),
     TableGet(u32),
     TableGrow(u32),
     TableSize(u32),
-    TableCopy { src_table: u32, dst_table: u32 },
+    TableCopy {
+        src_table: u32,
+        dst_table: u32,
+    },
 
     // SIMD instructions.
     V128Load(MemArg),
@@ -580,14 +723,38 @@ pub enum Instruction<'a> {
     V128Load32Zero(MemArg),
     V128Load64Zero(MemArg),
     V128Store(MemArg),
-    V128Load8Lane { memarg: MemArg, lane: Lane },
-    V128Load16Lane { memarg: MemArg, lane: Lane },
-    V128Load32Lane { memarg: MemArg, lane: Lane },
-    V128Load64Lane { memarg: MemArg, lane: Lane },
-    V128Store8Lane { memarg: MemArg, lane: Lane },
-    V128Store16Lane { memarg: MemArg, lane: Lane },
-    V128Store32Lane { memarg: MemArg, lane: Lane },
-    V128Store64Lane { memarg: MemArg, lane: Lane },
+    V128Load8Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Load16Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Load32Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Load64Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Store8Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Store16Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Store32Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
+    V128Store64Lane {
+        memarg: MemArg,
+        lane: Lane,
+    },
     V128Const(i128),
     I8x16Shuffle([Lane; 16]),
     I8x16ExtractLaneS(Lane),
@@ -930,6 +1097,7 @@ impl Encode for Instruction<'_> {
                 sink.push(0x09);
                 l.encode(sink);
             }
+            Instruction::ThrowRef => sink.push(0x0A),
             Instruction::End => sink.push(0x0B),
             Instruction::Br(l) => {
                 sink.push(0x0C);
@@ -987,6 +1155,11 @@ impl Encode for Instruction<'_> {
             Instruction::CatchAll => {
                 sink.push(0x19);
             }
+            Instruction::TryTable(ty, ref catches) => {
+                sink.push(0x1F);
+                ty.encode(sink);
+                catches.encode(sink);
+            }
 
             // Parametric instructions.
             Instruction::Drop => sink.push(0x1A),
@@ -1491,6 +1664,24 @@ impl Encode for Instruction<'_> {
                 sink.push(0x17);
                 heap_type.encode(sink);
             }
+            Instruction::BrOnCast {
+                relative_depth,
+                from_ref_type,
+                to_ref_type,
+            } => {
+                sink.push(0xfb);
+                sink.push(0x18);
+                encode_br_on_cast(sink, relative_depth, from_ref_type, to_ref_type);
+            }
+            Instruction::BrOnCastFail {
+                relative_depth,
+                from_ref_type,
+                to_ref_type,
+            } => {
+                sink.push(0xfb);
+                sink.push(0x19);
+                encode_br_on_cast(sink, relative_depth, from_ref_type, to_ref_type);
+            }
             Instruction::AnyConvertExtern => {
                 sink.push(0xfb);
                 sink.push(0x1a);
@@ -1512,6 +1703,50 @@ impl Encode for Instruction<'_> {
                 sink.push(0x1e);
             }
 
+            // Stack switching instructions.
+            Instruction::ContNew(type_index) => {
+                sink.push(0xe0);
+                type_index.encode(sink);
+            }
+            Instruction::ContBind {
+                argument_index,
+                result_index,
+            } => {
+                sink.push(0xe1);
+                argument_index.encode(sink);
+                result_index.encode(sink);
+            }
+            Instruction::Suspend(tag_index) => {
+                sink.push(0xe2);
+                tag_index.encode(sink);
+            }
+            Instruction::Resume {
+                cont_type_index,
+                ref resume_table,
+            } => {
+                sink.push(0xe3);
+                cont_type_index.encode(sink);
+                resume_table.encode(sink);
+            }
+            Instruction::ResumeThrow {
+                cont_type_index,
+                tag_index,
+                ref resume_table,
+            } => {
+                sink.push(0xe4);
+                cont_type_index.encode(sink);
+                tag_index.encode(sink);
+                resume_table.encode(sink);
+            }
+            Instruction::Switch {
+                cont_type_index,
+                tag_index,
+            } => {
+                sink.push(0xe5);
+                cont_type_index.encode(sink);
+                tag_index.encode(sink);
+            }
+
             // Bulk memory instructions.
             Instruction::TableInit { elem_index, table } => {
                 sink.push(0xfc);
@@ -2985,6 +3220,19 @@ impl Encode for Instruction<'_> {
     }
 }
 
+fn encode_br_on_cast(
+    sink: &mut Vec<u8>,
+    relative_depth: u32,
+    from_ref_type: RefType,
+    to_ref_type: RefType,
+) {
+    let cast_flags = (from_ref_type.nullable as u8) | ((to_ref_type.nullable as u8) << 1);
+    sink.push(cast_flags);
+    relative_depth.encode(sink);
+    from_ref_type.heap_type.encode(sink);
+    to_ref_type.heap_type.encode(sink);
+}
+
 /// A constant expression.
 ///
 /// Usable in contexts such as offsets or initializers.
@@ -48,6 +48,8 @@ pub enum CompositeType {
     Array(ArrayType),
     /// The type is for a struct.
     Struct(StructType),
+    /// The type is for a continuation.
+    Cont(ContType),
 }
 
 impl Encode for CompositeType {
@@ -64,6 +66,7 @@ impl Encode for CompositeType {
             CompositeType::Struct(ty) => {
                 TypeSection::encode_struct(sink, ty.fields.iter().cloned())
             }
+            CompositeType::Cont(ContType(index)) => TypeSection::encode_cont(sink, *index),
         }
     }
 }
@@ -76,6 +79,7 @@ impl TryFrom<wasmparser::CompositeType> for CompositeType {
             wasmparser::CompositeType::Func(f) => CompositeType::Func(f.try_into()?),
             wasmparser::CompositeType::Array(a) => CompositeType::Array(a.try_into()?),
             wasmparser::CompositeType::Struct(s) => CompositeType::Struct(s.try_into()?),
+            wasmparser::CompositeType::Cont(c) => CompositeType::Cont(c.try_into()?),
         })
     }
 }
@@ -135,6 +139,20 @@ impl TryFrom<wasmparser::StructType> for StructType {
     }
 }
 
+/// Represents a type of a continuation in a WebAssembly module.
+///
+/// The index refers to the function type of the continuation.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct ContType(pub u32);
+
+#[cfg(feature = "wasmparser")]
+impl TryFrom<wasmparser::ContType> for ContType {
+    type Error = ();
+    fn try_from(cont_ty: wasmparser::ContType) -> Result<Self, Self::Error> {
+        Ok(ContType(cont_ty.0.as_module_index().ok_or(())?))
+    }
+}
+
 /// Field type in composite types (structs, arrays).
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Ord, PartialOrd)]
 pub struct FieldType {
@@ -255,6 +273,8 @@ impl ValType {
     pub const FUNCREF: ValType = ValType::Ref(RefType::FUNCREF);
     /// Alias for the `externref` type in WebAssembly
     pub const EXTERNREF: ValType = ValType::Ref(RefType::EXTERNREF);
+    /// Alias for the `exnref` type in WebAssembly
+    pub const EXNREF: ValType = ValType::Ref(RefType::EXNREF);
 }
 
 impl Encode for StorageType {
@@ -304,6 +324,12 @@ impl RefType {
         nullable: true,
         heap_type: HeapType::Extern,
     };
+
+    /// Alias for the `exnref` type in WebAssembly
+    pub const EXNREF: RefType = RefType {
+        nullable: true,
+        heap_type: HeapType::Exn,
+    };
 }
 
 impl Encode for RefType {
@@ -393,6 +419,21 @@ pub enum HeapType {
     /// The unboxed `i31` heap type.
     I31,
 
+    /// The abstract `exn` heap type.
+    ///
+    /// The type of exception objects.
+    Exn,
+
+    /// The abstract `cont` heap type.
+    ///
+    /// The common supertype (a.k.a. top) of all continuation types.
+    Cont,
+
+    /// The abstract `nocont` heap type.
+    ///
+    /// The common subtype (a.k.a. bottom) of all continuation types.
+    NoCont,
+
     /// A concrete Wasm-defined type at the given index.
     Concrete(u32),
 }
@@ -410,6 +451,9 @@ impl Encode for HeapType {
             HeapType::Struct => sink.push(0x6B),
             HeapType::Array => sink.push(0x6A),
             HeapType::I31 => sink.push(0x6C),
+            HeapType::Exn => sink.push(0x69),
+            HeapType::Cont => sink.push(0x68),
+            HeapType::NoCont => sink.push(0x75),
             // Note that this is encoded as a signed type rather than unsigned
             // as it's decoded as an s33
             HeapType::Concrete(i) => i64::from(*i).encode(sink),
@@ -434,6 +478,9 @@ impl TryFrom<wasmparser::HeapType> for HeapType {
             wasmparser::HeapType::Struct => HeapType::Struct,
             wasmparser::HeapType::Array => HeapType::Array,
             wasmparser::HeapType::I31 => HeapType::I31,
+            wasmparser::HeapType::Exn => HeapType::Exn,
+            wasmparser::HeapType::Cont => HeapType::Cont,
+            wasmparser::HeapType::NoCont => HeapType::NoCont,
         })
     }
 }
@@ -547,6 +594,19 @@ impl TypeSection {
         }
     }
 
+    /// Define a continuation type in this type section, referring to the
+    /// function type at `type_index`.
+    pub fn cont(&mut self, type_index: u32) -> &mut Self {
+        Self::encode_cont(&mut self.bytes, type_index);
+        self.num_added += 1;
+        self
+    }
+
+    fn encode_cont(sink: &mut Vec<u8>, type_index: u32) {
+        sink.push(0x5d);
+        type_index.encode(sink);
+    }
+
     /// Define an explicit subtype in this type section.
     pub fn subtype(&mut self, ty: &SubType) -> &mut Self {
         ty.encode(&mut self.bytes);
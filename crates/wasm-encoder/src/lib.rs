@@ -73,6 +73,8 @@
 mod component;
 mod core;
 mod raw;
+#[cfg(feature = "wasmparser")]
+pub mod reencode;
 
 pub use self::component::*;
 pub use self::core::*;
@@ -0,0 +1,1610 @@
+//! Conversion from [`wasmparser`] types into `wasm-encoder` types, which can
+//! be used to re-encode a WebAssembly module which has been parsed.
+//!
+//! The [`Reencode`] trait has a method for each item that can be found in a
+//! module, along with a default implementation in the [`utils`] module which
+//! re-encodes it as-is. Components are handled likewise by the
+//! [`ReencodeComponent`] trait and the [`component_utils`] module.
+//!
+//! Rewriting tools can implement these traits and override just the hooks
+//! they're interested in, for example to remap indices with
+//! [`Reencode::function_index`] or to drop items from a section by overriding
+//! the `parse_*` hook for that item and skipping it.
+//!
+//! # Example
+//!
+//! ```
+//! use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+//!
+//! let wasm = wasm_encoder::Module::new().finish();
+//!
+//! let mut module = wasm_encoder::Module::new();
+//! RoundtripReencoder
+//!     .parse_core_module(&mut module, wasmparser::Parser::new(0), &wasm)
+//!     .unwrap();
+//! assert_eq!(module.finish(), wasm);
+//! ```
+
+use crate::*;
+use std::convert::Infallible;
+
+mod component;
+
+pub use self::component::*;
+
+/// A trait for converting [`wasmparser`] items into `wasm-encoder` items,
+/// with hooks to customize how each item is converted.
+///
+/// Every method has a default implementation which defers to the function of
+/// the same name in the [`utils`] module. Overriding implementations can call
+/// back into [`utils`] to get the default behavior for an item.
+#[allow(missing_docs)]
+pub trait Reencode {
+    /// The type of errors returned by user-defined hooks.
+    type Error;
+
+    /// Remaps a data segment index.
+    fn data_index(&mut self, data: u32) -> u32 {
+        data
+    }
+
+    /// Remaps an element segment index.
+    fn element_index(&mut self, element: u32) -> u32 {
+        element
+    }
+
+    /// Remaps a function index.
+    fn function_index(&mut self, func: u32) -> u32 {
+        func
+    }
+
+    /// Remaps a global index.
+    fn global_index(&mut self, global: u32) -> u32 {
+        global
+    }
+
+    /// Remaps a memory index.
+    fn memory_index(&mut self, memory: u32) -> u32 {
+        memory
+    }
+
+    /// Remaps a table index.
+    fn table_index(&mut self, table: u32) -> u32 {
+        table
+    }
+
+    /// Remaps a tag index.
+    fn tag_index(&mut self, tag: u32) -> u32 {
+        tag
+    }
+
+    /// Remaps a type index.
+    fn type_index(&mut self, ty: u32) -> u32 {
+        ty
+    }
+
+    fn array_type(
+        &mut self,
+        array_ty: wasmparser::ArrayType,
+    ) -> Result<ArrayType, Error<Self::Error>> {
+        utils::array_type(self, array_ty)
+    }
+
+    fn block_type(&mut self, arg: wasmparser::BlockType) -> Result<BlockType, Error<Self::Error>> {
+        utils::block_type(self, arg)
+    }
+
+    fn catch(&mut self, arg: wasmparser::Catch) -> Catch {
+        utils::catch(self, arg)
+    }
+
+    fn composite_type(
+        &mut self,
+        composite_ty: wasmparser::CompositeType,
+    ) -> Result<CompositeType, Error<Self::Error>> {
+        utils::composite_type(self, composite_ty)
+    }
+
+    fn const_expr(
+        &mut self,
+        const_expr: wasmparser::ConstExpr,
+    ) -> Result<ConstExpr, Error<Self::Error>> {
+        utils::const_expr(self, const_expr)
+    }
+
+    fn cont_type(&mut self, cont_ty: wasmparser::ContType) -> Result<ContType, Error<Self::Error>> {
+        utils::cont_type(self, cont_ty)
+    }
+
+    fn custom_section<'a>(
+        &mut self,
+        section: wasmparser::CustomSectionReader<'a>,
+    ) -> CustomSection<'a> {
+        utils::custom_section(self, section)
+    }
+
+    fn entity_type(
+        &mut self,
+        type_ref: wasmparser::TypeRef,
+    ) -> Result<EntityType, Error<Self::Error>> {
+        utils::entity_type(self, type_ref)
+    }
+
+    fn export_kind(&mut self, external_kind: wasmparser::ExternalKind) -> ExportKind {
+        utils::export_kind(self, external_kind)
+    }
+
+    fn field_type(
+        &mut self,
+        field_ty: wasmparser::FieldType,
+    ) -> Result<FieldType, Error<Self::Error>> {
+        utils::field_type(self, field_ty)
+    }
+
+    fn func_type(&mut self, func_ty: wasmparser::FuncType) -> Result<FuncType, Error<Self::Error>> {
+        utils::func_type(self, func_ty)
+    }
+
+    fn global_type(
+        &mut self,
+        global_ty: wasmparser::GlobalType,
+    ) -> Result<GlobalType, Error<Self::Error>> {
+        utils::global_type(self, global_ty)
+    }
+
+    fn handle(&mut self, arg: wasmparser::Handle) -> Handle {
+        utils::handle(self, arg)
+    }
+
+    fn heap_type(&mut self, heap_ty: wasmparser::HeapType) -> Result<HeapType, Error<Self::Error>> {
+        utils::heap_type(self, heap_ty)
+    }
+
+    fn instruction<'a>(
+        &mut self,
+        arg: wasmparser::Operator<'a>,
+    ) -> Result<Instruction<'a>, Error<Self::Error>> {
+        utils::instruction(self, arg)
+    }
+
+    fn memarg(&mut self, arg: wasmparser::MemArg) -> MemArg {
+        utils::memarg(self, arg)
+    }
+
+    fn memory_type(&mut self, memory_ty: wasmparser::MemoryType) -> MemoryType {
+        utils::memory_type(self, memory_ty)
+    }
+
+    fn ref_type(&mut self, ref_ty: wasmparser::RefType) -> Result<RefType, Error<Self::Error>> {
+        utils::ref_type(self, ref_ty)
+    }
+
+    fn storage_type(
+        &mut self,
+        storage_ty: wasmparser::StorageType,
+    ) -> Result<StorageType, Error<Self::Error>> {
+        utils::storage_type(self, storage_ty)
+    }
+
+    fn struct_type(
+        &mut self,
+        struct_ty: wasmparser::StructType,
+    ) -> Result<StructType, Error<Self::Error>> {
+        utils::struct_type(self, struct_ty)
+    }
+
+    fn sub_type(&mut self, sub_ty: wasmparser::SubType) -> Result<SubType, Error<Self::Error>> {
+        utils::sub_type(self, sub_ty)
+    }
+
+    fn table_type(
+        &mut self,
+        table_ty: wasmparser::TableType,
+    ) -> Result<TableType, Error<Self::Error>> {
+        utils::table_type(self, table_ty)
+    }
+
+    fn tag_kind(&mut self, kind: wasmparser::TagKind) -> TagKind {
+        utils::tag_kind(self, kind)
+    }
+
+    fn tag_type(&mut self, tag_ty: wasmparser::TagType) -> TagType {
+        utils::tag_type(self, tag_ty)
+    }
+
+    fn val_type(&mut self, val_ty: wasmparser::ValType) -> Result<ValType, Error<Self::Error>> {
+        utils::val_type(self, val_ty)
+    }
+
+    /// Parses the input `data` with `parser` as a core wasm module and
+    /// re-encodes each section into `module`.
+    fn parse_core_module(
+        &mut self,
+        module: &mut Module,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_core_module(self, module, parser, data)
+    }
+
+    /// Called before and after each section is re-encoded, and once at the
+    /// end of the module, to allow inserting new sections into `module`.
+    ///
+    /// `after` is the id of the last non-custom section that was re-encoded,
+    /// if any, and `before` is the id of the section about to be re-encoded,
+    /// which is `None` at the end of the module. Custom sections are passed in
+    /// `before` as well but never in `after`.
+    fn intersperse_section_hook(
+        &mut self,
+        module: &mut Module,
+        after: Option<SectionId>,
+        before: Option<SectionId>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::intersperse_section_hook(self, module, after, before)
+    }
+
+    fn parse_custom_section(
+        &mut self,
+        module: &mut Module,
+        section: wasmparser::CustomSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_custom_section(self, module, section)
+    }
+
+    fn parse_data(
+        &mut self,
+        data: &mut DataSection,
+        datum: wasmparser::Data<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_data(self, data, datum)
+    }
+
+    fn parse_data_section(
+        &mut self,
+        data: &mut DataSection,
+        section: wasmparser::DataSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_data_section(self, data, section)
+    }
+
+    fn parse_element(
+        &mut self,
+        elements: &mut ElementSection,
+        element: wasmparser::Element<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_element(self, elements, element)
+    }
+
+    fn parse_element_section(
+        &mut self,
+        elements: &mut ElementSection,
+        section: wasmparser::ElementSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_element_section(self, elements, section)
+    }
+
+    fn parse_export(&mut self, exports: &mut ExportSection, export: wasmparser::Export<'_>) {
+        utils::parse_export(self, exports, export)
+    }
+
+    fn parse_export_section(
+        &mut self,
+        exports: &mut ExportSection,
+        section: wasmparser::ExportSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_export_section(self, exports, section)
+    }
+
+    fn parse_function_body(
+        &mut self,
+        code: &mut CodeSection,
+        func: wasmparser::FunctionBody<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_function_body(self, code, func)
+    }
+
+    fn parse_function_section(
+        &mut self,
+        functions: &mut FunctionSection,
+        section: wasmparser::FunctionSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_function_section(self, functions, section)
+    }
+
+    fn parse_global(
+        &mut self,
+        globals: &mut GlobalSection,
+        global: wasmparser::Global<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_global(self, globals, global)
+    }
+
+    fn parse_global_section(
+        &mut self,
+        globals: &mut GlobalSection,
+        section: wasmparser::GlobalSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_global_section(self, globals, section)
+    }
+
+    fn parse_import(
+        &mut self,
+        imports: &mut ImportSection,
+        import: wasmparser::Import<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_import(self, imports, import)
+    }
+
+    fn parse_import_section(
+        &mut self,
+        imports: &mut ImportSection,
+        section: wasmparser::ImportSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_import_section(self, imports, section)
+    }
+
+    /// Re-encodes the instruction read from `reader` into `func`.
+    fn parse_instruction<'a>(
+        &mut self,
+        func: &mut Function,
+        reader: &mut wasmparser::OperatorsReader<'a>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_instruction(self, func, reader)
+    }
+
+    fn parse_memory_section(
+        &mut self,
+        memories: &mut MemorySection,
+        section: wasmparser::MemorySectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_memory_section(self, memories, section)
+    }
+
+    fn parse_recursive_type_group(
+        &mut self,
+        types: &mut TypeSection,
+        rec_group: wasmparser::RecGroup,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_recursive_type_group(self, types, rec_group)
+    }
+
+    fn parse_table(
+        &mut self,
+        tables: &mut TableSection,
+        table: wasmparser::Table<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_table(self, tables, table)
+    }
+
+    fn parse_table_section(
+        &mut self,
+        tables: &mut TableSection,
+        section: wasmparser::TableSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_table_section(self, tables, section)
+    }
+
+    fn parse_tag_section(
+        &mut self,
+        tags: &mut TagSection,
+        section: wasmparser::TagSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_tag_section(self, tags, section)
+    }
+
+    fn parse_type_section(
+        &mut self,
+        types: &mut TypeSection,
+        section: wasmparser::TypeSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        utils::parse_type_section(self, types, section)
+    }
+
+    /// Parses the locals of `func` and creates the [`Function`] that its
+    /// instructions are re-encoded into.
+    fn new_function_with_parsed_locals(
+        &mut self,
+        func: &wasmparser::FunctionBody<'_>,
+    ) -> Result<Function, Error<Self::Error>> {
+        utils::new_function_with_parsed_locals(self, func)
+    }
+
+    /// Re-encodes the start function's index.
+    fn start_section(&mut self, func: u32) -> StartSection {
+        utils::start_section(self, func)
+    }
+}
+
+/// An error that can occur while re-encoding a module or component.
+#[derive(Debug)]
+pub enum Error<E = Infallible> {
+    /// There was a type reference that was canonicalized and no longer
+    /// references an index into a module's types space, so it can't be
+    /// encoded into a Wasm binary again.
+    CanonicalizedHeapTypeReference,
+    /// The const expression is invalid: it doesn't end with a single `end`.
+    InvalidConstExpr,
+    /// A component section was found while parsing a core module.
+    UnexpectedNonCoreModuleSection,
+    /// The code section has a different number of function bodies than it
+    /// declared.
+    InvalidCodeSectionSize,
+    /// A core module section was found while parsing a component.
+    UnexpectedNonComponentSection,
+    /// A core type other than a function type was found in a component.
+    UnsupportedCoreTypeInComponent,
+    /// An error returned by a user-defined hook.
+    UserError(E),
+    /// There was an error parsing the input.
+    ParseError(wasmparser::BinaryReaderError),
+}
+
+impl<E> From<wasmparser::BinaryReaderError> for Error<E> {
+    fn from(err: wasmparser::BinaryReaderError) -> Self {
+        Self::ParseError(err)
+    }
+}
+
+impl<E: std::fmt::Display> std::fmt::Display for Error<E> {
+    fn fmt(&self, fmt: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::CanonicalizedHeapTypeReference => write!(
+                fmt,
+                "there was a canonicalized type reference without type index information"
+            ),
+            Self::InvalidConstExpr => write!(fmt, "the const expression was invalid"),
+            Self::UnexpectedNonCoreModuleSection => write!(
+                fmt,
+                "a component section was found while parsing a core module"
+            ),
+            Self::InvalidCodeSectionSize => {
+                write!(
+                    fmt,
+                    "the code section has a different number of function bodies than declared"
+                )
+            }
+            Self::UnexpectedNonComponentSection => write!(
+                fmt,
+                "a core module section was found while parsing a component"
+            ),
+            Self::UnsupportedCoreTypeInComponent => {
+                write!(fmt, "only function core types are supported in components")
+            }
+            Self::UserError(e) => write!(fmt, "{e}"),
+            Self::ParseError(e) => write!(fmt, "{e}"),
+        }
+    }
+}
+
+impl<E: 'static + std::error::Error> std::error::Error for Error<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::UserError(e) => Some(e),
+            Self::ParseError(e) => Some(e),
+            Self::CanonicalizedHeapTypeReference
+            | Self::InvalidConstExpr
+            | Self::UnexpectedNonCoreModuleSection
+            | Self::InvalidCodeSectionSize
+            | Self::UnexpectedNonComponentSection
+            | Self::UnsupportedCoreTypeInComponent => None,
+        }
+    }
+}
+
+/// A [`Reencode`] implementation which re-encodes a module as-is, without
+/// changing anything.
+#[derive(Debug)]
+pub struct RoundtripReencoder;
+
+impl Reencode for RoundtripReencoder {
+    type Error = Infallible;
+}
+
+/// The default implementations of each [`Reencode`] method.
+#[allow(missing_docs)]
+pub mod utils {
+    use super::{Error, Reencode};
+    use crate::*;
+
+    pub fn parse_core_module<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        module: &mut Module,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<T::Error>> {
+        fn handle_intersperse_section_hook<T: ?Sized + Reencode>(
+            reencoder: &mut T,
+            module: &mut Module,
+            last_section: &mut Option<SectionId>,
+            next_section: Option<SectionId>,
+        ) -> Result<(), Error<T::Error>> {
+            let after = std::mem::replace(last_section, next_section);
+            let before = next_section;
+            reencoder.intersperse_section_hook(module, after, before)
+        }
+
+        let mut sections = parser.parse_all(data);
+        let mut next_section = sections.next();
+        let mut last_section = None;
+
+        while let Some(section) = next_section {
+            match section? {
+                wasmparser::Payload::Version {
+                    encoding: wasmparser::Encoding::Module,
+                    ..
+                } => (),
+                wasmparser::Payload::Version { .. } => {
+                    return Err(Error::UnexpectedNonCoreModuleSection)
+                }
+                wasmparser::Payload::TypeSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Type),
+                    )?;
+                    let mut types = TypeSection::new();
+                    reencoder.parse_type_section(&mut types, section)?;
+                    module.section(&types);
+                }
+                wasmparser::Payload::ImportSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Import),
+                    )?;
+                    let mut imports = ImportSection::new();
+                    reencoder.parse_import_section(&mut imports, section)?;
+                    module.section(&imports);
+                }
+                wasmparser::Payload::FunctionSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Function),
+                    )?;
+                    let mut functions = FunctionSection::new();
+                    reencoder.parse_function_section(&mut functions, section)?;
+                    module.section(&functions);
+                }
+                wasmparser::Payload::TableSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Table),
+                    )?;
+                    let mut tables = TableSection::new();
+                    reencoder.parse_table_section(&mut tables, section)?;
+                    module.section(&tables);
+                }
+                wasmparser::Payload::MemorySection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Memory),
+                    )?;
+                    let mut memories = MemorySection::new();
+                    reencoder.parse_memory_section(&mut memories, section)?;
+                    module.section(&memories);
+                }
+                wasmparser::Payload::TagSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Tag),
+                    )?;
+                    let mut tags = TagSection::new();
+                    reencoder.parse_tag_section(&mut tags, section)?;
+                    module.section(&tags);
+                }
+                wasmparser::Payload::GlobalSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Global),
+                    )?;
+                    let mut globals = GlobalSection::new();
+                    reencoder.parse_global_section(&mut globals, section)?;
+                    module.section(&globals);
+                }
+                wasmparser::Payload::ExportSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Export),
+                    )?;
+                    let mut exports = ExportSection::new();
+                    reencoder.parse_export_section(&mut exports, section)?;
+                    module.section(&exports);
+                }
+                wasmparser::Payload::StartSection { func, .. } => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Start),
+                    )?;
+                    module.section(&reencoder.start_section(func));
+                }
+                wasmparser::Payload::ElementSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Element),
+                    )?;
+                    let mut elements = ElementSection::new();
+                    reencoder.parse_element_section(&mut elements, section)?;
+                    module.section(&elements);
+                }
+                wasmparser::Payload::DataCountSection { count, .. } => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::DataCount),
+                    )?;
+                    module.section(&DataCountSection { count });
+                }
+                wasmparser::Payload::DataSection(section) => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Data),
+                    )?;
+                    let mut data = DataSection::new();
+                    reencoder.parse_data_section(&mut data, section)?;
+                    module.section(&data);
+                }
+                wasmparser::Payload::CodeSectionStart { count, .. } => {
+                    handle_intersperse_section_hook(
+                        reencoder,
+                        module,
+                        &mut last_section,
+                        Some(SectionId::Code),
+                    )?;
+                    let mut codes = CodeSection::new();
+                    for _ in 0..count {
+                        if let Some(Ok(wasmparser::Payload::CodeSectionEntry(section))) =
+                            sections.next()
+                        {
+                            reencoder.parse_function_body(&mut codes, section)?;
+                        } else {
+                            return Err(Error::InvalidCodeSectionSize);
+                        }
+                    }
+                    module.section(&codes);
+                }
+                wasmparser::Payload::CodeSectionEntry(_) => {
+                    return Err(Error::InvalidCodeSectionSize)
+                }
+                wasmparser::Payload::ModuleSection { .. }
+                | wasmparser::Payload::InstanceSection(_)
+                | wasmparser::Payload::CoreTypeSection(_)
+                | wasmparser::Payload::ComponentSection { .. }
+                | wasmparser::Payload::ComponentInstanceSection(_)
+                | wasmparser::Payload::ComponentAliasSection(_)
+                | wasmparser::Payload::ComponentTypeSection(_)
+                | wasmparser::Payload::ComponentCanonicalSection(_)
+                | wasmparser::Payload::ComponentStartSection { .. }
+                | wasmparser::Payload::ComponentImportSection(_)
+                | wasmparser::Payload::ComponentExportSection(_) => {
+                    return Err(Error::UnexpectedNonCoreModuleSection)
+                }
+                wasmparser::Payload::CustomSection(section) => {
+                    reencoder.intersperse_section_hook(
+                        module,
+                        last_section,
+                        Some(SectionId::Custom),
+                    )?;
+                    reencoder.parse_custom_section(module, section)?;
+                }
+                wasmparser::Payload::UnknownSection { id, contents, .. } => {
+                    module.section(&RawSection { id, data: contents });
+                }
+                wasmparser::Payload::End(_) => {
+                    handle_intersperse_section_hook(reencoder, module, &mut last_section, None)?;
+                }
+            }
+
+            next_section = sections.next();
+        }
+
+        Ok(())
+    }
+
+    /// A hook method that is called inside [`Reencode::parse_core_module`]
+    /// before and after every non-custom core wasm section.
+    ///
+    /// This method can be used to insert new custom sections in between those
+    /// sections, or to detect when a non-custom section is missing and insert
+    /// it in the [proper order].
+    ///
+    /// The `after` and `before` arguments specify the non-custom section
+    /// identifiers of the sections surrounding the hook.
+    ///
+    /// [proper order]: https://webassembly.github.io/spec/core/binary/modules.html#binary-module
+    pub fn intersperse_section_hook<T: ?Sized + Reencode>(
+        _reencoder: &mut T,
+        _module: &mut Module,
+        _after: Option<SectionId>,
+        _before: Option<SectionId>,
+    ) -> Result<(), Error<T::Error>> {
+        Ok(())
+    }
+
+    pub fn memarg<T: ?Sized + Reencode>(reencoder: &mut T, memarg: wasmparser::MemArg) -> MemArg {
+        MemArg {
+            offset: memarg.offset,
+            align: memarg.align.into(),
+            memory_index: reencoder.memory_index(memarg.memory),
+        }
+    }
+
+    pub fn catch<T: ?Sized + Reencode>(reencoder: &mut T, arg: wasmparser::Catch) -> Catch {
+        match arg {
+            wasmparser::Catch::One { tag, label } => Catch::One {
+                tag: reencoder.tag_index(tag),
+                label,
+            },
+            wasmparser::Catch::OneRef { tag, label } => Catch::OneRef {
+                tag: reencoder.tag_index(tag),
+                label,
+            },
+            wasmparser::Catch::All { label } => Catch::All { label },
+            wasmparser::Catch::AllRef { label } => Catch::AllRef { label },
+        }
+    }
+
+    pub fn handle<T: ?Sized + Reencode>(reencoder: &mut T, arg: wasmparser::Handle) -> Handle {
+        match arg {
+            wasmparser::Handle::OnLabel { tag, label } => Handle::OnLabel {
+                tag: reencoder.tag_index(tag),
+                label,
+            },
+            wasmparser::Handle::OnSwitch { tag } => Handle::OnSwitch {
+                tag: reencoder.tag_index(tag),
+            },
+        }
+    }
+
+    pub fn custom_section<'a, T: ?Sized + Reencode>(
+        _reencoder: &mut T,
+        section: wasmparser::CustomSectionReader<'a>,
+    ) -> CustomSection<'a> {
+        CustomSection {
+            data: section.data().into(),
+            name: section.name().into(),
+        }
+    }
+
+    pub fn export_kind<T: ?Sized + Reencode>(
+        _reencoder: &mut T,
+        external_kind: wasmparser::ExternalKind,
+    ) -> ExportKind {
+        match external_kind {
+            wasmparser::ExternalKind::Func => ExportKind::Func,
+            wasmparser::ExternalKind::Table => ExportKind::Table,
+            wasmparser::ExternalKind::Memory => ExportKind::Memory,
+            wasmparser::ExternalKind::Global => ExportKind::Global,
+            wasmparser::ExternalKind::Tag => ExportKind::Tag,
+        }
+    }
+
+    pub fn memory_type<T: ?Sized + Reencode>(
+        _reencoder: &mut T,
+        memory_ty: wasmparser::MemoryType,
+    ) -> MemoryType {
+        MemoryType {
+            minimum: memory_ty.initial,
+            maximum: memory_ty.maximum,
+            memory64: memory_ty.memory64,
+            shared: memory_ty.shared,
+        }
+    }
+
+    pub fn tag_kind<T: ?Sized + Reencode>(
+        _reencoder: &mut T,
+        kind: wasmparser::TagKind,
+    ) -> TagKind {
+        match kind {
+            wasmparser::TagKind::Exception => TagKind::Exception,
+        }
+    }
+
+    pub fn tag_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        tag_ty: wasmparser::TagType,
+    ) -> TagType {
+        TagType {
+            kind: reencoder.tag_kind(tag_ty.kind),
+            func_type_idx: reencoder.type_index(tag_ty.func_type_idx),
+        }
+    }
+
+    pub fn start_section<T: ?Sized + Reencode>(reencoder: &mut T, func: u32) -> StartSection {
+        StartSection {
+            function_index: reencoder.function_index(func),
+        }
+    }
+
+    pub fn block_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        arg: wasmparser::BlockType,
+    ) -> Result<BlockType, Error<T::Error>> {
+        match arg {
+            wasmparser::BlockType::Empty => Ok(BlockType::Empty),
+            wasmparser::BlockType::FuncType(n) => {
+                Ok(BlockType::FunctionType(reencoder.type_index(n)))
+            }
+            wasmparser::BlockType::Type(t) => Ok(BlockType::Result(reencoder.val_type(t)?)),
+        }
+    }
+
+    pub fn const_expr<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        const_expr: wasmparser::ConstExpr,
+    ) -> Result<ConstExpr, Error<T::Error>> {
+        let mut ops = const_expr.get_operators_reader().into_iter();
+        let mut bytes = Vec::new();
+        loop {
+            match ops.next().transpose()? {
+                Some(wasmparser::Operator::End) => break,
+                Some(op) => reencoder.instruction(op)?.encode(&mut bytes),
+                None => return Err(Error::InvalidConstExpr),
+            }
+        }
+        if ops.next().is_some() {
+            return Err(Error::InvalidConstExpr);
+        }
+        Ok(ConstExpr::raw(bytes))
+    }
+
+    pub fn sub_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        sub_ty: wasmparser::SubType,
+    ) -> Result<SubType, Error<T::Error>> {
+        Ok(SubType {
+            is_final: sub_ty.is_final,
+            supertype_idx: sub_ty
+                .supertype_idx
+                .map(|i| {
+                    i.as_module_index()
+                        .map(|i| reencoder.type_index(i))
+                        .ok_or(Error::CanonicalizedHeapTypeReference)
+                })
+                .transpose()?,
+            composite_type: reencoder.composite_type(sub_ty.composite_type)?,
+        })
+    }
+
+    pub fn composite_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        composite_ty: wasmparser::CompositeType,
+    ) -> Result<CompositeType, Error<T::Error>> {
+        Ok(match composite_ty {
+            wasmparser::CompositeType::Func(f) => CompositeType::Func(reencoder.func_type(f)?),
+            wasmparser::CompositeType::Array(a) => CompositeType::Array(reencoder.array_type(a)?),
+            wasmparser::CompositeType::Struct(s) => {
+                CompositeType::Struct(reencoder.struct_type(s)?)
+            }
+            wasmparser::CompositeType::Cont(c) => CompositeType::Cont(reencoder.cont_type(c)?),
+        })
+    }
+
+    pub fn func_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        func_ty: wasmparser::FuncType,
+    ) -> Result<FuncType, Error<T::Error>> {
+        let mut buf = Vec::with_capacity(func_ty.params().len() + func_ty.results().len());
+        for ty in func_ty.params().iter().chain(func_ty.results()).copied() {
+            buf.push(reencoder.val_type(ty)?);
+        }
+        Ok(FuncType::from_parts(buf.into(), func_ty.params().len()))
+    }
+
+    pub fn array_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        array_ty: wasmparser::ArrayType,
+    ) -> Result<ArrayType, Error<T::Error>> {
+        Ok(ArrayType(reencoder.field_type(array_ty.0)?))
+    }
+
+    pub fn struct_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        struct_ty: wasmparser::StructType,
+    ) -> Result<StructType, Error<T::Error>> {
+        Ok(StructType {
+            fields: struct_ty
+                .fields
+                .iter()
+                .map(|field_ty| reencoder.field_type(*field_ty))
+                .collect::<Result<_, _>>()?,
+        })
+    }
+
+    pub fn cont_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        cont_ty: wasmparser::ContType,
+    ) -> Result<ContType, Error<T::Error>> {
+        let index = cont_ty
+            .0
+            .as_module_index()
+            .ok_or(Error::CanonicalizedHeapTypeReference)?;
+        Ok(ContType(reencoder.type_index(index)))
+    }
+
+    pub fn field_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        field_ty: wasmparser::FieldType,
+    ) -> Result<FieldType, Error<T::Error>> {
+        Ok(FieldType {
+            element_type: reencoder.storage_type(field_ty.element_type)?,
+            mutable: field_ty.mutable,
+        })
+    }
+
+    pub fn storage_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        storage_ty: wasmparser::StorageType,
+    ) -> Result<StorageType, Error<T::Error>> {
+        Ok(match storage_ty {
+            wasmparser::StorageType::I8 => StorageType::I8,
+            wasmparser::StorageType::I16 => StorageType::I16,
+            wasmparser::StorageType::Val(v) => StorageType::Val(reencoder.val_type(v)?),
+        })
+    }
+
+    pub fn val_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        val_ty: wasmparser::ValType,
+    ) -> Result<ValType, Error<T::Error>> {
+        Ok(match val_ty {
+            wasmparser::ValType::I32 => ValType::I32,
+            wasmparser::ValType::I64 => ValType::I64,
+            wasmparser::ValType::F32 => ValType::F32,
+            wasmparser::ValType::F64 => ValType::F64,
+            wasmparser::ValType::V128 => ValType::V128,
+            wasmparser::ValType::Ref(r) => ValType::Ref(reencoder.ref_type(r)?),
+        })
+    }
+
+    pub fn ref_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        ref_type: wasmparser::RefType,
+    ) -> Result<RefType, Error<T::Error>> {
+        Ok(RefType {
+            nullable: ref_type.is_nullable(),
+            heap_type: reencoder.heap_type(ref_type.heap_type())?,
+        })
+    }
+
+    pub fn heap_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        heap_type: wasmparser::HeapType,
+    ) -> Result<HeapType, Error<T::Error>> {
+        Ok(match heap_type {
+            wasmparser::HeapType::Concrete(i) => HeapType::Concrete(
+                reencoder.type_index(
+                    i.as_module_index()
+                        .ok_or(Error::CanonicalizedHeapTypeReference)?,
+                ),
+            ),
+            wasmparser::HeapType::Func => HeapType::Func,
+            wasmparser::HeapType::Extern => HeapType::Extern,
+            wasmparser::HeapType::Any => HeapType::Any,
+            wasmparser::HeapType::None => HeapType::None,
+            wasmparser::HeapType::NoExtern => HeapType::NoExtern,
+            wasmparser::HeapType::NoFunc => HeapType::NoFunc,
+            wasmparser::HeapType::Eq => HeapType::Eq,
+            wasmparser::HeapType::Struct => HeapType::Struct,
+            wasmparser::HeapType::Array => HeapType::Array,
+            wasmparser::HeapType::I31 => HeapType::I31,
+            wasmparser::HeapType::Exn => HeapType::Exn,
+            wasmparser::HeapType::Cont => HeapType::Cont,
+            wasmparser::HeapType::NoCont => HeapType::NoCont,
+        })
+    }
+
+    pub fn table_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        table_ty: wasmparser::TableType,
+    ) -> Result<TableType, Error<T::Error>> {
+        Ok(TableType {
+            element_type: reencoder.ref_type(table_ty.element_type)?,
+            minimum: table_ty.initial,
+            maximum: table_ty.maximum,
+        })
+    }
+
+    pub fn global_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        global_ty: wasmparser::GlobalType,
+    ) -> Result<GlobalType, Error<T::Error>> {
+        Ok(GlobalType {
+            val_type: reencoder.val_type(global_ty.content_type)?,
+            mutable: global_ty.mutable,
+        })
+    }
+
+    pub fn entity_type<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        type_ref: wasmparser::TypeRef,
+    ) -> Result<EntityType, Error<T::Error>> {
+        Ok(match type_ref {
+            wasmparser::TypeRef::Func(i) => EntityType::Function(reencoder.type_index(i)),
+            wasmparser::TypeRef::Table(t) => EntityType::Table(reencoder.table_type(t)?),
+            wasmparser::TypeRef::Memory(m) => EntityType::Memory(reencoder.memory_type(m)),
+            wasmparser::TypeRef::Global(g) => EntityType::Global(reencoder.global_type(g)?),
+            wasmparser::TypeRef::Tag(t) => EntityType::Tag(reencoder.tag_type(t)),
+        })
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the types to the `types` section.
+    pub fn parse_type_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        types: &mut TypeSection,
+        section: wasmparser::TypeSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for rec_group in section {
+            reencoder.parse_recursive_type_group(types, rec_group?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a single [`wasmparser::RecGroup`] and adds it to the `types`
+    /// section.
+    pub fn parse_recursive_type_group<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        types: &mut TypeSection,
+        rec_group: wasmparser::RecGroup,
+    ) -> Result<(), Error<T::Error>> {
+        if rec_group.is_explicit_rec_group() {
+            let subtypes = rec_group
+                .into_types()
+                .map(|t| reencoder.sub_type(t))
+                .collect::<Result<Vec<_>, _>>()?;
+            types.rec(subtypes);
+        } else {
+            let ty = rec_group.into_types().next().unwrap();
+            types.subtype(&reencoder.sub_type(ty)?);
+        }
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the imports to the `import` section.
+    pub fn parse_import_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        imports: &mut ImportSection,
+        section: wasmparser::ImportSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for import in section {
+            reencoder.parse_import(imports, import?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the single [`wasmparser::Import`] provided and adds it to the
+    /// `import` section.
+    pub fn parse_import<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        imports: &mut ImportSection,
+        import: wasmparser::Import<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        imports.import(
+            import.module,
+            import.name,
+            reencoder.entity_type(import.ty)?,
+        );
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the functions to the `functions` section.
+    pub fn parse_function_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        functions: &mut FunctionSection,
+        section: wasmparser::FunctionSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for func in section {
+            functions.function(reencoder.type_index(func?));
+        }
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the tables to the `tables` section.
+    pub fn parse_table_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        tables: &mut TableSection,
+        section: wasmparser::TableSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for table in section {
+            reencoder.parse_table(tables, table?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a single [`wasmparser::Table`] and adds it to the `tables`
+    /// section.
+    pub fn parse_table<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        tables: &mut TableSection,
+        table: wasmparser::Table<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let ty = reencoder.table_type(table.ty)?;
+        match table.init {
+            wasmparser::TableInit::RefNull => {
+                tables.table(ty);
+            }
+            wasmparser::TableInit::Expr(e) => {
+                tables.table_with_init(ty, &reencoder.const_expr(e)?);
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the memories to the `memories` section.
+    pub fn parse_memory_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        memories: &mut MemorySection,
+        section: wasmparser::MemorySectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for memory in section {
+            let memory = memory?;
+            memories.memory(reencoder.memory_type(memory));
+        }
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the tags to the `tags` section.
+    pub fn parse_tag_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        tags: &mut TagSection,
+        section: wasmparser::TagSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for tag in section {
+            let tag = tag?;
+            tags.tag(reencoder.tag_type(tag));
+        }
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the globals to the `globals` section.
+    pub fn parse_global_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        globals: &mut GlobalSection,
+        section: wasmparser::GlobalSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for global in section {
+            reencoder.parse_global(globals, global?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the single [`wasmparser::Global`] provided and adds it to the
+    /// `globals` section.
+    pub fn parse_global<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        globals: &mut GlobalSection,
+        global: wasmparser::Global<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        globals.global(
+            reencoder.global_type(global.ty)?,
+            &reencoder.const_expr(global.init_expr)?,
+        );
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the exports to the `exports` section.
+    pub fn parse_export_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        exports: &mut ExportSection,
+        section: wasmparser::ExportSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for export in section {
+            reencoder.parse_export(exports, export?);
+        }
+        Ok(())
+    }
+
+    /// Parses the single [`wasmparser::Export`] provided and adds it to the
+    /// `exports` section.
+    pub fn parse_export<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        exports: &mut ExportSection,
+        export: wasmparser::Export<'_>,
+    ) {
+        let kind = reencoder.export_kind(export.kind);
+        let index = match export.kind {
+            wasmparser::ExternalKind::Func => reencoder.function_index(export.index),
+            wasmparser::ExternalKind::Table => reencoder.table_index(export.index),
+            wasmparser::ExternalKind::Memory => reencoder.memory_index(export.index),
+            wasmparser::ExternalKind::Global => reencoder.global_index(export.index),
+            wasmparser::ExternalKind::Tag => reencoder.tag_index(export.index),
+        };
+        exports.export(export.name, kind, index);
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the elements to the `element` section.
+    pub fn parse_element_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        elements: &mut ElementSection,
+        section: wasmparser::ElementSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for element in section {
+            reencoder.parse_element(elements, element?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses the single [`wasmparser::Element`] provided and adds it to the
+    /// `element` section.
+    pub fn parse_element<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        elements: &mut ElementSection,
+        element: wasmparser::Element<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let offset;
+        let mode = match element.kind {
+            wasmparser::ElementKind::Active {
+                table_index,
+                offset_expr,
+            } => {
+                offset = reencoder.const_expr(offset_expr)?;
+                ElementMode::Active {
+                    table: table_index.map(|t| reencoder.table_index(t)),
+                    offset: &offset,
+                }
+            }
+            wasmparser::ElementKind::Passive => ElementMode::Passive,
+            wasmparser::ElementKind::Declared => ElementMode::Declared,
+        };
+        let functions;
+        let exprs;
+        let elems = match element.items {
+            wasmparser::ElementItems::Functions(reader) => {
+                functions = reader
+                    .into_iter()
+                    .map(|f| f.map(|f| reencoder.function_index(f)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Elements::Functions(&functions)
+            }
+            wasmparser::ElementItems::Expressions(ty, reader) => {
+                exprs = reader
+                    .into_iter()
+                    .map(|e| reencoder.const_expr(e?))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Elements::Expressions(reencoder.ref_type(ty)?, &exprs)
+            }
+        };
+        elements.segment(ElementSegment {
+            mode,
+            elements: elems,
+        });
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// all the data to the `data` section.
+    pub fn parse_data_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        data: &mut DataSection,
+        section: wasmparser::DataSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for datum in section {
+            reencoder.parse_data(data, datum?)?;
+        }
+        Ok(())
+    }
+
+    /// Parses a single [`wasmparser::Data`] and adds it to the `data` section.
+    pub fn parse_data<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        data: &mut DataSection,
+        datum: wasmparser::Data<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match datum.kind {
+            wasmparser::DataKind::Active {
+                memory_index,
+                offset_expr,
+            } => data.active(
+                reencoder.memory_index(memory_index),
+                &reencoder.const_expr(offset_expr)?,
+                datum.data.iter().copied(),
+            ),
+            wasmparser::DataKind::Passive => data.passive(datum.data.iter().copied()),
+        };
+        Ok(())
+    }
+
+    /// Parses a single [`wasmparser::FunctionBody`] and adds it to the `code`
+    /// section.
+    pub fn parse_function_body<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        code: &mut CodeSection,
+        func: wasmparser::FunctionBody<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let mut f = reencoder.new_function_with_parsed_locals(&func)?;
+        let mut reader = func.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        while !reader.eof() {
+            reencoder.parse_instruction(&mut f, &mut reader)?;
+        }
+        code.function(&f);
+        Ok(())
+    }
+
+    /// Creates a new [`Function`] with the locals of `func`, ready for its
+    /// instructions to be added.
+    pub fn new_function_with_parsed_locals<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        func: &wasmparser::FunctionBody<'_>,
+    ) -> Result<Function, Error<T::Error>> {
+        let mut locals = Vec::new();
+        for pair in func.get_locals_reader()? {
+            let (cnt, ty) = pair?;
+            locals.push((cnt, reencoder.val_type(ty)?));
+        }
+        Ok(Function::new(locals))
+    }
+
+    /// Parses a single instruction from `reader` and adds it to `function`.
+    pub fn parse_instruction<'a, T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        function: &mut Function,
+        reader: &mut wasmparser::OperatorsReader<'a>,
+    ) -> Result<(), Error<T::Error>> {
+        function.instruction(&reencoder.instruction(reader.read()?)?);
+        Ok(())
+    }
+
+    /// Parses the input `section` given from the `wasmparser` crate and adds
+    /// it as a custom section to `module`.
+    pub fn parse_custom_section<T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        module: &mut Module,
+        section: wasmparser::CustomSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        module.section(&reencoder.custom_section(section));
+        Ok(())
+    }
+
+    #[allow(unused_variables)]
+    pub fn instruction<'a, T: ?Sized + Reencode>(
+        reencoder: &mut T,
+        arg: wasmparser::Operator<'a>,
+    ) -> Result<Instruction<'a>, Error<T::Error>> {
+        use crate::Instruction as I;
+
+        macro_rules! translate {
+            ($( @$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
+                Ok(match arg {
+                    $(
+                        wasmparser::Operator::$op $({ $($arg),* })? => {
+                            $(
+                                $(let $arg = translate!(map $arg $arg);)*
+                            )?
+                            translate!(build $op $($($arg)*)?)
+                        }
+                    )*
+                })
+            };
+
+            // This case is used to map, based on the name of the field, from
+            // the wasmparser payload type to the wasm-encoder payload type
+            // through `Reencode` as applicable.
+            (map $arg:ident tag_index) => (reencoder.tag_index($arg));
+            (map $arg:ident function_index) => (reencoder.function_index($arg));
+            (map $arg:ident table) => (reencoder.table_index($arg));
+            (map $arg:ident table_index) => (reencoder.table_index($arg));
+            (map $arg:ident dst_table) => (reencoder.table_index($arg));
+            (map $arg:ident src_table) => (reencoder.table_index($arg));
+            (map $arg:ident type_index) => (reencoder.type_index($arg));
+            (map $arg:ident global_index) => (reencoder.global_index($arg));
+            (map $arg:ident mem) => (reencoder.memory_index($arg));
+            (map $arg:ident src_mem) => (reencoder.memory_index($arg));
+            (map $arg:ident dst_mem) => (reencoder.memory_index($arg));
+            (map $arg:ident data_index) => (reencoder.data_index($arg));
+            (map $arg:ident elem_index) => (reencoder.element_index($arg));
+            (map $arg:ident struct_type_index) => (reencoder.type_index($arg));
+            (map $arg:ident array_type_index) => (reencoder.type_index($arg));
+            (map $arg:ident array_type_index_dst) => (reencoder.type_index($arg));
+            (map $arg:ident array_type_index_src) => (reencoder.type_index($arg));
+            (map $arg:ident array_data_index) => (reencoder.data_index($arg));
+            (map $arg:ident array_elem_index) => (reencoder.element_index($arg));
+            (map $arg:ident cont_type_index) => (reencoder.type_index($arg));
+            (map $arg:ident argument_index) => (reencoder.type_index($arg));
+            (map $arg:ident result_index) => (reencoder.type_index($arg));
+            (map $arg:ident field_index) => ($arg);
+            (map $arg:ident array_size) => ($arg);
+            (map $arg:ident from_ref_type) => (reencoder.ref_type($arg)?);
+            (map $arg:ident to_ref_type) => (reencoder.ref_type($arg)?);
+            (map $arg:ident blockty) => (reencoder.block_type($arg)?);
+            (map $arg:ident relative_depth) => ($arg);
+            (map $arg:ident targets) => ((
+                $arg
+                    .targets()
+                    .collect::<Result<Vec<_>, wasmparser::BinaryReaderError>>()?
+                    .into(),
+                $arg.default(),
+            ));
+            (map $arg:ident try_table) => ((
+                reencoder.block_type($arg.ty)?,
+                $arg
+                    .catches
+                    .into_iter()
+                    .map(|catch| reencoder.catch(catch))
+                    .collect::<Vec<_>>()
+                    .into(),
+            ));
+            (map $arg:ident resume_table) => ((
+                $arg
+                    .handlers
+                    .into_iter()
+                    .map(|handle| reencoder.handle(handle))
+                    .collect::<Vec<_>>()
+                    .into()
+            ));
+            (map $arg:ident table_byte) => (());
+            (map $arg:ident mem_byte) => (());
+            (map $arg:ident flags) => (());
+            (map $arg:ident ty) => (reencoder.val_type($arg)?);
+            (map $arg:ident hty) => (reencoder.heap_type($arg)?);
+            (map $arg:ident memarg) => (reencoder.memarg($arg));
+            (map $arg:ident local_index) => ($arg);
+            (map $arg:ident value) => ($arg);
+            (map $arg:ident lane) => ($arg);
+            (map $arg:ident lanes) => ($arg);
+
+            // This case takes the arguments of a wasmparser instruction and
+            // creates a wasm-encoder instruction. There are a few special cases
+            // for where the structure of a wasmparser instruction differs from
+            // that of wasm-encoder.
+            (build $op:ident) => (I::$op);
+            (build BrTable $arg:ident) => (I::BrTable($arg.0, $arg.1));
+            (build TryTable $arg:ident) => (I::TryTable($arg.0, $arg.1));
+            (build I32Const $arg:ident) => (I::I32Const($arg));
+            (build I64Const $arg:ident) => (I::I64Const($arg));
+            (build F32Const $arg:ident) => (I::F32Const(f32::from_bits($arg.bits())));
+            (build F64Const $arg:ident) => (I::F64Const(f64::from_bits($arg.bits())));
+            (build V128Const $arg:ident) => (I::V128Const($arg.i128()));
+            (build RefTestNonNull $hty:ident) => (I::RefTest(RefType {
+                nullable: false,
+                heap_type: $hty,
+            }));
+            (build RefTestNullable $hty:ident) => (I::RefTest(RefType {
+                nullable: true,
+                heap_type: $hty,
+            }));
+            (build RefCastNonNull $hty:ident) => (I::RefCast(RefType {
+                nullable: false,
+                heap_type: $hty,
+            }));
+            (build RefCastNullable $hty:ident) => (I::RefCast(RefType {
+                nullable: true,
+                heap_type: $hty,
+            }));
+            (build $op:ident $arg:ident) => (I::$op($arg));
+            (build CallIndirect $ty:ident $table:ident $_:ident) => (I::CallIndirect {
+                ty: $ty,
+                table: $table,
+            });
+            (build ReturnCallIndirect $ty:ident $table:ident) => (I::ReturnCallIndirect {
+                ty: $ty,
+                table: $table,
+            });
+            (build MemoryGrow $mem:ident $_:ident) => (I::MemoryGrow($mem));
+            (build MemorySize $mem:ident $_:ident) => (I::MemorySize($mem));
+            (build StructGet $ty:ident $field:ident) => (I::StructGet($ty, $field));
+            (build StructGetS $ty:ident $field:ident) => (I::StructGetS($ty, $field));
+            (build StructGetU $ty:ident $field:ident) => (I::StructGetU($ty, $field));
+            (build StructSet $ty:ident $field:ident) => (I::StructSet($ty, $field));
+            (build ArrayNewFixed $ty:ident $size:ident) => (I::ArrayNewFixed($ty, $size));
+            (build ArrayNewData $ty:ident $data:ident) => (I::ArrayNewData($ty, $data));
+            (build ArrayNewElem $ty:ident $elem:ident) => (I::ArrayNewElem($ty, $elem));
+            (build ArrayCopy $dst:ident $src:ident) => (I::ArrayCopy($dst, $src));
+            (build ArrayInitData $ty:ident $data:ident) => (I::ArrayInitData($ty, $data));
+            (build ArrayInitElem $ty:ident $elem:ident) => (I::ArrayInitElem($ty, $elem));
+            (build $op:ident $($arg:ident)*) => (I::$op { $($arg),* });
+        }
+
+        wasmparser::for_each_operator!(translate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{utils, Error, Reencode};
+    use crate::*;
+    use std::convert::Infallible;
+    use wasmparser::{Parser, Payload};
+
+    /// Inserts an imported function at the front of the function index space.
+    struct InsertImport;
+
+    impl Reencode for InsertImport {
+        type Error = Infallible;
+
+        fn function_index(&mut self, func: u32) -> u32 {
+            func + 1
+        }
+
+        fn intersperse_section_hook(
+            &mut self,
+            module: &mut Module,
+            after: Option<SectionId>,
+            before: Option<SectionId>,
+        ) -> Result<(), Error> {
+            if after < Some(SectionId::Import) && before > Some(SectionId::Import) {
+                let mut imports = ImportSection::new();
+                imports.import("env", "f", EntityType::Function(0));
+                module.section(&imports);
+            }
+            utils::intersperse_section_hook(self, module, after, before)
+        }
+    }
+
+    #[test]
+    fn remap_function_indices() {
+        let mut module = Module::new();
+        let mut types = TypeSection::new();
+        types.function([], []);
+        module.section(&types);
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+        let mut exports = ExportSection::new();
+        exports.export("run", ExportKind::Func, 0);
+        module.section(&exports);
+        let mut code = CodeSection::new();
+        let mut f = Function::new([]);
+        f.instruction(&Instruction::Call(0));
+        f.instruction(&Instruction::End);
+        code.function(&f);
+        module.section(&code);
+        let wasm = module.finish();
+
+        let mut module = Module::new();
+        InsertImport
+            .parse_core_module(&mut module, Parser::new(0), &wasm)
+            .unwrap();
+        let wasm = module.finish();
+        wasmparser::validate(&wasm).unwrap();
+
+        for payload in Parser::new(0).parse_all(&wasm) {
+            match payload.unwrap() {
+                Payload::ImportSection(s) => assert_eq!(s.count(), 1),
+                Payload::ExportSection(s) => {
+                    let export = s.into_iter().next().unwrap().unwrap();
+                    assert_eq!(export.index, 1);
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let mut ops = body.get_operators_reader().unwrap();
+                    match ops.read().unwrap() {
+                        wasmparser::Operator::Call { function_index } => {
+                            assert_eq!(function_index, 1)
+                        }
+                        op => panic!("unexpected operator {op:?}"),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
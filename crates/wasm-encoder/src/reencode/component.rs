@@ -0,0 +1,1291 @@
+use crate::reencode::{Error, Reencode, RoundtripReencoder};
+use crate::*;
+
+/// Helper trait to convert [`wasmparser`] component items into `wasm-encoder`
+/// component items, with hooks to customize how each item is converted.
+///
+/// This is the component counterpart of [`Reencode`], whose hooks are used for
+/// core wasm items and for indices into a component's core index spaces, such
+/// as core functions and core memories. Every method has a default
+/// implementation which defers to the function of the same name in the
+/// [`component_utils`] module.
+///
+/// Nested core modules are re-encoded with [`Reencode::parse_core_module`] and
+/// nested components with [`ReencodeComponent::parse_component`], with
+/// [`ReencodeComponent::push_depth`] and [`ReencodeComponent::pop_depth`]
+/// called around them, as well as around component, instance and module type
+/// declarations, which have index spaces of their own.
+#[allow(missing_docs)]
+pub trait ReencodeComponent: Reencode {
+    /// Remaps a component type index.
+    fn component_type_index(&mut self, ty: u32) -> u32 {
+        ty
+    }
+
+    /// Remaps a component instance index.
+    fn component_instance_index(&mut self, instance: u32) -> u32 {
+        instance
+    }
+
+    /// Remaps a component function index.
+    fn component_func_index(&mut self, func: u32) -> u32 {
+        func
+    }
+
+    /// Remaps a nested component index.
+    fn component_index(&mut self, component: u32) -> u32 {
+        component
+    }
+
+    /// Remaps a component value index.
+    fn component_value_index(&mut self, value: u32) -> u32 {
+        value
+    }
+
+    /// Remaps a core module index.
+    fn module_index(&mut self, module: u32) -> u32 {
+        module
+    }
+
+    /// Remaps a core instance index.
+    fn instance_index(&mut self, instance: u32) -> u32 {
+        instance
+    }
+
+    /// Remaps the core type index `ty` of an outer alias `count` levels up.
+    fn outer_type_index(&mut self, count: u32, ty: u32) -> u32 {
+        let _ = count;
+        self.type_index(ty)
+    }
+
+    /// Remaps the component type index `ty` of an outer alias `count` levels
+    /// up.
+    fn outer_component_type_index(&mut self, count: u32, ty: u32) -> u32 {
+        let _ = count;
+        self.component_type_index(ty)
+    }
+
+    /// Remaps the component index `component` of an outer alias `count`
+    /// levels up.
+    fn outer_component_index(&mut self, count: u32, component: u32) -> u32 {
+        let _ = count;
+        self.component_index(component)
+    }
+
+    /// Remaps the core module index `module` of an outer alias `count` levels
+    /// up.
+    fn outer_module_index(&mut self, count: u32, module: u32) -> u32 {
+        let _ = count;
+        self.module_index(module)
+    }
+
+    /// Called when entering a nested module, component or type declaration
+    /// which has index spaces of its own.
+    fn push_depth(&mut self) {}
+
+    /// Called when leaving a nested module, component or type declaration
+    /// entered with [`ReencodeComponent::push_depth`].
+    fn pop_depth(&mut self) {}
+
+    /// Remaps the index of an item of kind `kind` in a component.
+    fn component_external_index(
+        &mut self,
+        kind: wasmparser::ComponentExternalKind,
+        index: u32,
+    ) -> u32 {
+        component_utils::component_external_index(self, kind, index)
+    }
+
+    fn canonical_option(&mut self, option: wasmparser::CanonicalOption) -> CanonicalOption {
+        component_utils::canonical_option(self, option)
+    }
+
+    fn component_alias<'a>(
+        &mut self,
+        alias: wasmparser::ComponentAlias<'a>,
+    ) -> Result<Alias<'a>, Error<Self::Error>> {
+        component_utils::component_alias(self, alias)
+    }
+
+    fn component_export_kind(
+        &mut self,
+        kind: wasmparser::ComponentExternalKind,
+    ) -> ComponentExportKind {
+        component_utils::component_export_kind(self, kind)
+    }
+
+    fn component_outer_alias_kind(
+        &mut self,
+        kind: wasmparser::ComponentOuterAliasKind,
+    ) -> ComponentOuterAliasKind {
+        component_utils::component_outer_alias_kind(self, kind)
+    }
+
+    fn component_primitive_val_type(
+        &mut self,
+        ty: wasmparser::PrimitiveValType,
+    ) -> PrimitiveValType {
+        component_utils::component_primitive_val_type(self, ty)
+    }
+
+    fn component_type_ref(
+        &mut self,
+        ty: wasmparser::ComponentTypeRef,
+    ) -> Result<ComponentTypeRef, Error<Self::Error>> {
+        component_utils::component_type_ref(self, ty)
+    }
+
+    fn component_val_type(&mut self, ty: wasmparser::ComponentValType) -> ComponentValType {
+        component_utils::component_val_type(self, ty)
+    }
+
+    fn type_bounds(&mut self, bounds: wasmparser::TypeBounds) -> TypeBounds {
+        component_utils::type_bounds(self, bounds)
+    }
+
+    /// Parses the input `data` with `parser` as a component and re-encodes
+    /// each section into `component`.
+    fn parse_component(
+        &mut self,
+        component: &mut Component,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component(self, component, parser, data)
+    }
+
+    fn parse_component_alias_section(
+        &mut self,
+        aliases: &mut ComponentAliasSection,
+        section: wasmparser::ComponentAliasSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_alias_section(self, aliases, section)
+    }
+
+    fn parse_component_canonical(
+        &mut self,
+        canonicals: &mut CanonicalFunctionSection,
+        func: wasmparser::CanonicalFunction,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_canonical(self, canonicals, func)
+    }
+
+    fn parse_component_canonical_section(
+        &mut self,
+        canonicals: &mut CanonicalFunctionSection,
+        section: wasmparser::ComponentCanonicalSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_canonical_section(self, canonicals, section)
+    }
+
+    fn parse_component_core_module_type_declaration(
+        &mut self,
+        module: &mut ModuleType,
+        decl: wasmparser::ModuleTypeDeclaration<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_core_module_type_declaration(self, module, decl)
+    }
+
+    fn parse_component_core_type(
+        &mut self,
+        ty: CoreTypeEncoder<'_>,
+        core: wasmparser::CoreType<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_core_type(self, ty, core)
+    }
+
+    fn parse_component_core_type_section(
+        &mut self,
+        types: &mut CoreTypeSection,
+        section: wasmparser::CoreTypeSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_core_type_section(self, types, section)
+    }
+
+    fn parse_component_custom_section(
+        &mut self,
+        component: &mut Component,
+        section: wasmparser::CustomSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_custom_section(self, component, section)
+    }
+
+    fn parse_component_defined_type(
+        &mut self,
+        ty: ComponentDefinedTypeEncoder<'_>,
+        defined: wasmparser::ComponentDefinedType<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_defined_type(self, ty, defined)
+    }
+
+    fn parse_component_export(
+        &mut self,
+        exports: &mut ComponentExportSection,
+        export: wasmparser::ComponentExport<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_export(self, exports, export)
+    }
+
+    fn parse_component_export_section(
+        &mut self,
+        exports: &mut ComponentExportSection,
+        section: wasmparser::ComponentExportSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_export_section(self, exports, section)
+    }
+
+    fn parse_component_func_type(
+        &mut self,
+        ty: ComponentFuncTypeEncoder<'_>,
+        func: wasmparser::ComponentFuncType<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_func_type(self, ty, func)
+    }
+
+    fn parse_component_import(
+        &mut self,
+        imports: &mut ComponentImportSection,
+        import: wasmparser::ComponentImport<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_import(self, imports, import)
+    }
+
+    fn parse_component_import_section(
+        &mut self,
+        imports: &mut ComponentImportSection,
+        section: wasmparser::ComponentImportSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_import_section(self, imports, section)
+    }
+
+    fn parse_component_instance(
+        &mut self,
+        instances: &mut ComponentInstanceSection,
+        instance: wasmparser::ComponentInstance<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_instance(self, instances, instance)
+    }
+
+    fn parse_component_instance_section(
+        &mut self,
+        instances: &mut ComponentInstanceSection,
+        section: wasmparser::ComponentInstanceSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_instance_section(self, instances, section)
+    }
+
+    fn parse_component_start(
+        &mut self,
+        component: &mut Component,
+        func: wasmparser::ComponentStartFunction,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_start(self, component, func)
+    }
+
+    /// Re-encodes the nested component in `data`, parsed with `parser`, and
+    /// adds it to `component`.
+    fn parse_component_subcomponent(
+        &mut self,
+        component: &mut Component,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_subcomponent(self, component, parser, data)
+    }
+
+    /// Re-encodes the nested core module in `data`, parsed with `parser`, and
+    /// adds it to `component`.
+    fn parse_component_submodule(
+        &mut self,
+        component: &mut Component,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_submodule(self, component, parser, data)
+    }
+
+    fn parse_component_type(
+        &mut self,
+        dst: ComponentTypeEncoder<'_>,
+        ty: wasmparser::ComponentType<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_type(self, dst, ty)
+    }
+
+    fn parse_component_type_declaration(
+        &mut self,
+        component: &mut ComponentType,
+        decl: wasmparser::ComponentTypeDeclaration<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_type_declaration(self, component, decl)
+    }
+
+    fn parse_component_instance_type_declaration(
+        &mut self,
+        instance: &mut InstanceType,
+        decl: wasmparser::InstanceTypeDeclaration<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_instance_type_declaration(self, instance, decl)
+    }
+
+    fn parse_component_type_section(
+        &mut self,
+        types: &mut ComponentTypeSection,
+        section: wasmparser::ComponentTypeSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_component_type_section(self, types, section)
+    }
+
+    fn parse_instance(
+        &mut self,
+        instances: &mut InstanceSection,
+        instance: wasmparser::Instance<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_instance(self, instances, instance)
+    }
+
+    fn parse_instance_section(
+        &mut self,
+        instances: &mut InstanceSection,
+        section: wasmparser::InstanceSectionReader<'_>,
+    ) -> Result<(), Error<Self::Error>> {
+        component_utils::parse_instance_section(self, instances, section)
+    }
+}
+
+impl ReencodeComponent for RoundtripReencoder {}
+
+/// The default implementations of each [`ReencodeComponent`] method.
+#[allow(missing_docs)]
+pub mod component_utils {
+    use super::ReencodeComponent;
+    use crate::reencode::Error;
+    use crate::*;
+
+    pub fn parse_component<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut Component,
+        mut parser: wasmparser::Parser,
+        mut remaining: &[u8],
+    ) -> Result<(), Error<T::Error>> {
+        loop {
+            let (payload, consumed) = match parser.parse(remaining, true)? {
+                wasmparser::Chunk::NeedMoreData(_) => unreachable!(),
+                wasmparser::Chunk::Parsed { payload, consumed } => (payload, consumed),
+            };
+            remaining = &remaining[consumed..];
+
+            match payload {
+                wasmparser::Payload::Version {
+                    encoding: wasmparser::Encoding::Component,
+                    ..
+                } => (),
+                wasmparser::Payload::Version { .. } => {
+                    return Err(Error::UnexpectedNonComponentSection)
+                }
+                wasmparser::Payload::ModuleSection { parser, range } => {
+                    let (module, rest) = remaining.split_at(range.len());
+                    reencoder.parse_component_submodule(component, parser, module)?;
+                    remaining = rest;
+                }
+                wasmparser::Payload::ComponentSection { parser, range } => {
+                    let (subcomponent, rest) = remaining.split_at(range.len());
+                    reencoder.parse_component_subcomponent(component, parser, subcomponent)?;
+                    remaining = rest;
+                }
+                wasmparser::Payload::InstanceSection(section) => {
+                    let mut instances = InstanceSection::new();
+                    reencoder.parse_instance_section(&mut instances, section)?;
+                    component.section(&instances);
+                }
+                wasmparser::Payload::CoreTypeSection(section) => {
+                    let mut types = CoreTypeSection::new();
+                    reencoder.parse_component_core_type_section(&mut types, section)?;
+                    component.section(&types);
+                }
+                wasmparser::Payload::ComponentInstanceSection(section) => {
+                    let mut instances = ComponentInstanceSection::new();
+                    reencoder.parse_component_instance_section(&mut instances, section)?;
+                    component.section(&instances);
+                }
+                wasmparser::Payload::ComponentAliasSection(section) => {
+                    let mut aliases = ComponentAliasSection::new();
+                    reencoder.parse_component_alias_section(&mut aliases, section)?;
+                    component.section(&aliases);
+                }
+                wasmparser::Payload::ComponentTypeSection(section) => {
+                    let mut types = ComponentTypeSection::new();
+                    reencoder.parse_component_type_section(&mut types, section)?;
+                    component.section(&types);
+                }
+                wasmparser::Payload::ComponentCanonicalSection(section) => {
+                    let mut canonicals = CanonicalFunctionSection::new();
+                    reencoder.parse_component_canonical_section(&mut canonicals, section)?;
+                    component.section(&canonicals);
+                }
+                wasmparser::Payload::ComponentStartSection { start, .. } => {
+                    reencoder.parse_component_start(component, start)?;
+                }
+                wasmparser::Payload::ComponentImportSection(section) => {
+                    let mut imports = ComponentImportSection::new();
+                    reencoder.parse_component_import_section(&mut imports, section)?;
+                    component.section(&imports);
+                }
+                wasmparser::Payload::ComponentExportSection(section) => {
+                    let mut exports = ComponentExportSection::new();
+                    reencoder.parse_component_export_section(&mut exports, section)?;
+                    component.section(&exports);
+                }
+                wasmparser::Payload::CustomSection(section) => {
+                    reencoder.parse_component_custom_section(component, section)?;
+                }
+                wasmparser::Payload::UnknownSection { id, contents, .. } => {
+                    component.section(&RawSection { id, data: contents });
+                }
+                wasmparser::Payload::End(_) => return Ok(()),
+
+                wasmparser::Payload::TypeSection(_)
+                | wasmparser::Payload::ImportSection(_)
+                | wasmparser::Payload::FunctionSection(_)
+                | wasmparser::Payload::TableSection(_)
+                | wasmparser::Payload::MemorySection(_)
+                | wasmparser::Payload::TagSection(_)
+                | wasmparser::Payload::GlobalSection(_)
+                | wasmparser::Payload::ExportSection(_)
+                | wasmparser::Payload::StartSection { .. }
+                | wasmparser::Payload::ElementSection(_)
+                | wasmparser::Payload::DataCountSection { .. }
+                | wasmparser::Payload::DataSection(_)
+                | wasmparser::Payload::CodeSectionStart { .. }
+                | wasmparser::Payload::CodeSectionEntry(_) => {
+                    return Err(Error::UnexpectedNonComponentSection)
+                }
+            }
+        }
+    }
+
+    pub fn parse_component_submodule<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut Component,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<T::Error>> {
+        reencoder.push_depth();
+        let mut module = Module::new();
+        reencoder.parse_core_module(&mut module, parser, data)?;
+        component.section(&ModuleSection(&module));
+        reencoder.pop_depth();
+        Ok(())
+    }
+
+    pub fn parse_component_subcomponent<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut Component,
+        parser: wasmparser::Parser,
+        data: &[u8],
+    ) -> Result<(), Error<T::Error>> {
+        reencoder.push_depth();
+        let mut subcomponent = Component::new();
+        reencoder.parse_component(&mut subcomponent, parser, data)?;
+        component.section(&NestedComponentSection(&subcomponent));
+        reencoder.pop_depth();
+        Ok(())
+    }
+
+    pub fn parse_component_custom_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut Component,
+        section: wasmparser::CustomSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        component.section(&reencoder.custom_section(section));
+        Ok(())
+    }
+
+    pub fn parse_component_type_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        types: &mut ComponentTypeSection,
+        section: wasmparser::ComponentTypeSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for ty in section {
+            reencoder.parse_component_type(types.ty(), ty?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        dst: ComponentTypeEncoder<'_>,
+        ty: wasmparser::ComponentType<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match ty {
+            wasmparser::ComponentType::Defined(ty) => {
+                reencoder.parse_component_defined_type(dst.defined_type(), ty)?;
+            }
+            wasmparser::ComponentType::Func(func) => {
+                reencoder.parse_component_func_type(dst.function(), func)?;
+            }
+            wasmparser::ComponentType::Component(decls) => {
+                reencoder.push_depth();
+                let mut component = ComponentType::new();
+                for decl in Vec::from(decls) {
+                    reencoder.parse_component_type_declaration(&mut component, decl)?;
+                }
+                reencoder.pop_depth();
+                dst.component(&component);
+            }
+            wasmparser::ComponentType::Instance(decls) => {
+                reencoder.push_depth();
+                let mut instance = InstanceType::new();
+                for decl in Vec::from(decls) {
+                    reencoder.parse_component_instance_type_declaration(&mut instance, decl)?;
+                }
+                reencoder.pop_depth();
+                dst.instance(&instance);
+            }
+            wasmparser::ComponentType::Resource { rep, dtor } => {
+                let rep = reencoder.val_type(rep)?;
+                let dtor = dtor.map(|i| reencoder.function_index(i));
+                dst.resource(rep, dtor);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_type_declaration<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut ComponentType,
+        decl: wasmparser::ComponentTypeDeclaration<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match decl {
+            wasmparser::ComponentTypeDeclaration::CoreType(ty) => {
+                reencoder.parse_component_core_type(component.core_type(), ty)?;
+            }
+            wasmparser::ComponentTypeDeclaration::Type(ty) => {
+                reencoder.parse_component_type(component.ty(), ty)?;
+            }
+            wasmparser::ComponentTypeDeclaration::Alias(alias) => {
+                let alias = reencoder.component_alias(alias)?;
+                component.alias(alias);
+            }
+            wasmparser::ComponentTypeDeclaration::Export { name, ty } => {
+                let ty = reencoder.component_type_ref(ty)?;
+                component.export(name.0, ty);
+            }
+            wasmparser::ComponentTypeDeclaration::Import(import) => {
+                let ty = reencoder.component_type_ref(import.ty)?;
+                component.import(import.name.0, ty);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_instance_type_declaration<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        instance: &mut InstanceType,
+        decl: wasmparser::InstanceTypeDeclaration<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match decl {
+            wasmparser::InstanceTypeDeclaration::CoreType(ty) => {
+                reencoder.parse_component_core_type(instance.core_type(), ty)?;
+            }
+            wasmparser::InstanceTypeDeclaration::Type(ty) => {
+                reencoder.parse_component_type(instance.ty(), ty)?;
+            }
+            wasmparser::InstanceTypeDeclaration::Alias(alias) => {
+                let alias = reencoder.component_alias(alias)?;
+                instance.alias(alias);
+            }
+            wasmparser::InstanceTypeDeclaration::Export { name, ty } => {
+                let ty = reencoder.component_type_ref(ty)?;
+                instance.export(name.0, ty);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_func_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        mut dst: ComponentFuncTypeEncoder<'_>,
+        func: wasmparser::ComponentFuncType<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let params = func
+            .params
+            .iter()
+            .map(|(name, ty)| (*name, reencoder.component_val_type(*ty)))
+            .collect::<Vec<_>>();
+        dst.params(params);
+        match func.results {
+            wasmparser::ComponentFuncResult::Unnamed(ty) => {
+                dst.result(reencoder.component_val_type(ty));
+            }
+            wasmparser::ComponentFuncResult::Named(results) => {
+                let results = results
+                    .iter()
+                    .map(|(name, ty)| (*name, reencoder.component_val_type(*ty)))
+                    .collect::<Vec<_>>();
+                dst.results(results);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_defined_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        dst: ComponentDefinedTypeEncoder<'_>,
+        ty: wasmparser::ComponentDefinedType<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match ty {
+            wasmparser::ComponentDefinedType::Primitive(p) => {
+                dst.primitive(reencoder.component_primitive_val_type(p));
+            }
+            wasmparser::ComponentDefinedType::Record(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|(name, ty)| (*name, reencoder.component_val_type(*ty)))
+                    .collect::<Vec<_>>();
+                dst.record(fields);
+            }
+            wasmparser::ComponentDefinedType::Variant(cases) => {
+                let cases = cases
+                    .iter()
+                    .map(|case| {
+                        let ty = case.ty.map(|t| reencoder.component_val_type(t));
+                        (case.name, ty, case.refines)
+                    })
+                    .collect::<Vec<_>>();
+                dst.variant(cases);
+            }
+            wasmparser::ComponentDefinedType::List(t) => {
+                dst.list(reencoder.component_val_type(t));
+            }
+            wasmparser::ComponentDefinedType::Tuple(types) => {
+                let types = types
+                    .iter()
+                    .map(|t| reencoder.component_val_type(*t))
+                    .collect::<Vec<_>>();
+                dst.tuple(types);
+            }
+            wasmparser::ComponentDefinedType::Flags(names) => {
+                dst.flags(names.iter().copied());
+            }
+            wasmparser::ComponentDefinedType::Enum(names) => {
+                dst.enum_type(names.iter().copied());
+            }
+            wasmparser::ComponentDefinedType::Option(t) => {
+                dst.option(reencoder.component_val_type(t));
+            }
+            wasmparser::ComponentDefinedType::Result { ok, err } => {
+                let ok = ok.map(|t| reencoder.component_val_type(t));
+                let err = err.map(|t| reencoder.component_val_type(t));
+                dst.result(ok, err);
+            }
+            wasmparser::ComponentDefinedType::Own(i) => {
+                dst.own(reencoder.component_type_index(i));
+            }
+            wasmparser::ComponentDefinedType::Borrow(i) => {
+                dst.borrow(reencoder.component_type_index(i));
+            }
+            wasmparser::ComponentDefinedType::Future(t) => {
+                dst.future(t.map(|t| reencoder.component_val_type(t)));
+            }
+            wasmparser::ComponentDefinedType::Stream { element, end } => {
+                let element = element.map(|t| reencoder.component_val_type(t));
+                let end = end.map(|t| reencoder.component_val_type(t));
+                dst.stream(element, end);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_core_type_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        types: &mut CoreTypeSection,
+        section: wasmparser::CoreTypeSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for ty in section {
+            reencoder.parse_component_core_type(types.ty(), ty?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_core_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        dst: CoreTypeEncoder<'_>,
+        ty: wasmparser::CoreType<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match ty {
+            wasmparser::CoreType::Sub(ty) => core_func_type(reencoder, dst, ty)?,
+            wasmparser::CoreType::Module(decls) => {
+                reencoder.push_depth();
+                let mut module = ModuleType::new();
+                for decl in Vec::from(decls) {
+                    reencoder.parse_component_core_module_type_declaration(&mut module, decl)?;
+                }
+                reencoder.pop_depth();
+                dst.module(&module);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_core_module_type_declaration<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        module: &mut ModuleType,
+        decl: wasmparser::ModuleTypeDeclaration<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match decl {
+            wasmparser::ModuleTypeDeclaration::Type(ty) => {
+                core_func_type(reencoder, module.ty(), ty)?;
+            }
+            wasmparser::ModuleTypeDeclaration::Export { name, ty } => {
+                let ty = reencoder.entity_type(ty)?;
+                module.export(name, ty);
+            }
+            wasmparser::ModuleTypeDeclaration::OuterAlias {
+                kind: wasmparser::OuterAliasKind::Type,
+                count,
+                index,
+            } => {
+                let index = reencoder.outer_type_index(count, index);
+                module.alias_outer_core_type(count, index);
+            }
+            wasmparser::ModuleTypeDeclaration::Import(import) => {
+                let ty = reencoder.entity_type(import.ty)?;
+                module.import(import.module, import.name, ty);
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the core type `ty` into `dst`, which must be a function type
+    /// since those are the only core types supported in components.
+    fn core_func_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        dst: CoreTypeEncoder<'_>,
+        ty: wasmparser::SubType,
+    ) -> Result<(), Error<T::Error>> {
+        let func = match ty.composite_type {
+            wasmparser::CompositeType::Func(func) if ty.is_final && ty.supertype_idx.is_none() => {
+                reencoder.func_type(func)?
+            }
+            _ => return Err(Error::UnsupportedCoreTypeInComponent),
+        };
+        dst.function(
+            func.params().iter().copied(),
+            func.results().iter().copied(),
+        );
+        Ok(())
+    }
+
+    pub fn parse_component_alias_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        aliases: &mut ComponentAliasSection,
+        section: wasmparser::ComponentAliasSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for alias in section {
+            aliases.alias(reencoder.component_alias(alias?)?);
+        }
+        Ok(())
+    }
+
+    pub fn component_alias<'a, T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        alias: wasmparser::ComponentAlias<'a>,
+    ) -> Result<Alias<'a>, Error<T::Error>> {
+        Ok(match alias {
+            wasmparser::ComponentAlias::InstanceExport {
+                kind,
+                instance_index,
+                name,
+            } => Alias::InstanceExport {
+                instance: reencoder.component_instance_index(instance_index),
+                kind: reencoder.component_export_kind(kind),
+                name,
+            },
+            wasmparser::ComponentAlias::CoreInstanceExport {
+                kind,
+                instance_index,
+                name,
+            } => Alias::CoreInstanceExport {
+                instance: reencoder.instance_index(instance_index),
+                kind: reencoder.export_kind(kind),
+                name,
+            },
+            wasmparser::ComponentAlias::Outer { kind, count, index } => Alias::Outer {
+                kind: reencoder.component_outer_alias_kind(kind),
+                count,
+                index: match kind {
+                    wasmparser::ComponentOuterAliasKind::CoreModule => {
+                        reencoder.outer_module_index(count, index)
+                    }
+                    wasmparser::ComponentOuterAliasKind::CoreType => {
+                        reencoder.outer_type_index(count, index)
+                    }
+                    wasmparser::ComponentOuterAliasKind::Type => {
+                        reencoder.outer_component_type_index(count, index)
+                    }
+                    wasmparser::ComponentOuterAliasKind::Component => {
+                        reencoder.outer_component_index(count, index)
+                    }
+                },
+            },
+        })
+    }
+
+    pub fn parse_component_instance_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        instances: &mut ComponentInstanceSection,
+        section: wasmparser::ComponentInstanceSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for instance in section {
+            reencoder.parse_component_instance(instances, instance?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_instance<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        instances: &mut ComponentInstanceSection,
+        instance: wasmparser::ComponentInstance<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match instance {
+            wasmparser::ComponentInstance::Instantiate {
+                component_index,
+                args,
+            } => {
+                let component_index = reencoder.component_index(component_index);
+                let args = args
+                    .iter()
+                    .map(|arg| {
+                        (
+                            arg.name,
+                            reencoder.component_export_kind(arg.kind),
+                            reencoder.component_external_index(arg.kind, arg.index),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                instances.instantiate(component_index, args);
+            }
+            wasmparser::ComponentInstance::FromExports(exports) => {
+                let exports = exports
+                    .iter()
+                    .map(|export| {
+                        (
+                            export.name.0,
+                            reencoder.component_export_kind(export.kind),
+                            reencoder.component_external_index(export.kind, export.index),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                instances.export_items(exports);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_instance_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        instances: &mut InstanceSection,
+        section: wasmparser::InstanceSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for instance in section {
+            reencoder.parse_instance(instances, instance?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_instance<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        instances: &mut InstanceSection,
+        instance: wasmparser::Instance<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        match instance {
+            wasmparser::Instance::Instantiate { module_index, args } => {
+                let module_index = reencoder.module_index(module_index);
+                let args = args
+                    .iter()
+                    .map(|arg| match arg.kind {
+                        wasmparser::InstantiationArgKind::Instance => (
+                            arg.name,
+                            ModuleArg::Instance(reencoder.instance_index(arg.index)),
+                        ),
+                    })
+                    .collect::<Vec<_>>();
+                instances.instantiate(module_index, args);
+            }
+            wasmparser::Instance::FromExports(exports) => {
+                let exports = exports
+                    .iter()
+                    .map(|export| {
+                        (
+                            export.name,
+                            reencoder.export_kind(export.kind),
+                            core_external_index(reencoder, export.kind, export.index),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                instances.export_items(exports);
+            }
+        }
+        Ok(())
+    }
+
+    /// Remaps the index of a core item of kind `kind`.
+    fn core_external_index<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        kind: wasmparser::ExternalKind,
+        index: u32,
+    ) -> u32 {
+        match kind {
+            wasmparser::ExternalKind::Func => reencoder.function_index(index),
+            wasmparser::ExternalKind::Table => reencoder.table_index(index),
+            wasmparser::ExternalKind::Memory => reencoder.memory_index(index),
+            wasmparser::ExternalKind::Global => reencoder.global_index(index),
+            wasmparser::ExternalKind::Tag => reencoder.tag_index(index),
+        }
+    }
+
+    pub fn parse_component_canonical_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        canonicals: &mut CanonicalFunctionSection,
+        section: wasmparser::ComponentCanonicalSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for func in section {
+            reencoder.parse_component_canonical(canonicals, func?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_canonical<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        canonicals: &mut CanonicalFunctionSection,
+        func: wasmparser::CanonicalFunction,
+    ) -> Result<(), Error<T::Error>> {
+        match func {
+            wasmparser::CanonicalFunction::Lift {
+                core_func_index,
+                type_index,
+                options,
+            } => {
+                let core_func_index = reencoder.function_index(core_func_index);
+                let type_index = reencoder.component_type_index(type_index);
+                let options = options
+                    .iter()
+                    .map(|o| reencoder.canonical_option(*o))
+                    .collect::<Vec<_>>();
+                canonicals.lift(core_func_index, type_index, options);
+            }
+            wasmparser::CanonicalFunction::Lower {
+                func_index,
+                options,
+            } => {
+                let func_index = reencoder.component_func_index(func_index);
+                let options = options
+                    .iter()
+                    .map(|o| reencoder.canonical_option(*o))
+                    .collect::<Vec<_>>();
+                canonicals.lower(func_index, options);
+            }
+            wasmparser::CanonicalFunction::ResourceNew { resource } => {
+                canonicals.resource_new(reencoder.component_type_index(resource));
+            }
+            wasmparser::CanonicalFunction::ResourceDrop { resource } => {
+                canonicals.resource_drop(reencoder.component_type_index(resource));
+            }
+            wasmparser::CanonicalFunction::ResourceRep { resource } => {
+                canonicals.resource_rep(reencoder.component_type_index(resource));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_import_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        imports: &mut ComponentImportSection,
+        section: wasmparser::ComponentImportSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for import in section {
+            reencoder.parse_component_import(imports, import?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_import<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        imports: &mut ComponentImportSection,
+        import: wasmparser::ComponentImport<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let ty = reencoder.component_type_ref(import.ty)?;
+        imports.import(import.name.0, ty);
+        Ok(())
+    }
+
+    pub fn parse_component_export_section<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        exports: &mut ComponentExportSection,
+        section: wasmparser::ComponentExportSectionReader<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        for export in section {
+            reencoder.parse_component_export(exports, export?)?;
+        }
+        Ok(())
+    }
+
+    pub fn parse_component_export<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        exports: &mut ComponentExportSection,
+        export: wasmparser::ComponentExport<'_>,
+    ) -> Result<(), Error<T::Error>> {
+        let kind = reencoder.component_export_kind(export.kind);
+        let index = reencoder.component_external_index(export.kind, export.index);
+        let ty = export
+            .ty
+            .map(|t| reencoder.component_type_ref(t))
+            .transpose()?;
+        exports.export(export.name.0, kind, index, ty);
+        Ok(())
+    }
+
+    pub fn parse_component_start<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        component: &mut Component,
+        func: wasmparser::ComponentStartFunction,
+    ) -> Result<(), Error<T::Error>> {
+        let function_index = reencoder.component_func_index(func.func_index);
+        let args = func
+            .arguments
+            .iter()
+            .map(|i| reencoder.component_value_index(*i))
+            .collect::<Vec<_>>();
+        component.section(&ComponentStartSection {
+            function_index,
+            args,
+            results: func.results,
+        });
+        Ok(())
+    }
+
+    pub fn component_external_index<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        kind: wasmparser::ComponentExternalKind,
+        index: u32,
+    ) -> u32 {
+        match kind {
+            wasmparser::ComponentExternalKind::Module => reencoder.module_index(index),
+            wasmparser::ComponentExternalKind::Func => reencoder.component_func_index(index),
+            wasmparser::ComponentExternalKind::Value => reencoder.component_value_index(index),
+            wasmparser::ComponentExternalKind::Type => reencoder.component_type_index(index),
+            wasmparser::ComponentExternalKind::Instance => {
+                reencoder.component_instance_index(index)
+            }
+            wasmparser::ComponentExternalKind::Component => reencoder.component_index(index),
+        }
+    }
+
+    pub fn component_type_ref<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        ty: wasmparser::ComponentTypeRef,
+    ) -> Result<ComponentTypeRef, Error<T::Error>> {
+        Ok(match ty {
+            wasmparser::ComponentTypeRef::Module(u) => {
+                ComponentTypeRef::Module(reencoder.type_index(u))
+            }
+            wasmparser::ComponentTypeRef::Func(u) => {
+                ComponentTypeRef::Func(reencoder.component_type_index(u))
+            }
+            wasmparser::ComponentTypeRef::Value(valty) => {
+                ComponentTypeRef::Value(reencoder.component_val_type(valty))
+            }
+            wasmparser::ComponentTypeRef::Type(bounds) => {
+                ComponentTypeRef::Type(reencoder.type_bounds(bounds))
+            }
+            wasmparser::ComponentTypeRef::Instance(u) => {
+                ComponentTypeRef::Instance(reencoder.component_type_index(u))
+            }
+            wasmparser::ComponentTypeRef::Component(u) => {
+                ComponentTypeRef::Component(reencoder.component_type_index(u))
+            }
+        })
+    }
+
+    pub fn type_bounds<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        bounds: wasmparser::TypeBounds,
+    ) -> TypeBounds {
+        match bounds {
+            wasmparser::TypeBounds::Eq(u) => TypeBounds::Eq(reencoder.component_type_index(u)),
+            wasmparser::TypeBounds::SubResource => TypeBounds::SubResource,
+        }
+    }
+
+    pub fn component_val_type<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        ty: wasmparser::ComponentValType,
+    ) -> ComponentValType {
+        match ty {
+            wasmparser::ComponentValType::Type(u) => {
+                ComponentValType::Type(reencoder.component_type_index(u))
+            }
+            wasmparser::ComponentValType::Primitive(pty) => {
+                ComponentValType::Primitive(reencoder.component_primitive_val_type(pty))
+            }
+        }
+    }
+
+    pub fn component_primitive_val_type<T: ?Sized + ReencodeComponent>(
+        _reencoder: &mut T,
+        ty: wasmparser::PrimitiveValType,
+    ) -> PrimitiveValType {
+        match ty {
+            wasmparser::PrimitiveValType::Bool => PrimitiveValType::Bool,
+            wasmparser::PrimitiveValType::S8 => PrimitiveValType::S8,
+            wasmparser::PrimitiveValType::U8 => PrimitiveValType::U8,
+            wasmparser::PrimitiveValType::S16 => PrimitiveValType::S16,
+            wasmparser::PrimitiveValType::U16 => PrimitiveValType::U16,
+            wasmparser::PrimitiveValType::S32 => PrimitiveValType::S32,
+            wasmparser::PrimitiveValType::U32 => PrimitiveValType::U32,
+            wasmparser::PrimitiveValType::S64 => PrimitiveValType::S64,
+            wasmparser::PrimitiveValType::U64 => PrimitiveValType::U64,
+            wasmparser::PrimitiveValType::Float32 => PrimitiveValType::Float32,
+            wasmparser::PrimitiveValType::Float64 => PrimitiveValType::Float64,
+            wasmparser::PrimitiveValType::Char => PrimitiveValType::Char,
+            wasmparser::PrimitiveValType::String => PrimitiveValType::String,
+        }
+    }
+
+    pub fn component_export_kind<T: ?Sized + ReencodeComponent>(
+        _reencoder: &mut T,
+        kind: wasmparser::ComponentExternalKind,
+    ) -> ComponentExportKind {
+        match kind {
+            wasmparser::ComponentExternalKind::Module => ComponentExportKind::Module,
+            wasmparser::ComponentExternalKind::Func => ComponentExportKind::Func,
+            wasmparser::ComponentExternalKind::Value => ComponentExportKind::Value,
+            wasmparser::ComponentExternalKind::Type => ComponentExportKind::Type,
+            wasmparser::ComponentExternalKind::Instance => ComponentExportKind::Instance,
+            wasmparser::ComponentExternalKind::Component => ComponentExportKind::Component,
+        }
+    }
+
+    pub fn component_outer_alias_kind<T: ?Sized + ReencodeComponent>(
+        _reencoder: &mut T,
+        kind: wasmparser::ComponentOuterAliasKind,
+    ) -> ComponentOuterAliasKind {
+        match kind {
+            wasmparser::ComponentOuterAliasKind::CoreModule => ComponentOuterAliasKind::CoreModule,
+            wasmparser::ComponentOuterAliasKind::CoreType => ComponentOuterAliasKind::CoreType,
+            wasmparser::ComponentOuterAliasKind::Type => ComponentOuterAliasKind::Type,
+            wasmparser::ComponentOuterAliasKind::Component => ComponentOuterAliasKind::Component,
+        }
+    }
+
+    pub fn canonical_option<T: ?Sized + ReencodeComponent>(
+        reencoder: &mut T,
+        option: wasmparser::CanonicalOption,
+    ) -> CanonicalOption {
+        match option {
+            wasmparser::CanonicalOption::UTF8 => CanonicalOption::UTF8,
+            wasmparser::CanonicalOption::UTF16 => CanonicalOption::UTF16,
+            wasmparser::CanonicalOption::CompactUTF16 => CanonicalOption::CompactUTF16,
+            wasmparser::CanonicalOption::Memory(u) => {
+                CanonicalOption::Memory(reencoder.memory_index(u))
+            }
+            wasmparser::CanonicalOption::Realloc(u) => {
+                CanonicalOption::Realloc(reencoder.function_index(u))
+            }
+            wasmparser::CanonicalOption::PostReturn(u) => {
+                CanonicalOption::PostReturn(reencoder.function_index(u))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReencodeComponent;
+    use crate::reencode::{Error, Reencode};
+    use crate::*;
+    use std::convert::Infallible;
+    use wasmparser::{Parser, Payload};
+
+    /// Drops custom sections from every module and component, tracking how
+    /// deeply nested the current item is.
+    #[derive(Default)]
+    struct StripCustomSections {
+        depth: u32,
+        max_depth: u32,
+    }
+
+    impl Reencode for StripCustomSections {
+        type Error = Infallible;
+
+        fn parse_custom_section(
+            &mut self,
+            _module: &mut Module,
+            _section: wasmparser::CustomSectionReader<'_>,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    impl ReencodeComponent for StripCustomSections {
+        fn push_depth(&mut self) {
+            self.depth += 1;
+            self.max_depth = self.max_depth.max(self.depth);
+        }
+
+        fn pop_depth(&mut self) {
+            self.depth -= 1;
+        }
+
+        fn parse_component_custom_section(
+            &mut self,
+            _component: &mut Component,
+            _section: wasmparser::CustomSectionReader<'_>,
+        ) -> Result<(), Error> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn strip_nested_custom_sections() {
+        let custom = CustomSection {
+            name: "producers".into(),
+            data: [0].as_slice().into(),
+        };
+        let mut module = Module::new();
+        module.section(&custom);
+        let mut inner = Component::new();
+        inner.section(&ModuleSection(&module));
+        inner.section(&custom);
+        let mut outer = Component::new();
+        outer.section(&ModuleSection(&module));
+        outer.section(&NestedComponentSection(&inner));
+        outer.section(&custom);
+        let wasm = outer.finish();
+
+        let mut reencoder = StripCustomSections::default();
+        let mut component = Component::new();
+        reencoder
+            .parse_component(&mut component, Parser::new(0), &wasm)
+            .unwrap();
+        let wasm = component.finish();
+        let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+            component_model: true,
+            ..Default::default()
+        });
+        validator.validate_all(&wasm).unwrap();
+        assert_eq!(reencoder.depth, 0);
+        assert_eq!(reencoder.max_depth, 2);
+
+        let mut modules = 0;
+        for payload in Parser::new(0).parse_all(&wasm) {
+            match payload.unwrap() {
+                Payload::ModuleSection { .. } => modules += 1,
+                Payload::CustomSection(s) => panic!("unexpected custom section {}", s.name()),
+                _ => {}
+            }
+        }
+        assert_eq!(modules, 2);
+    }
+}
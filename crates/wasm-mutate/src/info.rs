@@ -1,11 +1,11 @@
 use crate::{
     module::{PrimitiveTypeInfo, TypeInfo},
-    Result,
+    Error, Result,
 };
 use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::ops::Range;
-use wasm_encoder::{RawSection, SectionId};
+use wasm_encoder::{ComponentSectionId, RawSection, SectionId};
 use wasmparser::{Chunk, Parser, Payload};
 
 /// Provides module information for future usage during mutation
@@ -29,6 +29,7 @@ pub struct ModuleInfo<'a> {
     pub data: Option<usize>,
     pub code: Option<usize>,
     pub start: Option<usize>,
+    pub branch_hints: Option<usize>,
 
     pub exports_count: u32,
     elements_count: u32,
@@ -194,6 +195,9 @@ impl<'a> ModuleInfo<'a> {
                     info.section(SectionId::Data.into(), reader.range(), input_wasm);
                 }
                 Payload::CustomSection(c) => {
+                    if c.name() == "metadata.code.branch_hint" {
+                        info.branch_hints = Some(info.raw_sections.len());
+                    }
                     info.section(SectionId::Custom.into(), c.range(), input_wasm);
                 }
                 Payload::UnknownSection {
@@ -320,13 +324,13 @@ impl<'a> ModuleInfo<'a> {
         assert_ne!(src_idx, dest_idx);
         let mut module = wasm_encoder::Module::new();
         self.raw_sections.iter().enumerate().for_each(|(i, s)| {
-            if src_idx < dest_idx && i == dest_idx {
+            if dest_idx < src_idx && i == dest_idx {
                 module.section(&self.raw_sections[src_idx]);
             }
             if i != src_idx {
                 module.section(s);
             }
-            if dest_idx < src_idx && i == dest_idx {
+            if src_idx < dest_idx && i == dest_idx {
                 module.section(&self.raw_sections[src_idx]);
             }
         });
@@ -434,3 +438,183 @@ impl<'a> ModuleInfo<'a> {
         self.types_map.len() as u32
     }
 }
+
+/// Provides information about a component for use during mutation.
+///
+/// Only the top-level sections of the component are recorded here; nested
+/// modules and components are kept as opaque raw sections.
+#[derive(Default, Clone, Debug)]
+pub struct ComponentInfo<'a> {
+    // Indices of various sections within `self.raw_sections`.
+    pub modules: Vec<usize>,
+    pub core_instances: Vec<usize>,
+    pub instances: Vec<usize>,
+    pub canonicals: Vec<usize>,
+    pub imports: Vec<usize>,
+    pub exports: Vec<usize>,
+
+    pub imports_count: u32,
+    pub exports_count: u32,
+    pub export_names: HashSet<String>,
+
+    // raw_sections
+    pub raw_sections: Vec<RawSection<'a>>,
+    pub input_wasm: &'a [u8],
+}
+
+impl<'a> ComponentInfo<'a> {
+    /// Parse the given component bytes and fill out a `ComponentInfo` for it.
+    pub fn new(input_wasm: &[u8]) -> Result<ComponentInfo<'_>> {
+        let mut parser = Parser::new(0);
+        let mut info = ComponentInfo::default();
+        let mut wasm = input_wasm;
+        info.input_wasm = wasm;
+
+        loop {
+            let (payload, consumed) = match parser.parse(wasm, true)? {
+                Chunk::NeedMoreData(hint) => {
+                    return Err(Error::other(format!(
+                        "invalid Wasm component: needed {hint} more bytes"
+                    )));
+                }
+                Chunk::Parsed { consumed, payload } => (payload, consumed),
+            };
+            match payload {
+                Payload::ModuleSection { range, .. } => {
+                    info.modules.push(info.raw_sections.len());
+                    info.section(
+                        ComponentSectionId::CoreModule.into(),
+                        range.clone(),
+                        input_wasm,
+                    );
+                    // The nested module has already been accounted for by
+                    // the parser, so skip over its bytes entirely.
+                    wasm = &input_wasm[range.end..];
+                    continue;
+                }
+                Payload::ComponentSection { range, .. } => {
+                    info.section(
+                        ComponentSectionId::Component.into(),
+                        range.clone(),
+                        input_wasm,
+                    );
+                    wasm = &input_wasm[range.end..];
+                    continue;
+                }
+                Payload::InstanceSection(reader) => {
+                    info.core_instances.push(info.raw_sections.len());
+                    info.section(
+                        ComponentSectionId::CoreInstance.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::CoreTypeSection(reader) => {
+                    info.section(
+                        ComponentSectionId::CoreType.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::ComponentInstanceSection(reader) => {
+                    info.instances.push(info.raw_sections.len());
+                    info.section(
+                        ComponentSectionId::Instance.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::ComponentAliasSection(reader) => {
+                    info.section(ComponentSectionId::Alias.into(), reader.range(), input_wasm);
+                }
+                Payload::ComponentTypeSection(reader) => {
+                    info.section(ComponentSectionId::Type.into(), reader.range(), input_wasm);
+                }
+                Payload::ComponentCanonicalSection(reader) => {
+                    info.canonicals.push(info.raw_sections.len());
+                    info.section(
+                        ComponentSectionId::CanonicalFunction.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::ComponentStartSection { range, .. } => {
+                    info.section(ComponentSectionId::Start.into(), range, input_wasm);
+                }
+                Payload::ComponentImportSection(reader) => {
+                    info.imports.push(info.raw_sections.len());
+                    info.imports_count += reader.count();
+                    info.section(
+                        ComponentSectionId::Import.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::ComponentExportSection(reader) => {
+                    info.exports.push(info.raw_sections.len());
+                    info.exports_count += reader.count();
+
+                    for entry in reader.clone() {
+                        info.export_names.insert(entry?.name.0.into());
+                    }
+
+                    info.section(
+                        ComponentSectionId::Export.into(),
+                        reader.range(),
+                        input_wasm,
+                    );
+                }
+                Payload::CustomSection(c) => {
+                    info.section(ComponentSectionId::CoreCustom.into(), c.range(), input_wasm);
+                }
+                Payload::UnknownSection {
+                    id,
+                    contents: _,
+                    range,
+                } => {
+                    info.section(id, range, input_wasm);
+                }
+                Payload::Version { .. } => {}
+                Payload::End(_) => {
+                    break;
+                }
+                // Payloads the component mutators don't inspect are kept
+                // as-is.
+                _ => {
+                    if let Some((id, range)) = payload.as_section() {
+                        info.section(id, range, input_wasm);
+                    }
+                }
+            }
+            wasm = &wasm[consumed..];
+        }
+
+        Ok(info)
+    }
+
+    /// Registers a new raw_section in the ComponentInfo
+    pub fn section(&mut self, id: u8, range: Range<usize>, full_wasm: &'a [u8]) {
+        self.raw_sections.push(RawSection {
+            id,
+            data: &full_wasm[range],
+        });
+    }
+
+    /// Replace the `i`th section in this component with the given new section.
+    pub fn replace_section(
+        &self,
+        i: usize,
+        new_section: &impl wasm_encoder::ComponentSection,
+    ) -> wasm_encoder::Component {
+        log::trace!("replacing component section {}", i);
+        let mut component = wasm_encoder::Component::new();
+        for (j, s) in self.raw_sections.iter().enumerate() {
+            if i == j {
+                component.section(new_section);
+            } else {
+                component.section(s);
+            }
+        }
+        component
+    }
+}
@@ -17,15 +17,30 @@ mod mutators;
 pub use error::*;
 
 use crate::mutators::{
-    add_function::AddFunctionMutator, add_type::AddTypeMutator, codemotion::CodemotionMutator,
-    custom::AddCustomSectionMutator, custom::CustomSectionMutator,
-    custom::ReorderCustomSectionMutator, function_body_unreachable::FunctionBodyUnreachable,
-    modify_const_exprs::ConstExpressionMutator, modify_data::ModifyDataMutator,
-    peephole::PeepholeMutator, remove_export::RemoveExportMutator, remove_item::RemoveItemMutator,
-    remove_section::RemoveSection, rename_export::RenameExportMutator, snip_function::SnipMutator,
+    add_function::AddFunctionMutator,
+    add_type::AddTypeMutator,
+    codemotion::CodemotionMutator,
+    component::{
+        canonical_options::CanonicalOptionsMutator, nested_module::NestedModuleMutator,
+        remove_export::RemoveComponentExportMutator, remove_import::RemoveComponentImportMutator,
+        rename_export::RenameComponentExportMutator,
+        shuffle_instantiation_args::ShuffleInstantiationArgsMutator, ComponentMutator,
+    },
+    custom::AddCustomSectionMutator,
+    custom::CustomSectionMutator,
+    custom::ReorderCustomSectionMutator,
+    function_body_unreachable::FunctionBodyUnreachable,
+    modify_const_exprs::ConstExpressionMutator,
+    modify_data::ModifyDataMutator,
+    peephole::PeepholeMutator,
+    remove_export::RemoveExportMutator,
+    remove_item::RemoveItemMutator,
+    remove_section::RemoveSection,
+    rename_export::RenameExportMutator,
+    snip_function::SnipMutator,
     Item,
 };
-use info::ModuleInfo;
+use info::{ComponentInfo, ModuleInfo};
 use mutators::Mutator;
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 use std::sync::Arc;
@@ -120,6 +135,9 @@ pub struct WasmMutate<'wasm> {
 
     #[cfg_attr(feature = "clap", clap(skip = None))]
     info: Option<ModuleInfo<'wasm>>,
+
+    #[cfg_attr(feature = "clap", clap(skip = None))]
+    component_info: Option<Box<ComponentInfo<'wasm>>>,
 }
 
 impl Default for WasmMutate<'_> {
@@ -133,6 +151,7 @@ impl Default for WasmMutate<'_> {
             fuel: u64::MAX,
             rng: None,
             info: None,
+            component_info: None,
         }
     }
 }
@@ -200,12 +219,18 @@ impl<'wasm> WasmMutate<'wasm> {
     }
 
     /// Run this configured `WasmMutate` on the given input Wasm.
+    ///
+    /// The input may be either a core module or a component.
     pub fn run<'a>(
         &'a mut self,
         input_wasm: &'wasm [u8],
     ) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + 'a>> {
         self.setup(input_wasm)?;
 
+        if self.component_info.is_some() {
+            return self.run_component();
+        }
+
         const MUTATORS: &[&dyn Mutator] = &[
             &PeepholeMutator::new(2),
             &RemoveExportMutator,
@@ -239,6 +264,60 @@ impl<'wasm> WasmMutate<'wasm> {
             },
         ];
 
+        // Branch hints refer to offsets within function bodies, so remember
+        // the original function bodies to detect which hints a mutation
+        // invalidated.
+        let info = self.info();
+        let original_code = match info.branch_hints {
+            Some(_) => Some((
+                function_bodies(info.code.map(|i| info.raw_sections[i].data))?,
+                info.num_imported_functions(),
+            )),
+            None => None,
+        };
+
+        // Attempt all mutators, but start at an arbitrary index.
+        let start = self.rng().gen_range(0..MUTATORS.len());
+        for m in MUTATORS.iter().cycle().skip(start).take(MUTATORS.len()) {
+            let can_mutate = m.can_mutate(self);
+            log::trace!("Can `{}` mutate? {}", m.name(), can_mutate);
+            if !can_mutate {
+                continue;
+            }
+            log::debug!("attempting to mutate with `{}`", m.name());
+            match m.mutate(self) {
+                Ok(iter) => {
+                    log::debug!("mutator `{}` succeeded", m.name());
+                    return Ok(Box::new(iter.into_iter().map(move |r| {
+                        let wasm = r?.finish();
+                        match &original_code {
+                            Some((bodies, num_imported_functions)) => {
+                                remove_stale_branch_hints(wasm, bodies, *num_imported_functions)
+                            }
+                            None => Ok(wasm),
+                        }
+                    })));
+                }
+                Err(e) => {
+                    log::debug!("mutator `{}` failed: {}", m.name(), e);
+                    return Err(e);
+                }
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn run_component<'a>(&'a mut self) -> Result<Box<dyn Iterator<Item = Result<Vec<u8>>> + 'a>> {
+        const MUTATORS: &[&dyn ComponentMutator] = &[
+            &RemoveComponentExportMutator,
+            &RemoveComponentImportMutator,
+            &RenameComponentExportMutator { max_name_size: 100 },
+            &CanonicalOptionsMutator,
+            &NestedModuleMutator { max_mutations: 10 },
+            &ShuffleInstantiationArgsMutator,
+        ];
+
         // Attempt all mutators, but start at an arbitrary index.
         let start = self.rng().gen_range(0..MUTATORS.len());
         for m in MUTATORS.iter().cycle().skip(start).take(MUTATORS.len()) {
@@ -251,7 +330,7 @@ impl<'wasm> WasmMutate<'wasm> {
             match m.mutate(self) {
                 Ok(iter) => {
                     log::debug!("mutator `{}` succeeded", m.name());
-                    return Ok(Box::new(iter.into_iter().map(|r| r.map(|m| m.finish()))));
+                    return Ok(Box::new(iter.into_iter().map(|r| r.map(|c| c.finish()))));
                 }
                 Err(e) => {
                     log::debug!("mutator `{}` failed: {}", m.name(), e);
@@ -264,7 +343,13 @@ impl<'wasm> WasmMutate<'wasm> {
     }
 
     fn setup(&mut self, input_wasm: &'wasm [u8]) -> Result<()> {
-        self.info = Some(ModuleInfo::new(input_wasm)?);
+        if wasmparser::Parser::is_component(input_wasm) {
+            self.info = None;
+            self.component_info = Some(Box::new(ComponentInfo::new(input_wasm)?));
+        } else {
+            self.info = Some(ModuleInfo::new(input_wasm)?);
+            self.component_info = None;
+        }
         self.rng = Some(SmallRng::seed_from_u64(self.seed));
         Ok(())
     }
@@ -277,6 +362,10 @@ impl<'wasm> WasmMutate<'wasm> {
         self.info.as_ref().unwrap()
     }
 
+    pub(crate) fn component_info(&self) -> &ComponentInfo<'wasm> {
+        self.component_info.as_ref().unwrap()
+    }
+
     fn raw_mutate(&mut self, data: &mut Vec<u8>, max_size: usize) -> Result<()> {
         // If a raw mutation function is configured then that's prioritized.
         if let Some(mutate) = &self.raw_mutate_func {
@@ -318,6 +407,107 @@ impl<'wasm> WasmMutate<'wasm> {
     }
 }
 
+/// Returns the bytes of each function body in the code section `code`.
+fn function_bodies(code: Option<&[u8]>) -> Result<Vec<&[u8]>> {
+    let code = match code {
+        Some(code) => code,
+        None => return Ok(Vec::new()),
+    };
+    wasmparser::CodeSectionReader::new(code, 0)?
+        .into_iter()
+        .map(|body| Ok(&code[body?.range()]))
+        .collect()
+}
+
+/// Rewrites the `metadata.code.branch_hint` section of the mutated `wasm` to
+/// keep only the hints of functions whose bodies are identical to the
+/// original module's, since the offsets of the other hints may no longer be
+/// correct. Function indices are shifted by any change in the number of
+/// imported functions.
+fn remove_stale_branch_hints(
+    wasm: Vec<u8>,
+    original_bodies: &[&[u8]],
+    original_imported_functions: u32,
+) -> Result<Vec<u8>> {
+    let info = ModuleInfo::new(&wasm)?;
+    let branch_hints = match info.branch_hints {
+        Some(i) => i,
+        None => return Ok(wasm),
+    };
+    let bodies = function_bodies(info.code.map(|i| info.raw_sections[i].data))?;
+    let hints = match retained_branch_hints(
+        info.raw_sections[branch_hints].data,
+        &bodies,
+        info.num_imported_functions(),
+        original_bodies,
+        original_imported_functions,
+    ) {
+        Ok(None) => return Ok(wasm),
+        Ok(Some(hints)) => hints,
+        // Mutators which edit custom sections may have also left the section
+        // malformed, in which case none of its hints can be trusted.
+        Err(_) => wasm_encoder::BranchHints::new(),
+    };
+
+    log::debug!("removing or renumbering branch hints invalidated by the mutation");
+    Ok(info
+        .replace_multiple_sections(|i, _, module| {
+            if i != branch_hints {
+                return false;
+            }
+            if !hints.is_empty() {
+                module.section(&hints);
+            }
+            true
+        })
+        .finish())
+}
+
+/// Parses the branch hints custom `section`, which was copied from the
+/// original module, and returns the hints that are still correct for the
+/// mutated module with function `bodies`, or `None` if the section doesn't
+/// need to change.
+fn retained_branch_hints(
+    section: &[u8],
+    bodies: &[&[u8]],
+    imported_functions: u32,
+    original_bodies: &[&[u8]],
+    original_imported_functions: u32,
+) -> Result<Option<wasm_encoder::BranchHints>> {
+    let mut hints = wasm_encoder::BranchHints::new();
+    let mut changed = false;
+    let section = wasmparser::CustomSectionReader::new(section, 0)?;
+    for func in wasmparser::BranchHintSectionReader::new(section.data(), section.data_offset())? {
+        let func = func?;
+        let defined = func.func.checked_sub(original_imported_functions);
+        let unchanged = defined.is_some_and(|i| {
+            let i = i as usize;
+            bodies.get(i).is_some() && bodies.get(i) == original_bodies.get(i)
+        });
+        let index = match defined {
+            Some(i) if unchanged => imported_functions + i,
+            _ => {
+                changed = true;
+                continue;
+            }
+        };
+        changed |= index != func.func;
+        let func_hints = func
+            .hints
+            .into_iter()
+            .map(|hint| {
+                let hint = hint?;
+                Ok(wasm_encoder::BranchHint {
+                    branch_func_offset: hint.func_offset,
+                    branch_hint_value: hint.taken.into(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        hints.function_hints(index, func_hints);
+    }
+    Ok(if changed { Some(hints) } else { None })
+}
+
 #[cfg(test)]
 pub(crate) fn validate(bytes: &[u8]) {
     let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
@@ -25,6 +25,7 @@
 pub mod add_function;
 pub mod add_type;
 pub mod codemotion;
+pub mod component;
 pub mod custom;
 pub mod function_body_unreachable;
 pub mod modify_const_exprs;
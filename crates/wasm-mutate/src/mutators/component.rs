@@ -0,0 +1,212 @@
+//! Mutators for WebAssembly components.
+//!
+//! Component mutators operate on the top-level sections of a component as
+//! described by [`ComponentInfo`][crate::info::ComponentInfo]. Unlike core
+//! module mutators most of these can't cheaply prove that their output is
+//! valid since that would require resolving the full component type
+//! information, so instead candidate mutations are checked with
+//! [`is_valid_component`] before they're handed out.
+
+pub mod canonical_options;
+pub mod nested_module;
+pub mod remove_export;
+pub mod remove_import;
+pub mod rename_export;
+pub mod shuffle_instantiation_args;
+
+use crate::{Result, WasmMutate};
+use std::borrow::Cow;
+use wasm_encoder::{
+    CanonicalOption, Component, ComponentExportKind, ComponentTypeRef, ComponentValType,
+    ExportKind, PrimitiveValType, TypeBounds,
+};
+use wasmparser::{Validator, WasmFeatures};
+
+/// A mutation that can be applied to a Wasm component to produce a new,
+/// mutated Wasm component.
+///
+/// This is the component counterpart of [`Mutator`][super::Mutator] and
+/// follows the same contract for `can_mutate` and laziness of `mutate`.
+pub trait ComponentMutator {
+    /// Can this `ComponentMutator` *probably* be applied to the given
+    /// component and configuration?
+    fn can_mutate(&self, config: &WasmMutate) -> bool;
+
+    /// Run this mutation.
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>>;
+
+    /// What is this mutator's name?
+    ///
+    /// This is only used for debugging and logging purposes.
+    fn name(&self) -> Cow<'static, str> {
+        std::any::type_name::<Self>().into()
+    }
+}
+
+/// Returns whether `bytes` is a valid component with the component model
+/// proposal enabled.
+pub(crate) fn is_valid_component(bytes: &[u8]) -> bool {
+    let mut validator = Validator::new_with_features(WasmFeatures {
+        component_model: true,
+        ..WasmFeatures::default()
+    });
+    match validator.validate_all(bytes) {
+        Ok(_) => true,
+        Err(e) => {
+            log::debug!("discarding invalid component mutation: {}", e);
+            false
+        }
+    }
+}
+
+pub(crate) fn canonical_option(option: &wasmparser::CanonicalOption) -> CanonicalOption {
+    match *option {
+        wasmparser::CanonicalOption::UTF8 => CanonicalOption::UTF8,
+        wasmparser::CanonicalOption::UTF16 => CanonicalOption::UTF16,
+        wasmparser::CanonicalOption::CompactUTF16 => CanonicalOption::CompactUTF16,
+        wasmparser::CanonicalOption::Memory(i) => CanonicalOption::Memory(i),
+        wasmparser::CanonicalOption::Realloc(i) => CanonicalOption::Realloc(i),
+        wasmparser::CanonicalOption::PostReturn(i) => CanonicalOption::PostReturn(i),
+    }
+}
+
+pub(crate) fn export_kind(kind: wasmparser::ExternalKind) -> ExportKind {
+    match kind {
+        wasmparser::ExternalKind::Func => ExportKind::Func,
+        wasmparser::ExternalKind::Table => ExportKind::Table,
+        wasmparser::ExternalKind::Memory => ExportKind::Memory,
+        wasmparser::ExternalKind::Global => ExportKind::Global,
+        wasmparser::ExternalKind::Tag => ExportKind::Tag,
+    }
+}
+
+pub(crate) fn component_export_kind(
+    kind: wasmparser::ComponentExternalKind,
+) -> ComponentExportKind {
+    match kind {
+        wasmparser::ComponentExternalKind::Module => ComponentExportKind::Module,
+        wasmparser::ComponentExternalKind::Func => ComponentExportKind::Func,
+        wasmparser::ComponentExternalKind::Value => ComponentExportKind::Value,
+        wasmparser::ComponentExternalKind::Type => ComponentExportKind::Type,
+        wasmparser::ComponentExternalKind::Instance => ComponentExportKind::Instance,
+        wasmparser::ComponentExternalKind::Component => ComponentExportKind::Component,
+    }
+}
+
+pub(crate) fn component_type_ref(ty: wasmparser::ComponentTypeRef) -> ComponentTypeRef {
+    match ty {
+        wasmparser::ComponentTypeRef::Module(i) => ComponentTypeRef::Module(i),
+        wasmparser::ComponentTypeRef::Func(i) => ComponentTypeRef::Func(i),
+        wasmparser::ComponentTypeRef::Value(ty) => ComponentTypeRef::Value(component_val_type(ty)),
+        wasmparser::ComponentTypeRef::Type(wasmparser::TypeBounds::Eq(i)) => {
+            ComponentTypeRef::Type(TypeBounds::Eq(i))
+        }
+        wasmparser::ComponentTypeRef::Type(wasmparser::TypeBounds::SubResource) => {
+            ComponentTypeRef::Type(TypeBounds::SubResource)
+        }
+        wasmparser::ComponentTypeRef::Instance(i) => ComponentTypeRef::Instance(i),
+        wasmparser::ComponentTypeRef::Component(i) => ComponentTypeRef::Component(i),
+    }
+}
+
+fn component_val_type(ty: wasmparser::ComponentValType) -> ComponentValType {
+    match ty {
+        wasmparser::ComponentValType::Primitive(ty) => {
+            ComponentValType::Primitive(primitive_val_type(ty))
+        }
+        wasmparser::ComponentValType::Type(i) => ComponentValType::Type(i),
+    }
+}
+
+fn primitive_val_type(ty: wasmparser::PrimitiveValType) -> PrimitiveValType {
+    match ty {
+        wasmparser::PrimitiveValType::Bool => PrimitiveValType::Bool,
+        wasmparser::PrimitiveValType::S8 => PrimitiveValType::S8,
+        wasmparser::PrimitiveValType::U8 => PrimitiveValType::U8,
+        wasmparser::PrimitiveValType::S16 => PrimitiveValType::S16,
+        wasmparser::PrimitiveValType::U16 => PrimitiveValType::U16,
+        wasmparser::PrimitiveValType::S32 => PrimitiveValType::S32,
+        wasmparser::PrimitiveValType::U32 => PrimitiveValType::U32,
+        wasmparser::PrimitiveValType::S64 => PrimitiveValType::S64,
+        wasmparser::PrimitiveValType::U64 => PrimitiveValType::U64,
+        wasmparser::PrimitiveValType::Float32 => PrimitiveValType::Float32,
+        wasmparser::PrimitiveValType::Float64 => PrimitiveValType::Float64,
+        wasmparser::PrimitiveValType::Char => PrimitiveValType::Char,
+        wasmparser::PrimitiveValType::String => PrimitiveValType::String,
+    }
+}
+
+#[cfg(test)]
+fn match_component_mutation<T>(original: &str, mutator: T, expected: &str)
+where
+    T: ComponentMutator + Clone,
+{
+    WasmMutate::default().match_component_mutation(original, mutator, expected)
+}
+
+impl WasmMutate<'_> {
+    #[cfg(test)]
+    fn match_component_mutation<T>(&mut self, original: &str, mutator: T, expected: &str)
+    where
+        T: ComponentMutator + Clone,
+    {
+        use crate::ErrorKind;
+
+        drop(env_logger::try_init());
+
+        let original = &wat::parse_str(original).unwrap();
+
+        let expected = &wat::parse_str(expected).unwrap();
+        let expected_text = wasmprinter::print_bytes(expected).unwrap();
+
+        let mut config = self.clone();
+        config.setup(original).unwrap();
+
+        assert!(mutator.can_mutate(&config));
+
+        let attempts = 2000;
+        let mut last_mutation = None;
+
+        for _ in 0..attempts {
+            let mutation = match mutator
+                .clone()
+                .mutate(&mut config)
+                .map(|mut mutation| mutation.next())
+            {
+                Ok(Some(mutation)) => mutation.unwrap(),
+                Ok(None) => continue,
+                Err(e) if matches!(e.kind(), ErrorKind::NoMutationsApplicable) => continue,
+                Err(e) => panic!("mutation error: {}", e),
+            };
+
+            let mutation_bytes = mutation.finish();
+            assert!(is_valid_component(&mutation_bytes));
+
+            let text = wasmprinter::print_bytes(mutation_bytes).unwrap();
+            if text.trim() == expected_text.trim() {
+                return;
+            }
+            log::debug!("skipping mutation {}", text);
+            last_mutation = Some(text);
+        }
+
+        match last_mutation {
+            Some(mutation) => {
+                panic!(
+                    "after {} attempts the last mutation:\n{:?}\n\n\
+                     did not match the expected mutation\n{:?}",
+                    attempts, mutation, expected_text
+                );
+            }
+            None => {
+                panic!(
+                    "never found any applicable mutations after {} attempts",
+                    attempts
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,258 @@
+//! Mutator that changes the canonical ABI options of lifted and lowered
+//! functions.
+
+use super::{canonical_option, is_valid_component, ComponentMutator};
+use crate::{Error, Result, WasmMutate};
+use rand::{seq::SliceRandom, Rng};
+use wasm_encoder::{CanonicalFunctionSection, CanonicalOption, Component};
+use wasmparser::{CanonicalFunction, ComponentCanonicalSectionReader};
+
+/// Mutator that changes the options of a random `canon lift` or `canon lower`
+/// definition.
+///
+/// Options are mutated by switching the string encoding, by replacing the
+/// `realloc` function with one used by another canonical definition in the
+/// same component, or by removing the `realloc` or `post-return` functions.
+#[derive(Clone, Copy)]
+pub struct CanonicalOptionsMutator;
+
+/// The number of random mutations attempted before giving up.
+const MAX_ATTEMPTS: usize = 100;
+
+impl CanonicalOptionsMutator {
+    fn maybe_mutate_options(
+        &self,
+        config: &mut WasmMutate,
+        mutate: bool,
+        options: &[wasmparser::CanonicalOption],
+        reallocs: &[u32],
+        changed: &mut bool,
+    ) -> Vec<CanonicalOption> {
+        let original = options.iter().map(canonical_option).collect::<Vec<_>>();
+        if !mutate {
+            return original;
+        }
+        let mutated = self.mutate_options(config, options, reallocs);
+        *changed = mutated != original;
+        mutated
+    }
+
+    fn mutate_options(
+        &self,
+        config: &mut WasmMutate,
+        options: &[wasmparser::CanonicalOption],
+        reallocs: &[u32],
+    ) -> Vec<CanonicalOption> {
+        let mut options = options.iter().map(canonical_option).collect::<Vec<_>>();
+        let is_encoding = |o: &CanonicalOption| {
+            matches!(
+                o,
+                CanonicalOption::UTF8 | CanonicalOption::UTF16 | CanonicalOption::CompactUTF16
+            )
+        };
+        let realloc = options.iter().find_map(|o| match o {
+            CanonicalOption::Realloc(i) => Some(*i),
+            _ => None,
+        });
+        let other_reallocs = reallocs
+            .iter()
+            .copied()
+            .filter(|i| Some(*i) != realloc)
+            .collect::<Vec<_>>();
+
+        // Only removing options shrinks the component.
+        let choice = if config.reduce {
+            2
+        } else {
+            config.rng().gen_range(0..3)
+        };
+        match choice {
+            // Switch the string encoding, where the absence of an encoding
+            // option means UTF-8.
+            0 => {
+                let current = options
+                    .iter()
+                    .copied()
+                    .find(is_encoding)
+                    .unwrap_or(CanonicalOption::UTF8);
+                let encoding = *[
+                    CanonicalOption::UTF8,
+                    CanonicalOption::UTF16,
+                    CanonicalOption::CompactUTF16,
+                ]
+                .iter()
+                .filter(|e| **e != current)
+                .collect::<Vec<_>>()
+                .choose(config.rng())
+                .unwrap();
+                options.retain(|o| !is_encoding(o));
+                options.push(*encoding);
+            }
+            // Swap in the `realloc` used by another canonical definition.
+            1 if !other_reallocs.is_empty() => {
+                let new = *other_reallocs.choose(config.rng()).unwrap();
+                options.retain(|o| !matches!(o, CanonicalOption::Realloc(_)));
+                options.push(CanonicalOption::Realloc(new));
+            }
+            // Remove either the `realloc` or `post-return` option.
+            _ => {
+                let removable = options
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, o)| {
+                        matches!(
+                            o,
+                            CanonicalOption::Realloc(_) | CanonicalOption::PostReturn(_)
+                        )
+                    })
+                    .map(|(i, _)| i)
+                    .collect::<Vec<_>>();
+                if let Some(i) = removable.choose(config.rng()) {
+                    options.remove(*i);
+                }
+            }
+        }
+        options
+    }
+}
+
+impl ComponentMutator for CanonicalOptionsMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        // Gather all the `canon lift` and `canon lower` definitions along with
+        // all the `realloc` functions in use throughout the component.
+        let info = config.component_info();
+        let mut candidates = Vec::new();
+        let mut reallocs = Vec::new();
+        for &section in info.canonicals.iter() {
+            let reader = ComponentCanonicalSectionReader::new(info.raw_sections[section].data, 0)?;
+            for (i, func) in reader.into_iter().enumerate() {
+                let options = match func? {
+                    CanonicalFunction::Lift { options, .. }
+                    | CanonicalFunction::Lower { options, .. } => options,
+                    _ => continue,
+                };
+                candidates.push((section, i));
+                reallocs.extend(options.iter().filter_map(|o| match o {
+                    wasmparser::CanonicalOption::Realloc(i) => Some(*i),
+                    _ => None,
+                }));
+            }
+        }
+        reallocs.sort();
+        reallocs.dedup();
+        if candidates.is_empty() {
+            return Err(Error::no_mutations_applicable());
+        }
+
+        for _ in 0..MAX_ATTEMPTS {
+            config.consume_fuel(1)?;
+            let (section, func_index) = *candidates.choose(config.rng()).unwrap();
+
+            let reader = ComponentCanonicalSectionReader::new(
+                config.component_info().raw_sections[section].data,
+                0,
+            )?;
+            let mut funcs = CanonicalFunctionSection::new();
+            let mut changed = false;
+            for (i, func) in reader.into_iter().enumerate() {
+                match func? {
+                    CanonicalFunction::Lift {
+                        core_func_index,
+                        type_index,
+                        options,
+                    } => {
+                        let options = self.maybe_mutate_options(
+                            config,
+                            i == func_index,
+                            &options,
+                            &reallocs,
+                            &mut changed,
+                        );
+                        funcs.lift(core_func_index, type_index, options);
+                    }
+                    CanonicalFunction::Lower {
+                        func_index: index,
+                        options,
+                    } => {
+                        let options = self.maybe_mutate_options(
+                            config,
+                            i == func_index,
+                            &options,
+                            &reallocs,
+                            &mut changed,
+                        );
+                        funcs.lower(index, options);
+                    }
+                    CanonicalFunction::ResourceNew { resource } => {
+                        funcs.resource_new(resource);
+                    }
+                    CanonicalFunction::ResourceDrop { resource } => {
+                        funcs.resource_drop(resource);
+                    }
+                    CanonicalFunction::ResourceRep { resource } => {
+                        funcs.resource_rep(resource);
+                    }
+                }
+            }
+
+            if !changed {
+                continue;
+            }
+            let component = config.component_info().replace_section(section, &funcs);
+            if is_valid_component(&component.clone().finish()) {
+                return Ok(Box::new(std::iter::once(Ok(component))));
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && !config.component_info().canonicals.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::CanonicalOptionsMutator;
+
+    #[test]
+    fn test_canonical_options_mutator() {
+        crate::mutators::component::match_component_mutation(
+            r#"
+            (component
+                (import "f" (func $f (param "s" string)))
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+                )
+                (core instance $i (instantiate $m))
+                (core func (canon lower (func $f)
+                    string-encoding=utf8
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                ))
+            )
+            "#,
+            CanonicalOptionsMutator,
+            r#"
+            (component
+                (import "f" (func $f (param "s" string)))
+                (core module $m
+                    (memory (export "memory") 1)
+                    (func (export "realloc") (param i32 i32 i32 i32) (result i32) unreachable)
+                )
+                (core instance $i (instantiate $m))
+                (core func (canon lower (func $f)
+                    (memory $i "memory")
+                    (realloc (func $i "realloc"))
+                    string-encoding=utf16
+                ))
+            )
+            "#,
+        );
+    }
+}
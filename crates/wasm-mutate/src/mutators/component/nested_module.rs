@@ -0,0 +1,116 @@
+//! Mutator that applies a core module mutation to a module nested within a
+//! component.
+
+use super::{is_valid_component, ComponentMutator};
+use crate::{Error, ErrorKind, Result, WasmMutate};
+use rand::Rng;
+use wasm_encoder::{Component, ComponentSectionId, RawSection};
+
+/// Mutator that picks a core module defined in a component and mutates it in
+/// place with the core module mutators.
+///
+/// A mutated module may no longer line up with how the rest of the component
+/// instantiates it, so mutations of the nested module are tried until one
+/// results in a valid component.
+#[derive(Clone, Copy)]
+pub struct NestedModuleMutator {
+    /// The maximum number of mutations of a nested module to try before
+    /// moving on to the next module.
+    pub max_mutations: usize,
+}
+
+impl ComponentMutator for NestedModuleMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        let modules = config.component_info().modules.clone();
+        let start = config.rng().gen_range(0..modules.len());
+
+        for &section in modules.iter().cycle().skip(start).take(modules.len()) {
+            let module = config.component_info().raw_sections[section].data;
+
+            let mut inner = WasmMutate::default();
+            inner
+                .seed(config.rng().gen())
+                .preserve_semantics(config.preserve_semantics)
+                .reduce(config.reduce)
+                .fuel(config.fuel)
+                .raw_mutate_func(config.raw_mutate_func.clone());
+
+            let mut result = None;
+            match inner.run(module) {
+                Ok(mutations) => {
+                    for mutated in mutations.take(self.max_mutations) {
+                        let mutated = match mutated {
+                            Ok(mutated) => mutated,
+                            Err(e) => match e.kind() {
+                                ErrorKind::NoMutationsApplicable => continue,
+                                _ => return Err(e),
+                            },
+                        };
+                        let component = config.component_info().replace_section(
+                            section,
+                            &RawSection {
+                                id: ComponentSectionId::CoreModule.into(),
+                                data: &mutated,
+                            },
+                        );
+                        if is_valid_component(&component.clone().finish()) {
+                            result = Some(component);
+                            break;
+                        }
+                    }
+                }
+                Err(e) => match e.kind() {
+                    ErrorKind::OutOfFuel => return Err(e),
+                    _ => log::debug!("failed to mutate nested module: {}", e),
+                },
+            }
+
+            // Charge the fuel the nested mutation used to this mutation.
+            config.consume_fuel(config.fuel - inner.fuel)?;
+            if let Some(component) = result {
+                return Ok(Box::new(std::iter::once(Ok(component))));
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.component_info().modules.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NestedModuleMutator;
+
+    #[test]
+    fn test_nested_module_mutator() {
+        crate::mutators::component::match_component_mutation(
+            r#"
+            (component
+                (core module
+                    (func (export "f") (result i32)
+                        i32.const 42
+                    )
+                    (func (export "g"))
+                )
+            )
+            "#,
+            NestedModuleMutator { max_mutations: 10 },
+            r#"
+            (component
+                (core module
+                    (func (export "f") (result i32)
+                        i32.const 42
+                    )
+                    (func)
+                )
+            )
+            "#,
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Mutator that removes a random preexisting export from a component.
+
+use super::{component_export_kind, component_type_ref, is_valid_component, ComponentMutator};
+use crate::{Error, Result, WasmMutate};
+use rand::Rng;
+use wasm_encoder::{Component, ComponentExportSection};
+use wasmparser::ComponentExportSectionReader;
+
+/// Mutator that removes a random preexisting export from a component.
+///
+/// Other items in a component may refer to the index introduced by an export,
+/// so exports are tried starting from a random one until removing one still
+/// yields a valid component.
+#[derive(Clone, Copy)]
+pub struct RemoveComponentExportMutator;
+
+impl ComponentMutator for RemoveComponentExportMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        let info = config.component_info();
+        let mut candidates = Vec::new();
+        for &section in info.exports.iter() {
+            let reader = ComponentExportSectionReader::new(info.raw_sections[section].data, 0)?;
+            candidates.extend((0..reader.count()).map(|i| (section, i)));
+        }
+
+        let start = config.rng().gen_range(0..candidates.len());
+        for &(section, skip_at) in candidates.iter().cycle().skip(start).take(candidates.len()) {
+            config.consume_fuel(1)?;
+
+            let info = config.component_info();
+            let reader = ComponentExportSectionReader::new(info.raw_sections[section].data, 0)?;
+            let mut exports = ComponentExportSection::new();
+            for (i, export) in reader.into_iter().enumerate() {
+                let export = export?;
+                if i as u32 == skip_at {
+                    log::trace!("Removing component export {:?}", export);
+                    continue;
+                }
+                exports.export(
+                    export.name.0,
+                    component_export_kind(export.kind),
+                    export.index,
+                    export.ty.map(component_type_ref),
+                );
+            }
+
+            let component = info.replace_section(section, &exports);
+            if is_valid_component(&component.clone().finish()) {
+                return Ok(Box::new(std::iter::once(Ok(component))));
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.component_info().exports_count > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoveComponentExportMutator;
+
+    #[test]
+    fn test_remove_component_export_mutator() {
+        crate::mutators::component::match_component_mutation(
+            r#"
+            (component
+                (core module $m
+                    (func (export "f"))
+                )
+                (core instance $i (instantiate $m))
+                (func $f (canon lift (core func $i "f")))
+                (export "a" (func $f))
+                (export "b" (func $f))
+            )
+            "#,
+            RemoveComponentExportMutator,
+            r#"
+            (component
+                (core module $m
+                    (func (export "f"))
+                )
+                (core instance $i (instantiate $m))
+                (func $f (canon lift (core func $i "f")))
+                (export "b" (func $f))
+            )
+            "#,
+        );
+    }
+}
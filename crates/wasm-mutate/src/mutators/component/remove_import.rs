@@ -0,0 +1,451 @@
+//! Mutator that removes a random preexisting import from a component.
+
+use super::{
+    canonical_option, component_export_kind, component_type_ref, export_kind, is_valid_component,
+    ComponentMutator,
+};
+use crate::{Error, Result, WasmMutate};
+use rand::Rng;
+use wasm_encoder::{
+    Alias, CanonicalFunctionSection, Component, ComponentAliasSection, ComponentExportSection,
+    ComponentImportSection, ComponentInstanceSection, ComponentOuterAliasKind, ComponentSectionId,
+    ComponentStartSection, InstanceSection, ModuleArg,
+};
+use wasmparser::{
+    BinaryReader, CanonicalFunction, ComponentAlias, ComponentAliasSectionReader,
+    ComponentCanonicalSectionReader, ComponentExportSectionReader, ComponentExternalKind,
+    ComponentImportSectionReader, ComponentInstance, ComponentInstanceSectionReader,
+    ComponentStartFunction, ComponentTypeRef, Instance, InstanceSectionReader,
+    InstantiationArgKind,
+};
+
+/// Mutator that removes a random preexisting import from a component.
+///
+/// All references to items defined after the removed import are shifted
+/// down by one. Imports which are still referenced can't be removed, so
+/// imports are tried starting from a random one until one is found which
+/// yields a valid component. Type imports are never removed since other type
+/// definitions may refer to them.
+#[derive(Clone, Copy)]
+pub struct RemoveComponentImportMutator;
+
+/// The component-level index spaces that an import can introduce an item
+/// into.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Sort {
+    Module,
+    Func,
+    Value,
+    Type,
+    Instance,
+    Component,
+}
+
+impl Sort {
+    fn of_kind(kind: ComponentExternalKind) -> Sort {
+        match kind {
+            ComponentExternalKind::Module => Sort::Module,
+            ComponentExternalKind::Func => Sort::Func,
+            ComponentExternalKind::Value => Sort::Value,
+            ComponentExternalKind::Type => Sort::Type,
+            ComponentExternalKind::Instance => Sort::Instance,
+            ComponentExternalKind::Component => Sort::Component,
+        }
+    }
+
+    fn of_type_ref(ty: &ComponentTypeRef) -> Sort {
+        match ty {
+            ComponentTypeRef::Module(_) => Sort::Module,
+            ComponentTypeRef::Func(_) => Sort::Func,
+            ComponentTypeRef::Value(_) => Sort::Value,
+            ComponentTypeRef::Type(_) => Sort::Type,
+            ComponentTypeRef::Instance(_) => Sort::Instance,
+            ComponentTypeRef::Component(_) => Sort::Component,
+        }
+    }
+}
+
+/// The item being removed: its sort and its index within that sort.
+#[derive(Clone, Copy, Debug)]
+struct Removed {
+    sort: Sort,
+    index: u32,
+}
+
+impl Removed {
+    /// Returns the new index of the `sort` item at `index`, or an error if
+    /// that item is the one being removed.
+    fn remap(&self, sort: Sort, index: u32) -> Result<u32> {
+        if sort != self.sort || index < self.index {
+            Ok(index)
+        } else if index > self.index {
+            Ok(index - 1)
+        } else {
+            Err(Error::no_mutations_applicable())
+        }
+    }
+}
+
+impl RemoveComponentImportMutator {
+    /// Counts how many items of `sort` are defined by the sections before
+    /// `section`.
+    fn count_before(config: &WasmMutate, section: usize, sort: Sort) -> Result<u32> {
+        let info = config.component_info();
+        let mut count = 0;
+        for raw in info.raw_sections[..section].iter() {
+            let data = raw.data;
+            match raw.id {
+                id if id == ComponentSectionId::CoreModule as u8 => {
+                    count += u32::from(sort == Sort::Module);
+                }
+                id if id == ComponentSectionId::Component as u8 => {
+                    count += u32::from(sort == Sort::Component);
+                }
+                id if id == ComponentSectionId::Instance as u8 && sort == Sort::Instance => {
+                    count += ComponentInstanceSectionReader::new(data, 0)?.count();
+                }
+                id if id == ComponentSectionId::Type as u8 && sort == Sort::Type => {
+                    count += wasmparser::ComponentTypeSectionReader::new(data, 0)?.count();
+                }
+                id if id == ComponentSectionId::Alias as u8 => {
+                    for alias in ComponentAliasSectionReader::new(data, 0)? {
+                        let alias_sort = match alias? {
+                            ComponentAlias::InstanceExport { kind, .. } => Sort::of_kind(kind),
+                            ComponentAlias::CoreInstanceExport { .. } => continue,
+                            ComponentAlias::Outer { kind, .. } => match kind {
+                                wasmparser::ComponentOuterAliasKind::CoreModule => Sort::Module,
+                                wasmparser::ComponentOuterAliasKind::Type => Sort::Type,
+                                wasmparser::ComponentOuterAliasKind::Component => Sort::Component,
+                                wasmparser::ComponentOuterAliasKind::CoreType => continue,
+                            },
+                        };
+                        count += u32::from(alias_sort == sort);
+                    }
+                }
+                id if id == ComponentSectionId::CanonicalFunction as u8 => {
+                    for func in ComponentCanonicalSectionReader::new(data, 0)? {
+                        if let CanonicalFunction::Lift { .. } = func? {
+                            count += u32::from(sort == Sort::Func);
+                        }
+                    }
+                }
+                id if id == ComponentSectionId::Start as u8 && sort == Sort::Value => {
+                    count += BinaryReader::new(data)
+                        .read::<ComponentStartFunction>()?
+                        .results;
+                }
+                id if id == ComponentSectionId::Import as u8 => {
+                    for import in ComponentImportSectionReader::new(data, 0)? {
+                        count += u32::from(Sort::of_type_ref(&import?.ty) == sort);
+                    }
+                }
+                id if id == ComponentSectionId::Export as u8 => {
+                    for export in ComponentExportSectionReader::new(data, 0)? {
+                        count += u32::from(Sort::of_kind(export?.kind) == sort);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(count)
+    }
+
+    /// Builds the component without the `skip_at`th import of the import
+    /// section at `section`, failing if the import is still referenced.
+    fn remove(
+        config: &WasmMutate,
+        section: usize,
+        skip_at: u32,
+        removed: Removed,
+    ) -> Result<Component> {
+        let info = config.component_info();
+        let mut component = Component::new();
+        for (i, raw) in info.raw_sections.iter().enumerate() {
+            let data = raw.data;
+            match raw.id {
+                _ if i < section => {
+                    component.section(raw);
+                }
+                _ if i == section => {
+                    let mut imports = ComponentImportSection::new();
+                    for (j, import) in ComponentImportSectionReader::new(data, 0)?
+                        .into_iter()
+                        .enumerate()
+                    {
+                        let import = import?;
+                        if j as u32 == skip_at {
+                            log::trace!("Removing component import {:?}", import);
+                            continue;
+                        }
+                        imports.import(import.name.0, component_type_ref(import.ty));
+                    }
+                    component.section(&imports);
+                }
+                id if id == ComponentSectionId::CoreInstance as u8 => {
+                    let mut instances = InstanceSection::new();
+                    for instance in InstanceSectionReader::new(data, 0)? {
+                        match instance? {
+                            Instance::Instantiate { module_index, args } => {
+                                instances.instantiate(
+                                    removed.remap(Sort::Module, module_index)?,
+                                    args.iter().map(|arg| match arg.kind {
+                                        InstantiationArgKind::Instance => {
+                                            (arg.name, ModuleArg::Instance(arg.index))
+                                        }
+                                    }),
+                                );
+                            }
+                            Instance::FromExports(exports) => {
+                                instances.export_items(
+                                    exports
+                                        .iter()
+                                        .map(|e| (e.name, export_kind(e.kind), e.index)),
+                                );
+                            }
+                        }
+                    }
+                    component.section(&instances);
+                }
+                id if id == ComponentSectionId::Instance as u8 => {
+                    let mut instances = ComponentInstanceSection::new();
+                    for instance in ComponentInstanceSectionReader::new(data, 0)? {
+                        let (component_index, args) = match instance? {
+                            ComponentInstance::Instantiate {
+                                component_index,
+                                args,
+                            } => (
+                                Some(component_index),
+                                args.iter()
+                                    .map(|arg| (arg.name, arg.kind, arg.index))
+                                    .collect::<Vec<_>>(),
+                            ),
+                            ComponentInstance::FromExports(exports) => (
+                                None,
+                                exports
+                                    .iter()
+                                    .map(|e| (e.name.0, e.kind, e.index))
+                                    .collect(),
+                            ),
+                        };
+                        let args = args
+                            .into_iter()
+                            .map(|(name, kind, index)| {
+                                let index = removed.remap(Sort::of_kind(kind), index)?;
+                                Ok((name, component_export_kind(kind), index))
+                            })
+                            .collect::<Result<Vec<_>>>()?;
+                        match component_index {
+                            Some(index) => {
+                                instances.instantiate(removed.remap(Sort::Component, index)?, args)
+                            }
+                            None => instances.export_items(args),
+                        };
+                    }
+                    component.section(&instances);
+                }
+                id if id == ComponentSectionId::Alias as u8 => {
+                    let mut aliases = ComponentAliasSection::new();
+                    for alias in ComponentAliasSectionReader::new(data, 0)? {
+                        aliases.alias(match alias? {
+                            ComponentAlias::InstanceExport {
+                                kind,
+                                instance_index,
+                                name,
+                            } => Alias::InstanceExport {
+                                instance: removed.remap(Sort::Instance, instance_index)?,
+                                kind: component_export_kind(kind),
+                                name,
+                            },
+                            ComponentAlias::CoreInstanceExport {
+                                kind,
+                                instance_index,
+                                name,
+                            } => Alias::CoreInstanceExport {
+                                instance: instance_index,
+                                kind: export_kind(kind),
+                                name,
+                            },
+                            ComponentAlias::Outer { kind, count, index } => Alias::Outer {
+                                kind: match kind {
+                                    wasmparser::ComponentOuterAliasKind::CoreModule => {
+                                        ComponentOuterAliasKind::CoreModule
+                                    }
+                                    wasmparser::ComponentOuterAliasKind::CoreType => {
+                                        ComponentOuterAliasKind::CoreType
+                                    }
+                                    wasmparser::ComponentOuterAliasKind::Type => {
+                                        ComponentOuterAliasKind::Type
+                                    }
+                                    wasmparser::ComponentOuterAliasKind::Component => {
+                                        ComponentOuterAliasKind::Component
+                                    }
+                                },
+                                count,
+                                index,
+                            },
+                        });
+                    }
+                    component.section(&aliases);
+                }
+                id if id == ComponentSectionId::CanonicalFunction as u8 => {
+                    let mut funcs = CanonicalFunctionSection::new();
+                    for func in ComponentCanonicalSectionReader::new(data, 0)? {
+                        match func? {
+                            CanonicalFunction::Lift {
+                                core_func_index,
+                                type_index,
+                                options,
+                            } => {
+                                funcs.lift(
+                                    core_func_index,
+                                    type_index,
+                                    options.iter().map(canonical_option),
+                                );
+                            }
+                            CanonicalFunction::Lower {
+                                func_index,
+                                options,
+                            } => {
+                                funcs.lower(
+                                    removed.remap(Sort::Func, func_index)?,
+                                    options.iter().map(canonical_option),
+                                );
+                            }
+                            CanonicalFunction::ResourceNew { resource } => {
+                                funcs.resource_new(resource);
+                            }
+                            CanonicalFunction::ResourceDrop { resource } => {
+                                funcs.resource_drop(resource);
+                            }
+                            CanonicalFunction::ResourceRep { resource } => {
+                                funcs.resource_rep(resource);
+                            }
+                        }
+                    }
+                    component.section(&funcs);
+                }
+                id if id == ComponentSectionId::Start as u8 => {
+                    let start = BinaryReader::new(data).read::<ComponentStartFunction>()?;
+                    let args = start
+                        .arguments
+                        .iter()
+                        .map(|arg| removed.remap(Sort::Value, *arg))
+                        .collect::<Result<Vec<_>>>()?;
+                    component.section(&ComponentStartSection {
+                        function_index: removed.remap(Sort::Func, start.func_index)?,
+                        args,
+                        results: start.results,
+                    });
+                }
+                id if id == ComponentSectionId::Export as u8 => {
+                    let mut exports = ComponentExportSection::new();
+                    for export in ComponentExportSectionReader::new(data, 0)? {
+                        let export = export?;
+                        exports.export(
+                            export.name.0,
+                            component_export_kind(export.kind),
+                            removed.remap(Sort::of_kind(export.kind), export.index)?,
+                            export.ty.map(component_type_ref),
+                        );
+                    }
+                    component.section(&exports);
+                }
+                _ => {
+                    component.section(raw);
+                }
+            }
+        }
+        Ok(component)
+    }
+}
+
+impl ComponentMutator for RemoveComponentImportMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        let info = config.component_info();
+        let mut candidates = Vec::new();
+        for &section in info.imports.iter() {
+            let reader = ComponentImportSectionReader::new(info.raw_sections[section].data, 0)?;
+            for (i, import) in reader.into_iter().enumerate() {
+                let sort = Sort::of_type_ref(&import?.ty);
+                if sort != Sort::Type {
+                    candidates.push((section, i as u32, sort));
+                }
+            }
+        }
+        if candidates.is_empty() {
+            return Err(Error::no_mutations_applicable());
+        }
+
+        let start = config.rng().gen_range(0..candidates.len());
+        for &(section, skip_at, sort) in
+            candidates.iter().cycle().skip(start).take(candidates.len())
+        {
+            config.consume_fuel(1)?;
+
+            // The index of the import within its sort is the number of items
+            // of that sort defined before the import section plus the number
+            // of preceding imports in the same section of that sort.
+            let data = config.component_info().raw_sections[section].data;
+            let mut index = Self::count_before(config, section, sort)?;
+            for import in ComponentImportSectionReader::new(data, 0)?
+                .into_iter()
+                .take(skip_at as usize)
+            {
+                index += u32::from(Sort::of_type_ref(&import?.ty) == sort);
+            }
+
+            let removed = Removed { sort, index };
+            let component = match Self::remove(config, section, skip_at, removed) {
+                Ok(component) => component,
+                Err(e) if matches!(e.kind(), crate::ErrorKind::NoMutationsApplicable) => {
+                    log::trace!("component import {:?} is still in use", removed);
+                    continue;
+                }
+                Err(e) => return Err(e),
+            };
+            if is_valid_component(&component.clone().finish()) {
+                return Ok(Box::new(std::iter::once(Ok(component))));
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && config.component_info().imports_count > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RemoveComponentImportMutator;
+
+    #[test]
+    fn test_remove_component_import_mutator() {
+        crate::mutators::component::match_component_mutation(
+            r#"
+            (component
+                (type (func))
+                (type (func))
+                (import "a" (func (type 0)))
+                (import "b" (func (type 1)))
+                (core func (canon lower (func 1)))
+                (export "b" (func 1))
+            )
+            "#,
+            RemoveComponentImportMutator,
+            r#"
+            (component
+                (type (func))
+                (type (func))
+                (import "b" (func (type 1)))
+                (core func (canon lower (func 0)))
+                (export "b" (func 0))
+            )
+            "#,
+        );
+    }
+}
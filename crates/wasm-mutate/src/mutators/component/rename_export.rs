@@ -0,0 +1,123 @@
+//! Mutator that generates a random renaming of a component export.
+
+use super::{component_export_kind, component_type_ref, is_valid_component, ComponentMutator};
+use crate::{Error, Result, WasmMutate};
+use rand::Rng;
+use wasm_encoder::{Component, ComponentExportSection};
+use wasmparser::ComponentExportSectionReader;
+
+/// Generates a random renaming of pre-existing component exports.
+///
+/// Component export names must be kebab-case, so rather than mutating the raw
+/// bytes of the original name a fresh kebab-case name is generated.
+#[derive(Clone, Copy)]
+pub struct RenameComponentExportMutator {
+    /// The maximum length of the generated export name
+    pub max_name_size: usize,
+}
+
+impl RenameComponentExportMutator {
+    fn kebab_name(&self, config: &mut WasmMutate) -> Result<String> {
+        loop {
+            config.consume_fuel(1)?;
+            let len = config.rng().gen_range(1..=self.max_name_size.max(1));
+            let mut name = String::with_capacity(len);
+            for i in 0..len {
+                // Words are separated by single dashes and may neither start
+                // nor end the name.
+                if i > 0 && i + 1 < len && !name.ends_with('-') && config.rng().gen_ratio(1, 5) {
+                    name.push('-');
+                } else {
+                    name.push(config.rng().gen_range(b'a'..=b'z') as char);
+                }
+            }
+            if config.component_info().export_names.contains(&name) {
+                continue;
+            }
+            return Ok(name);
+        }
+    }
+}
+
+impl ComponentMutator for RenameComponentExportMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        let info = config.component_info();
+        let mut candidates = Vec::new();
+        for &section in info.exports.iter() {
+            let reader = ComponentExportSectionReader::new(info.raw_sections[section].data, 0)?;
+            candidates.extend((0..reader.count()).map(|i| (section, i)));
+        }
+
+        let start = config.rng().gen_range(0..candidates.len());
+        for &(section, rename_at) in candidates.iter().cycle().skip(start).take(candidates.len()) {
+            let new_name = self.kebab_name(config)?;
+
+            let info = config.component_info();
+            let reader = ComponentExportSectionReader::new(info.raw_sections[section].data, 0)?;
+            let mut exports = ComponentExportSection::new();
+            for (i, export) in reader.into_iter().enumerate() {
+                let export = export?;
+                let name = if i as u32 == rename_at {
+                    log::debug!("Renaming component export {:?} to {:?}", export, new_name);
+                    new_name.as_str()
+                } else {
+                    export.name.0
+                };
+                exports.export(
+                    name,
+                    component_export_kind(export.kind),
+                    export.index,
+                    export.ty.map(component_type_ref),
+                );
+            }
+
+            let component = info.replace_section(section, &exports);
+            if is_valid_component(&component.clone().finish()) {
+                return Ok(Box::new(std::iter::once(Ok(component))));
+            }
+        }
+
+        Err(Error::no_mutations_applicable())
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        !config.preserve_semantics && !config.reduce && config.component_info().exports_count > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RenameComponentExportMutator;
+    use crate::WasmMutate;
+
+    #[test]
+    fn test_rename_component_export_mutator() {
+        let mut config = WasmMutate::default();
+        config.match_component_mutation(
+            r#"
+            (component
+                (core module $m
+                    (func (export "f"))
+                )
+                (core instance $i (instantiate $m))
+                (func $f (canon lift (core func $i "f")))
+                (export "a" (func $f))
+            )
+            "#,
+            RenameComponentExportMutator { max_name_size: 1 },
+            r#"
+            (component
+                (core module $m
+                    (func (export "f"))
+                )
+                (core instance $i (instantiate $m))
+                (func $f (canon lift (core func $i "f")))
+                (export "z" (func $f))
+            )
+            "#,
+        );
+    }
+}
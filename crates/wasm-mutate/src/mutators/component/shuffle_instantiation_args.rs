@@ -0,0 +1,171 @@
+//! Mutator that reorders the arguments of an instantiation.
+
+use super::{component_export_kind, export_kind, ComponentMutator};
+use crate::{Error, Result, WasmMutate};
+use rand::{seq::SliceRandom, Rng};
+use wasm_encoder::{Component, ComponentInstanceSection, InstanceSection, ModuleArg};
+use wasmparser::{
+    ComponentInstance, ComponentInstanceSectionReader, Instance, InstanceSectionReader,
+    InstantiationArgKind,
+};
+
+/// Mutator that swaps two arguments of a random core module or component
+/// instantiation.
+///
+/// Instantiation arguments are matched up by name, so their order is
+/// irrelevant and this mutation always preserves semantics.
+#[derive(Clone, Copy)]
+pub struct ShuffleInstantiationArgsMutator;
+
+impl ComponentMutator for ShuffleInstantiationArgsMutator {
+    fn mutate<'a>(
+        &self,
+        config: &'a mut WasmMutate,
+    ) -> Result<Box<dyn Iterator<Item = Result<Component>> + 'a>> {
+        // Collect every instantiation with at least two arguments, remembering
+        // whether it lives in a core instance section or not.
+        let info = config.component_info();
+        let mut candidates = Vec::new();
+        for &section in info.core_instances.iter() {
+            let reader = InstanceSectionReader::new(info.raw_sections[section].data, 0)?;
+            for (i, instance) in reader.into_iter().enumerate() {
+                if let Instance::Instantiate { args, .. } = instance? {
+                    if args.len() > 1 {
+                        candidates.push((section, i, args.len()));
+                    }
+                }
+            }
+        }
+        for &section in info.instances.iter() {
+            let reader = ComponentInstanceSectionReader::new(info.raw_sections[section].data, 0)?;
+            for (i, instance) in reader.into_iter().enumerate() {
+                if let ComponentInstance::Instantiate { args, .. } = instance? {
+                    if args.len() > 1 {
+                        candidates.push((section, i, args.len()));
+                    }
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            return Err(Error::no_mutations_applicable());
+        }
+
+        config.consume_fuel(1)?;
+        let (section, instance_index, nargs) = *candidates.choose(config.rng()).unwrap();
+        let a = config.rng().gen_range(0..nargs);
+        let b = (a + config.rng().gen_range(1..nargs)) % nargs;
+        log::trace!("Swapping instantiation arguments {} and {}", a, b);
+
+        let info = config.component_info();
+        let data = info.raw_sections[section].data;
+        let component = if info.core_instances.contains(&section) {
+            let mut instances = InstanceSection::new();
+            for (i, instance) in InstanceSectionReader::new(data, 0)?.into_iter().enumerate() {
+                match instance? {
+                    Instance::Instantiate { module_index, args } => {
+                        let mut args = args
+                            .iter()
+                            .map(|arg| match arg.kind {
+                                InstantiationArgKind::Instance => {
+                                    (arg.name, ModuleArg::Instance(arg.index))
+                                }
+                            })
+                            .collect::<Vec<_>>();
+                        if i == instance_index {
+                            args.swap(a, b);
+                        }
+                        instances.instantiate(module_index, args);
+                    }
+                    Instance::FromExports(exports) => {
+                        instances.export_items(
+                            exports
+                                .iter()
+                                .map(|e| (e.name, export_kind(e.kind), e.index))
+                                .collect::<Vec<_>>(),
+                        );
+                    }
+                }
+            }
+            info.replace_section(section, &instances)
+        } else {
+            let mut instances = ComponentInstanceSection::new();
+            for (i, instance) in ComponentInstanceSectionReader::new(data, 0)?
+                .into_iter()
+                .enumerate()
+            {
+                match instance? {
+                    ComponentInstance::Instantiate {
+                        component_index,
+                        args,
+                    } => {
+                        let mut args = args
+                            .iter()
+                            .map(|arg| (arg.name, component_export_kind(arg.kind), arg.index))
+                            .collect::<Vec<_>>();
+                        if i == instance_index {
+                            args.swap(a, b);
+                        }
+                        instances.instantiate(component_index, args);
+                    }
+                    ComponentInstance::FromExports(exports) => {
+                        instances.export_items(
+                            exports
+                                .iter()
+                                .map(|e| (e.name.0, component_export_kind(e.kind), e.index))
+                                .collect::<Vec<_>>(),
+                        );
+                    }
+                }
+            }
+            info.replace_section(section, &instances)
+        };
+
+        Ok(Box::new(std::iter::once(Ok(component))))
+    }
+
+    fn can_mutate(&self, config: &WasmMutate) -> bool {
+        let info = config.component_info();
+        !config.reduce && (!info.core_instances.is_empty() || !info.instances.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ShuffleInstantiationArgsMutator;
+
+    #[test]
+    fn test_shuffle_instantiation_args_mutator() {
+        crate::mutators::component::match_component_mutation(
+            r#"
+            (component
+                (core module $a (func (export "f")))
+                (core module $b
+                    (import "x" "f" (func))
+                    (import "y" "f" (func))
+                )
+                (core instance $a (instantiate $a))
+                (core instance (instantiate $b
+                    (with "x" (instance $a))
+                    (with "y" (instance $a))
+                ))
+            )
+            "#,
+            ShuffleInstantiationArgsMutator,
+            r#"
+            (component
+                (core module $a (func (export "f")))
+                (core module $b
+                    (import "x" "f" (func))
+                    (import "y" "f" (func))
+                )
+                (core instance $a (instantiate $a))
+                (core instance (instantiate $b
+                    (with "y" (instance $a))
+                    (with "x" (instance $a))
+                ))
+            )
+            "#,
+        );
+    }
+}
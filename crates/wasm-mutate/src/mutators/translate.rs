@@ -210,6 +210,9 @@ pub fn heapty(t: &mut dyn Translator, ty: &wasmparser::HeapType) -> Result<HeapT
         wasmparser::HeapType::Struct => Ok(HeapType::Struct),
         wasmparser::HeapType::Array => Ok(HeapType::Array),
         wasmparser::HeapType::I31 => Ok(HeapType::I31),
+        wasmparser::HeapType::Cont => Ok(HeapType::Cont),
+        wasmparser::HeapType::NoCont => Ok(HeapType::NoCont),
+        wasmparser::HeapType::Exn => Ok(HeapType::Exn),
         wasmparser::HeapType::Concrete(i) => Ok(HeapType::Concrete(
             t.remap(Item::Type, i.as_module_index().unwrap())?,
         )),
@@ -343,6 +346,19 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
         (map $arg:ident dst_mem) => (t.remap(Item::Memory, *$arg)?);
         (map $arg:ident data_index) => (t.remap(Item::Data, *$arg)?);
         (map $arg:ident elem_index) => (t.remap(Item::Element, *$arg)?);
+        (map $arg:ident struct_type_index) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident array_type_index) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident array_type_index_dst) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident array_type_index_src) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident array_data_index) => (t.remap(Item::Data, *$arg)?);
+        (map $arg:ident array_elem_index) => (t.remap(Item::Element, *$arg)?);
+        (map $arg:ident cont_type_index) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident argument_index) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident result_index) => (t.remap(Item::Type, *$arg)?);
+        (map $arg:ident field_index) => (*$arg);
+        (map $arg:ident array_size) => (*$arg);
+        (map $arg:ident from_ref_type) => (t.translate_refty($arg)?);
+        (map $arg:ident to_ref_type) => (t.translate_refty($arg)?);
         (map $arg:ident blockty) => (t.translate_block_type($arg)?);
         (map $arg:ident relative_depth) => (*$arg);
         (map $arg:ident targets) => ((
@@ -352,6 +368,28 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
                 .into(),
             $arg.default(),
         ));
+        (map $arg:ident try_table) => ((
+            t.translate_block_type(&$arg.ty)?,
+            $arg
+                .catches
+                .iter()
+                .map(|catch| {
+                    Ok(match *catch {
+                        wasmparser::Catch::One { tag, label } => Catch::One {
+                            tag: t.remap(Item::Tag, tag)?,
+                            label,
+                        },
+                        wasmparser::Catch::OneRef { tag, label } => Catch::OneRef {
+                            tag: t.remap(Item::Tag, tag)?,
+                            label,
+                        },
+                        wasmparser::Catch::All { label } => Catch::All { label },
+                        wasmparser::Catch::AllRef { label } => Catch::AllRef { label },
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into(),
+        ));
         (map $arg:ident table_byte) => (());
         (map $arg:ident mem_byte) => (());
         (map $arg:ident flags) => (());
@@ -362,6 +400,24 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
         (map $arg:ident value) => ($arg);
         (map $arg:ident lane) => (*$arg);
         (map $arg:ident lanes) => (*$arg);
+        (map $arg:ident resume_table) => ((
+            $arg
+                .handlers
+                .iter()
+                .map(|handle| {
+                    Ok(match *handle {
+                        wasmparser::Handle::OnLabel { tag, label } => Handle::OnLabel {
+                            tag: t.remap(Item::Tag, tag)?,
+                            label,
+                        },
+                        wasmparser::Handle::OnSwitch { tag } => Handle::OnSwitch {
+                            tag: t.remap(Item::Tag, tag)?,
+                        },
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?
+                .into()
+        ));
 
         // This case takes the arguments of a wasmparser instruction and creates
         // a wasm-encoder instruction. There are a few special cases for where
@@ -369,11 +425,28 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
         // wasm-encoder.
         (build $op:ident) => (I::$op);
         (build BrTable $arg:ident) => (I::BrTable($arg.0, $arg.1));
+        (build TryTable $arg:ident) => (I::TryTable($arg.0, $arg.1));
         (build I32Const $arg:ident) => (I::I32Const(*$arg));
         (build I64Const $arg:ident) => (I::I64Const(*$arg));
         (build F32Const $arg:ident) => (I::F32Const(f32::from_bits($arg.bits())));
         (build F64Const $arg:ident) => (I::F64Const(f64::from_bits($arg.bits())));
         (build V128Const $arg:ident) => (I::V128Const($arg.i128()));
+        (build RefTestNonNull $hty:ident) => (I::RefTest(RefType {
+            nullable: false,
+            heap_type: $hty,
+        }));
+        (build RefTestNullable $hty:ident) => (I::RefTest(RefType {
+            nullable: true,
+            heap_type: $hty,
+        }));
+        (build RefCastNonNull $hty:ident) => (I::RefCast(RefType {
+            nullable: false,
+            heap_type: $hty,
+        }));
+        (build RefCastNullable $hty:ident) => (I::RefCast(RefType {
+            nullable: true,
+            heap_type: $hty,
+        }));
         (build $op:ident $arg:ident) => (I::$op($arg));
         (build CallIndirect $ty:ident $table:ident $_:ident) => (I::CallIndirect {
             ty: $ty,
@@ -385,6 +458,16 @@ pub fn op(t: &mut dyn Translator, op: &Operator<'_>) -> Result<Instruction<'stat
         });
         (build MemoryGrow $mem:ident $_:ident) => (I::MemoryGrow($mem));
         (build MemorySize $mem:ident $_:ident) => (I::MemorySize($mem));
+        (build StructGet $ty:ident $field:ident) => (I::StructGet($ty, $field));
+        (build StructGetS $ty:ident $field:ident) => (I::StructGetS($ty, $field));
+        (build StructGetU $ty:ident $field:ident) => (I::StructGetU($ty, $field));
+        (build StructSet $ty:ident $field:ident) => (I::StructSet($ty, $field));
+        (build ArrayNewFixed $ty:ident $size:ident) => (I::ArrayNewFixed($ty, $size));
+        (build ArrayNewData $ty:ident $data:ident) => (I::ArrayNewData($ty, $data));
+        (build ArrayNewElem $ty:ident $elem:ident) => (I::ArrayNewElem($ty, $elem));
+        (build ArrayCopy $dst:ident $src:ident) => (I::ArrayCopy($dst, $src));
+        (build ArrayInitData $ty:ident $data:ident) => (I::ArrayInitData($ty, $data));
+        (build ArrayInitElem $ty:ident $elem:ident) => (I::ArrayInitElem($ty, $elem));
         (build $op:ident $($arg:ident)*) => (I::$op { $($arg),* });
     }
 
@@ -68,3 +68,169 @@ fn integration_test() {
         elapsed.subsec_millis()
     );
 }
+
+#[test]
+fn component_integration_test() {
+    let _ = env_logger::try_init();
+
+    let wat = r#"
+        (component
+            (import "log" (func $log (param "msg" string)))
+            (core module $libc
+                (memory (export "memory") 1)
+                (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+                    unreachable
+                )
+            )
+            (core instance $libc (instantiate $libc))
+            (core func $log_lower (canon lower (func $log)
+                (memory $libc "memory")
+                (realloc (func $libc "realloc"))
+            ))
+            (core module $main
+                (import "host" "log" (func (param i32 i32)))
+                (import "libc" "memory" (memory 1))
+                (func (export "run") (param i32) (result i32)
+                    local.get 0
+                    i32.const 1
+                    i32.add
+                )
+            )
+            (core instance $host (export "log" (func $log_lower)))
+            (core instance $main (instantiate $main
+                (with "host" (instance $host))
+                (with "libc" (instance $libc))
+            ))
+            (func $run (param "x" u32) (result u32) (canon lift (core func $main "run")))
+            (export "run" (func $run))
+            (export "run2" (func $run))
+        )
+    "#;
+    let original = &wat::parse_str(wat).unwrap();
+
+    let features = wasmparser::WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    };
+
+    let mut count = 0;
+    for seed in 0..100 {
+        let mut mutator = WasmMutate::default();
+        mutator.fuel(1000);
+        mutator.seed(seed);
+
+        let it = match mutator.run(original) {
+            Ok(it) => it,
+            Err(e) => match e.kind() {
+                ErrorKind::NoMutationsApplicable => continue,
+                ErrorKind::OutOfFuel => break,
+                _ => panic!("{}", e),
+            },
+        };
+        for mutated in it.take(10) {
+            let mutated = mutated.unwrap();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &mutated);
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn branch_hints_integration_test() {
+    let _ = env_logger::try_init();
+
+    let wat = r#"
+        (module
+            (func (export "f") (param i32) (result i32)
+                local.get 0
+                (@metadata.code.branch_hint "\01")
+                if
+                    i32.const 98
+                    drop
+                end
+                i32.const 42
+            )
+            (func (export "g") (param i32) (result i32)
+                local.get 0
+                (@metadata.code.branch_hint "\00")
+                if (result i32)
+                    i32.const 1
+                else
+                    i32.const 2
+                end
+            )
+        )
+    "#;
+    let original = &wat::parse_str(wat).unwrap();
+
+    // Returns the number of imported functions and the function bodies of
+    // `wasm`, along with the indices of the functions which have branch hints.
+    fn bodies_and_hints(wasm: &[u8]) -> (u32, Vec<&[u8]>, Vec<u32>) {
+        let mut imports = 0;
+        let mut bodies = Vec::new();
+        let mut hints = Vec::new();
+        for payload in wasmparser::Parser::new(0).parse_all(wasm) {
+            match payload.unwrap() {
+                wasmparser::Payload::ImportSection(reader) => {
+                    for import in reader {
+                        if let wasmparser::TypeRef::Func(_) = import.unwrap().ty {
+                            imports += 1;
+                        }
+                    }
+                }
+                wasmparser::Payload::CodeSectionEntry(body) => bodies.push(&wasm[body.range()]),
+                wasmparser::Payload::CustomSection(c)
+                    if c.name() == "metadata.code.branch_hint" =>
+                {
+                    let reader =
+                        wasmparser::BranchHintSectionReader::new(c.data(), c.data_offset())
+                            .unwrap();
+                    for func in reader {
+                        hints.push(func.unwrap().func);
+                    }
+                }
+                _ => {}
+            }
+        }
+        (imports, bodies, hints)
+    }
+    let (_, original_bodies, original_hints) = bodies_and_hints(original);
+    assert_eq!(original_hints, [0, 1]);
+
+    let mut count = 0;
+    let mut partially_kept = 0;
+    for seed in 0..100 {
+        let mut mutator = WasmMutate::default();
+        mutator.fuel(1000);
+        mutator.seed(seed);
+
+        let it = match mutator.run(original) {
+            Ok(it) => it,
+            Err(e) => match e.kind() {
+                ErrorKind::NoMutationsApplicable => continue,
+                ErrorKind::OutOfFuel => break,
+                _ => panic!("{}", e),
+            },
+        };
+        for mutated in it.take(10) {
+            let mutated = mutated.unwrap();
+            validate(&mut Validator::new(), &mutated);
+
+            // Branch hints are only retained for functions whose bodies are
+            // unchanged.
+            let (imports, bodies, hints) = bodies_and_hints(&mutated);
+            for func in hints.iter() {
+                let i = (func - imports) as usize;
+                assert_eq!(bodies[i], original_bodies[i]);
+            }
+            if !hints.is_empty() && bodies != original_bodies {
+                partially_kept += 1;
+            }
+            count += 1;
+        }
+    }
+    assert!(count > 0);
+    assert!(partially_kept > 0);
+}
@@ -0,0 +1,164 @@
+//! Structural shrinking of Wasm components.
+//!
+//! `wasm-mutate` can only apply a handful of coarse mutations to a component
+//! as a whole, so before handing a component to the main search loop it is
+//! first collapsed into any of its nested components which is interesting on
+//! its own. After that the nested modules and components inside of it are
+//! shrunk individually. Each nested item is shrunk with its own `ShrinkRun`
+//! whose predicate splices the candidate back into the enclosing component
+//! before consulting the user's predicate.
+
+use crate::{IsInteresting, ShrinkRun, WasmShrink};
+use anyhow::Result;
+use rand::Rng;
+use std::ops::Range;
+use wasm_encoder::{ComponentSectionId, RawSection};
+use wasmparser::{Chunk, Parser, Payload};
+
+/// A top-level section of a component.
+struct Section {
+    id: u8,
+    range: Range<usize>,
+}
+
+/// Returns the top-level sections of the given component.
+fn sections(component: &[u8]) -> Result<Vec<Section>> {
+    let mut parser = Parser::new(0);
+    let mut sections = Vec::new();
+    let mut offset = 0;
+    loop {
+        let (payload, consumed) = match parser.parse(&component[offset..], true)? {
+            Chunk::NeedMoreData(_) => anyhow::bail!("unexpected end of component"),
+            Chunk::Parsed { payload, consumed } => (payload, consumed),
+        };
+        offset += consumed;
+        match &payload {
+            Payload::End(_) => break,
+            Payload::ModuleSection { range, .. } | Payload::ComponentSection { range, .. } => {
+                // The parser has already moved past the section header, so
+                // skip over the nested item's contents.
+                offset = range.end;
+            }
+            _ => {}
+        }
+        if let Some((id, range)) = payload.as_section() {
+            sections.push(Section { id, range });
+        }
+    }
+    Ok(sections)
+}
+
+/// Re-encodes `component` with the contents of its `i`th section replaced by
+/// `data`.
+fn replace_section(component: &[u8], sections: &[Section], i: usize, data: &[u8]) -> Vec<u8> {
+    let mut new = wasm_encoder::Component::new();
+    for (j, section) in sections.iter().enumerate() {
+        new.section(&RawSection {
+            id: section.id,
+            data: if i == j {
+                data
+            } else {
+                &component[section.range.clone()]
+            },
+        });
+    }
+    new.finish()
+}
+
+impl ShrinkRun {
+    /// Replaces `current` with one of its nested components, for as long as
+    /// there's a nested component which is interesting by itself.
+    ///
+    /// This removes layers of components which merely wrap the interesting
+    /// part of the test case.
+    pub(crate) fn collapse_nested<P, I>(
+        &mut self,
+        current: &mut Vec<u8>,
+        predicate: &mut P,
+    ) -> Result<()>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        'outer: loop {
+            for section in sections(current)? {
+                if section.id != ComponentSectionId::Component as u8 {
+                    continue;
+                }
+                let nested = &current[section.range];
+
+                // Nested components may refer to their enclosing component
+                // with outer aliases, in which case they aren't valid on their
+                // own.
+                if !self.nested && self.validate_wasm(nested).is_err() {
+                    continue;
+                }
+                if !self.already_tested.insert(blake3::hash(nested)) {
+                    continue;
+                }
+                if predicate(nested)?.is_interesting() {
+                    log::debug!("Collapsed component into a nested component");
+                    let nested = nested.to_vec();
+                    self.on_new_interesting(current, nested)?;
+                    continue 'outer;
+                }
+            }
+            return Ok(());
+        }
+    }
+
+    /// Shrinks each nested module and component of `current` in turn.
+    ///
+    /// Nested items may be shrunk all the way down to empty modules or
+    /// components, which typically happens when the item is unused.
+    pub(crate) fn shrink_nested<P, I>(
+        &mut self,
+        current: &mut Vec<u8>,
+        predicate: &mut P,
+    ) -> Result<()>
+    where
+        P: FnMut(&[u8]) -> Result<I>,
+        I: IsInteresting,
+    {
+        let mut i = 0;
+        loop {
+            let sections = sections(current)?;
+            let Some(section) = sections.get(i) else {
+                break;
+            };
+            if section.id != ComponentSectionId::CoreModule as u8
+                && section.id != ComponentSectionId::Component as u8
+            {
+                i += 1;
+                continue;
+            }
+            log::debug!("Shrinking nested item in section #{}", i);
+
+            let base = current.clone();
+            let nested = base[section.range.clone()].to_vec();
+            let shrink = WasmShrink {
+                attempts: self.shrink.attempts,
+                allow_empty: true,
+                seed: self.rng.gen(),
+                on_new_smallest: None,
+            };
+            let mut nested_predicate = |candidate: &[u8]| -> Result<bool> {
+                let wasm = replace_section(&base, &sections, i, candidate);
+                if let Err(e) = self.validate_wasm(&wasm) {
+                    log::trace!("Nested candidate yields an invalid component: {}", e);
+                    return Ok(false);
+                }
+                Ok(predicate(&wasm)?.is_interesting())
+            };
+            let nested_predicate: &mut dyn FnMut(&[u8]) -> Result<bool> = &mut nested_predicate;
+            let info = ShrinkRun::new_nested(shrink, nested).run(nested_predicate)?;
+
+            let wasm = replace_section(&base, &sections, i, &info.output);
+            if wasm.len() < current.len() {
+                self.on_new_interesting(current, wasm)?;
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+}
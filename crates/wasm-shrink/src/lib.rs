@@ -1,7 +1,8 @@
 //! Shrink a Wasm file while maintaining a property of interest (such as
 //! triggering a compiler bug).
 //!
-//! See the [`WasmShrink`][WasmShrink] type for details.
+//! Both core Wasm modules and components can be shrunk. See the
+//! [`WasmShrink`][WasmShrink] type for details.
 
 use std::collections::HashSet;
 
@@ -9,20 +10,46 @@ use anyhow::{Context, Result};
 use rand::{rngs::SmallRng, Rng, SeedableRng};
 use wasm_mutate::WasmMutate;
 
+mod component;
+
 #[rustfmt::skip]
-static EMPTY_WASM: &'static [u8] = &[
+static EMPTY_WASM: &[u8] = &[
     // Magic.
     0x00, b'a', b's', b'm',
     // Version.
     0x01, 0x00, 0x00, 0x00,
 ];
 
+#[rustfmt::skip]
+static EMPTY_COMPONENT: &[u8] = &[
+    // Magic.
+    0x00, b'a', b's', b'm',
+    // Version and layer.
+    0x0d, 0x00, 0x01, 0x00,
+];
+
+/// Returns the empty module or the empty component, depending on what kind of
+/// binary `wasm` is.
+fn empty_wasm(wasm: &[u8]) -> &'static [u8] {
+    if wasmparser::Parser::is_component(wasm) {
+        EMPTY_COMPONENT
+    } else {
+        EMPTY_WASM
+    }
+}
+
 #[cfg_attr(
     not(feature = "clap"),
     doc = r###"
 Shrink a Wasm file while maintaining a property of interest (such as
 triggering a compiler bug).
 
+The input may be either a core Wasm module or a component. Components are
+first shrunk structurally by collapsing them into a nested component when
+that component is interesting by itself, and by shrinking each of their
+nested modules and components in turn, after which their imports, exports,
+and canonical options are reduced along with everything else.
+
 # Example
 
 ```
@@ -165,6 +192,10 @@ struct ShrinkRun {
     // The count of how many times we've attempted to shrink our current test
     // case smaller than `best`.
     attempt: u32,
+
+    // Whether this run shrinks a module or component nested within another
+    // component, in which case the input can't be validated on its own.
+    nested: bool,
 }
 
 impl ShrinkRun {
@@ -179,12 +210,21 @@ impl ShrinkRun {
             best,
             already_tested: HashSet::new(),
             attempt: 0,
+            nested: false,
+        }
+    }
+
+    fn new_nested(shrink: WasmShrink, input: Vec<u8>) -> ShrinkRun {
+        ShrinkRun {
+            nested: true,
+            ..ShrinkRun::new(shrink, input)
         }
     }
 
     fn on_new_best(&mut self, new_best: Vec<u8>) -> Result<()> {
         debug_assert!(
-            new_best.len() < self.best.len() || (new_best == EMPTY_WASM && self.best == EMPTY_WASM)
+            new_best.len() < self.best.len()
+                || (new_best == empty_wasm(&new_best) && self.best == new_best)
         );
         log::info!("New smallest Wasm found: {} bytes", new_best.len());
         if let Some(f) = self.shrink.on_new_smallest.as_mut() {
@@ -232,10 +272,11 @@ impl ShrinkRun {
             mutable_global: true,
             saturating_float_to_int: true,
             sign_extension: true,
-            component_model: false,
+            component_model: true,
             function_references: false,
             gc: false,
             component_model_values: false,
+            stack_switching: false,
 
             floats: true,
             memory_control: true,
@@ -271,9 +312,13 @@ impl ShrinkRun {
         // [0]: https://en.wikipedia.org/wiki/Markov_chain_Monte_Carlo
         let mut current = self.best.clone();
 
-        // Check prerequisites for the input Wasm.
-        self.validate_wasm(&current)
-            .context("The input is not valid Wasm.")?;
+        // Check prerequisites for the input Wasm. Nested modules and
+        // components are only valid in the context of their enclosing
+        // component, which our predicate checks instead.
+        if !self.nested {
+            self.validate_wasm(&current)
+                .context("The input is not valid Wasm.")?;
+        }
 
         // First double check that the input Wasm passes the predicate.
         //
@@ -294,10 +339,11 @@ impl ShrinkRun {
         // considers the empty module interesting, and we might as well check
         // for it eagerly, rather than make the user wait forever until we
         // finally to reduce the whole Wasm module to nothing.
-        let result = predicate(EMPTY_WASM)?;
+        let empty = empty_wasm(&current);
+        let result = predicate(empty)?;
         if result.is_interesting() {
             if self.shrink.allow_empty {
-                self.on_new_best(EMPTY_WASM.to_vec())?;
+                self.on_new_best(empty.to_vec())?;
                 return Ok(self.finish());
             } else {
                 anyhow::bail!(
@@ -311,6 +357,15 @@ impl ShrinkRun {
             }
         }
 
+        // Components are first shrunk structurally, by collapsing them into
+        // their nested components and then shrinking one nested module or
+        // component at a time, since `wasm-mutate` can only make coarse
+        // changes to a component as a whole.
+        if wasmparser::Parser::is_component(&current) {
+            self.collapse_nested(&mut current, &mut predicate)?;
+            self.shrink_nested(&mut current, &mut predicate)?;
+        }
+
         // Now we perform the main search. Keep trying to find smaller and
         // interesting variants of the current smallest interesting Wasm file
         // until we run out of attempts and get stuck.
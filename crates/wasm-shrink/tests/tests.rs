@@ -56,3 +56,103 @@ fn smoke_test() -> Result<()> {
     wasmparser::validate(&info.output)?;
     Ok(())
 }
+
+fn component() -> Vec<u8> {
+    let _ = env_logger::try_init();
+    wat::parse_str(
+        r#"
+            (component
+                (core module $unused
+                    (memory 1)
+                    (func (export "g") (result i32)
+                        i32.const 1
+                        i32.const 2
+                        i32.add
+                    )
+                )
+                (core module $m
+                    (func $a (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        i32.add
+                    )
+                    (func (export "f") (param i32 i32) (result i32)
+                        local.get 0
+                        local.get 1
+                        call $a
+                    )
+                )
+                (core instance $i (instantiate $m))
+                (func (export "f") (param "a" s32) (param "b" s32) (result s32)
+                    (canon lift (core func $i "f"))
+                )
+                (component $c
+                    (core module (func (export "h")))
+                )
+                (instance (instantiate $c))
+            )
+        "#,
+    )
+    .unwrap()
+}
+
+#[test]
+fn shrink_component_to_empty_is_error() -> Result<()> {
+    let result = WasmShrink::default().run(component(), |_| Ok(true));
+    assert!(result.is_err());
+    let err_msg = result.err().unwrap().to_string();
+    assert!(err_msg.contains("empty Wasm"));
+    Ok(())
+}
+
+#[test]
+fn shrink_component() -> Result<()> {
+    let info = WasmShrink::default()
+        .attempts(100)
+        .run(component(), |wasm| {
+            let wat = wasmprinter::print_bytes(wasm)?;
+            Ok(wat.contains("call"))
+        })?;
+
+    assert!(info.input_size > info.output_size);
+    assert!(wasmparser::Parser::is_component(&info.output));
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert!(wat.contains("call"));
+    assert!(!wat.contains("i32.const"));
+
+    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+        component_model: true,
+        ..Default::default()
+    });
+    validator.validate_all(&info.output)?;
+    Ok(())
+}
+
+#[test]
+fn shrink_component_collapses_nested_component() -> Result<()> {
+    let _ = env_logger::try_init();
+    let wasm = wat::parse_str(
+        r#"
+            (component
+                (core module (func (export "g")))
+                (component $c
+                    (core module
+                        (func $a)
+                        (func (export "f") call $a)
+                    )
+                )
+                (instance (instantiate $c))
+            )
+        "#,
+    )?;
+    let info = WasmShrink::default().attempts(100).run(wasm, |wasm| {
+        let wat = wasmprinter::print_bytes(wasm)?;
+        Ok(wasmparser::Parser::is_component(wasm) && wat.contains("call"))
+    })?;
+
+    let wat = wasmprinter::print_bytes(&info.output)?;
+    assert_eq!(wat.matches("(component").count(), 1);
+    assert!(!wat.contains("instance"));
+    Ok(())
+}
@@ -748,9 +748,7 @@ impl ComponentBuilder {
             });
             let ty_idx = u32::try_from(types.len()).unwrap();
             types.push(realloc_ty.clone());
-            defs.push(ModuleTypeDef::TypeDef(crate::core::Type::Func(
-                realloc_ty.clone(),
-            )));
+            defs.push(ModuleTypeDef::TypeDef(realloc_ty.clone()));
             defs.push(ModuleTypeDef::Export(
                 "canonical_abi_realloc".into(),
                 crate::core::EntityType::Func(ty_idx, realloc_ty),
@@ -771,9 +769,7 @@ impl ComponentBuilder {
             });
             let ty_idx = u32::try_from(types.len()).unwrap();
             types.push(free_ty.clone());
-            defs.push(ModuleTypeDef::TypeDef(crate::core::Type::Func(
-                free_ty.clone(),
-            )));
+            defs.push(ModuleTypeDef::TypeDef(free_ty.clone()));
             defs.push(ModuleTypeDef::Export(
                 "canonical_abi_free".into(),
                 crate::core::EntityType::Func(ty_idx, free_ty),
@@ -864,7 +860,7 @@ impl ComponentBuilder {
                         },
                     )?;
                     types.push(ty.clone());
-                    defs.push(ModuleTypeDef::TypeDef(crate::core::Type::Func(ty)));
+                    defs.push(ModuleTypeDef::TypeDef(ty));
                 }
 
                 // Alias
@@ -1765,7 +1761,7 @@ impl ComponentBuilder {
             u,
             crate::core::DuplicateImportsBehavior::Disallowed,
         )?;
-        self.push_section(Section::CoreModule(module));
+        self.push_section(Section::CoreModule(Box::new(module)));
         self.total_modules += 1;
         Ok(Step::StillBuilding)
     }
@@ -1890,7 +1886,7 @@ fn inverse_scalar_canonical_abi_for(
 #[derive(Debug)]
 enum Section {
     Custom(CustomSection),
-    CoreModule(crate::Module),
+    CoreModule(Box<crate::Module>),
     CoreInstance(CoreInstanceSection),
     CoreType(CoreTypeSection),
     Component(Component),
@@ -1938,7 +1934,7 @@ struct ModuleType {
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 enum ModuleTypeDef {
-    TypeDef(crate::core::Type),
+    TypeDef(Rc<crate::core::FuncType>),
     Import(crate::core::Import),
     OuterAlias {
         count: u32,
@@ -355,6 +355,26 @@ pub trait Config: 'static + std::fmt::Debug {
         false
     }
 
+    /// Determines whether the typed function references proposal is enabled
+    /// for generating types and instructions.
+    ///
+    /// Defaults to `false`.
+    fn function_references_enabled(&self) -> bool {
+        false
+    }
+
+    /// Determines whether the GC proposal is enabled for generating types and
+    /// instructions.
+    ///
+    /// The GC proposal builds on top of typed function references, so this
+    /// also requires [`Config::function_references_enabled`] and
+    /// [`Config::reference_types_enabled`] to be `true`.
+    ///
+    /// Defaults to `false`.
+    fn gc_enabled(&self) -> bool {
+        false
+    }
+
     /// Determines whether the multi-value results are enabled.
     ///
     /// Defaults to `true`.
@@ -506,6 +526,8 @@ pub struct SwarmConfig {
     pub disallow_traps: bool,
     pub exceptions_enabled: bool,
     pub export_everything: bool,
+    pub function_references_enabled: bool,
+    pub gc_enabled: bool,
     pub max_aliases: usize,
     pub max_components: usize,
     pub max_data_segments: usize,
@@ -620,6 +642,8 @@ impl<'a> Arbitrary<'a> for SwarmConfig {
             allow_start_export: true,
             relaxed_simd_enabled: false,
             exceptions_enabled: false,
+            function_references_enabled: false,
+            gc_enabled: false,
             memory64_enabled: false,
             max_type_size: 1000,
             canonicalize_nans: false,
@@ -779,6 +803,14 @@ impl Config for SwarmConfig {
         self.exceptions_enabled
     }
 
+    fn function_references_enabled(&self) -> bool {
+        self.function_references_enabled
+    }
+
+    fn gc_enabled(&self) -> bool {
+        self.gc_enabled
+    }
+
     fn multi_value_enabled(&self) -> bool {
         self.multi_value_enabled
     }
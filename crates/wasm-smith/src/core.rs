@@ -14,7 +14,10 @@ use std::marker;
 use std::ops::Range;
 use std::rc::Rc;
 use std::str::{self, FromStr};
-use wasm_encoder::{BlockType, ConstExpr, ExportKind, HeapType, RefType, ValType};
+use wasm_encoder::{
+    ArrayType, BlockType, ConstExpr, ExportKind, FieldType, HeapType, RefType, StorageType,
+    StructType, ValType,
+};
 pub(crate) use wasm_encoder::{GlobalType, MemoryType, TableType};
 
 // NB: these constants are used to control the rate at which various events
@@ -26,6 +29,7 @@ pub(crate) use wasm_encoder::{GlobalType, MemoryType, TableType};
 const CHANCE_OFFSET_INBOUNDS: usize = 10; // bigger = less traps
 const CHANCE_SEGMENT_ON_EMPTY: usize = 10; // bigger = less traps
 const PCT_INBOUNDS: f64 = 0.995; // bigger = less traps
+const MAX_REC_GROUP_SIZE: usize = 10;
 
 type Instruction = wasm_encoder::Instruction<'static>;
 
@@ -49,7 +53,11 @@ pub struct Module {
 
     /// All types locally defined in this module (available in the type index
     /// space).
-    types: Vec<Type>,
+    types: Vec<SubType>,
+
+    /// The ranges of `types` which make up each recursion group. Without the
+    /// GC proposal every type is in its own implicit recursion group.
+    rec_groups: Vec<Range<usize>>,
 
     /// Whether we should encode a types section, even if `self.types` is empty.
     should_encode_types: bool,
@@ -66,6 +74,12 @@ pub struct Module {
     /// Indices within `types` that are function types.
     func_types: Vec<u32>,
 
+    /// Indices within `types` that are array types.
+    array_types: Vec<u32>,
+
+    /// Indices within `types` that are struct types.
+    struct_types: Vec<u32>,
+
     /// Number of imported items into this module.
     num_imports: usize,
 
@@ -178,10 +192,13 @@ impl Module {
             duplicate_imports_behavior,
             valtypes: Vec::new(),
             types: Vec::new(),
+            rec_groups: Vec::new(),
             should_encode_types: false,
             imports: Vec::new(),
             should_encode_imports: false,
             func_types: Vec::new(),
+            array_types: Vec::new(),
+            struct_types: Vec::new(),
             num_imports: 0,
             num_defined_tags: 0,
             num_defined_funcs: 0,
@@ -237,9 +254,23 @@ impl<'a> Arbitrary<'a> for MaybeInvalidModule {
     }
 }
 
+/// A type in the type section, along with its subtyping information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct SubType {
+    /// Whether other types may declare this type as their supertype.
+    pub(crate) is_final: bool,
+    /// The index of this type's declared supertype, if any.
+    pub(crate) supertype: Option<u32>,
+    /// The definition of this type.
+    pub(crate) composite_type: CompositeType,
+}
+
+/// The definition of a function, array, or struct type.
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
-pub(crate) enum Type {
+pub(crate) enum CompositeType {
     Func(Rc<FuncType>),
+    Array(ArrayType),
+    Struct(StructType),
 }
 
 /// A function signature.
@@ -384,36 +415,171 @@ impl Module {
         // for the available imports before generating arbitrary types here.
         let min = self.config.min_types().saturating_sub(self.types.len());
         let max = self.config.max_types().saturating_sub(self.types.len());
+        let max_types = self.config.max_types();
         arbitrary_loop(u, min, max, |u| {
-            let ty = self.arbitrary_type(u)?;
-            self.record_type(&ty);
-            self.types.push(ty);
+            if self.types.len() >= max_types {
+                return Ok(false);
+            }
+            // With GC types are defined in explicit recursion groups whose
+            // members may refer to each other. Otherwise each type is in its
+            // own group and may only refer to types defined before it.
+            let group_size = if self.config.gc_enabled() {
+                u.int_in_range(1..=MAX_REC_GROUP_SIZE.min(max_types - self.types.len()))?
+            } else {
+                1
+            };
+            let start = self.types.len();
+            let end = start + group_size;
+            for _ in 0..group_size {
+                let ty = self.arbitrary_sub_type(u, end)?;
+                self.add_type(ty);
+            }
+            self.rec_groups.push(start..end);
             Ok(true)
         })?;
+
+        // With typed function references any of the types defined above may
+        // be used as the (nullable) type of locals, globals, params, etc.
+        if self.config.function_references_enabled() {
+            for i in 0..self.types.len() {
+                self.valtypes.push(ValType::Ref(RefType {
+                    nullable: true,
+                    heap_type: HeapType::Concrete(i as u32),
+                }));
+            }
+        }
         Ok(())
     }
 
-    fn record_type(&mut self, ty: &Type) {
-        let list = match &ty {
-            Type::Func(_) => &mut self.func_types,
+    fn add_type(&mut self, ty: SubType) {
+        let list = match &ty.composite_type {
+            CompositeType::Func(_) => &mut self.func_types,
+            CompositeType::Array(_) => &mut self.array_types,
+            CompositeType::Struct(_) => &mut self.struct_types,
         };
         list.push(self.types.len() as u32);
+        self.types.push(ty);
     }
 
-    fn arbitrary_type(&mut self, u: &mut Unstructured) -> Result<Type> {
-        Ok(Type::Func(self.arbitrary_func_type(u)?))
+    /// Generates the type at index `self.types.len()`, which may refer to any
+    /// type whose index is less than `type_ref_limit`.
+    fn arbitrary_sub_type(
+        &mut self,
+        u: &mut Unstructured,
+        type_ref_limit: usize,
+    ) -> Result<SubType> {
+        if !self.config.gc_enabled() {
+            let ty = self.arbitrary_func_type(u, type_ref_limit)?;
+            return Ok(SubType {
+                is_final: true,
+                supertype: None,
+                composite_type: CompositeType::Func(ty),
+            });
+        }
+
+        // Sometimes declare a subtype of some previously defined non-final
+        // type. The subtype starts out as a copy of its supertype, and structs
+        // may then be extended with additional fields.
+        let candidate_supertypes = self
+            .types
+            .iter()
+            .enumerate()
+            .filter(|(_, ty)| !ty.is_final)
+            .map(|(i, _)| i as u32)
+            .collect::<Vec<_>>();
+        if !candidate_supertypes.is_empty() && u.arbitrary()? {
+            let supertype = *u.choose(&candidate_supertypes)?;
+            let mut composite_type = self.types[supertype as usize].composite_type.clone();
+            if let CompositeType::Struct(ty) = &mut composite_type {
+                let mut fields = ty.fields.to_vec();
+                arbitrary_loop(u, 0, 5, |u| {
+                    fields.push(self.arbitrary_field_type(u, type_ref_limit)?);
+                    Ok(true)
+                })?;
+                ty.fields = fields.into_boxed_slice();
+            }
+            return Ok(SubType {
+                is_final: u.arbitrary()?,
+                supertype: Some(supertype),
+                composite_type,
+            });
+        }
+
+        let composite_type = match u.int_in_range(0..=2)? {
+            0 => CompositeType::Func(self.arbitrary_func_type(u, type_ref_limit)?),
+            1 => CompositeType::Array(ArrayType(self.arbitrary_field_type(u, type_ref_limit)?)),
+            _ => {
+                let mut fields = vec![];
+                arbitrary_loop(u, 0, 20, |u| {
+                    fields.push(self.arbitrary_field_type(u, type_ref_limit)?);
+                    Ok(true)
+                })?;
+                CompositeType::Struct(StructType {
+                    fields: fields.into_boxed_slice(),
+                })
+            }
+        };
+        Ok(SubType {
+            is_final: u.arbitrary()?,
+            supertype: None,
+            composite_type,
+        })
     }
 
-    fn arbitrary_func_type(&mut self, u: &mut Unstructured) -> Result<Rc<FuncType>> {
-        arbitrary_func_type(
-            u,
-            &self.valtypes,
-            if !self.config.multi_value_enabled() {
-                Some(1)
-            } else {
-                None
-            },
-        )
+    fn arbitrary_field_type(
+        &self,
+        u: &mut Unstructured,
+        type_ref_limit: usize,
+    ) -> Result<FieldType> {
+        let element_type = match u.int_in_range(0..=2)? {
+            0 => StorageType::I8,
+            1 => StorageType::I16,
+            _ => StorageType::Val(self.arbitrary_type_valtype(u, type_ref_limit)?),
+        };
+        Ok(FieldType {
+            element_type,
+            mutable: u.arbitrary()?,
+        })
+    }
+
+    /// Generates a value type for use in a type definition, which may be a
+    /// reference to any type whose index is less than `type_ref_limit`.
+    fn arbitrary_type_valtype(
+        &self,
+        u: &mut Unstructured,
+        type_ref_limit: usize,
+    ) -> Result<ValType> {
+        if self.config.function_references_enabled() && type_ref_limit > 0 && u.ratio(1, 4)? {
+            let idx = u.int_in_range(0..=type_ref_limit - 1)?;
+            return Ok(ValType::Ref(RefType {
+                nullable: true,
+                heap_type: HeapType::Concrete(idx as u32),
+            }));
+        }
+        self.arbitrary_valtype(u)
+    }
+
+    fn arbitrary_func_type(
+        &self,
+        u: &mut Unstructured,
+        type_ref_limit: usize,
+    ) -> Result<Rc<FuncType>> {
+        let mut params = vec![];
+        let mut results = vec![];
+        let max_results = if self.config.multi_value_enabled() {
+            20
+        } else {
+            1
+        };
+        arbitrary_loop(u, 0, 20, |u| {
+            params.push(self.arbitrary_type_valtype(u, type_ref_limit)?);
+            Ok(true)
+        })?;
+        arbitrary_loop(u, 0, max_results, |u| {
+            results.push(self.arbitrary_type_valtype(u, type_ref_limit)?);
+            Ok(true)
+        })?;
+        Ok(Rc::new(FuncType { params, results }))
     }
 
     fn can_add_local_or_import_tag(&self) -> bool {
@@ -556,9 +722,30 @@ impl Module {
         for payload in wasmparser::Parser::new(0).parse_all(&example_module) {
             match payload.expect("could not parse the available import payload") {
                 wasmparser::Payload::TypeSection(type_reader) => {
-                    for ty in type_reader.into_iter_err_on_gc_types() {
-                        let ty = ty.expect("could not parse type section");
-                        available_types.push((ty, None));
+                    for group in type_reader {
+                        let group = group.expect("could not parse type section");
+                        let is_singleton = group.types().len() == 1;
+                        for ty in group.into_types() {
+                            // Only standalone function types which don't refer
+                            // to other types of the example module can be
+                            // carried over, everything else is skipped.
+                            let func_type = match ty.composite_type {
+                                wasmparser::CompositeType::Func(func_type)
+                                    if is_singleton
+                                        && ty.is_final
+                                        && ty.supertype_idx.is_none()
+                                        && !func_type
+                                            .params()
+                                            .iter()
+                                            .chain(func_type.results())
+                                            .any(|t| refers_to_type(*t)) =>
+                                {
+                                    Some(func_type)
+                                }
+                                _ => None,
+                            };
+                            available_types.push((func_type, None));
+                        }
                     }
                 }
                 wasmparser::Payload::ImportSection(import_reader) => {
@@ -583,7 +770,7 @@ impl Module {
         let multi_value_enabled = self.config.multi_value_enabled();
         let mut new_imports = Vec::with_capacity(available_imports.len());
         let first_type_index = self.types.len();
-        let mut new_types = Vec::<Type>::new();
+        let mut new_types = Vec::<Rc<FuncType>>::new();
 
         // Returns the index to the translated type in the to-be type section, and the reference to
         // the type itself.
@@ -591,7 +778,8 @@ impl Module {
             let serialized_sig_idx = match available_types.get_mut(parsed_sig_idx as usize) {
                 None => panic!("signature index refers to a type out of bounds"),
                 Some((_, Some(idx))) => *idx as usize,
-                Some((func_type, index_store)) => {
+                Some((None, None)) => return None,
+                Some((Some(func_type), index_store)) => {
                     let multi_value_required = func_type.results().len() > 1;
                     let new_index = first_type_index + new_types.len();
                     if new_index >= max_types || (multi_value_required && !multi_value_enabled) {
@@ -610,13 +798,12 @@ impl Module {
                             .collect(),
                     });
                     index_store.replace(new_index as u32);
-                    new_types.push(Type::Func(Rc::clone(&func_type)));
+                    new_types.push(func_type);
                     new_index
                 }
             };
-            match &new_types[serialized_sig_idx - first_type_index] {
-                Type::Func(f) => Some((serialized_sig_idx as u32, Rc::clone(f))),
-            }
+            let func_type = &new_types[serialized_sig_idx - first_type_index];
+            Some((serialized_sig_idx as u32, Rc::clone(func_type)))
         };
 
         for import in available_imports {
@@ -658,6 +845,9 @@ impl Module {
                 }
 
                 wasmparser::TypeRef::Table(table_ty) => {
+                    if table_ty.element_type.is_concrete_type_ref() {
+                        continue;
+                    }
                     let table_ty = TableType {
                         element_type: convert_reftype(table_ty.element_type),
                         minimum: table_ty.initial,
@@ -691,6 +881,9 @@ impl Module {
                 }
 
                 wasmparser::TypeRef::Global(global_ty) => {
+                    if refers_to_type(global_ty.content_type) {
+                        continue;
+                    }
                     let global_ty = GlobalType {
                         val_type: convert_type(global_ty.content_type),
                         mutable: global_ty.mutable,
@@ -714,7 +907,16 @@ impl Module {
         }
 
         // Finally, add the entities we just generated.
-        self.types.extend(new_types);
+        for i in 0..new_types.len() {
+            let start = first_type_index + i;
+            self.rec_groups.push(start..start + 1);
+        }
+        self.types
+            .extend(new_types.into_iter().map(|func_type| SubType {
+                is_final: true,
+                supertype: None,
+                composite_type: CompositeType::Func(func_type),
+            }));
         self.imports.extend(new_imports);
 
         Ok(true)
@@ -733,7 +935,7 @@ impl Module {
         }
     }
 
-    fn ty(&self, idx: u32) -> &Type {
+    fn ty(&self, idx: u32) -> &SubType {
         &self.types[idx as usize]
     }
 
@@ -745,8 +947,23 @@ impl Module {
     }
 
     fn func_type(&self, idx: u32) -> &Rc<FuncType> {
-        match self.ty(idx) {
-            Type::Func(f) => f,
+        match &self.ty(idx).composite_type {
+            CompositeType::Func(f) => f,
+            _ => panic!("types[{idx}] is not a function type"),
+        }
+    }
+
+    fn array_type(&self, idx: u32) -> &ArrayType {
+        match &self.ty(idx).composite_type {
+            CompositeType::Array(a) => a,
+            _ => panic!("types[{idx}] is not an array type"),
+        }
+    }
+
+    fn struct_type(&self, idx: u32) -> &StructType {
+        match &self.ty(idx).composite_type {
+            CompositeType::Struct(s) => s,
+            _ => panic!("types[{idx}] is not a struct type"),
         }
     }
 
@@ -1385,10 +1602,103 @@ pub(crate) fn configured_valtypes(config: &dyn Config) -> Vec<ValType> {
     if config.reference_types_enabled() {
         valtypes.push(ValType::EXTERNREF);
         valtypes.push(ValType::FUNCREF);
+        if config.exceptions_enabled() {
+            valtypes.push(ValType::EXNREF);
+        }
+    }
+    if config.gc_enabled() {
+        for heap_type in [
+            HeapType::Any,
+            HeapType::Eq,
+            HeapType::I31,
+            HeapType::Struct,
+            HeapType::Array,
+            HeapType::None,
+            HeapType::NoFunc,
+            HeapType::NoExtern,
+        ] {
+            valtypes.push(ValType::Ref(RefType {
+                nullable: true,
+                heap_type,
+            }));
+        }
     }
     valtypes
 }
 
+/// Returns whether `a` is a subtype of `b`, given the type section `types`
+/// that any concrete heap types refer to.
+///
+/// This only takes declared supertypes into account, so types which are
+/// equivalent only by virtue of having the same structure are considered
+/// unrelated. That is fine for generating valid modules since it is merely
+/// conservative.
+pub(crate) fn val_type_is_sub_type(types: &[SubType], a: ValType, b: ValType) -> bool {
+    match (a, b) {
+        (ValType::Ref(a), ValType::Ref(b)) => ref_type_is_sub_type(types, a, b),
+        (a, b) => a == b,
+    }
+}
+
+pub(crate) fn ref_type_is_sub_type(types: &[SubType], a: RefType, b: RefType) -> bool {
+    (b.nullable || !a.nullable) && heap_type_is_sub_type(types, a.heap_type, b.heap_type)
+}
+
+pub(crate) fn heap_type_is_sub_type(types: &[SubType], a: HeapType, b: HeapType) -> bool {
+    use HeapType as HT;
+    let composite = |i: u32| &types[i as usize].composite_type;
+    match (a, b) {
+        (a, b) if a == b => true,
+
+        (HT::Eq | HT::I31 | HT::Struct | HT::Array | HT::None, HT::Any) => true,
+        (HT::I31 | HT::Struct | HT::Array | HT::None, HT::Eq) => true,
+        (HT::None, HT::I31 | HT::Struct | HT::Array) => true,
+        (HT::NoExtern, HT::Extern) => true,
+        (HT::NoFunc, HT::Func) => true,
+
+        (HT::None, HT::Concrete(b)) => !matches!(composite(b), CompositeType::Func(_)),
+        (HT::NoFunc, HT::Concrete(b)) => matches!(composite(b), CompositeType::Func(_)),
+
+        (HT::Concrete(a), HT::Any | HT::Eq) => !matches!(composite(a), CompositeType::Func(_)),
+        (HT::Concrete(a), HT::Struct) => matches!(composite(a), CompositeType::Struct(_)),
+        (HT::Concrete(a), HT::Array) => matches!(composite(a), CompositeType::Array(_)),
+        (HT::Concrete(a), HT::Func) => matches!(composite(a), CompositeType::Func(_)),
+
+        (HT::Concrete(mut a), HT::Concrete(b)) => {
+            while let Some(supertype) = types[a as usize].supertype {
+                if supertype == b {
+                    return true;
+                }
+                a = supertype;
+            }
+            false
+        }
+
+        _ => false,
+    }
+}
+
+/// Returns the top type of the hierarchy that `ty` belongs to: `func`,
+/// `extern`, `any`, `cont` or `exn`.
+pub(crate) fn top_heap_type(types: &[SubType], ty: HeapType) -> HeapType {
+    match ty {
+        HeapType::Func | HeapType::NoFunc => HeapType::Func,
+        HeapType::Extern | HeapType::NoExtern => HeapType::Extern,
+        HeapType::Cont | HeapType::NoCont => HeapType::Cont,
+        HeapType::Exn => HeapType::Exn,
+        HeapType::Concrete(i) => match types[i as usize].composite_type {
+            CompositeType::Func(_) => HeapType::Func,
+            CompositeType::Array(_) | CompositeType::Struct(_) => HeapType::Any,
+        },
+        HeapType::Any
+        | HeapType::None
+        | HeapType::Eq
+        | HeapType::Struct
+        | HeapType::Array
+        | HeapType::I31 => HeapType::Any,
+    }
+}
+
 pub(crate) fn arbitrary_func_type(
     u: &mut Unstructured,
     valtypes: &[ValType],
@@ -1640,6 +1950,15 @@ fn convert_type(parsed_type: wasmparser::ValType) -> ValType {
     }
 }
 
+/// Returns whether `ty` refers to a concrete type by index, which is only
+/// meaningful within the module it was parsed from.
+fn refers_to_type(ty: wasmparser::ValType) -> bool {
+    match ty {
+        wasmparser::ValType::Ref(ty) => ty.is_concrete_type_ref(),
+        _ => false,
+    }
+}
+
 fn convert_reftype(ty: wasmparser::RefType) -> RefType {
     wasm_encoder::RefType {
         nullable: ty.is_nullable(),
@@ -1654,6 +1973,9 @@ fn convert_reftype(ty: wasmparser::RefType) -> RefType {
             wasmparser::HeapType::Struct => HeapType::Struct,
             wasmparser::HeapType::Array => HeapType::Array,
             wasmparser::HeapType::I31 => HeapType::I31,
+            wasmparser::HeapType::Exn => HeapType::Exn,
+            wasmparser::HeapType::Cont => HeapType::Cont,
+            wasmparser::HeapType::NoCont => HeapType::NoCont,
             wasmparser::HeapType::Concrete(i) => HeapType::Concrete(i.as_module_index().unwrap()),
         },
     }
@@ -1731,6 +2053,7 @@ flags! {
         Table,
         Memory,
         Control,
+        Aggregate,
     }
 }
 
@@ -1746,6 +2069,7 @@ impl FromStr for InstructionKind {
             "table" => Ok(InstructionKind::Table),
             "memory" => Ok(InstructionKind::Memory),
             "control" => Ok(InstructionKind::Control),
+            "aggregate" => Ok(InstructionKind::Aggregate),
             _ => Err(format!("unknown instruction kind: {}", s)),
         }
     }
@@ -1,13 +1,15 @@
 use super::{
-    Elements, FuncType, GlobalInitExpr, Instruction, InstructionKind::*, InstructionKinds, Module,
-    ValType,
+    CompositeType, Elements, FuncType, GlobalInitExpr, Instruction, InstructionKind::*,
+    InstructionKinds, Module, SubType, ValType,
 };
-use crate::unique_string;
+use crate::{arbitrary_loop, unique_string};
 use arbitrary::{Result, Unstructured};
 use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryFrom;
 use std::rc::Rc;
-use wasm_encoder::{BlockType, ConstExpr, ExportKind, GlobalType, MemArg, RefType};
+use wasm_encoder::{
+    BlockType, Catch, ConstExpr, ExportKind, GlobalType, HeapType, MemArg, RefType, StorageType,
+};
 mod no_traps;
 
 macro_rules! instructions {
@@ -95,6 +97,7 @@ instructions! {
     (None, block, Control),
     (None, r#loop, Control),
     (Some(try_valid), r#try, Control),
+    (Some(try_table_valid), try_table, Control),
     (Some(delegate_valid), delegate, Control),
     (Some(catch_valid), catch, Control),
     (Some(catch_all_valid), catch_all, Control),
@@ -109,8 +112,15 @@ instructions! {
     (Some(call_indirect_valid), call_indirect, Control),
     (Some(return_call_valid), return_call, Control),
     (Some(return_call_indirect_valid), return_call_indirect, Control),
+    (Some(call_ref_valid), call_ref, Control),
+    (Some(return_call_ref_valid), return_call_ref, Control),
+    (Some(br_on_null_valid), br_on_null, Control),
+    (Some(br_on_non_null_valid), br_on_non_null, Control),
+    (Some(br_on_cast_valid), br_on_cast, Control),
+    (Some(br_on_cast_fail_valid), br_on_cast_fail, Control),
     (Some(throw_valid), throw, Control, 850),
     (Some(rethrow_valid), rethrow, Control),
+    (Some(throw_ref_valid), throw_ref, Control),
     // Parametric instructions.
     (Some(drop_valid), drop, Parametric, 990),
     (Some(select_valid), select, Parametric),
@@ -303,6 +313,29 @@ instructions! {
     (Some(table_copy_valid), table_copy, Reference),
     (Some(table_init_valid), table_init, Reference),
     (Some(elem_drop_valid), elem_drop, Reference),
+    (Some(ref_as_non_null_valid), ref_as_non_null, Reference),
+    (Some(ref_eq_valid), ref_eq, Reference),
+    (Some(ref_test_valid), ref_test, Reference),
+    (Some(ref_cast_valid), ref_cast, Reference),
+    (Some(any_convert_extern_valid), any_convert_extern, Reference),
+    (Some(extern_convert_any_valid), extern_convert_any, Reference),
+    // Aggregate instructions.
+    (Some(struct_new_valid), struct_new, Aggregate),
+    (Some(struct_new_default_valid), struct_new_default, Aggregate),
+    (Some(struct_get_valid), struct_get, Aggregate),
+    (Some(struct_set_valid), struct_set, Aggregate),
+    (Some(array_new_valid), array_new, Aggregate),
+    (Some(array_new_default_valid), array_new_default, Aggregate),
+    (Some(array_new_fixed_valid), array_new_fixed, Aggregate),
+    (Some(array_new_data_valid), array_new_data, Aggregate),
+    (Some(array_get_valid), array_get, Aggregate),
+    (Some(array_set_valid), array_set, Aggregate),
+    (Some(array_len_valid), array_len, Aggregate),
+    (Some(array_fill_valid), array_fill, Aggregate),
+    (Some(array_copy_valid), array_copy, Aggregate),
+    (Some(ref_i31_valid), ref_i31, Aggregate),
+    (Some(i31_get_valid), i31_get_s, Aggregate),
+    (Some(i31_get_valid), i31_get_u, Aggregate),
     // SIMD instructions.
     (Some(simd_have_memory_and_offset), v128_load, Vector),
     (Some(simd_have_memory_and_offset), v128_load8x8s, Vector),
@@ -582,6 +615,10 @@ pub(crate) struct CodeBuilderAllocations {
     // all mutable).
     mutable_globals: BTreeMap<ValType, Vec<u32>>,
 
+    // A copy of the module's type section, used to check whether the types on
+    // the operand stack are subtypes of the types that an instruction expects.
+    types: Vec<SubType>,
+
     // Like mutable globals above this is a map from function types to the list
     // of functions that have that function type.
     functions: BTreeMap<Rc<FuncType>, Vec<u32>>,
@@ -665,6 +702,7 @@ enum ControlKind {
     Try,
     Catch,
     CatchAll,
+    TryTable,
 }
 
 enum Float {
@@ -747,6 +785,7 @@ impl CodeBuilderAllocations {
             functions,
             tags,
             mutable_globals,
+            types: module.types.clone(),
             funcref_tables,
             referenced_functions: referenced_functions.into_iter().collect(),
             table_init_possible,
@@ -839,11 +878,17 @@ impl CodeBuilder<'_> {
         self.types_on_stack(to_check.label_types())
     }
 
+    /// Returns whether a value of type `a` can be used where a value of type
+    /// `b` is expected.
+    fn is_sub_type(&self, a: ValType, b: ValType) -> bool {
+        a == b || super::val_type_is_sub_type(&self.allocs.types, a, b)
+    }
+
     fn type_on_stack(&self, ty: ValType) -> bool {
         match self.operands().last() {
             None => false,
             Some(None) => true,
-            Some(Some(x)) => *x == ty,
+            Some(Some(x)) => self.is_sub_type(*x, ty),
         }
     }
 
@@ -856,10 +901,29 @@ impl CodeBuilder<'_> {
                 .zip(types.iter().rev())
                 .all(|(a, b)| match (a, b) {
                     (None, _) => true,
-                    (Some(x), y) => x == y,
+                    (Some(x), y) => self.is_sub_type(*x, *y),
                 })
     }
 
+    /// Returns the type of the reference on top of the operand stack, if the
+    /// top of the stack is a reference with a known type.
+    fn ref_type_on_stack(&self) -> Option<RefType> {
+        match self.operands().last() {
+            Some(Some(ValType::Ref(ty))) => Some(*ty),
+            _ => None,
+        }
+    }
+
+    /// Replaces the top `types.len()` operands with exactly `types`.
+    ///
+    /// Instructions like `br_if` and `local.tee` leave their operands on the
+    /// stack, but validation then considers those operands to have the
+    /// expected type rather than whatever subtype they had before.
+    fn reset_operands(&mut self, types: &[ValType]) {
+        self.pop_operands(types);
+        self.push_operands(types);
+    }
+
     #[inline(never)]
     fn arbitrary_block_type(&self, u: &mut Unstructured, module: &Module) -> Result<BlockType> {
         let mut options: Vec<Box<dyn Fn(&mut Unstructured) -> Result<BlockType>>> = vec![
@@ -1134,7 +1198,7 @@ impl CodeBuilder<'_> {
         }
         for (i, expected) in label_results.iter().enumerate() {
             if let Some(actual) = operands.get(i) {
-                if Some(*expected) == *actual {
+                if actual.is_some_and(|actual| self.is_sub_type(actual, *expected)) {
                     continue;
                 }
                 for ty in operands[i..].iter().rev() {
@@ -1298,6 +1362,7 @@ fn block(
 ) -> Result<()> {
     let block_ty = builder.arbitrary_block_type(u, module)?;
     let (params, results) = module.params_results(&block_ty);
+    builder.reset_operands(&params);
     let height = builder.allocs.operands.len() - params.len();
     builder.allocs.controls.push(Control {
         kind: ControlKind::Block,
@@ -1322,6 +1387,7 @@ fn r#try(
 ) -> Result<()> {
     let block_ty = builder.arbitrary_block_type(u, module)?;
     let (params, results) = module.params_results(&block_ty);
+    builder.reset_operands(&params);
     let height = builder.allocs.operands.len() - params.len();
     builder.allocs.controls.push(Control {
         kind: ControlKind::Try,
@@ -1333,6 +1399,74 @@ fn r#try(
     Ok(())
 }
 
+#[inline]
+fn try_table_valid(module: &Module, _: &mut CodeBuilder) -> bool {
+    module.config.exceptions_enabled()
+}
+
+fn try_table(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let block_ty = builder.arbitrary_block_type(u, module)?;
+
+    // Collect all catch clauses that are valid for the labels currently in
+    // scope. Note that catch labels are relative to the frame enclosing the
+    // `try_table`, so this must happen before its own frame is pushed.
+    let is_exnref = |ty: &ValType| match ty {
+        ValType::Ref(r) => r.heap_type == HeapType::Exn,
+        _ => false,
+    };
+    let mut catch_options = Vec::new();
+    for (i, ctrl) in builder.allocs.controls.iter().rev().enumerate() {
+        let label = i as u32;
+        let label_types = ctrl.label_types();
+        if label_types.is_empty() {
+            catch_options.push(Catch::All { label });
+        }
+        if let [ty] = label_types {
+            if is_exnref(ty) {
+                catch_options.push(Catch::AllRef { label });
+            }
+        }
+        if let Some(tags) = builder.allocs.tags.get(label_types) {
+            for tag in tags {
+                catch_options.push(Catch::One { tag: *tag, label });
+            }
+        }
+        if let Some((ty, rest)) = label_types.split_last() {
+            if is_exnref(ty) {
+                if let Some(tags) = builder.allocs.tags.get(rest) {
+                    for tag in tags {
+                        catch_options.push(Catch::OneRef { tag: *tag, label });
+                    }
+                }
+            }
+        }
+    }
+    let mut catches = Vec::new();
+    if !catch_options.is_empty() {
+        arbitrary_loop(u, 0, 10, |u| {
+            catches.push(*u.choose(&catch_options)?);
+            Ok(true)
+        })?;
+    }
+
+    let (params, results) = module.params_results(&block_ty);
+    builder.reset_operands(&params);
+    let height = builder.allocs.operands.len() - params.len();
+    builder.allocs.controls.push(Control {
+        kind: ControlKind::TryTable,
+        params,
+        results,
+        height,
+    });
+    instructions.push(Instruction::TryTable(block_ty, catches.into()));
+    Ok(())
+}
+
 #[inline]
 fn delegate_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     let control_kind = builder.allocs.controls.last().unwrap().kind;
@@ -1357,7 +1491,8 @@ fn delegate(
     let target_relative_from_last = u.int_in_range(1..=n - 1)?;
     let target_relative_from_outer = target_relative_from_last - 1;
     // Delegate ends the try block
-    builder.allocs.controls.pop();
+    let control = builder.allocs.controls.pop().unwrap();
+    builder.reset_operands(&control.results);
     instructions.push(Instruction::Delegate(target_relative_from_outer as u32));
     Ok(())
 }
@@ -1429,6 +1564,7 @@ fn r#loop(
 ) -> Result<()> {
     let block_ty = builder.arbitrary_block_type(u, module)?;
     let (params, results) = module.params_results(&block_ty);
+    builder.reset_operands(&params);
     let height = builder.allocs.operands.len() - params.len();
     builder.allocs.controls.push(Control {
         kind: ControlKind::Loop,
@@ -1455,6 +1591,7 @@ fn r#if(
 
     let block_ty = builder.arbitrary_block_type(u, module)?;
     let (params, results) = module.params_results(&block_ty);
+    builder.reset_operands(&params);
     let height = builder.allocs.operands.len() - params.len();
     builder.allocs.controls.push(Control {
         kind: ControlKind::If,
@@ -1512,7 +1649,8 @@ fn end(
     builder: &mut CodeBuilder,
     instructions: &mut Vec<Instruction>,
 ) -> Result<()> {
-    builder.allocs.controls.pop();
+    let control = builder.allocs.controls.pop().unwrap();
+    builder.reset_operands(&control.results);
     instructions.push(Instruction::End);
     Ok(())
 }
@@ -1596,6 +1734,9 @@ fn br_if(
         .filter(|(_, l)| builder.label_types_on_stack(l))
         .nth(i)
         .unwrap();
+    let control = &builder.allocs.controls[builder.allocs.controls.len() - 1 - target];
+    let tys = control.label_types().to_vec();
+    builder.reset_operands(&tys);
     instructions.push(Instruction::BrIf(target as u32));
     Ok(())
 }
@@ -1833,6 +1974,296 @@ fn return_call_indirect(
     Ok(())
 }
 
+#[inline]
+fn call_ref_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.function_references_enabled() {
+        return false;
+    }
+    let idx = match func_ref_on_stack(module, builder) {
+        Some(idx) => idx,
+        None => return false,
+    };
+    let ty = builder.allocs.operands.pop().unwrap();
+    let is_valid = builder.types_on_stack(&module.func_type(idx).params);
+    builder.allocs.operands.push(ty);
+    is_valid
+}
+
+fn call_ref(
+    _: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = func_ref_on_stack(module, builder).unwrap();
+    let ty = module.func_type(idx);
+    builder.allocs.operands.pop();
+    builder.pop_operands(&ty.params);
+    builder.push_operands(&ty.results);
+    instructions.push(Instruction::CallRef(idx));
+    Ok(())
+}
+
+#[inline]
+fn return_call_ref_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.tail_call_enabled() {
+        return false;
+    }
+    match func_ref_on_stack(module, builder) {
+        Some(idx) => {
+            builder.allocs.controls[0].label_types() == module.func_type(idx).results
+                && call_ref_valid(module, builder)
+        }
+        None => false,
+    }
+}
+
+fn return_call_ref(
+    _: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = func_ref_on_stack(module, builder).unwrap();
+    let ty = module.func_type(idx);
+    builder.allocs.operands.pop();
+    builder.pop_operands(&ty.params);
+    builder.push_operands(&ty.results);
+    instructions.push(Instruction::ReturnCallRef(idx));
+    Ok(())
+}
+
+#[inline]
+fn br_on_null_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.function_references_enabled() || builder.ref_type_on_stack().is_none() {
+        return false;
+    }
+    let ty = builder.allocs.operands.pop().unwrap();
+    let is_valid = br_valid(module, builder);
+    builder.allocs.operands.push(ty);
+    is_valid
+}
+
+fn br_on_null(
+    u: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let ty = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    let target = arbitrary_label(u, builder, |l| builder.label_types_on_stack(l))?;
+    let control = &builder.allocs.controls[builder.allocs.controls.len() - 1 - target];
+    let tys = control.label_types().to_vec();
+    builder.reset_operands(&tys);
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: false,
+        ..ty
+    })]);
+    instructions.push(Instruction::BrOnNull(target as u32));
+    Ok(())
+}
+
+/// Whether `label` can be the target of a `br_on_non_null` whose operand has
+/// type `ty`, assuming that operand has already been popped.
+fn br_on_non_null_label_valid(builder: &CodeBuilder, ty: RefType, label: &Control) -> bool {
+    match label.label_types().split_last() {
+        Some((last, rest)) => {
+            let non_null = ValType::Ref(RefType {
+                nullable: false,
+                ..ty
+            });
+            builder.is_sub_type(non_null, *last) && builder.types_on_stack(rest)
+        }
+        None => false,
+    }
+}
+
+#[inline]
+fn br_on_non_null_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.function_references_enabled() {
+        return false;
+    }
+    let ty = match builder.ref_type_on_stack() {
+        Some(ty) => ty,
+        None => return false,
+    };
+    let operand = builder.allocs.operands.pop().unwrap();
+    let is_valid = builder
+        .allocs
+        .controls
+        .iter()
+        .any(|l| br_on_non_null_label_valid(builder, ty, l));
+    builder.allocs.operands.push(operand);
+    is_valid
+}
+
+fn br_on_non_null(
+    u: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let ty = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    let target = arbitrary_label(u, builder, |l| br_on_non_null_label_valid(builder, ty, l))?;
+    let control = &builder.allocs.controls[builder.allocs.controls.len() - 1 - target];
+    let tys = control.label_types().to_vec();
+    builder.reset_operands(&tys[..tys.len() - 1]);
+    instructions.push(Instruction::BrOnNonNull(target as u32));
+    Ok(())
+}
+
+/// Returns the last label type of `label` if it is a reference type in the
+/// same hierarchy as `from`, and the label's other types are on the stack,
+/// assuming the cast operand has already been popped.
+fn br_on_cast_label_type(builder: &CodeBuilder, from: RefType, label: &Control) -> Option<RefType> {
+    match label.label_types().split_last() {
+        Some((ValType::Ref(last), rest))
+            if super::top_heap_type(&builder.allocs.types, last.heap_type)
+                == super::top_heap_type(&builder.allocs.types, from.heap_type)
+                && builder.types_on_stack(rest) =>
+        {
+            Some(*last)
+        }
+        _ => None,
+    }
+}
+
+#[inline]
+fn br_on_cast_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() {
+        return false;
+    }
+    let from = match builder.ref_type_on_stack() {
+        Some(ty) => ty,
+        None => return false,
+    };
+    let operand = builder.allocs.operands.pop().unwrap();
+    // Any label whose type is in the same hierarchy works since at the very
+    // least we can cast to the bottom type of that hierarchy.
+    let is_valid = builder
+        .allocs
+        .controls
+        .iter()
+        .any(|l| br_on_cast_label_type(builder, from, l).is_some());
+    builder.allocs.operands.push(operand);
+    is_valid
+}
+
+fn br_on_cast(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let from = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    let target = arbitrary_label(u, builder, |l| {
+        br_on_cast_label_type(builder, from, l).is_some()
+    })?;
+    let control = &builder.allocs.controls[builder.allocs.controls.len() - 1 - target];
+    let label_ty = br_on_cast_label_type(builder, from, control).unwrap();
+    let tys = control.label_types().to_vec();
+
+    // The cast's target type is what's sent to the label, so it must be a
+    // subtype of both the operand's type and the label's type.
+    let types = &builder.allocs.types;
+    let heap_types = heap_types_in_hierarchy(module, from.heap_type)
+        .into_iter()
+        .filter(|ht| {
+            super::heap_type_is_sub_type(types, *ht, from.heap_type)
+                && super::heap_type_is_sub_type(types, *ht, label_ty.heap_type)
+        })
+        .collect::<Vec<_>>();
+    let to = RefType {
+        nullable: from.nullable && label_ty.nullable && u.arbitrary()?,
+        heap_type: *u.choose(&heap_types)?,
+    };
+
+    builder.reset_operands(&tys[..tys.len() - 1]);
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: from.nullable && !to.nullable,
+        heap_type: from.heap_type,
+    })]);
+    instructions.push(Instruction::BrOnCast {
+        relative_depth: target as u32,
+        from_ref_type: from,
+        to_ref_type: to,
+    });
+    Ok(())
+}
+
+/// Like `br_on_cast_label_type` but additionally requires that the operand's
+/// heap type is a subtype of the label's, since on failure the operand
+/// (minus, possibly, its nullability) is sent to the label.
+fn br_on_cast_fail_label_type(
+    builder: &CodeBuilder,
+    from: RefType,
+    label: &Control,
+) -> Option<RefType> {
+    br_on_cast_label_type(builder, from, label).filter(|last| {
+        super::heap_type_is_sub_type(&builder.allocs.types, from.heap_type, last.heap_type)
+    })
+}
+
+#[inline]
+fn br_on_cast_fail_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() {
+        return false;
+    }
+    let from = match builder.ref_type_on_stack() {
+        Some(ty) => ty,
+        None => return false,
+    };
+    let operand = builder.allocs.operands.pop().unwrap();
+    let is_valid = builder
+        .allocs
+        .controls
+        .iter()
+        .any(|l| br_on_cast_fail_label_type(builder, from, l).is_some());
+    builder.allocs.operands.push(operand);
+    is_valid
+}
+
+fn br_on_cast_fail(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let from = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    let target = arbitrary_label(u, builder, |l| {
+        br_on_cast_fail_label_type(builder, from, l).is_some()
+    })?;
+    let control = &builder.allocs.controls[builder.allocs.controls.len() - 1 - target];
+    let label_ty = br_on_cast_fail_label_type(builder, from, control).unwrap();
+    let tys = control.label_types().to_vec();
+
+    // Null operands are sent to the label unless the target type is
+    // nullable, so if the label can't accept nulls then the target type
+    // must be nullable.
+    let types = &builder.allocs.types;
+    let heap_types = heap_types_in_hierarchy(module, from.heap_type)
+        .into_iter()
+        .filter(|ht| super::heap_type_is_sub_type(types, *ht, from.heap_type))
+        .collect::<Vec<_>>();
+    let to = RefType {
+        nullable: from.nullable && (!label_ty.nullable || u.arbitrary()?),
+        heap_type: *u.choose(&heap_types)?,
+    };
+
+    builder.reset_operands(&tys[..tys.len() - 1]);
+    builder.push_operands(&[ValType::Ref(to)]);
+    instructions.push(Instruction::BrOnCastFail {
+        relative_depth: target as u32,
+        from_ref_type: from,
+        to_ref_type: to,
+    });
+    Ok(())
+}
+
 #[inline]
 fn throw_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.exceptions_enabled()
@@ -1904,6 +2335,22 @@ fn rethrow(
     Ok(())
 }
 
+#[inline]
+fn throw_ref_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.exceptions_enabled() && builder.type_on_stack(ValType::EXNREF)
+}
+
+fn throw_ref(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    builder.pop_operands(&[ValType::EXNREF]);
+    instructions.push(Instruction::ThrowRef);
+    Ok(())
+}
+
 #[inline]
 fn drop_valid(_: &Module, builder: &mut CodeBuilder) -> bool {
     !builder.operands().is_empty()
@@ -2027,7 +2474,7 @@ fn local_tee(
         .count();
     debug_assert!(n > 0);
     let i = u.int_in_range(0..=n - 1)?;
-    let (j, _) = builder
+    let (j, ty) = builder
         .func_ty
         .params
         .iter()
@@ -2036,6 +2483,8 @@ fn local_tee(
         .filter(|(_, ty)| builder.type_on_stack(**ty))
         .nth(i)
         .unwrap();
+    let ty = *ty;
+    builder.reset_operands(&[ty]);
     instructions.push(Instruction::LocalTee(j as u32));
     Ok(())
 }
@@ -4657,13 +5106,29 @@ fn ref_null_valid(module: &Module, _: &mut CodeBuilder) -> bool {
 
 fn ref_null(
     u: &mut Unstructured,
-    _: &Module,
+    module: &Module,
     builder: &mut CodeBuilder,
     instructions: &mut Vec<Instruction>,
 ) -> Result<()> {
-    let ty = *u.choose(&[RefType::EXTERNREF, RefType::FUNCREF])?;
-    builder.push_operands(&[ty.into()]);
-    instructions.push(Instruction::RefNull(ty.heap_type));
+    let mut choices = vec![HeapType::Extern, HeapType::Func];
+    if module.config.gc_enabled() {
+        choices.extend([
+            HeapType::Any,
+            HeapType::Eq,
+            HeapType::I31,
+            HeapType::Struct,
+            HeapType::Array,
+            HeapType::None,
+            HeapType::NoFunc,
+            HeapType::NoExtern,
+        ]);
+    }
+    if module.config.function_references_enabled() {
+        choices.extend((0..module.types.len()).map(|i| HeapType::Concrete(i as u32)));
+    }
+    let heap_type = *u.choose(&choices)?;
+    builder.push_operands(&[nullable_ref(heap_type)]);
+    instructions.push(Instruction::RefNull(heap_type));
     Ok(())
 }
 
@@ -4674,12 +5139,22 @@ fn ref_func_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
 
 fn ref_func(
     u: &mut Unstructured,
-    _: &Module,
+    module: &Module,
     builder: &mut CodeBuilder,
     instructions: &mut Vec<Instruction>,
 ) -> Result<()> {
     let i = *u.choose(&builder.allocs.referenced_functions)?;
-    builder.push_operands(&[ValType::FUNCREF]);
+    // With typed function references `ref.func` produces a non-nullable
+    // reference to the function's exact type.
+    let ty = if module.config.function_references_enabled() {
+        ValType::Ref(RefType {
+            nullable: false,
+            heap_type: HeapType::Concrete(module.funcs[i as usize].0),
+        })
+    } else {
+        ValType::FUNCREF
+    };
+    builder.push_operands(&[ty]);
     instructions.push(Instruction::RefFunc(i));
     Ok(())
 }
@@ -4687,7 +5162,10 @@ fn ref_func(
 #[inline]
 fn ref_is_null_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
     module.config.reference_types_enabled()
-        && (builder.type_on_stack(ValType::EXTERNREF) || builder.type_on_stack(ValType::FUNCREF))
+        && matches!(
+            builder.operands().last(),
+            Some(None | Some(ValType::Ref(_)))
+        )
 }
 
 fn ref_is_null(
@@ -4696,7 +5174,7 @@ fn ref_is_null(
     builder: &mut CodeBuilder,
     instructions: &mut Vec<Instruction>,
 ) -> Result<()> {
-    pop_reference_type(builder);
+    builder.allocs.operands.pop();
     builder.push_operands(&[ValType::I32]);
     instructions.push(Instruction::RefIsNull);
     Ok(())
@@ -4883,6 +5361,695 @@ fn elem_drop(
     Ok(())
 }
 
+#[inline]
+fn ref_as_non_null_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.function_references_enabled()
+        && builder
+            .ref_type_on_stack()
+            .is_some_and(|ty| !ty.nullable || !module.config.disallow_traps())
+}
+
+fn ref_as_non_null(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let ty = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: false,
+        ..ty
+    })]);
+    instructions.push(Instruction::RefAsNonNull);
+    Ok(())
+}
+
+#[inline]
+fn ref_eq_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    let eqref = nullable_ref(HeapType::Eq);
+    module.config.gc_enabled() && builder.types_on_stack(&[eqref, eqref])
+}
+
+fn ref_eq(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let eqref = nullable_ref(HeapType::Eq);
+    builder.pop_operands(&[eqref, eqref]);
+    builder.push_operands(&[ValType::I32]);
+    instructions.push(Instruction::RefEq);
+    Ok(())
+}
+
+#[inline]
+fn ref_test_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled() && builder.ref_type_on_stack().is_some()
+}
+
+fn ref_test(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let from = builder.ref_type_on_stack().unwrap();
+    let heap_types = heap_types_in_hierarchy(module, from.heap_type);
+    let to = RefType {
+        nullable: u.arbitrary()?,
+        heap_type: *u.choose(&heap_types)?,
+    };
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::I32]);
+    instructions.push(Instruction::RefTest(to));
+    Ok(())
+}
+
+#[inline]
+fn ref_cast_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && !module.config.disallow_traps() // Non-trapping ref.cast generation not yet implemented
+        && builder.ref_type_on_stack().is_some()
+}
+
+fn ref_cast(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let from = builder.ref_type_on_stack().unwrap();
+    let heap_types = heap_types_in_hierarchy(module, from.heap_type);
+    let to = RefType {
+        nullable: u.arbitrary()?,
+        heap_type: *u.choose(&heap_types)?,
+    };
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::Ref(to)]);
+    instructions.push(Instruction::RefCast(to));
+    Ok(())
+}
+
+#[inline]
+fn any_convert_extern_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && builder
+            .ref_type_on_stack()
+            .is_some_and(|ty| builder.is_sub_type(ValType::Ref(ty), ValType::EXTERNREF))
+}
+
+fn any_convert_extern(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let ty = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: ty.nullable,
+        heap_type: HeapType::Any,
+    })]);
+    instructions.push(Instruction::AnyConvertExtern);
+    Ok(())
+}
+
+#[inline]
+fn extern_convert_any_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && builder
+            .ref_type_on_stack()
+            .is_some_and(|ty| builder.is_sub_type(ValType::Ref(ty), nullable_ref(HeapType::Any)))
+}
+
+fn extern_convert_any(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let ty = builder.ref_type_on_stack().unwrap();
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: ty.nullable,
+        heap_type: HeapType::Extern,
+    })]);
+    instructions.push(Instruction::ExternConvertAny);
+    Ok(())
+}
+
+#[inline]
+fn struct_new_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && module
+            .struct_types
+            .iter()
+            .any(|i| field_types_on_stack(builder, &module.struct_type(*i).fields))
+}
+
+fn struct_new(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = module
+        .struct_types
+        .iter()
+        .copied()
+        .filter(|i| field_types_on_stack(builder, &module.struct_type(*i).fields))
+        .collect::<Vec<_>>();
+    let idx = *u.choose(&candidates)?;
+    let fields = &module.struct_type(idx).fields;
+    let new_len = builder.allocs.operands.len() - fields.len();
+    builder.allocs.operands.truncate(new_len);
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::StructNew(idx));
+    Ok(())
+}
+
+#[inline]
+fn struct_new_default_valid(module: &Module, _: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled() && !module.struct_types.is_empty()
+}
+
+fn struct_new_default(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = *u.choose(&module.struct_types)?;
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::StructNewDefault(idx));
+    Ok(())
+}
+
+#[inline]
+fn struct_get_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && struct_ref_on_stack(module, builder, 0)
+            .is_some_and(|idx| !module.struct_type(idx).fields.is_empty())
+}
+
+fn struct_get(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = struct_ref_on_stack(module, builder, 0).unwrap();
+    let fields = &module.struct_type(idx).fields;
+    let field_idx = u.int_in_range(0..=fields.len() - 1)?;
+    let field = fields[field_idx];
+    let field_idx = field_idx as u32;
+    builder.allocs.operands.pop();
+    builder.push_operands(&[unpack(field.element_type)]);
+    instructions.push(match field.element_type {
+        StorageType::Val(_) => Instruction::StructGet(idx, field_idx),
+        StorageType::I8 | StorageType::I16 => {
+            if u.arbitrary()? {
+                Instruction::StructGetS(idx, field_idx)
+            } else {
+                Instruction::StructGetU(idx, field_idx)
+            }
+        }
+    });
+    Ok(())
+}
+
+#[inline]
+fn struct_set_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() {
+        return false;
+    }
+    match struct_ref_on_stack(module, builder, 1) {
+        Some(idx) => module
+            .struct_type(idx)
+            .fields
+            .iter()
+            .any(|f| f.mutable && builder.type_on_stack(unpack(f.element_type))),
+        None => false,
+    }
+}
+
+fn struct_set(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = struct_ref_on_stack(module, builder, 1).unwrap();
+    let candidates = module
+        .struct_type(idx)
+        .fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| f.mutable && builder.type_on_stack(unpack(f.element_type)))
+        .map(|(i, _)| i as u32)
+        .collect::<Vec<_>>();
+    let field_idx = *u.choose(&candidates)?;
+    builder.allocs.operands.pop();
+    builder.allocs.operands.pop();
+    instructions.push(Instruction::StructSet(idx, field_idx));
+    Ok(())
+}
+
+#[inline]
+fn array_new_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && !module.config.disallow_traps() // Non-trapping array.new generation not yet implemented
+        && module.array_types.iter().any(|i| {
+            let elem = unpack(module.array_type(*i).0.element_type);
+            builder.types_on_stack(&[elem, ValType::I32])
+        })
+}
+
+fn array_new(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = module
+        .array_types
+        .iter()
+        .copied()
+        .filter(|i| {
+            let elem = unpack(module.array_type(*i).0.element_type);
+            builder.types_on_stack(&[elem, ValType::I32])
+        })
+        .collect::<Vec<_>>();
+    let idx = *u.choose(&candidates)?;
+    let elem = unpack(module.array_type(idx).0.element_type);
+    builder.pop_operands(&[elem, ValType::I32]);
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::ArrayNew(idx));
+    Ok(())
+}
+
+#[inline]
+fn array_new_default_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && !module.config.disallow_traps() // Non-trapping array.new_default generation not yet implemented
+        && !module.array_types.is_empty()
+        && builder.type_on_stack(ValType::I32)
+}
+
+fn array_new_default(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = *u.choose(&module.array_types)?;
+    builder.pop_operands(&[ValType::I32]);
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::ArrayNewDefault(idx));
+    Ok(())
+}
+
+#[inline]
+fn array_new_fixed_valid(module: &Module, _: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled() && !module.array_types.is_empty()
+}
+
+fn array_new_fixed(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = *u.choose(&module.array_types)?;
+    let elem = unpack(module.array_type(idx).0.element_type);
+    // Use some number of the operands on top of the stack which have the
+    // array's element type, possibly none of them.
+    let max = builder
+        .operands()
+        .iter()
+        .rev()
+        .take(10)
+        .take_while(|ty| ty.is_none_or(|ty| builder.is_sub_type(ty, elem)))
+        .count();
+    let n = u.int_in_range(0..=max)?;
+    let new_len = builder.allocs.operands.len() - n;
+    builder.allocs.operands.truncate(new_len);
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::ArrayNewFixed(idx, n as u32));
+    Ok(())
+}
+
+#[inline]
+fn array_new_data_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && module.config.bulk_memory_enabled()
+        && !module.config.disallow_traps() // Non-trapping array.new_data generation not yet implemented
+        && have_data(module, builder)
+        && builder.types_on_stack(&[ValType::I32, ValType::I32])
+        && module
+            .array_types
+            .iter()
+            .any(|i| is_numeric_storage_type(module.array_type(*i).0.element_type))
+}
+
+fn array_new_data(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let candidates = module
+        .array_types
+        .iter()
+        .copied()
+        .filter(|i| is_numeric_storage_type(module.array_type(*i).0.element_type))
+        .collect::<Vec<_>>();
+    let idx = *u.choose(&candidates)?;
+    let segment = u.int_in_range(0..=module.data.len() - 1)? as u32;
+    builder.pop_operands(&[ValType::I32, ValType::I32]);
+    builder.push_operands(&[non_null_ref(idx)]);
+    instructions.push(Instruction::ArrayNewData(idx, segment));
+    Ok(())
+}
+
+#[inline]
+fn array_get_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && !module.config.disallow_traps() // Non-trapping array.get generation not yet implemented
+        && builder.type_on_stack(ValType::I32)
+        && array_ref_on_stack(module, builder, 1).is_some()
+}
+
+fn array_get(
+    u: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = array_ref_on_stack(module, builder, 1).unwrap();
+    let elem = module.array_type(idx).0.element_type;
+    builder.allocs.operands.pop();
+    builder.allocs.operands.pop();
+    builder.push_operands(&[unpack(elem)]);
+    instructions.push(match elem {
+        StorageType::Val(_) => Instruction::ArrayGet(idx),
+        StorageType::I8 | StorageType::I16 => {
+            if u.arbitrary()? {
+                Instruction::ArrayGetS(idx)
+            } else {
+                Instruction::ArrayGetU(idx)
+            }
+        }
+    });
+    Ok(())
+}
+
+#[inline]
+fn array_set_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() || module.config.disallow_traps() {
+        // Non-trapping array.set generation not yet implemented
+        return false;
+    }
+    match array_ref_on_stack(module, builder, 2) {
+        Some(idx) => {
+            let ty = &module.array_type(idx).0;
+            ty.mutable && builder.types_on_stack(&[ValType::I32, unpack(ty.element_type)])
+        }
+        None => false,
+    }
+}
+
+fn array_set(
+    _: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = array_ref_on_stack(module, builder, 2).unwrap();
+    let new_len = builder.allocs.operands.len() - 3;
+    builder.allocs.operands.truncate(new_len);
+    instructions.push(Instruction::ArraySet(idx));
+    Ok(())
+}
+
+#[inline]
+fn array_len_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && builder.ref_type_on_stack().is_some_and(|ty| {
+            (!ty.nullable || !module.config.disallow_traps())
+                && builder.is_sub_type(ValType::Ref(ty), nullable_ref(HeapType::Array))
+        })
+}
+
+fn array_len(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::I32]);
+    instructions.push(Instruction::ArrayLen);
+    Ok(())
+}
+
+#[inline]
+fn array_fill_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() || module.config.disallow_traps() {
+        // Non-trapping array.fill generation not yet implemented
+        return false;
+    }
+    match array_ref_on_stack(module, builder, 3) {
+        Some(idx) => {
+            let ty = &module.array_type(idx).0;
+            ty.mutable
+                && builder.types_on_stack(&[ValType::I32, unpack(ty.element_type), ValType::I32])
+        }
+        None => false,
+    }
+}
+
+fn array_fill(
+    _: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let idx = array_ref_on_stack(module, builder, 3).unwrap();
+    let new_len = builder.allocs.operands.len() - 4;
+    builder.allocs.operands.truncate(new_len);
+    instructions.push(Instruction::ArrayFill(idx));
+    Ok(())
+}
+
+#[inline]
+fn array_copy_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    if !module.config.gc_enabled() || module.config.disallow_traps() {
+        // Non-trapping array.copy generation not yet implemented
+        return false;
+    }
+    let (dst, src) = match (
+        array_ref_on_stack(module, builder, 4),
+        array_ref_on_stack(module, builder, 2),
+    ) {
+        (Some(dst), Some(src)) => (module.array_type(dst).0, module.array_type(src).0),
+        _ => return false,
+    };
+    let elem_types_match = match (src.element_type, dst.element_type) {
+        (StorageType::Val(src), StorageType::Val(dst)) => builder.is_sub_type(src, dst),
+        (src, dst) => src == dst,
+    };
+    dst.mutable && elem_types_match && builder.type_on_stack(ValType::I32) && {
+        let operands = builder.operands();
+        let n = operands.len();
+        [operands[n - 2], operands[n - 4]]
+            .iter()
+            .all(|ty| ty.is_none_or(|ty| ty == ValType::I32))
+    }
+}
+
+fn array_copy(
+    _: &mut Unstructured,
+    module: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    let dst = array_ref_on_stack(module, builder, 4).unwrap();
+    let src = array_ref_on_stack(module, builder, 2).unwrap();
+    let new_len = builder.allocs.operands.len() - 5;
+    builder.allocs.operands.truncate(new_len);
+    instructions.push(Instruction::ArrayCopy(dst, src));
+    Ok(())
+}
+
+#[inline]
+fn ref_i31_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled() && builder.type_on_stack(ValType::I32)
+}
+
+fn ref_i31(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    builder.pop_operands(&[ValType::I32]);
+    builder.push_operands(&[ValType::Ref(RefType {
+        nullable: false,
+        heap_type: HeapType::I31,
+    })]);
+    instructions.push(Instruction::RefI31);
+    Ok(())
+}
+
+#[inline]
+fn i31_get_valid(module: &Module, builder: &mut CodeBuilder) -> bool {
+    module.config.gc_enabled()
+        && builder.ref_type_on_stack().is_some_and(|ty| {
+            (!ty.nullable || !module.config.disallow_traps())
+                && builder.is_sub_type(ValType::Ref(ty), nullable_ref(HeapType::I31))
+        })
+}
+
+fn i31_get_s(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::I32]);
+    instructions.push(Instruction::I31GetS);
+    Ok(())
+}
+
+fn i31_get_u(
+    _: &mut Unstructured,
+    _: &Module,
+    builder: &mut CodeBuilder,
+    instructions: &mut Vec<Instruction>,
+) -> Result<()> {
+    builder.allocs.operands.pop();
+    builder.push_operands(&[ValType::I32]);
+    instructions.push(Instruction::I31GetU);
+    Ok(())
+}
+
+fn nullable_ref(heap_type: HeapType) -> ValType {
+    ValType::Ref(RefType {
+        nullable: true,
+        heap_type,
+    })
+}
+
+fn non_null_ref(type_index: u32) -> ValType {
+    ValType::Ref(RefType {
+        nullable: false,
+        heap_type: HeapType::Concrete(type_index),
+    })
+}
+
+/// The type of a value of the storage type `ty` when it's on the stack.
+fn unpack(ty: StorageType) -> ValType {
+    match ty {
+        StorageType::I8 | StorageType::I16 => ValType::I32,
+        StorageType::Val(ty) => ty,
+    }
+}
+
+fn is_numeric_storage_type(ty: StorageType) -> bool {
+    !matches!(ty, StorageType::Val(ValType::Ref(_)))
+}
+
+fn field_types_on_stack(builder: &CodeBuilder, fields: &[wasm_encoder::FieldType]) -> bool {
+    builder.operands().len() >= fields.len()
+        && builder
+            .operands()
+            .iter()
+            .rev()
+            .zip(fields.iter().rev())
+            .all(|(a, f)| a.is_none_or(|a| builder.is_sub_type(a, unpack(f.element_type))))
+}
+
+/// Returns the type index of the concrete reference `depth` operands below
+/// the top of the stack, if that operand's type is known to be one.
+///
+/// Nullable references are skipped when traps are disallowed since most
+/// instructions trap when given a null reference.
+fn concrete_ref_on_stack(module: &Module, builder: &CodeBuilder, depth: usize) -> Option<u32> {
+    let operands = builder.operands();
+    let ty = match operands.len().checked_sub(depth + 1).map(|i| operands[i]) {
+        Some(Some(ValType::Ref(ty))) => ty,
+        _ => return None,
+    };
+    if ty.nullable && module.config.disallow_traps() {
+        return None;
+    }
+    match ty.heap_type {
+        HeapType::Concrete(idx) => Some(idx),
+        _ => None,
+    }
+}
+
+fn func_ref_on_stack(module: &Module, builder: &CodeBuilder) -> Option<u32> {
+    concrete_ref_on_stack(module, builder, 0)
+        .filter(|i| matches!(module.ty(*i).composite_type, CompositeType::Func(_)))
+}
+
+fn struct_ref_on_stack(module: &Module, builder: &CodeBuilder, depth: usize) -> Option<u32> {
+    concrete_ref_on_stack(module, builder, depth)
+        .filter(|i| matches!(module.ty(*i).composite_type, CompositeType::Struct(_)))
+}
+
+fn array_ref_on_stack(module: &Module, builder: &CodeBuilder, depth: usize) -> Option<u32> {
+    concrete_ref_on_stack(module, builder, depth)
+        .filter(|i| matches!(module.ty(*i).composite_type, CompositeType::Array(_)))
+}
+
+/// All the heap types, abstract and concrete, in the same hierarchy as `ty`.
+fn heap_types_in_hierarchy(module: &Module, ty: HeapType) -> Vec<HeapType> {
+    let top = super::top_heap_type(&module.types, ty);
+    let mut heap_types = match top {
+        HeapType::Func => vec![HeapType::Func, HeapType::NoFunc],
+        HeapType::Extern => vec![HeapType::Extern, HeapType::NoExtern],
+        _ => vec![
+            HeapType::Any,
+            HeapType::Eq,
+            HeapType::I31,
+            HeapType::Struct,
+            HeapType::Array,
+            HeapType::None,
+        ],
+    };
+    heap_types.extend(
+        (0..module.types.len() as u32)
+            .map(HeapType::Concrete)
+            .filter(|ht| super::top_heap_type(&module.types, *ht) == top),
+    );
+    heap_types
+}
+
+/// Chooses the relative depth of an arbitrary label for which `is_valid`
+/// returns `true`.
+fn arbitrary_label(
+    u: &mut Unstructured,
+    builder: &CodeBuilder,
+    is_valid: impl Fn(&Control) -> bool,
+) -> Result<usize> {
+    let candidates = builder
+        .allocs
+        .controls
+        .iter()
+        .rev()
+        .enumerate()
+        .filter(|(_, l)| is_valid(l))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    Ok(*u.choose(&candidates)?)
+}
+
 fn pop_reference_type(builder: &mut CodeBuilder) -> RefType {
     if builder.type_on_stack(ValType::EXTERNREF) {
         builder.pop_operands(&[ValType::EXTERNREF]);
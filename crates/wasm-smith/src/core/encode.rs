@@ -33,11 +33,11 @@ impl Module {
         }
 
         let mut section = wasm_encoder::TypeSection::new();
-        for ty in &self.types {
-            match ty {
-                Type::Func(ty) => {
-                    section.function(ty.params.iter().cloned(), ty.results.iter().cloned());
-                }
+        for group in &self.rec_groups {
+            if group.end - group.start == 1 {
+                section.subtype(&translate_sub_type(&self.types[group.start]));
+            } else {
+                section.rec(self.types[group.clone()].iter().map(translate_sub_type));
             }
         }
         module.section(&section);
@@ -260,3 +260,17 @@ pub(crate) fn translate_entity_type(ty: &EntityType) -> wasm_encoder::EntityType
         EntityType::Global(g) => (*g).into(),
     }
 }
+
+fn translate_sub_type(ty: &SubType) -> wasm_encoder::SubType {
+    wasm_encoder::SubType {
+        is_final: ty.is_final,
+        supertype_idx: ty.supertype,
+        composite_type: match &ty.composite_type {
+            CompositeType::Func(f) => wasm_encoder::CompositeType::Func(
+                wasm_encoder::FuncType::new(f.params.iter().copied(), f.results.iter().copied()),
+            ),
+            CompositeType::Array(a) => wasm_encoder::CompositeType::Array(a.clone()),
+            CompositeType::Struct(s) => wasm_encoder::CompositeType::Struct(s.clone()),
+        },
+    }
+}
@@ -73,6 +73,27 @@ fn multi_value_disabled() {
     }
 }
 
+#[test]
+fn smoke_test_gc() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 2048];
+    for _ in 0..1024 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut cfg = SwarmConfig::arbitrary(&mut u).unwrap();
+        cfg.bulk_memory_enabled = true;
+        cfg.reference_types_enabled = true;
+        cfg.function_references_enabled = true;
+        cfg.gc_enabled = true;
+        let features = parser_features_from_config(&cfg);
+        if let Ok(module) = Module::new(cfg, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+        }
+    }
+}
+
 #[test]
 fn smoke_can_smith_valid_webassembly_one_point_oh() {
     let mut rng = SmallRng::seed_from_u64(42);
@@ -196,6 +217,50 @@ fn smoke_test_imports_config() {
     assert!(n_partial > 0);
 }
 
+#[test]
+fn smoke_test_imports_config_with_gc_types() {
+    let available = wat::parse_str(
+        r#"
+        (module
+            (type $s (struct (field i32)))
+            (type $a (array i8))
+            (import "env" "f" (func (param i32)))
+            (import "env" "g" (global (ref null $s)))
+            (import "env" "h" (func (param (ref null $a))))
+        )
+        "#,
+    )
+    .unwrap();
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut buf = vec![0; 512];
+    for _ in 0..256 {
+        rng.fill_bytes(&mut buf);
+        let mut u = Unstructured::new(&buf);
+        let mut config = SwarmConfig::arbitrary(&mut u).unwrap();
+        config.bulk_memory_enabled = true;
+        config.reference_types_enabled = true;
+        config.function_references_enabled = true;
+        config.gc_enabled = true;
+        config.available_imports = Some(available.clone());
+        let features = parser_features_from_config(&config);
+        if let Ok(module) = Module::new(config, &mut u) {
+            let wasm_bytes = module.to_bytes();
+            let mut validator = Validator::new_with_features(features);
+            validate(&mut validator, &wasm_bytes);
+
+            // Imports whose types refer to types of the example module are
+            // skipped.
+            for payload in Parser::new(0).parse_all(&wasm_bytes) {
+                if let wasmparser::Payload::ImportSection(rdr) = payload.unwrap() {
+                    for import in rdr {
+                        assert_eq!(import.unwrap().name, "f");
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[test]
 fn smoke_test_no_trapping_mode() {
     let mut rng = SmallRng::seed_from_u64(0);
@@ -298,10 +363,11 @@ fn parser_features_from_config(config: &impl Config) -> WasmFeatures {
         floats: true,
         extended_const: false,
         component_model: false,
-        function_references: false,
+        function_references: config.function_references_enabled(),
         memory_control: false,
-        gc: false,
+        gc: config.gc_enabled(),
         component_model_values: false,
+        stack_switching: false,
     }
 }
 
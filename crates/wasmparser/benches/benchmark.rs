@@ -272,6 +272,7 @@ fn define_benchmarks(c: &mut Criterion) {
             memory_control: true,
             gc: true,
             component_model_values: true,
+            stack_switching: true,
         })
     }
 
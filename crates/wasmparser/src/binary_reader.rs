@@ -35,10 +35,62 @@ pub struct BinaryReaderError {
 #[derive(Debug, Clone)]
 pub(crate) struct BinaryReaderErrorInner {
     pub(crate) message: String,
+    pub(crate) kind: ErrorKind,
     pub(crate) offset: usize,
     pub(crate) needed_hint: Option<usize>,
 }
 
+/// The category of a [`BinaryReaderError`].
+///
+/// Error messages are free-form and may change between releases, but the
+/// kind of an error is intended to be stable so tools can act on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input ended before the end of the current item.
+    UnexpectedEof,
+    /// A LEB128-encoded integer was too long or out of range.
+    MalformedLeb,
+    /// A string was not valid UTF-8.
+    MalformedUtf8,
+    /// An operand or value didn't have the type expected.
+    TypeMismatch,
+    /// An index referred to an item which isn't defined, such as an unknown
+    /// function, type, or label.
+    UnknownIndex,
+    /// The input uses a feature which isn't enabled in the validator's
+    /// [`WasmFeatures`].
+    FeatureDisabled,
+    /// The input exceeds one of the validator's limits, for example those
+    /// configured with [`ValidatorLimits`].
+    LimitExceeded,
+    /// Any other error.
+    Other,
+}
+
+impl ErrorKind {
+    /// Returns a short, kebab-case name for this kind of error, such as
+    /// `type-mismatch`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ErrorKind::UnexpectedEof => "unexpected-eof",
+            ErrorKind::MalformedLeb => "malformed-leb",
+            ErrorKind::MalformedUtf8 => "malformed-utf8",
+            ErrorKind::TypeMismatch => "type-mismatch",
+            ErrorKind::UnknownIndex => "unknown-index",
+            ErrorKind::FeatureDisabled => "feature-disabled",
+            ErrorKind::LimitExceeded => "limit-exceeded",
+            ErrorKind::Other => "other",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
 /// The result for `BinaryReader` operations.
 pub type Result<T, E = BinaryReaderError> = std::result::Result<T, E>;
 
@@ -57,10 +109,15 @@ impl fmt::Display for BinaryReaderError {
 impl BinaryReaderError {
     #[cold]
     pub(crate) fn new(message: impl Into<String>, offset: usize) -> Self {
-        let message = message.into();
+        BinaryReaderError::with_kind(ErrorKind::Other, message, offset)
+    }
+
+    #[cold]
+    pub(crate) fn with_kind(kind: ErrorKind, message: impl Into<String>, offset: usize) -> Self {
         BinaryReaderError {
             inner: Box::new(BinaryReaderErrorInner {
-                message,
+                message: message.into(),
+                kind,
                 offset,
                 needed_hint: None,
             }),
@@ -72,11 +129,17 @@ impl BinaryReaderError {
         BinaryReaderError::new(args.to_string(), offset)
     }
 
+    #[cold]
+    pub(crate) fn fmt_with_kind(kind: ErrorKind, args: fmt::Arguments<'_>, offset: usize) -> Self {
+        BinaryReaderError::with_kind(kind, args.to_string(), offset)
+    }
+
     #[cold]
     pub(crate) fn eof(offset: usize, needed_hint: usize) -> Self {
         BinaryReaderError {
             inner: Box::new(BinaryReaderErrorInner {
                 message: "unexpected end-of-file".to_string(),
+                kind: ErrorKind::UnexpectedEof,
                 offset,
                 needed_hint: Some(needed_hint),
             }),
@@ -88,6 +151,11 @@ impl BinaryReaderError {
         &self.inner.message
     }
 
+    /// Get the kind of this error.
+    pub fn kind(&self) -> ErrorKind {
+        self.inner.kind
+    }
+
     /// Get the offset within the Wasm binary where the error occurred.
     pub fn offset(&self) -> usize {
         self.inner.offset
@@ -192,7 +260,8 @@ impl<'a> BinaryReader<'a> {
     pub(crate) fn read_u7(&mut self) -> Result<u8> {
         let b = self.read_u8()?;
         if (b & 0x80) != 0 {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::MalformedLeb,
                 "invalid u7",
                 self.original_position() - 1,
             ));
@@ -425,7 +494,11 @@ impl<'a> BinaryReader<'a> {
                     "invalid var_u32: integer too large"
                 };
                 // The continuation bit or unused bits are set.
-                return Err(BinaryReaderError::new(msg, self.original_position() - 1));
+                return Err(BinaryReaderError::with_kind(
+                    ErrorKind::MalformedLeb,
+                    msg,
+                    self.original_position() - 1,
+                ));
             }
             shift += 7;
             if (byte & 0x80) == 0 {
@@ -466,7 +539,11 @@ impl<'a> BinaryReader<'a> {
                     "invalid var_u64: integer too large"
                 };
                 // The continuation bit or unused bits are set.
-                return Err(BinaryReaderError::new(msg, self.original_position() - 1));
+                return Err(BinaryReaderError::with_kind(
+                    ErrorKind::MalformedLeb,
+                    msg,
+                    self.original_position() - 1,
+                ));
             }
             shift += 7;
             if (byte & 0x80) == 0 {
@@ -537,7 +614,11 @@ impl<'a> BinaryReader<'a> {
                     } else {
                         "invalid var_i32: integer too large"
                     };
-                    return Err(BinaryReaderError::new(msg, self.original_position() - 1));
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::MalformedLeb,
+                        msg,
+                        self.original_position() - 1,
+                    ));
                 }
                 return Ok(result);
             }
@@ -571,7 +652,8 @@ impl<'a> BinaryReader<'a> {
                 let continuation_bit = (byte & 0x80) != 0;
                 let sign_and_unused_bit = (byte << 1) as i8 >> (33 - shift);
                 if continuation_bit || (sign_and_unused_bit != 0 && sign_and_unused_bit != -1) {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::MalformedLeb,
                         "invalid var_s33: integer representation too long",
                         self.original_position() - 1,
                     ));
@@ -607,7 +689,11 @@ impl<'a> BinaryReader<'a> {
                     } else {
                         "invalid var_i64: integer too large"
                     };
-                    return Err(BinaryReaderError::new(msg, self.original_position() - 1));
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::MalformedLeb,
+                        msg,
+                        self.original_position() - 1,
+                    ));
                 }
                 return Ok(result);
             }
@@ -655,7 +741,11 @@ impl<'a> BinaryReader<'a> {
         }
         let bytes = self.read_bytes(len)?;
         str::from_utf8(bytes).map_err(|_| {
-            BinaryReaderError::new("invalid UTF-8 encoding", self.original_position() - 1)
+            BinaryReaderError::with_kind(
+                ErrorKind::MalformedUtf8,
+                "invalid UTF-8 encoding",
+                self.original_position() - 1,
+            )
         })
     }
 
@@ -681,7 +771,7 @@ impl<'a> BinaryReader<'a> {
         Ok(self.buffer[self.position])
     }
 
-    fn read_block_type(&mut self) -> Result<BlockType> {
+    pub(crate) fn read_block_type(&mut self) -> Result<BlockType> {
         let b = self.peek()?;
 
         // Check for empty block
@@ -770,6 +860,7 @@ impl<'a> BinaryReader<'a> {
             0x07 => visitor.visit_catch(self.read_var_u32()?),
             0x08 => visitor.visit_throw(self.read_var_u32()?),
             0x09 => visitor.visit_rethrow(self.read_var_u32()?),
+            0x0a => visitor.visit_throw_ref(),
             0x0b => visitor.visit_end(),
             0x0c => visitor.visit_br(self.read_var_u32()?),
             0x0d => visitor.visit_br_if(self.read_var_u32()?),
@@ -799,6 +890,7 @@ impl<'a> BinaryReader<'a> {
                 }
                 visitor.visit_typed_select(self.read()?)
             }
+            0x1f => visitor.visit_try_table(self.read()?),
 
             0x20 => visitor.visit_local_get(self.read_var_u32()?),
             0x21 => visitor.visit_local_set(self.read_var_u32()?),
@@ -978,10 +1070,20 @@ impl<'a> BinaryReader<'a> {
             0xd0 => visitor.visit_ref_null(self.read()?),
             0xd1 => visitor.visit_ref_is_null(),
             0xd2 => visitor.visit_ref_func(self.read_var_u32()?),
+            0xd3 => visitor.visit_ref_eq(),
             0xd4 => visitor.visit_ref_as_non_null(),
             0xd5 => visitor.visit_br_on_null(self.read_var_u32()?),
             0xd6 => visitor.visit_br_on_non_null(self.read_var_u32()?),
 
+            0xe0 => visitor.visit_cont_new(self.read_var_u32()?),
+            0xe1 => visitor.visit_cont_bind(self.read_var_u32()?, self.read_var_u32()?),
+            0xe2 => visitor.visit_suspend(self.read_var_u32()?),
+            0xe3 => visitor.visit_resume(self.read_var_u32()?, self.read()?),
+            0xe4 => {
+                visitor.visit_resume_throw(self.read_var_u32()?, self.read_var_u32()?, self.read()?)
+            }
+            0xe5 => visitor.visit_switch(self.read_var_u32()?, self.read_var_u32()?),
+
             0xfb => self.visit_0xfb_operator(pos, visitor)?,
             0xfc => self.visit_0xfc_operator(pos, visitor)?,
             0xfd => self.visit_0xfd_operator(pos, visitor)?,
@@ -1001,6 +1103,41 @@ impl<'a> BinaryReader<'a> {
     {
         let code = self.read_var_u32()?;
         Ok(match code {
+            0x00 => visitor.visit_struct_new(self.read_var_u32()?),
+            0x01 => visitor.visit_struct_new_default(self.read_var_u32()?),
+            0x02 => visitor.visit_struct_get(self.read_var_u32()?, self.read_var_u32()?),
+            0x03 => visitor.visit_struct_get_s(self.read_var_u32()?, self.read_var_u32()?),
+            0x04 => visitor.visit_struct_get_u(self.read_var_u32()?, self.read_var_u32()?),
+            0x05 => visitor.visit_struct_set(self.read_var_u32()?, self.read_var_u32()?),
+            0x06 => visitor.visit_array_new(self.read_var_u32()?),
+            0x07 => visitor.visit_array_new_default(self.read_var_u32()?),
+            0x08 => visitor.visit_array_new_fixed(self.read_var_u32()?, self.read_var_u32()?),
+            0x09 => visitor.visit_array_new_data(self.read_var_u32()?, self.read_var_u32()?),
+            0x0a => visitor.visit_array_new_elem(self.read_var_u32()?, self.read_var_u32()?),
+            0x0b => visitor.visit_array_get(self.read_var_u32()?),
+            0x0c => visitor.visit_array_get_s(self.read_var_u32()?),
+            0x0d => visitor.visit_array_get_u(self.read_var_u32()?),
+            0x0e => visitor.visit_array_set(self.read_var_u32()?),
+            0x0f => visitor.visit_array_len(),
+            0x10 => visitor.visit_array_fill(self.read_var_u32()?),
+            0x11 => visitor.visit_array_copy(self.read_var_u32()?, self.read_var_u32()?),
+            0x12 => visitor.visit_array_init_data(self.read_var_u32()?, self.read_var_u32()?),
+            0x13 => visitor.visit_array_init_elem(self.read_var_u32()?, self.read_var_u32()?),
+            0x14 => visitor.visit_ref_test_non_null(self.read()?),
+            0x15 => visitor.visit_ref_test_nullable(self.read()?),
+            0x16 => visitor.visit_ref_cast_non_null(self.read()?),
+            0x17 => visitor.visit_ref_cast_nullable(self.read()?),
+            0x18 => {
+                let (relative_depth, from_ref_type, to_ref_type) = self.read_br_on_cast()?;
+                visitor.visit_br_on_cast(relative_depth, from_ref_type, to_ref_type)
+            }
+            0x19 => {
+                let (relative_depth, from_ref_type, to_ref_type) = self.read_br_on_cast()?;
+                visitor.visit_br_on_cast_fail(relative_depth, from_ref_type, to_ref_type)
+            }
+            0x1a => visitor.visit_any_convert_extern(),
+            0x1b => visitor.visit_extern_convert_any(),
+
             0x1c => visitor.visit_ref_i31(),
             0x1d => visitor.visit_i31_get_s(),
             0x1e => visitor.visit_i31_get_u(),
@@ -1009,6 +1146,39 @@ impl<'a> BinaryReader<'a> {
         })
     }
 
+    /// Reads the immediates of a `br_on_cast` or `br_on_cast_fail`
+    /// instruction: a flags byte, the label, and the source and target heap
+    /// types whose nullability is determined by the flags.
+    fn read_br_on_cast(&mut self) -> Result<(u32, RefType, RefType)> {
+        let pos = self.original_position();
+        let cast_flags = self.read_u8()?;
+        let relative_depth = self.read_var_u32()?;
+        let (from_nullable, to_nullable) = match cast_flags {
+            0b00 => (false, false),
+            0b01 => (true, false),
+            0b10 => (false, true),
+            0b11 => (true, true),
+            _ => bail!(pos, "invalid cast flags: {cast_flags:08b}"),
+        };
+        let pos = self.original_position();
+        let from_ref_type = RefType::new(from_nullable, self.read()?).ok_or_else(|| {
+            format_err!(
+                pos,
+                ErrorKind::LimitExceeded,
+                "implementation error: type index too large"
+            )
+        })?;
+        let pos = self.original_position();
+        let to_ref_type = RefType::new(to_nullable, self.read()?).ok_or_else(|| {
+            format_err!(
+                pos,
+                ErrorKind::LimitExceeded,
+                "implementation error: type index too large"
+            )
+        })?;
+        Ok((relative_depth, from_ref_type, to_ref_type))
+    }
+
     fn visit_0xfc_operator<T>(
         &mut self,
         pos: usize,
@@ -0,0 +1,691 @@
+/* Copyright 2024 Mozilla Foundation
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::{
+    BlockType, ComponentAlias, ComponentExternalKind, ComponentType, ComponentTypeDeclaration,
+    ComponentTypeRef, CompositeType, ConstExpr, CoreType, DataKind, ElementItems, ElementKind,
+    Encoding, ExternalKind, FunctionBody, HeapType, InstanceTypeDeclaration, MemoryType,
+    ModuleTypeDeclaration, Parser, Payload, RefType, Result, StorageType, SubType, TableInit,
+    TableType, TypeRef, ValType, VisitOperator, WasmFeatures,
+};
+
+/// The WebAssembly features used by a module or component, as computed by
+/// [`required_features`].
+#[derive(Debug, Clone)]
+pub struct RequiredFeatures {
+    /// The minimal set of features which must be enabled for the input to
+    /// validate.
+    pub features: WasmFeatures,
+    /// The first use of each feature enabled in `features`, in the order that
+    /// they appear in the input.
+    pub uses: Vec<FeatureUse>,
+}
+
+/// The first use of a WebAssembly feature within a module or component.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FeatureUse {
+    /// The name of the feature, as returned by [`WasmFeatures::names`].
+    pub feature: &'static str,
+    /// The byte offset, within the input, of the item using the feature.
+    pub offset: usize,
+    /// The index of the function whose body uses the feature, if it's used
+    /// within a function body.
+    pub func: Option<u32>,
+}
+
+/// Computes the set of WebAssembly features used by the module or component
+/// in `wasm`.
+///
+/// This walks the types, imports, tables, memories, globals, element and data
+/// segments and instructions of each module, recursing into nested modules and
+/// components, and records every proposal they rely on. Features which other
+/// features build upon, such as `reference-types` for `gc`, are recorded as
+/// well, so the resulting [`WasmFeatures`] can be handed directly to a
+/// [`Validator`](crate::Validator).
+///
+/// The input is only parsed, not validated, so the result for an invalid input
+/// is unspecified. An error is returned if the input fails to parse.
+pub fn required_features(wasm: &[u8]) -> Result<RequiredFeatures> {
+    let mut detector = Detector {
+        required: RequiredFeatures {
+            features: WasmFeatures::none(),
+            uses: Vec::new(),
+        },
+        modules: Vec::new(),
+    };
+    for payload in Parser::new(0).parse_all(wasm) {
+        detector.payload(payload?)?;
+    }
+    Ok(detector.required)
+}
+
+struct Detector {
+    required: RequiredFeatures,
+    /// State of each module or component currently being parsed, innermost
+    /// last.
+    modules: Vec<ModuleState>,
+}
+
+#[derive(Default)]
+struct ModuleState {
+    /// Whether each function type has any results, indexed by type index.
+    type_has_results: Vec<bool>,
+    /// Whether each global is mutable, indexed by global index.
+    global_mutability: Vec<bool>,
+    num_imported_globals: u32,
+    num_imported_funcs: u32,
+    num_tables: u32,
+    num_memories: u32,
+    num_code_entries: u32,
+}
+
+impl Detector {
+    fn module(&mut self) -> &mut ModuleState {
+        self.modules.last_mut().unwrap()
+    }
+
+    /// Records that `feature` is used at `offset`, along with any features it
+    /// builds upon.
+    fn uses(&mut self, feature: &'static str, offset: usize, func: Option<u32>) {
+        let enabled = self.required.features.get_mut(feature).unwrap();
+        if *enabled {
+            return;
+        }
+        *enabled = true;
+        self.required.uses.push(FeatureUse {
+            feature,
+            offset,
+            func,
+        });
+        let prerequisite = match feature {
+            "function-references" | "gc" => "reference-types",
+            "relaxed-simd" => "simd",
+            "component-model-values" => "component-model",
+            _ => return,
+        };
+        self.uses(prerequisite, offset, func);
+    }
+
+    fn payload(&mut self, payload: Payload<'_>) -> Result<()> {
+        match payload {
+            Payload::Version {
+                encoding, range, ..
+            } => {
+                if encoding == Encoding::Component {
+                    self.uses("component-model", range.start, None);
+                }
+                self.modules.push(ModuleState::default());
+            }
+            Payload::End(_) => {
+                self.modules.pop();
+            }
+
+            Payload::TypeSection(s) => {
+                for group in s.into_iter_with_offsets() {
+                    let (offset, group) = group?;
+                    if group.is_explicit_rec_group() {
+                        self.uses("gc", offset, None);
+                    }
+                    for ty in group.into_types() {
+                        self.sub_type(&ty, offset);
+                    }
+                }
+            }
+            Payload::ImportSection(s) => {
+                for import in s.into_iter_with_offsets() {
+                    let (offset, import) = import?;
+                    self.import(import.ty, offset);
+                }
+            }
+            Payload::TableSection(s) => {
+                for table in s.into_iter_with_offsets() {
+                    let (offset, table) = table?;
+                    self.module().num_tables += 1;
+                    self.table_type(&table.ty, offset);
+                    if let TableInit::Expr(expr) = &table.init {
+                        self.uses("function-references", offset, None);
+                        self.const_expr(expr)?;
+                    }
+                }
+            }
+            Payload::MemorySection(s) => {
+                for ty in s.into_iter_with_offsets() {
+                    let (offset, ty) = ty?;
+                    self.module().num_memories += 1;
+                    self.memory_type(&ty, offset);
+                }
+            }
+            Payload::TagSection(s) => {
+                for ty in s.into_iter_with_offsets() {
+                    let (offset, ty) = ty?;
+                    self.uses("exception-handling", offset, None);
+                    self.tag_type(ty.func_type_idx, offset);
+                }
+            }
+            Payload::GlobalSection(s) => {
+                for global in s.into_iter_with_offsets() {
+                    let (offset, global) = global?;
+                    self.val_type(global.ty.content_type, offset, None);
+                    self.module().global_mutability.push(global.ty.mutable);
+                    self.const_expr(&global.init_expr)?;
+                }
+            }
+            Payload::ExportSection(s) => {
+                for export in s.into_iter_with_offsets() {
+                    let (offset, export) = export?;
+                    if export.kind != ExternalKind::Global {
+                        continue;
+                    }
+                    let mutable = self
+                        .module()
+                        .global_mutability
+                        .get(export.index as usize)
+                        .copied()
+                        .unwrap_or(false);
+                    if mutable {
+                        self.uses("mutable-global", offset, None);
+                    }
+                }
+            }
+            Payload::ElementSection(s) => {
+                for element in s.into_iter_with_offsets() {
+                    let (offset, element) = element?;
+                    match &element.kind {
+                        ElementKind::Active { offset_expr, .. } => self.const_expr(offset_expr)?,
+                        ElementKind::Passive | ElementKind::Declared => {
+                            self.uses("bulk-memory", offset, None);
+                        }
+                    }
+                    if let ElementItems::Expressions(ty, items) = element.items {
+                        self.ref_type(ty, offset, None);
+                        for item in items {
+                            self.const_expr(&item?)?;
+                        }
+                    }
+                }
+            }
+            Payload::DataCountSection { range, .. } => {
+                self.uses("bulk-memory", range.start, None);
+            }
+            Payload::DataSection(s) => {
+                for data in s.into_iter_with_offsets() {
+                    let (offset, data) = data?;
+                    match &data.kind {
+                        DataKind::Passive => self.uses("bulk-memory", offset, None),
+                        DataKind::Active {
+                            memory_index,
+                            offset_expr,
+                        } => {
+                            if *memory_index != 0 {
+                                self.uses("multi-memory", offset, None);
+                            }
+                            self.const_expr(offset_expr)?;
+                        }
+                    }
+                }
+            }
+            Payload::CodeSectionEntry(body) => self.func_body(&body)?,
+
+            Payload::CoreTypeSection(s) => {
+                for ty in s.into_iter_with_offsets() {
+                    let (offset, ty) = ty?;
+                    self.core_type(&ty, offset);
+                }
+            }
+            Payload::ComponentTypeSection(s) => {
+                for ty in s.into_iter_with_offsets() {
+                    let (offset, ty) = ty?;
+                    self.component_type(&ty, offset);
+                }
+            }
+            Payload::ComponentImportSection(s) => {
+                for import in s.into_iter_with_offsets() {
+                    let (offset, import) = import?;
+                    self.component_type_ref(import.ty, offset);
+                }
+            }
+            Payload::ComponentExportSection(s) => {
+                for export in s.into_iter_with_offsets() {
+                    let (offset, export) = export?;
+                    if export.kind == ComponentExternalKind::Value {
+                        self.uses("component-model-values", offset, None);
+                    }
+                }
+            }
+            Payload::ComponentAliasSection(s) => {
+                for alias in s.into_iter_with_offsets() {
+                    let (offset, alias) = alias?;
+                    self.component_alias(&alias, offset);
+                }
+            }
+            Payload::ComponentStartSection { range, .. } => {
+                self.uses("component-model-values", range.start, None);
+            }
+
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Records the features used by the type at the next index in the
+    /// current module's type index space.
+    fn sub_type(&mut self, ty: &SubType, offset: usize) {
+        let index = self.module().type_has_results.len() as u32;
+        if !ty.is_final || ty.supertype_idx.is_some() {
+            self.uses("gc", offset, None);
+        }
+        let mut has_results = false;
+        let mut storage_types = Vec::new();
+        match &ty.composite_type {
+            CompositeType::Func(f) => {
+                if f.results().len() > 1 {
+                    self.uses("multi-value", offset, None);
+                }
+                has_results = !f.results().is_empty();
+                storage_types.extend(
+                    f.params()
+                        .iter()
+                        .chain(f.results())
+                        .map(|ty| StorageType::Val(*ty)),
+                );
+            }
+            CompositeType::Array(a) => {
+                self.uses("gc", offset, None);
+                storage_types.push(a.0.element_type);
+            }
+            CompositeType::Struct(s) => {
+                self.uses("gc", offset, None);
+                storage_types.extend(s.fields.iter().map(|f| f.element_type));
+            }
+            CompositeType::Cont(_) => self.uses("stack-switching", offset, None),
+        }
+        for ty in storage_types {
+            let StorageType::Val(ty) = ty else { continue };
+            self.val_type(ty, offset, None);
+            // Referring to a type which isn't defined yet, including the type
+            // itself, requires the recursive types of the gc proposal.
+            if let ValType::Ref(r) = ty {
+                if let HeapType::Concrete(i) = r.heap_type() {
+                    if !matches!(i.as_module_index(), Some(i) if i < index) {
+                        self.uses("gc", offset, None);
+                    }
+                }
+            }
+        }
+        self.module().type_has_results.push(has_results);
+    }
+
+    fn import(&mut self, ty: TypeRef, offset: usize) {
+        match ty {
+            TypeRef::Func(_) => self.module().num_imported_funcs += 1,
+            TypeRef::Table(ty) => {
+                self.module().num_tables += 1;
+                self.table_type(&ty, offset);
+            }
+            TypeRef::Memory(ty) => {
+                self.module().num_memories += 1;
+                self.memory_type(&ty, offset);
+            }
+            TypeRef::Global(ty) => {
+                if ty.mutable {
+                    self.uses("mutable-global", offset, None);
+                }
+                self.val_type(ty.content_type, offset, None);
+                let module = self.module();
+                module.global_mutability.push(ty.mutable);
+                module.num_imported_globals += 1;
+            }
+            TypeRef::Tag(ty) => self.tag_type(ty.func_type_idx, offset),
+        }
+    }
+
+    fn core_type(&mut self, ty: &CoreType<'_>, offset: usize) {
+        let decls = match ty {
+            CoreType::Sub(ty) => return self.sub_type(ty, offset),
+            CoreType::Module(decls) => decls,
+        };
+        self.modules.push(ModuleState::default());
+        for decl in decls.iter() {
+            match decl {
+                ModuleTypeDeclaration::Type(ty) => self.sub_type(ty, offset),
+                ModuleTypeDeclaration::Import(import) => self.import(import.ty, offset),
+                ModuleTypeDeclaration::Export { ty, .. } => match *ty {
+                    TypeRef::Func(_) => {}
+                    TypeRef::Table(ty) => self.table_type(&ty, offset),
+                    TypeRef::Memory(ty) => self.memory_type(&ty, offset),
+                    TypeRef::Global(ty) => {
+                        if ty.mutable {
+                            self.uses("mutable-global", offset, None);
+                        }
+                        self.val_type(ty.content_type, offset, None);
+                    }
+                    TypeRef::Tag(ty) => self.tag_type(ty.func_type_idx, offset),
+                },
+                ModuleTypeDeclaration::OuterAlias { .. } => {
+                    self.module().type_has_results.push(false);
+                }
+            }
+        }
+        self.modules.pop();
+    }
+
+    fn component_type(&mut self, ty: &ComponentType<'_>, offset: usize) {
+        match ty {
+            ComponentType::Defined(_) | ComponentType::Func(_) => {}
+            ComponentType::Resource { rep, .. } => self.val_type(*rep, offset, None),
+            ComponentType::Component(decls) => {
+                for decl in decls.iter() {
+                    match decl {
+                        ComponentTypeDeclaration::CoreType(ty) => self.core_type(ty, offset),
+                        ComponentTypeDeclaration::Type(ty) => self.component_type(ty, offset),
+                        ComponentTypeDeclaration::Alias(alias) => {
+                            self.component_alias(alias, offset)
+                        }
+                        ComponentTypeDeclaration::Export { ty, .. } => {
+                            self.component_type_ref(*ty, offset)
+                        }
+                        ComponentTypeDeclaration::Import(import) => {
+                            self.component_type_ref(import.ty, offset)
+                        }
+                    }
+                }
+            }
+            ComponentType::Instance(decls) => {
+                for decl in decls.iter() {
+                    match decl {
+                        InstanceTypeDeclaration::CoreType(ty) => self.core_type(ty, offset),
+                        InstanceTypeDeclaration::Type(ty) => self.component_type(ty, offset),
+                        InstanceTypeDeclaration::Alias(alias) => {
+                            self.component_alias(alias, offset)
+                        }
+                        InstanceTypeDeclaration::Export { ty, .. } => {
+                            self.component_type_ref(*ty, offset)
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    fn component_type_ref(&mut self, ty: ComponentTypeRef, offset: usize) {
+        if let ComponentTypeRef::Value(_) = ty {
+            self.uses("component-model-values", offset, None);
+        }
+    }
+
+    fn component_alias(&mut self, alias: &ComponentAlias<'_>, offset: usize) {
+        let kind = match alias {
+            ComponentAlias::InstanceExport { kind, .. } => *kind,
+            ComponentAlias::CoreInstanceExport { .. } | ComponentAlias::Outer { .. } => return,
+        };
+        if kind == ComponentExternalKind::Value {
+            self.uses("component-model-values", offset, None);
+        }
+    }
+
+    fn val_type(&mut self, ty: ValType, offset: usize, func: Option<u32>) {
+        match ty {
+            ValType::I32 | ValType::I64 => {}
+            ValType::F32 | ValType::F64 => self.uses("floats", offset, func),
+            ValType::V128 => self.uses("simd", offset, func),
+            ValType::Ref(r) => self.ref_type(r, offset, func),
+        }
+    }
+
+    fn ref_type(&mut self, ty: RefType, offset: usize, func: Option<u32>) {
+        self.uses("reference-types", offset, func);
+        if !ty.is_nullable() {
+            self.uses("function-references", offset, func);
+        }
+        self.heap_type(ty.heap_type(), offset, func);
+    }
+
+    fn heap_type(&mut self, ty: HeapType, offset: usize, func: Option<u32>) {
+        match ty {
+            HeapType::Func | HeapType::Extern => {}
+            HeapType::Concrete(_) => self.uses("function-references", offset, func),
+            HeapType::Any
+            | HeapType::None
+            | HeapType::Eq
+            | HeapType::Struct
+            | HeapType::Array
+            | HeapType::I31
+            | HeapType::NoExtern
+            | HeapType::NoFunc => self.uses("gc", offset, func),
+            HeapType::Cont | HeapType::NoCont => self.uses("stack-switching", offset, func),
+            HeapType::Exn => self.uses("exception-handling", offset, func),
+        }
+    }
+
+    fn table_type(&mut self, ty: &TableType, offset: usize) {
+        if self.module().num_tables > 1 {
+            self.uses("reference-types", offset, None);
+        }
+        if ty.element_type != RefType::FUNCREF {
+            self.ref_type(ty.element_type, offset, None);
+        }
+    }
+
+    fn memory_type(&mut self, ty: &MemoryType, offset: usize) {
+        if self.module().num_memories > 1 {
+            self.uses("multi-memory", offset, None);
+        }
+        if ty.memory64 {
+            self.uses("memory64", offset, None);
+        }
+        if ty.shared {
+            self.uses("threads", offset, None);
+        }
+    }
+
+    fn tag_type(&mut self, type_index: u32, offset: usize) {
+        let has_results = self
+            .module()
+            .type_has_results
+            .get(type_index as usize)
+            .copied()
+            .unwrap_or(false);
+        if has_results {
+            self.uses("stack-switching", offset, None);
+        } else {
+            self.uses("exception-handling", offset, None);
+        }
+    }
+
+    fn const_expr(&mut self, expr: &ConstExpr<'_>) -> Result<()> {
+        let mut reader = expr.get_operators_reader();
+        let mut visitor = OperatorDetector {
+            detector: self,
+            offset: 0,
+            func: None,
+        };
+        while !reader.eof() {
+            visitor.offset = reader.original_position();
+            reader.visit_operator(&mut visitor)?;
+        }
+        Ok(())
+    }
+
+    fn func_body(&mut self, body: &FunctionBody<'_>) -> Result<()> {
+        let module = self.module();
+        let func = module.num_imported_funcs + module.num_code_entries;
+        module.num_code_entries += 1;
+
+        let mut locals = body.get_locals_reader()?;
+        for _ in 0..locals.get_count() {
+            let offset = locals.original_position();
+            let (_, ty) = locals.read()?;
+            self.val_type(ty, offset, Some(func));
+        }
+
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        let mut visitor = OperatorDetector {
+            detector: self,
+            offset: 0,
+            func: Some(func),
+        };
+        while !reader.eof() {
+            visitor.offset = reader.original_position();
+            reader.visit_operator(&mut visitor)?;
+        }
+        Ok(())
+    }
+}
+
+/// Records the features used by individual instructions, either in a function
+/// body or, if `func` is `None`, in a constant expression.
+struct OperatorDetector<'a> {
+    detector: &'a mut Detector,
+    offset: usize,
+    func: Option<u32>,
+}
+
+impl OperatorDetector<'_> {
+    fn uses(&mut self, feature: &'static str) {
+        self.detector.uses(feature, self.offset, self.func);
+    }
+
+    fn block_type(&mut self, ty: BlockType) {
+        match ty {
+            BlockType::Empty => {}
+            BlockType::Type(ty) => self.detector.val_type(ty, self.offset, self.func),
+            BlockType::FuncType(_) => self.uses("multi-value"),
+        }
+    }
+
+    /// Records the features required by the operator called `op` beyond the
+    /// proposal which introduced it.
+    fn op(&mut self, op: &str) {
+        if op.contains("F32") || op.contains("F64") {
+            self.uses("floats");
+        }
+        // Arithmetic in constant expressions is part of the extended-const
+        // proposal, as is `global.get` of a non-imported global in gc.
+        if self.func.is_none() {
+            match op {
+                "I32Add" | "I32Sub" | "I32Mul" | "I64Add" | "I64Sub" | "I64Mul" => {
+                    self.uses("extended-const")
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+macro_rules! define_visit_operator {
+    ($(@$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
+        $(
+            fn $visit(&mut self $($(,$arg: $argty)*)?) {
+                feature!(self $proposal);
+                self.op(stringify!($op));
+                $($(
+                    visit_arg!(self $arg $arg);
+                )*)?
+            }
+        )*
+    }
+}
+
+macro_rules! feature {
+    ($self:ident mvp) => {};
+    ($self:ident exceptions) => {
+        $self.uses("exception-handling")
+    };
+    ($self:ident sign_extension) => {
+        $self.uses("sign-extension")
+    };
+    ($self:ident saturating_float_to_int) => {
+        $self.uses("saturating-float-to-int")
+    };
+    ($self:ident bulk_memory) => {
+        $self.uses("bulk-memory")
+    };
+    ($self:ident reference_types) => {
+        $self.uses("reference-types")
+    };
+    ($self:ident simd) => {
+        $self.uses("simd")
+    };
+    ($self:ident relaxed_simd) => {
+        $self.uses("relaxed-simd")
+    };
+    ($self:ident threads) => {
+        $self.uses("threads")
+    };
+    ($self:ident tail_call) => {
+        $self.uses("tail-call")
+    };
+    ($self:ident function_references) => {
+        $self.uses("function-references")
+    };
+    ($self:ident gc) => {
+        $self.uses("gc")
+    };
+    ($self:ident memory_control) => {
+        $self.uses("memory-control")
+    };
+    ($self:ident stack_switching) => {
+        $self.uses("stack-switching")
+    };
+}
+
+macro_rules! visit_arg {
+    ($self:ident blockty $arg:ident) => {
+        $self.block_type($arg)
+    };
+    ($self:ident try_table $arg:ident) => {
+        $self.block_type($arg.ty)
+    };
+    ($self:ident ty $arg:ident) => {
+        $self.detector.val_type($arg, $self.offset, $self.func)
+    };
+    ($self:ident hty $arg:ident) => {
+        $self.detector.heap_type($arg, $self.offset, $self.func)
+    };
+    ($self:ident from_ref_type $arg:ident) => {
+        $self.detector.ref_type($arg, $self.offset, $self.func)
+    };
+    ($self:ident to_ref_type $arg:ident) => {
+        $self.detector.ref_type($arg, $self.offset, $self.func)
+    };
+    ($self:ident table_byte $arg:ident) => {
+        if $arg != 0 {
+            $self.uses("reference-types")
+        }
+    };
+    ($self:ident mem_byte $arg:ident) => {
+        if $arg != 0 {
+            $self.uses("multi-memory")
+        }
+    };
+    ($self:ident global_index $arg:ident) => {
+        if $self.func.is_none() {
+            let module = $self.detector.module();
+            if $arg >= module.num_imported_globals {
+                $self.uses("gc")
+            }
+        }
+    };
+    ($self:ident $name:ident $arg:ident) => {
+        let _ = $arg;
+    };
+}
+
+impl<'a> VisitOperator<'a> for OperatorDetector<'_> {
+    type Output = ();
+
+    for_each_operator!(define_visit_operator);
+}
@@ -53,6 +53,7 @@
 /// - `@simd`: [Wasm `simd` proposal]
 /// - `@relaxed_simd`: [Wasm `relaxed-simd` proposal]
 /// - `@gc`: [Wasm `gc` proposal]
+/// - `@stack_switching`: [Wasm `stack-switching` proposal]
 ///
 /// [Wasm `expection-handling` proposal]:
 /// https://github.com/WebAssembly/exception-handling
@@ -84,6 +85,9 @@
 /// [Wasm `gc` proposal]:
 /// https://github.com/WebAssembly/gc
 ///
+/// [Wasm `stack-switching` proposal]:
+/// https://github.com/WebAssembly/stack-switching
+///
 /// ```
 /// macro_rules! define_visit_operator {
 ///     // The outer layer of repetition represents how all operators are
@@ -133,7 +137,9 @@ macro_rules! for_each_operator {
             @exceptions Catch { tag_index: u32 } => visit_catch
             @exceptions Throw { tag_index: u32 } => visit_throw
             @exceptions Rethrow { relative_depth: u32 } => visit_rethrow
+            @exceptions ThrowRef => visit_throw_ref
             @mvp End => visit_end
+            @exceptions TryTable { try_table: $crate::TryTable } => visit_try_table
             @mvp Br { relative_depth: u32 } => visit_br
             @mvp BrIf { relative_depth: u32 } => visit_br_if
             @mvp BrTable { targets: $crate::BrTable<'a> } => visit_br_table
@@ -316,6 +322,43 @@ macro_rules! for_each_operator {
             // 0xFB prefixed operators
             // Garbage Collection
             // http://github.com/WebAssembly/gc
+            @gc RefEq => visit_ref_eq
+            @gc StructNew { struct_type_index: u32 } => visit_struct_new
+            @gc StructNewDefault { struct_type_index: u32 } => visit_struct_new_default
+            @gc StructGet { struct_type_index: u32, field_index: u32 } => visit_struct_get
+            @gc StructGetS { struct_type_index: u32, field_index: u32 } => visit_struct_get_s
+            @gc StructGetU { struct_type_index: u32, field_index: u32 } => visit_struct_get_u
+            @gc StructSet { struct_type_index: u32, field_index: u32 } => visit_struct_set
+            @gc ArrayNew { array_type_index: u32 } => visit_array_new
+            @gc ArrayNewDefault { array_type_index: u32 } => visit_array_new_default
+            @gc ArrayNewFixed { array_type_index: u32, array_size: u32 } => visit_array_new_fixed
+            @gc ArrayNewData { array_type_index: u32, array_data_index: u32 } => visit_array_new_data
+            @gc ArrayNewElem { array_type_index: u32, array_elem_index: u32 } => visit_array_new_elem
+            @gc ArrayGet { array_type_index: u32 } => visit_array_get
+            @gc ArrayGetS { array_type_index: u32 } => visit_array_get_s
+            @gc ArrayGetU { array_type_index: u32 } => visit_array_get_u
+            @gc ArraySet { array_type_index: u32 } => visit_array_set
+            @gc ArrayLen => visit_array_len
+            @gc ArrayFill { array_type_index: u32 } => visit_array_fill
+            @gc ArrayCopy { array_type_index_dst: u32, array_type_index_src: u32 } => visit_array_copy
+            @gc ArrayInitData { array_type_index: u32, array_data_index: u32 } => visit_array_init_data
+            @gc ArrayInitElem { array_type_index: u32, array_elem_index: u32 } => visit_array_init_elem
+            @gc RefTestNonNull { hty: $crate::HeapType } => visit_ref_test_non_null
+            @gc RefTestNullable { hty: $crate::HeapType } => visit_ref_test_nullable
+            @gc RefCastNonNull { hty: $crate::HeapType } => visit_ref_cast_non_null
+            @gc RefCastNullable { hty: $crate::HeapType } => visit_ref_cast_nullable
+            @gc BrOnCast {
+                relative_depth: u32,
+                from_ref_type: $crate::RefType,
+                to_ref_type: $crate::RefType
+            } => visit_br_on_cast
+            @gc BrOnCastFail {
+                relative_depth: u32,
+                from_ref_type: $crate::RefType,
+                to_ref_type: $crate::RefType
+            } => visit_br_on_cast_fail
+            @gc AnyConvertExtern => visit_any_convert_extern
+            @gc ExternConvertAny => visit_extern_convert_any
             @gc RefI31 => visit_ref_i31
             @gc I31GetS => visit_i31_get_s
             @gc I31GetU => visit_i31_get_u
@@ -698,27 +741,44 @@ macro_rules! for_each_operator {
             @function_references RefAsNonNull => visit_ref_as_non_null
             @function_references BrOnNull { relative_depth: u32 } => visit_br_on_null
             @function_references BrOnNonNull { relative_depth: u32 } => visit_br_on_non_null
+
+            // Stack switching
+            @stack_switching ContNew { cont_type_index: u32 } => visit_cont_new
+            @stack_switching ContBind { argument_index: u32, result_index: u32 } => visit_cont_bind
+            @stack_switching Suspend { tag_index: u32 } => visit_suspend
+            @stack_switching Resume { cont_type_index: u32, resume_table: $crate::ResumeTable } => visit_resume
+            @stack_switching ResumeThrow { cont_type_index: u32, tag_index: u32, resume_table: $crate::ResumeTable } => visit_resume_throw
+            @stack_switching Switch { cont_type_index: u32, tag_index: u32 } => visit_switch
         }
     };
 }
 
 macro_rules! format_err {
+    ($offset:expr, ErrorKind::$kind:ident, $($arg:tt)*) => {
+        crate::BinaryReaderError::fmt_with_kind(
+            crate::ErrorKind::$kind,
+            format_args!($($arg)*),
+            $offset,
+        )
+    };
     ($offset:expr, $($arg:tt)*) => {
         crate::BinaryReaderError::fmt(format_args!($($arg)*), $offset)
-    }
+    };
 }
 
 macro_rules! bail {
     ($($arg:tt)*) => {return Err(format_err!($($arg)*))}
 }
 
-pub use crate::binary_reader::{BinaryReader, BinaryReaderError, Result};
+pub use crate::binary_reader::{BinaryReader, BinaryReaderError, ErrorKind, Result};
+pub use crate::features::*;
 pub use crate::parser::*;
 pub use crate::readers::*;
 pub use crate::resources::*;
 pub use crate::validator::*;
 
 mod binary_reader;
+mod features;
 mod limits;
 mod parser;
 mod readers;
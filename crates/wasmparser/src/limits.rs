@@ -35,6 +35,8 @@ pub const MAX_WASM_TABLES: usize = 100;
 pub const MAX_WASM_MEMORIES: usize = 100;
 pub const MAX_WASM_TAGS: usize = 1_000_000;
 pub const MAX_WASM_BR_TABLE_SIZE: usize = MAX_WASM_FUNCTION_SIZE;
+pub const MAX_WASM_HANDLERS: usize = 10_000;
+pub const MAX_WASM_CATCHES: usize = 10_000;
 pub const MAX_WASM_STRUCT_FIELDS: usize = 10_000;
 
 // Component-related limits
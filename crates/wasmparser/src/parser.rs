@@ -4,9 +4,10 @@ use crate::{
     limits::MAX_WASM_MODULE_SIZE, BinaryReader, BinaryReaderError, ComponentCanonicalSectionReader,
     ComponentExportSectionReader, ComponentImportSectionReader, ComponentInstanceSectionReader,
     ComponentStartFunction, ComponentTypeSectionReader, CustomSectionReader, DataSectionReader,
-    ElementSectionReader, ExportSectionReader, FromReader, FunctionBody, FunctionSectionReader,
-    GlobalSectionReader, ImportSectionReader, InstanceSectionReader, MemorySectionReader, Result,
-    SectionLimited, TableSectionReader, TagSectionReader, TypeSectionReader,
+    ElementSectionReader, ErrorKind, ExportSectionReader, FromReader, FunctionBody,
+    FunctionSectionReader, GlobalSectionReader, ImportSectionReader, InstanceSectionReader,
+    MemorySectionReader, Result, SectionLimited, TableSectionReader, TagSectionReader,
+    TypeSectionReader,
 };
 use std::convert::TryInto;
 use std::fmt;
@@ -1053,7 +1054,13 @@ fn delimited<'a, T>(
         .and_then(|i| len.checked_sub(i))
     {
         Some(i) => i,
-        None => return Err(BinaryReaderError::new("unexpected end-of-file", start)),
+        None => {
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::UnexpectedEof,
+                "unexpected end-of-file",
+                start,
+            ))
+        }
     };
     Ok(ret)
 }
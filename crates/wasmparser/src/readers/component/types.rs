@@ -490,6 +490,15 @@ pub enum ComponentDefinedType<'a> {
     Own(u32),
     /// A borrowed handle to a resource.
     Borrow(u32),
+    /// A future which resolves to a value of the given type, if any.
+    Future(Option<ComponentValType>),
+    /// A stream of values.
+    Stream {
+        /// The type of the elements of the stream, if any.
+        element: Option<ComponentValType>,
+        /// The type of the value produced when the stream ends, if any.
+        end: Option<ComponentValType>,
+    },
 }
 
 impl<'a> ComponentDefinedType<'a> {
@@ -529,6 +538,11 @@ impl<'a> ComponentDefinedType<'a> {
             },
             0x69 => ComponentDefinedType::Own(reader.read()?),
             0x68 => ComponentDefinedType::Borrow(reader.read()?),
+            0x66 => ComponentDefinedType::Stream {
+                element: reader.read()?,
+                end: reader.read()?,
+            },
+            0x65 => ComponentDefinedType::Future(reader.read()?),
             x => return reader.invalid_leading_byte(x, "component defined type"),
         })
     }
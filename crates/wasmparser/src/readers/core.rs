@@ -1,3 +1,4 @@
+mod branch_hinting;
 mod code;
 mod coredumps;
 mod custom;
@@ -13,10 +14,12 @@ mod memories;
 mod names;
 mod operators;
 mod producers;
+mod references;
 mod tables;
 mod tags;
 mod types;
 
+pub use self::branch_hinting::*;
 pub use self::code::*;
 pub use self::coredumps::*;
 pub use self::custom::*;
@@ -32,6 +35,7 @@ pub use self::memories::*;
 pub use self::names::*;
 pub use self::operators::*;
 pub use self::producers::*;
+pub use self::references::*;
 pub use self::tables::*;
 pub use self::tags::*;
 pub use self::types::*;
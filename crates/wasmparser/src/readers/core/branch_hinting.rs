@@ -0,0 +1,81 @@
+use crate::{BinaryReader, FromReader, Result, SectionLimited};
+
+/// A reader for the `metadata.code.branch_hint` custom section of a
+/// WebAssembly module.
+///
+/// This section is defined by the [branch hinting proposal] and records, for
+/// individual functions, whether conditional branches at particular offsets
+/// within their bodies are likely to be taken or not.
+///
+/// [branch hinting proposal]: https://github.com/WebAssembly/branch-hinting
+///
+/// # Examples
+///
+/// ```
+/// # let data: &[u8] = &[0x01, 0x00, 0x01, 0x05, 0x01, 0x01];
+/// use wasmparser::{BranchHintSectionReader, Result};
+/// let reader = BranchHintSectionReader::new(data, 0).expect("branch hint reader");
+/// let func = reader.into_iter().next().unwrap().expect("branch hint function");
+/// assert_eq!(func.func, 0);
+/// let hints = func.hints.into_iter().collect::<Result<Vec<_>>>().expect("hints");
+/// assert_eq!(hints.len(), 1);
+/// assert_eq!(hints[0].func_offset, 5);
+/// assert!(hints[0].taken);
+/// ```
+pub type BranchHintSectionReader<'a> = SectionLimited<'a, BranchHintFunction<'a>>;
+
+/// The branch hints for a single function.
+#[derive(Debug, Clone)]
+pub struct BranchHintFunction<'a> {
+    /// The function index that these hints apply to.
+    pub func: u32,
+    /// The hints for this function, in increasing order of offset.
+    pub hints: SectionLimited<'a, BranchHint>,
+}
+
+impl<'a> FromReader<'a> for BranchHintFunction<'a> {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        let func = reader.read_var_u32()?;
+        let hints = reader.skip(|reader| {
+            // The hints of each function are prefixed with their count rather
+            // than their size, so they have to be parsed to find where the
+            // next function's hints begin.
+            for _ in 0..reader.read_var_u32()? {
+                reader.read::<BranchHint>()?;
+            }
+            Ok(())
+        })?;
+        Ok(BranchHintFunction {
+            func,
+            hints: SectionLimited::new(hints.remaining_buffer(), hints.original_position())?,
+        })
+    }
+}
+
+/// A hint for a single branch instruction.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BranchHint {
+    /// The byte offset, relative to the start of the function body, of the
+    /// branch instruction that this hint applies to.
+    pub func_offset: u32,
+    /// Whether the branch is likely to be taken.
+    pub taken: bool,
+}
+
+impl<'a> FromReader<'a> for BranchHint {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        let func_offset = reader.read_var_u32()?;
+        let offset = reader.original_position();
+        let size = reader.read_var_u32()?;
+        if size != 1 {
+            bail!(offset, "invalid branch hint size: {size}");
+        }
+        let offset = reader.original_position();
+        let taken = match reader.read_u8()? {
+            0 => false,
+            1 => true,
+            n => bail!(offset, "invalid branch hint value: {n}"),
+        };
+        Ok(BranchHint { func_offset, taken })
+    }
+}
@@ -13,7 +13,8 @@
  * limitations under the License.
  */
 
-use crate::{BinaryReader, BinaryReaderError, Result, ValType};
+use crate::limits::{MAX_WASM_CATCHES, MAX_WASM_HANDLERS};
+use crate::{BinaryReader, BinaryReaderError, FromReader, Result, ValType};
 
 /// Represents a block type.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -62,6 +63,125 @@ pub struct BrTable<'a> {
     pub(crate) default: u32,
 }
 
+/// The handlers of a `resume` or `resume_throw` instruction.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ResumeTable {
+    /// The handlers, in the order they're listed in the instruction.
+    pub handlers: Vec<Handle>,
+}
+
+/// A handler within a [`ResumeTable`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Handle {
+    /// `(on $tag $label)`: a suspension with `tag` branches to `label`.
+    OnLabel {
+        /// The tag being handled.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// `(on $tag switch)`: a `switch` with `tag` is handled by switching
+    /// directly to the target continuation.
+    OnSwitch {
+        /// The tag being handled.
+        tag: u32,
+    },
+}
+
+impl<'a> FromReader<'a> for ResumeTable {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        let handlers = reader
+            .read_iter(MAX_WASM_HANDLERS, "resume table")?
+            .collect::<Result<_>>()?;
+        Ok(ResumeTable { handlers })
+    }
+}
+
+impl<'a> FromReader<'a> for Handle {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        Ok(match reader.read_u8()? {
+            0x00 => Handle::OnLabel {
+                tag: reader.read_var_u32()?,
+                label: reader.read_var_u32()?,
+            },
+            0x01 => Handle::OnSwitch {
+                tag: reader.read_var_u32()?,
+            },
+            x => return reader.invalid_leading_byte(x, "resume table handler"),
+        })
+    }
+}
+
+/// A `try_table` entries representation.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TryTable {
+    /// The block type describing the try block itself.
+    pub ty: BlockType,
+    /// Outer blocks which will receive exceptions.
+    pub catches: Vec<Catch>,
+}
+
+/// Catch clauses that can be specified in [`TryTable`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Catch {
+    /// Equivalent of `catch`
+    One {
+        /// The tag being caught.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Equivalent of `catch_ref`
+    OneRef {
+        /// The tag being caught.
+        tag: u32,
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Equivalent of `catch_all`
+    All {
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+    /// Equivalent of `catch_all_ref`
+    AllRef {
+        /// The relative depth of the label to branch to.
+        label: u32,
+    },
+}
+
+impl<'a> FromReader<'a> for TryTable {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        let ty = reader.read_block_type()?;
+        let catches = reader
+            .read_iter(MAX_WASM_CATCHES, "catches")?
+            .collect::<Result<_>>()?;
+        Ok(TryTable { ty, catches })
+    }
+}
+
+impl<'a> FromReader<'a> for Catch {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        Ok(match reader.read_u8()? {
+            0x00 => Catch::One {
+                tag: reader.read_var_u32()?,
+                label: reader.read_var_u32()?,
+            },
+            0x01 => Catch::OneRef {
+                tag: reader.read_var_u32()?,
+                label: reader.read_var_u32()?,
+            },
+            0x02 => Catch::All {
+                label: reader.read_var_u32()?,
+            },
+            0x03 => Catch::AllRef {
+                label: reader.read_var_u32()?,
+            },
+            x => return reader.invalid_leading_byte(x, "catch"),
+        })
+    }
+}
+
 /// An IEEE binary32 immediate floating point value, represented as a u32
 /// containing the bit pattern.
 ///
@@ -0,0 +1,248 @@
+use crate::{BlockType, Catch, Handle, HeapType, ResumeTable, TryTable, ValType, VisitOperator};
+
+/// An item in one of a module's index spaces which an operator refers to.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ItemRef {
+    /// A type, including concrete heap types.
+    Type(u32),
+    /// A function.
+    Func(u32),
+    /// A table.
+    Table(u32),
+    /// A memory.
+    Memory(u32),
+    /// A global.
+    Global(u32),
+    /// A tag, including those named by `try_table` catch clauses and stack
+    /// switching handlers.
+    Tag(u32),
+    /// An element segment.
+    Elem(u32),
+    /// A data segment.
+    Data(u32),
+}
+
+/// A [`VisitOperator`] which reports each item that the visited operators
+/// refer to.
+///
+/// This is intended for tools which need to know what a function body or
+/// constant expression depends on, such as garbage collectors or size
+/// profilers. Each reference is passed to the callback as an [`ItemRef`];
+/// operators which refer to the same item several times report it several
+/// times.
+///
+/// # Examples
+///
+/// ```
+/// use wasmparser::{BinaryReader, ItemRef, ItemRefVisitor};
+///
+/// // call 3; global.get 1; end
+/// let body = [0x10, 0x03, 0x23, 0x01, 0x0b];
+/// let mut reader = BinaryReader::new(&body);
+/// let mut refs = Vec::new();
+/// let mut visitor = ItemRefVisitor::new(|r| refs.push(r));
+/// while !reader.eof() {
+///     reader.visit_operator(&mut visitor).unwrap();
+/// }
+/// assert_eq!(refs, [ItemRef::Func(3), ItemRef::Global(1)]);
+/// ```
+pub struct ItemRefVisitor<F> {
+    f: F,
+}
+
+impl<F: FnMut(ItemRef)> ItemRefVisitor<F> {
+    /// Creates a new visitor which passes each reference to `f`.
+    pub fn new(f: F) -> ItemRefVisitor<F> {
+        ItemRefVisitor { f }
+    }
+
+    fn push(&mut self, r: ItemRef) {
+        (self.f)(r);
+    }
+
+    fn val_type(&mut self, ty: ValType) {
+        if let ValType::Ref(r) = ty {
+            self.heap_type(r.heap_type());
+        }
+    }
+
+    fn heap_type(&mut self, ty: HeapType) {
+        if let HeapType::Concrete(i) = ty {
+            if let Some(i) = i.as_module_index() {
+                self.push(ItemRef::Type(i));
+            }
+        }
+    }
+
+    fn block_type(&mut self, ty: BlockType) {
+        match ty {
+            BlockType::Empty => {}
+            BlockType::Type(ty) => self.val_type(ty),
+            BlockType::FuncType(i) => self.push(ItemRef::Type(i)),
+        }
+    }
+
+    fn try_table(&mut self, try_table: TryTable) {
+        self.block_type(try_table.ty);
+        for catch in try_table.catches {
+            match catch {
+                Catch::One { tag, .. } | Catch::OneRef { tag, .. } => self.push(ItemRef::Tag(tag)),
+                Catch::All { .. } | Catch::AllRef { .. } => {}
+            }
+        }
+    }
+
+    fn resume_table(&mut self, table: ResumeTable) {
+        for handle in table.handlers {
+            match handle {
+                Handle::OnLabel { tag, .. } | Handle::OnSwitch { tag } => {
+                    self.push(ItemRef::Tag(tag))
+                }
+            }
+        }
+    }
+}
+
+// The items an instruction refers to are identified by the names of its
+// fields. There's a rule for every field name, and no fallback, so that an
+// instruction with a new kind of field fails to compile until it's handled.
+macro_rules! define_visit_operator {
+    ($(@$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
+        $(
+            fn $visit(&mut self $($(,$arg: $argty)*)?) {
+                $($(
+                    visit_arg!(self $arg $arg);
+                )*)?
+            }
+        )*
+    }
+}
+
+macro_rules! visit_arg {
+    ($self:ident type_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident struct_type_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident array_type_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident array_type_index_dst $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident array_type_index_src $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident cont_type_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident argument_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident result_index $arg:ident) => {
+        $self.push(ItemRef::Type($arg))
+    };
+    ($self:ident src_table $arg:ident) => {
+        $self.push(ItemRef::Table($arg))
+    };
+    ($self:ident dst_table $arg:ident) => {
+        $self.push(ItemRef::Table($arg))
+    };
+    ($self:ident table_index $arg:ident) => {
+        $self.push(ItemRef::Table($arg))
+    };
+    ($self:ident table $arg:ident) => {
+        $self.push(ItemRef::Table($arg))
+    };
+    ($self:ident global_index $arg:ident) => {
+        $self.push(ItemRef::Global($arg))
+    };
+    ($self:ident function_index $arg:ident) => {
+        $self.push(ItemRef::Func($arg))
+    };
+    ($self:ident mem $arg:ident) => {
+        $self.push(ItemRef::Memory($arg))
+    };
+    ($self:ident src_mem $arg:ident) => {
+        $self.push(ItemRef::Memory($arg))
+    };
+    ($self:ident dst_mem $arg:ident) => {
+        $self.push(ItemRef::Memory($arg))
+    };
+    ($self:ident memarg $arg:ident) => {
+        $self.push(ItemRef::Memory($arg.memory))
+    };
+    ($self:ident tag_index $arg:ident) => {
+        $self.push(ItemRef::Tag($arg))
+    };
+    ($self:ident data_index $arg:ident) => {
+        $self.push(ItemRef::Data($arg))
+    };
+    ($self:ident array_data_index $arg:ident) => {
+        $self.push(ItemRef::Data($arg))
+    };
+    ($self:ident elem_index $arg:ident) => {
+        $self.push(ItemRef::Elem($arg))
+    };
+    ($self:ident array_elem_index $arg:ident) => {
+        $self.push(ItemRef::Elem($arg))
+    };
+    ($self:ident blockty $arg:ident) => {
+        $self.block_type($arg)
+    };
+    ($self:ident try_table $arg:ident) => {
+        $self.try_table($arg)
+    };
+    ($self:ident resume_table $arg:ident) => {
+        $self.resume_table($arg)
+    };
+    ($self:ident ty $arg:ident) => {
+        $self.val_type($arg)
+    };
+    ($self:ident hty $arg:ident) => {
+        $self.heap_type($arg)
+    };
+    ($self:ident from_ref_type $arg:ident) => {
+        $self.heap_type($arg.heap_type())
+    };
+    ($self:ident to_ref_type $arg:ident) => {
+        $self.heap_type($arg.heap_type())
+    };
+    ($self:ident lane $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident lanes $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident value $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident mem_byte $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident table_byte $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident local_index $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident relative_depth $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident targets $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident field_index $arg:ident) => {
+        let _ = $arg;
+    };
+    ($self:ident array_size $arg:ident) => {
+        let _ = $arg;
+    };
+}
+
+impl<'a, F: FnMut(ItemRef)> VisitOperator<'a> for ItemRefVisitor<F> {
+    type Output = ();
+
+    for_each_operator!(define_visit_operator);
+}
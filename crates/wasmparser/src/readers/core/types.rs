@@ -20,7 +20,7 @@ use crate::limits::{
     MAX_WASM_SUPERTYPES, MAX_WASM_TYPES,
 };
 use crate::types::CoreTypeId;
-use crate::{BinaryReader, BinaryReaderError, FromReader, Result, SectionLimited};
+use crate::{BinaryReader, BinaryReaderError, ErrorKind, FromReader, Result, SectionLimited};
 
 mod matches;
 pub(crate) use self::matches::{Matches, WithRecGroup};
@@ -416,6 +416,13 @@ impl SubType {
     pub fn unwrap_struct(&self) -> &StructType {
         self.composite_type.unwrap_struct()
     }
+
+    /// Unwrap a `ContType` or panic.
+    ///
+    /// Does not check finality or whether there is a supertype.
+    pub fn unwrap_cont(&self) -> &ContType {
+        self.composite_type.unwrap_cont()
+    }
 }
 
 /// Represents a composite type in a WebAssembly module.
@@ -427,6 +434,8 @@ pub enum CompositeType {
     Array(ArrayType),
     /// The type is for a struct.
     Struct(StructType),
+    /// The type is for a continuation.
+    Cont(ContType),
 }
 
 impl CompositeType {
@@ -453,6 +462,14 @@ impl CompositeType {
             _ => panic!("not a struct"),
         }
     }
+
+    /// Unwrap a `ContType` or panic.
+    pub fn unwrap_cont(&self) -> &ContType {
+        match self {
+            Self::Cont(c) => c,
+            _ => panic!("not a cont"),
+        }
+    }
 }
 
 /// Represents a type of a function in a WebAssembly module.
@@ -549,6 +566,12 @@ impl FuncType {
     }
 }
 
+/// Represents a continuation type in a WebAssembly module.
+///
+/// The index refers to the function type of the continuation.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ContType(pub PackedIndex);
+
 /// Represents a type of an array in a WebAssembly module.
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
 pub struct ArrayType(pub FieldType);
@@ -573,6 +596,29 @@ pub enum StorageType {
     Val(ValType),
 }
 
+impl StorageType {
+    /// Is this a packed storage type, i.e. one that must be sign- or
+    /// zero-extended when converted to a `ValType`?
+    pub fn is_packed(&self) -> bool {
+        match self {
+            Self::I8 | Self::I16 => true,
+            Self::Val(_) => false,
+        }
+    }
+
+    /// Unpack this storage type into the value type that it is represented as
+    /// on the operand stack.
+    ///
+    /// For non-packed storage types, this is the identity function. For packed
+    /// storage types, this is `i32`.
+    pub fn unpack(&self) -> ValType {
+        match *self {
+            Self::Val(ty) => ty,
+            Self::I8 | Self::I16 => ValType::I32,
+        }
+    }
+}
+
 /// Represents a type of a struct in a WebAssembly module.
 #[derive(Debug, Clone, Eq, PartialEq, Hash)]
 pub struct StructType {
@@ -624,6 +670,9 @@ impl ValType {
     /// Alias for the wasm `externref` type.
     pub const EXTERNREF: ValType = ValType::Ref(RefType::EXTERNREF);
 
+    /// Alias for the wasm `exnref` type.
+    pub const EXNREF: ValType = ValType::Ref(RefType::EXNREF);
+
     /// Returns whether this value type is a "reference type".
     ///
     /// Only reference types are allowed in tables, for example, and with some
@@ -690,6 +739,11 @@ impl ValType {
 //   0011 = extern
 //   0010 = noextern
 //
+//   0111 = cont
+//   0110 = nocont
+//
+//   0001 = exn
+//
 //   0000 = none
 //   ```
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
@@ -718,6 +772,12 @@ impl std::fmt::Debug for RefType {
             (false, HeapType::Extern) => write!(f, "(ref extern)"),
             (true, HeapType::Func) => write!(f, "funcref"),
             (false, HeapType::Func) => write!(f, "(ref func)"),
+            (true, HeapType::Exn) => write!(f, "exnref"),
+            (false, HeapType::Exn) => write!(f, "(ref exn)"),
+            (true, HeapType::Cont) => write!(f, "contref"),
+            (false, HeapType::Cont) => write!(f, "(ref cont)"),
+            (true, HeapType::NoCont) => write!(f, "nullcontref"),
+            (false, HeapType::NoCont) => write!(f, "(ref nocont)"),
             (true, HeapType::Concrete(idx)) => write!(f, "(ref null {idx})"),
             (false, HeapType::Concrete(idx)) => write!(f, "(ref {idx})"),
         }
@@ -767,6 +827,9 @@ impl RefType {
     const NOFUNC_ABSTYPE: u32 = 0b0100 << 18;
     const EXTERN_ABSTYPE: u32 = 0b0011 << 18;
     const NOEXTERN_ABSTYPE: u32 = 0b0010 << 18;
+    const CONT_ABSTYPE: u32 = 0b0111 << 18;
+    const NOCONT_ABSTYPE: u32 = 0b0110 << 18;
+    const EXN_ABSTYPE: u32 = 0b0001 << 18;
     const NONE_ABSTYPE: u32 = 0b0000 << 18;
 
     // The `index` is valid only when `concrete == 1`.
@@ -810,6 +873,19 @@ impl RefType {
     /// A non-nullable reference to an i31 object aka `(ref i31)`.
     pub const I31: Self = RefType::from_u32(Self::I31_ABSTYPE);
 
+    /// A nullable reference to an exception object aka `(ref null exn)` aka
+    /// `exnref`.
+    pub const EXNREF: Self = RefType::EXN.nullable();
+
+    /// A non-nullable reference to an exception object aka `(ref exn)`.
+    pub const EXN: Self = RefType::from_u32(Self::EXN_ABSTYPE);
+
+    /// A non-nullable reference to a continuation aka `(ref cont)`.
+    pub const CONT: Self = RefType::from_u32(Self::CONT_ABSTYPE);
+
+    /// A non-nullable reference to a nocont object aka `(ref nocont)`.
+    pub const NOCONT: Self = RefType::from_u32(Self::NOCONT_ABSTYPE);
+
     const fn can_represent_type_index(index: u32) -> bool {
         index & Self::INDEX_MASK == index
     }
@@ -848,6 +924,9 @@ impl RefType {
                         | Self::NOFUNC_ABSTYPE
                         | Self::EXTERN_ABSTYPE
                         | Self::NOEXTERN_ABSTYPE
+                        | Self::CONT_ABSTYPE
+                        | Self::NOCONT_ABSTYPE
+                        | Self::EXN_ABSTYPE
                         | Self::NONE_ABSTYPE
                 )
         );
@@ -886,6 +965,9 @@ impl RefType {
             HeapType::Struct => Some(Self::from_u32(nullable32 | Self::STRUCT_ABSTYPE)),
             HeapType::Array => Some(Self::from_u32(nullable32 | Self::ARRAY_ABSTYPE)),
             HeapType::I31 => Some(Self::from_u32(nullable32 | Self::I31_ABSTYPE)),
+            HeapType::Cont => Some(Self::from_u32(nullable32 | Self::CONT_ABSTYPE)),
+            HeapType::NoCont => Some(Self::from_u32(nullable32 | Self::NOCONT_ABSTYPE)),
+            HeapType::Exn => Some(Self::from_u32(nullable32 | Self::EXN_ABSTYPE)),
         }
     }
 
@@ -966,6 +1048,9 @@ impl RefType {
                 Self::STRUCT_ABSTYPE => HeapType::Struct,
                 Self::ARRAY_ABSTYPE => HeapType::Array,
                 Self::I31_ABSTYPE => HeapType::I31,
+                Self::CONT_ABSTYPE => HeapType::Cont,
+                Self::NOCONT_ABSTYPE => HeapType::NoCont,
+                Self::EXN_ABSTYPE => HeapType::Exn,
                 _ => unreachable!(),
             }
         }
@@ -986,6 +1071,9 @@ impl RefType {
             (true, HeapType::Struct) => "structref",
             (true, HeapType::Array) => "arrayref",
             (true, HeapType::I31) => "i31ref",
+            (true, HeapType::Cont) => "contref",
+            (true, HeapType::NoCont) => "nullcontref",
+            (true, HeapType::Exn) => "exnref",
             (false, HeapType::Func) => "(ref func)",
             (false, HeapType::Extern) => "(ref extern)",
             (false, HeapType::Concrete(_)) => "(ref $type)",
@@ -997,6 +1085,9 @@ impl RefType {
             (false, HeapType::Struct) => "(ref struct)",
             (false, HeapType::Array) => "(ref array)",
             (false, HeapType::I31) => "(ref i31)",
+            (false, HeapType::Cont) => "(ref cont)",
+            (false, HeapType::NoCont) => "(ref nocont)",
+            (false, HeapType::Exn) => "(ref exn)",
         }
     }
 }
@@ -1077,13 +1168,34 @@ pub enum HeapType {
     ///
     /// Introduced in the GC proposal.
     I31,
+
+    /// The abstract `cont` heap type.
+    ///
+    /// The common supertype (a.k.a. top) of all continuation types.
+    ///
+    /// Introduced in the stack-switching proposal.
+    Cont,
+
+    /// The abstract `nocont` heap type.
+    ///
+    /// The common subtype (a.k.a. bottom) of all continuation types.
+    ///
+    /// Introduced in the stack-switching proposal.
+    NoCont,
+
+    /// The abstract `exn` heap type.
+    ///
+    /// The type of exception objects.
+    ///
+    /// Introduced in the exception-handling proposal.
+    Exn,
 }
 
 impl ValType {
     pub(crate) fn is_valtype_byte(byte: u8) -> bool {
         match byte {
             0x7F | 0x7E | 0x7D | 0x7C | 0x7B | 0x70 | 0x6F | 0x64 | 0x63 | 0x6E | 0x71 | 0x72
-            | 0x73 | 0x6D | 0x6B | 0x6A | 0x6C => true,
+            | 0x73 | 0x6D | 0x6B | 0x6A | 0x6C | 0x68 | 0x75 | 0x69 => true,
             _ => false,
         }
     }
@@ -1128,9 +1240,8 @@ impl<'a> FromReader<'a> for ValType {
                 reader.position += 1;
                 Ok(ValType::V128)
             }
-            0x70 | 0x6F | 0x64 | 0x63 | 0x6E | 0x71 | 0x72 | 0x73 | 0x6D | 0x6B | 0x6A | 0x6C => {
-                Ok(ValType::Ref(reader.read()?))
-            }
+            0x70 | 0x6F | 0x64 | 0x63 | 0x6E | 0x71 | 0x72 | 0x73 | 0x6D | 0x6B | 0x6A | 0x6C
+            | 0x68 | 0x75 | 0x69 => Ok(ValType::Ref(reader.read()?)),
             _ => bail!(reader.original_position(), "invalid value type"),
         }
     }
@@ -1149,11 +1260,19 @@ impl<'a> FromReader<'a> for RefType {
             0x6B => Ok(RefType::STRUCT.nullable()),
             0x6A => Ok(RefType::ARRAY.nullable()),
             0x6C => Ok(RefType::I31.nullable()),
+            0x68 => Ok(RefType::CONT.nullable()),
+            0x75 => Ok(RefType::NOCONT.nullable()),
+            0x69 => Ok(RefType::EXN.nullable()),
             byte @ (0x63 | 0x64) => {
                 let nullable = byte == 0x63;
                 let pos = reader.original_position();
-                RefType::new(nullable, reader.read()?)
-                    .ok_or_else(|| crate::BinaryReaderError::new("type index too large", pos))
+                RefType::new(nullable, reader.read()?).ok_or_else(|| {
+                    crate::BinaryReaderError::with_kind(
+                        ErrorKind::LimitExceeded,
+                        "type index too large",
+                        pos,
+                    )
+                })
             }
             _ => bail!(reader.original_position(), "malformed reference type"),
         }
@@ -1203,6 +1322,18 @@ impl<'a> FromReader<'a> for HeapType {
                 reader.position += 1;
                 Ok(HeapType::I31)
             }
+            0x68 => {
+                reader.position += 1;
+                Ok(HeapType::Cont)
+            }
+            0x75 => {
+                reader.position += 1;
+                Ok(HeapType::NoCont)
+            }
+            0x69 => {
+                reader.position += 1;
+                Ok(HeapType::Exn)
+            }
             _ => {
                 let idx = match u32::try_from(reader.read_var_s33()?) {
                     Ok(idx) => idx,
@@ -1211,7 +1342,8 @@ impl<'a> FromReader<'a> for HeapType {
                     }
                 };
                 let idx = PackedIndex::from_module_index(idx).ok_or_else(|| {
-                    BinaryReaderError::new(
+                    BinaryReaderError::with_kind(
+                        ErrorKind::LimitExceeded,
                         "type index greater than implementation limits",
                         reader.original_position(),
                     )
@@ -1319,7 +1451,7 @@ impl<'a> TypeSectionReader<'a> {
             }
             match ty.composite_type {
                 CompositeType::Func(f) => Ok(f),
-                CompositeType::Array(_) | CompositeType::Struct(_) => {
+                CompositeType::Array(_) | CompositeType::Struct(_) | CompositeType::Cont(_) => {
                     bail!(offset, "gc proposal not supported");
                 }
             }
@@ -1341,6 +1473,7 @@ fn read_composite_type(
         0x60 => CompositeType::Func(reader.read()?),
         0x5e => CompositeType::Array(reader.read()?),
         0x5f => CompositeType::Struct(reader.read()?),
+        0x5d => CompositeType::Cont(reader.read()?),
         x => return reader.invalid_leading_byte(x, "type"),
     })
 }
@@ -1376,7 +1509,8 @@ impl<'a> FromReader<'a> for SubType {
                     .copied()
                     .map(|idx| {
                         PackedIndex::from_module_index(idx).ok_or_else(|| {
-                            BinaryReaderError::new(
+                            BinaryReaderError::with_kind(
+                                ErrorKind::LimitExceeded,
                                 "type index greater than implementation limits",
                                 reader.original_position(),
                             )
@@ -1437,6 +1571,21 @@ impl<'a> FromReader<'a> for ArrayType {
     }
 }
 
+impl<'a> FromReader<'a> for ContType {
+    fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
+        let pos = reader.original_position();
+        let idx = reader.read_var_u32()?;
+        let idx = PackedIndex::from_module_index(idx).ok_or_else(|| {
+            BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
+                "type index greater than implementation limits",
+                pos,
+            )
+        })?;
+        Ok(ContType(idx))
+    }
+}
+
 impl<'a> FromReader<'a> for StructType {
     fn from_reader(reader: &mut BinaryReader<'a>) -> Result<Self> {
         let fields = reader.read_iter(MAX_WASM_STRUCT_FIELDS, "struct fields")?;
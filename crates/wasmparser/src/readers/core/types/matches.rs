@@ -14,8 +14,8 @@
 
 use crate::{
     types::{CoreTypeId, RecGroupId, TypeList},
-    ArrayType, CompositeType, FieldType, FuncType, RefType, StorageType, StructType, SubType,
-    ValType,
+    ArrayType, CompositeType, ContType, FieldType, FuncType, RefType, StorageType, StructType,
+    SubType, ValType,
 };
 
 /// Wasm type matching.
@@ -124,6 +124,13 @@ impl<'a> Matches for WithRecGroup<&'a CompositeType> {
                 WithRecGroup::map(b, |_| sb),
             ),
             (CompositeType::Struct(_), _) => false,
+
+            (CompositeType::Cont(ca), CompositeType::Cont(cb)) => Matches::matches(
+                types,
+                WithRecGroup::map(a, |_| *ca),
+                WithRecGroup::map(b, |_| *cb),
+            ),
+            (CompositeType::Cont(_), _) => false,
         }
     }
 }
@@ -196,6 +203,18 @@ impl<'a> Matches for WithRecGroup<&'a FuncType> {
     }
 }
 
+impl Matches for WithRecGroup<ContType> {
+    fn matches(types: &TypeList, a: Self, b: Self) -> bool {
+        // A continuation type matches another if its function type is a
+        // declared subtype of the other's.
+        Matches::matches(
+            types,
+            WithRecGroup::map(a, |a| RefType::concrete(false, a.0)),
+            WithRecGroup::map(b, |b| RefType::concrete(false, b.0)),
+        )
+    }
+}
+
 impl Matches for WithRecGroup<ArrayType> {
     fn matches(types: &TypeList, a: Self, b: Self) -> bool {
         Matches::matches(
@@ -14,8 +14,8 @@
  */
 
 use crate::{
-    BinaryReaderError, FuncType, GlobalType, HeapType, MemoryType, RefType, TableType, ValType,
-    WasmFeatures,
+    types::CoreTypeId, BinaryReaderError, ErrorKind, FuncType, GlobalType, HeapType, MemoryType,
+    RefType, SubType, TableType, ValType, WasmFeatures,
 };
 use std::ops::Range;
 
@@ -217,6 +217,18 @@ pub trait WasmModuleResources {
     /// The function type must be canonicalized.
     fn func_type_at(&self, type_idx: u32) -> Option<Self::FuncType>;
 
+    /// Returns the `SubType` associated with the given type index.
+    ///
+    /// The value types within the sub type's composite type must be
+    /// canonicalized.
+    fn sub_type_at(&self, type_idx: u32) -> Option<SubType>;
+
+    /// Returns the `SubType` associated with the given canonical type id.
+    ///
+    /// Like `sub_type_at`, the value types within the sub type's composite
+    /// type must be canonicalized.
+    fn sub_type_of_id(&self, id: CoreTypeId) -> Option<SubType>;
+
     /// Returns the type index associated with the given function
     /// index.
     ///
@@ -238,6 +250,12 @@ pub trait WasmModuleResources {
     /// Is `a` a subtype of `b`?
     fn is_subtype(&self, a: ValType, b: ValType) -> bool;
 
+    /// Returns the top type of the hierarchy that the given heap type belongs
+    /// to, for example `any` for `i31` or `func` for a concrete function type.
+    ///
+    /// The heap type must be canonicalized.
+    fn top_type(&self, heap_type: &HeapType) -> HeapType;
+
     /// Check a value type.
     ///
     /// This requires using func_type_at to check references
@@ -261,7 +279,8 @@ pub trait WasmModuleResources {
         self.check_value_type(
             RefType::new(true, heap_type)
                 .ok_or_else(|| {
-                    BinaryReaderError::new(
+                    BinaryReaderError::with_kind(
+                        ErrorKind::LimitExceeded,
                         "heap type index beyond this crate's implementation limits",
                         offset,
                     )
@@ -307,6 +326,12 @@ where
     fn func_type_at(&self, at: u32) -> Option<Self::FuncType> {
         T::func_type_at(self, at)
     }
+    fn sub_type_at(&self, at: u32) -> Option<SubType> {
+        T::sub_type_at(self, at)
+    }
+    fn sub_type_of_id(&self, id: CoreTypeId) -> Option<SubType> {
+        T::sub_type_of_id(self, id)
+    }
     fn type_index_of_function(&self, func_idx: u32) -> Option<u32> {
         T::type_index_of_function(self, func_idx)
     }
@@ -327,6 +352,9 @@ where
     fn is_subtype(&self, a: ValType, b: ValType) -> bool {
         T::is_subtype(self, a, b)
     }
+    fn top_type(&self, heap_type: &HeapType) -> HeapType {
+        T::top_type(self, heap_type)
+    }
 
     fn element_count(&self) -> u32 {
         T::element_count(self)
@@ -368,6 +396,14 @@ where
         T::func_type_at(self, type_idx)
     }
 
+    fn sub_type_at(&self, type_idx: u32) -> Option<SubType> {
+        T::sub_type_at(self, type_idx)
+    }
+
+    fn sub_type_of_id(&self, id: CoreTypeId) -> Option<SubType> {
+        T::sub_type_of_id(self, id)
+    }
+
     fn type_index_of_function(&self, func_idx: u32) -> Option<u32> {
         T::type_index_of_function(self, func_idx)
     }
@@ -393,6 +429,10 @@ where
         T::is_subtype(self, a, b)
     }
 
+    fn top_type(&self, heap_type: &HeapType) -> HeapType {
+        T::top_type(self, heap_type)
+    }
+
     fn element_count(&self) -> u32 {
         T::element_count(self)
     }
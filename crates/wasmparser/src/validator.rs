@@ -14,8 +14,8 @@
  */
 
 use crate::{
-    limits::*, BinaryReaderError, Encoding, FromReader, FunctionBody, HeapType, Parser, Payload,
-    Result, SectionLimited, ValType, WASM_COMPONENT_VERSION, WASM_MODULE_VERSION,
+    limits::*, BinaryReaderError, Encoding, ErrorKind, FromReader, FunctionBody, HeapType, Parser,
+    Payload, Result, SectionLimited, ValType, WASM_COMPONENT_VERSION, WASM_MODULE_VERSION,
 };
 use std::mem;
 use std::ops::Range;
@@ -66,10 +66,14 @@ fn check_max(cur_len: usize, amt_added: u32, max: usize, desc: &str, offset: usi
         .is_none()
     {
         if max == 1 {
-            bail!(offset, "multiple {desc}");
+            bail!(offset, ErrorKind::LimitExceeded, "multiple {desc}");
         }
 
-        bail!(offset, "{desc} count exceeds limit of {max}");
+        bail!(
+            offset,
+            ErrorKind::LimitExceeded,
+            "{desc} count exceeds limit of {max}"
+        );
     }
 
     Ok(())
@@ -80,6 +84,7 @@ fn combine_type_sizes(a: u32, b: u32, offset: usize) -> Result<u32> {
         Some(sum) if sum < MAX_WASM_TYPE_SIZE => Ok(sum),
         _ => Err(format_err!(
             offset,
+            ErrorKind::LimitExceeded,
             "effective type size exceeds the limit of {MAX_WASM_TYPE_SIZE}",
         )),
     }
@@ -128,6 +133,9 @@ pub struct Validator {
     /// Enabled WebAssembly feature flags, dictating what's valid and what
     /// isn't.
     features: WasmFeatures,
+
+    /// Limits on the size of modules and components being validated.
+    limits: ValidatorLimits,
 }
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -247,6 +255,8 @@ pub struct WasmFeatures {
     pub memory_control: bool,
     /// The WebAssembly gc proposal
     pub gc: bool,
+    /// The WebAssembly stack switching proposal
+    pub stack_switching: bool,
     /// Support for the `value` type in the component model proposal.
     pub component_model_values: bool,
 }
@@ -274,10 +284,61 @@ impl WasmFeatures {
             function_references: true,
             memory_control: true,
             gc: true,
+            stack_switching: true,
             component_model_values: true,
         }
     }
 
+    /// Returns [`WasmFeatures`] with all features disabled.
+    pub fn none() -> Self {
+        WasmFeatures {
+            mutable_global: false,
+            saturating_float_to_int: false,
+            sign_extension: false,
+            reference_types: false,
+            multi_value: false,
+            bulk_memory: false,
+            simd: false,
+            relaxed_simd: false,
+            threads: false,
+            tail_call: false,
+            floats: false,
+            multi_memory: false,
+            exceptions: false,
+            memory64: false,
+            extended_const: false,
+            component_model: false,
+            function_references: false,
+            memory_control: false,
+            gc: false,
+            stack_switching: false,
+            component_model_values: false,
+        }
+    }
+
+    /// Returns the names of all features, such as `reference-types` or
+    /// `exception-handling`.
+    ///
+    /// These are the names accepted by [`WasmFeatures::get`] and
+    /// [`WasmFeatures::get_mut`].
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        FEATURE_NAMES.iter().map(|(name, _)| *name)
+    }
+
+    /// Returns whether the feature called `name` is enabled, or `None` if
+    /// there's no feature with that name.
+    pub fn get(&self, name: &str) -> Option<bool> {
+        let mut features = *self;
+        features.get_mut(name).map(|enabled| *enabled)
+    }
+
+    /// Returns a mutable reference to the flag for the feature called `name`,
+    /// or `None` if there's no feature with that name.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        let (_, accessor) = FEATURE_NAMES.iter().find(|(n, _)| *n == name)?;
+        Some(accessor(self))
+    }
+
     /// NOTE: This only checks that the value type corresponds to the feature set!!
     ///
     /// To check that reference types are valid, we need access to the module
@@ -337,6 +398,24 @@ impl WasmFeatures {
                             Err("heap types not supported without the gc feature")
                         }
                     }
+
+                    // types added in the stack switching proposal
+                    (HeapType::Cont | HeapType::NoCont, _) => {
+                        if self.stack_switching {
+                            Ok(())
+                        } else {
+                            Err("stack switching support is not enabled")
+                        }
+                    }
+
+                    // types added in the exception handling proposal
+                    (HeapType::Exn, _) => {
+                        if self.exceptions {
+                            Ok(())
+                        } else {
+                            Err("exception refs not supported without the exception handling feature")
+                        }
+                    }
                 }
             }
             ValType::V128 => {
@@ -350,6 +429,36 @@ impl WasmFeatures {
     }
 }
 
+type FeatureAccessor = fn(&mut WasmFeatures) -> &mut bool;
+
+/// Names of each of the fields of [`WasmFeatures`], used by
+/// [`WasmFeatures::names`] and friends.
+const FEATURE_NAMES: &[(&str, FeatureAccessor)] = &[
+    ("reference-types", |f| &mut f.reference_types),
+    ("function-references", |f| &mut f.function_references),
+    ("simd", |f| &mut f.simd),
+    ("threads", |f| &mut f.threads),
+    ("bulk-memory", |f| &mut f.bulk_memory),
+    ("multi-value", |f| &mut f.multi_value),
+    ("tail-call", |f| &mut f.tail_call),
+    ("component-model", |f| &mut f.component_model),
+    ("component-model-values", |f| &mut f.component_model_values),
+    ("multi-memory", |f| &mut f.multi_memory),
+    ("exception-handling", |f| &mut f.exceptions),
+    ("memory64", |f| &mut f.memory64),
+    ("extended-const", |f| &mut f.extended_const),
+    ("floats", |f| &mut f.floats),
+    ("saturating-float-to-int", |f| {
+        &mut f.saturating_float_to_int
+    }),
+    ("sign-extension", |f| &mut f.sign_extension),
+    ("mutable-global", |f| &mut f.mutable_global),
+    ("relaxed-simd", |f| &mut f.relaxed_simd),
+    ("gc", |f| &mut f.gc),
+    ("stack-switching", |f| &mut f.stack_switching),
+    ("memory-control", |f| &mut f.memory_control),
+];
+
 impl Default for WasmFeatures {
     fn default() -> WasmFeatures {
         WasmFeatures {
@@ -361,6 +470,7 @@ impl Default for WasmFeatures {
             function_references: false,
             memory_control: false,
             gc: false,
+            stack_switching: false,
             component_model_values: false,
 
             // On-by-default features (phase 4 or greater).
@@ -380,6 +490,89 @@ impl Default for WasmFeatures {
     }
 }
 
+/// Limits on the size of WebAssembly modules and components that are enforced
+/// during validation.
+///
+/// The default limits are the ones agreed upon with other engines for
+/// consistency. Embeddings with tighter resource constraints can lower them
+/// and tools which need to process unusually large inputs can raise them.
+///
+/// Note that limits enforced while decoding the binary format, such as the
+/// maximum length of a string or the number of targets in a `br_table`, are
+/// not configurable.
+#[derive(Hash, Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ValidatorLimits {
+    /// Maximum number of types in a module or component.
+    pub types: usize,
+    /// Maximum number of functions in a module or component.
+    pub functions: usize,
+    /// Maximum size, in bytes, of a function body.
+    ///
+    /// Function bodies are not limited in size by default.
+    pub function_size: usize,
+    /// Maximum number of locals, including parameters, in a function.
+    pub function_locals: usize,
+    /// Maximum number of exports in a module or component.
+    pub exports: usize,
+    /// Maximum number of globals in a module or component.
+    pub globals: usize,
+    /// Maximum number of element segments in a module.
+    pub element_segments: usize,
+    /// Maximum number of data segments in a module.
+    pub data_segments: usize,
+    /// Maximum number of tables in a module or component.
+    ///
+    /// Without the reference types proposal at most one table is allowed
+    /// regardless of this limit.
+    pub tables: usize,
+    /// Maximum number of entries in a table and in an element segment.
+    pub table_entries: usize,
+    /// Maximum number of memories in a module or component.
+    ///
+    /// Without the multi-memory proposal at most one memory is allowed
+    /// regardless of this limit.
+    pub memories: usize,
+    /// Maximum number of pages of a 32-bit memory.
+    pub memory32_pages: u64,
+    /// Maximum number of pages of a 64-bit memory.
+    pub memory64_pages: u64,
+    /// Maximum number of tags in a module or component.
+    pub tags: usize,
+    /// Maximum number of core modules in a component.
+    pub modules: usize,
+    /// Maximum number of nested components in a component.
+    pub components: usize,
+    /// Maximum number of instances in a component.
+    pub instances: usize,
+    /// Maximum number of values in a component.
+    pub values: usize,
+}
+
+impl Default for ValidatorLimits {
+    fn default() -> ValidatorLimits {
+        ValidatorLimits {
+            types: MAX_WASM_TYPES,
+            functions: MAX_WASM_FUNCTIONS,
+            function_size: usize::MAX,
+            function_locals: MAX_WASM_FUNCTION_LOCALS,
+            exports: MAX_WASM_EXPORTS,
+            globals: MAX_WASM_GLOBALS,
+            element_segments: MAX_WASM_ELEMENT_SEGMENTS,
+            data_segments: MAX_WASM_DATA_SEGMENTS,
+            tables: MAX_WASM_TABLES,
+            table_entries: MAX_WASM_TABLE_ENTRIES,
+            memories: MAX_WASM_MEMORIES,
+            memory32_pages: MAX_WASM_MEMORY32_PAGES,
+            memory64_pages: MAX_WASM_MEMORY64_PAGES,
+            tags: MAX_WASM_TAGS,
+            modules: MAX_WASM_MODULES,
+            components: MAX_WASM_COMPONENTS,
+            instances: MAX_WASM_INSTANCES,
+            values: MAX_WASM_VALUES,
+        }
+    }
+}
+
 /// Possible return values from [`Validator::payload`].
 #[allow(clippy::large_enum_variant)]
 pub enum ValidPayload<'a> {
@@ -416,8 +609,23 @@ impl Validator {
     /// can allow enabling experimental proposals or also turning off
     /// on-by-default wasm proposals.
     pub fn new_with_features(features: WasmFeatures) -> Validator {
+        Validator::new_with_features_and_limits(features, ValidatorLimits::default())
+    }
+
+    /// Creates a new [`Validator`] which has the specified set of wasm
+    /// features activated and enforces the specified limits.
+    ///
+    /// This function is the same as [`Validator::new_with_features`] except
+    /// it also allows you to customize the limits placed on the size of
+    /// modules and components, such as the number of functions or the number
+    /// of locals in a function.
+    pub fn new_with_features_and_limits(
+        features: WasmFeatures,
+        limits: ValidatorLimits,
+    ) -> Validator {
         let mut ret = Validator::new();
         ret.features = features;
+        ret.limits = limits;
         ret
     }
 
@@ -426,6 +634,11 @@ impl Validator {
         &self.features
     }
 
+    /// Returns the limits enforced by this validator.
+    pub fn limits(&self) -> &ValidatorLimits {
+        &self.limits
+    }
+
     /// Validates an entire in-memory module or component with this validator.
     ///
     /// This function will internally create a [`Parser`] to parse the `bytes`
@@ -585,7 +798,7 @@ impl Validator {
             Encoding::Module => {
                 if num == WASM_MODULE_VERSION {
                     assert!(self.module.is_none());
-                    self.module = Some(ModuleState::default());
+                    self.module = Some(ModuleState::new(self.limits));
                     State::Module
                 } else {
                     bail!(range.start, "unknown binary version: {num:#x}");
@@ -595,6 +808,7 @@ impl Validator {
                 if !self.features.component_model {
                     bail!(
                         range.start,
+                        ErrorKind::FeatureDisabled,
                         "unknown binary version and encoding combination: {num:#x} and 0x1, \
                         note: encoded as a component but the WebAssembly component model feature \
                         is not enabled - enable the feature to allow component validation",
@@ -602,7 +816,7 @@ impl Validator {
                 }
                 if num == WASM_COMPONENT_VERSION {
                     self.components
-                        .push(ComponentState::new(ComponentKind::Component));
+                        .push(ComponentState::new(ComponentKind::Component, self.limits));
                     State::Component
                 } else if num < WASM_COMPONENT_VERSION {
                     bail!(range.start, "unsupported component version: {num:#x}");
@@ -625,7 +839,7 @@ impl Validator {
                 check_max(
                     state.module.types.len(),
                     count,
-                    MAX_WASM_TYPES,
+                    state.module.limits.types,
                     "types",
                     offset,
                 )?;
@@ -672,7 +886,7 @@ impl Validator {
                 check_max(
                     state.module.functions.len(),
                     count,
-                    MAX_WASM_FUNCTIONS,
+                    state.module.limits.functions,
                     "functions",
                     offset,
                 )?;
@@ -739,7 +953,8 @@ impl Validator {
     /// This method should only be called when parsing a module.
     pub fn tag_section(&mut self, section: &crate::TagSectionReader<'_>) -> Result<()> {
         if !self.features.exceptions {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::FeatureDisabled,
                 "exceptions proposal not enabled",
                 section.range().start,
             ));
@@ -753,7 +968,7 @@ impl Validator {
                 check_max(
                     state.module.tags.len(),
                     count,
-                    MAX_WASM_TAGS,
+                    state.module.limits.tags,
                     "tags",
                     offset,
                 )?;
@@ -781,7 +996,7 @@ impl Validator {
                 check_max(
                     state.module.globals.len(),
                     count,
-                    MAX_WASM_GLOBALS,
+                    state.module.limits.globals,
                     "globals",
                     offset,
                 )?;
@@ -806,7 +1021,7 @@ impl Validator {
                 check_max(
                     state.module.exports.len(),
                     count,
-                    MAX_WASM_EXPORTS,
+                    state.module.limits.exports,
                     "exports",
                     offset,
                 )?;
@@ -856,7 +1071,7 @@ impl Validator {
                 check_max(
                     state.module.element_types.len(),
                     count,
-                    MAX_WASM_ELEMENT_SEGMENTS,
+                    state.module.limits.element_segments,
                     "element segments",
                     offset,
                 )?;
@@ -883,8 +1098,9 @@ impl Validator {
         let state = self.module.as_mut().unwrap();
         state.update_order(Order::DataCount, offset)?;
 
-        if count > MAX_WASM_DATA_SEGMENTS as u32 {
-            return Err(BinaryReaderError::new(
+        if count as usize > state.module.limits.data_segments {
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
                 "data count section specifies too many data segments",
                 offset,
             ));
@@ -952,11 +1168,20 @@ impl Validator {
         let state = self.module.as_mut().unwrap();
 
         let (index, ty) = state.next_code_index_and_type(offset)?;
-        Ok(FuncToValidate::new(
+        if body.range().len() > self.limits.function_size {
+            bail!(
+                offset,
+                ErrorKind::LimitExceeded,
+                "function body size exceeds limit of {}",
+                self.limits.function_size
+            );
+        }
+        Ok(FuncToValidate::new_with_limits(
             index,
             ty,
             ValidatorResources(state.module.arc().clone()),
             &self.features,
+            &self.limits,
         ))
     }
 
@@ -970,7 +1195,13 @@ impl Validator {
             "data",
             |state, _, _, count, offset| {
                 state.data_segment_count = count;
-                check_max(0, count, MAX_WASM_DATA_SEGMENTS, "data segments", offset)
+                check_max(
+                    0,
+                    count,
+                    state.module.limits.data_segments,
+                    "data segments",
+                    offset,
+                )
             },
             |state, features, types, d, offset| state.add_data_segment(d, features, types, offset),
         )
@@ -986,7 +1217,7 @@ impl Validator {
         check_max(
             current.core_modules.len(),
             1,
-            MAX_WASM_MODULES,
+            current.limits.modules,
             "modules",
             range.start,
         )?;
@@ -1011,7 +1242,7 @@ impl Validator {
                 check_max(
                     current.instance_count(),
                     count,
-                    MAX_WASM_INSTANCES,
+                    current.limits.instances,
                     "instances",
                     offset,
                 )?;
@@ -1036,7 +1267,13 @@ impl Validator {
             "core type",
             |components, _types, count, offset| {
                 let current = components.last_mut().unwrap();
-                check_max(current.type_count(), count, MAX_WASM_TYPES, "types", offset)?;
+                check_max(
+                    current.type_count(),
+                    count,
+                    current.limits.types,
+                    "types",
+                    offset,
+                )?;
                 current.core_types.reserve(count as usize);
                 Ok(())
             },
@@ -1058,7 +1295,7 @@ impl Validator {
         check_max(
             current.components.len(),
             1,
-            MAX_WASM_COMPONENTS,
+            current.limits.components,
             "components",
             range.start,
         )?;
@@ -1086,7 +1323,7 @@ impl Validator {
                 check_max(
                     current.instance_count(),
                     count,
-                    MAX_WASM_INSTANCES,
+                    current.limits.instances,
                     "instances",
                     offset,
                 )?;
@@ -1131,7 +1368,13 @@ impl Validator {
             "type",
             |components, _types, count, offset| {
                 let current = components.last_mut().unwrap();
-                check_max(current.type_count(), count, MAX_WASM_TYPES, "types", offset)?;
+                check_max(
+                    current.type_count(),
+                    count,
+                    current.limits.types,
+                    "types",
+                    offset,
+                )?;
                 current.types.reserve(count as usize);
                 Ok(())
             },
@@ -1158,7 +1401,7 @@ impl Validator {
                 check_max(
                     current.function_count(),
                     count,
-                    MAX_WASM_FUNCTIONS,
+                    current.limits.functions,
                     "functions",
                     offset,
                 )?;
@@ -1252,7 +1495,7 @@ impl Validator {
                 check_max(
                     current.exports.len(),
                     count,
-                    MAX_WASM_EXPORTS,
+                    current.limits.exports,
                     "exports",
                     offset,
                 )?;
@@ -1404,7 +1647,8 @@ impl Validator {
         let offset = section.range().start;
 
         if !self.features.component_model {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::FeatureDisabled,
                 "component model feature is not enabled",
                 offset,
             ));
@@ -1435,7 +1679,10 @@ impl Validator {
 
 #[cfg(test)]
 mod tests {
-    use crate::{GlobalType, MemoryType, RefType, TableType, ValType, Validator, WasmFeatures};
+    use crate::{
+        ErrorKind, GlobalType, MemoryType, RefType, TableType, ValType, Validator, ValidatorLimits,
+        WasmFeatures,
+    };
     use anyhow::Result;
 
     #[test]
@@ -1600,4 +1847,151 @@ mod tests {
 
         Ok(())
     }
+    #[test]
+    fn test_custom_limits() -> Result<()> {
+        let module = wat::parse_str(
+            r#"
+            (module
+                (memory 2)
+                (func (local i32 i32 i32))
+                (func)
+                (export "a" (func 0))
+                (export "b" (func 1))
+            )
+        "#,
+        )?;
+        let validate = |limits: ValidatorLimits| -> Result<()> {
+            Validator::new_with_features_and_limits(WasmFeatures::default(), limits)
+                .validate_all(&module)?;
+            Ok(())
+        };
+
+        validate(ValidatorLimits::default())?;
+
+        let err = validate(ValidatorLimits {
+            functions: 1,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("multiple functions"));
+
+        let err = validate(ValidatorLimits {
+            exports: 1,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("multiple exports"));
+
+        let err = validate(ValidatorLimits {
+            memory32_pages: 1,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("memory size must be at most 1 pages"));
+
+        let err = validate(ValidatorLimits {
+            function_locals: 2,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err.to_string().contains("too many locals"));
+
+        let err = validate(ValidatorLimits {
+            function_size: 3,
+            ..Default::default()
+        })
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("function body size exceeds limit of 3"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lowered_limits_reject_module() -> Result<()> {
+        let module = wat::parse_str(
+            r#"
+            (module
+                (func (local i32 i32 i32 i32))
+                (func)
+                (func)
+            )
+        "#,
+        )?;
+
+        // The module is fine with the default limits.
+        Validator::new().validate_all(&module)?;
+
+        let mut validator = Validator::new_with_features_and_limits(
+            WasmFeatures::default(),
+            ValidatorLimits {
+                functions: 2,
+                ..ValidatorLimits::default()
+            },
+        );
+        let err = validator.validate_all(&module).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+        assert!(err.message().contains("functions count exceeds limit of 2"));
+
+        let mut validator = Validator::new_with_features_and_limits(
+            WasmFeatures::default(),
+            ValidatorLimits {
+                function_locals: 3,
+                ..ValidatorLimits::default()
+            },
+        );
+        let err = validator.validate_all(&module).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+        assert!(err.message().contains("too many locals"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn error_kinds() {
+        let kind = |wasm: &[u8]| {
+            let features = WasmFeatures {
+                memory64: false,
+                ..WasmFeatures::default()
+            };
+            let limits = ValidatorLimits {
+                functions: 2,
+                ..ValidatorLimits::default()
+            };
+            match Validator::new_with_features_and_limits(features, limits).validate_all(wasm) {
+                Ok(_) => panic!("expected a validation error"),
+                Err(e) => e.kind(),
+            }
+        };
+        let kind_of_wat = |wat: &str| kind(&wat::parse_str(wat).unwrap());
+
+        assert_eq!(
+            kind_of_wat("(module (func (result i32) i64.const 0))"),
+            ErrorKind::TypeMismatch
+        );
+        assert_eq!(
+            kind_of_wat("(module (func call 1))"),
+            ErrorKind::UnknownIndex
+        );
+        assert_eq!(
+            kind_of_wat("(module (memory i64 1))"),
+            ErrorKind::FeatureDisabled
+        );
+        assert_eq!(
+            kind_of_wat("(module (func) (func) (func))"),
+            ErrorKind::LimitExceeded
+        );
+        assert_eq!(
+            kind_of_wat("(module (func unreachable end))"),
+            ErrorKind::Other
+        );
+        assert_eq!(kind(b"\0asm\x01\0\0"), ErrorKind::UnexpectedEof);
+        assert_eq!(
+            kind(b"\0asm\x01\0\0\0\x01\xff\xff\xff\xff\x7f"),
+            ErrorKind::MalformedLeb
+        );
+    }
 }
@@ -12,7 +12,6 @@ use super::{
 };
 use crate::validator::names::{ComponentName, ComponentNameKind, KebabStr, KebabString};
 use crate::{
-    limits::*,
     types::{
         ComponentAnyTypeId, ComponentCoreModuleTypeId, ComponentCoreTypeId, ComponentDefinedType,
         ComponentEntityType, Context, CoreInstanceTypeKind, LoweringInfo, Remap, SubtypeCx,
@@ -21,7 +20,7 @@ use crate::{
     BinaryReaderError, CanonicalOption, ComponentExportName, ComponentExternalKind,
     ComponentOuterAliasKind, ComponentTypeRef, CompositeType, ExternalKind, FuncType, GlobalType,
     InstantiationArgKind, MemoryType, RecGroup, Result, SubType, TableType, TypeBounds, ValType,
-    WasmFeatures,
+    ValidatorLimits, WasmFeatures,
 };
 use indexmap::{map::Entry, IndexMap, IndexSet};
 use std::collections::{HashMap, HashSet};
@@ -45,6 +44,9 @@ pub(crate) struct ComponentState {
     /// component type.
     kind: ComponentKind,
 
+    /// Limits on the number of items in this component.
+    pub limits: ValidatorLimits,
+
     // Core index spaces
     pub core_types: Vec<ComponentCoreTypeId>,
     pub core_funcs: Vec<CoreTypeId>,
@@ -209,9 +211,10 @@ impl ExternKind {
 }
 
 impl ComponentState {
-    pub fn new(kind: ComponentKind) -> Self {
+    pub fn new(kind: ComponentKind, limits: ValidatorLimits) -> Self {
         Self {
             kind,
+            limits,
             core_types: Default::default(),
             core_modules: Default::default(),
             core_instances: Default::default(),
@@ -282,7 +285,13 @@ impl ComponentState {
 
         let current = components.last_mut().unwrap();
         if check_limit {
-            check_max(current.type_count(), 1, MAX_WASM_TYPES, "types", offset)?;
+            check_max(
+                current.type_count(),
+                1,
+                current.limits.types,
+                "types",
+                offset,
+            )?;
         }
         current.core_types.push(id);
         Ok(())
@@ -417,7 +426,13 @@ impl ComponentState {
 
         let current = current(components);
         if check_limit {
-            check_max(current.type_count(), 1, MAX_WASM_TYPES, "types", offset)?;
+            check_max(
+                current.type_count(),
+                1,
+                current.limits.types,
+                "types",
+                offset,
+            )?;
         }
         current.types.push(id);
 
@@ -464,11 +479,11 @@ impl ComponentState {
         let (len, max, desc) = match ty {
             ComponentEntityType::Module(id) => {
                 self.core_modules.push(*id);
-                (self.core_modules.len(), MAX_WASM_MODULES, "modules")
+                (self.core_modules.len(), self.limits.modules, "modules")
             }
             ComponentEntityType::Component(id) => {
                 self.components.push(*id);
-                (self.components.len(), MAX_WASM_COMPONENTS, "components")
+                (self.components.len(), self.limits.components, "components")
             }
             ComponentEntityType::Instance(id) => {
                 match kind {
@@ -477,11 +492,11 @@ impl ComponentState {
                     None => {}
                 }
                 self.instances.push(*id);
-                (self.instance_count(), MAX_WASM_INSTANCES, "instances")
+                (self.instance_count(), self.limits.instances, "instances")
             }
             ComponentEntityType::Func(id) => {
                 self.funcs.push(*id);
-                (self.function_count(), MAX_WASM_FUNCTIONS, "functions")
+                (self.function_count(), self.limits.functions, "functions")
             }
             ComponentEntityType::Value(ty) => {
                 self.check_value_support(features, offset)?;
@@ -490,7 +505,7 @@ impl ComponentState {
                     Some(ExternKind::Export) => true,
                 };
                 self.values.push((*ty, value_used));
-                (self.values.len(), MAX_WASM_VALUES, "values")
+                (self.values.len(), self.limits.values, "values")
             }
             ComponentEntityType::Type {
                 created,
@@ -541,7 +556,7 @@ impl ComponentState {
                         None => {}
                     }
                 }
-                (self.types.len(), MAX_WASM_TYPES, "types")
+                (self.types.len(), self.limits.types, "types")
             }
         };
 
@@ -757,6 +772,21 @@ impl ComponentState {
             ComponentDefinedType::Own(id) | ComponentDefinedType::Borrow(id) => {
                 set.contains(&(*id).into())
             }
+
+            ComponentDefinedType::Future(ty) => ty
+                .as_ref()
+                .map(|ty| types.type_named_valtype(ty, set))
+                .unwrap_or(true),
+            ComponentDefinedType::Stream { element, end } => {
+                element
+                    .as_ref()
+                    .map(|t| types.type_named_valtype(t, set))
+                    .unwrap_or(true)
+                    && end
+                        .as_ref()
+                        .map(|t| types.type_named_valtype(t, set))
+                        .unwrap_or(true)
+            }
         }
     }
 
@@ -923,7 +953,13 @@ impl ComponentState {
         check_limit: bool,
     ) -> Result<()> {
         if check_limit {
-            check_max(self.exports.len(), 1, MAX_WASM_EXPORTS, "exports", offset)?;
+            check_max(
+                self.exports.len(),
+                1,
+                self.limits.exports,
+                "exports",
+                offset,
+            )?;
         }
         self.add_entity(
             &mut ty,
@@ -1187,6 +1223,7 @@ impl ComponentState {
         if !features.component_model_values {
             bail!(
                 offset,
+                ErrorKind::FeatureDisabled,
                 "support for component model `value`s is not enabled"
             );
         }
@@ -1479,6 +1516,7 @@ impl ComponentState {
         offset: usize,
     ) -> Result<ModuleType> {
         let mut state = Module::default();
+        state.limits = components.last().unwrap().limits;
 
         for decl in decls {
             match decl {
@@ -1509,7 +1547,7 @@ impl ComponentState {
                                 component.core_type_at(index, offset)?
                             };
 
-                            check_max(state.types.len(), 1, MAX_WASM_TYPES, "types", offset)?;
+                            check_max(state.types.len(), 1, state.limits.types, "types", offset)?;
 
                             match ty {
                                 ComponentCoreTypeId::Sub(ty) => state.types.push(ty),
@@ -1545,7 +1583,8 @@ impl ComponentState {
         types: &mut TypeAlloc,
         offset: usize,
     ) -> Result<ComponentType> {
-        components.push(ComponentState::new(ComponentKind::ComponentType));
+        let limits = components.last().unwrap().limits;
+        components.push(ComponentState::new(ComponentKind::ComponentType, limits));
 
         for decl in decls {
             match decl {
@@ -1582,7 +1621,8 @@ impl ComponentState {
         types: &mut TypeAlloc,
         offset: usize,
     ) -> Result<ComponentInstanceType> {
-        components.push(ComponentState::new(ComponentKind::InstanceType));
+        let limits = components.last().unwrap().limits;
+        components.push(ComponentState::new(ComponentKind::InstanceType, limits));
 
         for decl in decls {
             match decl {
@@ -2282,21 +2322,27 @@ impl ComponentState {
                 check_max(
                     self.function_count(),
                     1,
-                    MAX_WASM_FUNCTIONS,
+                    self.limits.functions,
                     "functions",
                     offset,
                 )?;
                 push_module_export!(EntityType::Func, core_funcs, "function")
             }
             ExternalKind::Table => {
-                check_max(self.core_tables.len(), 1, MAX_WASM_TABLES, "tables", offset)?;
+                check_max(
+                    self.core_tables.len(),
+                    1,
+                    self.limits.tables,
+                    "tables",
+                    offset,
+                )?;
                 push_module_export!(EntityType::Table, core_tables, "table")
             }
             ExternalKind::Memory => {
                 check_max(
                     self.core_memories.len(),
                     1,
-                    MAX_WASM_MEMORIES,
+                    self.limits.memories,
                     "memories",
                     offset,
                 )?;
@@ -2306,14 +2352,14 @@ impl ComponentState {
                 check_max(
                     self.core_globals.len(),
                     1,
-                    MAX_WASM_GLOBALS,
+                    self.limits.globals,
                     "globals",
                     offset,
                 )?;
                 push_module_export!(EntityType::Global, core_globals, "global")
             }
             ExternalKind::Tag => {
-                check_max(self.core_tags.len(), 1, MAX_WASM_TAGS, "tags", offset)?;
+                check_max(self.core_tags.len(), 1, self.limits.tags, "tags", offset)?;
                 push_module_export!(EntityType::Tag, core_tags, "tag")
             }
         }
@@ -2376,7 +2422,7 @@ impl ComponentState {
         check_max(
             current.core_modules.len(),
             1,
-            MAX_WASM_MODULES,
+            current.limits.modules,
             "modules",
             offset,
         )?;
@@ -2398,7 +2444,7 @@ impl ComponentState {
         check_max(
             current.components.len(),
             1,
-            MAX_WASM_COMPONENTS,
+            current.limits.components,
             "components",
             offset,
         )?;
@@ -2417,7 +2463,13 @@ impl ComponentState {
         let ty = component.core_type_at(index, offset)?;
 
         let current = components.last_mut().unwrap();
-        check_max(current.type_count(), 1, MAX_WASM_TYPES, "types", offset)?;
+        check_max(
+            current.type_count(),
+            1,
+            current.limits.types,
+            "types",
+            offset,
+        )?;
 
         current.core_types.push(ty);
 
@@ -2472,7 +2524,13 @@ impl ComponentState {
         }
 
         let current = components.last_mut().unwrap();
-        check_max(current.type_count(), 1, MAX_WASM_TYPES, "types", offset)?;
+        check_max(
+            current.type_count(),
+            1,
+            current.limits.types,
+            "types",
+            offset,
+        )?;
 
         current.types.push(ty);
 
@@ -2531,6 +2589,20 @@ impl ComponentState {
             crate::ComponentDefinedType::Borrow(idx) => Ok(ComponentDefinedType::Borrow(
                 self.resource_at(idx, types, offset)?,
             )),
+            crate::ComponentDefinedType::Future(ty) => Ok(ComponentDefinedType::Future(
+                ty.map(|ty| self.create_component_val_type(ty, offset))
+                    .transpose()?,
+            )),
+            crate::ComponentDefinedType::Stream { element, end } => {
+                Ok(ComponentDefinedType::Stream {
+                    element: element
+                        .map(|ty| self.create_component_val_type(ty, offset))
+                        .transpose()?,
+                    end: end
+                        .map(|ty| self.create_component_val_type(ty, offset))
+                        .transpose()?,
+                })
+            }
         }
     }
 
@@ -2722,17 +2794,23 @@ impl ComponentState {
     }
 
     pub fn core_type_at(&self, idx: u32, offset: usize) -> Result<ComponentCoreTypeId> {
-        self.core_types
-            .get(idx as usize)
-            .copied()
-            .ok_or_else(|| format_err!(offset, "unknown type {idx}: type index out of bounds"))
+        self.core_types.get(idx as usize).copied().ok_or_else(|| {
+            format_err!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown type {idx}: type index out of bounds"
+            )
+        })
     }
 
     pub fn component_type_at(&self, idx: u32, offset: usize) -> Result<ComponentAnyTypeId> {
-        self.types
-            .get(idx as usize)
-            .copied()
-            .ok_or_else(|| format_err!(offset, "unknown type {idx}: type index out of bounds"))
+        self.types.get(idx as usize).copied().ok_or_else(|| {
+            format_err!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown type {idx}: type index out of bounds"
+            )
+        })
     }
 
     fn function_type_at<'a>(
@@ -2752,6 +2830,7 @@ impl ComponentState {
         self.funcs.get(idx as usize).copied().ok_or_else(|| {
             format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown function {idx}: function index out of bounds"
             )
         })
@@ -2761,6 +2840,7 @@ impl ComponentState {
         self.components.get(idx as usize).copied().ok_or_else(|| {
             format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown component {idx}: component index out of bounds"
             )
         })
@@ -2770,6 +2850,7 @@ impl ComponentState {
         self.instances.get(idx as usize).copied().ok_or_else(|| {
             format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown instance {idx}: instance index out of bounds"
             )
         })
@@ -2782,7 +2863,11 @@ impl ComponentState {
                 Ok(ty)
             }
             Some(_) => bail!(offset, "value {idx} cannot be used more than once"),
-            None => bail!(offset, "unknown value {idx}: value index out of bounds"),
+            None => bail!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown value {idx}: value index out of bounds"
+            ),
         }
     }
 
@@ -2798,6 +2883,7 @@ impl ComponentState {
             Some(id) => Ok(*id),
             None => bail!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown core function {idx}: function index out of bounds"
             ),
         }
@@ -2806,7 +2892,11 @@ impl ComponentState {
     fn module_at(&self, idx: u32, offset: usize) -> Result<ComponentCoreModuleTypeId> {
         match self.core_modules.get(idx as usize) {
             Some(id) => Ok(*id),
-            None => bail!(offset, "unknown module {idx}: module index out of bounds"),
+            None => bail!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown module {idx}: module index out of bounds"
+            ),
         }
     }
 
@@ -2815,6 +2905,7 @@ impl ComponentState {
             Some(id) => Ok(*id),
             None => bail!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown core instance {idx}: instance index out of bounds"
             ),
         }
@@ -2842,21 +2933,33 @@ impl ComponentState {
     fn global_at(&self, idx: u32, offset: usize) -> Result<&GlobalType> {
         match self.core_globals.get(idx as usize) {
             Some(t) => Ok(t),
-            None => bail!(offset, "unknown global {idx}: global index out of bounds"),
+            None => bail!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown global {idx}: global index out of bounds"
+            ),
         }
     }
 
     fn table_at(&self, idx: u32, offset: usize) -> Result<&TableType> {
         match self.core_tables.get(idx as usize) {
             Some(t) => Ok(t),
-            None => bail!(offset, "unknown table {idx}: table index out of bounds"),
+            None => bail!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown table {idx}: table index out of bounds"
+            ),
         }
     }
 
     fn memory_at(&self, idx: u32, offset: usize) -> Result<&MemoryType> {
         match self.core_memories.get(idx as usize) {
             Some(t) => Ok(t),
-            None => bail!(offset, "unknown memory {idx}: memory index out of bounds"),
+            None => bail!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown memory {idx}: memory index out of bounds"
+            ),
         }
     }
 
@@ -2964,6 +3067,7 @@ impl ComponentState {
         if !features.component_model_values {
             bail!(
                 offset,
+                ErrorKind::FeatureDisabled,
                 "support for component model `value`s is not enabled"
             );
         }
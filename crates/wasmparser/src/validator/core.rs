@@ -14,9 +14,9 @@ use super::{
 };
 use crate::{
     limits::*, BinaryReaderError, CompositeType, ConstExpr, Data, DataKind, Element, ElementKind,
-    ExternalKind, FuncType, Global, GlobalType, HeapType, MemoryType, PackedIndex, RecGroup,
-    RefType, Result, StorageType, SubType, Table, TableInit, TableType, TagType, TypeRef,
-    UnpackedIndex, ValType, VisitOperator, WasmFeatures, WasmModuleResources,
+    ErrorKind, ExternalKind, FuncType, Global, GlobalType, HeapType, MemoryType, PackedIndex,
+    RecGroup, RefType, Result, StorageType, SubType, Table, TableInit, TableType, TagType, TypeRef,
+    UnpackedIndex, ValType, ValidatorLimits, VisitOperator, WasmFeatures, WasmModuleResources,
 };
 use indexmap::IndexMap;
 use std::mem;
@@ -74,6 +74,12 @@ pub(crate) struct ModuleState {
 }
 
 impl ModuleState {
+    pub fn new(limits: ValidatorLimits) -> ModuleState {
+        let mut state = ModuleState::default();
+        state.module.assert_mut().limits = limits;
+        state
+    }
+
     pub fn update_order(&mut self, order: Order, offset: usize) -> Result<()> {
         if self.order >= order {
             return Err(BinaryReaderError::new("section out of order", offset));
@@ -152,7 +158,11 @@ impl ModuleState {
         match &table.init {
             TableInit::RefNull => {
                 if !table.ty.element_type.is_nullable() {
-                    bail!(offset, "type mismatch: non-defaultable element type");
+                    bail!(
+                        offset,
+                        ErrorKind::TypeMismatch,
+                        "type mismatch: non-defaultable element type"
+                    );
                 }
             }
             TableInit::Expr(expr) => {
@@ -214,7 +224,8 @@ impl ModuleState {
             } => {
                 let table = self.module.table_at(table_index.unwrap_or(0), offset)?;
                 if !types.reftype_is_subtype(element_ty, table.element_type) {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::TypeMismatch,
                         format!(
                             "type mismatch: invalid element type `{}` for table type `{}`",
                             ty_to_str(element_ty.into()),
@@ -228,7 +239,8 @@ impl ModuleState {
             }
             ElementKind::Passive | ElementKind::Declared => {
                 if !features.bulk_memory {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::FeatureDisabled,
                         "bulk memory must be enabled",
                         offset,
                     ));
@@ -237,7 +249,7 @@ impl ModuleState {
         }
 
         let validate_count = |count: u32| -> Result<(), BinaryReaderError> {
-            if count > MAX_WASM_TABLE_ENTRIES as u32 {
+            if count as usize > self.module.limits.table_entries {
                 Err(BinaryReaderError::new(
                     "number of elements is out of bounds",
                     offset,
@@ -461,6 +473,34 @@ impl ModuleState {
                 $self.validate_gc("ref.i31")?;
                 $self.validator().visit_ref_i31()
             }};
+            (@visit $self:ident visit_struct_new $type_index:ident) => {{
+                $self.validate_gc("struct.new")?;
+                $self.validator().visit_struct_new($type_index)
+            }};
+            (@visit $self:ident visit_struct_new_default $type_index:ident) => {{
+                $self.validate_gc("struct.new_default")?;
+                $self.validator().visit_struct_new_default($type_index)
+            }};
+            (@visit $self:ident visit_array_new $type_index:ident) => {{
+                $self.validate_gc("array.new")?;
+                $self.validator().visit_array_new($type_index)
+            }};
+            (@visit $self:ident visit_array_new_default $type_index:ident) => {{
+                $self.validate_gc("array.new_default")?;
+                $self.validator().visit_array_new_default($type_index)
+            }};
+            (@visit $self:ident visit_array_new_fixed $type_index:ident $n:ident) => {{
+                $self.validate_gc("array.new_fixed")?;
+                $self.validator().visit_array_new_fixed($type_index, $n)
+            }};
+            (@visit $self:ident visit_any_convert_extern) => {{
+                $self.validate_gc("any.convert_extern")?;
+                $self.validator().visit_any_convert_extern()
+            }};
+            (@visit $self:ident visit_extern_convert_any) => {{
+                $self.validate_gc("extern.convert_any")?;
+                $self.validator().visit_extern_convert_any()
+            }};
 
             // `global.get` is a valid const expression for imported, immutable
             // globals.
@@ -510,6 +550,7 @@ pub(crate) struct Module {
     pub imports: IndexMap<(String, String), Vec<EntityType>>,
     pub exports: IndexMap<String, EntityType>,
     pub type_size: u32,
+    pub limits: ValidatorLimits,
     num_imported_globals: u32,
     num_imported_functions: u32,
 }
@@ -542,6 +583,7 @@ impl Module {
         if rec_group.is_explicit_rec_group() && !features.gc {
             bail!(
                 offset,
+                ErrorKind::FeatureDisabled,
                 "rec group usage requires `gc` proposal to be enabled"
             );
         }
@@ -550,7 +592,7 @@ impl Module {
             check_max(
                 self.types.len(),
                 rec_group.types().len() as u32,
-                MAX_WASM_TYPES,
+                self.limits.types,
                 "types",
                 offset,
             )?;
@@ -588,10 +630,14 @@ impl Module {
     ) -> Result<()> {
         let ty = &types[id];
         if !features.gc && (!ty.is_final || ty.supertype_idx.is_some()) {
-            bail!(offset, "gc proposal must be enabled to use subtypes");
+            bail!(
+                offset,
+                ErrorKind::FeatureDisabled,
+                "gc proposal must be enabled to use subtypes"
+            );
         }
 
-        self.check_composite_type(&ty.composite_type, features, offset)?;
+        self.check_composite_type(&ty.composite_type, features, types, rec_group, offset)?;
 
         if let Some(supertype_index) = ty.supertype_idx {
             debug_assert!(supertype_index.is_canonical());
@@ -611,6 +657,8 @@ impl Module {
         &mut self,
         ty: &CompositeType,
         features: &WasmFeatures,
+        types: &TypeList,
+        rec_group: RecGroupId,
         offset: usize,
     ) -> Result<()> {
         match ty {
@@ -619,7 +667,7 @@ impl Module {
                     self.check_value_type(*ty, features, offset)?;
                 }
                 if t.results().len() > 1 && !features.multi_value {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(ErrorKind::FeatureDisabled,
                         "func type returns multiple values but the multi-value feature is not enabled",
                         offset,
                     ));
@@ -627,7 +675,8 @@ impl Module {
             }
             CompositeType::Array(t) => {
                 if !features.gc {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::FeatureDisabled,
                         "array indexed types not supported without the gc feature",
                         offset,
                     ));
@@ -641,7 +690,8 @@ impl Module {
             }
             CompositeType::Struct(t) => {
                 if !features.gc {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::FeatureDisabled,
                         "struct indexed types not supported without the gc feature",
                         offset,
                     ));
@@ -650,6 +700,23 @@ impl Module {
                     self.check_storage_type(ty.element_type, features, offset)?;
                 }
             }
+            CompositeType::Cont(t) => {
+                if !features.stack_switching {
+                    bail!(
+                        offset,
+                        ErrorKind::FeatureDisabled,
+                        "stack switching support is not enabled"
+                    );
+                }
+                let id = types.at_canonicalized_packed_index(rec_group, t.0, offset)?;
+                if !matches!(types[id].composite_type, CompositeType::Func(_)) {
+                    bail!(
+                        offset,
+                        ErrorKind::TypeMismatch,
+                        "type mismatch: continuation type must refer to a function type"
+                    );
+                }
+            }
         }
         Ok(())
     }
@@ -667,7 +734,7 @@ impl Module {
             TypeRef::Func(type_index) => {
                 self.functions.push(type_index);
                 self.num_imported_functions += 1;
-                (self.functions.len(), MAX_WASM_FUNCTIONS, "functions")
+                (self.functions.len(), self.limits.functions, "functions")
             }
             TypeRef::Table(ty) => {
                 self.tables.push(ty);
@@ -679,18 +746,19 @@ impl Module {
             }
             TypeRef::Tag(ty) => {
                 self.tags.push(self.types[ty.func_type_idx as usize]);
-                (self.tags.len(), MAX_WASM_TAGS, "tags")
+                (self.tags.len(), self.limits.tags, "tags")
             }
             TypeRef::Global(ty) => {
                 if !features.mutable_global && ty.mutable {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::FeatureDisabled,
                         "mutable global support is not enabled",
                         offset,
                     ));
                 }
                 self.globals.push(ty);
                 self.num_imported_globals += 1;
-                (self.globals.len(), MAX_WASM_GLOBALS, "globals")
+                (self.globals.len(), self.limits.globals, "globals")
             }
         };
 
@@ -718,7 +786,8 @@ impl Module {
         if !features.mutable_global {
             if let EntityType::Global(global_type) = ty {
                 if global_type.mutable {
-                    return Err(BinaryReaderError::new(
+                    return Err(BinaryReaderError::with_kind(
+                        ErrorKind::FeatureDisabled,
                         "mutable global support is not enabled",
                         offset,
                     ));
@@ -727,7 +796,13 @@ impl Module {
         }
 
         if check_limit {
-            check_max(self.exports.len(), 1, MAX_WASM_EXPORTS, "exports", offset)?;
+            check_max(
+                self.exports.len(),
+                1,
+                self.limits.exports,
+                "exports",
+                offset,
+            )?;
         }
 
         self.type_size = combine_type_sizes(self.type_size, ty.info(types).size(), offset)?;
@@ -771,10 +846,13 @@ impl Module {
     }
 
     pub fn type_id_at(&self, idx: u32, offset: usize) -> Result<CoreTypeId> {
-        self.types
-            .get(idx as usize)
-            .copied()
-            .ok_or_else(|| format_err!(offset, "unknown type {idx}: type index out of bounds"))
+        self.types.get(idx as usize).copied().ok_or_else(|| {
+            format_err!(
+                offset,
+                ErrorKind::UnknownIndex,
+                "unknown type {idx}: type index out of bounds"
+            )
+        })
     }
 
     fn sub_type_at<'a>(&self, types: &'a TypeList, idx: u32, offset: usize) -> Result<&'a SubType> {
@@ -838,7 +916,7 @@ impl Module {
         }
 
         self.check_limits(ty.initial, ty.maximum, offset)?;
-        if ty.initial > MAX_WASM_TABLE_ENTRIES as u32 {
+        if ty.initial as usize > self.limits.table_entries {
             return Err(BinaryReaderError::new(
                 "minimum table size is out of bounds",
                 offset,
@@ -854,34 +932,32 @@ impl Module {
         offset: usize,
     ) -> Result<()> {
         self.check_limits(ty.initial, ty.maximum, offset)?;
-        let (true_maximum, err) = if ty.memory64 {
+        let true_maximum = if ty.memory64 {
             if !features.memory64 {
-                return Err(BinaryReaderError::new(
+                return Err(BinaryReaderError::with_kind(
+                    ErrorKind::FeatureDisabled,
                     "memory64 must be enabled for 64-bit memories",
                     offset,
                 ));
             }
-            (
-                MAX_WASM_MEMORY64_PAGES,
-                "memory size must be at most 2**48 pages",
-            )
+            self.limits.memory64_pages
         } else {
-            (
-                MAX_WASM_MEMORY32_PAGES,
-                "memory size must be at most 65536 pages (4GiB)",
-            )
+            self.limits.memory32_pages
         };
-        if ty.initial > true_maximum {
+        if ty.initial > true_maximum || ty.maximum.is_some_and(|max| max > true_maximum) {
+            let err = match (ty.memory64, true_maximum) {
+                (true, MAX_WASM_MEMORY64_PAGES) => "memory size must be at most 2**48 pages".into(),
+                (false, MAX_WASM_MEMORY32_PAGES) => {
+                    "memory size must be at most 65536 pages (4GiB)".into()
+                }
+                (_, max) => format!("memory size must be at most {max} pages"),
+            };
             return Err(BinaryReaderError::new(err, offset));
         }
-        if let Some(maximum) = ty.maximum {
-            if maximum > true_maximum {
-                return Err(BinaryReaderError::new(err, offset));
-            }
-        }
         if ty.shared {
             if !features.threads {
-                return Err(BinaryReaderError::new(
+                return Err(BinaryReaderError::with_kind(
+                    ErrorKind::FeatureDisabled,
                     "threads must be enabled for shared memories",
                     offset,
                 ));
@@ -934,7 +1010,7 @@ impl Module {
     fn check_value_type(&self, ty: ValType, features: &WasmFeatures, offset: usize) -> Result<()> {
         features
             .check_value_type(ty)
-            .map_err(|e| BinaryReaderError::new(e, offset))?;
+            .map_err(|e| BinaryReaderError::with_kind(ErrorKind::FeatureDisabled, e, offset))?;
 
         // The above only checks the value type for features.
         // We must check it if it's a reference.
@@ -956,7 +1032,10 @@ impl Module {
             | HeapType::Eq
             | HeapType::Struct
             | HeapType::Array
-            | HeapType::I31 => Ok(()),
+            | HeapType::I31
+            | HeapType::Cont
+            | HeapType::NoCont
+            | HeapType::Exn => Ok(()),
             HeapType::Concrete(type_index) => {
                 match type_index {
                     UnpackedIndex::Module(idx) => {
@@ -981,14 +1060,18 @@ impl Module {
         types: &TypeList,
         offset: usize,
     ) -> Result<()> {
-        if !features.exceptions {
-            return Err(BinaryReaderError::new(
+        if !features.exceptions && !features.stack_switching {
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::FeatureDisabled,
                 "exceptions proposal not enabled",
                 offset,
             ));
         }
         let ty = self.func_type_at(ty.func_type_idx, types, offset)?;
-        if !ty.results().is_empty() {
+        // Tags used for stack switching may have results, in which case
+        // they're the values passed back when the suspended continuation is
+        // resumed.
+        if !ty.results().is_empty() && !features.stack_switching {
             return Err(BinaryReaderError::new(
                 "invalid exception type: non-empty tag result type",
                 offset,
@@ -1023,7 +1106,7 @@ impl Module {
 
     pub fn max_tables(&self, features: &WasmFeatures) -> usize {
         if features.reference_types {
-            MAX_WASM_TABLES
+            self.limits.tables
         } else {
             1
         }
@@ -1031,7 +1114,7 @@ impl Module {
 
     pub fn max_memories(&self, features: &WasmFeatures) -> usize {
         if features.multi_memory {
-            MAX_WASM_MEMORIES
+            self.limits.memories
         } else {
             1
         }
@@ -1046,6 +1129,7 @@ impl Module {
             if index as usize >= total {
                 Err(format_err!(
                     offset,
+                    ErrorKind::UnknownIndex,
                     "unknown {ty} {index}: exported {ty} index out of bounds",
                 ))
             } else {
@@ -1088,6 +1172,7 @@ impl Module {
             Some(idx) => self.func_type_at(*idx, types, offset),
             None => Err(format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown function {func_idx}: func index out of bounds",
             )),
         }
@@ -1098,6 +1183,7 @@ impl Module {
             Some(t) => Ok(t),
             None => Err(format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown global {idx}: global index out of bounds"
             )),
         }
@@ -1108,6 +1194,7 @@ impl Module {
             Some(t) => Ok(t),
             None => Err(format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown table {idx}: table index out of bounds"
             )),
         }
@@ -1118,6 +1205,7 @@ impl Module {
             Some(t) => Ok(t),
             None => Err(format_err!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown memory {idx}: memory index out of bounds"
             )),
         }
@@ -1140,6 +1228,7 @@ impl Default for Module {
             imports: Default::default(),
             exports: Default::default(),
             type_size: 1,
+            limits: Default::default(),
             num_imported_globals: Default::default(),
             num_imported_functions: Default::default(),
         }
@@ -1169,6 +1258,22 @@ fn canonicalize_valtype_impl(
         .expect("already checked type references are in-bounds at this point");
 }
 
+fn sub_type_at_impl(module: &Module, types: &TypeList, at: u32) -> Option<SubType> {
+    let id = *module.types.get(at as usize)?;
+    Some(sub_type_of_id_impl(module, types, id))
+}
+
+fn sub_type_of_id_impl(module: &Module, types: &TypeList, id: CoreTypeId) -> SubType {
+    let mut ty = types[id].clone();
+    let mut canonicalizer = TypeCanonicalizer::new(module, usize::MAX);
+    canonicalizer.with_only_ids();
+    canonicalizer.within_rec_group(types, types.rec_group_id_of(id));
+    canonicalizer
+        .canonicalize_composite_type(&mut ty.composite_type)
+        .expect("already checked type references are in-bounds at this point");
+    ty
+}
+
 impl WasmModuleResources for OperatorValidatorResources<'_> {
     type FuncType = crate::FuncType;
 
@@ -1220,6 +1325,15 @@ impl WasmModuleResources for OperatorValidatorResources<'_> {
         Some(f)
     }
 
+    fn sub_type_at(&self, at: u32) -> Option<SubType> {
+        sub_type_at_impl(self.module, self.types, at)
+    }
+
+    fn sub_type_of_id(&self, id: CoreTypeId) -> Option<SubType> {
+        self.types.get(id)?;
+        Some(sub_type_of_id_impl(self.module, self.types, id))
+    }
+
     fn type_index_of_function(&self, at: u32) -> Option<u32> {
         self.module.functions.get(at as usize).cloned()
     }
@@ -1242,6 +1356,10 @@ impl WasmModuleResources for OperatorValidatorResources<'_> {
         self.types.valtype_is_subtype(a, b)
     }
 
+    fn top_type(&self, heap_type: &HeapType) -> HeapType {
+        self.types.top_type(heap_type)
+    }
+
     fn element_count(&self) -> u32 {
         self.module.element_types.len() as u32
     }
@@ -1319,6 +1437,16 @@ impl WasmModuleResources for ValidatorResources {
         Some(f)
     }
 
+    fn sub_type_at(&self, at: u32) -> Option<SubType> {
+        sub_type_at_impl(&self.0, self.0.snapshot.as_ref().unwrap(), at)
+    }
+
+    fn sub_type_of_id(&self, id: CoreTypeId) -> Option<SubType> {
+        let types = self.0.snapshot.as_ref().unwrap();
+        types.get(id)?;
+        Some(sub_type_of_id_impl(&self.0, types, id))
+    }
+
     fn type_index_of_function(&self, at: u32) -> Option<u32> {
         self.0.functions.get(at as usize).cloned()
     }
@@ -1341,6 +1469,10 @@ impl WasmModuleResources for ValidatorResources {
         self.0.snapshot.as_ref().unwrap().valtype_is_subtype(a, b)
     }
 
+    fn top_type(&self, heap_type: &HeapType) -> HeapType {
+        self.0.snapshot.as_ref().unwrap().top_type(heap_type)
+    }
+
     fn element_count(&self) -> u32 {
         self.0.element_types.len() as u32
     }
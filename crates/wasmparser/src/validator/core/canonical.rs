@@ -70,8 +70,8 @@
 use super::{Module, RecGroupId, TypeAlloc, TypeList};
 use crate::{
     types::{CoreTypeId, TypeIdentifier},
-    ArrayType, CompositeType, FieldType, FuncType, HeapType, PackedIndex, RecGroup, RefType,
-    Result, StorageType, StructType, SubType, UnpackedIndex, ValType, WasmFeatures,
+    ArrayType, CompositeType, ContType, FieldType, FuncType, HeapType, PackedIndex, RecGroup,
+    RefType, Result, StorageType, StructType, SubType, UnpackedIndex, ValType, WasmFeatures,
 };
 
 /// Canonicalize the rec group and return its id and whether it is a new group
@@ -185,6 +185,7 @@ impl<'a> TypeCanonicalizer<'a> {
                     } else {
                         bail!(
                             self.offset,
+                            ErrorKind::LimitExceeded,
                             "implementation limit: too many types in `TypeList`"
                         )
                     }
@@ -203,6 +204,7 @@ impl<'a> TypeCanonicalizer<'a> {
                     } else {
                         bail!(
                             self.offset,
+                            ErrorKind::LimitExceeded,
                             "implementation limit: too many types in a recursion group"
                         )
                     }
@@ -210,6 +212,7 @@ impl<'a> TypeCanonicalizer<'a> {
 
                 bail!(
                     self.offset,
+                    ErrorKind::UnknownIndex,
                     "unknown type {index}: type index out of bounds"
                 )
             }
@@ -250,14 +253,19 @@ impl<'a> TypeCanonicalizer<'a> {
         self.canonicalize_composite_type(&mut ty.composite_type)
     }
 
-    fn canonicalize_composite_type(&self, ty: &mut CompositeType) -> Result<()> {
+    pub fn canonicalize_composite_type(&self, ty: &mut CompositeType) -> Result<()> {
         match ty {
             CompositeType::Func(f) => self.canonicalize_func_type(f),
             CompositeType::Array(a) => self.canonicalize_array_type(a),
             CompositeType::Struct(s) => self.canonicalize_struct_type(s),
+            CompositeType::Cont(c) => self.canonicalize_cont_type(c),
         }
     }
 
+    fn canonicalize_cont_type(&self, ty: &mut ContType) -> Result<()> {
+        self.canonicalize_type_index(&mut ty.0)
+    }
+
     fn canonicalize_func_type(&self, ty: &mut FuncType) -> Result<()> {
         for ty in ty.params_mut() {
             self.canonicalize_val_type(ty)?;
@@ -316,7 +324,10 @@ impl<'a> TypeCanonicalizer<'a> {
             | HeapType::Eq
             | HeapType::Struct
             | HeapType::Array
-            | HeapType::I31 => Ok(()),
+            | HeapType::I31
+            | HeapType::Cont
+            | HeapType::NoCont
+            | HeapType::Exn => Ok(()),
         }
     }
 }
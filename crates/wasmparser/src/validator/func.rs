@@ -1,6 +1,6 @@
 use super::operators::{Frame, OperatorValidator, OperatorValidatorAllocations};
 use crate::{BinaryReader, Result, ValType, VisitOperator};
-use crate::{FunctionBody, Operator, WasmFeatures, WasmModuleResources};
+use crate::{FunctionBody, Operator, ValidatorLimits, WasmFeatures, WasmModuleResources};
 
 /// Resources necessary to perform validation of a function.
 ///
@@ -14,6 +14,7 @@ pub struct FuncToValidate<T> {
     index: u32,
     ty: u32,
     features: WasmFeatures,
+    limits: ValidatorLimits,
 }
 
 impl<T: WasmModuleResources> FuncToValidate<T> {
@@ -27,11 +28,25 @@ impl<T: WasmModuleResources> FuncToValidate<T> {
     ///   this function is validated within.
     /// * `features` - enabled WebAssembly features.
     pub fn new(index: u32, ty: u32, resources: T, features: &WasmFeatures) -> FuncToValidate<T> {
+        FuncToValidate::new_with_limits(index, ty, resources, features, &Default::default())
+    }
+
+    /// Same as [`FuncToValidate::new`] except that the function is also
+    /// validated against the `limits` specified, such as the maximum number
+    /// of locals.
+    pub fn new_with_limits(
+        index: u32,
+        ty: u32,
+        resources: T,
+        features: &WasmFeatures,
+        limits: &ValidatorLimits,
+    ) -> FuncToValidate<T> {
         FuncToValidate {
             resources,
             index,
             ty,
             features: *features,
+            limits: *limits,
         }
     }
 
@@ -54,9 +69,10 @@ impl<T: WasmModuleResources> FuncToValidate<T> {
             index,
             ty,
             features,
+            limits,
         } = self;
         let validator =
-            OperatorValidator::new_func(ty, 0, &features, &resources, allocs.0).unwrap();
+            OperatorValidator::new_func(ty, 0, &features, &limits, &resources, allocs.0).unwrap();
         FuncValidator {
             validator,
             resources,
@@ -270,6 +286,12 @@ mod tests {
         fn func_type_at(&self, _type_idx: u32) -> Option<Self::FuncType> {
             Some(EmptyFuncType)
         }
+        fn sub_type_at(&self, _type_idx: u32) -> Option<crate::SubType> {
+            todo!()
+        }
+        fn sub_type_of_id(&self, _id: crate::types::CoreTypeId) -> Option<crate::SubType> {
+            todo!()
+        }
         fn type_index_of_function(&self, _at: u32) -> Option<u32> {
             todo!()
         }
@@ -290,6 +312,9 @@ mod tests {
         fn is_subtype(&self, _t1: ValType, _t2: ValType) -> bool {
             todo!()
         }
+        fn top_type(&self, _heap_type: &crate::HeapType) -> crate::HeapType {
+            todo!()
+        }
         fn element_count(&self) -> u32 {
             todo!()
         }
@@ -23,9 +23,11 @@
 // the various methods here.
 
 use crate::{
-    limits::MAX_WASM_FUNCTION_LOCALS, BinaryReaderError, BlockType, BrTable, HeapType, Ieee32,
-    Ieee64, MemArg, PackedIndex, RefType, Result, UnpackedIndex, ValType, VisitOperator,
-    WasmFeatures, WasmFuncType, WasmModuleResources, V128,
+    limits::MAX_WASM_FUNCTION_LOCALS, BinaryReaderError, BlockType, BrTable, Catch, CompositeType,
+    ContType, ErrorKind, FieldType, FuncType, Handle, HeapType, Ieee32, Ieee64, MemArg,
+    PackedIndex, RefType, Result, ResumeTable, StorageType, StructType, SubType, TryTable,
+    UnpackedIndex, ValType, ValidatorLimits, VisitOperator, WasmFeatures, WasmFuncType,
+    WasmModuleResources, V128,
 };
 use std::ops::{Deref, DerefMut};
 
@@ -61,6 +63,9 @@ pub(super) struct Locals {
     // Total number of locals in the function.
     num_locals: u32,
 
+    // Maximum number of locals allowed in the function.
+    max_locals: u32,
+
     // The first MAX_LOCALS_TO_TRACK locals in a function. This is used to
     // optimize the theoretically common case where most functions don't have
     // many locals and don't need a full binary search in the entire local space
@@ -130,6 +135,12 @@ pub enum FrameKind {
     ///
     /// This belongs to the Wasm exception handling proposal.
     CatchAll,
+    /// A Wasm `try_table` control block.
+    ///
+    /// # Note
+    ///
+    /// This belongs to the Wasm exception handling proposal.
+    TryTable,
 }
 
 struct OperatorValidatorTemp<'validator, 'resources, T> {
@@ -201,6 +212,7 @@ impl OperatorValidator {
         OperatorValidator {
             locals: Locals {
                 num_locals: 0,
+                max_locals: MAX_WASM_FUNCTION_LOCALS as u32,
                 first: locals_first,
                 all: locals_all,
             },
@@ -223,6 +235,7 @@ impl OperatorValidator {
         ty: u32,
         offset: usize,
         features: &WasmFeatures,
+        limits: &ValidatorLimits,
         resources: &T,
         allocs: OperatorValidatorAllocations,
     ) -> Result<Self>
@@ -230,6 +243,7 @@ impl OperatorValidator {
         T: WasmModuleResources,
     {
         let mut ret = OperatorValidator::new(features, allocs);
+        ret.locals.max_locals = u32::try_from(limits.function_locals).unwrap_or(u32::MAX);
         ret.control.push(Frame {
             kind: FrameKind::Block,
             block_type: BlockType::FuncType(ty),
@@ -285,7 +299,8 @@ impl OperatorValidator {
             return Ok(());
         }
         if !self.locals.define(count, ty) {
-            return Err(BinaryReaderError::new(
+            return Err(BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
                 "too many locals: locals exceed maximum",
                 offset,
             ));
@@ -501,6 +516,7 @@ where
                 };
                 bail!(
                     self.offset,
+                    ErrorKind::TypeMismatch,
                     "type mismatch: expected {desc} but nothing on stack"
                 )
             } else {
@@ -521,7 +537,7 @@ where
                     if !self.resources.is_subtype(actual, expected) {
                         bail!(
                             self.offset,
-                            "type mismatch: expected {}, found {}",
+                            ErrorKind::TypeMismatch, "type mismatch: expected {}, found {}",
                             ty_to_str(expected),
                             ty_to_str(actual)
                         );
@@ -535,7 +551,7 @@ where
                 ) => {
                     bail!(
                         self.offset,
-                        "type mismatch: expected {}, found heap type",
+                        ErrorKind::TypeMismatch, "type mismatch: expected {}, found heap type",
                         ty_to_str(expected)
                     )
                 }
@@ -550,6 +566,7 @@ where
             MaybeType::Type(ValType::Ref(rt)) => Ok(Some(rt)),
             MaybeType::Type(ty) => bail!(
                 self.offset,
+                ErrorKind::TypeMismatch,
                 "type mismatch: expected ref but found {}",
                 ty_to_str(ty)
             ),
@@ -563,6 +580,7 @@ where
             Some(ty) => Ok(ty),
             None => bail!(
                 self.offset,
+                ErrorKind::UnknownIndex,
                 "unknown local {}: local index out of bounds",
                 idx
             ),
@@ -639,6 +657,7 @@ where
         if self.operands.len() != height {
             bail!(
                 self.offset,
+                ErrorKind::TypeMismatch,
                 "type mismatch: values remaining on stack at end of block"
             );
         }
@@ -660,7 +679,11 @@ where
                 let frame = &self.control[i];
                 Ok((frame.block_type, frame.kind))
             }
-            None => bail!(self.offset, "unknown label: branch depth too large"),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown label: branch depth too large"
+            ),
         }
     }
 
@@ -669,7 +692,12 @@ where
     fn check_memory_index(&self, memory_index: u32) -> Result<ValType> {
         match self.resources.memory_at(memory_index) {
             Some(mem) => Ok(mem.index_type()),
-            None => bail!(self.offset, "unknown memory {}", memory_index),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown memory {}",
+                memory_index
+            ),
         }
     }
 
@@ -721,6 +749,7 @@ where
                 if !self.features.multi_value {
                     bail!(
                         self.offset,
+                        ErrorKind::FeatureDisabled,
                         "blocks, loops, and ifs may only produce a resulttype \
                          when multi-value is not enabled",
                     );
@@ -739,6 +768,7 @@ where
             None => {
                 bail!(
                     self.offset,
+                    ErrorKind::UnknownIndex,
                     "unknown function {function_index}: function index out of bounds",
                 );
             }
@@ -752,6 +782,7 @@ where
             None => {
                 bail!(
                     self.offset,
+                    ErrorKind::UnknownIndex,
                     "unknown type {type_index}: type index out of bounds",
                 );
             }
@@ -771,7 +802,11 @@ where
     fn check_call_indirect(&mut self, index: u32, table_index: u32) -> Result<()> {
         match self.resources.table_at(table_index) {
             None => {
-                bail!(self.offset, "unknown table: table index out of bounds");
+                bail!(
+                    self.offset,
+                    ErrorKind::UnknownIndex,
+                    "unknown table: table index out of bounds"
+                );
             }
             Some(tab) => {
                 if !self
@@ -969,15 +1004,24 @@ where
     }
 
     fn func_type_at(&self, at: u32) -> Result<R::FuncType> {
-        self.resources
-            .func_type_at(at)
-            .ok_or_else(|| format_err!(self.offset, "unknown type: type index out of bounds"))
+        self.resources.func_type_at(at).ok_or_else(|| {
+            format_err!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown type: type index out of bounds"
+            )
+        })
     }
 
     fn tag_at(&self, at: u32) -> Result<R::FuncType> {
-        self.resources
-            .tag_at(at)
-            .ok_or_else(|| format_err!(self.offset, "unknown tag {}: tag index out of bounds", at))
+        self.resources.tag_at(at).ok_or_else(|| {
+            format_err!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown tag {}: tag index out of bounds",
+                at
+            )
+        })
     }
 
     fn params(&self, ty: BlockType) -> Result<impl PreciseIterator<Item = ValType> + 'resources> {
@@ -1008,6 +1052,418 @@ where
             _ => Either::B(self.results(ty)?),
         })
     }
+
+    fn sub_type_at(&self, at: u32) -> Result<SubType> {
+        self.resources.sub_type_at(at).ok_or_else(|| {
+            format_err!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown type: type index out of bounds"
+            )
+        })
+    }
+
+    fn struct_type_at(&self, at: u32) -> Result<StructType> {
+        match self.sub_type_at(at)?.composite_type {
+            CompositeType::Struct(ty) => Ok(ty),
+            _ => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected struct type at index {at}"
+            ),
+        }
+    }
+
+    fn struct_field_at(&self, struct_type_index: u32, field_index: u32) -> Result<FieldType> {
+        let ty = self.struct_type_at(struct_type_index)?;
+        match ty.fields.get(field_index as usize) {
+            Some(field) => Ok(*field),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown field: field index {field_index} out of bounds"
+            ),
+        }
+    }
+
+    fn mutable_struct_field_at(
+        &self,
+        struct_type_index: u32,
+        field_index: u32,
+    ) -> Result<FieldType> {
+        let field = self.struct_field_at(struct_type_index, field_index)?;
+        if !field.mutable {
+            bail!(self.offset, "invalid struct.set: field is immutable");
+        }
+        Ok(field)
+    }
+
+    fn array_type_at(&self, at: u32) -> Result<FieldType> {
+        match self.sub_type_at(at)?.composite_type {
+            CompositeType::Array(ty) => Ok(ty.0),
+            _ => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected array type at index {at}"
+            ),
+        }
+    }
+
+    fn mutable_array_type_at(&self, at: u32) -> Result<FieldType> {
+        let field = self.array_type_at(at)?;
+        if !field.mutable {
+            bail!(
+                self.offset,
+                "invalid array modification: array is immutable"
+            );
+        }
+        Ok(field)
+    }
+
+    /// Returns the canonicalized `(ref null? $type_index)` value type for the
+    /// module-level type index provided.
+    fn concrete_ref_type(&self, nullable: bool, type_index: u32) -> Result<ValType> {
+        let index = PackedIndex::from_module_index(type_index).ok_or_else(|| {
+            BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
+                "implementation limit: type index too large",
+                self.offset,
+            )
+        })?;
+        let mut ty = ValType::Ref(RefType::concrete(nullable, index));
+        self.resources.canonicalize_valtype(&mut ty);
+        Ok(ty)
+    }
+
+    /// Validates the `ref_type` immediate of an instruction and returns its
+    /// canonicalized form.
+    fn check_ref_type(&self, ref_type: RefType) -> Result<RefType> {
+        self.resources
+            .check_value_type(ValType::Ref(ref_type), &self.features, self.offset)?;
+        let mut ty = ValType::Ref(ref_type);
+        self.resources.canonicalize_valtype(&mut ty);
+        match ty {
+            ValType::Ref(rt) => Ok(rt),
+            _ => unreachable!(),
+        }
+    }
+
+    fn check_data_segment(&self, segment: u32) -> Result<()> {
+        match self.resources.data_count() {
+            None => bail!(self.offset, "data count section required"),
+            Some(count) if segment < count => Ok(()),
+            Some(_) => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown data segment {}",
+                segment
+            ),
+        }
+    }
+
+    /// Checks that `segment` is an element segment whose elements can be
+    /// stored in an array whose elements are of type `elem_ty`.
+    fn check_array_elem_segment(&self, elem_ty: StorageType, segment: u32) -> Result<()> {
+        let elem_ty = match elem_ty {
+            StorageType::Val(ty @ ValType::Ref(_)) => ty,
+            _ => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: array element type must be a reference type"
+            ),
+        };
+        let segment_ty = match self.resources.element_type_at(segment) {
+            Some(ty) => ty,
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown elem segment {}: segment index out of bounds",
+                segment
+            ),
+        };
+        if !self.resources.is_subtype(ValType::Ref(segment_ty), elem_ty) {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: invalid array element segment type"
+            );
+        }
+        Ok(())
+    }
+
+    /// Checks that arrays with `elem_ty` elements can be initialized from data
+    /// segments.
+    fn check_array_data_elem_type(&self, elem_ty: StorageType) -> Result<()> {
+        match elem_ty {
+            StorageType::I8
+            | StorageType::I16
+            | StorageType::Val(
+                ValType::I32 | ValType::I64 | ValType::F32 | ValType::F64 | ValType::V128,
+            ) => Ok(()),
+            StorageType::Val(ValType::Ref(_)) => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: array type is not numeric or vector"
+            ),
+        }
+    }
+
+    fn check_struct_get(
+        &mut self,
+        struct_type_index: u32,
+        field_index: u32,
+        packed: bool,
+    ) -> Result<()> {
+        let field = self.struct_field_at(struct_type_index, field_index)?;
+        check_packedness(self.offset, "struct.get", field.element_type, packed)?;
+        let ty = self.concrete_ref_type(true, struct_type_index)?;
+        self.pop_operand(Some(ty))?;
+        self.push_operand(field.element_type.unpack())
+    }
+
+    fn check_array_get(&mut self, array_type_index: u32, packed: bool) -> Result<()> {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type;
+        check_packedness(self.offset, "array.get", elem_ty, packed)?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index)?;
+        self.pop_operand(Some(ty))?;
+        self.push_operand(elem_ty.unpack())
+    }
+
+    fn check_ref_test(&mut self, nullable: bool, heap_type: HeapType) -> Result<()> {
+        let rt = self.check_ref_type(RefType::new(nullable, heap_type).ok_or_else(|| {
+            BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
+                "implementation limit: type index too large",
+                self.offset,
+            )
+        })?)?;
+        let top = self.resources.top_type(&rt.heap_type());
+        let top = RefType::new(true, top).expect("top types are always representable");
+        self.pop_operand(Some(top.into()))?;
+        self.push_operand(ValType::I32)
+    }
+
+    fn check_ref_cast(&mut self, nullable: bool, heap_type: HeapType) -> Result<()> {
+        let rt = self.check_ref_type(RefType::new(nullable, heap_type).ok_or_else(|| {
+            BinaryReaderError::with_kind(
+                ErrorKind::LimitExceeded,
+                "implementation limit: type index too large",
+                self.offset,
+            )
+        })?)?;
+        let top = self.resources.top_type(&rt.heap_type());
+        let top = RefType::new(true, top).expect("top types are always representable");
+        self.pop_operand(Some(top.into()))?;
+        self.push_operand(rt)
+    }
+
+    /// Common validation of `br_on_cast` and `br_on_cast_fail`.
+    ///
+    /// The value sent to the label is the cast's target type for `br_on_cast`
+    /// and the input type minus the target type for `br_on_cast_fail`, and
+    /// the value left on the stack when the branch isn't taken is the other
+    /// one.
+    fn check_br_on_cast(
+        &mut self,
+        relative_depth: u32,
+        from_ref_type: RefType,
+        to_ref_type: RefType,
+        on_failure: bool,
+    ) -> Result<()> {
+        let from_ref_type = self.check_ref_type(from_ref_type)?;
+        let to_ref_type = self.check_ref_type(to_ref_type)?;
+        if !self
+            .resources
+            .is_subtype(to_ref_type.into(), from_ref_type.into())
+        {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected {}, found {}",
+                ty_to_str(from_ref_type.into()),
+                ty_to_str(to_ref_type.into())
+            );
+        }
+
+        // The type of values that fail the cast: everything in `from_ref_type`
+        // except for values in `to_ref_type`. The only thing that can be
+        // statically subtracted is nullability.
+        let diff_ref_type = RefType::new(
+            from_ref_type.is_nullable() && !to_ref_type.is_nullable(),
+            from_ref_type.heap_type(),
+        )
+        .expect("heap type was previously representable");
+        let (branch_ty, fallthrough_ty) = if on_failure {
+            (diff_ref_type, to_ref_type)
+        } else {
+            (to_ref_type, diff_ref_type)
+        };
+
+        let (ft, kind) = self.jump(relative_depth)?;
+        let mut label_types = self.label_types(ft, kind)?;
+        match label_types.next_back() {
+            Some(label_ty) if self.resources.is_subtype(branch_ty.into(), label_ty) => {}
+            Some(label_ty) => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected {}, found {}",
+                ty_to_str(label_ty),
+                ty_to_str(branch_ty.into())
+            ),
+            None => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected a reference type as the last label type, found nothing"
+            ),
+        }
+
+        self.pop_operand(Some(from_ref_type.into()))?;
+        for ty in self.label_types(ft, kind)?.rev().skip(1) {
+            self.pop_operand(Some(ty))?;
+        }
+        for ty in label_types {
+            self.push_operand(ty)?;
+        }
+        self.push_operand(fallthrough_ty)
+    }
+
+    /// Returns the canonicalized index of the function type of the
+    /// continuation type at `at`, along with that function type.
+    fn cont_type_at(&self, at: u32) -> Result<(PackedIndex, FuncType)> {
+        match self.sub_type_at(at)?.composite_type {
+            CompositeType::Cont(ContType(index)) => Ok((index, self.func_type_of_id(index)?)),
+            _ => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected continuation type at index {at}"
+            ),
+        }
+    }
+
+    /// Returns the function type with the canonicalized type index `index`.
+    fn func_type_of_id(&self, index: PackedIndex) -> Result<FuncType> {
+        let sub_type = index
+            .as_core_type_id()
+            .and_then(|id| self.resources.sub_type_of_id(id));
+        match sub_type.map(|ty| ty.composite_type) {
+            Some(CompositeType::Func(ty)) => Ok(ty),
+            _ => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: expected a function type"
+            ),
+        }
+    }
+
+    /// If `ty` is a reference to a continuation type then returns the
+    /// continuation's function type.
+    fn cont_func_type_of_ref(&self, ty: ValType) -> Option<FuncType> {
+        let id = match ty {
+            ValType::Ref(rt) => rt.type_index()?.as_core_type_id()?,
+            _ => return None,
+        };
+        match self.resources.sub_type_of_id(id)?.composite_type {
+            CompositeType::Cont(ContType(index)) => self.func_type_of_id(index).ok(),
+            _ => None,
+        }
+    }
+
+    /// Are all of `a` subtypes of the corresponding types in `b`?
+    fn types_are_subtypes(&self, a: &[ValType], b: &[ValType]) -> bool {
+        a.len() == b.len()
+            && a.iter()
+                .zip(b)
+                .all(|(a, b)| self.resources.is_subtype(*a, *b))
+    }
+
+    /// Is the function type `a` a subtype of `b`?
+    fn func_type_is_subtype(&self, a: &FuncType, b: &FuncType) -> bool {
+        self.types_are_subtypes(b.params(), a.params())
+            && self.types_are_subtypes(a.results(), b.results())
+    }
+
+    /// Checks the handlers of a `resume` or `resume_throw` of a continuation
+    /// whose function type is `func`.
+    fn check_resume_table(&self, table: &ResumeTable, func: &FuncType) -> Result<()> {
+        for handle in table.handlers.iter() {
+            match *handle {
+                // The label receives the tag's parameters followed by the
+                // suspended continuation, which takes the tag's results and
+                // eventually produces the resumed continuation's results.
+                Handle::OnLabel { tag, label } => {
+                    let tag = self.tag_at(tag)?;
+                    let (ty, kind) = self.jump(label)?;
+                    let mut label_types = self.label_types(ty, kind)?.collect::<Vec<_>>();
+                    let cont = match label_types.pop() {
+                        Some(ty) => self.cont_func_type_of_ref(ty),
+                        None => None,
+                    };
+                    let cont = match cont {
+                        Some(cont) => cont,
+                        None => bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: handler label must end with a continuation reference"
+                        ),
+                    };
+                    let params = tag.clone().inputs().collect::<Vec<_>>();
+                    if !self.types_are_subtypes(&params, &label_types) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: handler label does not match tag parameters"
+                        );
+                    }
+                    let suspended = FuncType::new(tag.outputs(), func.results().iter().copied());
+                    if !self.func_type_is_subtype(&suspended, &cont) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: handler continuation type does not match tag results"
+                        );
+                    }
+                }
+                Handle::OnSwitch { tag } => {
+                    let tag = self.tag_at(tag)?;
+                    if tag.len_inputs() != 0 {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: switch tag must not have parameters"
+                        );
+                    }
+                    let results = tag.outputs().collect::<Vec<_>>();
+                    if !self.types_are_subtypes(func.results(), &results) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: switch tag results do not match continuation results"
+                        );
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `ty` is a packed storage type if and only if `packed` is set,
+/// as required by the `_s` and `_u` variants of struct and array accessors.
+fn check_packedness(offset: usize, op: &str, ty: StorageType, packed: bool) -> Result<()> {
+    match (ty.is_packed(), packed) {
+        (true, true) | (false, false) => Ok(()),
+        (true, false) => bail!(
+            offset,
+            ErrorKind::TypeMismatch,
+            "type mismatch: can't use {op} on packed storage types; use {op}_s or {op}_u"
+        ),
+        (false, true) => bail!(
+            offset,
+            ErrorKind::TypeMismatch,
+            "type mismatch: can only use {op}_s and {op}_u on packed storage types"
+        ),
+    }
 }
 
 pub fn ty_to_str(ty: ValType) -> &'static str {
@@ -1038,7 +1494,11 @@ impl<T> WasmProposalValidator<'_, '_, T> {
         if flag {
             return Ok(());
         }
-        bail!(self.0.offset, "{desc} support is not enabled");
+        bail!(
+            self.0.offset,
+            ErrorKind::FeatureDisabled,
+            "{desc} support is not enabled"
+        );
     }
 }
 
@@ -1069,6 +1529,7 @@ macro_rules! validate_proposal {
     (desc function_references) => ("function references");
     (desc memory_control) => ("memory control");
     (desc gc) => ("gc");
+    (desc stack_switching) => ("stack switching");
 }
 
 impl<'a, T> VisitOperator<'a> for WasmProposalValidator<'_, '_, T>
@@ -1149,6 +1610,74 @@ where
         self.push_ctrl(FrameKind::Try, ty)?;
         Ok(())
     }
+    fn visit_try_table(&mut self, table: TryTable) -> Self::Output {
+        self.check_block_type(table.ty)?;
+        for ty in self.params(table.ty)?.rev() {
+            self.pop_operand(Some(ty))?;
+        }
+        // Catch labels are resolved relative to the enclosing frame, so they
+        // are checked before the `try_table` frame itself is pushed.
+        let exn = ValType::Ref(RefType::EXN);
+        for catch in table.catches.iter() {
+            match *catch {
+                Catch::One { tag, label } => {
+                    let tag = self.tag_at(tag)?;
+                    let (ty, kind) = self.jump(label)?;
+                    let label_types = self.label_types(ty, kind)?.collect::<Vec<_>>();
+                    let params = tag.inputs().collect::<Vec<_>>();
+                    if !self.types_are_subtypes(&params, &label_types) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: catch label does not match tag parameters"
+                        );
+                    }
+                }
+                Catch::OneRef { tag, label } => {
+                    let tag = self.tag_at(tag)?;
+                    let (ty, kind) = self.jump(label)?;
+                    let label_types = self.label_types(ty, kind)?.collect::<Vec<_>>();
+                    let params = tag.inputs().chain([exn]).collect::<Vec<_>>();
+                    if !self.types_are_subtypes(&params, &label_types) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: catch_ref label does not match tag parameters \
+                             followed by an exnref"
+                        );
+                    }
+                }
+                Catch::All { label } => {
+                    let (ty, kind) = self.jump(label)?;
+                    if self.label_types(ty, kind)?.len() != 0 {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: catch_all label must have no result types"
+                        );
+                    }
+                }
+                Catch::AllRef { label } => {
+                    let (ty, kind) = self.jump(label)?;
+                    let label_types = self.label_types(ty, kind)?.collect::<Vec<_>>();
+                    if !self.types_are_subtypes(&[exn], &label_types) {
+                        bail!(
+                            self.offset,
+                            ErrorKind::TypeMismatch,
+                            "type mismatch: catch_all_ref label must be a supertype of (ref exn)"
+                        );
+                    }
+                }
+            }
+        }
+        self.push_ctrl(FrameKind::TryTable, table.ty)?;
+        Ok(())
+    }
+    fn visit_throw_ref(&mut self) -> Self::Output {
+        self.pop_operand(Some(ValType::EXNREF))?;
+        self.unreachable()?;
+        Ok(())
+    }
     fn visit_catch(&mut self, index: u32) -> Self::Output {
         let frame = self.pop_ctrl()?;
         if frame.kind != FrameKind::Try && frame.kind != FrameKind::Catch {
@@ -1282,6 +1811,7 @@ where
             if tys.len() != default_types.len() {
                 bail!(
                     self.offset,
+                    ErrorKind::TypeMismatch,
                     "type mismatch: br_table target labels have different number of types"
                 );
             }
@@ -1324,7 +1854,8 @@ where
             let expected = RefType::concrete(
                 true,
                 unpacked_index.pack().ok_or_else(|| {
-                    BinaryReaderError::new(
+                    BinaryReaderError::with_kind(
+                        ErrorKind::LimitExceeded,
                         "implementation limit: type index too large",
                         self.offset,
                     )
@@ -1335,6 +1866,7 @@ where
             if !self.resources.is_subtype(ValType::Ref(rt), expected) {
                 bail!(
                     self.offset,
+                    ErrorKind::TypeMismatch,
                     "type mismatch: funcref on stack does not match specified type",
                 );
             }
@@ -1354,6 +1886,7 @@ where
         if table_byte != 0 && !self.features.reference_types {
             bail!(
                 self.offset,
+                ErrorKind::FeatureDisabled,
                 "reference-types not enabled: zero byte expected"
             );
         }
@@ -1383,6 +1916,7 @@ where
             | (_, MaybeType::Type(ValType::Ref(_))) => {
                 bail!(
                     self.offset,
+                    ErrorKind::TypeMismatch,
                     "type mismatch: select only takes integral types"
                 )
             }
@@ -1397,6 +1931,7 @@ where
                 if t1 != t2 {
                     bail!(
                         self.offset,
+                        ErrorKind::TypeMismatch,
                         "type mismatch: select operands have different types"
                     );
                 }
@@ -1451,7 +1986,11 @@ where
             debug_assert_type_indices_are_ids(ty);
             self.push_operand(ty)?;
         } else {
-            bail!(self.offset, "unknown global: global index out of bounds");
+            bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown global: global index out of bounds"
+            );
         };
         Ok(())
     }
@@ -1465,7 +2004,11 @@ where
             }
             self.pop_operand(Some(ty.content_type))?;
         } else {
-            bail!(self.offset, "unknown global: global index out of bounds");
+            bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown global: global index out of bounds"
+            );
         };
         Ok(())
     }
@@ -1598,7 +2141,11 @@ where
     }
     fn visit_memory_size(&mut self, mem: u32, mem_byte: u8) -> Self::Output {
         if mem_byte != 0 && !self.features.multi_memory {
-            bail!(self.offset, "multi-memory not enabled: zero byte expected");
+            bail!(
+                self.offset,
+                ErrorKind::FeatureDisabled,
+                "multi-memory not enabled: zero byte expected"
+            );
         }
         let index_ty = self.check_memory_index(mem)?;
         self.push_operand(index_ty)?;
@@ -1606,7 +2153,11 @@ where
     }
     fn visit_memory_grow(&mut self, mem: u32, mem_byte: u8) -> Self::Output {
         if mem_byte != 0 && !self.features.multi_memory {
-            bail!(self.offset, "multi-memory not enabled: zero byte expected");
+            bail!(
+                self.offset,
+                ErrorKind::FeatureDisabled,
+                "multi-memory not enabled: zero byte expected"
+            );
         }
         let index_ty = self.check_memory_index(mem)?;
         self.pop_operand(Some(index_ty))?;
@@ -2295,6 +2846,7 @@ where
         match (lts.next_back(), ty) {
             (None, _) => bail!(
                 self.offset,
+                ErrorKind::TypeMismatch,
                 "type mismatch: br_on_non_null target has no label types",
             ),
             (Some(ValType::Ref(_)), None) => {}
@@ -2306,6 +2858,7 @@ where
                 if !self.resources.is_subtype(ty.into(), rt1) {
                     bail!(
                         self.offset,
+                        ErrorKind::TypeMismatch,
                         "type mismatch: expected {} but found {}",
                         ty_to_str(rt0.into()),
                         ty_to_str(rt1)
@@ -2314,6 +2867,7 @@ where
             }
             (Some(_), _) => bail!(
                 self.offset,
+                ErrorKind::TypeMismatch,
                 "type mismatch: br_on_non_null target does not end with heap type",
             ),
         }
@@ -2335,6 +2889,7 @@ where
             Some(idx) => idx,
             None => bail!(
                 self.offset,
+                ErrorKind::UnknownIndex,
                 "unknown function {}: function index out of bounds",
                 function_index,
             ),
@@ -2347,7 +2902,11 @@ where
         // proposals.
         if self.features.function_references {
             let index = PackedIndex::from_module_index(type_index).ok_or_else(|| {
-                BinaryReaderError::new("implementation limit: type index too large", self.offset)
+                BinaryReaderError::with_kind(
+                    ErrorKind::LimitExceeded,
+                    "implementation limit: type index too large",
+                    self.offset,
+                )
             })?;
             let mut ty = ValType::Ref(RefType::concrete(false, index));
             self.resources.canonicalize_valtype(&mut ty);
@@ -3246,7 +3805,12 @@ where
         match self.resources.data_count() {
             None => bail!(self.offset, "data count section required"),
             Some(count) if segment < count => {}
-            Some(_) => bail!(self.offset, "unknown data segment {}", segment),
+            Some(_) => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown data segment {}",
+                segment
+            ),
         }
         self.pop_operand(Some(ValType::I32))?;
         self.pop_operand(Some(ValType::I32))?;
@@ -3257,7 +3821,12 @@ where
         match self.resources.data_count() {
             None => bail!(self.offset, "data count section required"),
             Some(count) if segment < count => {}
-            Some(_) => bail!(self.offset, "unknown data segment {}", segment),
+            Some(_) => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "unknown data segment {}",
+                segment
+            ),
         }
         Ok(())
     }
@@ -3297,6 +3866,7 @@ where
             Some(table) => table,
             None => bail!(
                 self.offset,
+                ErrorKind::UnknownIndex,
                 "unknown table {}: table index out of bounds",
                 table
             ),
@@ -3305,6 +3875,7 @@ where
             Some(ty) => ty,
             None => bail!(
                 self.offset,
+                ErrorKind::UnknownIndex,
                 "unknown elem segment {}: segment index out of bounds",
                 segment
             ),
@@ -3313,7 +3884,7 @@ where
             .resources
             .is_subtype(ValType::Ref(segment_ty), ValType::Ref(table.element_type))
         {
-            bail!(self.offset, "type mismatch");
+            bail!(self.offset, ErrorKind::TypeMismatch, "type mismatch");
         }
         self.pop_operand(Some(ValType::I32))?;
         self.pop_operand(Some(ValType::I32))?;
@@ -3324,6 +3895,7 @@ where
         if segment >= self.resources.element_count() {
             bail!(
                 self.offset,
+                ErrorKind::UnknownIndex,
                 "unknown elem segment {}: segment index out of bounds",
                 segment
             );
@@ -3337,13 +3909,17 @@ where
             self.resources.table_at(dst_table),
         ) {
             (Some(a), Some(b)) => (a, b),
-            _ => bail!(self.offset, "table index out of bounds"),
+            _ => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            ),
         };
         if !self.resources.is_subtype(
             ValType::Ref(src.element_type),
             ValType::Ref(dst.element_type),
         ) {
-            bail!(self.offset, "type mismatch");
+            bail!(self.offset, ErrorKind::TypeMismatch, "type mismatch");
         }
         self.pop_operand(Some(ValType::I32))?;
         self.pop_operand(Some(ValType::I32))?;
@@ -3353,7 +3929,11 @@ where
     fn visit_table_get(&mut self, table: u32) -> Self::Output {
         let ty = match self.resources.table_at(table) {
             Some(ty) => ty.element_type,
-            None => bail!(self.offset, "table index out of bounds"),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            ),
         };
         let ty = ValType::Ref(ty);
         debug_assert_type_indices_are_ids(ty);
@@ -3364,7 +3944,11 @@ where
     fn visit_table_set(&mut self, table: u32) -> Self::Output {
         let ty = match self.resources.table_at(table) {
             Some(ty) => ValType::Ref(ty.element_type),
-            None => bail!(self.offset, "table index out of bounds"),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            ),
         };
         debug_assert_type_indices_are_ids(ty);
         self.pop_operand(Some(ty))?;
@@ -3374,7 +3958,11 @@ where
     fn visit_table_grow(&mut self, table: u32) -> Self::Output {
         let ty = match self.resources.table_at(table) {
             Some(ty) => ValType::Ref(ty.element_type),
-            None => bail!(self.offset, "table index out of bounds"),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            ),
         };
         debug_assert_type_indices_are_ids(ty);
         self.pop_operand(Some(ValType::I32))?;
@@ -3384,7 +3972,11 @@ where
     }
     fn visit_table_size(&mut self, table: u32) -> Self::Output {
         if self.resources.table_at(table).is_none() {
-            bail!(self.offset, "table index out of bounds");
+            bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            );
         }
         self.push_operand(ValType::I32)?;
         Ok(())
@@ -3392,7 +3984,11 @@ where
     fn visit_table_fill(&mut self, table: u32) -> Self::Output {
         let ty = match self.resources.table_at(table) {
             Some(ty) => ValType::Ref(ty.element_type),
-            None => bail!(self.offset, "table index out of bounds"),
+            None => bail!(
+                self.offset,
+                ErrorKind::UnknownIndex,
+                "table index out of bounds"
+            ),
         };
         debug_assert_type_indices_are_ids(ty);
         self.pop_operand(Some(ValType::I32))?;
@@ -3400,27 +3996,383 @@ where
         self.pop_operand(Some(ValType::I32))?;
         Ok(())
     }
+    fn visit_ref_eq(&mut self) -> Self::Output {
+        self.pop_operand(Some(RefType::EQ.nullable().into()))?;
+        self.pop_operand(Some(RefType::EQ.nullable().into()))?;
+        self.push_operand(ValType::I32)
+    }
+    fn visit_struct_new(&mut self, struct_type_index: u32) -> Self::Output {
+        let struct_ty = self.struct_type_at(struct_type_index)?;
+        for ty in struct_ty.fields.iter().rev() {
+            self.pop_operand(Some(ty.element_type.unpack()))?;
+        }
+        self.push_operand(self.concrete_ref_type(false, struct_type_index)?)
+    }
+    fn visit_struct_new_default(&mut self, struct_type_index: u32) -> Self::Output {
+        let ty = self.struct_type_at(struct_type_index)?;
+        for field in ty.fields.iter() {
+            if !field.element_type.unpack().is_defaultable() {
+                bail!(
+                    self.offset,
+                    "invalid `struct.new_default`: field type is not defaultable"
+                );
+            }
+        }
+        self.push_operand(self.concrete_ref_type(false, struct_type_index)?)
+    }
+    fn visit_struct_get(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.check_struct_get(struct_type_index, field_index, false)
+    }
+    fn visit_struct_get_s(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.check_struct_get(struct_type_index, field_index, true)
+    }
+    fn visit_struct_get_u(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        self.check_struct_get(struct_type_index, field_index, true)
+    }
+    fn visit_struct_set(&mut self, struct_type_index: u32, field_index: u32) -> Self::Output {
+        let field = self.mutable_struct_field_at(struct_type_index, field_index)?;
+        self.pop_operand(Some(field.element_type.unpack()))?;
+        let ty = self.concrete_ref_type(true, struct_type_index)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_array_new(&mut self, array_type_index: u32) -> Self::Output {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(elem_ty.unpack()))?;
+        self.push_operand(self.concrete_ref_type(false, array_type_index)?)
+    }
+    fn visit_array_new_default(&mut self, array_type_index: u32) -> Self::Output {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type;
+        if !elem_ty.unpack().is_defaultable() {
+            bail!(
+                self.offset,
+                "invalid `array.new_default`: array type is not defaultable"
+            );
+        }
+        self.pop_operand(Some(ValType::I32))?;
+        self.push_operand(self.concrete_ref_type(false, array_type_index)?)
+    }
+    fn visit_array_new_fixed(&mut self, array_type_index: u32, array_size: u32) -> Self::Output {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type.unpack();
+        for _ in 0..array_size {
+            self.pop_operand(Some(elem_ty))?;
+        }
+        self.push_operand(self.concrete_ref_type(false, array_type_index)?)
+    }
+    fn visit_array_new_data(
+        &mut self,
+        array_type_index: u32,
+        array_data_index: u32,
+    ) -> Self::Output {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type;
+        self.check_array_data_elem_type(elem_ty)?;
+        self.check_data_segment(array_data_index)?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.push_operand(self.concrete_ref_type(false, array_type_index)?)
+    }
+    fn visit_array_new_elem(
+        &mut self,
+        array_type_index: u32,
+        array_elem_index: u32,
+    ) -> Self::Output {
+        let elem_ty = self.array_type_at(array_type_index)?.element_type;
+        self.check_array_elem_segment(elem_ty, array_elem_index)?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.push_operand(self.concrete_ref_type(false, array_type_index)?)
+    }
+    fn visit_array_get(&mut self, array_type_index: u32) -> Self::Output {
+        self.check_array_get(array_type_index, false)
+    }
+    fn visit_array_get_s(&mut self, array_type_index: u32) -> Self::Output {
+        self.check_array_get(array_type_index, true)
+    }
+    fn visit_array_get_u(&mut self, array_type_index: u32) -> Self::Output {
+        self.check_array_get(array_type_index, true)
+    }
+    fn visit_array_set(&mut self, array_type_index: u32) -> Self::Output {
+        let elem_ty = self.mutable_array_type_at(array_type_index)?.element_type;
+        self.pop_operand(Some(elem_ty.unpack()))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_array_len(&mut self) -> Self::Output {
+        self.pop_operand(Some(RefType::ARRAY.nullable().into()))?;
+        self.push_operand(ValType::I32)
+    }
+    fn visit_array_fill(&mut self, array_type_index: u32) -> Self::Output {
+        let elem_ty = self.mutable_array_type_at(array_type_index)?.element_type;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(elem_ty.unpack()))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_array_copy(
+        &mut self,
+        array_type_index_dst: u32,
+        array_type_index_src: u32,
+    ) -> Self::Output {
+        let dst_elem_ty = self
+            .mutable_array_type_at(array_type_index_dst)?
+            .element_type;
+        let src_elem_ty = self.array_type_at(array_type_index_src)?.element_type;
+
+        // Packed storage types can only be copied between arrays of exactly
+        // the same packed type, otherwise the source's element type must be a
+        // subtype of the destination's.
+        let ok = match (src_elem_ty, dst_elem_ty) {
+            (StorageType::Val(src), StorageType::Val(dst)) => self.resources.is_subtype(src, dst),
+            (src, dst) => src == dst,
+        };
+        if !ok {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: array.copy source element type is not a subtype \
+                 of the destination element type"
+            );
+        }
+
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index_src)?;
+        self.pop_operand(Some(ty))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index_dst)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_array_init_data(
+        &mut self,
+        array_type_index: u32,
+        array_data_index: u32,
+    ) -> Self::Output {
+        let elem_ty = self.mutable_array_type_at(array_type_index)?.element_type;
+        self.check_array_data_elem_type(elem_ty)?;
+        self.check_data_segment(array_data_index)?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_array_init_elem(
+        &mut self,
+        array_type_index: u32,
+        array_elem_index: u32,
+    ) -> Self::Output {
+        let elem_ty = self.mutable_array_type_at(array_type_index)?.element_type;
+        self.check_array_elem_segment(elem_ty, array_elem_index)?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        self.pop_operand(Some(ValType::I32))?;
+        let ty = self.concrete_ref_type(true, array_type_index)?;
+        self.pop_operand(Some(ty))?;
+        Ok(())
+    }
+    fn visit_ref_test_non_null(&mut self, hty: HeapType) -> Self::Output {
+        self.check_ref_test(false, hty)
+    }
+    fn visit_ref_test_nullable(&mut self, hty: HeapType) -> Self::Output {
+        self.check_ref_test(true, hty)
+    }
+    fn visit_ref_cast_non_null(&mut self, hty: HeapType) -> Self::Output {
+        self.check_ref_cast(false, hty)
+    }
+    fn visit_ref_cast_nullable(&mut self, hty: HeapType) -> Self::Output {
+        self.check_ref_cast(true, hty)
+    }
+    fn visit_br_on_cast(
+        &mut self,
+        relative_depth: u32,
+        from_ref_type: RefType,
+        to_ref_type: RefType,
+    ) -> Self::Output {
+        self.check_br_on_cast(relative_depth, from_ref_type, to_ref_type, false)
+    }
+    fn visit_br_on_cast_fail(
+        &mut self,
+        relative_depth: u32,
+        from_ref_type: RefType,
+        to_ref_type: RefType,
+    ) -> Self::Output {
+        self.check_br_on_cast(relative_depth, from_ref_type, to_ref_type, true)
+    }
+    fn visit_any_convert_extern(&mut self) -> Self::Output {
+        let any_ref = match self.pop_operand(Some(RefType::EXTERNREF.into()))? {
+            MaybeType::Bot | MaybeType::HeapBot => MaybeType::HeapBot,
+            MaybeType::Type(ValType::Ref(rt)) => {
+                let ty = if rt.is_nullable() {
+                    RefType::ANY.nullable()
+                } else {
+                    RefType::ANY
+                };
+                MaybeType::Type(ty.into())
+            }
+            MaybeType::Type(_) => unreachable!(),
+        };
+        self.push_operand(any_ref)
+    }
+    fn visit_extern_convert_any(&mut self) -> Self::Output {
+        let extern_ref = match self.pop_operand(Some(RefType::ANY.nullable().into()))? {
+            MaybeType::Bot | MaybeType::HeapBot => MaybeType::HeapBot,
+            MaybeType::Type(ValType::Ref(rt)) => {
+                let ty = if rt.is_nullable() {
+                    RefType::EXTERN.nullable()
+                } else {
+                    RefType::EXTERN
+                };
+                MaybeType::Type(ty.into())
+            }
+            MaybeType::Type(_) => unreachable!(),
+        };
+        self.push_operand(extern_ref)
+    }
     fn visit_ref_i31(&mut self) -> Self::Output {
         self.pop_operand(Some(ValType::I32))?;
         self.push_operand(ValType::Ref(RefType::I31))
     }
     fn visit_i31_get_s(&mut self) -> Self::Output {
-        match self.pop_ref()? {
-            Some(ref_type) => match ref_type.heap_type() {
-                HeapType::I31 => self.push_operand(ValType::I32),
-                _ => bail!(self.offset, "ref heap type mismatch: expected i31"),
-            },
-            _ => bail!(self.offset, "type mismatch: expected (ref null? i31)"),
-        }
+        self.pop_operand(Some(ValType::Ref(RefType::I31.nullable())))?;
+        self.push_operand(ValType::I32)
     }
     fn visit_i31_get_u(&mut self) -> Self::Output {
-        match self.pop_ref()? {
-            Some(ref_type) => match ref_type.heap_type() {
-                HeapType::I31 => self.push_operand(ValType::I32),
-                _ => bail!(self.offset, "ref heap type mismatch: expected i31"),
-            },
-            _ => bail!(self.offset, "type mismatch: expected (ref null? i31)"),
+        self.pop_operand(Some(ValType::Ref(RefType::I31.nullable())))?;
+        self.push_operand(ValType::I32)
+    }
+    fn visit_cont_new(&mut self, cont_type_index: u32) -> Self::Output {
+        let (func_type_index, _) = self.cont_type_at(cont_type_index)?;
+        self.pop_operand(Some(RefType::concrete(true, func_type_index).into()))?;
+        let ty = self.concrete_ref_type(false, cont_type_index)?;
+        self.push_operand(ty)
+    }
+    fn visit_cont_bind(&mut self, argument_index: u32, result_index: u32) -> Self::Output {
+        let (_, argument) = self.cont_type_at(argument_index)?;
+        let (_, result) = self.cont_type_at(result_index)?;
+        let bound = match argument.params().len().checked_sub(result.params().len()) {
+            Some(bound) => bound,
+            None => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: cont.bind result type has more parameters than argument type"
+            ),
+        };
+        let remaining = FuncType::new(
+            argument.params()[bound..].iter().copied(),
+            argument.results().iter().copied(),
+        );
+        if !self.func_type_is_subtype(&remaining, &result) {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: cont.bind argument type does not match result type"
+            );
+        }
+        let ty = self.concrete_ref_type(true, argument_index)?;
+        self.pop_operand(Some(ty))?;
+        for ty in argument.params()[..bound].iter().rev() {
+            self.pop_operand(Some(*ty))?;
+        }
+        let ty = self.concrete_ref_type(false, result_index)?;
+        self.push_operand(ty)
+    }
+    fn visit_suspend(&mut self, tag_index: u32) -> Self::Output {
+        let ty = self.tag_at(tag_index)?;
+        for ty in ty.clone().inputs().rev() {
+            self.pop_operand(Some(ty))?;
+        }
+        for ty in ty.outputs() {
+            self.push_operand(ty)?;
+        }
+        Ok(())
+    }
+    fn visit_resume(&mut self, cont_type_index: u32, resume_table: ResumeTable) -> Self::Output {
+        let (_, func) = self.cont_type_at(cont_type_index)?;
+        self.check_resume_table(&resume_table, &func)?;
+        let ty = self.concrete_ref_type(true, cont_type_index)?;
+        self.pop_operand(Some(ty))?;
+        for ty in func.params().iter().rev() {
+            self.pop_operand(Some(*ty))?;
+        }
+        for ty in func.results() {
+            self.push_operand(*ty)?;
+        }
+        Ok(())
+    }
+    fn visit_resume_throw(
+        &mut self,
+        cont_type_index: u32,
+        tag_index: u32,
+        resume_table: ResumeTable,
+    ) -> Self::Output {
+        let (_, func) = self.cont_type_at(cont_type_index)?;
+        let tag = self.tag_at(tag_index)?;
+        if tag.len_outputs() > 0 {
+            bail!(
+                self.offset,
+                "result type expected to be empty for exception"
+            );
+        }
+        self.check_resume_table(&resume_table, &func)?;
+        let ty = self.concrete_ref_type(true, cont_type_index)?;
+        self.pop_operand(Some(ty))?;
+        for ty in tag.inputs().rev() {
+            self.pop_operand(Some(ty))?;
         }
+        for ty in func.results() {
+            self.push_operand(*ty)?;
+        }
+        Ok(())
+    }
+    fn visit_switch(&mut self, cont_type_index: u32, tag_index: u32) -> Self::Output {
+        // The continuation being switched to receives `params` along with a
+        // continuation of type `current` for the code after the `switch`.
+        let (_, func) = self.cont_type_at(cont_type_index)?;
+        let current = match func.params().split_last() {
+            Some((last, params)) => self.cont_func_type_of_ref(*last).map(|ty| (params, ty)),
+            None => None,
+        };
+        let (params, current) = match current {
+            Some(pair) => pair,
+            None => bail!(
+                self.offset,
+                ErrorKind::TypeMismatch, "type mismatch: switch continuation type must take a continuation as its last parameter"
+            ),
+        };
+        let tag = self.tag_at(tag_index)?;
+        if tag.len_inputs() != 0 {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: switch tag must not have parameters"
+            );
+        }
+        let results = tag.outputs().collect::<Vec<_>>();
+        if !self.types_are_subtypes(func.results(), &results)
+            || !self.types_are_subtypes(current.results(), &results)
+        {
+            bail!(
+                self.offset,
+                ErrorKind::TypeMismatch,
+                "type mismatch: switch tag results do not match continuation results"
+            );
+        }
+        let ty = self.concrete_ref_type(true, cont_type_index)?;
+        self.pop_operand(Some(ty))?;
+        for ty in params.iter().rev() {
+            self.pop_operand(Some(*ty))?;
+        }
+        for ty in current.params() {
+            self.push_operand(*ty)?;
+        }
+        Ok(())
     }
 }
 
@@ -3484,7 +4436,7 @@ impl Locals {
             Some(n) => self.num_locals = n,
             None => return false,
         }
-        if self.num_locals > (MAX_WASM_FUNCTION_LOCALS as u32) {
+        if self.num_locals > self.max_locals {
             return false;
         }
         for _ in 0..count {
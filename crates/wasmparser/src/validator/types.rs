@@ -299,6 +299,7 @@ impl TypeData for SubType {
             CompositeType::Func(ty) => 1 + (ty.params().len() + ty.results().len()) as u32,
             CompositeType::Array(_) => 2,
             CompositeType::Struct(ty) => 1 + 2 * ty.fields.len() as u32,
+            CompositeType::Cont(_) => 1,
         };
         TypeInfo::core(size)
     }
@@ -317,7 +318,7 @@ impl CoreType {
     pub fn unwrap_func(&self) -> &FuncType {
         match &self.unwrap_sub().composite_type {
             CompositeType::Func(f) => f,
-            CompositeType::Array(_) | CompositeType::Struct(_) => {
+            CompositeType::Array(_) | CompositeType::Struct(_) | CompositeType::Cont(_) => {
                 panic!("`unwrap_func` on non-func composite type")
             }
         }
@@ -1301,6 +1302,15 @@ pub enum ComponentDefinedType {
     Own(AliasableResourceId),
     /// The type is a borrowed handle to the specified resource.
     Borrow(AliasableResourceId),
+    /// The type is a `future`.
+    Future(Option<ComponentValType>),
+    /// The type is a `stream`.
+    Stream {
+        /// The type of the elements of the stream.
+        element: Option<ComponentValType>,
+        /// The type of the value produced when the stream ends.
+        end: Option<ComponentValType>,
+    },
 }
 
 impl TypeData for ComponentDefinedType {
@@ -1313,8 +1323,13 @@ impl TypeData for ComponentDefinedType {
             Self::Record(r) => r.info,
             Self::Variant(v) => v.info,
             Self::Tuple(t) => t.info,
-            Self::List(ty) | Self::Option(ty) => ty.info(types),
-            Self::Result { ok, err } => {
+            Self::List(ty) | Self::Option(ty) | Self::Future(Some(ty)) => ty.info(types),
+            Self::Future(None) => TypeInfo::new(),
+            Self::Result { ok, err }
+            | Self::Stream {
+                element: ok,
+                end: err,
+            } => {
                 let default = TypeInfo::new();
                 let mut info = ok.map(|ty| ty.type_info(types)).unwrap_or(default);
                 info.combine(err.map(|ty| ty.type_info(types)).unwrap_or(default), 0)
@@ -1336,7 +1351,12 @@ impl ComponentDefinedType {
                 .any(|case| case.ty.map(|ty| ty.contains_ptr(types)).unwrap_or(false)),
             Self::List(_) => true,
             Self::Tuple(t) => t.types.iter().any(|ty| ty.contains_ptr(types)),
-            Self::Flags(_) | Self::Enum(_) | Self::Own(_) | Self::Borrow(_) => false,
+            Self::Flags(_)
+            | Self::Enum(_)
+            | Self::Own(_)
+            | Self::Borrow(_)
+            | Self::Future(_)
+            | Self::Stream { .. } => false,
             Self::Option(ty) => ty.contains_ptr(types),
             Self::Result { ok, err } => {
                 ok.map(|ty| ty.contains_ptr(types)).unwrap_or(false)
@@ -1365,7 +1385,11 @@ impl ComponentDefinedType {
             Self::Flags(names) => {
                 (0..(names.len() + 31) / 32).all(|_| lowered_types.push(ValType::I32))
             }
-            Self::Enum(_) | Self::Own(_) | Self::Borrow(_) => lowered_types.push(ValType::I32),
+            Self::Enum(_)
+            | Self::Own(_)
+            | Self::Borrow(_)
+            | Self::Future(_)
+            | Self::Stream { .. } => lowered_types.push(ValType::I32),
             Self::Option(ty) => {
                 Self::push_variant_wasm_types([ty].into_iter(), types, lowered_types)
             }
@@ -1433,6 +1457,8 @@ impl ComponentDefinedType {
             ComponentDefinedType::Result { .. } => "result",
             ComponentDefinedType::Own(_) => "own",
             ComponentDefinedType::Borrow(_) => "borrow",
+            ComponentDefinedType::Future(_) => "future",
+            ComponentDefinedType::Stream { .. } => "stream",
         }
     }
 }
@@ -2572,6 +2598,7 @@ impl TypeList {
         } else {
             bail!(
                 offset,
+                ErrorKind::UnknownIndex,
                 "unknown type {index}: type index out of rec group bounds"
             )
         }
@@ -2695,6 +2722,7 @@ impl TypeList {
             (HT::I31 | HT::Struct | HT::Array | HT::None, HT::Eq) => true,
             (HT::NoExtern, HT::Extern) => true,
             (HT::NoFunc, HT::Func) => true,
+            (HT::NoCont, HT::Cont) => true,
             (HT::None, HT::I31 | HT::Array | HT::Struct) => true,
 
             (HT::Concrete(a), HT::Eq | HT::Any) => matches!(
@@ -2714,6 +2742,10 @@ impl TypeList {
                 matches!(subtype(a_group, a).composite_type, CompositeType::Func(_))
             }
 
+            (HT::Concrete(a), HT::Cont) => {
+                matches!(subtype(a_group, a).composite_type, CompositeType::Cont(_))
+            }
+
             (HT::Concrete(a), HT::Concrete(b)) => {
                 self.id_is_subtype(core_type_id(a_group, a), core_type_id(b_group, b))
             }
@@ -2727,6 +2759,10 @@ impl TypeList {
                 matches!(subtype(b_group, b).composite_type, CompositeType::Func(_))
             }
 
+            (HT::NoCont, HT::Concrete(b)) => {
+                matches!(subtype(b_group, b).composite_type, CompositeType::Cont(_))
+            }
+
             // Nothing else matches. (Avoid full wildcard matches so that
             // adding/modifying variants is easier in the future.)
             (HT::Concrete(_), _)
@@ -2739,7 +2775,38 @@ impl TypeList {
             | (HT::Eq, _)
             | (HT::Struct, _)
             | (HT::Array, _)
-            | (HT::I31, _) => false,
+            | (HT::I31, _)
+            | (HT::Cont, _)
+            | (HT::NoCont, _)
+            | (HT::Exn, _) => false,
+        }
+    }
+
+    /// Get the top type of the hierarchy that the given heap type belongs to.
+    ///
+    /// Concrete heap types must be canonicalized to `CoreTypeId`s already.
+    pub fn top_type(&self, heap_type: &HeapType) -> HeapType {
+        match *heap_type {
+            HeapType::Concrete(idx) => {
+                let id = idx
+                    .as_core_type_id()
+                    .expect("heap type should be canonicalized");
+                match self[id].composite_type {
+                    CompositeType::Func(_) => HeapType::Func,
+                    CompositeType::Array(_) | CompositeType::Struct(_) => HeapType::Any,
+                    CompositeType::Cont(_) => HeapType::Cont,
+                }
+            }
+            HeapType::Func | HeapType::NoFunc => HeapType::Func,
+            HeapType::Cont | HeapType::NoCont => HeapType::Cont,
+            HeapType::Exn => HeapType::Exn,
+            HeapType::Extern | HeapType::NoExtern => HeapType::Extern,
+            HeapType::Any
+            | HeapType::Eq
+            | HeapType::Struct
+            | HeapType::Array
+            | HeapType::I31
+            | HeapType::None => HeapType::Any,
         }
     }
 
@@ -3043,7 +3110,11 @@ impl TypeAlloc {
             ComponentDefinedType::List(ty) | ComponentDefinedType::Option(ty) => {
                 self.free_variables_valtype(ty, set);
             }
-            ComponentDefinedType::Result { ok, err } => {
+            ComponentDefinedType::Result { ok, err }
+            | ComponentDefinedType::Stream {
+                element: ok,
+                end: err,
+            } => {
                 if let Some(ok) = ok {
                     self.free_variables_valtype(ok, set);
                 }
@@ -3051,6 +3122,11 @@ impl TypeAlloc {
                     self.free_variables_valtype(err, set);
                 }
             }
+            ComponentDefinedType::Future(ty) => {
+                if let Some(ty) = ty {
+                    self.free_variables_valtype(ty, set);
+                }
+            }
             ComponentDefinedType::Own(id) | ComponentDefinedType::Borrow(id) => {
                 set.insert(id.resource());
             }
@@ -3168,7 +3244,11 @@ impl TypeAlloc {
             ComponentDefinedType::Tuple(r) => {
                 r.types.iter().all(|t| self.type_named_valtype(t, set))
             }
-            ComponentDefinedType::Result { ok, err } => {
+            ComponentDefinedType::Result { ok, err }
+            | ComponentDefinedType::Stream {
+                element: ok,
+                end: err,
+            } => {
                 ok.as_ref()
                     .map(|t| self.type_named_valtype(t, set))
                     .unwrap_or(true)
@@ -3180,6 +3260,10 @@ impl TypeAlloc {
             ComponentDefinedType::List(ty) | ComponentDefinedType::Option(ty) => {
                 self.type_named_valtype(ty, set)
             }
+            ComponentDefinedType::Future(ty) => ty
+                .as_ref()
+                .map(|ty| self.type_named_valtype(ty, set))
+                .unwrap_or(true),
 
             // own/borrow themselves don't have to be named, but the resource
             // they refer to must be named.
@@ -3357,7 +3441,11 @@ where
             ComponentDefinedType::List(ty) | ComponentDefinedType::Option(ty) => {
                 any_changed |= self.remap_valtype(ty, map);
             }
-            ComponentDefinedType::Result { ok, err } => {
+            ComponentDefinedType::Result { ok, err }
+            | ComponentDefinedType::Stream {
+                element: ok,
+                end: err,
+            } => {
                 if let Some(ok) = ok {
                     any_changed |= self.remap_valtype(ok, map);
                 }
@@ -3365,6 +3453,11 @@ where
                     any_changed |= self.remap_valtype(err, map);
                 }
             }
+            ComponentDefinedType::Future(ty) => {
+                if let Some(ty) = ty {
+                    any_changed |= self.remap_valtype(ty, map);
+                }
+            }
             ComponentDefinedType::Own(id) | ComponentDefinedType::Borrow(id) => {
                 any_changed |= self.remap_resource_id(id, map);
             }
@@ -4307,6 +4400,44 @@ impl<'a> SubtypeCx<'a> {
             }
             (Own(_), b) => bail!(offset, "expected {}, found own", b.desc()),
             (Borrow(_), b) => bail!(offset, "expected {}, found borrow", b.desc()),
+            (Future(a), Future(b)) => match (a, b) {
+                (None, None) => Ok(()),
+                (Some(a), Some(b)) => self
+                    .component_val_type(a, b, offset)
+                    .with_context(|| "type mismatch in future"),
+                (None, Some(_)) => bail!(offset, "expected future type, but found none"),
+                (Some(_), None) => bail!(offset, "expected future type to not be present"),
+            },
+            (Future(_), b) => bail!(offset, "expected {}, found future", b.desc()),
+            (
+                Stream {
+                    element: ae,
+                    end: an,
+                },
+                Stream {
+                    element: be,
+                    end: bn,
+                },
+            ) => {
+                match (ae, be) {
+                    (None, None) => {}
+                    (Some(a), Some(b)) => self
+                        .component_val_type(a, b, offset)
+                        .with_context(|| "type mismatch in stream element")?,
+                    (None, Some(_)) => bail!(offset, "expected element type, but found none"),
+                    (Some(_), None) => bail!(offset, "expected element type to not be present"),
+                }
+                match (an, bn) {
+                    (None, None) => {}
+                    (Some(a), Some(b)) => self
+                        .component_val_type(a, b, offset)
+                        .with_context(|| "type mismatch in stream end")?,
+                    (None, Some(_)) => bail!(offset, "expected end type, but found none"),
+                    (Some(_), None) => bail!(offset, "expected end type to not be present"),
+                }
+                Ok(())
+            }
+            (Stream { .. }, b) => bail!(offset, "expected {}, found stream", b.desc()),
         }
     }
 
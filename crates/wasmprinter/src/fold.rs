@@ -0,0 +1,1082 @@
+//! Support for printing function bodies in the folded S-expression form of
+//! the text format, for example `(i32.add (local.get 0) (i32.const 1))`.
+//!
+//! Folding is a purely syntactic transformation: `(op e1 e2)` means exactly
+//! the same thing as `e1 e2 op`. The operand-stack arity of each instruction
+//! is only used to decide which of the preceding instructions read best when
+//! nested as its operands, so an unknown or surprising arity never changes
+//! the meaning of the printed function, it just leaves more of it flat.
+
+use super::operator::{OpKind, PrintOperator};
+use super::{Printer, State};
+use std::mem;
+use wasmparser::{
+    BinaryReader, BlockType, CompositeType, ContType, Operator, SubType, VisitOperator,
+};
+
+/// Function bodies whose folded form would nest deeper than this are printed
+/// in the flat form instead.
+const MAX_FOLD_DEPTH: u32 = 1000;
+
+/// A folded instruction along with its folded operands and, for block
+/// instructions, the arms of the block.
+struct Node {
+    offset: usize,
+    /// The instruction as it's printed in the flat form.
+    text: String,
+    operands: Vec<Node>,
+    arms: Option<Vec<Arm>>,
+    /// Number of values this instruction pushes, if known.
+    results: Option<u32>,
+    depth: u32,
+}
+
+/// A `(then ...)`, `(else ...)`, `(do ...)`, `(catch ...)`, etc, clause of a
+/// block instruction, or the body of a `block` or `loop` if `header` is
+/// `None`.
+struct Arm {
+    offset: Option<usize>,
+    header: Option<String>,
+    body: Vec<Node>,
+}
+
+#[derive(PartialEq)]
+enum FrameKind {
+    Func,
+    Block,
+    If,
+    Try,
+}
+
+/// A block instruction which is still being folded.
+struct Frame {
+    kind: FrameKind,
+    offset: usize,
+    text: String,
+    operands: Vec<Node>,
+    arms: Vec<Arm>,
+    arm_offset: Option<usize>,
+    arm_header: Option<String>,
+    nodes: Vec<Node>,
+    label_arity: Option<u32>,
+    results: Option<u32>,
+}
+
+impl Frame {
+    fn finish_arm(&mut self) {
+        self.arms.push(Arm {
+            offset: self.arm_offset.take(),
+            header: self.arm_header.take(),
+            body: mem::take(&mut self.nodes),
+        });
+    }
+}
+
+struct Folder {
+    frames: Vec<Frame>,
+}
+
+impl Folder {
+    fn new(func_results: Option<u32>) -> Folder {
+        Folder {
+            frames: vec![Frame {
+                kind: FrameKind::Func,
+                offset: 0,
+                text: String::new(),
+                operands: Vec::new(),
+                arms: Vec::new(),
+                arm_offset: None,
+                arm_header: None,
+                nodes: Vec::new(),
+                label_arity: func_results,
+                results: func_results,
+            }],
+        }
+    }
+
+    /// Adds the next instruction of the function body to the folded form,
+    /// where `arity` is as computed by `Arity` for the instruction.
+    ///
+    /// Returns `None` if the function body isn't well-structured enough to be
+    /// folded, and `Some(nodes)` once the final `end` of the function has
+    /// been seen.
+    fn push(
+        &mut self,
+        op: &Operator<'_>,
+        kind: OpKind,
+        arity: Option<(u32, u32)>,
+        offset: usize,
+        text: String,
+    ) -> Option<Option<Vec<Node>>> {
+        match kind {
+            OpKind::BlockStart => {
+                // A block of unknown type is still folded, but nothing
+                // around it is folded into or out of it.
+                let (params, results) = match arity {
+                    Some((params, results)) => (Some(params), Some(results)),
+                    None => (None, None),
+                };
+                let (kind, arm_header) = match op {
+                    Operator::If { .. } => (FrameKind::If, Some("then".to_string())),
+                    Operator::Try { .. } => (FrameKind::Try, Some("do".to_string())),
+                    _ => (FrameKind::Block, None),
+                };
+                // The condition of an `if` is folded into it, but block
+                // parameters are left on the stack before the block.
+                let operands = match (&kind, params) {
+                    (FrameKind::If, Some(params)) => self.operands(params + 1),
+                    _ => Vec::new(),
+                };
+                let label_arity = match op {
+                    Operator::Loop { .. } => params,
+                    _ => results,
+                };
+                self.frames.push(Frame {
+                    kind,
+                    offset,
+                    text,
+                    operands,
+                    arms: Vec::new(),
+                    arm_offset: None,
+                    arm_header,
+                    nodes: Vec::new(),
+                    label_arity,
+                    results,
+                });
+            }
+
+            OpKind::BlockMid => {
+                let frame = self.frames.last_mut()?;
+                let ok = match op {
+                    Operator::Else => frame.kind == FrameKind::If && frame.arms.is_empty(),
+                    _ => frame.kind == FrameKind::Try,
+                };
+                if !ok {
+                    return None;
+                }
+                frame.finish_arm();
+                frame.arm_offset = Some(offset);
+                frame.arm_header = Some(text);
+            }
+
+            OpKind::End | OpKind::Delegate => {
+                let mut frame = self.frames.pop()?;
+                frame.finish_arm();
+                if frame.kind == FrameKind::Func {
+                    if matches!(kind, OpKind::Delegate) {
+                        return None;
+                    }
+                    return Some(Some(frame.arms.pop()?.body));
+                }
+                if matches!(kind, OpKind::Delegate) {
+                    if frame.kind != FrameKind::Try {
+                        return None;
+                    }
+                    frame.arms.push(Arm {
+                        offset: Some(offset),
+                        header: Some(text),
+                        body: Vec::new(),
+                    });
+                }
+                let depth = frame
+                    .operands
+                    .iter()
+                    .chain(frame.arms.iter().flat_map(|arm| arm.body.iter()))
+                    .map(|node| node.depth)
+                    .max()
+                    .unwrap_or(0)
+                    + 1;
+                let node = Node {
+                    offset: frame.offset,
+                    text: frame.text,
+                    operands: frame.operands,
+                    arms: Some(frame.arms),
+                    results: frame.results,
+                    depth,
+                };
+                self.push_node(node)?;
+            }
+
+            OpKind::Normal => {
+                let (operands, results) = match arity {
+                    Some((params, results)) => (self.operands(params), Some(results)),
+                    None => (Vec::new(), None),
+                };
+                let depth = operands.iter().map(|node| node.depth).max().unwrap_or(0) + 1;
+                self.push_node(Node {
+                    offset,
+                    text,
+                    operands,
+                    arms: None,
+                    results,
+                    depth,
+                })?;
+            }
+        }
+        Some(None)
+    }
+
+    fn push_node(&mut self, node: Node) -> Option<()> {
+        if node.depth > MAX_FOLD_DEPTH {
+            return None;
+        }
+        self.frames.last_mut()?.nodes.push(node);
+        Some(())
+    }
+
+    /// Removes and returns the trailing instructions of the current block
+    /// which together push exactly `count` values, or nothing if there's no
+    /// such sequence of instructions.
+    fn operands(&mut self, count: u32) -> Vec<Node> {
+        let nodes = match self.frames.last_mut() {
+            Some(frame) => &mut frame.nodes,
+            None => return Vec::new(),
+        };
+        let mut pushed = 0;
+        let mut start = nodes.len();
+        while pushed < count && start > 0 {
+            match nodes[start - 1].results {
+                Some(n) if n > 0 => pushed += n,
+                _ => break,
+            }
+            start -= 1;
+        }
+        if pushed == count {
+            nodes.split_off(start)
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn label_arity(&self, relative_depth: u32) -> Option<u32> {
+        let i = self.frames.len().checked_sub(relative_depth as usize + 1)?;
+        self.frames[i].label_arity
+    }
+}
+
+/// Computes the number of values popped and pushed by an instruction at the
+/// current position of a `Folder`, if known.
+///
+/// For block instructions this is the arity of the block's type, and for the
+/// instructions which continue or end a block it's `None`.
+struct Arity<'a> {
+    folder: &'a Folder,
+    state: &'a State,
+}
+
+impl Arity<'_> {
+    fn label_arity(&self, relative_depth: u32) -> Option<u32> {
+        self.folder.label_arity(relative_depth)
+    }
+}
+
+macro_rules! define_visit {
+    ($(@$proposal:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident )*) => ($(
+        #[allow(unused_variables)]
+        fn $visit(&mut self $( , $($arg: $argty),* )?) -> Self::Output {
+            define_visit!(arity self $op $( $($arg)* )?)
+        }
+    )*);
+
+    // There's one rule per instruction, with no fallback, so that new
+    // instructions have to be given an arity here. `None` means that the
+    // arity isn't known, which leaves the instruction's operands flat.
+    (arity $s:ident Block $blockty:ident) => (block_arity($s.state, $blockty));
+    (arity $s:ident If $blockty:ident) => (block_arity($s.state, $blockty));
+    (arity $s:ident Else) => (None);
+    (arity $s:ident Loop $blockty:ident) => (block_arity($s.state, $blockty));
+    (arity $s:ident End) => (None);
+    (arity $s:ident Unreachable $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident Nop $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident Br $relative_depth:ident) => (
+        Some(($s.label_arity($relative_depth)?, 0))
+    );
+    (arity $s:ident BrIf $relative_depth:ident) => ({
+        let n = $s.label_arity($relative_depth)?;
+        Some((n + 1, n))
+    });
+    (arity $s:ident BrOnNull $relative_depth:ident) => ({
+        let n = $s.label_arity($relative_depth)?;
+        Some((n + 1, n + 1))
+    });
+    (arity $s:ident BrOnNonNull $relative_depth:ident) => ({
+        let n = $s.label_arity($relative_depth)?;
+        Some((n, n.checked_sub(1)?))
+    });
+    (arity $s:ident BrTable $targets:ident) => (
+        Some(($s.label_arity($targets.default())? + 1, 0))
+    );
+    (arity $s:ident Return) => (Some(($s.folder.frames.first()?.label_arity?, 0)));
+    (arity $s:ident Call $function_index:ident) => (
+        func_type_arity($s.state, func_type_index($s.state, $function_index)?)
+    );
+    (arity $s:ident CallIndirect $type_index:ident $($rest:ident)*) => ({
+        let (params, results) = func_type_arity($s.state, $type_index)?;
+        Some((params + 1, results))
+    });
+    (arity $s:ident CallRef $type_index:ident) => ({
+        let (params, results) = func_type_arity($s.state, $type_index)?;
+        Some((params + 1, results))
+    });
+    (arity $s:ident ReturnCall $function_index:ident) => ({
+        let ty = func_type_index($s.state, $function_index)?;
+        let (params, _) = func_type_arity($s.state, ty)?;
+        Some((params, 0))
+    });
+    (arity $s:ident ReturnCallIndirect $type_index:ident $($rest:ident)*) => ({
+        let (params, _) = func_type_arity($s.state, $type_index)?;
+        Some((params + 1, 0))
+    });
+    (arity $s:ident ReturnCallRef $type_index:ident) => ({
+        let (params, _) = func_type_arity($s.state, $type_index)?;
+        Some((params + 1, 0))
+    });
+    (arity $s:ident Drop $($arg:ident)*) => (Some((1, 0)));
+    (arity $s:ident Select $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident TypedSelect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident LocalGet $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident LocalSet $($arg:ident)*) => (Some((1, 0)));
+    (arity $s:ident LocalTee $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident GlobalGet $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident GlobalSet $($arg:ident)*) => (Some((1, 0)));
+    (arity $s:ident TableGet $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident TableSet $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32Load $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Load $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Load $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Load8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Load8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Load16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Load16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Load32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Store $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64Store $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident F32Store $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident F64Store $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32Store8 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32Store16 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64Store8 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64Store16 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64Store32 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident MemorySize $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident MemoryGrow $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident MemoryInit $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident MemoryCopy $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident MemoryFill $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident MemoryDiscard $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident DataDrop $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident ElemDrop $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident TableInit $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident TableCopy $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident TableFill $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident TableSize $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident TableGrow $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident RefAsNonNull $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefNull $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident RefIsNull $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefFunc $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident I32Const $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident I64Const $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident F32Const $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident F64Const $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident I32Clz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Ctz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Popcnt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32DivS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32DivU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32RemS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32RemU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32And $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Or $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Xor $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Rotl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Rotr $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Clz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Ctz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Popcnt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64DivS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64DivU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64RemS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64RemU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64And $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Or $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Xor $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Rotl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Rotr $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Ceil $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Floor $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Trunc $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Nearest $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Sqrt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Div $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Min $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Max $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Copysign $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Ceil $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Floor $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Trunc $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Nearest $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Sqrt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Div $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Min $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Max $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Copysign $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Eqz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32LtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32GtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32LeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32GeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Eqz $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64LtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64GtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64LeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64GeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Lt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Gt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Le $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32Ge $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Lt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Gt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Le $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64Ge $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32WrapI64 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncF32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncF32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncF64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncF64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64ExtendI32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64ExtendI32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncF32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncF32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncF64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncF64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32ConvertI32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32ConvertI32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32ConvertI64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32ConvertI64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32DemoteF64 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64ConvertI32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64ConvertI32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64ConvertI64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64ConvertI64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64PromoteF32 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32ReinterpretF32 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64ReinterpretF64 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32ReinterpretI32 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64ReinterpretI64 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncSatF32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncSatF32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncSatF64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32TruncSatF64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncSatF32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncSatF32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncSatF64S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64TruncSatF64U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Extend8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32Extend16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Extend8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Extend16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64Extend32S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident MemoryAtomicNotify $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident MemoryAtomicWait32 $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident MemoryAtomicWait64 $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident AtomicFence $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident I32AtomicLoad $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64AtomicLoad $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32AtomicLoad8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32AtomicLoad16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64AtomicLoad8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64AtomicLoad16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64AtomicLoad32U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32AtomicStore $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64AtomicStore $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32AtomicStore8 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32AtomicStore16 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64AtomicStore8 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64AtomicStore16 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I64AtomicStore32 $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident I32AtomicRmwAdd $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwAdd $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8AddU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16AddU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8AddU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16AddU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32AddU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwSub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwSub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8SubU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16SubU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8SubU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16SubU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32SubU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwAnd $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwAnd $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8AndU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16AndU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8AndU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16AndU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32AndU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwOr $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwOr $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8OrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16OrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8OrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16OrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32OrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwXor $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwXor $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8XorU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16XorU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8XorU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16XorU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32XorU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwXchg $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmwXchg $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw8XchgU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmw16XchgU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw8XchgU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw16XchgU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64AtomicRmw32XchgU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32AtomicRmwCmpxchg $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I64AtomicRmwCmpxchg $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I32AtomicRmw8CmpxchgU $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I32AtomicRmw16CmpxchgU $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I64AtomicRmw8CmpxchgU $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I64AtomicRmw16CmpxchgU $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I64AtomicRmw32CmpxchgU $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident V128Load $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load8x8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load8x8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load16x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load16x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load32x2S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load32x2U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load8Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load16Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load32Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load64Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load32Zero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Load64Zero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128Store $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident V128Load8Lane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Load16Lane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Load32Lane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Load64Lane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Store8Lane $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident V128Store16Lane $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident V128Store32Lane $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident V128Store64Lane $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident V128Const $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident I8x16Shuffle $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16ExtractLaneS $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16ExtractLaneU $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtractLaneS $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ExtractLaneU $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtractLane $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ExtractLane $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4ExtractLane $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2ExtractLane $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2ReplaceLane $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Swizzle $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Splat $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16LtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16GtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16LeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16GeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8LtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8GtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8LeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8GeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4LtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4GtU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4LeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4GeU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2LtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2GtS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2LeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2GeS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Lt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Gt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Le $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Ge $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Eq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Ne $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Lt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Gt $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Le $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Ge $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Not $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident V128And $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128AndNot $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Or $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Xor $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident V128Bitselect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident V128AnyTrue $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16Popcnt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16AllTrue $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16Bitmask $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I8x16NarrowI16x8S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16NarrowI16x8U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16AddSatS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16AddSatU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16SubSatS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16SubSatU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16MinS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16MinU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16MaxS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16MaxU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I8x16AvgrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtAddPairwiseI8x16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ExtAddPairwiseI8x16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Q15MulrSatS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8AllTrue $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Bitmask $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8NarrowI32x4S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8NarrowI32x4U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtendLowI8x16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ExtendHighI8x16S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ExtendLowI8x16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8ExtendHighI8x16U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I16x8Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8AddSatS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8AddSatU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8SubSatS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8SubSatU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8MinS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8MinU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8MaxS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8MaxU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8AvgrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtMulLowI8x16S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtMulHighI8x16S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtMulLowI8x16U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8ExtMulHighI8x16U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtAddPairwiseI16x8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ExtAddPairwiseI16x8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4AllTrue $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4Bitmask $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ExtendLowI16x8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ExtendHighI16x8S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ExtendLowI16x8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4ExtendHighI16x8U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4MinS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4MinU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4MaxS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4MaxU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4DotI16x8S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtMulLowI16x8S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtMulHighI16x8S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtMulLowI16x8U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4ExtMulHighI16x8U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2AllTrue $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2Bitmask $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2ExtendLowI32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2ExtendHighI32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2ExtendLowI32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2ExtendHighI32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I64x2Shl $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ShrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ShrU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ExtMulLowI32x4S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ExtMulHighI32x4S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ExtMulLowI32x4U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I64x2ExtMulHighI32x4U $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Ceil $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Floor $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Trunc $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Nearest $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Sqrt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Div $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Min $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4Max $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4PMin $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4PMax $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Ceil $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Floor $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Trunc $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Nearest $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Abs $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Neg $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Sqrt $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2Add $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Sub $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Mul $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Div $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Min $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2Max $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2PMin $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2PMax $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4TruncSatF32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4TruncSatF32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4ConvertI32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4ConvertI32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4TruncSatF64x2SZero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4TruncSatF64x2UZero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2ConvertLowI32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2ConvertLowI32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4DemoteF64x2Zero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F64x2PromoteLowF32x4 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident Try $blockty:ident) => (block_arity($s.state, $blockty));
+    // The catch clauses of a `try_table` only name outer labels, so its
+    // arity is that of its block type.
+    (arity $s:ident TryTable $try_table:ident) => (block_arity($s.state, $try_table.ty));
+    (arity $s:ident Catch $tag_index:ident) => (None);
+    (arity $s:ident Throw $tag_index:ident) => ({
+        let (params, _) = tag_arity($s.state, $tag_index)?;
+        Some((params, 0))
+    });
+    (arity $s:ident Rethrow $($arg:ident)*) => (Some((0, 0)));
+    (arity $s:ident ThrowRef $($arg:ident)*) => (Some((1, 0)));
+    (arity $s:ident Delegate $relative_depth:ident) => (None);
+    (arity $s:ident CatchAll) => (None);
+    (arity $s:ident I8x16RelaxedSwizzle $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4RelaxedTruncF32x4S $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4RelaxedTruncF32x4U $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4RelaxedTruncF64x2SZero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I32x4RelaxedTruncF64x2UZero $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident F32x4RelaxedMadd $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident F32x4RelaxedNmadd $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident F64x2RelaxedMadd $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident F64x2RelaxedNmadd $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I8x16RelaxedLaneselect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I16x8RelaxedLaneselect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I32x4RelaxedLaneselect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident I64x2RelaxedLaneselect $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident F32x4RelaxedMin $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F32x4RelaxedMax $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2RelaxedMin $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident F64x2RelaxedMax $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8RelaxedQ15mulrS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I16x8RelaxedDotI8x16I7x16S $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident I32x4RelaxedDotI8x16I7x16AddS $($arg:ident)*) => (Some((3, 1)));
+    (arity $s:ident RefEq $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident StructNew $struct_type_index:ident) => (
+        match $s.state.core.types.get($struct_type_index as usize) {
+            Some(Some(SubType {
+                composite_type: CompositeType::Struct(ty),
+                ..
+            })) => Some((ty.fields.len() as u32, 1)),
+            _ => None,
+        }
+    );
+    (arity $s:ident StructNewDefault $($arg:ident)*) => (Some((0, 1)));
+    (arity $s:ident StructGet $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident StructGetS $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident StructGetU $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident StructSet $($arg:ident)*) => (Some((2, 0)));
+    (arity $s:ident ArrayNew $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArrayNewDefault $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident ArrayNewFixed $array_type_index:ident $array_size:ident) => (
+        Some(($array_size, 1))
+    );
+    (arity $s:ident ArrayNewData $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArrayNewElem $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArrayGet $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArrayGetS $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArrayGetU $($arg:ident)*) => (Some((2, 1)));
+    (arity $s:ident ArraySet $($arg:ident)*) => (Some((3, 0)));
+    (arity $s:ident ArrayLen $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident ArrayFill $($arg:ident)*) => (Some((4, 0)));
+    (arity $s:ident ArrayCopy $($arg:ident)*) => (Some((5, 0)));
+    (arity $s:ident ArrayInitData $($arg:ident)*) => (Some((4, 0)));
+    (arity $s:ident ArrayInitElem $($arg:ident)*) => (Some((4, 0)));
+    (arity $s:ident RefTestNonNull $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefTestNullable $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefCastNonNull $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefCastNullable $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident BrOnCast $relative_depth:ident $($rest:ident)*) => ({
+        let n = $s.label_arity($relative_depth)?;
+        Some((n, n))
+    });
+    (arity $s:ident BrOnCastFail $relative_depth:ident $($rest:ident)*) => ({
+        let n = $s.label_arity($relative_depth)?;
+        Some((n, n))
+    });
+    (arity $s:ident AnyConvertExtern $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident ExternConvertAny $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident RefI31 $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I31GetS $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident I31GetU $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident ContNew $($arg:ident)*) => (Some((1, 1)));
+    (arity $s:ident ContBind $argument_index:ident $result_index:ident) => ({
+        let (params, _) = cont_type_arity($s.state, $argument_index)?;
+        let (remaining, _) = cont_type_arity($s.state, $result_index)?;
+        Some((params.checked_sub(remaining)? + 1, 1))
+    });
+    (arity $s:ident Suspend $tag_index:ident) => (tag_arity($s.state, $tag_index));
+    (arity $s:ident Resume $cont_type_index:ident $resume_table:ident) => ({
+        let (params, results) = cont_type_arity($s.state, $cont_type_index)?;
+        Some((params + 1, results))
+    });
+    (arity $s:ident ResumeThrow $cont_type_index:ident $tag_index:ident $resume_table:ident) => ({
+        let (_, results) = cont_type_arity($s.state, $cont_type_index)?;
+        let (params, _) = tag_arity($s.state, $tag_index)?;
+        Some((params + 1, results))
+    });
+    (arity $s:ident Switch $cont_type_index:ident $tag_index:ident) => (None);
+}
+
+impl<'a> VisitOperator<'a> for Arity<'_> {
+    type Output = Option<(u32, u32)>;
+
+    wasmparser::for_each_operator!(define_visit);
+}
+
+fn func_type_index(state: &State, func_index: u32) -> Option<u32> {
+    *state.core.func_to_type.get(func_index as usize)?
+}
+
+fn func_type_arity(state: &State, type_index: u32) -> Option<(u32, u32)> {
+    match state.core.types.get(type_index as usize) {
+        Some(Some(SubType {
+            composite_type: CompositeType::Func(ty),
+            ..
+        })) => Some((ty.params().len() as u32, ty.results().len() as u32)),
+        _ => None,
+    }
+}
+
+fn tag_arity(state: &State, tag_index: u32) -> Option<(u32, u32)> {
+    func_type_arity(state, (*state.core.tag_to_type.get(tag_index as usize)?)?)
+}
+
+fn cont_type_arity(state: &State, type_index: u32) -> Option<(u32, u32)> {
+    match state.core.types.get(type_index as usize) {
+        Some(Some(SubType {
+            composite_type: CompositeType::Cont(ContType(ty)),
+            ..
+        })) => func_type_arity(state, ty.as_module_index()?),
+        _ => None,
+    }
+}
+
+fn block_arity(state: &State, ty: BlockType) -> Option<(u32, u32)> {
+    match ty {
+        BlockType::Empty => Some((0, 0)),
+        BlockType::Type(_) => Some((0, 1)),
+        BlockType::FuncType(idx) => func_type_arity(state, idx),
+    }
+}
+
+impl Printer {
+    /// Attempts to print the instructions of `body` in the folded form.
+    ///
+    /// Returns `false`, having printed nothing, if the body couldn't be
+    /// folded in which case it should be printed in the flat form instead.
+    pub(crate) fn print_folded_func_body(
+        &mut self,
+        state: &mut State,
+        func_idx: u32,
+        mut body: BinaryReader<'_>,
+    ) -> bool {
+        let labels = state.core.labels;
+        let nesting = self.nesting;
+        let nodes = self.fold_func_body(state, func_idx, &mut body);
+        self.nesting = nesting;
+        match nodes {
+            Some(nodes) => {
+                for node in nodes.iter() {
+                    self.print_node(node);
+                }
+                true
+            }
+            None => {
+                state.core.labels = labels;
+                false
+            }
+        }
+    }
+
+    fn fold_func_body(
+        &mut self,
+        state: &mut State,
+        func_idx: u32,
+        body: &mut BinaryReader<'_>,
+    ) -> Option<Vec<Node>> {
+        let results = match state.core.func_to_type.get(func_idx as usize) {
+            Some(Some(ty)) => func_type_arity(state, *ty).map(|(_, results)| results),
+            _ => None,
+        };
+        let mut folder = Folder::new(results);
+        let mut buf = String::new();
+        let mut op_printer = PrintOperator::new(self, state);
+        while !body.eof() {
+            let offset = body.original_position();
+            let op = body.clone().read_operator().ok()?;
+            let arity = body
+                .clone()
+                .visit_operator(&mut Arity {
+                    folder: &folder,
+                    state: op_printer.state,
+                })
+                .ok()?;
+
+            // Print the instruction in its flat form, tracking the nesting of
+            // blocks the same way as the flat printer so labels are printed
+            // the same way.
+            mem::swap(&mut buf, &mut op_printer.printer.result);
+            let kind = body.visit_operator(&mut op_printer);
+            mem::swap(&mut buf, &mut op_printer.printer.result);
+            let kind = kind.ok()?.ok()?;
+            let text = mem::take(&mut buf);
+            let nesting = &mut op_printer.printer.nesting;
+            match kind {
+                OpKind::BlockStart => *nesting += 1,
+                OpKind::End | OpKind::Delegate => *nesting = nesting.checked_sub(1)?,
+                OpKind::BlockMid | OpKind::Normal => {}
+            }
+
+            if let Some(nodes) = folder.push(&op, kind, arity, offset, text)? {
+                return if body.eof() { Some(nodes) } else { None };
+            }
+        }
+        None
+    }
+
+    fn print_node(&mut self, node: &Node) {
+        self.newline(node.offset);
+        self.result.push('(');
+        self.result.push_str(&node.text);
+        self.nesting += 1;
+        for operand in node.operands.iter() {
+            self.print_node(operand);
+        }
+        for arm in node.arms.iter().flatten() {
+            match &arm.header {
+                Some(header) => {
+                    self.print_newline(arm.offset);
+                    self.result.push('(');
+                    self.result.push_str(header);
+                    self.nesting += 1;
+                    for node in arm.body.iter() {
+                        self.print_node(node);
+                    }
+                    self.nesting -= 1;
+                    self.result.push(')');
+                }
+                None => {
+                    for node in arm.body.iter() {
+                        self.print_node(node);
+                    }
+                    // The block's header may end in a `;;` comment, so make
+                    // sure the closing paren ends up on a line of its own.
+                    if arm.body.is_empty() {
+                        self.nesting -= 1;
+                        self.newline_unknown_pos();
+                        self.nesting += 1;
+                    }
+                }
+            }
+        }
+        self.nesting -= 1;
+        self.result.push(')');
+    }
+}
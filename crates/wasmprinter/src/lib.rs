@@ -19,6 +19,7 @@ const MAX_LOCALS: u32 = 50000;
 const MAX_NESTING_TO_PRINT: u32 = 50;
 const MAX_WASM_FUNCTIONS: u32 = 1_000_000;
 
+mod fold;
 mod operator;
 
 /// Reads a WebAssembly `file` from the filesystem and then prints it into an
@@ -43,6 +44,8 @@ pub fn print_bytes(wasm: impl AsRef<[u8]>) -> Result<String> {
 pub struct Printer {
     print_offsets: bool,
     print_skeleton: bool,
+    fold_instructions: bool,
+    print_stack_types: bool,
     printers: HashMap<String, Box<dyn FnMut(&mut Printer, usize, &[u8]) -> Result<()>>>,
     result: String,
     /// The `i`th line in `result` is at offset `lines[i]`.
@@ -58,6 +61,8 @@ pub struct Printer {
 struct CoreState {
     types: Vec<Option<SubType>>,
     funcs: u32,
+    func_to_type: Vec<Option<u32>>,
+    tag_to_type: Vec<Option<u32>>,
     memories: u32,
     tags: u32,
     globals: u32,
@@ -76,6 +81,9 @@ struct CoreState {
     data_names: HashMap<u32, Naming>,
     module_names: HashMap<u32, Naming>,
     instance_names: HashMap<u32, Naming>,
+    branch_hints: HashMap<u32, Vec<BranchHint>>,
+    invalid_name_section: bool,
+    func_validators: Vec<FuncToValidate<ValidatorResources>>,
 }
 
 #[derive(Default)]
@@ -97,6 +105,7 @@ struct State {
     name: Option<Naming>,
     core: CoreState,
     component: ComponentState,
+    custom_section_place: Option<&'static str>,
 }
 
 impl State {
@@ -106,6 +115,13 @@ impl State {
             name: None,
             core: CoreState::default(),
             component: ComponentState::default(),
+            // Custom sections in components are encoded in the order they're
+            // defined in the text format, but in modules their placement
+            // relative to other sections needs to be printed explicitly.
+            custom_section_place: match encoding {
+                Encoding::Module => Some("before first"),
+                Encoding::Component => None,
+            },
         }
     }
 }
@@ -134,6 +150,28 @@ impl Printer {
         self.print_skeleton = print;
     }
 
+    /// Whether or not to print function bodies in the folded S-expression
+    /// form, for example `(i32.add (local.get 0) (i32.const 1))`.
+    ///
+    /// Instructions whose operands can't be determined are left unfolded and
+    /// function bodies which can't be folded at all are printed in the flat
+    /// form.
+    pub fn fold_instructions(&mut self, fold: bool) {
+        self.fold_instructions = fold;
+    }
+
+    /// Whether or not to print the types on the operand stack after each
+    /// instruction as a comment, for example `;; [i32 f64]`.
+    ///
+    /// This validates modules alongside printing them. Instructions which
+    /// start a block additionally print the block's result types and
+    /// instructions in unreachable code are marked as such. Functions are
+    /// annotated only up to the first validation error, if any, and function
+    /// bodies are always printed in the flat form when this is enabled.
+    pub fn print_stack_types(&mut self, print: bool) {
+        self.print_stack_types = print;
+    }
+
     /// Registers a custom `printer` function to get invoked whenever a custom
     /// section of name `section` is seen.
     ///
@@ -141,7 +179,8 @@ impl Printer {
     /// sections, such as by emitting annotations and/or other textual
     /// references (maybe comments!)
     ///
-    /// By default all custom sections are ignored for the text format.
+    /// By default custom sections without a dedicated printer are printed as
+    /// `@custom` annotations.
     ///
     /// The `printer` function provided takes three arguments:
     ///
@@ -201,6 +240,17 @@ impl Printer {
         state: &mut State,
         code: &mut Vec<FunctionBody<'a>>,
     ) -> Result<()> {
+        // Printing stack types requires validating each function, so validate
+        // modules here while we're at it. The module's `Version` payload has
+        // already been parsed so it's fed into the validator manually.
+        let mut validator = None;
+        if self.print_stack_types && state.encoding == Encoding::Module {
+            let mut v = Validator::new_with_features(WasmFeatures::all());
+            if v.version(1, Encoding::Module, &(0..0)).is_ok() {
+                validator = Some(v);
+            }
+        }
+
         loop {
             let payload = match parser.parse(bytes, true)? {
                 Chunk::NeedMoreData(_) => unreachable!(),
@@ -210,6 +260,16 @@ impl Printer {
                 }
             };
 
+            if let Some(v) = &mut validator {
+                match v.payload(&payload) {
+                    Ok(ValidPayload::Func(func, _)) => state.core.func_validators.push(func),
+                    Ok(_) => {}
+                    // Stack types aren't printed for the rest of an invalid
+                    // module.
+                    Err(_) => validator = None,
+                }
+            }
+
             match payload {
                 Payload::FunctionSection(s) => {
                     if s.count() > MAX_WASM_FUNCTIONS {
@@ -220,10 +280,32 @@ impl Printer {
                         );
                     }
                     code.reserve(s.count() as usize);
+                    if self.fold_instructions {
+                        for ty in s.into_iter().flatten() {
+                            state.core.func_to_type.push(Some(ty));
+                        }
+                    }
                 }
                 Payload::CodeSectionEntry(f) => {
                     code.push(f);
                 }
+
+                // Folding instructions needs to know the types of functions
+                // and tags before they're printed, so record those here.
+                Payload::ImportSection(s) if self.fold_instructions => {
+                    for import in s.into_iter().flatten() {
+                        match import.ty {
+                            TypeRef::Func(ty) => state.core.func_to_type.push(Some(ty)),
+                            TypeRef::Tag(ty) => state.core.tag_to_type.push(Some(ty.func_type_idx)),
+                            _ => {}
+                        }
+                    }
+                }
+                Payload::TagSection(s) if self.fold_instructions => {
+                    for tag in s.into_iter().flatten() {
+                        state.core.tag_to_type.push(Some(tag.func_type_idx));
+                    }
+                }
                 Payload::ModuleSection { range, .. } | Payload::ComponentSection { range, .. } => {
                     let offset = range.end - range.start;
                     if offset > bytes.len() {
@@ -235,12 +317,18 @@ impl Printer {
                 // Ignore any error associated with the name sections.
                 Payload::CustomSection(c) if c.name() == "name" => {
                     let reader = NameSectionReader::new(c.data(), c.data_offset());
-                    drop(self.register_names(state, reader));
+                    state.core.invalid_name_section = self.register_names(state, reader).is_err();
                 }
                 Payload::CustomSection(c) if c.name() == "component-name" => {
                     let reader = ComponentNameSectionReader::new(c.data(), c.data_offset());
                     drop(self.register_component_names(state, reader));
                 }
+                Payload::CustomSection(c) if c.name() == "metadata.code.branch_hint" => {
+                    drop(
+                        BranchHintSectionReader::new(c.data(), c.data_offset())
+                            .map(|reader| self.register_branch_hints(state, reader)),
+                    );
+                }
 
                 Payload::End(_) => break,
                 _ => {}
@@ -250,6 +338,23 @@ impl Printer {
         Ok(())
     }
 
+    fn register_branch_hints(
+        &mut self,
+        state: &mut State,
+        reader: BranchHintSectionReader<'_>,
+    ) -> Result<()> {
+        // Only register the hints once the whole section has been
+        // successfully read, otherwise ignore them entirely.
+        let mut branch_hints = HashMap::new();
+        for func in reader {
+            let func = func?;
+            let hints = func.hints.into_iter().collect::<Result<Vec<_>, _>>()?;
+            branch_hints.insert(func.func, hints);
+        }
+        state.core.branch_hints = branch_hints;
+        Ok(())
+    }
+
     fn ensure_module(states: &[State]) -> Result<()> {
         if !matches!(states.last().unwrap().encoding, Encoding::Module) {
             bail!("a module section was encountered when parsing a component");
@@ -287,6 +392,40 @@ impl Printer {
                     payload
                 }
             };
+            // Track where custom sections within modules are placed relative
+            // to the known sections so this can be printed with `@custom`.
+            let custom_section_place = match &payload {
+                Payload::TypeSection(_) => Some("after type"),
+                Payload::ImportSection(_) => Some("after import"),
+                Payload::FunctionSection(_) => Some("after func"),
+                Payload::TableSection(_) => Some("after table"),
+                Payload::MemorySection(_) => Some("after memory"),
+                Payload::TagSection(_) => Some("after tag"),
+                Payload::GlobalSection(_) => Some("after global"),
+                Payload::ExportSection(_) => Some("after export"),
+                Payload::StartSection { .. } => Some("after start"),
+                Payload::ElementSection(_) => Some("after elem"),
+                Payload::DataCountSection { .. } => Some("before code"),
+                Payload::CodeSectionStart { .. } => Some("after code"),
+                Payload::DataSection(_) => Some("after data"),
+                // A valid name section is printed through the names of items,
+                // and is encoded after all other sections.
+                Payload::CustomSection(c)
+                    if c.name() == "name"
+                        && states.last().is_some_and(|s| !s.core.invalid_name_section) =>
+                {
+                    Some("after last")
+                }
+                _ => None,
+            };
+            if let Some(place) = custom_section_place {
+                if let Some(state) = states.last_mut() {
+                    if state.encoding == Encoding::Module {
+                        state.custom_section_place = Some(place);
+                    }
+                }
+            }
+
             match payload {
                 Payload::Version { encoding, .. } => {
                     if let Some(e) = expected {
@@ -364,10 +503,14 @@ impl Printer {
                         continue;
                     }
                     let cur = self.result.len();
-                    let err = match self.print_custom_section(c.clone()) {
-                        Ok(()) => continue,
-                        Err(e) => e,
-                    };
+                    let state = states.last().unwrap();
+                    let place = state.custom_section_place;
+                    let invalid_name_section = state.core.invalid_name_section;
+                    let err =
+                        match self.print_custom_section(c.clone(), place, invalid_name_section) {
+                            Ok(()) => continue,
+                            Err(e) => e,
+                        };
                     if !err.is::<BinaryReaderError>() {
                         return Err(err);
                     }
@@ -646,6 +789,9 @@ impl Printer {
                     CompositeType::Array(_) | CompositeType::Struct(_) => {
                         unreachable!("Wasm GC types cannot appear in components yet")
                     }
+                    CompositeType::Cont(_) => {
+                        unreachable!("continuation types cannot appear in components yet")
+                    }
                 };
                 self.result.push(' ');
                 self.start_group("func");
@@ -731,6 +877,12 @@ impl Printer {
                 self.end_group(); // `struct`
                 r
             }
+            CompositeType::Cont(ty) => {
+                self.start_group("cont ");
+                self.print_idx(&state.core.type_names, ty.0.as_module_index().unwrap())?;
+                self.end_group(); // `cont`
+                0
+            }
         };
         Ok(r)
     }
@@ -887,6 +1039,9 @@ impl Printer {
                 RefType::EQ => self.result.push_str("eqref"),
                 RefType::STRUCT => self.result.push_str("structref"),
                 RefType::ARRAY => self.result.push_str("arrayref"),
+                RefType::EXN => self.result.push_str("exnref"),
+                RefType::CONT => self.result.push_str("contref"),
+                RefType::NOCONT => self.result.push_str("nullcontref"),
                 _ => {
                     self.result.push_str("(ref null ");
                     self.print_heaptype(ty.heap_type())?;
@@ -913,6 +1068,9 @@ impl Printer {
             HeapType::Struct => self.result.push_str("struct"),
             HeapType::Array => self.result.push_str("array"),
             HeapType::I31 => self.result.push_str("i31"),
+            HeapType::Exn => self.result.push_str("exn"),
+            HeapType::Cont => self.result.push_str("cont"),
+            HeapType::NoCont => self.result.push_str("nocont"),
             HeapType::Concrete(i) => self
                 .result
                 .push_str(&format!("{}", i.as_module_index().unwrap())),
@@ -1092,7 +1250,11 @@ impl Printer {
         if funcs.count() != code.len() as u32 {
             bail!("mismatch in function and code section counts");
         }
+        let mut func_validators = mem::take(&mut state.core.func_validators).into_iter();
         for (body, ty) in code.iter().zip(funcs) {
+            let validator = func_validators
+                .next()
+                .map(|func| func.into_validator(Default::default()));
             let mut body = body.get_binary_reader();
             let offset = body.original_position();
             let ty = ty?;
@@ -1108,7 +1270,7 @@ impl Printer {
             if self.print_skeleton {
                 self.result.push_str(" ...");
             } else {
-                self.print_func_body(state, func_idx, params, &mut body)?;
+                self.print_func_body(state, func_idx, params, &mut body, validator)?;
             }
 
             self.end_group();
@@ -1124,7 +1286,9 @@ impl Printer {
         func_idx: u32,
         params: u32,
         body: &mut BinaryReader<'_>,
+        mut validator: Option<FuncValidator<ValidatorResources>>,
     ) -> Result<()> {
+        let func_start = body.original_position();
         let mut first = true;
         let mut local_idx = 0;
         let mut locals = NamedLocalPrinter::new("local");
@@ -1132,6 +1296,11 @@ impl Printer {
             let offset = body.original_position();
             let cnt = body.read_var_u32()?;
             let ty = body.read()?;
+            if let Some(v) = &mut validator {
+                if v.define_locals(offset, cnt, ty).is_err() {
+                    validator = None;
+                }
+            }
             if MAX_LOCALS
                 .checked_sub(local_idx)
                 .and_then(|s| s.checked_sub(cnt))
@@ -1157,11 +1326,36 @@ impl Printer {
         let nesting_start = self.nesting;
         body.allow_memarg64(true);
 
+        // Branch hints are attached to specific instructions and aren't
+        // printed in the folded form, so functions with hints are always
+        // printed flat.
+        let branch_hints = state.core.branch_hints.remove(&func_idx);
+        if self.fold_instructions
+            && branch_hints.is_none()
+            && validator.is_none()
+            && self.print_folded_func_body(state, func_idx, body.clone())
+        {
+            return Ok(());
+        }
+
+        let mut branch_hints = branch_hints.unwrap_or_default().into_iter().peekable();
         let mut buf = String::new();
         let mut op_printer = operator::PrintOperator::new(self, state);
         while !body.eof() {
             // TODO
             let offset = body.original_position();
+            let func_offset = offset - func_start;
+            while let Some(hint) = branch_hints.next_if(|h| h.func_offset as usize <= func_offset) {
+                if hint.func_offset as usize == func_offset {
+                    op_printer.printer.newline(offset);
+                    let value = if hint.taken { "\\01" } else { "\\00" };
+                    write!(
+                        op_printer.printer.result,
+                        "(@metadata.code.branch_hint \"{value}\")"
+                    )?;
+                }
+            }
+            let mut op_reader = body.clone();
             mem::swap(&mut buf, &mut op_printer.printer.result);
             let op_kind = body.visit_operator(&mut op_printer)??;
             mem::swap(&mut buf, &mut op_printer.printer.result);
@@ -1203,6 +1397,19 @@ impl Printer {
             }
             op_printer.printer.result.push_str(&buf);
             buf.truncate(0);
+
+            if let Some(v) = &mut validator {
+                let result = op_reader.visit_operator(&mut v.visitor(offset));
+                if let Ok(Ok(())) = result {
+                    let block_start = matches!(
+                        op_kind,
+                        operator::OpKind::BlockStart | operator::OpKind::BlockMid
+                    );
+                    op_printer.printer.print_operand_stack(v, block_start)?;
+                } else {
+                    validator = None;
+                }
+            }
         }
 
         // If this was an invalid function body then the nesting may not
@@ -1218,6 +1425,57 @@ impl Printer {
         Ok(())
     }
 
+    /// Prints the types on the operand stack of `validator` as a comment,
+    /// along with the results of the innermost block if `block_start` is set
+    /// and whether the current code is unreachable.
+    fn print_operand_stack(
+        &mut self,
+        validator: &FuncValidator<ValidatorResources>,
+        block_start: bool,
+    ) -> Result<()> {
+        let frame = match validator.get_control_frame(0) {
+            Some(frame) => *frame,
+            None => return Ok(()),
+        };
+        self.result.push_str(" ;; [");
+        for depth in (0..validator.operand_stack_height() as usize).rev() {
+            match validator.get_operand_type(depth) {
+                Some(Some(ty)) => self.print_valtype(ty)?,
+                // Operands of unknown type only exist in unreachable code.
+                _ => self.result.push('?'),
+            }
+            if depth > 0 {
+                self.result.push(' ');
+            }
+        }
+        self.result.push(']');
+        if block_start {
+            let results = match frame.block_type {
+                BlockType::Empty => Vec::new(),
+                BlockType::Type(ty) => vec![ty],
+                BlockType::FuncType(idx) => match validator.resources().sub_type_at(idx) {
+                    Some(SubType {
+                        composite_type: CompositeType::Func(ty),
+                        ..
+                    }) => ty.results().to_vec(),
+                    _ => Vec::new(),
+                },
+            };
+            if !results.is_empty() {
+                self.result.push_str(" (result");
+                for ty in results {
+                    self.result.push(' ');
+                    self.print_valtype(ty)?;
+                }
+                self.result.push(')');
+            }
+        }
+        if frame.unreachable {
+            self.result.push_str(" unreachable");
+        }
+        Ok(())
+    }
+
     fn newline(&mut self, offset: usize) {
         self.print_newline(Some(offset))
     }
@@ -1605,6 +1863,44 @@ impl Printer {
         Ok(())
     }
 
+    fn print_stream_type(
+        &mut self,
+        state: &State,
+        element: Option<ComponentValType>,
+        end: Option<ComponentValType>,
+    ) -> Result<()> {
+        self.start_group("stream");
+
+        if let Some(element) = element {
+            self.result.push(' ');
+            self.print_component_val_type(state, &element)?;
+        }
+
+        if let Some(end) = end {
+            self.result.push(' ');
+            self.start_group("end ");
+            self.print_component_val_type(state, &end)?;
+            self.end_group();
+        }
+
+        self.end_group();
+
+        Ok(())
+    }
+
+    fn print_future_type(&mut self, state: &State, ty: Option<ComponentValType>) -> Result<()> {
+        self.start_group("future");
+
+        if let Some(ty) = ty {
+            self.result.push(' ');
+            self.print_component_val_type(state, &ty)?;
+        }
+
+        self.end_group();
+
+        Ok(())
+    }
+
     fn print_defined_type(&mut self, state: &State, ty: &ComponentDefinedType) -> Result<()> {
         match ty {
             ComponentDefinedType::Primitive(ty) => self.print_primitive_val_type(ty),
@@ -1626,6 +1922,10 @@ impl Printer {
                 self.print_idx(&state.component.type_names, *idx)?;
                 self.end_group();
             }
+            ComponentDefinedType::Stream { element, end } => {
+                self.print_stream_type(state, *element, *end)?
+            }
+            ComponentDefinedType::Future(ty) => self.print_future_type(state, *ty)?,
         }
 
         Ok(())
@@ -2553,8 +2853,18 @@ impl Printer {
         self.result.push(to_hex(byte & 0xf));
     }
 
-    fn print_custom_section(&mut self, section: CustomSectionReader<'_>) -> Result<()> {
+    fn print_custom_section(
+        &mut self,
+        section: CustomSectionReader<'_>,
+        place: Option<&str>,
+        invalid_name_section: bool,
+    ) -> Result<()> {
         match section.name() {
+            // These sections are printed as part of the items that they
+            // describe rather than as their own section. The exception is an
+            // invalid name section, which is printed as-is to preserve it.
+            "name" if !invalid_name_section => Ok(()),
+            "component-name" | "metadata.code.branch_hint" => Ok(()),
             "producers" => {
                 self.newline(section.range().start);
                 self.print_producers_section(ProducersSectionReader::new(
@@ -2569,10 +2879,29 @@ impl Printer {
                     section.data_offset(),
                 ))
             }
-            _ => Ok(()),
+            _ => {
+                self.newline(section.range().start);
+                self.print_raw_custom_section(section, place)
+            }
         }
     }
 
+    fn print_raw_custom_section(
+        &mut self,
+        section: CustomSectionReader<'_>,
+        place: Option<&str>,
+    ) -> Result<()> {
+        self.start_group("@custom ");
+        self.print_str(section.name())?;
+        if let Some(place) = place {
+            write!(self.result, " ({place})")?;
+        }
+        self.result.push(' ');
+        self.print_bytes(section.data())?;
+        self.end_group();
+        Ok(())
+    }
+
     fn print_producers_section(&mut self, section: ProducersSectionReader<'_>) -> Result<()> {
         self.start_group("@producers");
         for field in section {
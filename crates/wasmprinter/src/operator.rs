@@ -1,12 +1,12 @@
 use super::{Printer, State};
 use anyhow::{bail, Result};
 use std::fmt::Write;
-use wasmparser::{BlockType, BrTable, MemArg, VisitOperator};
+use wasmparser::{BlockType, BrTable, Catch, Handle, MemArg, ResumeTable, TryTable, VisitOperator};
 
 pub struct PrintOperator<'a, 'b> {
     pub(super) printer: &'a mut Printer,
     nesting_start: u32,
-    state: &'b mut State,
+    pub(super) state: &'b mut State,
 }
 
 impl<'a, 'b> PrintOperator<'a, 'b> {
@@ -27,6 +27,11 @@ impl<'a, 'b> PrintOperator<'a, 'b> {
     }
 
     fn blockty(&mut self, ty: BlockType) -> Result<()> {
+        self.blockty_without_label_comment(ty)?;
+        self.blockty_label_comment()
+    }
+
+    fn blockty_without_label_comment(&mut self, ty: BlockType) -> Result<()> {
         if let Some(name) = self
             .state
             .core
@@ -49,6 +54,10 @@ impl<'a, 'b> PrintOperator<'a, 'b> {
                 self.printer.result.push(' ');
             }
         }
+        Ok(())
+    }
+
+    fn blockty_label_comment(&mut self) -> Result<()> {
         // Note that 1 is added to the current depth here since if a block type
         // is being printed then a block is being created which will increase
         // the label depth of the block itself.
@@ -58,6 +67,40 @@ impl<'a, 'b> PrintOperator<'a, 'b> {
         Ok(())
     }
 
+    fn try_table(&mut self, table: TryTable) -> Result<()> {
+        self.blockty_without_label_comment(table.ty)?;
+
+        // Note that the labels of catch clauses are resolved relative to the
+        // block enclosing the `try_table`, so they're printed before the label
+        // comment which introduces the `try_table`'s own label.
+        for catch in table.catches {
+            match catch {
+                Catch::One { tag, label } => {
+                    self.push_str("(catch ");
+                    self.tag_index(tag)?;
+                    self.push_str(" ");
+                    self.relative_depth(label)?;
+                }
+                Catch::OneRef { tag, label } => {
+                    self.push_str("(catch_ref ");
+                    self.tag_index(tag)?;
+                    self.push_str(" ");
+                    self.relative_depth(label)?;
+                }
+                Catch::All { label } => {
+                    self.push_str("(catch_all ");
+                    self.relative_depth(label)?;
+                }
+                Catch::AllRef { label } => {
+                    self.push_str("(catch_all_ref ");
+                    self.relative_depth(label)?;
+                }
+            }
+            self.push_str(") ");
+        }
+        self.blockty_label_comment()
+    }
+
     fn cur_depth(&self) -> u32 {
         self.printer.nesting - self.nesting_start
     }
@@ -128,6 +171,71 @@ impl<'a, 'b> PrintOperator<'a, 'b> {
         self.printer.print_idx(&self.state.core.element_names, idx)
     }
 
+    fn struct_type_index(&mut self, idx: u32) -> Result<()> {
+        self.printer.print_idx(&self.state.core.type_names, idx)
+    }
+
+    fn array_type_index(&mut self, idx: u32) -> Result<()> {
+        self.printer.print_idx(&self.state.core.type_names, idx)
+    }
+
+    fn array_type_index_dst(&mut self, idx: u32) -> Result<()> {
+        self.printer.print_idx(&self.state.core.type_names, idx)
+    }
+
+    fn array_type_index_src(&mut self, idx: u32) -> Result<()> {
+        self.printer.print_idx(&self.state.core.type_names, idx)
+    }
+
+    fn cont_type_index(&mut self, idx: u32) -> Result<()> {
+        self.printer.print_idx(&self.state.core.type_names, idx)
+    }
+
+    fn argument_index(&mut self, idx: u32) -> Result<()> {
+        self.cont_type_index(idx)
+    }
+
+    fn result_index(&mut self, idx: u32) -> Result<()> {
+        self.cont_type_index(idx)
+    }
+
+    fn resume_table(&mut self, table: ResumeTable) -> Result<()> {
+        for handle in table.handlers {
+            self.push_str(" (on ");
+            match handle {
+                Handle::OnLabel { tag, label } => {
+                    self.tag_index(tag)?;
+                    self.push_str(" ");
+                    self.relative_depth(label)?;
+                }
+                Handle::OnSwitch { tag } => {
+                    self.tag_index(tag)?;
+                    self.push_str(" switch");
+                }
+            }
+            self.push_str(")");
+        }
+        Ok(())
+    }
+
+    fn field_index(&mut self, idx: u32) -> Result<()> {
+        write!(self.result(), "{idx}")?;
+        Ok(())
+    }
+
+    fn array_size(&mut self, size: u32) -> Result<()> {
+        write!(self.result(), "{size}")?;
+        Ok(())
+    }
+
+    fn array_data_index(&mut self, idx: u32) -> Result<()> {
+        self.data_index(idx)
+    }
+
+    fn array_elem_index(&mut self, idx: u32) -> Result<()> {
+        self.elem_index(idx)
+    }
+
     fn lane(&mut self, lane: u8) -> Result<()> {
         write!(self.result(), "{lane}")?;
         Ok(())
@@ -199,6 +307,7 @@ macro_rules! define_visit {
     (kind Loop) => (OpKind::BlockStart);
     (kind If) => (OpKind::BlockStart);
     (kind Try) => (OpKind::BlockStart);
+    (kind TryTable) => (OpKind::BlockStart);
     (kind Else) => (OpKind::BlockMid);
     (kind Catch) => (OpKind::BlockMid);
     (kind CatchAll) => (OpKind::BlockMid);
@@ -243,6 +352,42 @@ macro_rules! define_visit {
         $self.push_str(" ");
         $self.printer.print_heaptype($hty)?;
     );
+    (payload $self:ident RefTestNonNull $hty:ident) => (
+        $self.push_str(" (ref ");
+        $self.printer.print_heaptype($hty)?;
+        $self.push_str(")");
+    );
+    (payload $self:ident RefTestNullable $hty:ident) => (
+        $self.push_str(" (ref null ");
+        $self.printer.print_heaptype($hty)?;
+        $self.push_str(")");
+    );
+    (payload $self:ident RefCastNonNull $hty:ident) => (
+        $self.push_str(" (ref ");
+        $self.printer.print_heaptype($hty)?;
+        $self.push_str(")");
+    );
+    (payload $self:ident RefCastNullable $hty:ident) => (
+        $self.push_str(" (ref null ");
+        $self.printer.print_heaptype($hty)?;
+        $self.push_str(")");
+    );
+    (payload $self:ident BrOnCast $depth:ident $from:ident $to:ident) => (
+        $self.push_str(" ");
+        $self.relative_depth($depth)?;
+        $self.push_str(" ");
+        $self.printer.print_reftype($from)?;
+        $self.push_str(" ");
+        $self.printer.print_reftype($to)?;
+    );
+    (payload $self:ident BrOnCastFail $depth:ident $from:ident $to:ident) => (
+        $self.push_str(" ");
+        $self.relative_depth($depth)?;
+        $self.push_str(" ");
+        $self.printer.print_reftype($from)?;
+        $self.push_str(" ");
+        $self.printer.print_reftype($to)?;
+    );
     (payload $self:ident TableInit $segment:ident $table:ident) => (
         $self.push_str(" ");
         if $table != 0 {
@@ -293,6 +438,18 @@ macro_rules! define_visit {
             $self.memory_index($mem)?;
         }
     );
+    (payload $self:ident Resume $ty:ident $table:ident) => (
+        $self.push_str(" ");
+        $self.cont_type_index($ty)?;
+        $self.resume_table($table)?;
+    );
+    (payload $self:ident ResumeThrow $ty:ident $tag:ident $table:ident) => (
+        $self.push_str(" ");
+        $self.cont_type_index($ty)?;
+        $self.push_str(" ");
+        $self.tag_index($tag)?;
+        $self.resume_table($table)?;
+    );
     (payload $self:ident I32Const $val:ident) => (write!($self.result(), " {}", $val)?);
     (payload $self:ident I64Const $val:ident) => (write!($self.result(), " {}", $val)?);
     (payload $self:ident F32Const $val:ident) => (
@@ -836,9 +993,11 @@ macro_rules! define_visit {
     (name F32x4DemoteF64x2Zero) => ("f32x4.demote_f64x2_zero");
     (name F64x2PromoteLowF32x4) => ("f64x2.promote_low_f32x4");
     (name Try) => ("try");
+    (name TryTable) => ("try_table");
     (name Catch) => ("catch");
     (name Throw) => ("throw");
     (name Rethrow) => ("rethrow");
+    (name ThrowRef) => ("throw_ref");
     (name Delegate) => ("delegate");
     (name CatchAll) => ("catch_all");
     (name I8x16RelaxedSwizzle) => ("i8x16.relaxed_swizzle");
@@ -861,9 +1020,44 @@ macro_rules! define_visit {
     (name I16x8RelaxedQ15mulrS) => ("i16x8.relaxed_q15mulr_s");
     (name I16x8RelaxedDotI8x16I7x16S) => ("i16x8.relaxed_dot_i8x16_i7x16_s");
     (name I32x4RelaxedDotI8x16I7x16AddS) => ("i32x4.relaxed_dot_i8x16_i7x16_add_s");
+    (name RefEq) => ("ref.eq");
+    (name StructNew) => ("struct.new");
+    (name StructNewDefault) => ("struct.new_default");
+    (name StructGet) => ("struct.get");
+    (name StructGetS) => ("struct.get_s");
+    (name StructGetU) => ("struct.get_u");
+    (name StructSet) => ("struct.set");
+    (name ArrayNew) => ("array.new");
+    (name ArrayNewDefault) => ("array.new_default");
+    (name ArrayNewFixed) => ("array.new_fixed");
+    (name ArrayNewData) => ("array.new_data");
+    (name ArrayNewElem) => ("array.new_elem");
+    (name ArrayGet) => ("array.get");
+    (name ArrayGetS) => ("array.get_s");
+    (name ArrayGetU) => ("array.get_u");
+    (name ArraySet) => ("array.set");
+    (name ArrayLen) => ("array.len");
+    (name ArrayFill) => ("array.fill");
+    (name ArrayCopy) => ("array.copy");
+    (name ArrayInitData) => ("array.init_data");
+    (name ArrayInitElem) => ("array.init_elem");
+    (name RefTestNonNull) => ("ref.test");
+    (name RefTestNullable) => ("ref.test");
+    (name RefCastNonNull) => ("ref.cast");
+    (name RefCastNullable) => ("ref.cast");
+    (name BrOnCast) => ("br_on_cast");
+    (name BrOnCastFail) => ("br_on_cast_fail");
+    (name AnyConvertExtern) => ("any.convert_extern");
+    (name ExternConvertAny) => ("extern.convert_any");
     (name RefI31) => ("ref.i31");
     (name I31GetS) => ("i31.get_s");
     (name I31GetU) => ("i31.get_u");
+    (name ContNew) => ("cont.new");
+    (name ContBind) => ("cont.bind");
+    (name Suspend) => ("suspend");
+    (name Resume) => ("resume");
+    (name ResumeThrow) => ("resume_throw");
+    (name Switch) => ("switch");
 }
 
 impl<'a> VisitOperator<'a> for PrintOperator<'_, '_> {
@@ -64,6 +64,9 @@ fn encode_core_type(encoder: CoreTypeEncoder, ty: &CoreTypeDef) {
         CoreTypeDef::Def(core::TypeDef::Struct(_)) | CoreTypeDef::Def(core::TypeDef::Array(_)) => {
             todo!("encoding of GC proposal types not yet implemented")
         }
+        CoreTypeDef::Def(core::TypeDef::Cont(_)) => {
+            unreachable!("continuation types are rejected during parsing")
+        }
         CoreTypeDef::Module(t) => {
             encoder.module(&t.into());
         }
@@ -136,6 +139,15 @@ fn encode_defined_type(encoder: ComponentDefinedTypeEncoder, ty: &ComponentDefin
         }
         ComponentDefinedType::Own(i) => encoder.own((*i).into()),
         ComponentDefinedType::Borrow(i) => encoder.borrow((*i).into()),
+        ComponentDefinedType::Stream(s) => {
+            encoder.stream(
+                s.element.as_deref().map(Into::into),
+                s.end.as_deref().map(Into::into),
+            );
+        }
+        ComponentDefinedType::Future(f) => {
+            encoder.future(f.element.as_deref().map(Into::into));
+        }
     }
 }
 
@@ -612,6 +624,8 @@ impl From<core::HeapType<'_>> for wasm_encoder::HeapType {
             | core::HeapType::I31 => {
                 todo!("encoding of GC proposal types not yet implemented")
             }
+            core::HeapType::Cont => Self::Cont,
+            core::HeapType::NoCont => Self::NoCont,
         }
     }
 }
@@ -882,6 +896,9 @@ impl From<&ModuleType<'_>> for wasm_encoder::ModuleType {
                     core::TypeDef::Struct(_) | core::TypeDef::Array(_) => {
                         todo!("encoding of GC proposal types not yet implemented")
                     }
+                    core::TypeDef::Cont(_) => {
+                        unreachable!("continuation types are rejected during parsing")
+                    }
                 },
                 ModuleTypeDecl::Alias(a) => match &a.target {
                     AliasTarget::Outer {
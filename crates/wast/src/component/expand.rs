@@ -435,6 +435,7 @@ impl<'a> Expander<'a> {
                     }
                     core::TypeDef::Struct(_) => {}
                     core::TypeDef::Array(_) => {}
+                    core::TypeDef::Cont(_) => {}
                 },
                 ModuleTypeDecl::Alias(_) => {}
                 ModuleTypeDecl::Import(ty) => {
@@ -567,6 +568,20 @@ impl<'a> Expander<'a> {
                     self.expand_component_val_ty(ty);
                 }
             }
+            ComponentDefinedType::Stream(s) => {
+                if let Some(ty) = &mut s.element {
+                    self.expand_component_val_ty(ty);
+                }
+
+                if let Some(ty) = &mut s.end {
+                    self.expand_component_val_ty(ty);
+                }
+            }
+            ComponentDefinedType::Future(f) => {
+                if let Some(ty) = &mut f.element {
+                    self.expand_component_val_ty(ty);
+                }
+            }
             ComponentDefinedType::Own(_) | ComponentDefinedType::Borrow(_) => {}
         }
     }
@@ -469,6 +469,20 @@ impl<'a> Resolver<'a> {
             ComponentDefinedType::Own(t) | ComponentDefinedType::Borrow(t) => {
                 self.resolve_ns(t, Ns::Type)?;
             }
+            ComponentDefinedType::Stream(s) => {
+                if let Some(ty) = &mut s.element {
+                    self.component_val_type(ty)?;
+                }
+
+                if let Some(ty) = &mut s.end {
+                    self.component_val_type(ty)?;
+                }
+            }
+            ComponentDefinedType::Future(f) => {
+                if let Some(ty) = &mut f.element {
+                    self.component_val_type(ty)?;
+                }
+            }
         }
         Ok(())
     }
@@ -531,7 +545,9 @@ impl<'a> Resolver<'a> {
                         | core::HeapType::Struct
                         | core::HeapType::None
                         | core::HeapType::NoFunc
-                        | core::HeapType::NoExtern => {}
+                        | core::HeapType::NoExtern
+                        | core::HeapType::Cont
+                        | core::HeapType::NoCont => {}
                         core::HeapType::Concrete(id) => {
                             self.resolve_ns(id, Ns::Type)?;
                         }
@@ -7,6 +7,7 @@ use crate::parser::{Parse, Parser, Result};
 use crate::token::Index;
 use crate::token::LParen;
 use crate::token::{Id, NameAnnotation, Span};
+use crate::Error;
 
 /// A core type declaration.
 #[derive(Debug)]
@@ -56,6 +57,8 @@ impl<'a> Parse<'a> for CoreTypeDef<'a> {
         if parser.peek::<kw::module>()? {
             parser.parse::<kw::module>()?;
             Ok(Self::Module(parser.parse()?))
+        } else if parser.peek::<kw::cont>()? {
+            Err(parser.error("continuation types are not supported in components"))
         } else {
             Ok(Self::Def(parser.parse()?))
         }
@@ -95,7 +98,14 @@ impl<'a> Parse<'a> for ModuleTypeDecl<'a> {
     fn parse(parser: Parser<'a>) -> Result<Self> {
         let mut l = parser.lookahead1();
         if l.peek::<kw::r#type>()? {
-            Ok(Self::Type(parser.parse()?))
+            let ty: core::Type = parser.parse()?;
+            if let core::TypeDef::Cont(_) = ty.def {
+                return Err(Error::new(
+                    ty.span,
+                    "continuation types are not supported in components".to_string(),
+                ));
+            }
+            Ok(Self::Type(ty))
         } else if l.peek::<kw::alias>()? {
             Ok(Self::Alias(Alias::parse_outer_core_type_alias(parser)?))
         } else if l.peek::<kw::import>()? {
@@ -376,6 +386,8 @@ pub enum ComponentDefinedType<'a> {
     Result(ResultType<'a>),
     Own(Index<'a>),
     Borrow(Index<'a>),
+    Stream(StreamType<'a>),
+    Future(FutureType<'a>),
 }
 
 impl<'a> ComponentDefinedType<'a> {
@@ -403,6 +415,10 @@ impl<'a> ComponentDefinedType<'a> {
         } else if l.peek::<kw::borrow>()? {
             parser.parse::<kw::borrow>()?;
             Ok(Self::Borrow(parser.parse()?))
+        } else if l.peek::<kw::stream>()? {
+            Ok(Self::Stream(parser.parse()?))
+        } else if l.peek::<kw::future>()? {
+            Ok(Self::Future(parser.parse()?))
         } else {
             Err(l.error())
         }
@@ -434,6 +450,8 @@ impl Peek for ComponentDefinedType<'_> {
                     | Some(("result", _))
                     | Some(("own", _))
                     | Some(("borrow", _))
+                    | Some(("stream", _))
+                    | Some(("future", _))
             ),
             None => false,
         })
@@ -672,6 +690,53 @@ impl<'a> Parse<'a> for ResultType<'a> {
     }
 }
 
+/// A stream type.
+#[derive(Debug)]
+pub struct StreamType<'a> {
+    /// The type of the elements of the stream.
+    pub element: Option<Box<ComponentValType<'a>>>,
+    /// The type of the value produced when the stream ends.
+    pub end: Option<Box<ComponentValType<'a>>>,
+}
+
+impl<'a> Parse<'a> for StreamType<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::stream>()?;
+
+        let element: Option<ComponentValType> = parser.parse()?;
+        let end: Option<ComponentValType> = if parser.peek::<LParen>()? {
+            Some(parser.parens(|parser| {
+                parser.parse::<kw::end>()?;
+                parser.parse()
+            })?)
+        } else {
+            None
+        };
+
+        Ok(Self {
+            element: element.map(Box::new),
+            end: end.map(Box::new),
+        })
+    }
+}
+
+/// A future type.
+#[derive(Debug)]
+pub struct FutureType<'a> {
+    /// The type of the value the future resolves to, if any.
+    pub element: Option<Box<ComponentValType<'a>>>,
+}
+
+impl<'a> Parse<'a> for FutureType<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::future>()?;
+        let element: Option<ComponentValType> = parser.parse()?;
+        Ok(Self {
+            element: element.map(Box::new),
+        })
+    }
+}
+
 /// A component function type with parameters and result.
 #[derive(Debug)]
 pub struct ComponentFunctionType<'a> {
@@ -69,11 +69,14 @@ pub fn encode(
     if needs_data_count(&funcs) {
         e.section(12, &data.len());
     }
-    e.section_list(10, Code, &funcs);
+    e.code_section(&imports, &funcs);
     e.section_list(11, Data, &data);
 
+    // An explicit `(@custom "name" ...)` section replaces the name section
+    // that would otherwise be generated from identifiers and `@name`
+    // annotations.
     let names = find_names(module_id, module_name, fields);
-    if !names.is_empty() {
+    if !names.is_empty() && !customs.iter().any(|c| c.name() == "name") {
         e.section(0, &("name", names));
     }
     e.custom_sections(AfterLast);
@@ -121,6 +124,62 @@ impl Encoder<'_> {
         }
         self.custom_sections(CustomPlace::After(anchor));
     }
+
+    /// Encodes the code section, preceded by a `metadata.code.branch_hint`
+    /// custom section if any function has branch hints.
+    ///
+    /// Branch hints refer to byte offsets within function bodies, so the code
+    /// section is encoded first to learn those offsets, but the hints section
+    /// is required to precede the code section in the final module.
+    fn code_section(&mut self, imports: &[&Import<'_>], funcs: &[&crate::core::Func<'_>]) {
+        self.custom_sections(CustomPlace::Before(CustomPlaceAnchor::Code));
+        if !funcs.is_empty() {
+            let num_import_funcs = imports
+                .iter()
+                .filter(|i| matches!(i.item.kind, ItemKind::Func(..)))
+                .count();
+            let mut code = Vec::new();
+            let mut branch_hints = Vec::new();
+            funcs.len().encode(&mut code);
+            for (i, func) in funcs.iter().enumerate() {
+                let hints = func.encode_with_branch_hints(&mut code);
+                if !hints.is_empty() {
+                    branch_hints.push(FunctionBranchHints {
+                        func_index: (num_import_funcs + i) as u32,
+                        hints,
+                    });
+                }
+            }
+            if !branch_hints.is_empty() {
+                self.section(0, &("metadata.code.branch_hint", branch_hints));
+            }
+            self.wasm.push(10);
+            code.encode(&mut self.wasm);
+        }
+        self.custom_sections(CustomPlace::After(CustomPlaceAnchor::Code));
+    }
+}
+
+/// The branch hints of one function in the `metadata.code.branch_hint` custom
+/// section.
+struct FunctionBranchHints {
+    func_index: u32,
+    /// Pairs of the offset of the hinted instruction, relative to the start of
+    /// the function body, and the hint's value.
+    hints: Vec<(u32, u32)>,
+}
+
+impl Encode for FunctionBranchHints {
+    fn encode(&self, e: &mut Vec<u8>) {
+        self.func_index.encode(e);
+        self.hints.len().encode(e);
+        for (offset, value) in self.hints.iter() {
+            offset.encode(e);
+            // The size of the hint's value, which is always one byte.
+            e.push(1);
+            value.encode(e);
+        }
+    }
 }
 
 impl Encode for FunctionType<'_> {
@@ -206,6 +265,10 @@ impl Encode for Type<'_> {
                 e.push(0x5e);
                 array.encode(e)
             }
+            TypeDef::Cont(cont) => {
+                e.push(0x5d);
+                cont.0.encode(e)
+            }
         }
     }
 }
@@ -255,6 +318,8 @@ impl<'a> Encode for HeapType<'a> {
             HeapType::NoFunc => e.push(0x73),
             HeapType::NoExtern => e.push(0x72),
             HeapType::None => e.push(0x71),
+            HeapType::Cont => e.push(0x68),
+            HeapType::NoCont => e.push(0x75),
             // Note that this is encoded as a signed leb128 so be sure to cast
             // to an i64 first
             HeapType::Concrete(Index::Num(n, _)) => i64::from(*n).encode(e),
@@ -313,6 +378,16 @@ impl<'a> Encode for RefType<'a> {
                 nullable: true,
                 heap: HeapType::None,
             } => e.push(0x71),
+            // The 'contref' binary abbreviation
+            RefType {
+                nullable: true,
+                heap: HeapType::Cont,
+            } => e.push(0x68),
+            // The 'nullcontref' binary abbreviation
+            RefType {
+                nullable: true,
+                heap: HeapType::NoCont,
+            } => e.push(0x75),
 
             // Generic 'ref null <heaptype>' encoding
             RefType {
@@ -625,8 +700,10 @@ impl Encode for Data<'_> {
     }
 }
 
-impl Encode for Func<'_> {
-    fn encode(&self, e: &mut Vec<u8>) {
+impl Func<'_> {
+    /// Encodes this function's body, returning the offsets, relative to the
+    /// start of the body, and values of any of its branch hints.
+    fn encode_with_branch_hints(&self, e: &mut Vec<u8>) -> Vec<(u32, u32)> {
         assert!(self.exports.names.is_empty());
         let mut tmp = Vec::new();
         let (expr, locals) = match &self.kind {
@@ -635,10 +712,17 @@ impl Encode for Func<'_> {
         };
 
         locals.encode(&mut tmp);
-        expr.encode(&mut tmp);
+        let hints = expr.encode_with_branch_hints(&mut tmp);
 
         tmp.len().encode(e);
         e.extend_from_slice(&tmp);
+        hints
+    }
+}
+
+impl Encode for Func<'_> {
+    fn encode(&self, e: &mut Vec<u8>) {
+        self.encode_with_branch_hints(e);
     }
 }
 
@@ -658,12 +742,26 @@ impl Encode for Box<[Local<'_>]> {
     }
 }
 
-impl Encode for Expression<'_> {
-    fn encode(&self, e: &mut Vec<u8>) {
-        for instr in self.instrs.iter() {
+impl Expression<'_> {
+    /// Encodes this expression, returning the byte offset within `e` and the
+    /// value of each of its branch hints.
+    fn encode_with_branch_hints(&self, e: &mut Vec<u8>) -> Vec<(u32, u32)> {
+        let mut hints = Vec::with_capacity(self.branch_hints.len());
+        let mut next_hint = self.branch_hints.iter().peekable();
+        for (i, instr) in self.instrs.iter().enumerate() {
+            if let Some(hint) = next_hint.next_if(|h| h.instr_index == i) {
+                hints.push((e.len() as u32, hint.value));
+            }
             instr.encode(e);
         }
         e.push(0x0b);
+        hints
+    }
+}
+
+impl Encode for Expression<'_> {
+    fn encode(&self, e: &mut Vec<u8>) {
+        self.encode_with_branch_hints(e);
     }
 }
 
@@ -939,6 +1037,12 @@ fn find_names<'a>(
                             }
                             label_idx += 1;
                         }
+                        Instruction::TryTable(TryTable { block, .. }) => {
+                            if let Some(name) = get_name(&block.label, &block.label_name) {
+                                label_names.push((label_idx, name));
+                            }
+                            label_idx += 1;
+                        }
                         _ => {}
                     }
                 }
@@ -1038,40 +1142,76 @@ impl<'a> Encode for TryTable<'a> {
     fn encode(&self, dst: &mut Vec<u8>) {
         self.block.encode(dst);
         self.catches.encode(dst);
-
-        let catch_all_flag_byte: u8 = match self.catch_all {
-            None => 0,
-            Some(TryTableCatchAll {
-                kind: TryTableCatchKind::Drop,
-                ..
-            }) => 1,
-            Some(TryTableCatchAll {
-                kind: TryTableCatchKind::Ref,
-                ..
-            }) => 2,
-        };
-        catch_all_flag_byte.encode(dst);
-        if let Some(catch_all) = &self.catch_all {
-            catch_all.encode(dst);
-        }
     }
 }
 
 impl<'a> Encode for TryTableCatch<'a> {
     fn encode(&self, dst: &mut Vec<u8>) {
-        let catch_flag_byte: u8 = match self.kind {
-            TryTableCatchKind::Drop => 0,
-            TryTableCatchKind::Ref => 1,
+        let flag_byte: u8 = match self.kind {
+            TryTableCatchKind::Catch(..) => 0,
+            TryTableCatchKind::CatchRef(..) => 1,
+            TryTableCatchKind::CatchAll => 2,
+            TryTableCatchKind::CatchAllRef => 3,
         };
-        catch_flag_byte.encode(dst);
-        self.tag.encode(dst);
+        flag_byte.encode(dst);
+        match self.kind {
+            TryTableCatchKind::Catch(tag) | TryTableCatchKind::CatchRef(tag) => {
+                tag.encode(dst);
+            }
+            TryTableCatchKind::CatchAll | TryTableCatchKind::CatchAllRef => {}
+        }
         self.label.encode(dst);
     }
 }
 
-impl<'a> Encode for TryTableCatchAll<'a> {
+impl Encode for ContBind<'_> {
     fn encode(&self, dst: &mut Vec<u8>) {
-        self.label.encode(dst);
+        self.argument_index.encode(dst);
+        self.result_index.encode(dst);
+    }
+}
+
+impl Encode for Resume<'_> {
+    fn encode(&self, dst: &mut Vec<u8>) {
+        self.type_index.encode(dst);
+        self.table.encode(dst);
+    }
+}
+
+impl Encode for ResumeThrow<'_> {
+    fn encode(&self, dst: &mut Vec<u8>) {
+        self.type_index.encode(dst);
+        self.tag_index.encode(dst);
+        self.table.encode(dst);
+    }
+}
+
+impl Encode for ResumeTable<'_> {
+    fn encode(&self, dst: &mut Vec<u8>) {
+        self.handlers.encode(dst);
+    }
+}
+
+impl Encode for Handle<'_> {
+    fn encode(&self, dst: &mut Vec<u8>) {
+        match self {
+            Handle::OnLabel { tag, label } => {
+                dst.push(0x00);
+                tag.encode(dst);
+                label.encode(dst);
+            }
+            Handle::OnSwitch { tag } => {
+                dst.push(0x01);
+                tag.encode(dst);
+            }
+        }
+    }
+}
+
+impl Encode for Switch<'_> {
+    fn encode(&self, dst: &mut Vec<u8>) {
+        self.type_index.encode(dst);
+        self.tag_index.encode(dst);
     }
 }
 
@@ -1,3 +1,4 @@
+use crate::annotation;
 use crate::core::*;
 use crate::encode::Encode;
 use crate::kw;
@@ -14,6 +15,19 @@ use std::mem;
 #[allow(missing_docs)]
 pub struct Expression<'a> {
     pub instrs: Box<[Instruction<'a>]>,
+    pub branch_hints: Box<[BranchHint]>,
+}
+
+/// A `@metadata.code.branch_hint` annotation within an expression, attached
+/// to the `if` or `br_if` instruction that follows it.
+#[derive(Debug)]
+pub struct BranchHint {
+    /// The index, within the `instrs` of the enclosing [`Expression`], of the
+    /// instruction that this hint applies to.
+    pub instr_index: usize,
+    /// The value of the hint: `0` for likely not taken and `1` for likely
+    /// taken.
+    pub value: u32,
 }
 
 impl<'a> Parse<'a> for Expression<'a> {
@@ -22,6 +36,7 @@ impl<'a> Parse<'a> for Expression<'a> {
         exprs.parse(parser)?;
         Ok(Expression {
             instrs: exprs.instrs.into(),
+            branch_hints: exprs.branch_hints.into(),
         })
     }
 }
@@ -47,6 +62,7 @@ impl<'a> Expression<'a> {
         exprs.parse_folded_instruction(parser)?;
         Ok(Expression {
             instrs: exprs.instrs.into(),
+            branch_hints: exprs.branch_hints.into(),
         })
     }
 }
@@ -66,6 +82,14 @@ struct ExpressionParser<'a> {
     /// Descriptor of all our nested s-expr blocks. This only happens when
     /// instructions themselves are nested.
     stack: Vec<Level<'a>>,
+
+    /// The branch hints that we've parsed so far, referring to indices within
+    /// `instrs`.
+    branch_hints: Vec<BranchHint>,
+
+    /// A branch hint which has been parsed but whose instruction hasn't been
+    /// parsed yet.
+    pending_branch_hint: Option<u32>,
 }
 
 enum Paren {
@@ -77,8 +101,9 @@ enum Paren {
 /// A "kind" of nested block that we can be parsing inside of.
 enum Level<'a> {
     /// This is a normal `block` or `loop` or similar, where the instruction
-    /// payload here is pushed when the block is exited.
-    EndWith(Instruction<'a>),
+    /// payload here is pushed when the block is exited, along with the value
+    /// of its branch hint, if any.
+    EndWith(Instruction<'a>, Option<u32>),
 
     /// This is a pretty special variant which means that we're parsing an `if`
     /// statement, and the state of the `if` parsing is tracked internally in
@@ -96,15 +121,20 @@ enum Level<'a> {
 
     /// Similar to `IfArm` but for `(do ...)` and `(catch ...)` blocks.
     TryArm,
+
+    /// This is a `(@metadata.code.branch_hint ...)` annotation, which doesn't
+    /// produce any instructions.
+    BranchHint,
 }
 
 /// Possible states of "what is currently being parsed?" in an `if` expression.
 enum If<'a> {
     /// Only the `if` instructoin has been parsed, next thing to parse is the
-    /// clause, if any, of the `if` instruction.
+    /// clause, if any, of the `if` instruction. The value of the branch hint
+    /// for the `if`, if any, is also recorded here.
     ///
     /// This parse ends when `(then ...)` is encountered.
-    Clause(Instruction<'a>),
+    Clause(Instruction<'a>, Option<u32>),
     /// Currently parsing the `then` block, and afterwards a closing paren is
     /// required or an `(else ...)` expression.
     Then,
@@ -150,7 +180,11 @@ impl<'a> ExpressionParser<'a> {
             match self.paren(parser)? {
                 // No parenthesis seen? Then we just parse the next instruction
                 // and move on.
-                Paren::None => self.instrs.push(parser.parse()?),
+                Paren::None => {
+                    let instr = parser.parse()?;
+                    let hint = self.take_branch_hint(parser, &instr)?;
+                    self.push_instr(instr, hint);
+                }
 
                 // If we see a left-parenthesis then things are a little
                 // special. We handle block-like instructions specially
@@ -161,7 +195,14 @@ impl<'a> ExpressionParser<'a> {
                 // In all cases here we push something onto the `stack` to get
                 // popped when the `)` character is seen.
                 Paren::Left => {
-                    // First up is handling `if` parsing, which is funky in a
+                    // Branch hint annotations apply to the next instruction
+                    // and otherwise don't produce anything themselves.
+                    if parser.peek::<annotation::metadata_code_branch_hint>()? {
+                        self.parse_branch_hint(parser)?;
+                        self.stack.push(Level::BranchHint);
+                        continue;
+                    }
+                    // Next up is handling `if` parsing, which is funky in a
                     // whole bunch of ways. See the method internally for more
                     // information.
                     if self.handle_if_lparen(parser)? {
@@ -172,22 +213,26 @@ impl<'a> ExpressionParser<'a> {
                     if self.handle_try_lparen(parser)? {
                         continue;
                     }
-                    match parser.parse()? {
-                        // If block/loop show up then we just need to be sure to
-                        // push an `end` instruction whenever the `)` token is
-                        // seen
+                    let instr = parser.parse()?;
+                    let hint = self.take_branch_hint(parser, &instr)?;
+                    match instr {
+                        // If block/loop/try_table show up then we just need to
+                        // be sure to push an `end` instruction whenever the `)`
+                        // token is seen
                         i @ Instruction::Block(_)
                         | i @ Instruction::Loop(_)
+                        | i @ Instruction::TryTable(_)
                         | i @ Instruction::Let(_) => {
                             self.instrs.push(i);
-                            self.stack.push(Level::EndWith(Instruction::End(None)));
+                            self.stack
+                                .push(Level::EndWith(Instruction::End(None), None));
                         }
 
                         // Parsing an `if` instruction is super tricky, so we
                         // push an `If` scope and we let all our scope-based
                         // parsing handle the remaining items.
                         i @ Instruction::If(_) => {
-                            self.stack.push(Level::If(If::Clause(i)));
+                            self.stack.push(Level::If(If::Clause(i, hint)));
                         }
 
                         // Parsing a `try` is easier than `if` but we also push
@@ -199,7 +244,7 @@ impl<'a> ExpressionParser<'a> {
                         // Anything else means that we're parsing a nested form
                         // such as `(i32.add ...)` which means that the
                         // instruction we parsed will be coming at the end.
-                        other => self.stack.push(Level::EndWith(other)),
+                        other => self.stack.push(Level::EndWith(other, hint)),
                     }
                 }
 
@@ -207,7 +252,13 @@ impl<'a> ExpressionParser<'a> {
                 // guaranteed there's an item in the `stack` stack for us to
                 // pop. We peel that off and take a look at what it says to do.
                 Paren::Right => match self.stack.pop().unwrap() {
-                    Level::EndWith(i) => self.instrs.push(i),
+                    Level::BranchHint => {}
+                    _ if self.pending_branch_hint.is_some() => {
+                        return Err(parser.error(
+                            "branch hint must be followed by an `if` or `br_if` instruction",
+                        ));
+                    }
+                    Level::EndWith(i, hint) => self.push_instr(i, hint),
                     Level::IfArm => {}
                     Level::TryArm => {}
 
@@ -215,7 +266,7 @@ impl<'a> ExpressionParser<'a> {
                     // block, then that's an error because there weren't enough
                     // items in the `if` statement. Otherwise we're just careful
                     // to terminate with an `end` instruction.
-                    Level::If(If::Clause(_)) => {
+                    Level::If(If::Clause(..)) => {
                         return Err(parser.error("previous `if` had no `then`"));
                     }
                     Level::If(_) => {
@@ -236,19 +287,72 @@ impl<'a> ExpressionParser<'a> {
             }
         }
 
+        if self.pending_branch_hint.is_some() {
+            return Err(parser.error(
+                "branch hint must be followed by an `if` or `br_if` instruction",
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Parses a `(@metadata.code.branch_hint "\xx")` annotation, after the
+    /// leading `(` has already been consumed, and records it as pending for
+    /// the next instruction.
+    fn parse_branch_hint(&mut self, parser: Parser<'a>) -> Result<()> {
+        parser.parse::<annotation::metadata_code_branch_hint>()?;
+        if self.pending_branch_hint.is_some() {
+            return Err(parser.error("multiple branch hints for one instruction"));
+        }
+        let value = match parser.parse::<&[u8]>()? {
+            [0] => 0,
+            [1] => 1,
+            _ => return Err(parser.error("invalid value for branch hint")),
+        };
+        self.pending_branch_hint = Some(value);
         Ok(())
     }
 
+    /// Takes the pending branch hint, if any, for the just-parsed `instr`,
+    /// returning an error if it's not an instruction which can be hinted.
+    fn take_branch_hint(
+        &mut self,
+        parser: Parser<'a>,
+        instr: &Instruction<'a>,
+    ) -> Result<Option<u32>> {
+        let hint = match self.pending_branch_hint.take() {
+            Some(hint) => hint,
+            None => return Ok(None),
+        };
+        match instr {
+            Instruction::If(_) | Instruction::BrIf(_) => Ok(Some(hint)),
+            _ => Err(parser.error(
+                "branch hint must be followed by an `if` or `br_if` instruction",
+            )),
+        }
+    }
+
+    /// Pushes `instr`, recording its branch hint, if any.
+    fn push_instr(&mut self, instr: Instruction<'a>, hint: Option<u32>) {
+        if let Some(value) = hint {
+            self.branch_hints.push(BranchHint {
+                instr_index: self.instrs.len(),
+                value,
+            });
+        }
+        self.instrs.push(instr);
+    }
+
     fn parse_folded_instruction(&mut self, parser: Parser<'a>) -> Result<()> {
         let mut done = false;
         while !done {
             match self.paren(parser)? {
                 Paren::Left => {
-                    self.stack.push(Level::EndWith(parser.parse()?));
+                    self.stack.push(Level::EndWith(parser.parse()?, None));
                 }
                 Paren::Right => {
                     let top_instr = match self.stack.pop().unwrap() {
-                        Level::EndWith(i) => i,
+                        Level::EndWith(i, _) => i,
                         _ => panic!("unknown level type"),
                     };
                     self.instrs.push(top_instr);
@@ -304,12 +408,18 @@ impl<'a> ExpressionParser<'a> {
             // folded instruction unless it starts with `then`, in which case
             // this transitions to the `Then` state and a new level has been
             // reached.
-            If::Clause(if_instr) => {
+            If::Clause(if_instr, hint) => {
                 if !parser.peek::<kw::then>()? {
                     return Ok(false);
                 }
                 parser.parse::<kw::then>()?;
                 let instr = mem::replace(if_instr, Instruction::End(None));
+                if let Some(value) = hint.take() {
+                    self.branch_hints.push(BranchHint {
+                        instr_index: self.instrs.len(),
+                        value,
+                    });
+                }
                 self.instrs.push(instr);
                 *i = If::Then;
                 self.stack.push(Level::IfArm);
@@ -649,8 +759,8 @@ instructions! {
         BrOnCastFail(Box<BrOnCastFail<'a>>) : [] : "br_on_cast_fail",
 
         // gc proposal extern/any coercion operations
-        ExternInternalize : [0xfb, 0x1a] : "extern.internalize",
-        ExternExternalize : [0xfb, 0x1b] : "extern.externalize",
+        AnyConvertExtern : [0xfb, 0x1a] : "any.convert_extern" | "extern.internalize",
+        ExternConvertAny : [0xfb, 0x1b] : "extern.convert_any" | "extern.externalize",
 
         I32Const(i32) : [0x41] : "i32.const",
         I64Const(i64) : [0x42] : "i64.const",
@@ -1153,6 +1263,14 @@ instructions! {
         ThrowRef : [0x0a] : "throw_ref",
         TryTable(TryTable<'a>) : [0x1f] : "try_table",
 
+        // Stack switching proposal
+        ContNew(Index<'a>) : [0xe0] : "cont.new",
+        ContBind(ContBind<'a>) : [0xe1] : "cont.bind",
+        Suspend(Index<'a>) : [0xe2] : "suspend",
+        Resume(Box<Resume<'a>>) : [0xe3] : "resume",
+        ResumeThrow(Box<ResumeThrow<'a>>) : [0xe4] : "resume_throw",
+        Switch(Switch<'a>) : [0xe5] : "switch",
+
         // Relaxed SIMD proposal
         I8x16RelaxedSwizzle : [0xfd, 0x100]: "i8x16.relaxed_swizzle",
         I32x4RelaxedTruncF32x4S : [0xfd, 0x101]: "i32x4.relaxed_trunc_f32x4_s",
@@ -1228,7 +1346,6 @@ impl<'a> Parse<'a> for BlockType<'a> {
 pub struct TryTable<'a> {
     pub block: Box<BlockType<'a>>,
     pub catches: Vec<TryTableCatch<'a>>,
-    pub catch_all: Option<TryTableCatchAll<'a>>,
 }
 
 impl<'a> Parse<'a> for TryTable<'a> {
@@ -1236,69 +1353,53 @@ impl<'a> Parse<'a> for TryTable<'a> {
         let block = parser.parse()?;
 
         let mut catches = Vec::new();
-        while parser.peek2::<kw::catch>()? || parser.peek2::<kw::catch_ref>()? {
+        while parser.peek2::<kw::catch>()?
+            || parser.peek2::<kw::catch_ref>()?
+            || parser.peek2::<kw::catch_all>()?
+            || parser.peek2::<kw::catch_all_ref>()?
+        {
             catches.push(parser.parens(|p| {
                 let kind = if parser.peek::<kw::catch_ref>()? {
                     p.parse::<kw::catch_ref>()?;
-                    TryTableCatchKind::Ref
-                } else {
+                    TryTableCatchKind::CatchRef(p.parse()?)
+                } else if parser.peek::<kw::catch>()? {
                     p.parse::<kw::catch>()?;
-                    TryTableCatchKind::Drop
-                };
-                Ok(TryTableCatch {
-                    kind,
-                    tag: p.parse()?,
-                    label: p.parse()?,
-                })
-            })?);
-        }
-
-        let mut catch_all = None;
-        if parser.peek2::<kw::catch_all>()? || parser.peek2::<kw::catch_all_ref>()? {
-            catch_all = Some(parser.parens(|p| {
-                let kind = if parser.peek::<kw::catch_all_ref>()? {
-                    p.parse::<kw::catch_all_ref>()?;
-                    TryTableCatchKind::Ref
-                } else {
+                    TryTableCatchKind::Catch(p.parse()?)
+                } else if parser.peek::<kw::catch_all>()? {
                     p.parse::<kw::catch_all>()?;
-                    TryTableCatchKind::Drop
+                    TryTableCatchKind::CatchAll
+                } else {
+                    p.parse::<kw::catch_all_ref>()?;
+                    TryTableCatchKind::CatchAllRef
                 };
-                Ok(TryTableCatchAll {
+                Ok(TryTableCatch {
                     kind,
                     label: p.parse()?,
                 })
             })?);
         }
 
-        Ok(TryTable {
-            block,
-            catches,
-            catch_all,
-        })
+        Ok(TryTable { block, catches })
     }
 }
 
 #[derive(Debug)]
 #[allow(missing_docs)]
-pub enum TryTableCatchKind {
-    // Capture the exnref for the try
-    Ref,
-    // Drop the exnref for the try
-    Drop,
+pub enum TryTableCatchKind<'a> {
+    // Catch a tagged exception, do not capture an exnref.
+    Catch(Index<'a>),
+    // Catch a tagged exception, and capture the exnref.
+    CatchRef(Index<'a>),
+    // Catch any exception, do not capture an exnref.
+    CatchAll,
+    // Catch any exception, and capture the exnref.
+    CatchAllRef,
 }
 
 #[derive(Debug)]
 #[allow(missing_docs)]
 pub struct TryTableCatch<'a> {
-    pub kind: TryTableCatchKind,
-    pub tag: Index<'a>,
-    pub label: Index<'a>,
-}
-
-#[derive(Debug)]
-#[allow(missing_docs)]
-pub struct TryTableCatchAll<'a> {
-    pub kind: TryTableCatchKind,
+    pub kind: TryTableCatchKind<'a>,
     pub label: Index<'a>,
 }
 
@@ -1852,6 +1953,132 @@ impl<'a> Parse<'a> for BrOnCastFail<'a> {
     }
 }
 
+/// Extra data associated with the `cont.bind` instruction
+#[derive(Debug)]
+pub struct ContBind<'a> {
+    /// The continuation type of the continuation being bound.
+    pub argument_index: Index<'a>,
+    /// The continuation type of the resulting continuation.
+    pub result_index: Index<'a>,
+}
+
+impl<'a> Parse<'a> for ContBind<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(ContBind {
+            argument_index: parser.parse()?,
+            result_index: parser.parse()?,
+        })
+    }
+}
+
+/// Extra data associated with the `resume` instruction
+#[derive(Debug)]
+pub struct Resume<'a> {
+    /// The continuation type of the continuation being resumed.
+    pub type_index: Index<'a>,
+    /// The handlers installed while the continuation runs.
+    pub table: ResumeTable<'a>,
+}
+
+impl<'a> Parse<'a> for Resume<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Resume {
+            type_index: parser.parse()?,
+            table: parser.parse()?,
+        })
+    }
+}
+
+/// Extra data associated with the `resume_throw` instruction
+#[derive(Debug)]
+pub struct ResumeThrow<'a> {
+    /// The continuation type of the continuation being resumed.
+    pub type_index: Index<'a>,
+    /// The exception tag to throw in the continuation.
+    pub tag_index: Index<'a>,
+    /// The handlers installed while the continuation runs.
+    pub table: ResumeTable<'a>,
+}
+
+impl<'a> Parse<'a> for ResumeThrow<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(ResumeThrow {
+            type_index: parser.parse()?,
+            tag_index: parser.parse()?,
+            table: parser.parse()?,
+        })
+    }
+}
+
+/// Extra data associated with the `switch` instruction
+#[derive(Debug)]
+pub struct Switch<'a> {
+    /// The continuation type of the continuation being switched to.
+    pub type_index: Index<'a>,
+    /// The tag used to switch.
+    pub tag_index: Index<'a>,
+}
+
+impl<'a> Parse<'a> for Switch<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(Switch {
+            type_index: parser.parse()?,
+            tag_index: parser.parse()?,
+        })
+    }
+}
+
+/// The list of `(on ...)` handler clauses of a `resume` or `resume_throw`
+/// instruction.
+#[derive(Debug)]
+pub struct ResumeTable<'a> {
+    /// The handlers, in the order they were listed.
+    pub handlers: Vec<Handle<'a>>,
+}
+
+impl<'a> Parse<'a> for ResumeTable<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        let mut handlers = Vec::new();
+        while parser.peek::<LParen>()? && parser.peek2::<kw::on>()? {
+            handlers.push(parser.parens(|p| p.parse())?);
+        }
+        Ok(ResumeTable { handlers })
+    }
+}
+
+/// A single `(on ...)` handler clause.
+#[derive(Debug)]
+pub enum Handle<'a> {
+    /// `(on $tag $label)`: branch to `label` when `tag` is suspended.
+    OnLabel {
+        /// The tag being handled.
+        tag: Index<'a>,
+        /// The label to branch to.
+        label: Index<'a>,
+    },
+    /// `(on $tag switch)`: allow a `switch` using `tag`.
+    OnSwitch {
+        /// The tag being handled.
+        tag: Index<'a>,
+    },
+}
+
+impl<'a> Parse<'a> for Handle<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        parser.parse::<kw::on>()?;
+        let tag = parser.parse()?;
+        if parser.peek::<kw::switch>()? {
+            parser.parse::<kw::switch>()?;
+            Ok(Handle::OnSwitch { tag })
+        } else {
+            Ok(Handle::OnLabel {
+                tag,
+                label: parser.parse()?,
+            })
+        }
+    }
+}
+
 /// Different ways to specify a `v128.const` instruction
 #[derive(Debug)]
 #[allow(missing_docs)]
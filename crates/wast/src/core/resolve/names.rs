@@ -65,7 +65,7 @@ impl<'a> Resolver<'a> {
                 }
             }
 
-            TypeDef::Array(_) | TypeDef::Func(_) => {}
+            TypeDef::Array(_) | TypeDef::Func(_) | TypeDef::Cont(_) => {}
         }
 
         // Record function signatures as we see them to so we can
@@ -128,6 +128,9 @@ impl<'a> Resolver<'a> {
                 }
             }
             TypeDef::Array(array) => self.resolve_storagetype(&mut array.ty)?,
+            TypeDef::Cont(cont) => {
+                self.resolve(&mut cont.0, Ns::Type)?;
+            }
         }
         if let Some(parent) = &mut ty.parent {
             self.resolve(parent, Ns::Type)?;
@@ -522,18 +525,22 @@ impl<'a, 'b> ExprResolver<'a, 'b> {
                 self.resolve_block_type(bt)?;
             }
             TryTable(try_table) => {
+                // Catch labels are relative to the block enclosing the
+                // `try_table`, so resolve them before pushing its own label.
+                for catch in &mut try_table.catches {
+                    match &mut catch.kind {
+                        TryTableCatchKind::Catch(tag) | TryTableCatchKind::CatchRef(tag) => {
+                            self.resolver.resolve(tag, Ns::Tag)?;
+                        }
+                        TryTableCatchKind::CatchAll | TryTableCatchKind::CatchAllRef => {}
+                    }
+                    self.resolve_label(&mut catch.label)?;
+                }
                 self.blocks.push(ExprBlock {
                     label: try_table.block.label,
                     pushed_scope: false,
                 });
                 self.resolve_block_type(&mut try_table.block)?;
-                for catch in &mut try_table.catches {
-                    self.resolver.resolve(&mut catch.tag, Ns::Tag)?;
-                    self.resolve_label(&mut catch.label)?;
-                }
-                if let Some(catch_all) = &mut try_table.catch_all {
-                    self.resolve_label(&mut catch_all.label)?;
-                }
             }
 
             // On `End` instructions we pop a label from the stack, and for both
@@ -667,11 +674,50 @@ impl<'a, 'b> ExprResolver<'a, 'b> {
 
             RefNull(ty) => self.resolver.resolve_heaptype(ty)?,
 
+            ContNew(i) => {
+                self.resolver.resolve(i, Ns::Type)?;
+            }
+            ContBind(c) => {
+                self.resolver.resolve(&mut c.argument_index, Ns::Type)?;
+                self.resolver.resolve(&mut c.result_index, Ns::Type)?;
+            }
+            Suspend(i) => {
+                self.resolver.resolve(i, Ns::Tag)?;
+            }
+            Resume(r) => {
+                self.resolver.resolve(&mut r.type_index, Ns::Type)?;
+                self.resolve_resume_table(&mut r.table)?;
+            }
+            ResumeThrow(r) => {
+                self.resolver.resolve(&mut r.type_index, Ns::Type)?;
+                self.resolver.resolve(&mut r.tag_index, Ns::Tag)?;
+                self.resolve_resume_table(&mut r.table)?;
+            }
+            Switch(s) => {
+                self.resolver.resolve(&mut s.type_index, Ns::Type)?;
+                self.resolver.resolve(&mut s.tag_index, Ns::Tag)?;
+            }
+
             _ => {}
         }
         Ok(())
     }
 
+    fn resolve_resume_table(&self, table: &mut ResumeTable<'a>) -> Result<(), Error> {
+        for handle in table.handlers.iter_mut() {
+            match handle {
+                Handle::OnLabel { tag, label } => {
+                    self.resolver.resolve(tag, Ns::Tag)?;
+                    self.resolve_label(label)?;
+                }
+                Handle::OnSwitch { tag } => {
+                    self.resolver.resolve(tag, Ns::Tag)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn resolve_label(&self, label: &mut Index<'a>) -> Result<(), Error> {
         let id = match label {
             Index::Num(..) => return Ok(()),
@@ -86,6 +86,12 @@ pub enum HeapType<'a> {
     NoExtern,
     /// The bottom type of the anyref hierarchy. Part of the GC proposal.
     None,
+    /// A reference to a continuation: contref. Part of the stack switching
+    /// proposal.
+    Cont,
+    /// The bottom type of the contref hierarchy. Part of the stack switching
+    /// proposal.
+    NoCont,
     /// A reference to a concrete function, struct, or array type defined by
     /// Wasm: `ref T`. This is part of the function references and GC proposals.
     Concrete(Index<'a>),
@@ -127,6 +133,12 @@ impl<'a> Parse<'a> for HeapType<'a> {
         } else if l.peek::<kw::none>()? {
             parser.parse::<kw::none>()?;
             Ok(HeapType::None)
+        } else if l.peek::<kw::cont>()? {
+            parser.parse::<kw::cont>()?;
+            Ok(HeapType::Cont)
+        } else if l.peek::<kw::nocont>()? {
+            parser.parse::<kw::nocont>()?;
+            Ok(HeapType::NoCont)
         } else if l.peek::<Index>()? {
             Ok(HeapType::Concrete(parser.parse()?))
         } else {
@@ -148,6 +160,8 @@ impl<'a> Peek for HeapType<'a> {
             || kw::nofunc::peek(cursor)?
             || kw::noextern::peek(cursor)?
             || kw::none::peek(cursor)?
+            || kw::cont::peek(cursor)?
+            || kw::nocont::peek(cursor)?
             || (LParen::peek(cursor)? && kw::r#type::peek2(cursor)?))
     }
     fn display() -> &'static str {
@@ -251,6 +265,22 @@ impl<'a> RefType<'a> {
             heap: HeapType::None,
         }
     }
+
+    /// A `contref` as an abbreviation for `(ref null cont)`.
+    pub fn cont() -> Self {
+        RefType {
+            nullable: true,
+            heap: HeapType::Cont,
+        }
+    }
+
+    /// A `nullcontref` as an abbreviation for `(ref null nocont)`.
+    pub fn nullcontref() -> Self {
+        RefType {
+            nullable: true,
+            heap: HeapType::NoCont,
+        }
+    }
 }
 
 impl<'a> Parse<'a> for RefType<'a> {
@@ -292,6 +322,12 @@ impl<'a> Parse<'a> for RefType<'a> {
         } else if l.peek::<kw::nullref>()? {
             parser.parse::<kw::nullref>()?;
             Ok(RefType::nullref())
+        } else if l.peek::<kw::contref>()? {
+            parser.parse::<kw::contref>()?;
+            Ok(RefType::cont())
+        } else if l.peek::<kw::nullcontref>()? {
+            parser.parse::<kw::nullcontref>()?;
+            Ok(RefType::nullcontref())
         } else if l.peek::<LParen>()? {
             parser.parens(|p| {
                 let mut l = parser.lookahead1();
@@ -332,6 +368,8 @@ impl<'a> Peek for RefType<'a> {
             || kw::nullfuncref::peek(cursor)?
             || kw::nullexternref::peek(cursor)?
             || kw::nullref::peek(cursor)?
+            || kw::contref::peek(cursor)?
+            || kw::nullcontref::peek(cursor)?
             || (LParen::peek(cursor)? && kw::r#ref::peek2(cursor)?))
     }
     fn display() -> &'static str {
@@ -683,6 +721,16 @@ impl<'a> Parse<'a> for ArrayType<'a> {
     }
 }
 
+/// A continuation type, referring to the function type of the continuation.
+#[derive(Clone, Debug)]
+pub struct ContType<'a>(pub Index<'a>);
+
+impl<'a> Parse<'a> for ContType<'a> {
+    fn parse(parser: Parser<'a>) -> Result<Self> {
+        Ok(ContType(parser.parse()?))
+    }
+}
+
 /// The type of an exported item from a module or instance.
 #[derive(Debug, Clone)]
 pub struct ExportType<'a> {
@@ -712,6 +760,8 @@ pub enum TypeDef<'a> {
     Struct(StructType<'a>),
     /// An array type definition.
     Array(ArrayType<'a>),
+    /// A continuation type definition.
+    Cont(ContType<'a>),
 }
 
 impl<'a> Parse<'a> for TypeDef<'a> {
@@ -726,6 +776,9 @@ impl<'a> Parse<'a> for TypeDef<'a> {
         } else if l.peek::<kw::array>()? {
             parser.parse::<kw::array>()?;
             Ok(TypeDef::Array(parser.parse()?))
+        } else if l.peek::<kw::cont>()? {
+            parser.parse::<kw::cont>()?;
+            Ok(TypeDef::Cont(parser.parse()?))
         } else {
             Err(l.error())
         }
@@ -398,6 +398,8 @@ pub mod kw {
     custom_keyword!(catch_all_ref);
     custom_keyword!(code);
     custom_keyword!(component);
+    custom_keyword!(cont);
+    custom_keyword!(contref);
     custom_keyword!(data);
     custom_keyword!(declare);
     custom_keyword!(delegate);
@@ -421,6 +423,7 @@ pub mod kw {
     custom_keyword!(first);
     custom_keyword!(func);
     custom_keyword!(funcref);
+    custom_keyword!(future);
     custom_keyword!(get);
     custom_keyword!(global);
     custom_keyword!(i16);
@@ -446,6 +449,7 @@ pub mod kw {
     custom_keyword!(modulecode);
     custom_keyword!(nan_arithmetic = "nan:arithmetic");
     custom_keyword!(nan_canonical = "nan:canonical");
+    custom_keyword!(nocont);
     custom_keyword!(nofunc);
     custom_keyword!(noextern);
     custom_keyword!(none);
@@ -453,7 +457,9 @@ pub mod kw {
     custom_keyword!(nullfuncref);
     custom_keyword!(nullexternref);
     custom_keyword!(nullref);
+    custom_keyword!(nullcontref);
     custom_keyword!(offset);
+    custom_keyword!(on);
     custom_keyword!(outer);
     custom_keyword!(own);
     custom_keyword!(param);
@@ -478,7 +484,9 @@ pub mod kw {
     custom_keyword!(result);
     custom_keyword!(shared);
     custom_keyword!(start);
+    custom_keyword!(stream);
     custom_keyword!(sub);
+    custom_keyword!(switch);
     custom_keyword!(r#final = "final");
     custom_keyword!(table);
     custom_keyword!(then);
@@ -539,4 +547,5 @@ pub mod annotation {
     annotation!(name);
     annotation!(producers);
     annotation!(dylink_0 = "dylink.0");
+    annotation!(metadata_code_branch_hint = "metadata.code.branch_hint");
 }
@@ -92,7 +92,6 @@ use wit_parser::{
 
 const INDIRECT_TABLE_NAME: &str = "$imports";
 
-pub mod docs;
 mod wit;
 pub use wit::{encode, encode_component, encode_world};
 
@@ -310,8 +309,9 @@ impl TypeContents {
                 TypeDefKind::Enum(_) => Self::empty(),
                 TypeDefKind::List(t) => Self::for_type(resolve, t) | Self::LIST,
                 TypeDefKind::Type(t) => Self::for_type(resolve, t),
-                TypeDefKind::Future(_) => todo!("encoding for future"),
-                TypeDefKind::Stream(_) => todo!("encoding for stream"),
+                // Futures and streams are passed as handles so their payloads
+                // don't require any memory to be lifted or lowered.
+                TypeDefKind::Future(_) | TypeDefKind::Stream(_) => Self::empty(),
                 TypeDefKind::Unknown => unreachable!(),
             },
             Type::String => Self::STRING,
@@ -3,8 +3,8 @@ use anyhow::Result;
 use std::collections::HashMap;
 use wasm_encoder::*;
 use wit_parser::{
-    Enum, Flags, Function, Handle, InterfaceId, Params, Record, Resolve, Result_, Results, Tuple,
-    Type, TypeDefKind, TypeId, TypeOwner, Variant,
+    Enum, Flags, Function, Handle, InterfaceId, Params, Record, Resolve, Result_, Results, Stream,
+    Tuple, Type, TypeDefKind, TypeId, TypeOwner, Variant,
 };
 
 /// Represents a key type for interface function definitions.
@@ -153,8 +153,8 @@ pub trait ValtypeEncoder<'a> {
                         ComponentValType::Type(index)
                     }
                     TypeDefKind::Type(ty) => self.encode_valtype(resolve, ty)?,
-                    TypeDefKind::Future(_) => todo!("encoding for future type"),
-                    TypeDefKind::Stream(_) => todo!("encoding for stream type"),
+                    TypeDefKind::Future(ty) => self.encode_future(resolve, ty)?,
+                    TypeDefKind::Stream(s) => self.encode_stream(resolve, s)?,
                     TypeDefKind::Unknown => unreachable!(),
                     TypeDefKind::Resource => {
                         let name = ty.name.as_ref().expect("resources must be named");
@@ -304,6 +304,25 @@ pub trait ValtypeEncoder<'a> {
         Ok(ComponentValType::Type(index))
     }
 
+    fn encode_future(
+        &mut self,
+        resolve: &'a Resolve,
+        payload: &Option<Type>,
+    ) -> Result<ComponentValType> {
+        let ty = self.encode_optional_valtype(resolve, payload.as_ref())?;
+        let (index, encoder) = self.defined_type();
+        encoder.future(ty);
+        Ok(ComponentValType::Type(index))
+    }
+
+    fn encode_stream(&mut self, resolve: &'a Resolve, stream: &Stream) -> Result<ComponentValType> {
+        let element = self.encode_optional_valtype(resolve, stream.element.as_ref())?;
+        let end = self.encode_optional_valtype(resolve, stream.end.as_ref())?;
+        let (index, encoder) = self.defined_type();
+        encoder.stream(element, end);
+        Ok(ComponentValType::Type(index))
+    }
+
     fn encode_enum(&mut self, enum_: &Enum) -> Result<ComponentValType> {
         let (index, encoder) = self.defined_type();
         encoder.enum_type(enum_.cases.iter().map(|c| c.name.as_str()));
@@ -467,12 +467,6 @@ impl<'a> Module<'a> {
         self.live_memories.insert(memory);
     }
 
-    fn blockty(&mut self, ty: BlockType) {
-        if let BlockType::FuncType(ty) = ty {
-            self.ty(ty);
-        }
-    }
-
     fn valty(&mut self, ty: ValType) {
         match ty {
             ValType::Ref(r) => self.heapty(r.heap_type()),
@@ -491,7 +485,10 @@ impl<'a> Module<'a> {
             | HeapType::Eq
             | HeapType::Struct
             | HeapType::Array
-            | HeapType::I31 => {}
+            | HeapType::I31
+            | HeapType::Exn
+            | HeapType::Cont
+            | HeapType::NoCont => {}
             HeapType::Concrete(i) => self.ty(i.as_module_index().unwrap()),
         }
     }
@@ -510,8 +507,16 @@ impl<'a> Module<'a> {
     }
 
     fn operators(&mut self, mut reader: BinaryReader<'a>) -> Result<()> {
+        let mut visitor = ItemRefVisitor::new(|r| match r {
+            ItemRef::Type(i) => self.ty(i),
+            ItemRef::Func(i) => self.func(i),
+            ItemRef::Table(i) => self.table(i),
+            ItemRef::Memory(i) => self.memory(i),
+            ItemRef::Global(i) => self.global(i),
+            ItemRef::Tag(_) | ItemRef::Elem(_) | ItemRef::Data(_) => {}
+        });
         while !reader.eof() {
-            reader.visit_operator(self)?;
+            reader.visit_operator(&mut visitor)?;
         }
         Ok(())
     }
@@ -979,61 +984,6 @@ impl<'a> Module<'a> {
     }
 }
 
-// This helper macro is used to define a visitor of all instructions with
-// special handling for all payloads of instructions to mark any referenced
-// items live.
-//
-// Currently item identification happens through the field name of the payload.
-// While not exactly the most robust solution this should work well enough for
-// now.
-macro_rules! define_visit {
-    ($(@$p:ident $op:ident $({ $($arg:ident: $argty:ty),* })? => $visit:ident)*) => {
-        $(
-            fn $visit(&mut self $(, $($arg: $argty),*)?)  {
-                $(
-                    $(
-                        define_visit!(mark_live self $arg $arg);
-                    )*
-                )?
-            }
-        )*
-    };
-
-    (mark_live $self:ident $arg:ident type_index) => {$self.ty($arg);};
-    (mark_live $self:ident $arg:ident src_table) => {$self.table($arg);};
-    (mark_live $self:ident $arg:ident dst_table) => {$self.table($arg);};
-    (mark_live $self:ident $arg:ident table_index) => {$self.table($arg);};
-    (mark_live $self:ident $arg:ident table) => {$self.table($arg);};
-    (mark_live $self:ident $arg:ident table_index) => {$self.table($arg);};
-    (mark_live $self:ident $arg:ident global_index) => {$self.global($arg);};
-    (mark_live $self:ident $arg:ident function_index) => {$self.func($arg);};
-    (mark_live $self:ident $arg:ident mem) => {$self.memory($arg);};
-    (mark_live $self:ident $arg:ident src_mem) => {$self.memory($arg);};
-    (mark_live $self:ident $arg:ident dst_mem) => {$self.memory($arg);};
-    (mark_live $self:ident $arg:ident memarg) => {$self.memory($arg.memory);};
-    (mark_live $self:ident $arg:ident blockty) => {$self.blockty($arg);};
-    (mark_live $self:ident $arg:ident ty) => {$self.valty($arg)};
-    (mark_live $self:ident $arg:ident hty) => {$self.heapty($arg)};
-    (mark_live $self:ident $arg:ident lane) => {};
-    (mark_live $self:ident $arg:ident lanes) => {};
-    (mark_live $self:ident $arg:ident flags) => {};
-    (mark_live $self:ident $arg:ident value) => {};
-    (mark_live $self:ident $arg:ident mem_byte) => {};
-    (mark_live $self:ident $arg:ident table_byte) => {};
-    (mark_live $self:ident $arg:ident local_index) => {};
-    (mark_live $self:ident $arg:ident relative_depth) => {};
-    (mark_live $self:ident $arg:ident tag_index) => {};
-    (mark_live $self:ident $arg:ident targets) => {};
-    (mark_live $self:ident $arg:ident data_index) => {};
-    (mark_live $self:ident $arg:ident elem_index) => {};
-}
-
-impl<'a> VisitOperator<'a> for Module<'a> {
-    type Output = ();
-
-    wasmparser::for_each_operator!(define_visit);
-}
-
 /// Helper function to filter `iter` based on the `live` set, yielding an
 /// iterator over the index of the item that's live as well as the item itself.
 fn live_iter<'a, T>(
@@ -1114,6 +1064,9 @@ impl Encoder {
             HeapType::Struct => wasm_encoder::HeapType::Struct,
             HeapType::Array => wasm_encoder::HeapType::Array,
             HeapType::I31 => wasm_encoder::HeapType::I31,
+            HeapType::Exn => wasm_encoder::HeapType::Exn,
+            HeapType::Cont => wasm_encoder::HeapType::Cont,
+            HeapType::NoCont => wasm_encoder::HeapType::NoCont,
             HeapType::Concrete(idx) => {
                 wasm_encoder::HeapType::Concrete(self.types.remap(idx.as_module_index().unwrap()))
             }
@@ -1155,6 +1108,9 @@ macro_rules! define_encode {
     (mk BrTable $arg:ident) => ({
         BrTable($arg.0, $arg.1)
     });
+    (mk TryTable $arg:ident) => ({
+        TryTable($arg.0, $arg.1)
+    });
     (mk CallIndirect $ty:ident $table:ident $table_byte:ident) => ({
         let _ = $table_byte;
         CallIndirect { ty: $ty, table: $table }
@@ -1175,6 +1131,32 @@ macro_rules! define_encode {
     (mk F32Const $v:ident) => (F32Const(f32::from_bits($v.bits())));
     (mk F64Const $v:ident) => (F64Const(f64::from_bits($v.bits())));
     (mk V128Const $v:ident) => (V128Const($v.i128()));
+    (mk RefTestNonNull $hty:ident) => (RefTest(wasm_encoder::RefType {
+        nullable: false,
+        heap_type: $hty,
+    }));
+    (mk RefTestNullable $hty:ident) => (RefTest(wasm_encoder::RefType {
+        nullable: true,
+        heap_type: $hty,
+    }));
+    (mk RefCastNonNull $hty:ident) => (RefCast(wasm_encoder::RefType {
+        nullable: false,
+        heap_type: $hty,
+    }));
+    (mk RefCastNullable $hty:ident) => (RefCast(wasm_encoder::RefType {
+        nullable: true,
+        heap_type: $hty,
+    }));
+    (mk StructGet $ty:ident $field:ident) => (StructGet($ty, $field));
+    (mk StructGetS $ty:ident $field:ident) => (StructGetS($ty, $field));
+    (mk StructGetU $ty:ident $field:ident) => (StructGetU($ty, $field));
+    (mk StructSet $ty:ident $field:ident) => (StructSet($ty, $field));
+    (mk ArrayNewFixed $ty:ident $size:ident) => (ArrayNewFixed($ty, $size));
+    (mk ArrayNewData $ty:ident $data:ident) => (ArrayNewData($ty, $data));
+    (mk ArrayNewElem $ty:ident $elem:ident) => (ArrayNewElem($ty, $elem));
+    (mk ArrayCopy $dst:ident $src:ident) => (ArrayCopy($dst, $src));
+    (mk ArrayInitData $ty:ident $data:ident) => (ArrayInitData($ty, $data));
+    (mk ArrayInitElem $ty:ident $elem:ident) => (ArrayInitElem($ty, $elem));
 
     // Catch-all for the translation of one payload argument which is typically
     // represented as a tuple-enum in wasm-encoder.
@@ -1201,6 +1183,19 @@ macro_rules! define_encode {
     (map $self:ident $arg:ident src_table) => {$self.tables.remap($arg)};
     (map $self:ident $arg:ident dst_table) => {$self.tables.remap($arg)};
     (map $self:ident $arg:ident type_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident struct_type_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident array_type_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident array_type_index_dst) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident array_type_index_src) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident cont_type_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident argument_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident result_index) => {$self.types.remap($arg)};
+    (map $self:ident $arg:ident from_ref_type) => {$self.refty($arg)};
+    (map $self:ident $arg:ident to_ref_type) => {$self.refty($arg)};
+    (map $self:ident $arg:ident field_index) => {$arg};
+    (map $self:ident $arg:ident array_size) => {$arg};
+    (map $self:ident $arg:ident array_data_index) => {$arg};
+    (map $self:ident $arg:ident array_elem_index) => {$arg};
     (map $self:ident $arg:ident ty) => {$self.valty($arg)};
     (map $self:ident $arg:ident local_index) => {$arg};
     (map $self:ident $arg:ident lane) => {$arg};
@@ -1210,6 +1205,21 @@ macro_rules! define_encode {
     (map $self:ident $arg:ident table_byte) => {$arg};
     (map $self:ident $arg:ident mem_byte) => {$arg};
     (map $self:ident $arg:ident value) => {$arg};
+    (map $self:ident $arg:ident resume_table) => ((
+        $arg.handlers.into_iter().map(|h| match h {
+            Handle::OnLabel { tag, label } => wasm_encoder::Handle::OnLabel { tag, label },
+            Handle::OnSwitch { tag } => wasm_encoder::Handle::OnSwitch { tag },
+        }).collect::<Vec<_>>().into()
+    ));
+    (map $self:ident $arg:ident try_table) => ((
+        $self.blockty($arg.ty),
+        $arg.catches.into_iter().map(|c| match c {
+            wasmparser::Catch::One { tag, label } => wasm_encoder::Catch::One { tag, label },
+            wasmparser::Catch::OneRef { tag, label } => wasm_encoder::Catch::OneRef { tag, label },
+            wasmparser::Catch::All { label } => wasm_encoder::Catch::All { label },
+            wasmparser::Catch::AllRef { label } => wasm_encoder::Catch::AllRef { label },
+        }).collect::<Vec<_>>().into(),
+    ));
     (map $self:ident $arg:ident targets) => ((
         $arg.targets().map(|i| i.unwrap()).collect::<Vec<_>>().into(),
         $arg.default(),
@@ -318,7 +318,7 @@ fn decode_custom_section(wasm: &[u8]) -> Result<(Resolve, WorldId, StringEncodin
         _ => bail!("expected an exported component type"),
     };
 
-    let (resolve, world) = crate::decoding::decode_world(types, ty)?;
+    let (resolve, world) = wit_parser::decoding::decode_world(types, ty)?;
     Ok((resolve, world, string_encoding))
 }
 
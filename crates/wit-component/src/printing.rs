@@ -59,6 +59,7 @@ impl WitPrinter {
         self.output.push_str("\n\n");
         for (name, id) in pkg.interfaces.iter() {
             self.print_docs(&resolve.interfaces[*id].docs);
+            self.print_stability(&resolve.interfaces[*id].stability);
             self.output.push_str("interface ");
             self.print_name(name);
             self.output.push_str(" {\n");
@@ -119,6 +120,7 @@ impl WitPrinter {
         for (name, func) in freestanding {
             self.new_item();
             self.print_docs(&func.docs);
+            self.print_stability(&func.stability);
             self.print_name(name);
             self.output.push_str(": ");
             self.print_function(resolve, func)?;
@@ -206,6 +208,7 @@ impl WitPrinter {
         for id in types_to_declare {
             self.new_item();
             self.print_docs(&resolve.types[id].docs);
+            self.print_stability(&resolve.types[id].stability);
             match resolve.types[id].kind {
                 TypeDefKind::Resource => self.print_resource(
                     resolve,
@@ -230,17 +233,15 @@ impl WitPrinter {
         }
         self.output.push_str(" {\n");
         for func in funcs {
+            self.print_docs(&func.docs);
+            self.print_stability(&func.stability);
             match &func.kind {
-                FunctionKind::Constructor(_) => {
-                    self.print_docs(&func.docs);
-                }
+                FunctionKind::Constructor(_) => {}
                 FunctionKind::Method(_) => {
-                    self.print_docs(&func.docs);
                     self.print_name(func.item_name());
                     self.output.push_str(": ");
                 }
                 FunctionKind::Static(_) => {
-                    self.print_docs(&func.docs);
                     self.print_name(func.item_name());
                     self.output.push_str(": ");
                     self.output.push_str("static ");
@@ -360,12 +361,17 @@ impl WitPrinter {
     ) -> Result<()> {
         // Print inline item docs
         if matches!(name, WorldKey::Name(_)) {
-            self.print_docs(match item {
-                WorldItem::Interface(id) => &resolve.interfaces[*id].docs,
-                WorldItem::Function(f) => &f.docs,
+            let (docs, stability) = match item {
+                WorldItem::Interface(id) => {
+                    let iface = &resolve.interfaces[*id];
+                    (&iface.docs, &iface.stability)
+                }
+                WorldItem::Function(f) => (&f.docs, &f.stability),
                 // Types are handled separately
                 WorldItem::Type(_) => unreachable!(),
-            });
+            };
+            self.print_docs(docs);
+            self.print_stability(stability);
         }
 
         self.output.push_str(desc);
@@ -483,11 +489,11 @@ impl WitPrinter {
                         self.output.push_str(">");
                     }
                     TypeDefKind::Type(ty) => self.print_type_name(resolve, ty)?,
-                    TypeDefKind::Future(_) => {
-                        todo!("document has an unnamed future type")
+                    TypeDefKind::Future(t) => {
+                        self.print_future_type(resolve, t)?;
                     }
-                    TypeDefKind::Stream(_) => {
-                        todo!("document has an unnamed stream type")
+                    TypeDefKind::Stream(s) => {
+                        self.print_stream_type(resolve, s)?;
                     }
                     TypeDefKind::Unknown => unreachable!(),
                 }
@@ -592,6 +598,54 @@ impl WitPrinter {
         Ok(())
     }
 
+    fn print_future_type(&mut self, resolve: &Resolve, ty: &Option<Type>) -> Result<()> {
+        self.output.push_str("future");
+        if let Some(ty) = ty {
+            self.output.push_str("<");
+            self.print_type_name(resolve, ty)?;
+            self.output.push_str(">");
+        }
+        Ok(())
+    }
+
+    fn print_stream_type(&mut self, resolve: &Resolve, stream: &Stream) -> Result<()> {
+        match stream {
+            Stream {
+                element: Some(element),
+                end: Some(end),
+            } => {
+                self.output.push_str("stream<");
+                self.print_type_name(resolve, element)?;
+                self.output.push_str(", ");
+                self.print_type_name(resolve, end)?;
+                self.output.push_str(">");
+            }
+            Stream {
+                element: None,
+                end: Some(end),
+            } => {
+                self.output.push_str("stream<_, ");
+                self.print_type_name(resolve, end)?;
+                self.output.push_str(">");
+            }
+            Stream {
+                element: Some(element),
+                end: None,
+            } => {
+                self.output.push_str("stream<");
+                self.print_type_name(resolve, element)?;
+                self.output.push_str(">");
+            }
+            Stream {
+                element: None,
+                end: None,
+            } => {
+                self.output.push_str("stream");
+            }
+        }
+        Ok(())
+    }
+
     fn declare_type(&mut self, resolve: &Resolve, ty: &Type) -> Result<()> {
         match ty {
             Type::Bool
@@ -644,8 +698,12 @@ impl WitPrinter {
                         }
                         None => bail!("unnamed type in document"),
                     },
-                    TypeDefKind::Future(_) => todo!("declare future"),
-                    TypeDefKind::Stream(_) => todo!("declare stream"),
+                    TypeDefKind::Future(inner) => {
+                        self.declare_future(resolve, ty.name.as_deref(), inner)?
+                    }
+                    TypeDefKind::Stream(inner) => {
+                        self.declare_stream(resolve, ty.name.as_deref(), inner)?
+                    }
                     TypeDefKind::Unknown => unreachable!(),
                 }
             }
@@ -832,6 +890,40 @@ impl WitPrinter {
         Ok(())
     }
 
+    fn declare_future(
+        &mut self,
+        resolve: &Resolve,
+        name: Option<&str>,
+        ty: &Option<Type>,
+    ) -> Result<()> {
+        if let Some(name) = name {
+            self.output.push_str("type ");
+            self.print_name(name);
+            self.output.push_str(" = ");
+            self.print_future_type(resolve, ty)?;
+            self.print_semicolon();
+            self.output.push_str("\n");
+        }
+        Ok(())
+    }
+
+    fn declare_stream(
+        &mut self,
+        resolve: &Resolve,
+        name: Option<&str>,
+        stream: &Stream,
+    ) -> Result<()> {
+        if let Some(name) = name {
+            self.output.push_str("type ");
+            self.print_name(name);
+            self.output.push_str(" = ");
+            self.print_stream_type(resolve, stream)?;
+            self.print_semicolon();
+            self.output.push_str("\n");
+        }
+        Ok(())
+    }
+
     fn print_name(&mut self, name: &str) {
         if is_keyword(name) {
             self.output.push_str("%");
@@ -839,6 +931,25 @@ impl WitPrinter {
         self.output.push_str(name);
     }
 
+    fn print_stability(&mut self, stability: &Stability) {
+        match stability {
+            Stability::Unknown => {}
+            Stability::Stable { since, feature } => {
+                self.output.push_str(&format!("@since(version = {since}"));
+                if let Some(feature) = feature {
+                    self.output.push_str(", feature = ");
+                    self.print_name(feature);
+                }
+                self.output.push_str(")\n");
+            }
+            Stability::Unstable { feature } => {
+                self.output.push_str("@unstable(feature = ");
+                self.print_name(feature);
+                self.output.push_str(")\n");
+            }
+        }
+    }
+
     fn print_docs(&mut self, docs: &Docs) {
         if self.emit_docs {
             if let Some(contents) = &docs.contents {
@@ -11,6 +11,7 @@
     )
   )
   (export (;1;) "console" (type 0))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -100,6 +100,7 @@
     )
   )
   (export (;7;) "w3" (type 6))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -43,6 +43,7 @@
     )
   )
   (export (;5;) "actually-empty-world" (type 4))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
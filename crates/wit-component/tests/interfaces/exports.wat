@@ -32,6 +32,7 @@
     )
   )
   (export (;3;) "export-foo" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -80,6 +80,7 @@
     )
   )
   (export (;3;) "flags-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
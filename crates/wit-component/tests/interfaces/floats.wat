@@ -40,6 +40,7 @@
     )
   )
   (export (;3;) "floats-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -24,6 +24,7 @@
     )
   )
   (export (;1;) "foo" (type 0))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -0,0 +1,123 @@
+(component
+  (type (;0;)
+    (component
+      (type (;0;)
+        (instance
+          (type (;0;) (list u8))
+          (type (;1;) (record (field "id" u32) (field "data" 0)))
+          (export (;2;) "payload" (type (eq 1)))
+          (type (;3;) (future))
+          (export (;4;) "unit-future" (type (eq 3)))
+          (type (;5;) (future 2))
+          (export (;6;) "payload-future" (type (eq 5)))
+          (type (;7;) (stream))
+          (export (;8;) "unit-stream" (type (eq 7)))
+          (type (;9;) (stream u8))
+          (export (;10;) "byte-stream" (type (eq 9)))
+          (type (;11;) (stream (end string)))
+          (export (;12;) "end-stream" (type (eq 11)))
+          (type (;13;) (result (error string)))
+          (type (;14;) (stream 2 (end 13)))
+          (export (;15;) "payload-stream" (type (eq 14)))
+          (type (;16;) (future u32))
+          (type (;17;) (func (param "x" 4) (result 16)))
+          (export (;0;) "wait" (func (type 17)))
+          (type (;18;) (func (param "id" u32) (result 6)))
+          (export (;1;) "fetch" (func (type 18)))
+          (type (;19;) (stream 0 (end u64)))
+          (type (;20;) (func (param "s" 10) (param "n" u32) (result 19)))
+          (export (;2;) "read" (func (type 20)))
+          (type (;21;) (stream u8))
+          (type (;22;) (option 21))
+          (type (;23;) (future 22))
+          (type (;24;) (func (param "a" 15) (param "b" 12) (param "c" 8) (result 23)))
+          (export (;3;) "forward" (func (type 24)))
+        )
+      )
+      (export (;0;) "foo:futures-and-streams/transfers" (instance (type 0)))
+    )
+  )
+  (export (;1;) "transfers" (type 0))
+  (type (;2;)
+    (component
+      (type (;0;)
+        (component
+          (type (;0;)
+            (instance
+              (type (;0;) (list u8))
+              (type (;1;) (record (field "id" u32) (field "data" 0)))
+              (export (;2;) "payload" (type (eq 1)))
+              (type (;3;) (future))
+              (export (;4;) "unit-future" (type (eq 3)))
+              (type (;5;) (future 2))
+              (export (;6;) "payload-future" (type (eq 5)))
+              (type (;7;) (stream))
+              (export (;8;) "unit-stream" (type (eq 7)))
+              (type (;9;) (stream u8))
+              (export (;10;) "byte-stream" (type (eq 9)))
+              (type (;11;) (stream (end string)))
+              (export (;12;) "end-stream" (type (eq 11)))
+              (type (;13;) (result (error string)))
+              (type (;14;) (stream 2 (end 13)))
+              (export (;15;) "payload-stream" (type (eq 14)))
+              (type (;16;) (future u32))
+              (type (;17;) (func (param "x" 4) (result 16)))
+              (export (;0;) "wait" (func (type 17)))
+              (type (;18;) (func (param "id" u32) (result 6)))
+              (export (;1;) "fetch" (func (type 18)))
+              (type (;19;) (stream 0 (end u64)))
+              (type (;20;) (func (param "s" 10) (param "n" u32) (result 19)))
+              (export (;2;) "read" (func (type 20)))
+              (type (;21;) (stream u8))
+              (type (;22;) (option 21))
+              (type (;23;) (future 22))
+              (type (;24;) (func (param "a" 15) (param "b" 12) (param "c" 8) (result 23)))
+              (export (;3;) "forward" (func (type 24)))
+            )
+          )
+          (import "foo:futures-and-streams/transfers" (instance (;0;) (type 0)))
+          (type (;1;)
+            (instance
+              (type (;0;) (list u8))
+              (type (;1;) (record (field "id" u32) (field "data" 0)))
+              (export (;2;) "payload" (type (eq 1)))
+              (type (;3;) (future))
+              (export (;4;) "unit-future" (type (eq 3)))
+              (type (;5;) (future 2))
+              (export (;6;) "payload-future" (type (eq 5)))
+              (type (;7;) (stream))
+              (export (;8;) "unit-stream" (type (eq 7)))
+              (type (;9;) (stream u8))
+              (export (;10;) "byte-stream" (type (eq 9)))
+              (type (;11;) (stream (end string)))
+              (export (;12;) "end-stream" (type (eq 11)))
+              (type (;13;) (result (error string)))
+              (type (;14;) (stream 2 (end 13)))
+              (export (;15;) "payload-stream" (type (eq 14)))
+              (type (;16;) (future u32))
+              (type (;17;) (func (param "x" 4) (result 16)))
+              (export (;0;) "wait" (func (type 17)))
+              (type (;18;) (func (param "id" u32) (result 6)))
+              (export (;1;) "fetch" (func (type 18)))
+              (type (;19;) (stream 0 (end u64)))
+              (type (;20;) (func (param "s" 10) (param "n" u32) (result 19)))
+              (export (;2;) "read" (func (type 20)))
+              (type (;21;) (stream u8))
+              (type (;22;) (option 21))
+              (type (;23;) (future 22))
+              (type (;24;) (func (param "a" 15) (param "b" 12) (param "c" 8) (result 23)))
+              (export (;3;) "forward" (func (type 24)))
+            )
+          )
+          (export (;1;) "foo:futures-and-streams/transfers" (instance (type 1)))
+        )
+      )
+      (export (;0;) "foo:futures-and-streams/futures-and-streams-world" (component (type 0)))
+    )
+  )
+  (export (;3;) "futures-and-streams-world" (type 2))
+  (@custom "package-docs" "\00{}")
+  (@producers
+    (processed-by "wit-component" "$CARGO_PKG_VERSION")
+  )
+)
\ No newline at end of file
@@ -102,6 +102,7 @@
     )
   )
   (export (;3;) "integers-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
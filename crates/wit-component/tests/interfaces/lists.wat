@@ -204,6 +204,7 @@
     )
   )
   (export (;3;) "lists-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -71,6 +71,7 @@
     )
   )
   (export (;7;) "a2" (type 6))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -40,6 +40,7 @@
     )
   )
   (export (;3;) "bar" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
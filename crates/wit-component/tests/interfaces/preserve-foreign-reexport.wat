@@ -17,6 +17,7 @@
     )
   )
   (export (;1;) "foo" (type 0))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
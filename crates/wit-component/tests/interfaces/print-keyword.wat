@@ -14,6 +14,7 @@
     )
   )
   (export (;1;) "interface" (type 0))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -134,6 +134,7 @@
     )
   )
   (export (;3;) "records-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -56,6 +56,7 @@
     )
   )
   (export (;3;) "foo-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -180,6 +180,7 @@
     )
   )
   (export (;11;) "implicit-own-handles3" (type 10))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
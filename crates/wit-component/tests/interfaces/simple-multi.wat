@@ -17,6 +17,7 @@
     )
   )
   (export (;3;) "foo" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
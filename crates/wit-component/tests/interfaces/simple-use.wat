@@ -33,6 +33,7 @@
     )
   )
   (export (;3;) "console" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -44,6 +44,7 @@
     )
   )
   (export (;3;) "my-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -190,6 +190,7 @@
     )
   )
   (export (;3;) "variants-world" (type 2))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -545,6 +545,7 @@
     )
   )
   (export (;7;) "proxy" (type 6))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
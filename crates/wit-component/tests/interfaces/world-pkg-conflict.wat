@@ -40,6 +40,7 @@
     )
   )
   (export (;5;) "c" (type 4))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
@@ -48,6 +48,7 @@
     )
   )
   (export (;5;) "just-export" (type 4))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
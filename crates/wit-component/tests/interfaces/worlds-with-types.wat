@@ -49,6 +49,7 @@
     )
   )
   (export (;5;) "with-imports" (type 4))
+  (@custom "package-docs" "\00{}")
   (@producers
     (processed-by "wit-component" "$CARGO_PKG_VERSION")
   )
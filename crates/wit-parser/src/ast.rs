@@ -32,7 +32,8 @@ impl<'a> Ast<'a> {
             docs = parse_docs(lexer)?;
         }
         while lexer.clone().next()?.is_some() {
-            items.push(AstItem::parse(lexer, docs)?);
+            let attributes = Attribute::parse_list(lexer)?;
+            items.push(AstItem::parse(lexer, docs, attributes)?);
             docs = parse_docs(lexer)?;
         }
         Ok(Self { package_id, items })
@@ -127,11 +128,23 @@ enum AstItem<'a> {
 }
 
 impl<'a> AstItem<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         match tokens.clone().next()? {
-            Some((_span, Token::Interface)) => Interface::parse(tokens, docs).map(Self::Interface),
-            Some((_span, Token::World)) => World::parse(tokens, docs).map(Self::World),
-            Some((_span, Token::Use)) => ToplevelUse::parse(tokens).map(Self::Use),
+            Some((_span, Token::Interface)) => {
+                Interface::parse(tokens, docs, attributes).map(Self::Interface)
+            }
+            Some((_span, Token::World)) => {
+                Attribute::deny(&attributes, "worlds")?;
+                World::parse(tokens, docs).map(Self::World)
+            }
+            Some((_span, Token::Use)) => {
+                Attribute::deny(&attributes, "`use` statements")?;
+                ToplevelUse::parse(tokens).map(Self::Use)
+            }
             other => Err(err_expected(tokens, "`world`, `interface` or `use`", other).into()),
         }
     }
@@ -217,7 +230,8 @@ impl<'a> World<'a> {
             if tokens.eat(Token::RightBrace)? {
                 break;
             }
-            items.push(WorldItem::parse(tokens, docs)?);
+            let attributes = Attribute::parse_list(tokens)?;
+            items.push(WorldItem::parse(tokens, docs, attributes)?);
         }
         Ok(items)
     }
@@ -232,24 +246,44 @@ enum WorldItem<'a> {
 }
 
 impl<'a> WorldItem<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<WorldItem<'a>> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<WorldItem<'a>> {
         match tokens.clone().next()? {
-            Some((_span, Token::Import)) => Import::parse(tokens, docs).map(WorldItem::Import),
-            Some((_span, Token::Export)) => Export::parse(tokens, docs).map(WorldItem::Export),
-            Some((_span, Token::Use)) => Use::parse(tokens).map(WorldItem::Use),
-            Some((_span, Token::Type)) => TypeDef::parse(tokens, docs).map(WorldItem::Type),
-            Some((_span, Token::Flags)) => TypeDef::parse_flags(tokens, docs).map(WorldItem::Type),
+            Some((_span, Token::Import)) => {
+                Import::parse(tokens, docs, attributes).map(WorldItem::Import)
+            }
+            Some((_span, Token::Export)) => {
+                Export::parse(tokens, docs, attributes).map(WorldItem::Export)
+            }
+            Some((_span, Token::Use)) => {
+                Attribute::deny(&attributes, "`use` statements")?;
+                Use::parse(tokens).map(WorldItem::Use)
+            }
+            Some((_span, Token::Type)) => {
+                TypeDef::parse(tokens, docs, attributes).map(WorldItem::Type)
+            }
+            Some((_span, Token::Flags)) => {
+                TypeDef::parse_flags(tokens, docs, attributes).map(WorldItem::Type)
+            }
             Some((_span, Token::Resource)) => {
-                TypeDef::parse_resource(tokens, docs).map(WorldItem::Type)
+                TypeDef::parse_resource(tokens, docs, attributes).map(WorldItem::Type)
             }
             Some((_span, Token::Record)) => {
-                TypeDef::parse_record(tokens, docs).map(WorldItem::Type)
+                TypeDef::parse_record(tokens, docs, attributes).map(WorldItem::Type)
             }
             Some((_span, Token::Variant)) => {
-                TypeDef::parse_variant(tokens, docs).map(WorldItem::Type)
+                TypeDef::parse_variant(tokens, docs, attributes).map(WorldItem::Type)
+            }
+            Some((_span, Token::Enum)) => {
+                TypeDef::parse_enum(tokens, docs, attributes).map(WorldItem::Type)
+            }
+            Some((_span, Token::Include)) => {
+                Attribute::deny(&attributes, "`include` statements")?;
+                Include::parse(tokens).map(WorldItem::Include)
             }
-            Some((_span, Token::Enum)) => TypeDef::parse_enum(tokens, docs).map(WorldItem::Type),
-            Some((_span, Token::Include)) => Include::parse(tokens).map(WorldItem::Include),
             other => Err(err_expected(
                 tokens,
                 "`import`, `export`, `include`, `use`, or type definition",
@@ -262,27 +296,45 @@ impl<'a> WorldItem<'a> {
 
 struct Import<'a> {
     docs: Docs<'a>,
+    attributes: Vec<Attribute<'a>>,
     kind: ExternKind<'a>,
 }
 
 impl<'a> Import<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Import<'a>> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Import<'a>> {
         tokens.expect(Token::Import)?;
-        let kind = ExternKind::parse(tokens)?;
-        Ok(Import { docs, kind })
+        let kind = ExternKind::parse(tokens, &attributes)?;
+        Ok(Import {
+            docs,
+            attributes,
+            kind,
+        })
     }
 }
 
 struct Export<'a> {
     docs: Docs<'a>,
+    attributes: Vec<Attribute<'a>>,
     kind: ExternKind<'a>,
 }
 
 impl<'a> Export<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Export<'a>> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Export<'a>> {
         tokens.expect(Token::Export)?;
-        let kind = ExternKind::parse(tokens)?;
-        Ok(Export { docs, kind })
+        let kind = ExternKind::parse(tokens, &attributes)?;
+        Ok(Export {
+            docs,
+            attributes,
+            kind,
+        })
     }
 }
 
@@ -293,7 +345,7 @@ enum ExternKind<'a> {
 }
 
 impl<'a> ExternKind<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>) -> Result<ExternKind<'a>> {
+    fn parse(tokens: &mut Tokenizer<'a>, attributes: &[Attribute<'a>]) -> Result<ExternKind<'a>> {
         // Create a copy of the token stream to test out if this is a function
         // or an interface import. In those situations the token stream gets
         // reset to the state of the clone and we continue down those paths.
@@ -323,6 +375,11 @@ impl<'a> ExternKind<'a> {
         // import foo
         // import foo/bar
         // import foo:bar/baz
+        //
+        // Stability is a property of the interface itself, so it's not
+        // possible to attach a different stability to an import or export
+        // of an interface defined elsewhere.
+        Attribute::deny(attributes, "imports or exports of named interfaces")?;
         let ret = ExternKind::Path(UsePath::parse(tokens)?);
         tokens.expect_semicolon()?;
         Ok(ret)
@@ -340,16 +397,26 @@ impl<'a> ExternKind<'a> {
 
 struct Interface<'a> {
     docs: Docs<'a>,
+    attributes: Vec<Attribute<'a>>,
     name: Id<'a>,
     items: Vec<InterfaceItem<'a>>,
 }
 
 impl<'a> Interface<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Interface)?;
         let name = parse_id(tokens)?;
         let items = Self::parse_items(tokens)?;
-        Ok(Interface { docs, name, items })
+        Ok(Interface {
+            docs,
+            attributes,
+            name,
+            items,
+        })
     }
 
     pub(super) fn parse_items(tokens: &mut Tokenizer<'a>) -> Result<Vec<InterfaceItem<'a>>> {
@@ -360,7 +427,8 @@ impl<'a> Interface<'a> {
             if tokens.eat(Token::RightBrace)? {
                 break;
             }
-            items.push(InterfaceItem::parse(tokens, docs)?);
+            let attributes = Attribute::parse_list(tokens)?;
+            items.push(InterfaceItem::parse(tokens, docs, attributes)?);
         }
         Ok(items)
     }
@@ -525,8 +593,101 @@ impl<'a> Default for Docs<'a> {
     }
 }
 
+/// A stability attribute such as `@since(version = 1.0.0)` or
+/// `@unstable(feature = foo)` preceding an item.
+enum Attribute<'a> {
+    Since {
+        span: Span,
+        version: Version,
+        feature: Option<Id<'a>>,
+    },
+    Unstable {
+        span: Span,
+        feature: Id<'a>,
+    },
+}
+
+impl<'a> Attribute<'a> {
+    fn parse_list(tokens: &mut Tokenizer<'a>) -> Result<Vec<Attribute<'a>>> {
+        let mut ret = Vec::new();
+        while tokens.eat(Token::At)? {
+            let id = parse_id(tokens)?;
+            let attr = match id.name {
+                "since" => {
+                    tokens.expect(Token::LeftParen)?;
+                    eat_id(tokens, "version")?;
+                    tokens.expect(Token::Equals)?;
+                    let (_span, version) = parse_version(tokens)?;
+                    let feature = if tokens.eat(Token::Comma)? {
+                        eat_id(tokens, "feature")?;
+                        tokens.expect(Token::Equals)?;
+                        Some(parse_id(tokens)?)
+                    } else {
+                        None
+                    };
+                    tokens.expect(Token::RightParen)?;
+                    Attribute::Since {
+                        span: id.span,
+                        version,
+                        feature,
+                    }
+                }
+                "unstable" => {
+                    tokens.expect(Token::LeftParen)?;
+                    eat_id(tokens, "feature")?;
+                    tokens.expect(Token::Equals)?;
+                    let feature = parse_id(tokens)?;
+                    tokens.expect(Token::RightParen)?;
+                    Attribute::Unstable {
+                        span: id.span,
+                        feature,
+                    }
+                }
+                other => {
+                    bail!(Error {
+                        span: id.span,
+                        msg: format!("unknown attribute `{other}`"),
+                    })
+                }
+            };
+            ret.push(attr);
+        }
+        Ok(ret)
+    }
+
+    fn span(&self) -> Span {
+        match self {
+            Attribute::Since { span, .. } | Attribute::Unstable { span, .. } => *span,
+        }
+    }
+
+    /// Returns an error if any attributes were specified for an item which
+    /// doesn't support them.
+    fn deny(attributes: &[Attribute<'a>], what: &str) -> Result<()> {
+        match attributes.first() {
+            Some(attr) => bail!(Error {
+                span: attr.span(),
+                msg: format!("attributes are not supported on {what}"),
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+fn eat_id(tokens: &mut Tokenizer<'_>, expected: &str) -> Result<Span> {
+    let id = parse_id(tokens)?;
+    if id.name != expected {
+        bail!(Error {
+            span: id.span,
+            msg: format!("expected `{expected}`, found `{}`", id.name),
+        });
+    }
+    Ok(id.span)
+}
+
 struct TypeDef<'a> {
     docs: Docs<'a>,
+    attributes: Vec<Attribute<'a>>,
     name: Id<'a>,
     ty: Type<'a>,
 }
@@ -576,7 +737,11 @@ enum ResourceFunc<'a> {
 }
 
 impl<'a> ResourceFunc<'a> {
-    fn parse(docs: Docs<'a>, tokens: &mut Tokenizer<'a>) -> Result<Self> {
+    fn parse(
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+        tokens: &mut Tokenizer<'a>,
+    ) -> Result<Self> {
         match tokens.clone().next()? {
             Some((span, Token::Constructor)) => {
                 tokens.expect(Token::Constructor)?;
@@ -590,6 +755,7 @@ impl<'a> ResourceFunc<'a> {
                 tokens.expect_semicolon()?;
                 Ok(ResourceFunc::Constructor(NamedFunc {
                     docs,
+                    attributes,
                     name: Id {
                         span,
                         name: "constructor",
@@ -610,7 +776,12 @@ impl<'a> ResourceFunc<'a> {
                 };
                 let func = Func::parse(tokens)?;
                 tokens.expect_semicolon()?;
-                Ok(ctor(NamedFunc { docs, name, func }))
+                Ok(ctor(NamedFunc {
+                    docs,
+                    attributes,
+                    name,
+                    func,
+                }))
             }
             other => Err(err_expected(tokens, "`constructor` or identifier", other).into()),
         }
@@ -676,6 +847,7 @@ struct Stream<'a> {
 
 struct NamedFunc<'a> {
     docs: Docs<'a>,
+    attributes: Vec<Attribute<'a>>,
     name: Id<'a>,
     func: Func<'a>,
 }
@@ -726,44 +898,66 @@ impl<'a> Func<'a> {
 }
 
 impl<'a> InterfaceItem<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<InterfaceItem<'a>> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<InterfaceItem<'a>> {
         match tokens.clone().next()? {
-            Some((_span, Token::Type)) => TypeDef::parse(tokens, docs).map(InterfaceItem::TypeDef),
+            Some((_span, Token::Type)) => {
+                TypeDef::parse(tokens, docs, attributes).map(InterfaceItem::TypeDef)
+            }
             Some((_span, Token::Flags)) => {
-                TypeDef::parse_flags(tokens, docs).map(InterfaceItem::TypeDef)
+                TypeDef::parse_flags(tokens, docs, attributes).map(InterfaceItem::TypeDef)
             }
             Some((_span, Token::Enum)) => {
-                TypeDef::parse_enum(tokens, docs).map(InterfaceItem::TypeDef)
+                TypeDef::parse_enum(tokens, docs, attributes).map(InterfaceItem::TypeDef)
             }
             Some((_span, Token::Variant)) => {
-                TypeDef::parse_variant(tokens, docs).map(InterfaceItem::TypeDef)
+                TypeDef::parse_variant(tokens, docs, attributes).map(InterfaceItem::TypeDef)
             }
             Some((_span, Token::Resource)) => {
-                TypeDef::parse_resource(tokens, docs).map(InterfaceItem::TypeDef)
+                TypeDef::parse_resource(tokens, docs, attributes).map(InterfaceItem::TypeDef)
             }
             Some((_span, Token::Record)) => {
-                TypeDef::parse_record(tokens, docs).map(InterfaceItem::TypeDef)
+                TypeDef::parse_record(tokens, docs, attributes).map(InterfaceItem::TypeDef)
             }
             Some((_span, Token::Id)) | Some((_span, Token::ExplicitId)) => {
-                NamedFunc::parse(tokens, docs).map(InterfaceItem::Func)
+                NamedFunc::parse(tokens, docs, attributes).map(InterfaceItem::Func)
+            }
+            Some((_span, Token::Use)) => {
+                Attribute::deny(&attributes, "`use` statements")?;
+                Use::parse(tokens).map(InterfaceItem::Use)
             }
-            Some((_span, Token::Use)) => Use::parse(tokens).map(InterfaceItem::Use),
             other => Err(err_expected(tokens, "`type`, `resource` or `func`", other).into()),
         }
     }
 }
 
 impl<'a> TypeDef<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Type)?;
         let name = parse_id(tokens)?;
         tokens.expect(Token::Equals)?;
         let ty = Type::parse(tokens)?;
         tokens.expect_semicolon()?;
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 
-    fn parse_flags(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse_flags(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Flags)?;
         let name = parse_id(tokens)?;
         let ty = Type::Flags(Flags {
@@ -777,25 +971,45 @@ impl<'a> TypeDef<'a> {
                 },
             )?,
         });
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 
-    fn parse_resource(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse_resource(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Resource)?;
         let name = parse_id(tokens)?;
         let mut funcs = Vec::new();
         if tokens.eat(Token::LeftBrace)? {
             while !tokens.eat(Token::RightBrace)? {
-                funcs.push(ResourceFunc::parse(parse_docs(tokens)?, tokens)?);
+                let docs = parse_docs(tokens)?;
+                let attributes = Attribute::parse_list(tokens)?;
+                funcs.push(ResourceFunc::parse(docs, attributes, tokens)?);
             }
         } else {
             tokens.expect_semicolon()?;
         }
         let ty = Type::Resource(Resource { funcs });
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 
-    fn parse_record(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse_record(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Record)?;
         let name = parse_id(tokens)?;
         let ty = Type::Record(Record {
@@ -811,10 +1025,19 @@ impl<'a> TypeDef<'a> {
                 },
             )?,
         });
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 
-    fn parse_variant(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse_variant(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Variant)?;
         let name = parse_id(tokens)?;
         let ty = Type::Variant(Variant {
@@ -836,10 +1059,19 @@ impl<'a> TypeDef<'a> {
                 },
             )?,
         });
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 
-    fn parse_enum(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse_enum(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         tokens.expect(Token::Enum)?;
         let name = parse_id(tokens)?;
         let ty = Type::Enum(Enum {
@@ -854,17 +1086,31 @@ impl<'a> TypeDef<'a> {
                 },
             )?,
         });
-        Ok(TypeDef { docs, name, ty })
+        Ok(TypeDef {
+            docs,
+            attributes,
+            name,
+            ty,
+        })
     }
 }
 
 impl<'a> NamedFunc<'a> {
-    fn parse(tokens: &mut Tokenizer<'a>, docs: Docs<'a>) -> Result<Self> {
+    fn parse(
+        tokens: &mut Tokenizer<'a>,
+        docs: Docs<'a>,
+        attributes: Vec<Attribute<'a>>,
+    ) -> Result<Self> {
         let name = parse_id(tokens)?;
         tokens.expect(Token::Colon)?;
         let func = Func::parse(tokens)?;
         tokens.expect_semicolon()?;
-        Ok(NamedFunc { docs, name, func })
+        Ok(NamedFunc {
+            docs,
+            attributes,
+            name,
+            func,
+        })
     }
 }
 
@@ -886,6 +1132,10 @@ fn parse_opt_version(tokens: &mut Tokenizer<'_>) -> Result<Option<(Span, Version
     if !tokens.eat(Token::At)? {
         return Ok(None);
     }
+    parse_version(tokens).map(Some)
+}
+
+fn parse_version(tokens: &mut Tokenizer<'_>) -> Result<(Span, Version)> {
     let start = tokens.expect(Token::Integer)?.start;
     tokens.expect(Token::Period)?;
     tokens.expect(Token::Integer)?;
@@ -899,7 +1149,7 @@ fn parse_opt_version(tokens: &mut Tokenizer<'_>) -> Result<Option<(Span, Version
         span,
         msg: e.to_string(),
     })?;
-    return Ok(Some((span, version)));
+    return Ok((span, version));
 
     fn eat_ids(tokens: &mut Tokenizer<'_>, prefix: Token, end: &mut Span) -> Result<()> {
         if !tokens.eat(prefix)? {
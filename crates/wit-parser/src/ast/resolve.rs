@@ -1,4 +1,4 @@
-use super::{Error, ParamList, ResultList, WorldOrInterface};
+use super::{Attribute, Error, ParamList, ResultList, WorldOrInterface};
 use crate::ast::toposort::toposort;
 use crate::*;
 use anyhow::{bail, Result};
@@ -194,7 +194,7 @@ impl<'a> Resolver<'a> {
         for id in iface_order {
             let (interface, i) = &iface_id_to_ast[&id];
             self.cur_ast_index = *i;
-            self.resolve_interface(id, &interface.items, &interface.docs)?;
+            self.resolve_interface(id, &interface.items, &interface.docs, &interface.attributes)?;
         }
 
         for id in world_order {
@@ -297,6 +297,7 @@ impl<'a> Resolver<'a> {
             docs: Docs::default(),
             functions: IndexMap::new(),
             package: None,
+            stability: Default::default(),
         })
     }
 
@@ -551,6 +552,7 @@ impl<'a> Resolver<'a> {
                         kind: TypeDefKind::Unknown,
                         name: Some(name.name.name.to_string()),
                         owner: TypeOwner::Interface(iface),
+                        stability: Default::default(),
                     });
                     self.unknown_type_spans.push(name.name.span);
                     lookup.insert(name.name.name, (TypeOrItem::Type(id), name.name.span));
@@ -617,9 +619,10 @@ impl<'a> Resolver<'a> {
         let mut imported_interfaces = HashSet::new();
         let mut exported_interfaces = HashSet::new();
         for item in world.items.iter() {
-            let (docs, kind, desc, spans, interfaces) = match item {
+            let (docs, attrs, kind, desc, spans, interfaces) = match item {
                 ast::WorldItem::Import(import) => (
                     &import.docs,
+                    &import.attributes,
                     &import.kind,
                     "import",
                     &mut import_spans,
@@ -627,6 +630,7 @@ impl<'a> Resolver<'a> {
                 ),
                 ast::WorldItem::Export(export) => (
                     &export.docs,
+                    &export.attributes,
                     &export.kind,
                     "export",
                     &mut export_spans,
@@ -678,7 +682,7 @@ impl<'a> Resolver<'a> {
                     WorldKey::Interface(id)
                 }
             };
-            let world_item = self.resolve_world_item(docs, kind)?;
+            let world_item = self.resolve_world_item(docs, attrs, kind)?;
             if let WorldItem::Interface(id) = world_item {
                 if !interfaces.insert(id) {
                     bail!(Error {
@@ -705,13 +709,14 @@ impl<'a> Resolver<'a> {
     fn resolve_world_item(
         &mut self,
         docs: &ast::Docs<'a>,
+        attrs: &[Attribute<'a>],
         kind: &ast::ExternKind<'a>,
     ) -> Result<WorldItem> {
         match kind {
             ast::ExternKind::Interface(name, items) => {
                 let prev = mem::take(&mut self.type_lookup);
                 let id = self.alloc_interface(name.span);
-                self.resolve_interface(id, items, docs)?;
+                self.resolve_interface(id, items, docs, attrs)?;
                 self.type_lookup = prev;
                 Ok(WorldItem::Interface(id))
             }
@@ -721,8 +726,13 @@ impl<'a> Resolver<'a> {
                 Ok(WorldItem::Interface(id))
             }
             ast::ExternKind::Func(name, func) => {
-                let func =
-                    self.resolve_function(docs, name.name, func, FunctionKind::Freestanding)?;
+                let func = self.resolve_function(
+                    docs,
+                    attrs,
+                    name.name,
+                    func,
+                    FunctionKind::Freestanding,
+                )?;
                 Ok(WorldItem::Function(func))
             }
         }
@@ -733,9 +743,12 @@ impl<'a> Resolver<'a> {
         interface_id: InterfaceId,
         fields: &[ast::InterfaceItem<'a>],
         docs: &ast::Docs<'a>,
+        attrs: &[Attribute<'a>],
     ) -> Result<()> {
         let docs = self.docs(docs);
         self.interfaces[interface_id].docs = docs;
+        let stability = self.stability(attrs)?;
+        self.interfaces[interface_id].stability = stability;
 
         self.resolve_types(
             TypeOwner::Interface(interface_id),
@@ -766,6 +779,7 @@ impl<'a> Resolver<'a> {
                     self.define_interface_name(&f.name, TypeOrItem::Item("function"))?;
                     funcs.push(self.resolve_function(
                         &f.docs,
+                        &f.attributes,
                         &f.name.name,
                         &f.func,
                         FunctionKind::Freestanding,
@@ -854,12 +868,14 @@ impl<'a> Resolver<'a> {
                 None => continue,
             };
             let docs = self.docs(&def.docs);
+            let stability = self.stability(&def.attributes)?;
             let kind = self.resolve_type_def(&def.ty)?;
             let id = self.types.alloc(TypeDef {
                 docs,
                 kind,
                 name: Some(def.name.name.to_string()),
                 owner,
+                stability,
             });
             self.define_interface_name(&def.name, TypeOrItem::Type(id))?;
         }
@@ -891,6 +907,7 @@ impl<'a> Resolver<'a> {
                 kind: TypeDefKind::Type(Type::Id(id)),
                 name: Some(name.name.to_string()),
                 owner,
+                stability: Default::default(),
             });
             self.define_interface_name(name, TypeOrItem::Type(id))?;
         }
@@ -944,21 +961,30 @@ impl<'a> Resolver<'a> {
             }
         }
         let named_func = func.named_func();
-        self.resolve_function(&named_func.docs, &name, &named_func.func, kind)
+        self.resolve_function(
+            &named_func.docs,
+            &named_func.attributes,
+            &name,
+            &named_func.func,
+            kind,
+        )
     }
 
     fn resolve_function(
         &mut self,
         docs: &ast::Docs<'_>,
+        attrs: &[Attribute<'_>],
         name: &str,
         func: &ast::Func,
         kind: FunctionKind,
     ) -> Result<Function> {
         let docs = self.docs(docs);
+        let stability = self.stability(attrs)?;
         let params = self.resolve_params(&func.params, &kind)?;
         let results = self.resolve_results(&func.results, &kind)?;
         Ok(Function {
             docs,
+            stability,
             name: name.to_string(),
             kind,
             params,
@@ -1223,6 +1249,7 @@ impl<'a> Resolver<'a> {
             name: None,
             docs: Docs::default(),
             owner: TypeOwner::None,
+            stability: Default::default(),
         }))
     }
 
@@ -1294,6 +1321,25 @@ impl<'a> Resolver<'a> {
         Docs { contents }
     }
 
+    fn stability(&mut self, attrs: &[Attribute<'_>]) -> Result<Stability> {
+        match attrs {
+            [] => Ok(Stability::Unknown),
+            [Attribute::Since {
+                version, feature, ..
+            }] => Ok(Stability::Stable {
+                since: version.clone(),
+                feature: feature.as_ref().map(|f| f.name.to_string()),
+            }),
+            [Attribute::Unstable { feature, .. }] => Ok(Stability::Unstable {
+                feature: feature.name.to_string(),
+            }),
+            [_, other, ..] => bail!(Error {
+                span: other.span(),
+                msg: "only one stability attribute may be specified".to_string(),
+            }),
+        }
+    }
+
     fn resolve_params(&mut self, params: &ParamList<'_>, kind: &FunctionKind) -> Result<Params> {
         let mut ret = IndexMap::new();
         match *kind {
@@ -1310,6 +1356,7 @@ impl<'a> Resolver<'a> {
                     kind: TypeDefKind::Handle(Handle::Borrow(id)),
                     name: None,
                     owner: TypeOwner::None,
+                    stability: Default::default(),
                 });
                 ret.insert("self".to_string(), shared);
             }
@@ -1,3 +1,5 @@
+use crate::docs::{PackageDocs, PACKAGE_DOCS_SECTION_NAME};
+use crate::*;
 use anyhow::{anyhow, bail, Context, Result};
 use indexmap::{IndexMap, IndexSet};
 use std::collections::HashMap;
@@ -7,9 +9,6 @@ use wasmparser::{
     types, ComponentExport, ComponentExternalKind, ComponentImport, Parser, Payload,
     PrimitiveValType, ValidPayload, Validator, WasmFeatures,
 };
-use wit_parser::*;
-
-use crate::encoding::docs::{PackageDocs, PACKAGE_DOCS_SECTION_NAME};
 
 /// Represents information about a decoded WebAssembly component.
 struct ComponentInfo<'a> {
@@ -136,7 +135,7 @@ impl<'a> ComponentInfo<'a> {
             if pkg.is_some() {
                 bail!("more than one top-level exported component type found");
             }
-            let name = ComponentName::new(*name, 0).unwrap();
+            let name = ComponentName::new(name, 0).unwrap();
             pkg = Some(
                 decoder
                     .decode_v1_package(&name, ty)
@@ -351,7 +350,7 @@ pub fn decode(bytes: &[u8]) -> Result<DecodedWasm> {
 /// itself imports nothing and exports a single component, and the single
 /// component export represents the world. The name of the export is also the
 /// name of the package/world/etc.
-pub(crate) fn decode_world(
+pub fn decode_world(
     types: &types::Types,
     world: types::ComponentTypeId,
 ) -> Result<(Resolve, WorldId)> {
@@ -361,7 +360,7 @@ pub(crate) fn decode_world(
     let ty = &types[world];
     assert_eq!(ty.imports.len(), 0);
     assert_eq!(ty.exports.len(), 1);
-    let name = ty.exports.keys().nth(0).unwrap();
+    let name = ty.exports.keys().next().unwrap();
     let ty = match ty.exports[0] {
         types::ComponentEntityType::Component(ty) => ty,
         _ => unreachable!(),
@@ -781,6 +780,7 @@ impl WitPackageDecoder<'_> {
                     types: IndexMap::default(),
                     functions: IndexMap::new(),
                     package: None,
+                    stability: Default::default(),
                 })
             });
 
@@ -835,6 +835,7 @@ impl WitPackageDecoder<'_> {
             types: IndexMap::default(),
             functions: IndexMap::new(),
             package: None,
+            stability: Default::default(),
         };
 
         let owner = TypeOwner::Interface(self.resolve.interfaces.next_id());
@@ -929,6 +930,7 @@ impl WitPackageDecoder<'_> {
             name: Some(name.to_string()),
             kind,
             docs: Default::default(),
+            stability: Default::default(),
             owner,
         });
 
@@ -939,7 +941,7 @@ impl WitPackageDecoder<'_> {
             let prev = self
                 .resources
                 .entry(owner)
-                .or_insert(HashMap::new())
+                .or_default()
                 .insert(name.to_string(), ty);
             assert!(prev.is_none());
         }
@@ -1075,6 +1077,7 @@ impl WitPackageDecoder<'_> {
         };
         Ok(Function {
             docs: Default::default(),
+            stability: Default::default(),
             kind: match name.kind() {
                 ComponentNameKind::Label(_) => FunctionKind::Freestanding,
                 ComponentNameKind::Constructor(resource) => {
@@ -1128,15 +1131,15 @@ impl WitPackageDecoder<'_> {
             | TypeDefKind::Tuple(_)
             | TypeDefKind::Option(_)
             | TypeDefKind::Result(_)
-            | TypeDefKind::Handle(_) => {}
+            | TypeDefKind::Handle(_)
+            | TypeDefKind::Future(_)
+            | TypeDefKind::Stream(_) => {}
 
             TypeDefKind::Resource
             | TypeDefKind::Record(_)
             | TypeDefKind::Enum(_)
             | TypeDefKind::Variant(_)
-            | TypeDefKind::Flags(_)
-            | TypeDefKind::Future(_)
-            | TypeDefKind::Stream(_) => {
+            | TypeDefKind::Flags(_) => {
                 bail!("unexpected unnamed type of kind '{}'", kind.as_str());
             }
             TypeDefKind::Unknown => unreachable!(),
@@ -1144,6 +1147,7 @@ impl WitPackageDecoder<'_> {
         let ty = self.resolve.types.alloc(TypeDef {
             name: None,
             docs: Default::default(),
+            stability: Default::default(),
             owner: TypeOwner::None,
             kind,
         });
@@ -1262,6 +1266,26 @@ impl WitPackageDecoder<'_> {
                 let id = self.type_map[&(*id).into()];
                 Ok(TypeDefKind::Handle(Handle::Borrow(id)))
             }
+
+            types::ComponentDefinedType::Future(ty) => {
+                let ty = match ty {
+                    Some(t) => Some(self.convert_valtype(t)?),
+                    None => None,
+                };
+                Ok(TypeDefKind::Future(ty))
+            }
+
+            types::ComponentDefinedType::Stream { element, end } => {
+                let element = match element {
+                    Some(t) => Some(self.convert_valtype(t)?),
+                    None => None,
+                };
+                let end = match end {
+                    Some(t) => Some(self.convert_valtype(t)?),
+                    None => None,
+                };
+                Ok(TypeDefKind::Stream(Stream { element, end }))
+            }
         }
     }
 
@@ -1285,7 +1309,7 @@ impl WitPackageDecoder<'_> {
 
     fn register_defined(&mut self, id: TypeId, def: &types::ComponentDefinedType) -> Result<()> {
         Registrar {
-            types: &self.types,
+            types: self.types,
             type_map: &mut self.type_map,
             resolve: &self.resolve,
         }
@@ -1492,6 +1516,38 @@ impl Registrar<'_> {
                 Ok(())
             }
 
+            types::ComponentDefinedType::Future(payload) => {
+                let ty = match &self.resolve.types[id].kind {
+                    TypeDefKind::Future(t) => t,
+                    TypeDefKind::Type(Type::Id(_)) => return Ok(()),
+                    _ => bail!("expected a future"),
+                };
+                match (payload, ty) {
+                    (Some(a), Some(b)) => self.valtype(a, b),
+                    (None, None) => Ok(()),
+                    _ => bail!("disagreement on future structure"),
+                }
+            }
+
+            types::ComponentDefinedType::Stream { element, end } => {
+                let ty = match &self.resolve.types[id].kind {
+                    TypeDefKind::Stream(s) => s,
+                    TypeDefKind::Type(Type::Id(_)) => return Ok(()),
+                    _ => bail!("expected a stream"),
+                };
+                match (element, &ty.element) {
+                    (Some(a), Some(b)) => self.valtype(a, b)?,
+                    (None, None) => {}
+                    _ => bail!("disagreement on stream structure"),
+                }
+                match (end, &ty.end) {
+                    (Some(a), Some(b)) => self.valtype(a, b)?,
+                    (None, None) => {}
+                    _ => bail!("disagreement on stream structure"),
+                }
+                Ok(())
+            }
+
             types::ComponentDefinedType::Record(def) => {
                 let ty = match &self.resolve.types[id].kind {
                     TypeDefKind::Record(r) => r,
@@ -1646,7 +1702,7 @@ impl Registrar<'_> {
     }
 }
 
-pub(crate) trait InterfaceNameExt {
+trait InterfaceNameExt {
     fn to_package_name(&self) -> PackageName;
 }
 
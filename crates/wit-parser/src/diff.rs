@@ -0,0 +1,701 @@
+use crate::{
+    Function, FunctionKind, Handle, InterfaceId, PackageId, Params, Resolve, Results, Type,
+    TypeDefKind, TypeId, TypeOwner, WorldId, WorldItem, WorldKey,
+};
+use indexmap::IndexMap;
+use std::fmt;
+
+/// A single difference found between two versions of a WIT package.
+///
+/// Changes are produced by [`Resolve::diff_packages`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Change {
+    /// Whether the item was added, removed, or changed.
+    pub kind: ChangeKind,
+    /// The kind of item which changed.
+    pub item: ItemKind,
+    /// Dotted path to the item which changed, such as `types.point.x` for the
+    /// field `x` of the record `point` in the interface `types`.
+    pub path: String,
+    /// Whether this change is backwards-compatible.
+    pub compatibility: Compatibility,
+}
+
+impl Change {
+    /// Returns whether this change breaks users of the old package.
+    pub fn is_breaking(&self) -> bool {
+        self.compatibility == Compatibility::Breaking
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {} `{}`",
+            self.compatibility.as_str(),
+            self.kind.as_str(),
+            self.item.as_str(),
+            self.path
+        )
+    }
+}
+
+/// How an item differs between two versions of a package.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The item is only present in the new version.
+    Added,
+    /// The item is only present in the old version.
+    Removed,
+    /// The item is present in both versions but its definition differs.
+    Changed,
+}
+
+impl ChangeKind {
+    /// Returns the name of this kind of change as it's printed in reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "added",
+            ChangeKind::Removed => "removed",
+            ChangeKind::Changed => "changed",
+        }
+    }
+}
+
+/// The kind of item described by a [`Change`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemKind {
+    /// An interface of the package.
+    Interface,
+    /// A world of the package.
+    World,
+    /// An import of a world.
+    Import,
+    /// An export of a world.
+    Export,
+    /// A named type defined in an interface or world.
+    Type,
+    /// A freestanding function of an interface or world.
+    Function,
+    /// A method, static method, or constructor of a resource.
+    Method,
+    /// A parameter of a function.
+    Param,
+    /// The result, or one of the named results, of a function.
+    Result,
+    /// A field of a record.
+    Field,
+    /// A case of a variant or enum.
+    Case,
+    /// A flag of a flags type.
+    Flag,
+}
+
+impl ItemKind {
+    /// Returns the name of this kind of item as it's printed in reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ItemKind::Interface => "interface",
+            ItemKind::World => "world",
+            ItemKind::Import => "import",
+            ItemKind::Export => "export",
+            ItemKind::Type => "type",
+            ItemKind::Function => "function",
+            ItemKind::Method => "method",
+            ItemKind::Param => "param",
+            ItemKind::Result => "result",
+            ItemKind::Field => "field",
+            ItemKind::Case => "case",
+            ItemKind::Flag => "flag",
+        }
+    }
+}
+
+/// Whether a [`Change`] is backwards-compatible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compatibility {
+    /// Users of the old version of the package are unaffected.
+    Compatible,
+    /// Users of the old version of the package may no longer work with the
+    /// new version.
+    Breaking,
+}
+
+impl Compatibility {
+    /// Returns the name of this compatibility as it's printed in reports.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Compatibility::Compatible => "compatible",
+            Compatibility::Breaking => "breaking",
+        }
+    }
+}
+
+impl Resolve {
+    /// Computes the semantic differences between the package `old` in this
+    /// `Resolve` and the package `new` in `new_resolve`.
+    ///
+    /// Interfaces, worlds, types, and functions are matched up by name. Adding
+    /// one of these items is considered backwards-compatible while removing
+    /// or modifying one is considered breaking. Adding a param, record field,
+    /// variant case, or flag modifies the enclosing definition and is
+    /// therefore breaking as well.
+    ///
+    /// Named types are compared by name, and differences within dependencies
+    /// of the packages are not reported. Package versions are ignored
+    /// throughout so two releases of the same package can be compared.
+    pub fn diff_packages(
+        &self,
+        old: PackageId,
+        new_resolve: &Resolve,
+        new: PackageId,
+    ) -> Vec<Change> {
+        let mut differ = Differ {
+            old: self,
+            new: new_resolve,
+            changes: Vec::new(),
+        };
+        let old = &self.packages[old];
+        let new = &new_resolve.packages[new];
+        differ.diff_by_name(
+            "",
+            ItemKind::Interface,
+            &old.interfaces,
+            &new.interfaces,
+            |d, path, a, b| d.diff_interface(path, *a, *b),
+        );
+        differ.diff_by_name(
+            "",
+            ItemKind::World,
+            &old.worlds,
+            &new.worlds,
+            |d, path, a, b| d.diff_world(path, *a, *b),
+        );
+        differ.changes
+    }
+}
+
+struct Differ<'a> {
+    old: &'a Resolve,
+    new: &'a Resolve,
+    changes: Vec<Change>,
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+impl Differ<'_> {
+    fn push(&mut self, kind: ChangeKind, item: ItemKind, path: String) {
+        let compatibility = match kind {
+            ChangeKind::Added => Compatibility::Compatible,
+            ChangeKind::Removed | ChangeKind::Changed => Compatibility::Breaking,
+        };
+        self.push_with(kind, item, path, compatibility);
+    }
+
+    fn push_with(
+        &mut self,
+        kind: ChangeKind,
+        item: ItemKind,
+        path: String,
+        compatibility: Compatibility,
+    ) {
+        self.changes.push(Change {
+            kind,
+            item,
+            path,
+            compatibility,
+        });
+    }
+
+    /// Matches up the items of `old` and `new` by name, recording removed and
+    /// added items and calling `diff` on items present in both.
+    fn diff_by_name<T>(
+        &mut self,
+        path: &str,
+        item: ItemKind,
+        old: &IndexMap<String, T>,
+        new: &IndexMap<String, T>,
+        mut diff: impl FnMut(&mut Self, String, &T, &T),
+    ) {
+        for (name, a) in old {
+            match new.get(name) {
+                Some(b) => diff(self, join(path, name), a, b),
+                None => self.push(ChangeKind::Removed, item, join(path, name)),
+            }
+        }
+        for name in new.keys() {
+            if !old.contains_key(name) {
+                self.push(ChangeKind::Added, item, join(path, name));
+            }
+        }
+    }
+
+    /// Same as `diff_by_name` but for ordered lists of items where a
+    /// difference in order alone is reported as a change to the enclosing
+    /// item at `path`.
+    fn diff_ordered<T>(
+        &mut self,
+        path: &str,
+        parent: ItemKind,
+        item: ItemKind,
+        old: &[(String, T)],
+        new: &[(String, T)],
+        mut eq: impl FnMut(&Self, &T, &T) -> bool,
+    ) {
+        let before = self.changes.len();
+        for (name, a) in old {
+            match new.iter().find(|(n, _)| n == name) {
+                Some((_, b)) if eq(self, a, b) => {}
+                Some(_) => self.push(ChangeKind::Changed, item, join(path, name)),
+                None => self.push(ChangeKind::Removed, item, join(path, name)),
+            }
+        }
+        // Unlike other additions these modify the enclosing item, so they're
+        // breaking.
+        for (name, _) in new {
+            if !old.iter().any(|(n, _)| n == name) {
+                self.push_with(
+                    ChangeKind::Added,
+                    item,
+                    join(path, name),
+                    Compatibility::Breaking,
+                );
+            }
+        }
+        // Positions are significant in the canonical ABI, so reordering items
+        // is a change as well.
+        if self.changes.len() == before && old.iter().zip(new).any(|((a, _), (b, _))| a != b) {
+            self.push(ChangeKind::Changed, parent, path.to_string());
+        }
+    }
+
+    fn diff_interface(&mut self, path: String, old: InterfaceId, new: InterfaceId) {
+        let old = &self.old.interfaces[old];
+        let new = &self.new.interfaces[new];
+        self.diff_by_name(
+            &path,
+            ItemKind::Type,
+            &old.types,
+            &new.types,
+            |d, path, a, b| d.diff_type(path, *a, *b),
+        );
+        self.diff_functions(&path, &old.functions, &new.functions);
+    }
+
+    fn diff_functions(
+        &mut self,
+        path: &str,
+        old: &IndexMap<String, Function>,
+        new: &IndexMap<String, Function>,
+    ) {
+        let func_path = |resolve: &Resolve, func: &Function| match &func.kind {
+            FunctionKind::Freestanding => (ItemKind::Function, join(path, &func.name)),
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                let resource = resolve.types[*id].name.as_deref().unwrap_or("<unnamed>");
+                (
+                    ItemKind::Method,
+                    join(&join(path, resource), func.item_name()),
+                )
+            }
+        };
+        for (name, a) in old {
+            let (item, path) = func_path(self.old, a);
+            match new.get(name) {
+                Some(b) => self.diff_function(item, path, a, b),
+                None => self.push(ChangeKind::Removed, item, path),
+            }
+        }
+        for (name, b) in new {
+            if !old.contains_key(name) {
+                let (item, path) = func_path(self.new, b);
+                self.push(ChangeKind::Added, item, path);
+            }
+        }
+    }
+
+    fn diff_function(&mut self, item: ItemKind, path: String, old: &Function, new: &Function) {
+        self.diff_params(&path, item, ItemKind::Param, &old.params, &new.params);
+        match (&old.results, &new.results) {
+            (Results::Anon(a), Results::Anon(b)) => {
+                if !self.type_eq(a, b) {
+                    self.push(ChangeKind::Changed, ItemKind::Result, path);
+                }
+            }
+            (Results::Named(a), Results::Named(b)) => {
+                self.diff_params(&path, item, ItemKind::Result, a, b);
+            }
+            _ => self.push(ChangeKind::Changed, ItemKind::Result, path),
+        }
+    }
+
+    fn diff_params(
+        &mut self,
+        path: &str,
+        parent: ItemKind,
+        item: ItemKind,
+        old: &Params,
+        new: &Params,
+    ) {
+        self.diff_ordered(path, parent, item, old, new, |d, a, b| d.type_eq(a, b));
+    }
+
+    fn diff_world(&mut self, path: String, old: WorldId, new: WorldId) {
+        let old = &self.old.worlds[old];
+        let new = &self.new.worlds[new];
+        for (item, old_items, new_items) in [
+            (ItemKind::Import, &old.imports, &new.imports),
+            (ItemKind::Export, &old.exports, &new.exports),
+        ] {
+            let old_items = self.world_items(self.old, old_items);
+            let new_items = self.world_items(self.new, new_items);
+            self.diff_by_name(&path, item, &old_items, &new_items, |d, path, a, b| {
+                d.diff_world_item(item, path, a, b)
+            });
+        }
+    }
+
+    /// Keys the items of a world by name, using the unversioned name of the
+    /// interface for interfaces which aren't assigned a kebab-name.
+    fn world_items<'b>(
+        &self,
+        resolve: &Resolve,
+        items: &'b IndexMap<WorldKey, WorldItem>,
+    ) -> IndexMap<String, &'b WorldItem> {
+        items
+            .iter()
+            .map(|(key, item)| {
+                let name = match key {
+                    WorldKey::Name(name) => name.clone(),
+                    WorldKey::Interface(id) => interface_name(resolve, *id),
+                };
+                (name, item)
+            })
+            .collect()
+    }
+
+    fn diff_world_item(&mut self, item: ItemKind, path: String, old: &WorldItem, new: &WorldItem) {
+        match (old, new) {
+            (WorldItem::Interface(a), WorldItem::Interface(b)) => {
+                let named = self.old.interfaces[*a].name.is_some();
+                if named != self.new.interfaces[*b].name.is_some() {
+                    self.push(ChangeKind::Changed, item, path);
+                } else if !named {
+                    // Named interfaces are diffed on their own as part of
+                    // their package, so only inline interfaces are diffed
+                    // here.
+                    self.diff_interface(path, *a, *b);
+                }
+            }
+            (WorldItem::Function(a), WorldItem::Function(b)) => {
+                self.diff_function(ItemKind::Function, path, a, b);
+            }
+            (WorldItem::Type(a), WorldItem::Type(b)) => self.diff_type(path, *a, *b),
+            _ => self.push(ChangeKind::Changed, item, path),
+        }
+    }
+
+    fn diff_type(&mut self, path: String, old: TypeId, new: TypeId) {
+        let a = &self.old.types[old].kind;
+        let b = &self.new.types[new].kind;
+        match (a, b) {
+            (TypeDefKind::Record(a), TypeDefKind::Record(b)) => {
+                let a = a
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.clone(), f.ty))
+                    .collect::<Vec<_>>();
+                let b = b
+                    .fields
+                    .iter()
+                    .map(|f| (f.name.clone(), f.ty))
+                    .collect::<Vec<_>>();
+                self.diff_ordered(&path, ItemKind::Type, ItemKind::Field, &a, &b, |d, a, b| {
+                    d.type_eq(a, b)
+                });
+            }
+            (TypeDefKind::Variant(a), TypeDefKind::Variant(b)) => {
+                let a = a
+                    .cases
+                    .iter()
+                    .map(|c| (c.name.clone(), c.ty))
+                    .collect::<Vec<_>>();
+                let b = b
+                    .cases
+                    .iter()
+                    .map(|c| (c.name.clone(), c.ty))
+                    .collect::<Vec<_>>();
+                self.diff_ordered(&path, ItemKind::Type, ItemKind::Case, &a, &b, |d, a, b| {
+                    d.optional_type_eq(a.as_ref(), b.as_ref())
+                });
+            }
+            (TypeDefKind::Enum(a), TypeDefKind::Enum(b)) => {
+                let a = a
+                    .cases
+                    .iter()
+                    .map(|c| (c.name.clone(), ()))
+                    .collect::<Vec<_>>();
+                let b = b
+                    .cases
+                    .iter()
+                    .map(|c| (c.name.clone(), ()))
+                    .collect::<Vec<_>>();
+                self.diff_ordered(&path, ItemKind::Type, ItemKind::Case, &a, &b, |_, _, _| {
+                    true
+                });
+            }
+            (TypeDefKind::Flags(a), TypeDefKind::Flags(b)) => {
+                let a = a
+                    .flags
+                    .iter()
+                    .map(|f| (f.name.clone(), ()))
+                    .collect::<Vec<_>>();
+                let b = b
+                    .flags
+                    .iter()
+                    .map(|f| (f.name.clone(), ()))
+                    .collect::<Vec<_>>();
+                self.diff_ordered(&path, ItemKind::Type, ItemKind::Flag, &a, &b, |_, _, _| {
+                    true
+                });
+            }
+            // Methods of resources are diffed along with the functions of the
+            // enclosing interface.
+            (TypeDefKind::Resource, TypeDefKind::Resource) => {}
+            (a, b) => {
+                if !self.kind_eq(a, b) {
+                    self.push(ChangeKind::Changed, ItemKind::Type, path);
+                }
+            }
+        }
+    }
+
+    fn type_eq(&self, a: &Type, b: &Type) -> bool {
+        match (a, b) {
+            (Type::Id(a), Type::Id(b)) => self.type_id_eq(*a, *b),
+            (Type::Id(_), _) | (_, Type::Id(_)) => false,
+            (a, b) => a == b,
+        }
+    }
+
+    fn optional_type_eq(&self, a: Option<&Type>, b: Option<&Type>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => self.type_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    fn type_id_eq(&self, a: TypeId, b: TypeId) -> bool {
+        let a = &self.old.types[a];
+        let b = &self.new.types[b];
+        match (&a.name, &b.name) {
+            (Some(x), Some(y)) => {
+                x == y && owner_name(self.old, &a.owner) == owner_name(self.new, &b.owner)
+            }
+            (None, None) => self.kind_eq(&a.kind, &b.kind),
+            _ => false,
+        }
+    }
+
+    fn kind_eq(&self, a: &TypeDefKind, b: &TypeDefKind) -> bool {
+        match (a, b) {
+            (TypeDefKind::Record(a), TypeDefKind::Record(b)) => {
+                a.fields.len() == b.fields.len()
+                    && a.fields
+                        .iter()
+                        .zip(&b.fields)
+                        .all(|(a, b)| a.name == b.name && self.type_eq(&a.ty, &b.ty))
+            }
+            (TypeDefKind::Resource, TypeDefKind::Resource) => true,
+            (TypeDefKind::Handle(Handle::Own(a)), TypeDefKind::Handle(Handle::Own(b)))
+            | (TypeDefKind::Handle(Handle::Borrow(a)), TypeDefKind::Handle(Handle::Borrow(b))) => {
+                self.type_id_eq(*a, *b)
+            }
+            (TypeDefKind::Flags(a), TypeDefKind::Flags(b)) => {
+                a.flags.len() == b.flags.len()
+                    && a.flags.iter().zip(&b.flags).all(|(a, b)| a.name == b.name)
+            }
+            (TypeDefKind::Tuple(a), TypeDefKind::Tuple(b)) => {
+                a.types.len() == b.types.len()
+                    && a.types
+                        .iter()
+                        .zip(&b.types)
+                        .all(|(a, b)| self.type_eq(a, b))
+            }
+            (TypeDefKind::Variant(a), TypeDefKind::Variant(b)) => {
+                a.cases.len() == b.cases.len()
+                    && a.cases.iter().zip(&b.cases).all(|(a, b)| {
+                        a.name == b.name && self.optional_type_eq(a.ty.as_ref(), b.ty.as_ref())
+                    })
+            }
+            (TypeDefKind::Enum(a), TypeDefKind::Enum(b)) => {
+                a.cases.len() == b.cases.len()
+                    && a.cases.iter().zip(&b.cases).all(|(a, b)| a.name == b.name)
+            }
+            (TypeDefKind::Option(a), TypeDefKind::Option(b))
+            | (TypeDefKind::List(a), TypeDefKind::List(b))
+            | (TypeDefKind::Type(a), TypeDefKind::Type(b)) => self.type_eq(a, b),
+            (TypeDefKind::Result(a), TypeDefKind::Result(b)) => {
+                self.optional_type_eq(a.ok.as_ref(), b.ok.as_ref())
+                    && self.optional_type_eq(a.err.as_ref(), b.err.as_ref())
+            }
+            (TypeDefKind::Future(a), TypeDefKind::Future(b)) => {
+                self.optional_type_eq(a.as_ref(), b.as_ref())
+            }
+            (TypeDefKind::Stream(a), TypeDefKind::Stream(b)) => {
+                self.optional_type_eq(a.element.as_ref(), b.element.as_ref())
+                    && self.optional_type_eq(a.end.as_ref(), b.end.as_ref())
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Returns the name of `interface` qualified by its package but without the
+/// package's version.
+fn interface_name(resolve: &Resolve, interface: InterfaceId) -> String {
+    let interface = &resolve.interfaces[interface];
+    let name = interface.name.as_deref().unwrap_or("<anonymous>");
+    match interface.package {
+        Some(pkg) => {
+            let pkg = &resolve.packages[pkg].name;
+            format!("{}:{}/{}", pkg.namespace, pkg.name, name)
+        }
+        None => name.to_string(),
+    }
+}
+
+fn owner_name(resolve: &Resolve, owner: &TypeOwner) -> Option<String> {
+    match owner {
+        TypeOwner::Interface(id) => Some(interface_name(resolve, *id)),
+        TypeOwner::World(id) => Some(resolve.worlds[*id].name.clone()),
+        TypeOwner::None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Resolve, UnresolvedPackage};
+
+    fn diff(old: &str, new: &str) -> Vec<String> {
+        let mut old_resolve = Resolve::default();
+        let old_pkg = old_resolve
+            .push(UnresolvedPackage::parse("old.wit".as_ref(), old).unwrap())
+            .unwrap();
+        let mut new_resolve = Resolve::default();
+        let new_pkg = new_resolve
+            .push(UnresolvedPackage::parse("new.wit".as_ref(), new).unwrap())
+            .unwrap();
+        old_resolve
+            .diff_packages(old_pkg, &new_resolve, new_pkg)
+            .iter()
+            .map(|c| c.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn unchanged() {
+        let wit = r#"
+            package foo:bar@1.0.0;
+
+            interface i {
+                record r { a: u32, b: list<string> }
+                f: func(x: r) -> option<r>;
+            }
+
+            world w {
+                import i;
+                export g: func();
+            }
+        "#;
+        assert!(diff(wit, &wit.replace("1.0.0", "1.1.0")).is_empty());
+    }
+
+    #[test]
+    fn functions_and_params() {
+        let changes = diff(
+            r#"
+                package foo:bar;
+
+                interface i {
+                    f: func(x: u32, y: u32);
+                    g: func() -> u32;
+                    h: func();
+                }
+            "#,
+            r#"
+                package foo:bar;
+
+                interface i {
+                    f: func(y: u32, z: u32);
+                    g: func() -> u64;
+                    k: func();
+                }
+            "#,
+        );
+        assert_eq!(
+            changes,
+            [
+                "breaking: removed param `i.f.x`",
+                "breaking: added param `i.f.z`",
+                "breaking: changed result `i.g`",
+                "breaking: removed function `i.h`",
+                "compatible: added function `i.k`",
+            ]
+        );
+    }
+
+    #[test]
+    fn types_and_resources() {
+        let changes = diff(
+            r#"
+                package foo:bar;
+
+                interface i {
+                    record r { a: u32, b: u32 }
+                    variant v { a, b(u32) }
+                    enum e { a, b }
+                    resource res {
+                        constructor();
+                        m: func();
+                    }
+                }
+            "#,
+            r#"
+                package foo:bar;
+
+                interface i {
+                    record r { b: u32, a: u32 }
+                    variant v { a, b(string), c }
+                    enum e { a, b }
+                    type t = u32;
+                    resource res {
+                        m: func();
+                        n: static func();
+                    }
+                }
+
+                interface j {}
+            "#,
+        );
+        assert_eq!(
+            changes,
+            [
+                "breaking: changed type `i.r`",
+                "breaking: changed case `i.v.b`",
+                "breaking: added case `i.v.c`",
+                "compatible: added type `i.t`",
+                "breaking: removed method `i.res.constructor`",
+                "compatible: added method `i.res.n`",
+                "compatible: added interface `j`",
+            ]
+        );
+    }
+}
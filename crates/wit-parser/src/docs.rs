@@ -1,8 +1,7 @@
+use crate::{Docs, InterfaceId, PackageId, Resolve, TypeId, WorldId, WorldItem, WorldKey};
 use anyhow::{bail, Result};
 use indexmap::IndexMap;
 use serde_derive::{Deserialize, Serialize};
-use wasm_encoder::{CustomSection, Encode};
-use wit_parser::{Docs, InterfaceId, PackageId, Resolve, TypeId, WorldId, WorldItem, WorldKey};
 
 type StringMap<V> = IndexMap<String, V>;
 
@@ -68,19 +67,12 @@ impl PackageDocs {
         Ok(())
     }
 
-    /// Encode package docs as a package-docs custom section.
-    pub fn raw_custom_section(&self) -> Result<Vec<u8>> {
+    /// Encode package docs as the contents of a package-docs custom section.
+    pub fn encode(&self) -> Result<Vec<u8>> {
         // Version byte (0), followed by JSON encoding of docs
         let mut data = vec![PACKAGE_DOCS_SECTION_VERSION];
         serde_json::to_writer(&mut data, self)?;
-
-        let mut raw_section = vec![];
-        CustomSection {
-            name: PACKAGE_DOCS_SECTION_NAME.into(),
-            data: data.into(),
-        }
-        .encode(&mut raw_section);
-        Ok(raw_section)
+        Ok(data)
     }
 
     /// Decode package docs from package-docs custom section content.
@@ -277,16 +269,16 @@ impl TypeDocs {
         }
         let ty = &resolve.types[id];
         let items = match &ty.kind {
-            wit_parser::TypeDefKind::Record(record) => {
+            crate::TypeDefKind::Record(record) => {
                 extract_items(&record.fields, |item| (&item.name, &item.docs))
             }
-            wit_parser::TypeDefKind::Flags(flags) => {
+            crate::TypeDefKind::Flags(flags) => {
                 extract_items(&flags.flags, |item| (&item.name, &item.docs))
             }
-            wit_parser::TypeDefKind::Variant(variant) => {
+            crate::TypeDefKind::Variant(variant) => {
                 extract_items(&variant.cases, |item| (&item.name, &item.docs))
             }
-            wit_parser::TypeDefKind::Enum(enum_) => {
+            crate::TypeDefKind::Enum(enum_) => {
                 extract_items(&enum_.cases, |item| (&item.name, &item.docs))
             }
             // other types don't have inner items
@@ -303,16 +295,16 @@ impl TypeDocs {
         let ty = &mut resolve.types[id];
         if !self.items.is_empty() {
             match &mut ty.kind {
-                wit_parser::TypeDefKind::Record(record) => {
+                crate::TypeDefKind::Record(record) => {
                     self.inject_items(&mut record.fields, |item| (&item.name, &mut item.docs))?
                 }
-                wit_parser::TypeDefKind::Flags(flags) => {
+                crate::TypeDefKind::Flags(flags) => {
                     self.inject_items(&mut flags.flags, |item| (&item.name, &mut item.docs))?
                 }
-                wit_parser::TypeDefKind::Variant(variant) => {
+                crate::TypeDefKind::Variant(variant) => {
                     self.inject_items(&mut variant.cases, |item| (&item.name, &mut item.docs))?
                 }
-                wit_parser::TypeDefKind::Enum(enum_) => {
+                crate::TypeDefKind::Enum(enum_) => {
                     self.inject_items(&mut enum_.cases, |item| (&item.name, &mut item.docs))?
                 }
                 _ => {
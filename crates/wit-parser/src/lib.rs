@@ -2,7 +2,7 @@ use anyhow::{Context, Result};
 use id_arena::{Arena, Id};
 use indexmap::IndexMap;
 use semver::Version;
-use serde_derive::Serialize;
+use serde_derive::{Deserialize, Serialize};
 use std::borrow::Cow;
 use std::fmt;
 use std::path::Path;
@@ -17,10 +17,17 @@ mod resolve;
 pub use resolve::{Package, PackageId, Remap, Resolve};
 mod live;
 pub use live::LiveTypes;
+mod diff;
+pub use diff::{Change, ChangeKind, Compatibility, ItemKind};
+mod docs;
+pub use docs::{PackageDocs, PACKAGE_DOCS_SECTION_NAME};
+#[cfg(feature = "decoding")]
+pub mod decoding;
 mod serde_;
 use serde_::{
-    serialize_anon_result, serialize_id, serialize_id_map, serialize_none, serialize_optional_id,
-    serialize_params,
+    deserialize_id, deserialize_id_map, deserialize_optional_id, deserialize_params,
+    deserialize_version, serialize_anon_result, serialize_id, serialize_id_map, serialize_none,
+    serialize_optional_id, serialize_params, serialize_version,
 };
 
 /// Checks if the given string is a legal identifier in wit.
@@ -121,8 +128,8 @@ pub enum AstItem {
 ///
 /// This is directly encoded as an "ID" in the binary component representation
 /// with an interfaced tacked on as well.
-#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize)]
-#[serde(into = "String")]
+#[derive(Debug, Clone, Hash, Eq, PartialEq, Ord, PartialOrd, Serialize, Deserialize)]
+#[serde(into = "String", try_from = "String")]
 pub struct PackageName {
     /// A namespace such as `wasi` in `wasi:foo/bar`
     pub namespace: String,
@@ -138,6 +145,31 @@ impl From<PackageName> for String {
     }
 }
 
+impl TryFrom<String> for PackageName {
+    type Error = anyhow::Error;
+
+    fn try_from(name: String) -> Result<PackageName> {
+        let (namespace, rest) = name
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("package name `{name}` is missing a namespace"))?;
+        let (name, version) = match rest.split_once('@') {
+            Some((name, version)) => (name, Some(version)),
+            None => (rest, None),
+        };
+        validate_id(namespace)?;
+        validate_id(name)?;
+        let version = version
+            .map(|v| v.parse::<Version>())
+            .transpose()
+            .with_context(|| format!("invalid version in package name `{namespace}:{rest}`"))?;
+        Ok(PackageName {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            version,
+        })
+    }
+}
+
 impl PackageName {
     /// Returns the ID that this package name would assign the `interface` name
     /// specified.
@@ -247,7 +279,7 @@ impl UnresolvedPackage {
     }
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct World {
     /// The WIT identifier name of this world.
     pub name: String,
@@ -259,11 +291,14 @@ pub struct World {
     pub exports: IndexMap<WorldKey, WorldItem>,
 
     /// The package that owns this world.
-    #[serde(serialize_with = "serialize_optional_id")]
+    #[serde(
+        serialize_with = "serialize_optional_id",
+        deserialize_with = "deserialize_optional_id"
+    )]
     pub package: Option<PackageId>,
 
     /// Documentation associated with this world declaration.
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 
     /// All the included worlds from this world. Empty if this is fully resolved
@@ -286,8 +321,8 @@ pub struct IncludeName {
 
 /// The key to the import/export maps of a world. Either a kebab-name or a
 /// unique interface.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
-#[serde(into = "String")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "String", from = "String")]
 pub enum WorldKey {
     /// A kebab-name.
     Name(String),
@@ -304,6 +339,20 @@ impl From<WorldKey> for String {
     }
 }
 
+impl From<String> for WorldKey {
+    fn from(key: String) -> WorldKey {
+        // Note that `interface-N` isn't a valid WIT identifier since its
+        // second part starts with a digit, so this can't be confused with a
+        // kebab-name.
+        if let Some(index) = key.strip_prefix("interface-") {
+            if let Ok(index) = index.parse() {
+                return WorldKey::Interface(serde_::unresolved_id(index));
+            }
+        }
+        WorldKey::Name(key)
+    }
+}
+
 impl WorldKey {
     /// Asserts that this is `WorldKey::Name` and returns the name.
     #[track_caller]
@@ -315,12 +364,12 @@ impl WorldKey {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum WorldItem {
     /// An interface is being imported or exported from a world, indicating that
     /// it's a namespace of functions.
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Interface(InterfaceId),
 
     /// A function is being directly imported or exported from this world.
@@ -329,11 +378,11 @@ pub enum WorldItem {
     /// A type is being exported from this world.
     ///
     /// Note that types are never imported into worlds at this time.
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Type(TypeId),
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Interface {
     /// Optionally listed name of this interface.
     ///
@@ -344,31 +393,44 @@ pub struct Interface {
     ///
     /// Export names are listed within the types themselves. Note that the
     /// export name here matches the name listed in the `TypeDef`.
-    #[serde(serialize_with = "serialize_id_map")]
+    #[serde(
+        serialize_with = "serialize_id_map",
+        deserialize_with = "deserialize_id_map"
+    )]
     pub types: IndexMap<String, TypeId>,
 
     /// Exported functions from this interface.
     pub functions: IndexMap<String, Function>,
 
     /// Documentation associated with this interface.
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 
     /// The package that owns this interface.
-    #[serde(serialize_with = "serialize_optional_id")]
+    #[serde(
+        serialize_with = "serialize_optional_id",
+        deserialize_with = "deserialize_optional_id"
+    )]
     pub package: Option<PackageId>,
+
+    /// Stability attribute for this interface.
+    #[serde(default, skip_serializing_if = "Stability::is_unknown")]
+    pub stability: Stability,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TypeDef {
     pub name: Option<String>,
     pub kind: TypeDefKind,
     pub owner: TypeOwner,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
+    /// Stability attribute for this type.
+    #[serde(default, skip_serializing_if = "Stability::is_unknown")]
+    pub stability: Stability,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TypeDefKind {
     Record(Record),
@@ -417,14 +479,14 @@ impl TypeDefKind {
     }
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum TypeOwner {
     /// This type was defined within a `world` block.
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     World(WorldId),
     /// This type was defined within an `interface` block.
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Interface(InterfaceId),
     /// This type wasn't inherently defined anywhere, such as a `list<T>`, which
     /// doesn't need an owner.
@@ -432,12 +494,12 @@ pub enum TypeOwner {
     None,
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize)]
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum Handle {
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Own(TypeId),
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Borrow(TypeId),
 }
 
@@ -467,29 +529,29 @@ pub enum Int {
     U64,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Record {
     pub fields: Vec<Field>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Field {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: Type,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Flags {
     pub flags: Vec<Flag>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Flag {
     pub name: String,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 }
 
@@ -521,22 +583,22 @@ impl FlagsRepr {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Tuple {
     pub types: Vec<Type>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Variant {
     pub cases: Vec<Case>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Case {
     pub name: String,
     #[serde(rename = "type")]
     pub ty: Option<Type>,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 }
 
@@ -551,15 +613,15 @@ impl Variant {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Enum {
     pub cases: Vec<EnumCase>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct EnumCase {
     pub name: String,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 }
 
@@ -574,19 +636,19 @@ impl Enum {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Result_ {
     pub ok: Option<Type>,
     pub err: Option<Type>,
 }
 
-#[derive(Debug, Clone, PartialEq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Stream {
     pub element: Option<Type>,
     pub end: Option<Type>,
 }
 
-#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Docs {
     pub contents: Option<String>,
 }
@@ -597,6 +659,43 @@ impl Docs {
     }
 }
 
+/// Stability of an interface, type, or function, as declared with the
+/// `@since` and `@unstable` attributes in WIT.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Stability {
+    /// `@since(version = 1.2.3)`, optionally with `feature = foo`.
+    ///
+    /// The item is stable as of the specified version. If a feature is
+    /// listed then that's the feature the item was previously gated behind.
+    Stable {
+        #[serde(
+            serialize_with = "serialize_version",
+            deserialize_with = "deserialize_version"
+        )]
+        since: Version,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        feature: Option<String>,
+    },
+
+    /// `@unstable(feature = foo)`
+    ///
+    /// The item is only available when `feature` is enabled in the
+    /// [`Resolve`] it's being added to.
+    Unstable { feature: String },
+
+    /// No stability attribute was specified.
+    #[default]
+    Unknown,
+}
+
+impl Stability {
+    /// Returns whether no stability attribute was specified.
+    pub fn is_unknown(&self) -> bool {
+        matches!(self, Stability::Unknown)
+    }
+}
+
 pub type Params = Vec<(String, Type)>;
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
@@ -667,26 +766,32 @@ impl Results {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Function {
     pub name: String,
     pub kind: FunctionKind,
-    #[serde(serialize_with = "serialize_params")]
+    #[serde(
+        serialize_with = "serialize_params",
+        deserialize_with = "deserialize_params"
+    )]
     pub params: Params,
     pub results: Results,
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
+    /// Stability attribute for this function.
+    #[serde(default, skip_serializing_if = "Stability::is_unknown")]
+    pub stability: Stability,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum FunctionKind {
     Freestanding,
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Method(TypeId),
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Static(TypeId),
-    #[serde(serialize_with = "serialize_id")]
+    #[serde(serialize_with = "serialize_id", deserialize_with = "deserialize_id")]
     Constructor(TypeId),
 }
 
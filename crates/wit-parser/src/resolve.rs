@@ -1,15 +1,16 @@
 use crate::ast::lex::Span;
 use crate::ast::{parse_use_path, AstUsePath};
-use crate::serde_::{serialize_arena, serialize_id_map};
+use crate::serde_::{deserialize_id_map, serialize_arena, serialize_id_map};
 use crate::{
     AstItem, Docs, Error, Function, FunctionKind, Handle, IncludeName, Interface, InterfaceId,
-    PackageName, Results, Type, TypeDef, TypeDefKind, TypeId, TypeOwner, UnresolvedPackage, World,
-    WorldId, WorldItem, WorldKey,
+    PackageName, Results, Stability, Type, TypeDef, TypeDefKind, TypeId, TypeOwner,
+    UnresolvedPackage, World, WorldId, WorldItem, WorldKey,
 };
 use anyhow::{anyhow, bail, Context, Result};
 use id_arena::{Arena, Id};
 use indexmap::{IndexMap, IndexSet};
-use serde_derive::Serialize;
+use semver::Version;
+use serde_derive::{Deserialize, Serialize};
 use std::collections::{BTreeMap, HashMap, HashSet};
 use std::mem;
 use std::path::{Path, PathBuf};
@@ -39,6 +40,18 @@ pub struct Resolve {
     pub packages: Arena<Package>,
     #[serde(skip)]
     pub package_names: IndexMap<PackageName, PackageId>,
+
+    /// Features which are enabled when pushing packages into this `Resolve`.
+    ///
+    /// Items annotated with `@unstable(feature = foo)` are only included if
+    /// `foo` is listed here (or `all_features` is set). Otherwise they're
+    /// removed from their package when it's pushed.
+    #[serde(skip)]
+    pub features: IndexSet<String>,
+
+    /// Activate all features, regardless of `features`.
+    #[serde(skip)]
+    pub all_features: bool,
 }
 
 /// A WIT package within a `Resolve`.
@@ -46,22 +59,28 @@ pub struct Resolve {
 /// A package is a collection of interfaces and worlds. Packages additionally
 /// have a unique identifier that affects generated components and uniquely
 /// identifiers this particular package.
-#[derive(Clone, Serialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Package {
     /// A unique name corresponding to this package.
     pub name: PackageName,
 
     /// Documentation associated with this package.
-    #[serde(skip_serializing_if = "Docs::is_empty")]
+    #[serde(default, skip_serializing_if = "Docs::is_empty")]
     pub docs: Docs,
 
     /// All interfaces contained in this packaged, keyed by the interface's
     /// name.
-    #[serde(serialize_with = "serialize_id_map")]
+    #[serde(
+        serialize_with = "serialize_id_map",
+        deserialize_with = "deserialize_id_map"
+    )]
     pub interfaces: IndexMap<String, InterfaceId>,
 
     /// All worlds contained in this package, keyed by the world's name.
-    #[serde(serialize_with = "serialize_id_map")]
+    #[serde(
+        serialize_with = "serialize_id_map",
+        deserialize_with = "deserialize_id_map"
+    )]
     pub worlds: IndexMap<String, WorldId>,
 }
 
@@ -81,6 +100,19 @@ impl Resolve {
     /// be parsed as a WIT package. The directory name containing each package
     /// is not used as each package is otherwise self-identifying.
     ///
+    /// With the `decoding` feature enabled `*.wasm` files in `deps/` are also
+    /// decoded as binary-encoded WIT packages, such as those produced by
+    /// `wasm-tools component wit --wasm`. The `wat` feature additionally
+    /// enables `*.wat` files. Other files in `deps/` are ignored. Note that
+    /// binary packages are merged as-is, so [`Resolve::features`] doesn't
+    /// apply to them.
+    ///
+    /// Multiple versions of the same package may be present in `deps/`. A
+    /// versioned reference to a package, such as `use wasi:io/streams@0.2.0`,
+    /// is satisfied by that exact version if present, or otherwise by the
+    /// greatest semver-compatible version available. An unversioned reference
+    /// is satisfied only if a single version of the package is available.
+    ///
     /// This function returns the [`PackageId`] of the root parsed package at
     /// `path`, along with a list of all paths that were consumed during parsing
     /// for the root package and all dependency packages.
@@ -95,10 +127,11 @@ impl Resolve {
         // Perform a simple topological sort which will bail out on cycles
         // and otherwise determine the order that packages must be added to
         // this `Resolve`.
+        let root = DepPackage::Wit(pkg);
         let mut order = IndexSet::new();
         let mut visiting = HashSet::new();
-        for pkg in deps.values().chain([&pkg]) {
-            visit(&pkg, &deps, &mut order, &mut visiting)?;
+        for pkg in deps.values().chain([&root]) {
+            visit(pkg, &deps, &mut order, &mut visiting)?;
         }
 
         // Using the topological ordering insert each package incrementally.
@@ -106,17 +139,60 @@ impl Resolve {
         // package, which is the one returned here.
         let mut last = None;
         let mut files = Vec::new();
-        let mut pkg = Some(pkg);
+        let mut root = Some(root);
         for name in order {
-            let pkg = deps.remove(&name).unwrap_or_else(|| pkg.take().unwrap());
-            files.extend(pkg.source_files().map(|p| p.to_path_buf()));
-            let pkgid = self.push(pkg)?;
+            let pkg = deps.remove(&name).unwrap_or_else(|| root.take().unwrap());
+            let pkgid = match pkg {
+                DepPackage::Wit(pkg) => {
+                    files.extend(pkg.source_files().map(|p| p.to_path_buf()));
+                    self.push(pkg)?
+                }
+                #[cfg(feature = "decoding")]
+                DepPackage::Wasm {
+                    path,
+                    resolve,
+                    package,
+                } => {
+                    let remap = self
+                        .merge(resolve)
+                        .with_context(|| format!("failed to merge package: {}", path.display()))?;
+                    files.push(path);
+                    remap.packages[package.index()]
+                }
+            };
             last = Some(pkgid);
         }
 
         return Ok((last.unwrap(), files));
 
-        fn parse_deps_dir(path: &Path) -> Result<BTreeMap<PackageName, UnresolvedPackage>> {
+        /// A package found in the `deps` directory.
+        enum DepPackage {
+            /// A package parsed from `*.wit` files in a directory.
+            Wit(UnresolvedPackage),
+
+            /// A binary-encoded WIT package, which is already fully resolved
+            /// along with all of the dependencies that it refers to.
+            #[cfg(feature = "decoding")]
+            Wasm {
+                path: PathBuf,
+                resolve: Resolve,
+                package: PackageId,
+            },
+        }
+
+        impl DepPackage {
+            fn name(&self) -> &PackageName {
+                match self {
+                    DepPackage::Wit(pkg) => &pkg.name,
+                    #[cfg(feature = "decoding")]
+                    DepPackage::Wasm {
+                        resolve, package, ..
+                    } => &resolve.packages[*package].name,
+                }
+            }
+        }
+
+        fn parse_deps_dir(path: &Path) -> Result<BTreeMap<PackageName, DepPackage>> {
             let mut ret = BTreeMap::new();
             // If there's no `deps` dir, then there's no deps, so return the
             // empty set.
@@ -127,51 +203,115 @@ impl Resolve {
                 let dep = dep.context("failed to read directory iterator")?;
                 let path = dep.path();
 
-                // Files in deps dir are ignored for now to avoid accidentally
-                // including things like `.DS_Store` files in the call below to
-                // `parse_dir`.
-                if path.is_file() {
-                    continue;
-                }
-
-                let pkg = UnresolvedPackage::parse_dir(&path)
-                    .with_context(|| format!("failed to parse package: {}", path.display()))?;
-                let prev = ret.insert(pkg.name.clone(), pkg);
+                let pkg = if path.is_file() {
+                    // Files other than binary-encoded packages are ignored to
+                    // avoid accidentally including things like `.DS_Store`.
+                    match parse_deps_file(&path)
+                        .with_context(|| format!("failed to decode package: {}", path.display()))?
+                    {
+                        Some(pkg) => pkg,
+                        None => continue,
+                    }
+                } else {
+                    let pkg = UnresolvedPackage::parse_dir(&path)
+                        .with_context(|| format!("failed to parse package: {}", path.display()))?;
+                    DepPackage::Wit(pkg)
+                };
+                let prev = ret.insert(pkg.name().clone(), pkg);
                 if let Some(prev) = prev {
-                    bail!("duplicate definitions of package `{}` found", prev.name);
+                    bail!("duplicate definitions of package `{}` found", prev.name());
                 }
             }
             Ok(ret)
         }
 
+        #[cfg(feature = "decoding")]
+        fn parse_deps_file(path: &Path) -> Result<Option<DepPackage>> {
+            let bytes = match path.extension().and_then(|s| s.to_str()) {
+                Some("wasm") => std::fs::read(path)?,
+                #[cfg(feature = "wat")]
+                Some("wat") => wat::parse_file(path)?,
+                _ => return Ok(None),
+            };
+            match crate::decoding::decode(&bytes)? {
+                crate::decoding::DecodedWasm::WitPackage(resolve, package) => {
+                    Ok(Some(DepPackage::Wasm {
+                        path: path.to_path_buf(),
+                        resolve,
+                        package,
+                    }))
+                }
+                crate::decoding::DecodedWasm::Component(..) => {
+                    bail!("expected a binary-encoded WIT package, found a component")
+                }
+            }
+        }
+
+        #[cfg(not(feature = "decoding"))]
+        fn parse_deps_file(_path: &Path) -> Result<Option<DepPackage>> {
+            Ok(None)
+        }
+
         fn visit<'a>(
-            pkg: &'a UnresolvedPackage,
-            deps: &'a BTreeMap<PackageName, UnresolvedPackage>,
+            pkg: &'a DepPackage,
+            deps: &'a BTreeMap<PackageName, DepPackage>,
             order: &mut IndexSet<PackageName>,
             visiting: &mut HashSet<&'a PackageName>,
         ) -> Result<()> {
-            if order.contains(&pkg.name) {
+            if order.contains(pkg.name()) {
                 return Ok(());
             }
-            pkg.source_map.rewrite_error(|| {
-                for (i, (dep, _)) in pkg.foreign_deps.iter().enumerate() {
-                    let span = pkg.foreign_dep_spans[i];
-                    if !visiting.insert(dep) {
-                        bail!(Error {
-                            span,
-                            msg: format!("package depends on itself"),
-                        });
+            match pkg {
+                DepPackage::Wit(pkg) => pkg.source_map.rewrite_error(|| {
+                    for (i, (dep, _)) in pkg.foreign_deps.iter().enumerate() {
+                        let span = pkg.foreign_dep_spans[i];
+                        let name = find_package(dep, deps.keys())
+                            .map_err(|msg| Error { span, msg })?
+                            .ok_or_else(|| Error {
+                                span,
+                                msg: format!("failed to find package `{dep}` in `deps` directory"),
+                            })?;
+                        if !visiting.insert(name) {
+                            bail!(Error {
+                                span,
+                                msg: "package depends on itself".to_string(),
+                            });
+                        }
+                        let dep = &deps[name];
+                        visit(dep, deps, order, visiting)?;
+                        assert!(visiting.remove(dep.name()));
+                    }
+                    Ok(())
+                })?,
+
+                // Binary packages carry along the definitions of the packages
+                // they depend on, but if any of those are also present in
+                // `deps` then they're pushed first so the two are unioned
+                // when the binary package is merged in.
+                #[cfg(feature = "decoding")]
+                DepPackage::Wasm {
+                    resolve, package, ..
+                } => {
+                    for (id, dep) in resolve.packages.iter() {
+                        if id == *package {
+                            continue;
+                        }
+                        let name = match find_package(&dep.name, deps.keys()) {
+                            Ok(Some(name)) => name,
+                            Ok(None) => continue,
+                            Err(msg) => bail!("{msg}"),
+                        };
+                        if !visiting.insert(name) {
+                            bail!("package `{}` depends on itself", pkg.name());
+                        }
+                        let dep = &deps[name];
+                        visit(dep, deps, order, visiting)?;
+                        assert!(visiting.remove(dep.name()));
                     }
-                    let dep = deps.get(dep).ok_or_else(|| Error {
-                        span,
-                        msg: format!("failed to find package `{dep}` in `deps` directory"),
-                    })?;
-                    visit(dep, deps, order, visiting)?;
-                    assert!(visiting.remove(&dep.name));
                 }
-                assert!(order.insert(pkg.name.clone()));
-                Ok(())
-            })
+            }
+            assert!(order.insert(pkg.name().clone()));
+            Ok(())
         }
     }
 
@@ -181,11 +321,207 @@ impl Resolve {
     /// The `deps` argument indicates that the named dependencies in
     /// `unresolved` to packages are resolved by the mapping specified.
     ///
+    /// Items gated behind a feature which isn't enabled in
+    /// [`Resolve::features`] are removed from `unresolved` before it's added.
+    ///
     /// Any dependency resolution error or otherwise world-elaboration error
     /// will be returned here. If successful a package identifier is returned.
     pub fn push(&mut self, mut unresolved: UnresolvedPackage) -> Result<PackageId> {
         let source_map = mem::take(&mut unresolved.source_map);
-        source_map.rewrite_error(|| Remap::default().append(self, unresolved))
+        source_map.rewrite_error(|| {
+            self.remove_disabled_items(&mut unresolved)?;
+            Remap::default().append(self, unresolved)
+        })
+    }
+
+    /// Returns whether an item with the given `stability` is enabled by the
+    /// features configured in this `Resolve`.
+    pub fn include_stability(&self, stability: &Stability) -> bool {
+        match stability {
+            Stability::Stable { .. } | Stability::Unknown => true,
+            Stability::Unstable { feature } => self.all_features || self.features.contains(feature),
+        }
+    }
+
+    /// Removes all interfaces, types, and functions from `unresolved` whose
+    /// feature isn't enabled.
+    ///
+    /// Disabled interfaces are left in the arena, but are no longer imported
+    /// or exported by worlds and won't be registered in the package. It's an
+    /// error for an enabled item to refer to a disabled type.
+    fn remove_disabled_items(&self, unresolved: &mut UnresolvedPackage) -> Result<()> {
+        let disabled_interfaces = unresolved
+            .interfaces
+            .iter()
+            .filter(|(_, iface)| !self.include_stability(&iface.stability))
+            .map(|(id, _)| id)
+            .collect::<HashSet<_>>();
+        let disabled_types = unresolved
+            .types
+            .iter()
+            .filter(|(_, ty)| {
+                !self.include_stability(&ty.stability)
+                    || match ty.owner {
+                        TypeOwner::Interface(id) => disabled_interfaces.contains(&id),
+                        TypeOwner::World(_) | TypeOwner::None => false,
+                    }
+            })
+            .map(|(id, _)| id)
+            .collect::<HashSet<_>>();
+        let function_enabled = |func: &Function| {
+            self.include_stability(&func.stability)
+                && match func.kind {
+                    FunctionKind::Freestanding => true,
+                    FunctionKind::Method(id)
+                    | FunctionKind::Static(id)
+                    | FunctionKind::Constructor(id) => !disabled_types.contains(&id),
+                }
+        };
+
+        for (id, iface) in unresolved.interfaces.iter_mut() {
+            if disabled_interfaces.contains(&id) {
+                continue;
+            }
+            iface.types.retain(|_, id| !disabled_types.contains(id));
+            iface.functions.retain(|_, func| function_enabled(func));
+        }
+
+        let item_enabled = |item: &WorldItem| match item {
+            WorldItem::Interface(id) => !disabled_interfaces.contains(id),
+            WorldItem::Function(func) => function_enabled(func),
+            WorldItem::Type(id) => !disabled_types.contains(id),
+        };
+        for ((_, world), (import_spans, export_spans)) in unresolved
+            .worlds
+            .iter_mut()
+            .zip(&mut unresolved.world_item_spans)
+        {
+            retain_world_items(&mut world.imports, import_spans, item_enabled);
+            retain_world_items(&mut world.exports, export_spans, item_enabled);
+        }
+
+        // With everything removed, make sure that nothing left over refers to
+        // a type which no longer exists.
+        let mut visited = HashSet::new();
+        let mut check =
+            |ty: &Type| check_type_enabled(unresolved, &disabled_types, &mut visited, ty);
+        for (id, iface) in unresolved.interfaces.iter() {
+            if disabled_interfaces.contains(&id) {
+                continue;
+            }
+            for id in iface.types.values() {
+                check(&Type::Id(*id))?;
+            }
+            for func in iface.functions.values() {
+                for (_, ty) in func.params.iter() {
+                    check(ty)?;
+                }
+                for ty in func.results.iter_types() {
+                    check(ty)?;
+                }
+            }
+        }
+        for (_, world) in unresolved.worlds.iter() {
+            for item in world.imports.values().chain(world.exports.values()) {
+                match item {
+                    WorldItem::Interface(_) => {}
+                    WorldItem::Type(id) => check(&Type::Id(*id))?,
+                    WorldItem::Function(func) => {
+                        for (_, ty) in func.params.iter() {
+                            check(ty)?;
+                        }
+                        for ty in func.results.iter_types() {
+                            check(ty)?;
+                        }
+                    }
+                }
+            }
+        }
+        return Ok(());
+
+        fn retain_world_items(
+            items: &mut IndexMap<WorldKey, WorldItem>,
+            spans: &mut Vec<Span>,
+            enabled: impl Fn(&WorldItem) -> bool,
+        ) {
+            let mut i = 0;
+            items.retain(|_, item| {
+                if enabled(item) {
+                    i += 1;
+                    true
+                } else {
+                    spans.remove(i);
+                    false
+                }
+            });
+        }
+
+        fn check_type_enabled(
+            unresolved: &UnresolvedPackage,
+            disabled: &HashSet<TypeId>,
+            visited: &mut HashSet<TypeId>,
+            ty: &Type,
+        ) -> Result<()> {
+            let id = match ty {
+                Type::Id(id) => *id,
+                _ => return Ok(()),
+            };
+            let ty = &unresolved.types[id];
+            if disabled.contains(&id) {
+                bail!(
+                    "found a reference to type `{}` which is excluded because \
+                     its feature is not enabled",
+                    ty.name.as_deref().unwrap_or("<anonymous>"),
+                );
+            }
+            if !visited.insert(id) {
+                return Ok(());
+            }
+            let mut check = |ty: &Type| check_type_enabled(unresolved, disabled, visited, ty);
+            match &ty.kind {
+                TypeDefKind::Record(r) => {
+                    for field in r.fields.iter() {
+                        check(&field.ty)?;
+                    }
+                }
+                TypeDefKind::Tuple(t) => {
+                    for ty in t.types.iter() {
+                        check(ty)?;
+                    }
+                }
+                TypeDefKind::Variant(v) => {
+                    for ty in v.cases.iter().filter_map(|c| c.ty.as_ref()) {
+                        check(ty)?;
+                    }
+                }
+                TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => {
+                    check(&Type::Id(*id))?;
+                }
+                TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+                    check(ty)?;
+                }
+                TypeDefKind::Result(r) => {
+                    for ty in r.ok.iter().chain(r.err.iter()) {
+                        check(ty)?;
+                    }
+                }
+                TypeDefKind::Future(ty) => {
+                    if let Some(ty) = ty {
+                        check(ty)?;
+                    }
+                }
+                TypeDefKind::Stream(s) => {
+                    for ty in s.element.iter().chain(s.end.iter()) {
+                        check(ty)?;
+                    }
+                }
+                TypeDefKind::Resource
+                | TypeDefKind::Flags(_)
+                | TypeDefKind::Enum(_)
+                | TypeDefKind::Unknown => {}
+            }
+            Ok(())
+        }
     }
 
     pub fn all_bits_valid(&self, ty: &Type) -> bool {
@@ -287,6 +623,8 @@ impl Resolve {
             interfaces,
             packages,
             package_names,
+            features: _,
+            all_features: _,
         } = resolve;
 
         let mut moved_types = Vec::new();
@@ -576,30 +914,10 @@ impl Resolve {
         let (pkg, world) = match path {
             AstUsePath::Name(name) => (pkg, name),
             AstUsePath::Package(pkg, interface) => {
-                let pkg = match self.package_names.get(&pkg) {
-                    Some(pkg) => *pkg,
-                    None => {
-                        let mut candidates = self.package_names.iter().filter(|(name, _)| {
-                            pkg.version.is_none()
-                                && pkg.name == name.name
-                                && pkg.namespace == name.namespace
-                                && name.version.is_some()
-                        });
-                        let candidate = candidates.next();
-                        if let Some((c2, _)) = candidates.next() {
-                            let (c1, _) = candidate.unwrap();
-                            bail!(
-                                "package name `{pkg}` is available at both \
-                                 versions {} and {} but which is not specified",
-                                c1.version.as_ref().unwrap(),
-                                c2.version.as_ref().unwrap(),
-                            );
-                        }
-                        match candidate {
-                            Some((_, id)) => *id,
-                            None => bail!("unknown package `{pkg}`"),
-                        }
-                    }
+                let pkg = match find_package(&pkg, self.package_names.keys()) {
+                    Ok(Some(name)) => self.package_names[name],
+                    Ok(None) => bail!("unknown package `{pkg}`"),
+                    Err(msg) => bail!("{msg}"),
                 };
                 (pkg, interface)
             }
@@ -705,6 +1023,7 @@ impl Remap {
                     owner: TypeOwner::None,
                     kind: TypeDefKind::Handle(Handle::Own(id)),
                     docs: _,
+                    stability: _,
                 } => *self.own_handles.entry(id).or_insert(new_id),
 
                 // Everything not-related to `own<T>` doesn't get its ID
@@ -778,9 +1097,10 @@ impl Remap {
         let prev = resolve.package_names.insert(unresolved.name.clone(), pkgid);
         assert!(prev.is_none());
         for id in self.interfaces.iter().skip(foreign_interfaces) {
+            let enabled = resolve.include_stability(&resolve.interfaces[*id].stability);
             let iface = &mut resolve.interfaces[*id];
             iface.package = Some(pkgid);
-            if let Some(name) = &iface.name {
+            if let Some(name) = iface.name.as_ref().filter(|_| enabled) {
                 let prev = resolve.packages[pkgid].interfaces.insert(name.clone(), *id);
                 assert!(prev.is_none());
             }
@@ -871,10 +1191,9 @@ impl Remap {
                 // further interfaces will be non-foreign as well.
                 None => break,
             };
-            let pkgid = resolve
-                .package_names
-                .get(pkg_name)
-                .copied()
+            let pkgid = find_package(pkg_name, resolve.package_names.keys())
+                .map_err(|msg| Error { span, msg })?
+                .map(|name| resolve.package_names[name])
                 .ok_or_else(|| Error {
                     span,
                     msg: format!("package not found"),
@@ -919,10 +1238,9 @@ impl Remap {
                 None => break,
             };
 
-            let pkgid = resolve
-                .package_names
-                .get(pkg_name)
-                .copied()
+            let pkgid = find_package(pkg_name, resolve.package_names.keys())
+                .map_err(|msg| Error { span, msg })?
+                .map(|name| resolve.package_names[name])
                 .ok_or_else(|| Error {
                     span,
                     msg: format!("package not found"),
@@ -1070,6 +1388,7 @@ impl Remap {
                     owner: TypeOwner::None,
                     kind: TypeDefKind::Handle(Handle::Own(*id)),
                     docs: Default::default(),
+                    stability: Default::default(),
                 })
             });
         }
@@ -1808,6 +2127,80 @@ impl<'a> MergeMap<'a> {
     }
 }
 
+/// Finds the package among `candidates` which satisfies a reference to the
+/// package `name`.
+///
+/// An exact match is always preferred. Otherwise a versioned reference is
+/// satisfied by the greatest semver-compatible version of the same package and
+/// an unversioned reference by the only version of the package available.
+///
+/// Returns `Ok(None)` if no version of the package is available at all and an
+/// error describing the available versions if none of them are suitable.
+fn find_package<'a>(
+    name: &PackageName,
+    candidates: impl IntoIterator<Item = &'a PackageName>,
+) -> Result<Option<&'a PackageName>, String> {
+    let candidates = candidates
+        .into_iter()
+        .filter(|c| c.namespace == name.namespace && c.name == name.name)
+        .collect::<Vec<_>>();
+    if let Some(exact) = candidates.iter().find(|c| **c == name) {
+        return Ok(Some(exact));
+    }
+    if candidates.is_empty() {
+        return Ok(None);
+    }
+    let available = candidates
+        .iter()
+        .filter_map(|c| c.version.as_ref())
+        .map(|v| v.to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    match &name.version {
+        Some(version) => candidates
+            .iter()
+            .copied()
+            .filter(|c| {
+                c.version
+                    .as_ref()
+                    .is_some_and(|c| semver_compatible(version, c))
+            })
+            .max_by(|a, b| a.version.cmp(&b.version))
+            .map(Some)
+            .ok_or_else(|| {
+                format!(
+                    "no version of package `{}:{}` compatible with `{version}` \
+                     found, available versions: {available}",
+                    name.namespace, name.name
+                )
+            }),
+        None => match candidates[..] {
+            [candidate] => Ok(Some(candidate)),
+            _ => Err(format!(
+                "package `{name}` is available at versions {available} \
+                 but which is not specified"
+            )),
+        },
+    }
+}
+
+/// Returns whether `candidate` can be used in place of `requested` according
+/// to semver rules.
+///
+/// Within a `0.x` series minor versions are considered breaking, and `0.0.x`
+/// and prerelease versions are only compatible with themselves.
+fn semver_compatible(requested: &Version, candidate: &Version) -> bool {
+    if candidate < requested || !requested.pre.is_empty() || !candidate.pre.is_empty() {
+        return false;
+    }
+    match (requested.major, requested.minor) {
+        (0, 0) => false,
+        (0, minor) => candidate.major == 0 && candidate.minor == minor,
+        (major, _) => candidate.major == major,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{PackageId, Resolve};
@@ -1865,6 +2258,102 @@ mod tests {
             .is_ok());
     }
 
+    #[test]
+    fn enabled_features() {
+        const WIT: &str = r#"
+            package foo:bar;
+
+            @unstable(feature = active)
+            interface a {
+                @unstable(feature = inactive)
+                f: func();
+                g: func();
+            }
+
+            @unstable(feature = inactive)
+            interface b {}
+
+            world w {
+                import a;
+                import b;
+            }
+        "#;
+
+        let mut resolve = Resolve::default();
+        resolve.features.insert("active".to_string());
+        let pkg = parse_into(&mut resolve, WIT);
+        let world = resolve.select_world(pkg, Some("w")).unwrap();
+        assert_eq!(resolve.worlds[world].imports.len(), 1);
+        let interfaces = &resolve.packages[pkg].interfaces;
+        assert!(!interfaces.contains_key("b"));
+        let a = &resolve.interfaces[interfaces["a"]];
+        assert!(!a.functions.contains_key("f"));
+        assert!(a.functions.contains_key("g"));
+
+        let mut resolve = Resolve {
+            all_features: true,
+            ..Default::default()
+        };
+        let pkg = parse_into(&mut resolve, WIT);
+        let world = resolve.select_world(pkg, Some("w")).unwrap();
+        assert_eq!(resolve.worlds[world].imports.len(), 2);
+        let a = &resolve.interfaces[resolve.packages[pkg].interfaces["a"]];
+        assert_eq!(a.functions.len(), 2);
+    }
+
+    #[test]
+    fn deserialize_json() {
+        let mut resolve = Resolve::default();
+        let pkg = parse_into(
+            &mut resolve,
+            r#"
+                package foo:bar;
+
+                interface a {
+                    resource r {
+                        get: func() -> list<u8>;
+                    }
+                }
+
+                world w {
+                    import a;
+                }
+            "#,
+        );
+        let json = serde_json::to_value(&resolve).unwrap();
+
+        let deserialized: Resolve = serde_json::from_value(json.clone()).unwrap();
+        let pkg = &deserialized.packages[deserialized.package_names[&resolve.packages[pkg].name]];
+        let world = &deserialized.worlds[pkg.worlds["w"]];
+        assert_eq!(world.imports.len(), 1);
+        let a = &deserialized.interfaces[pkg.interfaces["a"]];
+        let r = &deserialized.types[a.types["r"]];
+        assert_eq!(r.owner, crate::TypeOwner::Interface(pkg.interfaces["a"]));
+
+        let deserialize_err = |f: &dyn Fn(&mut serde_json::Value)| {
+            let mut json = json.clone();
+            f(&mut json);
+            match serde_json::from_value::<Resolve>(json) {
+                Ok(_) => panic!("expected an error"),
+                Err(e) => e.to_string(),
+            }
+        };
+        let err = deserialize_err(&|json| json["worlds"][0]["package"] = 10.into());
+        assert!(err.contains("package index 10 is out of bounds"), "{err}");
+        let err = deserialize_err(&|json| {
+            json["types"][1]["kind"]["handle"] = serde_json::json!({ "own": 1 })
+        });
+        assert!(err.contains("type 1 isn't defined before type 1"), "{err}");
+        let err = deserialize_err(&|json| {
+            let package = json["packages"][0].clone();
+            json["packages"].as_array_mut().unwrap().push(package);
+        });
+        assert!(
+            err.contains("package `foo:bar` is defined more than once"),
+            "{err}"
+        );
+    }
+
     fn parse_into(resolve: &mut Resolve, wit: &str) -> PackageId {
         let pkg = crate::UnresolvedPackage::parse("input.wit".as_ref(), wit).unwrap();
         resolve.push(pkg).unwrap()
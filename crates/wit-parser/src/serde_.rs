@@ -1,8 +1,24 @@
-use crate::{Params, Type};
-use id_arena::{Arena, Id};
+use crate::{
+    Function, FunctionKind, Handle, Interface, Package, Params, Resolve, Results, Type, TypeDef,
+    TypeDefKind, TypeId, TypeOwner, World, WorldItem, WorldKey,
+};
+use anyhow::{bail, Context};
+use id_arena::{Arena, ArenaBehavior, DefaultArenaBehavior, Id};
 use indexmap::IndexMap;
+use serde::de::{self, Deserializer};
 use serde::ser::{SerializeMap, SerializeSeq, Serializer};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::mem;
+
+/// Arena index used for ids created during deserialization.
+///
+/// Serialized ids are plain indices so when they're deserialized it's not yet
+/// known which arena they belong to. Ids are created with this placeholder
+/// arena and are later rebased onto the arenas of the deserialized `Resolve`,
+/// see `Resolve`'s `Deserialize` implementation.
+pub const UNRESOLVED_ARENA_ID: u32 = u32::MAX;
 
 pub fn serialize_none<S>(serializer: S) -> Result<S::Ok, S::Error>
 where
@@ -23,6 +39,33 @@ where
     seq.end()
 }
 
+pub fn deserialize_arena<'de, T, D>(deserializer: D) -> Result<Arena<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let mut arena = Arena::new();
+    for item in Vec::<T>::deserialize(deserializer)? {
+        arena.alloc(item);
+    }
+    Ok(arena)
+}
+
+pub fn serialize_version<S>(version: &semver::Version, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.collect_str(version)
+}
+
+pub fn deserialize_version<'de, D>(deserializer: D) -> Result<semver::Version, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let version = String::deserialize(deserializer)?;
+    version.parse().map_err(de::Error::custom)
+}
+
 pub fn serialize_id<T, S>(id: &Id<T>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -30,6 +73,21 @@ where
     serializer.serialize_u64(id.index() as u64)
 }
 
+/// Creates an id with the index specified in the placeholder
+/// [`UNRESOLVED_ARENA_ID`] arena.
+pub fn unresolved_id<T>(index: usize) -> Id<T> {
+    DefaultArenaBehavior::<T>::new_id(UNRESOLVED_ARENA_ID, index)
+}
+
+pub fn deserialize_id<'de, T, D>(deserializer: D) -> Result<Id<T>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let index = u64::deserialize(deserializer)?;
+    let index = usize::try_from(index).map_err(de::Error::custom)?;
+    Ok(unresolved_id(index))
+}
+
 pub fn serialize_optional_id<T, S>(id: &Option<Id<T>>, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -40,6 +98,19 @@ where
     }
 }
 
+pub fn deserialize_optional_id<'de, T, D>(deserializer: D) -> Result<Option<Id<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let index = Option::<u64>::deserialize(deserializer)?;
+    index
+        .map(|index| {
+            let index = usize::try_from(index).map_err(de::Error::custom)?;
+            Ok(unresolved_id(index))
+        })
+        .transpose()
+}
+
 pub fn serialize_id_map<K, T, S>(map: &IndexMap<K, Id<T>>, serializer: S) -> Result<S::Ok, S::Error>
 where
     K: Serialize,
@@ -52,6 +123,20 @@ where
     s.end()
 }
 
+pub fn deserialize_id_map<'de, K, T, D>(deserializer: D) -> Result<IndexMap<K, Id<T>>, D::Error>
+where
+    K: Deserialize<'de> + Hash + Eq,
+    D: Deserializer<'de>,
+{
+    let map = IndexMap::<K, u64>::deserialize(deserializer)?;
+    map.into_iter()
+        .map(|(key, index)| {
+            let index = usize::try_from(index).map_err(de::Error::custom)?;
+            Ok((key, unresolved_id(index)))
+        })
+        .collect()
+}
+
 impl Serialize for Type {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -76,6 +161,55 @@ impl Serialize for Type {
     }
 }
 
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct TypeVisitor;
+
+        impl<'de> de::Visitor<'de> for TypeVisitor {
+            type Value = Type;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a primitive type name or a type index")
+            }
+
+            fn visit_str<E>(self, name: &str) -> Result<Type, E>
+            where
+                E: de::Error,
+            {
+                Ok(match name {
+                    "bool" => Type::Bool,
+                    "u8" => Type::U8,
+                    "u16" => Type::U16,
+                    "u32" => Type::U32,
+                    "u64" => Type::U64,
+                    "s8" => Type::S8,
+                    "s16" => Type::S16,
+                    "s32" => Type::S32,
+                    "s64" => Type::S64,
+                    "float32" => Type::Float32,
+                    "float64" => Type::Float64,
+                    "char" => Type::Char,
+                    "string" => Type::String,
+                    _ => return Err(E::custom(format!("unknown primitive type `{name}`"))),
+                })
+            }
+
+            fn visit_u64<E>(self, index: u64) -> Result<Type, E>
+            where
+                E: de::Error,
+            {
+                let index = usize::try_from(index).map_err(E::custom)?;
+                Ok(Type::Id(unresolved_id(index)))
+            }
+        }
+
+        deserializer.deserialize_any(TypeVisitor)
+    }
+}
+
 pub fn serialize_anon_result<S>(typ: &Type, serializer: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
@@ -99,10 +233,407 @@ where
     seq.end()
 }
 
-#[derive(Debug, Clone, PartialEq, serde_derive::Serialize)]
+pub fn deserialize_params<'de, D>(deserializer: D) -> Result<Params, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let params = Vec::<Param>::deserialize(deserializer)?;
+    Ok(params.into_iter().map(|p| (p.name, p.typ)).collect())
+}
+
+impl<'de> Deserialize<'de> for Results {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // An anonymous result is serialized as a single unnamed parameter, see
+        // `serialize_anon_result`.
+        let mut params = Vec::<Param>::deserialize(deserializer)?;
+        match params.as_slice() {
+            [param] if param.name.is_empty() => Ok(Results::Anon(params.pop().unwrap().typ)),
+            _ => {
+                if params.iter().any(|p| p.name.is_empty()) {
+                    return Err(de::Error::custom("only a single result may be unnamed"));
+                }
+                Ok(Results::Named(
+                    params.into_iter().map(|p| (p.name, p.typ)).collect(),
+                ))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
 struct Param {
-    #[serde(skip_serializing_if = "String::is_empty")]
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub name: String,
     #[serde(rename = "type")]
     pub typ: Type,
 }
+
+impl<'de> Deserialize<'de> for Resolve {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(serde_derive::Deserialize)]
+        struct RawResolve {
+            #[serde(deserialize_with = "deserialize_arena")]
+            worlds: Arena<World>,
+            #[serde(deserialize_with = "deserialize_arena")]
+            interfaces: Arena<Interface>,
+            #[serde(deserialize_with = "deserialize_arena")]
+            types: Arena<TypeDef>,
+            #[serde(deserialize_with = "deserialize_arena")]
+            packages: Arena<Package>,
+        }
+
+        let RawResolve {
+            worlds,
+            interfaces,
+            types,
+            packages,
+        } = RawResolve::deserialize(deserializer)?;
+        let mut resolve = Resolve {
+            worlds,
+            interfaces,
+            types,
+            packages,
+            ..Resolve::default()
+        };
+        rebase_ids(&mut resolve)
+            .and_then(|()| validate(&mut resolve))
+            .map_err(|e| de::Error::custom(format!("{e:#}")))?;
+        Ok(resolve)
+    }
+}
+
+/// The index space of one of the arenas in a deserialized `Resolve`.
+struct IndexSpace<T> {
+    arena_id: u32,
+    len: usize,
+    desc: &'static str,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> IndexSpace<T> {
+    fn new(arena: &Arena<T>, desc: &'static str) -> IndexSpace<T> {
+        IndexSpace {
+            arena_id: DefaultArenaBehavior::<T>::arena_id(arena.next_id()),
+            len: arena.len(),
+            desc,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Moves `id` from the placeholder arena it was deserialized into to this
+    /// arena, failing if it's out of bounds.
+    fn rebase(&self, id: &mut Id<T>) -> anyhow::Result<()> {
+        if id.index() >= self.len {
+            bail!("{} index {} is out of bounds", self.desc, id.index());
+        }
+        *id = DefaultArenaBehavior::<T>::new_id(self.arena_id, id.index());
+        Ok(())
+    }
+}
+
+/// Updates all ids within `resolve`, which were deserialized as plain
+/// indices, to point into the arenas of `resolve`.
+fn rebase_ids(resolve: &mut Resolve) -> anyhow::Result<()> {
+    let types = IndexSpace::new(&resolve.types, "type");
+    let interfaces = IndexSpace::new(&resolve.interfaces, "interface");
+    let worlds = IndexSpace::new(&resolve.worlds, "world");
+    let packages = IndexSpace::new(&resolve.packages, "package");
+
+    let rebase_ty = |ty: &mut Type| match ty {
+        Type::Id(id) => types.rebase(id),
+        _ => Ok(()),
+    };
+    let rebase_func = |func: &mut Function| -> anyhow::Result<()> {
+        match &mut func.kind {
+            FunctionKind::Freestanding => {}
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                types.rebase(id)?
+            }
+        }
+        for (_, ty) in func.params.iter_mut() {
+            rebase_ty(ty)?;
+        }
+        match &mut func.results {
+            Results::Named(results) => {
+                for (_, ty) in results.iter_mut() {
+                    rebase_ty(ty)?;
+                }
+            }
+            Results::Anon(ty) => rebase_ty(ty)?,
+        }
+        Ok(())
+    };
+
+    for (index, (_, ty)) in resolve.types.iter_mut().enumerate() {
+        let cx = || format!("failed to process type {index}");
+
+        // Types are sorted topologically so they may only refer to types
+        // defined before them, which notably also rules out cycles.
+        let rebase_id = |id: &mut TypeId| -> anyhow::Result<()> {
+            types.rebase(id)?;
+            if id.index() >= index {
+                bail!("type {} isn't defined before type {index}", id.index());
+            }
+            Ok(())
+        };
+        let rebase_ty = |ty: &mut Type| match ty {
+            Type::Id(id) => rebase_id(id),
+            _ => Ok(()),
+        };
+        let rebase_opt_ty = |ty: &mut Option<Type>| match ty {
+            Some(ty) => rebase_ty(ty),
+            None => Ok(()),
+        };
+        match &mut ty.kind {
+            TypeDefKind::Record(r) => {
+                for field in r.fields.iter_mut() {
+                    rebase_ty(&mut field.ty).with_context(cx)?;
+                }
+            }
+            TypeDefKind::Handle(Handle::Own(id) | Handle::Borrow(id)) => {
+                rebase_id(id).with_context(cx)?
+            }
+            TypeDefKind::Tuple(t) => {
+                for ty in t.types.iter_mut() {
+                    rebase_ty(ty).with_context(cx)?;
+                }
+            }
+            TypeDefKind::Variant(v) => {
+                for case in v.cases.iter_mut() {
+                    rebase_opt_ty(&mut case.ty).with_context(cx)?;
+                }
+            }
+            TypeDefKind::Option(ty) | TypeDefKind::List(ty) | TypeDefKind::Type(ty) => {
+                rebase_ty(ty).with_context(cx)?
+            }
+            TypeDefKind::Result(r) => {
+                rebase_opt_ty(&mut r.ok).with_context(cx)?;
+                rebase_opt_ty(&mut r.err).with_context(cx)?;
+            }
+            TypeDefKind::Future(ty) => rebase_opt_ty(ty).with_context(cx)?,
+            TypeDefKind::Stream(s) => {
+                rebase_opt_ty(&mut s.element).with_context(cx)?;
+                rebase_opt_ty(&mut s.end).with_context(cx)?;
+            }
+            TypeDefKind::Resource | TypeDefKind::Flags(_) | TypeDefKind::Enum(_) => {}
+            TypeDefKind::Unknown => bail!("type {index} has an unknown definition"),
+        }
+        match &mut ty.owner {
+            TypeOwner::Interface(id) => interfaces.rebase(id).with_context(cx)?,
+            TypeOwner::World(id) => worlds.rebase(id).with_context(cx)?,
+            TypeOwner::None => {}
+        }
+    }
+
+    for (index, (_, iface)) in resolve.interfaces.iter_mut().enumerate() {
+        let cx = || format!("failed to process interface {index}");
+        for (_, id) in iface.types.iter_mut() {
+            types.rebase(id).with_context(cx)?;
+        }
+        for (_, func) in iface.functions.iter_mut() {
+            rebase_func(func).with_context(cx)?;
+        }
+        if let Some(id) = &mut iface.package {
+            packages.rebase(id).with_context(cx)?;
+        }
+    }
+
+    for (index, (_, world)) in resolve.worlds.iter_mut().enumerate() {
+        let cx = || format!("failed to process world {index}");
+        for items in [&mut world.imports, &mut world.exports] {
+            for (mut key, mut item) in mem::take(items) {
+                if let WorldKey::Interface(id) = &mut key {
+                    interfaces.rebase(id).with_context(cx)?;
+                }
+                match &mut item {
+                    WorldItem::Interface(id) => interfaces.rebase(id).with_context(cx)?,
+                    WorldItem::Function(func) => rebase_func(func).with_context(cx)?,
+                    WorldItem::Type(id) => types.rebase(id).with_context(cx)?,
+                }
+                items.insert(key, item);
+            }
+        }
+        if let Some(id) = &mut world.package {
+            packages.rebase(id).with_context(cx)?;
+        }
+    }
+
+    for (index, (_, package)) in resolve.packages.iter_mut().enumerate() {
+        let cx = || format!("failed to process package {index}");
+        for (_, id) in package.interfaces.iter_mut() {
+            interfaces.rebase(id).with_context(cx)?;
+        }
+        for (_, id) in package.worlds.iter_mut() {
+            worlds.rebase(id).with_context(cx)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks the invariants that [`Resolve::push`] otherwise maintains on a
+/// deserialized `resolve`, additionally filling in its `package_names`.
+fn validate(resolve: &mut Resolve) -> anyhow::Result<()> {
+    for (id, package) in resolve.packages.iter() {
+        let prev = resolve.package_names.insert(package.name.clone(), id);
+        if prev.is_some() {
+            bail!("package `{}` is defined more than once", package.name);
+        }
+        for (name, iface) in package.interfaces.iter() {
+            let iface = &resolve.interfaces[*iface];
+            if iface.name.as_deref() != Some(name) || iface.package != Some(id) {
+                bail!(
+                    "interface `{name}` of package `{}` doesn't have a matching name and package",
+                    package.name
+                );
+            }
+        }
+        for (name, world) in package.worlds.iter() {
+            let world = &resolve.worlds[*world];
+            if world.name != *name || world.package != Some(id) {
+                bail!(
+                    "world `{name}` of package `{}` doesn't have a matching name and package",
+                    package.name
+                );
+            }
+        }
+    }
+
+    let resource = |mut id: TypeId| loop {
+        match &resolve.types[id].kind {
+            TypeDefKind::Type(Type::Id(other)) => id = *other,
+            TypeDefKind::Resource => break true,
+            _ => break false,
+        }
+    };
+    let check_func = |func: &Function| -> anyhow::Result<()> {
+        match func.kind {
+            FunctionKind::Freestanding => {}
+            FunctionKind::Method(id) | FunctionKind::Static(id) | FunctionKind::Constructor(id) => {
+                if !resource(id) {
+                    bail!("function `{}` is not associated with a resource", func.name);
+                }
+            }
+        }
+        Ok(())
+    };
+
+    for (id, ty) in resolve.types.iter() {
+        if let TypeDefKind::Handle(Handle::Own(t) | Handle::Borrow(t)) = ty.kind {
+            if !resource(t) {
+                bail!(
+                    "type {} is a handle to a type which isn't a resource",
+                    id.index()
+                );
+            }
+        }
+    }
+
+    for (id, iface) in resolve.interfaces.iter() {
+        let desc = || match &iface.name {
+            Some(name) => format!("interface `{name}`"),
+            None => format!("interface {}", id.index()),
+        };
+        if iface.package.is_none() {
+            bail!("{} is not part of a package", desc());
+        }
+        for (name, ty) in iface.types.iter() {
+            let ty = &resolve.types[*ty];
+            if ty.name.as_deref() != Some(name) || ty.owner != TypeOwner::Interface(id) {
+                bail!(
+                    "type `{name}` of {} doesn't have a matching name and owner",
+                    desc()
+                );
+            }
+        }
+        for func in iface.functions.values() {
+            check_func(func).with_context(|| format!("failed to validate {}", desc()))?;
+        }
+
+        // Interfaces are sorted topologically as well.
+        for dep in resolve.interface_direct_deps(id) {
+            if dep.index() >= id.index() {
+                bail!(
+                    "{} depends on interface {} which isn't defined before it",
+                    desc(),
+                    dep.index()
+                );
+            }
+        }
+    }
+
+    for (id, world) in resolve.worlds.iter() {
+        let package = match world.package {
+            Some(package) => package,
+            None => bail!("world `{}` is not part of a package", world.name),
+        };
+        if resolve.packages[package].worlds.get(&world.name) != Some(&id) {
+            bail!(
+                "world `{}` is not listed in its package `{}`",
+                world.name,
+                resolve.packages[package].name
+            );
+        }
+        for (key, item) in world.imports.iter().chain(&world.exports) {
+            match (key, item) {
+                (WorldKey::Interface(a), WorldItem::Interface(b)) if a == b => {}
+                (WorldKey::Interface(_), _) => bail!(
+                    "world `{}` has an item keyed by an interface which isn't that interface",
+                    world.name
+                ),
+                (WorldKey::Name(_), WorldItem::Interface(_)) => {}
+                (WorldKey::Name(_), WorldItem::Function(func)) => check_func(func)
+                    .with_context(|| format!("failed to validate world `{}`", world.name))?,
+                (WorldKey::Name(name), WorldItem::Type(ty)) => {
+                    let ty = &resolve.types[*ty];
+                    if ty.name.as_deref() != Some(name) || ty.owner != TypeOwner::World(id) {
+                        bail!(
+                            "type `{name}` of world `{}` doesn't have a matching name and owner",
+                            world.name
+                        );
+                    }
+                }
+            }
+        }
+
+        // Worlds are fully elaborated, meaning that the dependencies of all
+        // imported interfaces are imported and the dependencies of exported
+        // interfaces are either imported or exported.
+        let interfaces = |items: &IndexMap<WorldKey, WorldItem>| {
+            items
+                .values()
+                .filter_map(|item| match item {
+                    WorldItem::Interface(id) => Some(*id),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let imports = interfaces(&world.imports);
+        let exports = interfaces(&world.exports);
+        for (iface, available) in imports
+            .iter()
+            .map(|i| (i, &imports[..]))
+            .chain(exports.iter().map(|i| (i, &exports[..])))
+        {
+            for dep in resolve.interface_direct_deps(*iface) {
+                if !imports.contains(&dep) && !available.contains(&dep) {
+                    bail!(
+                        "interface {} in world `{}` depends on interface {} which is not \
+                         imported or exported",
+                        iface.index(),
+                        world.name,
+                        dep.index(),
+                    );
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -132,6 +132,14 @@ impl Runner<'_> {
             let json_result = serde_json::to_string_pretty(&resolve)?;
             // "foo.wit" => "foo.wit.json"
             self.read_or_write_to_file(test, &json_result, "json")?;
+
+            // Deserializing the json should yield an equivalent `Resolve`.
+            let roundtrip: Resolve = serde_json::from_str(&json_result)
+                .context("failed to deserialize json into a `Resolve`")?;
+            let roundtrip = serde_json::to_string_pretty(&roundtrip)?;
+            if roundtrip != json_result {
+                bail!("json changed after a deserialization roundtrip:\n{roundtrip}");
+            }
             return Ok(());
         };
 
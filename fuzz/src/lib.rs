@@ -27,6 +27,8 @@ pub fn generate_valid_module(
     config.exceptions_enabled = u.arbitrary()?;
     config.canonicalize_nans = u.arbitrary()?;
     config.tail_call_enabled = u.arbitrary()?;
+    config.gc_enabled = u.arbitrary()?;
+    config.function_references_enabled = config.gc_enabled || u.arbitrary()?;
 
     configure(&mut config, u)?;
 
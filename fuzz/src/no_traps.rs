@@ -15,6 +15,8 @@ pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
         config.disallow_traps = true;
         config.threads_enabled = false;
         config.exceptions_enabled = false;
+        config.gc_enabled = false;
+        config.function_references_enabled = false;
         config.max_memory_pages = config.max_memory_pages.min(100);
         Ok(())
     })?;
@@ -111,6 +113,9 @@ fn validate_module(config: SwarmConfig, wasm_bytes: &Vec<u8>) {
         memory64: config.memory64_enabled,
         threads: config.threads_enabled,
         exceptions: config.exceptions_enabled,
+        tail_call: config.tail_call_enabled,
+        function_references: config.function_references_enabled,
+        gc: config.gc_enabled,
         ..wasmparser::WasmFeatures::default()
     });
     if let Err(e) = validator.validate_all(wasm_bytes) {
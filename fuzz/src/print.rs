@@ -1,7 +1,9 @@
 use arbitrary::{Result, Unstructured};
 
 pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
+    let mut printer = wasmprinter::Printer::new();
+    printer.fold_instructions(u.arbitrary()?);
     let data = u.bytes(u.len())?;
-    drop(wasmprinter::print_bytes(data));
+    drop(printer.print(data));
     Ok(())
 }
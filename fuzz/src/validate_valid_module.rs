@@ -36,6 +36,9 @@ pub fn run(u: &mut Unstructured<'_>) -> Result<()> {
         memory64: config.memory64_enabled,
         threads: config.threads_enabled,
         exceptions: config.exceptions_enabled,
+        tail_call: config.tail_call_enabled,
+        function_references: config.function_references_enabled,
+        gc: config.gc_enabled,
         ..wasmparser::WasmFeatures::default()
     });
     if let Err(e) = validator.validate_all(&wasm_bytes) {
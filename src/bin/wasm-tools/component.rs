@@ -12,16 +12,19 @@ use wit_component::{
     embed_component_metadata, is_wasm_binary_or_wat, parse_wit_from_path, ComponentEncoder,
     DecodedWasm, Linker, StringEncoding, WitPrinter,
 };
-use wit_parser::{Resolve, UnresolvedPackage};
+use wit_parser::{PackageId, Resolve, UnresolvedPackage};
 
 /// WebAssembly wit-based component tooling.
 #[derive(Parser)]
 pub enum Opts {
     New(NewOpts),
     Wit(WitOpts),
+    WitDiff(WitDiffOpts),
     Embed(EmbedOpts),
     Targets(TargetsOpts),
     Link(LinkOpts),
+    Dedupe(DedupeOpts),
+    Size(SizeOpts),
 }
 
 impl Opts {
@@ -29,9 +32,12 @@ impl Opts {
         match self {
             Opts::New(new) => new.run(),
             Opts::Wit(wit) => wit.run(),
+            Opts::WitDiff(diff) => diff.run(),
             Opts::Embed(embed) => embed.run(),
             Opts::Targets(targets) => targets.run(),
             Opts::Link(link) => link.run(),
+            Opts::Dedupe(dedupe) => dedupe.run(),
+            Opts::Size(size) => size.run(),
         }
     }
 
@@ -39,9 +45,12 @@ impl Opts {
         match self {
             Opts::New(new) => new.general_opts(),
             Opts::Wit(wit) => wit.general_opts(),
+            Opts::WitDiff(diff) => diff.general_opts(),
             Opts::Embed(embed) => embed.general_opts(),
             Opts::Targets(targets) => targets.general_opts(),
             Opts::Link(link) => link.general_opts(),
+            Opts::Dedupe(dedupe) => dedupe.general_opts(),
+            Opts::Size(size) => size.general_opts(),
         }
     }
 }
@@ -173,6 +182,9 @@ pub struct EmbedOpts {
     #[clap(flatten)]
     io: wasm_tools::InputOutput,
 
+    #[clap(flatten)]
+    features: WitFeatureOpts,
+
     /// The expected string encoding format for the component.
     ///
     /// Supported values are: `utf8` (default), `utf16`, and `compact-utf16`.
@@ -219,7 +231,7 @@ impl EmbedOpts {
         } else {
             Some(self.io.parse_input_wasm()?)
         };
-        let (resolve, id) = parse_wit_from_path(self.wit)?;
+        let (resolve, id) = self.features.parse_wit(&self.wit)?;
         let world = resolve.select_world(id, self.world.as_deref())?;
 
         let mut wasm = wasm.unwrap_or_else(|| wit_component::dummy_module(&resolve, world));
@@ -382,7 +394,9 @@ pub struct WitOpts {
     /// The file specified can be a `*.wit` file parsed as a single-document
     /// package. It can be a directory to be parsed as a WIT package. It can be
     /// a `*.wat` or `*.wasm` file for either the binary representation of a WIT
-    /// package or a component itself to extract the interface from. The type of
+    /// package or a component itself to extract the interface from. It can
+    /// also be a `*.json` file in the format produced by `--json`, in which
+    /// case the last package in the document is the one emitted. The type of
     /// input is inferred from the contents of the path specified.
     ///
     /// If not provided or if this is `-` then stdin is read entirely and
@@ -392,6 +406,9 @@ pub struct WitOpts {
     #[clap(flatten)]
     output: wasm_tools::OutputArg,
 
+    #[clap(flatten)]
+    features: WitFeatureOpts,
+
     /// Emit a WebAssembly binary representation instead of the WIT text format.
     #[clap(short, long, conflicts_with = "wat", conflicts_with = "out_dir")]
     wasm: bool,
@@ -445,6 +462,7 @@ impl WitOpts {
         // come from a number of sources:
         //
         // * If a `*.wat` or `*.wasm` is specified, use `wit_component::decode`
+        // * If a `*.json` file is specified, deserialize it as a `Resolve`.
         // * If a directory is specified, parse it as a `Resolve`-oriented
         //   package with a `deps` directory optionally available.
         // * If a file is specified then it's just a normal wit package where
@@ -456,16 +474,7 @@ impl WitOpts {
         // specifying a directory but specifying the WIT dependencies are
         // located elsewhere. This should be sufficient for now though.
         let decoded = match &self.input {
-            Some(input) => match input.extension().and_then(|s| s.to_str()) {
-                Some("wat") | Some("wasm") => {
-                    let bytes = wat::parse_file(&input)?;
-                    decode_wasm(&bytes).context("failed to decode WIT document")?
-                }
-                _ => {
-                    let (resolve, id) = parse_wit_from_path(input)?;
-                    DecodedWasm::WitPackage(resolve, id)
-                }
-            },
+            Some(input) => decode_path(input, &self.features)?,
             None => {
                 let mut stdin = Vec::new();
                 std::io::stdin()
@@ -479,12 +488,14 @@ impl WitOpts {
                     })?;
 
                     decode_wasm(&bytes).context("failed to decode WIT document")?
+                } else if is_json(&stdin) {
+                    decode_json(&stdin).context("failed to decode JSON from <stdin>")?
                 } else {
                     let stdin = match std::str::from_utf8(&stdin) {
                         Ok(s) => s,
                         Err(_) => bail!("stdin was not valid utf-8"),
                     };
-                    let mut resolve = Resolve::default();
+                    let mut resolve = self.features.resolve();
                     let pkg = UnresolvedPackage::parse("<stdin>".as_ref(), stdin)?;
                     let id = resolve.push(pkg)?;
                     DecodedWasm::WitPackage(resolve, id)
@@ -599,6 +610,60 @@ impl WitOpts {
     }
 }
 
+/// Compare two versions of a WIT package to find API changes.
+///
+/// Each input can be a `*.wit` file, a directory containing a WIT package, or
+/// a `*.wat` or `*.wasm` file containing either a WIT package or a component
+/// whose interface is extracted. Interfaces, worlds, types, functions, and
+/// their params, fields, cases, and resource methods are matched up by name
+/// and each difference is printed along with whether it's backwards-compatible
+/// or breaking.
+///
+/// Adding items is considered compatible while removing or modifying them is
+/// considered breaking. Package versions are ignored.
+#[derive(Parser)]
+pub struct WitDiffOpts {
+    #[clap(flatten)]
+    general: wasm_tools::GeneralOpts,
+
+    /// The old version of the WIT package.
+    old: PathBuf,
+
+    /// The new version of the WIT package.
+    new: PathBuf,
+
+    #[clap(flatten)]
+    features: WitFeatureOpts,
+
+    /// Exit with an error if any breaking changes are found.
+    #[clap(long)]
+    deny_breaking: bool,
+}
+
+impl WitDiffOpts {
+    fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        &self.general
+    }
+
+    /// Executes the application.
+    fn run(self) -> Result<()> {
+        let old = decode_path(&self.old, &self.features)?;
+        let new = decode_path(&self.new, &self.features)?;
+        let changes =
+            old.resolve()
+                .diff_packages(old.package(), new.resolve(), new.package());
+
+        for change in changes.iter() {
+            println!("{change}");
+        }
+        let breaking = changes.iter().filter(|c| c.is_breaking()).count();
+        if self.deny_breaking && breaking > 0 {
+            bail!("found {breaking} breaking change(s)");
+        }
+        Ok(())
+    }
+}
+
 /// Tool for verifying whether a component conforms to a world.
 #[derive(Parser)]
 pub struct TargetsOpts {
@@ -635,6 +700,561 @@ impl TargetsOpts {
     }
 }
 
+/// Removes duplicate core modules and components from a component.
+///
+/// Compositions frequently embed byte-identical copies of the same core module
+/// or component. This subcommand keeps the first definition of each within an
+/// enclosing component and rewrites all instantiations, aliases, and exports of
+/// the remaining copies to refer to it.
+#[derive(Parser)]
+pub struct DedupeOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Skip validation of the output component.
+    #[clap(long)]
+    skip_validation: bool,
+
+    /// Print the output in the WebAssembly text format instead of binary.
+    #[clap(long, short = 't')]
+    wat: bool,
+}
+
+impl DedupeOpts {
+    fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    /// Executes the application.
+    fn run(self) -> Result<()> {
+        let wasm = self.io.parse_input_wasm()?;
+        let deduped = wasm_compose::dedupe::dedupe(&wasm)?;
+
+        if !self.skip_validation {
+            wasmparser::Validator::new_with_features(wasmparser::WasmFeatures {
+                component_model: true,
+                ..Default::default()
+            })
+            .validate_all(&deduped.bytes)
+            .context("failed to validate deduplicated component")?;
+        }
+
+        eprintln!(
+            "removed {} core module(s) and {} component(s), saving {} bytes",
+            deduped.modules_removed, deduped.components_removed, deduped.bytes_saved
+        );
+
+        self.io.output(Output::Wasm {
+            bytes: &deduped.bytes,
+            wat: self.wat,
+        })?;
+
+        Ok(())
+    }
+}
+
+/// Prints how many bytes of a component go to each of its parts.
+///
+/// This prints a tree of the nested core modules and components within a
+/// component, using names from the `component-name` custom section where
+/// available. Each module and component is broken down further into its
+/// sections, with canonical functions split into lifts, lowers and resource
+/// intrinsics and custom sections listed by name, along with the percentage of
+/// the whole input that each part takes up.
+#[derive(Parser)]
+pub struct SizeOpts {
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+
+    /// Only print modules and components nested at most this deep, including
+    /// their sections.
+    #[clap(long)]
+    depth: Option<usize>,
+}
+
+impl SizeOpts {
+    fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    /// Executes the application.
+    fn run(self) -> Result<()> {
+        let wasm = self.io.parse_input_wasm()?;
+        let root = SizeNode::parse(&wasm)?;
+
+        let mut output = self.io.output_writer()?;
+        writeln!(output, "{:>10} {:>8}  part", "size", "%")?;
+        let mut stack = vec![(0, &root)];
+        while let Some((depth, node)) = stack.pop() {
+            writeln!(
+                output,
+                "{:>10} {:>7.2}%  {:indent$}{}",
+                node.size,
+                node.size as f64 * 100.0 / wasm.len() as f64,
+                "",
+                node.label(),
+                indent = depth * 2,
+            )?;
+            if self
+                .depth
+                .is_some_and(|max| node.is_nested() && depth >= max)
+            {
+                continue;
+            }
+            for child in node.children.iter().rev() {
+                stack.push((depth + 1, child));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A part of a component, as printed by `component size`.
+struct SizeNode {
+    kind: &'static str,
+    index: Option<u32>,
+    name: Option<String>,
+    size: usize,
+    children: Vec<SizeNode>,
+}
+
+/// State for a module or component which is being parsed.
+struct SizeFrame {
+    node: SizeNode,
+    num_modules: u32,
+    num_components: u32,
+    /// Positions within `node.children` of nested modules and components,
+    /// keyed by their index.
+    modules: HashMap<u32, usize>,
+    components: HashMap<u32, usize>,
+}
+
+impl SizeNode {
+    fn new(kind: &'static str, index: Option<u32>, name: Option<String>) -> SizeNode {
+        SizeNode {
+            kind,
+            index,
+            name,
+            size: 0,
+            children: Vec::new(),
+        }
+    }
+
+    fn is_nested(&self) -> bool {
+        matches!(self.kind, "module" | "component")
+    }
+
+    fn label(&self) -> String {
+        let mut label = self.kind.to_string();
+        if let Some(index) = self.index {
+            label.push_str(&format!("[{index}]"));
+        }
+        if let Some(name) = &self.name {
+            label.push_str(&format!(" {name:?}"));
+        }
+        label
+    }
+
+    fn parse(wasm: &[u8]) -> Result<SizeNode> {
+        use wasmparser::{
+            CanonicalFunction, ComponentAlias, ComponentExternalKind, ComponentNameSectionReader,
+            ComponentOuterAliasKind, ComponentTypeRef, Name, NameSectionReader, Parser, Payload::*,
+        };
+
+        let mut stack: Vec<SizeFrame> = Vec::new();
+        let mut pos = 0;
+        let mut root = None;
+
+        // Attributes the bytes from the end of the previous part up to `end`
+        // to the part called `kind`/`name` of the innermost module or
+        // component, merging it with any existing part of the same name.
+        let section = |stack: &mut Vec<SizeFrame>,
+                       pos: &mut usize,
+                       kind: &'static str,
+                       name: &str,
+                       end: usize| {
+            let node = &mut stack.last_mut().unwrap().node;
+            let size = end - *pos;
+            *pos = end;
+            let existing = node
+                .children
+                .iter_mut()
+                .find(|c| c.kind == kind && c.name.as_deref() == Some(name));
+            match existing {
+                Some(child) => child.size += size,
+                None => {
+                    let mut child = SizeNode::new(kind, None, Some(name.to_string()));
+                    child.size = size;
+                    node.children.push(child);
+                }
+            }
+        };
+
+        for payload in Parser::new(0).parse_all(wasm) {
+            let payload = payload?;
+            if stack.is_empty() {
+                let kind = match &payload {
+                    Version {
+                        encoding: wasmparser::Encoding::Component,
+                        ..
+                    } => "component",
+                    _ => "module",
+                };
+                let mut node = SizeNode::new(kind, None, None);
+                node.size = wasm.len();
+                stack.push(SizeFrame::new(node));
+            }
+
+            match payload {
+                Version { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "header", range.end)
+                }
+
+                ModuleSection { range, .. } => {
+                    let frame = stack.last_mut().unwrap();
+                    frame.num_modules += 1;
+                    let mut node = SizeNode::new("module", Some(frame.num_modules - 1), None);
+                    node.size = range.end - pos;
+                    pos = range.start;
+                    stack.push(SizeFrame::new(node));
+                }
+                ComponentSection { range, .. } => {
+                    let frame = stack.last_mut().unwrap();
+                    frame.num_components += 1;
+                    let mut node = SizeNode::new("component", Some(frame.num_components - 1), None);
+                    node.size = range.end - pos;
+                    pos = range.start;
+                    stack.push(SizeFrame::new(node));
+                }
+                End(offset) => {
+                    let frame = stack.pop().unwrap();
+                    if offset > pos {
+                        let mut unknown = SizeNode::new("section", None, Some("unknown".into()));
+                        unknown.size = offset - pos;
+                        pos = offset;
+                        let mut node = frame.node;
+                        node.children.push(unknown);
+                        SizeFrame::attach(&mut stack, node, &mut root);
+                    } else {
+                        SizeFrame::attach(&mut stack, frame.node, &mut root);
+                    }
+                }
+
+                TypeSection(s) => section(&mut stack, &mut pos, "section", "type", s.range().end),
+                ImportSection(s) => {
+                    section(&mut stack, &mut pos, "section", "import", s.range().end)
+                }
+                FunctionSection(s) => {
+                    section(&mut stack, &mut pos, "section", "function", s.range().end)
+                }
+                TableSection(s) => section(&mut stack, &mut pos, "section", "table", s.range().end),
+                MemorySection(s) => {
+                    section(&mut stack, &mut pos, "section", "memory", s.range().end)
+                }
+                TagSection(s) => section(&mut stack, &mut pos, "section", "tag", s.range().end),
+                GlobalSection(s) => {
+                    section(&mut stack, &mut pos, "section", "global", s.range().end)
+                }
+                ExportSection(s) => {
+                    section(&mut stack, &mut pos, "section", "export", s.range().end)
+                }
+                StartSection { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "start", range.end)
+                }
+                ElementSection(s) => {
+                    section(&mut stack, &mut pos, "section", "element", s.range().end)
+                }
+                DataCountSection { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "data count", range.end)
+                }
+                DataSection(s) => section(&mut stack, &mut pos, "section", "data", s.range().end),
+                CodeSectionStart { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "code", range.end)
+                }
+                CodeSectionEntry(_) => {}
+
+                InstanceSection(s) => section(
+                    &mut stack,
+                    &mut pos,
+                    "section",
+                    "core instance",
+                    s.range().end,
+                ),
+                CoreTypeSection(s) => {
+                    section(&mut stack, &mut pos, "section", "core type", s.range().end)
+                }
+                ComponentInstanceSection(s) => {
+                    section(&mut stack, &mut pos, "section", "instance", s.range().end)
+                }
+                ComponentAliasSection(s) => {
+                    let end = s.range().end;
+                    for alias in s.clone() {
+                        let frame = stack.last_mut().unwrap();
+                        match alias? {
+                            ComponentAlias::InstanceExport {
+                                kind: ComponentExternalKind::Module,
+                                ..
+                            }
+                            | ComponentAlias::Outer {
+                                kind: ComponentOuterAliasKind::CoreModule,
+                                ..
+                            } => frame.num_modules += 1,
+                            ComponentAlias::InstanceExport {
+                                kind: ComponentExternalKind::Component,
+                                ..
+                            }
+                            | ComponentAlias::Outer {
+                                kind: ComponentOuterAliasKind::Component,
+                                ..
+                            } => frame.num_components += 1,
+                            _ => {}
+                        }
+                    }
+                    section(&mut stack, &mut pos, "section", "alias", end)
+                }
+                ComponentTypeSection(s) => {
+                    section(&mut stack, &mut pos, "section", "type", s.range().end)
+                }
+                ComponentCanonicalSection(s) => {
+                    let end = s.range().end;
+                    let mut items = Vec::new();
+                    for item in s.into_iter_with_offsets() {
+                        let (offset, func) = item?;
+                        items.push((offset, func));
+                    }
+                    let header_end = items.first().map(|(o, _)| *o).unwrap_or(end);
+                    section(&mut stack, &mut pos, "section", "canonical", header_end);
+                    let ends = items
+                        .iter()
+                        .skip(1)
+                        .map(|(o, _)| *o)
+                        .chain([end])
+                        .collect::<Vec<_>>();
+                    for ((_, func), end) in items.into_iter().zip(ends) {
+                        let name = match func {
+                            CanonicalFunction::Lift { .. } => "lift",
+                            CanonicalFunction::Lower { .. } => "lower",
+                            CanonicalFunction::ResourceNew { .. } => "resource.new",
+                            CanonicalFunction::ResourceDrop { .. } => "resource.drop",
+                            CanonicalFunction::ResourceRep { .. } => "resource.rep",
+                        };
+                        section(&mut stack, &mut pos, "canon", name, end);
+                    }
+                }
+                ComponentStartSection { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "start", range.end)
+                }
+                ComponentImportSection(s) => {
+                    let end = s.range().end;
+                    for import in s {
+                        let frame = stack.last_mut().unwrap();
+                        match import?.ty {
+                            ComponentTypeRef::Module(_) => frame.num_modules += 1,
+                            ComponentTypeRef::Component(_) => frame.num_components += 1,
+                            _ => {}
+                        }
+                    }
+                    section(&mut stack, &mut pos, "section", "import", end)
+                }
+                ComponentExportSection(s) => {
+                    let end = s.range().end;
+                    for export in s {
+                        let frame = stack.last_mut().unwrap();
+                        match export?.kind {
+                            ComponentExternalKind::Module => frame.num_modules += 1,
+                            ComponentExternalKind::Component => frame.num_components += 1,
+                            _ => {}
+                        }
+                    }
+                    section(&mut stack, &mut pos, "section", "export", end)
+                }
+
+                CustomSection(s) => {
+                    let frame = stack.last_mut().unwrap();
+                    // Names are only used for display, so errors in name
+                    // sections are ignored.
+                    match s.name() {
+                        "component-name" => {
+                            let reader = ComponentNameSectionReader::new(s.data(), s.data_offset());
+                            drop(frame.component_names(reader));
+                        }
+                        "name" => {
+                            let reader = NameSectionReader::new(s.data(), s.data_offset());
+                            for name in reader {
+                                if let Ok(Name::Module { name, .. }) = name {
+                                    frame.node.name.get_or_insert_with(|| name.to_string());
+                                }
+                            }
+                        }
+                        _ => {}
+                    }
+                    section(&mut stack, &mut pos, "custom", s.name(), s.range().end)
+                }
+                UnknownSection { range, .. } => {
+                    section(&mut stack, &mut pos, "section", "unknown", range.end)
+                }
+            }
+        }
+
+        match root {
+            Some(root) => Ok(root),
+            None => bail!("input ended before the end of the module or component"),
+        }
+    }
+}
+
+impl SizeFrame {
+    fn new(node: SizeNode) -> SizeFrame {
+        SizeFrame {
+            node,
+            num_modules: 0,
+            num_components: 0,
+            modules: HashMap::new(),
+            components: HashMap::new(),
+        }
+    }
+
+    /// Adds `node`, which has been fully parsed, to its enclosing component or
+    /// stores it in `root` if it's the outermost one.
+    fn attach(stack: &mut [SizeFrame], node: SizeNode, root: &mut Option<SizeNode>) {
+        let parent = match stack.last_mut() {
+            Some(parent) => parent,
+            None => {
+                *root = Some(node);
+                return;
+            }
+        };
+        let index = node.index.unwrap();
+        let position = parent.node.children.len();
+        match node.kind {
+            "module" => parent.modules.insert(index, position),
+            _ => parent.components.insert(index, position),
+        };
+        parent.node.children.push(node);
+    }
+
+    fn component_names(
+        &mut self,
+        reader: wasmparser::ComponentNameSectionReader<'_>,
+    ) -> Result<()> {
+        use wasmparser::ComponentName;
+
+        for name in reader {
+            let (map, positions) = match name? {
+                ComponentName::Component { name, .. } => {
+                    self.node.name = Some(name.to_string());
+                    continue;
+                }
+                ComponentName::CoreModules(map) => (map, &self.modules),
+                ComponentName::Components(map) => (map, &self.components),
+                _ => continue,
+            };
+            for naming in map {
+                let naming = naming?;
+                if let Some(position) = positions.get(&naming.index) {
+                    self.node.children[*position].name = Some(naming.name.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Decodes a WIT package from a `*.wat` or `*.wasm` file, or parses it from a
+/// WIT file or directory otherwise.
+/// Options to select which features are enabled when parsing WIT.
+#[derive(clap::Args)]
+struct WitFeatureOpts {
+    /// Features to enable when parsing WIT.
+    ///
+    /// Items annotated with `@unstable(feature = foo)` are removed unless
+    /// `foo` is listed here. Multiple features may be separated with commas.
+    #[clap(long, value_name = "FEATURES", value_delimiter = ',')]
+    features: Vec<String>,
+
+    /// Enable all features when parsing WIT.
+    #[clap(long)]
+    all_features: bool,
+}
+
+impl WitFeatureOpts {
+    /// Returns an empty `Resolve` with these features enabled.
+    fn resolve(&self) -> Resolve {
+        let mut resolve = Resolve::default();
+        resolve.features.extend(self.features.iter().cloned());
+        resolve.all_features = self.all_features;
+        resolve
+    }
+
+    /// Parses the WIT package at `path`, which is either a directory or a
+    /// single file, with these features enabled.
+    ///
+    /// Binary-encoded WIT packages are decoded as-is since feature gates
+    /// have already been applied to them.
+    fn parse_wit(&self, path: &Path) -> Result<(Resolve, PackageId)> {
+        let mut resolve = self.resolve();
+        if path.is_dir() {
+            let (id, _) = resolve.push_dir(path).with_context(|| {
+                format!(
+                    "failed to resolve directory while parsing WIT for path [{}]",
+                    path.display()
+                )
+            })?;
+            return Ok((resolve, id));
+        }
+        let contents = std::fs::read(path)
+            .with_context(|| format!("failed to parse WIT from path [{}]", path.display()))?;
+        if is_wasm_binary_or_wat(&contents) {
+            return parse_wit_from_path(path);
+        }
+        let text = match std::str::from_utf8(&contents) {
+            Ok(s) => s,
+            Err(_) => bail!("input file is not valid utf-8"),
+        };
+        let id = resolve.push(UnresolvedPackage::parse(path, text)?)?;
+        Ok((resolve, id))
+    }
+}
+
+fn decode_path(input: &Path, features: &WitFeatureOpts) -> Result<DecodedWasm> {
+    match input.extension().and_then(|s| s.to_str()) {
+        Some("wat") | Some("wasm") => {
+            let bytes = wat::parse_file(input)?;
+            decode_wasm(&bytes).context("failed to decode WIT document")
+        }
+        Some("json") => {
+            let contents = std::fs::read(input)
+                .with_context(|| format!("failed to read file {}", input.display()))?;
+            decode_json(&contents)
+                .with_context(|| format!("failed to decode JSON from {}", input.display()))
+        }
+        _ => {
+            let (resolve, id) = features.parse_wit(input)?;
+            Ok(DecodedWasm::WitPackage(resolve, id))
+        }
+    }
+}
+
+/// Returns whether `bytes` looks like a JSON document, which is always an
+/// object as produced by `component wit --json`.
+///
+/// WIT documents can't start with `{` so there's no ambiguity here.
+fn is_json(bytes: &[u8]) -> bool {
+    bytes
+        .iter()
+        .find(|b| !b.is_ascii_whitespace())
+        .is_some_and(|b| *b == b'{')
+}
+
+fn decode_json(bytes: &[u8]) -> Result<DecodedWasm> {
+    let resolve: Resolve = serde_json::from_slice(bytes)?;
+    let id = match resolve.packages.iter().next_back() {
+        Some((id, _)) => id,
+        None => bail!("JSON document does not contain any packages"),
+    };
+    Ok(DecodedWasm::WitPackage(resolve, id))
+}
+
 fn decode_wasm(bytes: &[u8]) -> Result<DecodedWasm> {
     if wasmparser::Parser::is_component(bytes) {
         wit_component::decode(bytes)
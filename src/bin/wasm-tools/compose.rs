@@ -38,6 +38,11 @@ pub struct Opts {
     #[clap(long = "no-imports")]
     disallow_imports: bool,
 
+    /// Deduplicate byte-identical core modules and components in the composed
+    /// output component.
+    #[clap(long)]
+    dedupe: bool,
+
     /// The path to the root component to compose.
     #[clap(value_name = "COMPONENT")]
     component: PathBuf,
@@ -115,6 +120,7 @@ impl Opts {
             .extend(self.paths.iter().map(|p| cur_dir.join(p)));
         config.skip_validation |= self.skip_validation;
         config.disallow_imports |= self.disallow_imports;
+        config.dedupe |= self.dedupe;
         Ok(config)
     }
 }
@@ -0,0 +1,71 @@
+use crate::validate::parse_features;
+use anyhow::{bail, Result};
+use std::io::Write;
+use wasmparser::WasmFeatures;
+
+/// Print the WebAssembly features a binary requires
+///
+/// This subcommand will determine the minimal set of WebAssembly feature
+/// proposals that must be enabled for a module or component to validate. Each
+/// required feature is printed along with where it's first used, which is the
+/// index of the function whose body uses it, if any, and the byte offset of
+/// its use within the input.
+///
+/// Examples:
+///
+/// ```sh
+/// # Print the features required by `foo.wasm`.
+/// $ wasm-tools features foo.wasm
+///
+/// # Check that `foo.wasm` doesn't use anything beyond the MVP.
+/// $ wasm-tools features foo.wasm --target=-all,mutable-global,floats
+/// ```
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// Comma-separated list of WebAssembly features supported by the target
+    /// engine, using the same syntax as `validate --features`.
+    ///
+    /// If this is specified then an error is returned if the input requires
+    /// any features which aren't in this list.
+    #[clap(long, value_parser = parse_features)]
+    target: Option<WasmFeatures>,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+}
+
+impl Opts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let wasm = self.io.parse_input_wasm()?;
+        let required = wasmparser::required_features(&wasm)?;
+
+        let mut output = self.io.output_writer()?;
+        for feature_use in required.uses.iter() {
+            write!(output, "{}: ", feature_use.feature)?;
+            if let Some(func) = feature_use.func {
+                write!(output, "func {func} ")?;
+            }
+            writeln!(output, "at offset 0x{:x}", feature_use.offset)?;
+        }
+
+        if let Some(target) = &self.target {
+            let missing = required
+                .uses
+                .iter()
+                .map(|u| u.feature)
+                .filter(|name| target.get(name) == Some(false))
+                .collect::<Vec<_>>();
+            if !missing.is_empty() {
+                bail!(
+                    "input requires features not supported by the target: {}",
+                    missing.join(", ")
+                );
+            }
+        }
+        Ok(())
+    }
+}
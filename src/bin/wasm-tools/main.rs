@@ -54,6 +54,7 @@ macro_rules! subcommands {
 subcommands! {
     (parse, "parse")
     (validate, "validate")
+    (features, "features")
     (print, "print")
     (smith, "smith")
     // The shrink subcommand relies on executing new processes to test a
@@ -63,6 +64,7 @@ subcommands! {
     (mutate, "mutate")
     (dump, "dump")
     (objdump, "objdump")
+    (size, "size")
     (strip, "strip")
     (compose, "compose")
     (demangle, "demangle")
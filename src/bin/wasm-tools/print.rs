@@ -17,6 +17,17 @@ pub struct Opts {
     /// replaced with "..." instead of printing their actual contents.
     #[clap(long)]
     skeleton: bool,
+
+    /// Whether or not to print function bodies in the folded S-expression
+    /// form, nesting instructions inside of the instructions which consume
+    /// their results.
+    #[clap(long)]
+    fold: bool,
+
+    /// Whether or not to print the types on the operand stack after each
+    /// instruction as comments, validating the module alongside printing.
+    #[clap(long)]
+    stack_types: bool,
 }
 
 impl Opts {
@@ -29,6 +40,8 @@ impl Opts {
         let mut printer = wasmprinter::Printer::new();
         printer.print_offsets(self.print_offsets);
         printer.print_skeleton(self.skeleton);
+        printer.fold_instructions(self.fold);
+        printer.print_stack_types(self.stack_types);
         let wat = printer.print(&wasm)?;
         self.io.output(wasm_tools::Output::Wat(&wat))?;
         Ok(())
@@ -0,0 +1,759 @@
+use anyhow::{bail, Result};
+use serde_derive::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::Write;
+use wasm_tools::Output;
+use wasmparser::*;
+
+/// Profile the code size of a WebAssembly module
+///
+/// This subcommand attributes every byte of a core wasm module to an item
+/// within it, such as a function, data segment, type, export or custom
+/// section. The bytes of section headers are attributed to their section.
+///
+/// Items refer to each other, for example a function refers to the functions
+/// it calls and the globals it uses, and exports and custom sections are the
+/// roots of these references. From this graph a dominator tree is computed
+/// where the "retained" size of an item is the number of bytes which would be
+/// removed along with it, because nothing else refers to them.
+///
+/// Examples:
+///
+/// ```sh
+/// # Print the 20 items in `foo.wasm` retaining the most bytes.
+/// $ wasm-tools size foo.wasm
+///
+/// # Print all items in `foo.wasm` as JSON.
+/// $ wasm-tools size foo.wasm --top 0 --json
+///
+/// # Print what's keeping the function named `malloc` alive.
+/// $ wasm-tools size foo.wasm --paths malloc
+/// ```
+#[derive(clap::Parser)]
+pub struct Opts {
+    /// Number of items to print, sorted by their retained size, or 0 to print
+    /// all items.
+    #[clap(long, short = 'n', default_value_t = 20)]
+    top: usize,
+
+    /// Print the chains of items that refer to ITEM instead of the largest
+    /// items.
+    ///
+    /// ITEM can either be the name of an item as printed by this command, such
+    /// as `func[3]` or `export "main"`, or the name of an item in the name
+    /// section, such as `malloc`.
+    #[clap(long, value_name = "ITEM")]
+    paths: Option<String>,
+
+    /// Emit the results as JSON instead of as a table.
+    #[clap(long)]
+    json: bool,
+
+    #[clap(flatten)]
+    io: wasm_tools::InputOutput,
+}
+
+impl Opts {
+    pub fn general_opts(&self) -> &wasm_tools::GeneralOpts {
+        self.io.general_opts()
+    }
+
+    pub fn run(&self) -> Result<()> {
+        let wasm = self.io.parse_input_wasm()?;
+        let mut graph = Graph::default();
+        graph.parse(&wasm)?;
+        let sizes = graph.retained_sizes();
+
+        match &self.paths {
+            Some(name) => self.print_paths(&graph, name),
+            None => self.print_top(&graph, &sizes, wasm.len()),
+        }
+    }
+
+    fn print_top(&self, graph: &Graph, sizes: &Sizes, total: usize) -> Result<()> {
+        let mut items = (1..graph.items.len()).collect::<Vec<_>>();
+        items.sort_by_key(|i| (std::cmp::Reverse(sizes.retained[*i]), *i));
+        if self.top > 0 {
+            items.truncate(self.top);
+        }
+
+        if self.json {
+            let items = items
+                .iter()
+                .map(|i| JsonItem {
+                    name: graph.items[*i].to_string(),
+                    kind: graph.items[*i].kind,
+                    index: graph.items[*i].index,
+                    shallow_size: graph.items[*i].size,
+                    retained_size: sizes.retained[*i],
+                    dominator: sizes.idom[*i]
+                        .filter(|d| *d != ROOT)
+                        .map(|d| graph.items[d].to_string()),
+                })
+                .collect::<Vec<_>>();
+            let output = serde_json::to_string_pretty(&items)?;
+            return self.io.output(Output::Json(&format!("{output}\n")));
+        }
+
+        let mut output = self.io.output_writer()?;
+        writeln!(output, "{:>10} {:>8} {:>10}  item", "retained", "%", "shallow")?;
+        for i in items {
+            let retained = sizes.retained[i];
+            writeln!(
+                output,
+                "{retained:>10} {:>7.2}% {:>10}  {}",
+                retained as f64 * 100.0 / total as f64,
+                graph.items[i].size,
+                graph.items[i],
+            )?;
+        }
+        writeln!(output, "{total:>10} {:>7.2}% {:>10}  total", 100.0, "")?;
+        Ok(())
+    }
+
+    fn print_paths(&self, graph: &Graph, name: &str) -> Result<()> {
+        let item = match graph.find(name) {
+            Some(item) => item,
+            None => bail!("failed to find an item named `{name}`"),
+        };
+        let mut referrers = vec![Vec::new(); graph.items.len()];
+        for (from, edges) in graph.edges.iter().enumerate() {
+            for to in edges {
+                if !referrers[*to].contains(&from) {
+                    referrers[*to].push(from);
+                }
+            }
+        }
+
+        let mut seen = HashSet::new();
+        let path = path_to(graph, &referrers, item, &mut seen);
+        if self.json {
+            let output = serde_json::to_string_pretty(&path)?;
+            return self.io.output(Output::Json(&format!("{output}\n")));
+        }
+
+        let mut output = self.io.output_writer()?;
+        let mut stack = vec![(0, &path)];
+        while let Some((depth, path)) = stack.pop() {
+            writeln!(output, "{:depth$}{}", "", path.name, depth = depth * 2)?;
+            for referrer in path.referrers.iter().rev() {
+                stack.push((depth + 1, referrer));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Builds the tree of items which refer to `item`, listing each item at most
+/// once.
+fn path_to(
+    graph: &Graph,
+    referrers: &[Vec<usize>],
+    item: usize,
+    seen: &mut HashSet<usize>,
+) -> JsonPath {
+    let mut path = JsonPath {
+        name: graph.items[item].to_string(),
+        referrers: Vec::new(),
+    };
+    if !seen.insert(item) {
+        return path;
+    }
+    for referrer in referrers[item].iter() {
+        if *referrer != ROOT {
+            path.referrers
+                .push(path_to(graph, referrers, *referrer, seen));
+        }
+    }
+    path
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct JsonItem {
+    name: String,
+    kind: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<u32>,
+    shallow_size: usize,
+    retained_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dominator: Option<String>,
+}
+
+#[derive(Serialize)]
+struct JsonPath {
+    name: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    referrers: Vec<JsonPath>,
+}
+
+/// The index of the synthetic root item in a [`Graph`], which refers to all
+/// items that are live by definition such as exports.
+const ROOT: usize = 0;
+
+/// A graph of the items within a module along with the references between
+/// them.
+#[derive(Default)]
+struct Graph {
+    items: Vec<Item>,
+    edges: Vec<Vec<usize>>,
+
+    // Index spaces of the module, mapping indices to items.
+    types: Vec<usize>,
+    funcs: Vec<usize>,
+    tables: Vec<usize>,
+    memories: Vec<usize>,
+    globals: Vec<usize>,
+    tags: Vec<usize>,
+    elems: Vec<usize>,
+    datas: Vec<usize>,
+    num_imported_funcs: usize,
+    num_code_entries: usize,
+
+    /// References found while parsing, which are resolved once the whole
+    /// module has been parsed since they may refer to items defined later.
+    refs: Vec<(usize, ItemRef)>,
+    /// Items created for each section's header, keyed by section name.
+    sections: HashMap<&'static str, usize>,
+    /// The offset at which the previous section ended.
+    pos: usize,
+}
+
+struct Item {
+    kind: &'static str,
+    index: Option<u32>,
+    name: Option<String>,
+    size: usize,
+}
+
+impl std::fmt::Display for Item {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(index) = self.index {
+            write!(f, "[{index}]")?;
+        }
+        if let Some(name) = &self.name {
+            match self.index {
+                Some(_) => write!(f, " {name}")?,
+                None => write!(f, " {name:?}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    fn parse(&mut self, wasm: &[u8]) -> Result<()> {
+        self.push("root", None, None);
+        for payload in Parser::new(0).parse_all(wasm) {
+            match payload? {
+                Payload::Version {
+                    encoding, range, ..
+                } => {
+                    if encoding != Encoding::Module {
+                        bail!("size profiling is only supported for core wasm modules");
+                    }
+                    self.section_header("header", range.end);
+                }
+
+                Payload::TypeSection(s) => {
+                    for (size, group) in self.section_items("type", s)? {
+                        let item = self.push("type", Some(self.types.len() as u32), None);
+                        self.items[item].size = size;
+                        for ty in group.into_types() {
+                            self.types.push(item);
+                            if let Some(i) = ty.supertype_idx.and_then(|i| i.as_module_index()) {
+                                self.refs.push((item, ItemRef::Type(i)));
+                            }
+                            match &ty.composite_type {
+                                CompositeType::Func(f) => {
+                                    for ty in f.params().iter().chain(f.results()) {
+                                        self.val_type(item, *ty);
+                                    }
+                                }
+                                CompositeType::Array(a) => self.storage_type(item, a.0.element_type),
+                                CompositeType::Struct(s) => {
+                                    for field in s.fields.iter() {
+                                        self.storage_type(item, field.element_type);
+                                    }
+                                }
+                                CompositeType::Cont(c) => {
+                                    if let Some(i) = c.0.as_module_index() {
+                                        self.refs.push((item, ItemRef::Type(i)));
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+                Payload::ImportSection(s) => {
+                    for (size, import) in self.section_items("import", s)? {
+                        let name = format!("{}::{}", import.module, import.name);
+                        let item = match import.ty {
+                            TypeRef::Func(ty) => {
+                                self.num_imported_funcs += 1;
+                                let item = self.push_func(Some(name));
+                                self.refs.push((item, ItemRef::Type(ty)));
+                                item
+                            }
+                            TypeRef::Table(ty) => self.push_table(Some(name), &ty),
+                            TypeRef::Memory(_) => self.push_memory(Some(name)),
+                            TypeRef::Global(ty) => self.push_global(Some(name), ty),
+                            TypeRef::Tag(ty) => self.push_tag(Some(name), ty),
+                        };
+                        self.items[item].size = size;
+                    }
+                }
+                Payload::FunctionSection(s) => {
+                    for (size, ty) in self.section_items("function", s)? {
+                        let item = self.push_func(None);
+                        self.items[item].size = size;
+                        self.refs.push((item, ItemRef::Type(ty)));
+                    }
+                }
+                Payload::TableSection(s) => {
+                    for (size, table) in self.section_items("table", s)? {
+                        let item = self.push_table(None, &table.ty);
+                        self.items[item].size = size;
+                        if let TableInit::Expr(expr) = &table.init {
+                            self.const_expr(item, expr)?;
+                        }
+                    }
+                }
+                Payload::MemorySection(s) => {
+                    for (size, _) in self.section_items("memory", s)? {
+                        let item = self.push_memory(None);
+                        self.items[item].size = size;
+                    }
+                }
+                Payload::TagSection(s) => {
+                    for (size, ty) in self.section_items("tag", s)? {
+                        let item = self.push_tag(None, ty);
+                        self.items[item].size = size;
+                    }
+                }
+                Payload::GlobalSection(s) => {
+                    for (size, global) in self.section_items("global", s)? {
+                        let item = self.push_global(None, global.ty);
+                        self.items[item].size = size;
+                        self.const_expr(item, &global.init_expr)?;
+                    }
+                }
+                Payload::ExportSection(s) => {
+                    for (size, export) in self.section_items("export", s)? {
+                        let item = self.push("export", None, Some(export.name.to_string()));
+                        self.items[item].size = size;
+                        self.edges[ROOT].push(item);
+                        let r = match export.kind {
+                            ExternalKind::Func => ItemRef::Func(export.index),
+                            ExternalKind::Table => ItemRef::Table(export.index),
+                            ExternalKind::Memory => ItemRef::Memory(export.index),
+                            ExternalKind::Global => ItemRef::Global(export.index),
+                            ExternalKind::Tag => ItemRef::Tag(export.index),
+                        };
+                        self.refs.push((item, r));
+                    }
+                }
+                Payload::StartSection { func, range } => {
+                    let item = self.push("start", None, None);
+                    self.items[item].size = range.end - self.pos;
+                    self.pos = range.end;
+                    self.edges[ROOT].push(item);
+                    self.refs.push((item, ItemRef::Func(func)));
+                }
+                Payload::ElementSection(s) => {
+                    for (size, elem) in self.section_items("elem", s)? {
+                        let item = self.push("elem", Some(self.elems.len() as u32), None);
+                        self.items[item].size = size;
+                        self.elems.push(item);
+                        match &elem.kind {
+                            ElementKind::Active {
+                                table_index,
+                                offset_expr,
+                            } => {
+                                // Active segments are applied when the module
+                                // is instantiated so they're always live.
+                                self.edges[ROOT].push(item);
+                                self.refs
+                                    .push((item, ItemRef::Table(table_index.unwrap_or(0))));
+                                self.const_expr(item, offset_expr)?;
+                            }
+                            ElementKind::Passive | ElementKind::Declared => {}
+                        }
+                        match elem.items {
+                            ElementItems::Functions(funcs) => {
+                                for func in funcs {
+                                    self.refs.push((item, ItemRef::Func(func?)));
+                                }
+                            }
+                            ElementItems::Expressions(ty, exprs) => {
+                                self.val_type(item, ValType::Ref(ty));
+                                for expr in exprs {
+                                    self.const_expr(item, &expr?)?;
+                                }
+                            }
+                        }
+                    }
+                }
+                Payload::DataCountSection { range, .. } => {
+                    self.section_header("data count", range.end);
+                }
+                Payload::DataSection(s) => {
+                    for (size, data) in self.section_items("data", s)? {
+                        let item = self.push("data", Some(self.datas.len() as u32), None);
+                        self.items[item].size = size;
+                        self.datas.push(item);
+                        if let DataKind::Active {
+                            memory_index,
+                            offset_expr,
+                        } = &data.kind
+                        {
+                            self.edges[ROOT].push(item);
+                            self.refs.push((item, ItemRef::Memory(*memory_index)));
+                            self.const_expr(item, offset_expr)?;
+                        }
+                    }
+                }
+                Payload::CodeSectionStart { range, .. } => {
+                    let mut reader = BinaryReader::new_with_offset(&wasm[range.clone()], range.start);
+                    reader.read_var_u32()?;
+                    self.section_header("code", reader.original_position());
+                }
+                Payload::CodeSectionEntry(body) => {
+                    let func = self.num_imported_funcs + self.num_code_entries;
+                    self.num_code_entries += 1;
+                    let item = match self.funcs.get(func) {
+                        Some(item) => *item,
+                        None => bail!("code section entry without a function"),
+                    };
+                    self.items[item].size += body.range().end - self.pos;
+                    self.pos = body.range().end;
+                    self.func_body(item, &body)?;
+                }
+
+                Payload::CustomSection(s) => {
+                    let item = self.push("custom", None, Some(s.name().to_string()));
+                    self.items[item].size = s.range().end - self.pos;
+                    self.pos = s.range().end;
+                    self.edges[ROOT].push(item);
+                    if s.name() == "name" {
+                        // Names are only used for display, so ignore errors
+                        // in the name section.
+                        drop(self.parse_name_section(&s));
+                    }
+                }
+                Payload::UnknownSection { range, .. } => self.section_header("unknown", range.end),
+
+                Payload::End(offset) if offset != self.pos => {
+                    self.section_header("unknown", offset);
+                }
+
+                // Component payloads are rejected above when the header is
+                // parsed, and an `End` without trailing bytes has nothing to
+                // record.
+                _ => {}
+            }
+        }
+
+        self.resolve_refs();
+        Ok(())
+    }
+
+    fn push(&mut self, kind: &'static str, index: Option<u32>, name: Option<String>) -> usize {
+        self.items.push(Item {
+            kind,
+            index,
+            name,
+            size: 0,
+        });
+        self.edges.push(Vec::new());
+        self.items.len() - 1
+    }
+
+    fn push_func(&mut self, name: Option<String>) -> usize {
+        let item = self.push("func", Some(self.funcs.len() as u32), name);
+        self.funcs.push(item);
+        item
+    }
+
+    fn push_table(&mut self, name: Option<String>, ty: &TableType) -> usize {
+        let item = self.push("table", Some(self.tables.len() as u32), name);
+        self.tables.push(item);
+        self.val_type(item, ValType::Ref(ty.element_type));
+        item
+    }
+
+    fn push_memory(&mut self, name: Option<String>) -> usize {
+        let item = self.push("memory", Some(self.memories.len() as u32), name);
+        self.memories.push(item);
+        item
+    }
+
+    fn push_global(&mut self, name: Option<String>, ty: GlobalType) -> usize {
+        let item = self.push("global", Some(self.globals.len() as u32), name);
+        self.globals.push(item);
+        self.val_type(item, ty.content_type);
+        item
+    }
+
+    fn push_tag(&mut self, name: Option<String>, ty: TagType) -> usize {
+        let item = self.push("tag", Some(self.tags.len() as u32), name);
+        self.tags.push(item);
+        self.refs.push((item, ItemRef::Type(ty.func_type_idx)));
+        item
+    }
+
+    /// Attributes the bytes from the end of the previous section to `end` to
+    /// the header of the section `name`.
+    fn section_header(&mut self, name: &'static str, end: usize) {
+        let item = match self.sections.get(name) {
+            Some(item) => *item,
+            None => {
+                let item = self.push("section", None, Some(name.to_string()));
+                self.edges[ROOT].push(item);
+                self.sections.insert(name, item);
+                item
+            }
+        };
+        self.items[item].size += end - self.pos;
+        self.pos = end;
+    }
+
+    /// Returns each item in the section `reader` along with its size in bytes,
+    /// attributing the section's header to the section `name`.
+    fn section_items<'a, T>(
+        &mut self,
+        name: &'static str,
+        reader: SectionLimited<'a, T>,
+    ) -> Result<Vec<(usize, T)>>
+    where
+        T: FromReader<'a>,
+    {
+        let end = reader.range().end;
+        let mut items = Vec::new();
+        let mut offsets = Vec::new();
+        for item in reader.into_iter_with_offsets() {
+            let (offset, item) = item?;
+            offsets.push(offset);
+            items.push(item);
+        }
+        self.section_header(name, offsets.first().copied().unwrap_or(end));
+        offsets.push(end);
+        self.pos = end;
+        Ok(offsets
+            .windows(2)
+            .map(|w| w[1] - w[0])
+            .zip(items)
+            .collect())
+    }
+
+    fn parse_name_section(&mut self, section: &CustomSectionReader<'_>) -> Result<()> {
+        let section = NameSectionReader::new(section.data(), section.data_offset());
+        for s in section {
+            let (map, space) = match s? {
+                Name::Function(map) => (map, &self.funcs),
+                Name::Type(map) => (map, &self.types),
+                Name::Table(map) => (map, &self.tables),
+                Name::Memory(map) => (map, &self.memories),
+                Name::Global(map) => (map, &self.globals),
+                Name::Element(map) => (map, &self.elems),
+                Name::Data(map) => (map, &self.datas),
+                _ => continue,
+            };
+            let mut names = Vec::new();
+            for naming in map {
+                let naming = naming?;
+                if let Some(item) = space.get(naming.index as usize) {
+                    names.push((*item, naming.name.to_string()));
+                }
+            }
+            for (item, name) in names {
+                self.items[item].name = Some(name);
+            }
+        }
+        Ok(())
+    }
+
+    fn val_type(&mut self, item: usize, ty: ValType) {
+        if let ValType::Ref(r) = ty {
+            self.heap_type(item, r.heap_type());
+        }
+    }
+
+    fn heap_type(&mut self, item: usize, ty: HeapType) {
+        if let HeapType::Concrete(i) = ty {
+            if let Some(i) = i.as_module_index() {
+                self.refs.push((item, ItemRef::Type(i)));
+            }
+        }
+    }
+
+    fn storage_type(&mut self, item: usize, ty: StorageType) {
+        if let StorageType::Val(ty) = ty {
+            self.val_type(item, ty);
+        }
+    }
+
+    fn const_expr(&mut self, item: usize, expr: &ConstExpr<'_>) -> Result<()> {
+        self.operators(item, expr.get_operators_reader())
+    }
+
+    fn func_body(&mut self, item: usize, body: &FunctionBody<'_>) -> Result<()> {
+        for local in body.get_locals_reader()? {
+            let (_, ty) = local?;
+            self.val_type(item, ty);
+        }
+        let mut reader = body.get_operators_reader()?;
+        reader.allow_memarg64(true);
+        self.operators(item, reader)
+    }
+
+    fn operators(&mut self, item: usize, mut reader: OperatorsReader<'_>) -> Result<()> {
+        let mut visitor = ItemRefVisitor::new(|r| self.refs.push((item, r)));
+        while !reader.eof() {
+            reader.visit_operator(&mut visitor)?;
+        }
+        Ok(())
+    }
+
+    /// Turns the references found while parsing into edges of the graph.
+    fn resolve_refs(&mut self) {
+        for (from, r) in std::mem::take(&mut self.refs) {
+            let (space, index) = match r {
+                ItemRef::Type(i) => (&self.types, i),
+                ItemRef::Func(i) => (&self.funcs, i),
+                ItemRef::Table(i) => (&self.tables, i),
+                ItemRef::Memory(i) => (&self.memories, i),
+                ItemRef::Global(i) => (&self.globals, i),
+                ItemRef::Tag(i) => (&self.tags, i),
+                ItemRef::Elem(i) => (&self.elems, i),
+                ItemRef::Data(i) => (&self.datas, i),
+            };
+            if let Some(to) = space.get(index as usize) {
+                if *to != from && !self.edges[from].contains(to) {
+                    self.edges[from].push(*to);
+                }
+            }
+        }
+    }
+
+    /// Finds the item called `name`, either as it's displayed or by its name
+    /// in the name section.
+    fn find(&self, name: &str) -> Option<usize> {
+        (1..self.items.len()).find(|i| {
+            let item = &self.items[*i];
+            item.to_string() == name
+                || item.name.as_deref() == Some(name)
+                || item.index.is_some_and(|idx| format!("{}[{idx}]", item.kind) == name)
+        })
+    }
+
+    /// Computes the dominator tree of this graph along with the retained size
+    /// of each item.
+    ///
+    /// Items which aren't reachable from the root are first made roots
+    /// themselves so that every byte is accounted for.
+    fn retained_sizes(&mut self) -> Sizes {
+        let mut order = self.postorder();
+        if order.len() < self.items.len() {
+            let mut reachable = vec![false; self.items.len()];
+            for i in order.iter() {
+                reachable[*i] = true;
+            }
+            for (i, reachable) in reachable.iter().enumerate() {
+                if !reachable {
+                    self.edges[ROOT].push(i);
+                }
+            }
+            order = self.postorder();
+        }
+
+        // Compute immediate dominators with the iterative algorithm from "A
+        // Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy.
+        let mut postorder_index = vec![0; self.items.len()];
+        for (i, item) in order.iter().enumerate() {
+            postorder_index[*item] = i;
+        }
+        let mut preds = vec![Vec::new(); self.items.len()];
+        for (from, edges) in self.edges.iter().enumerate() {
+            for to in edges {
+                preds[*to].push(from);
+            }
+        }
+        let mut idom: Vec<Option<usize>> = vec![None; self.items.len()];
+        idom[ROOT] = Some(ROOT);
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for item in order.iter().rev().skip(1) {
+                let mut new_idom: Option<usize> = None;
+                for pred in preds[*item].iter() {
+                    if idom[*pred].is_none() {
+                        continue;
+                    }
+                    new_idom = Some(match new_idom {
+                        None => *pred,
+                        Some(mut a) => {
+                            let mut b = *pred;
+                            while a != b {
+                                while postorder_index[a] < postorder_index[b] {
+                                    a = idom[a].unwrap();
+                                }
+                                while postorder_index[b] < postorder_index[a] {
+                                    b = idom[b].unwrap();
+                                }
+                            }
+                            a
+                        }
+                    });
+                }
+                if idom[*item] != new_idom {
+                    idom[*item] = new_idom;
+                    changed = true;
+                }
+            }
+        }
+
+        // Items come after their dominators in reverse postorder, so summing
+        // up sizes in postorder visits each item's children first.
+        let mut retained = self.items.iter().map(|i| i.size).collect::<Vec<_>>();
+        for item in order.iter() {
+            if *item == ROOT {
+                continue;
+            }
+            let dominator = idom[*item].unwrap();
+            retained[dominator] += retained[*item];
+        }
+        idom[ROOT] = None;
+        Sizes { retained, idom }
+    }
+
+    /// Returns the items reachable from the root in postorder.
+    fn postorder(&self) -> Vec<usize> {
+        let mut order = Vec::new();
+        let mut visited = vec![false; self.items.len()];
+        let mut stack = vec![(ROOT, 0)];
+        visited[ROOT] = true;
+        while let Some((item, next)) = stack.pop() {
+            match self.edges[item].get(next) {
+                Some(child) => {
+                    stack.push((item, next + 1));
+                    if !visited[*child] {
+                        visited[*child] = true;
+                        stack.push((*child, 0));
+                    }
+                }
+                None => order.push(item),
+            }
+        }
+        order
+    }
+}
+
+struct Sizes {
+    /// The retained size of each item.
+    retained: Vec<usize>,
+    /// The immediate dominator of each item, which is `None` for the root.
+    idom: Vec<Option<usize>>,
+}
@@ -158,6 +158,12 @@ struct Config {
     #[clap(long = "exception-handling")]
     #[serde(rename = "exception-handling")]
     exceptions_enabled: Option<bool>,
+    #[clap(long = "function-references")]
+    #[serde(rename = "function-references")]
+    function_references_enabled: Option<bool>,
+    #[clap(long = "gc")]
+    #[serde(rename = "gc")]
+    gc_enabled: Option<bool>,
     #[clap(long = "allow-start")]
     #[serde(rename = "allow-start")]
     allow_start_export: Option<bool>,
@@ -184,7 +190,7 @@ struct Config {
     /// Limit what kinds of instructions are allowed.
     ///
     /// By default, all kinds are allowed; available kinds: numeric, vector,
-    /// reference, parametric, variable, table, memory, control. Specify
+    /// reference, parametric, variable, table, memory, control, aggregate. Specify
     /// multiple kinds with a comma-separated list: e.g.,
     /// `--allowed-instructions numeric,control,parametric`
     #[clap(long = "allowed-instructions", use_value_delimiter = true)]
@@ -307,6 +313,8 @@ impl wasm_smith::Config for CliAndJsonConfig {
         (simd_enabled, bool, true),
         (relaxed_simd_enabled, bool, false),
         (exceptions_enabled, bool, false),
+        (function_references_enabled, bool, false),
+        (gc_enabled, bool, false),
         (multi_value_enabled, bool, true),
         (saturating_float_to_int_enabled, bool, true),
         (sign_extension_ops_enabled, bool, true),
@@ -7,7 +7,8 @@ use wasmparser::{Parser, Payload::*};
 ///
 /// This command will by default strip all custom sections such as DWARF
 /// debugging information from a wasm file. It will not strip the `name` section
-/// by default unless the `--all` flag is passed.
+/// or the `metadata.code.branch_hint` section by default unless the `--all`
+/// flag is passed.
 #[derive(clap::Parser)]
 pub struct Opts {
     #[clap(flatten)]
@@ -46,8 +47,10 @@ impl Opts {
                 return to_delete.is_match(name);
             }
 
-            // Finally default strip everything but the `name` section.
-            name != "name"
+            // Finally default strip everything but the `name` section and
+            // branch hints, which are kept in sync with the function bodies
+            // here since those are copied over as-is.
+            name != "name" && name != "metadata.code.branch_hint"
         };
 
         let mut output = Vec::new();
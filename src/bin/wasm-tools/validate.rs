@@ -1,8 +1,15 @@
-use anyhow::{anyhow, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use rayon::prelude::*;
+use serde_derive::Serialize;
+use std::fmt::Write;
 use std::mem;
+use std::path::PathBuf;
 use std::time::Instant;
-use wasmparser::{FuncValidatorAllocations, Parser, ValidPayload, Validator, WasmFeatures};
+use wasm_tools::Output;
+use wasmparser::{
+    BinaryReaderError, FuncToValidate, FuncValidatorAllocations, FunctionBody, Parser,
+    ValidPayload, Validator, ValidatorLimits, ValidatorResources, WasmFeatures,
+};
 
 /// Validate a WebAssembly binary
 ///
@@ -25,6 +32,12 @@ use wasmparser::{FuncValidatorAllocations, Parser, ValidPayload, Validator, Wasm
 ///
 /// # Validate `mvp.wasm` without any Wasm feature proposals enabled.
 /// $ wasm-tools validate --features=-all mvp.wasm
+///
+/// # Validate `foo.wasm` with the limits configured in `limits.txt`.
+/// $ wasm-tools validate --limits limits.txt foo.wasm
+///
+/// # Report every invalid function in `foo.wasm` as JSON.
+/// $ wasm-tools validate --json --keep-going foo.wasm
 /// ```
 #[derive(clap::Parser)]
 pub struct Opts {
@@ -39,6 +52,35 @@ pub struct Opts {
     #[clap(long, short = 'f', value_parser = parse_features)]
     features: Option<WasmFeatures>,
 
+    /// File containing limits to enforce on the size of the input, such as the
+    /// maximum number of functions or locals.
+    ///
+    /// Each line of the file has the form `name = value`, for example
+    /// `functions = 1000`, and lines starting with `#` are ignored. Limits
+    /// which aren't listed keep their default value. Available limits are the
+    /// fields of `ValidatorLimits` in the wasmparser crate, with underscores
+    /// replaced by dashes.
+    #[clap(long, value_name = "FILE")]
+    limits: Option<PathBuf>,
+
+    /// Emit validation errors as a JSON array instead of as text on stderr.
+    ///
+    /// Each error has a `kind` (such as `type-mismatch` or `unknown-index`), a
+    /// `message`, and the byte `offset` of the error in the input. Errors
+    /// within a function body additionally have the `function` index and the
+    /// `instruction-offset` of the error relative to the start of the body.
+    /// The array is empty if the input is valid.
+    #[clap(long)]
+    json: bool,
+
+    /// Continue validating the remaining functions after one fails to
+    /// validate, reporting all invalid functions instead of just the first.
+    ///
+    /// Errors in the structure of the module, outside of function bodies,
+    /// still stop validation immediately.
+    #[clap(long)]
+    keep_going: bool,
+
     #[clap(flatten)]
     io: wasm_tools::InputOutput,
 }
@@ -59,18 +101,23 @@ impl Opts {
         // `Validator` we're using as we navigate nested modules (the module
         // linking proposal) and any functions found are deferred to get
         // validated later.
-        let mut validator = Validator::new_with_features(self.features.unwrap_or_default());
+        let limits = match &self.limits {
+            Some(path) => {
+                let contents = std::fs::read_to_string(path)
+                    .with_context(|| format!("failed to read limits file {path:?}"))?;
+                parse_limits(&contents)
+                    .with_context(|| format!("failed to parse limits file {path:?}"))?
+            }
+            None => ValidatorLimits::default(),
+        };
+        let mut validator =
+            Validator::new_with_features_and_limits(self.features.unwrap_or_default(), limits);
         let mut functions_to_validate = Vec::new();
         let wasm = self.io.parse_input_wasm()?;
 
         let start = Instant::now();
-        for payload in Parser::new(0).parse_all(&wasm) {
-            match validator.payload(&payload?)? {
-                ValidPayload::Ok | ValidPayload::Parser(_) | ValidPayload::End(_) => {}
-                ValidPayload::Func(validator, body) => {
-                    functions_to_validate.push((validator, body))
-                }
-            }
+        if let Err(e) = validate_structure(&mut validator, &wasm, &mut functions_to_validate) {
+            return self.report(vec![Diagnostic::new(&e, None)]);
         }
         log::info!("module structure validated in {:?}", start.elapsed());
 
@@ -78,48 +125,116 @@ impl Opts {
         // over all functions in parallel and perform parallel validation of the
         // input wasm module.
         let start = Instant::now();
-        functions_to_validate.into_par_iter().try_for_each_init(
-            FuncValidatorAllocations::default,
-            |allocs, (to_validate, body)| -> Result<_> {
-                let mut validator = to_validate.into_validator(mem::take(allocs));
-                validator
-                    .validate(&body)
-                    .with_context(|| format!("func {} failed to validate", validator.index()))?;
-                *allocs = validator.into_allocations();
-                Ok(())
-            },
-        )?;
+        let validate_func = |allocs: &mut FuncValidatorAllocations,
+                             (to_validate, body): (
+            FuncToValidate<ValidatorResources>,
+            FunctionBody<'_>,
+        )|
+         -> Result<(), Diagnostic> {
+            let mut validator = to_validate.into_validator(mem::take(allocs));
+            validator
+                .validate(&body)
+                .map_err(|e| Diagnostic::new(&e, Some((validator.index(), &body))))?;
+            *allocs = validator.into_allocations();
+            Ok(())
+        };
+        let mut errors = if self.keep_going {
+            functions_to_validate
+                .into_par_iter()
+                .map_init(FuncValidatorAllocations::default, validate_func)
+                .filter_map(Result::err)
+                .collect()
+        } else {
+            match functions_to_validate
+                .into_par_iter()
+                .try_for_each_init(FuncValidatorAllocations::default, validate_func)
+            {
+                Ok(()) => Vec::new(),
+                Err(e) => vec![e],
+            }
+        };
+        errors.sort_by_key(|e| e.function);
         log::info!("functions validated in {:?}", start.elapsed());
-        Ok(())
+        self.report(errors)
+    }
+
+    /// Reports `errors`, returning an error if there are any.
+    fn report(&self, errors: Vec<Diagnostic>) -> Result<()> {
+        if self.json {
+            let output = serde_json::to_string_pretty(&errors)?;
+            self.io.output(Output::Json(&format!("{output}\n")))?;
+            if !errors.is_empty() {
+                bail!("input failed to validate");
+            }
+            return Ok(());
+        }
+        match errors.as_slice() {
+            [] => Ok(()),
+            [error] => Err(error.to_error()),
+            errors => {
+                let mut msg = format!("{} functions failed to validate:", errors.len());
+                for error in errors {
+                    write!(msg, "\n  {:#}", error.to_error())?;
+                }
+                bail!("{msg}")
+            }
+        }
     }
 }
 
-fn parse_features(arg: &str) -> Result<WasmFeatures> {
-    let mut ret = WasmFeatures::default();
+/// Validates everything in `wasm` except function bodies, which are pushed
+/// onto `functions` to get validated later.
+fn validate_structure<'a>(
+    validator: &mut Validator,
+    wasm: &'a [u8],
+    functions: &mut Vec<(FuncToValidate<ValidatorResources>, FunctionBody<'a>)>,
+) -> wasmparser::Result<()> {
+    for payload in Parser::new(0).parse_all(wasm) {
+        match validator.payload(&payload?)? {
+            ValidPayload::Ok | ValidPayload::Parser(_) | ValidPayload::End(_) => {}
+            ValidPayload::Func(validator, body) => functions.push((validator, body)),
+        }
+    }
+    Ok(())
+}
 
-    const FEATURES: &[(&str, fn(&mut WasmFeatures) -> &mut bool)] = &[
-        ("reference-types", |f| &mut f.reference_types),
-        ("function-references", |f| &mut f.function_references),
-        ("simd", |f| &mut f.simd),
-        ("threads", |f| &mut f.threads),
-        ("bulk-memory", |f| &mut f.bulk_memory),
-        ("multi-value", |f| &mut f.multi_value),
-        ("tail-call", |f| &mut f.tail_call),
-        ("component-model", |f| &mut f.component_model),
-        ("component-model-values", |f| &mut f.component_model_values),
-        ("multi-memory", |f| &mut f.multi_memory),
-        ("exception-handling", |f| &mut f.exceptions),
-        ("memory64", |f| &mut f.memory64),
-        ("extended-const", |f| &mut f.extended_const),
-        ("floats", |f| &mut f.floats),
-        ("saturating-float-to-int", |f| {
-            &mut f.saturating_float_to_int
-        }),
-        ("sign-extension", |f| &mut f.sign_extension),
-        ("mutable-global", |f| &mut f.mutable_global),
-        ("relaxed-simd", |f| &mut f.relaxed_simd),
-        ("gc", |f| &mut f.gc),
-    ];
+/// A single validation error, as reported by `--json`.
+#[derive(Serialize)]
+#[serde(rename_all = "kebab-case")]
+struct Diagnostic {
+    kind: &'static str,
+    message: String,
+    offset: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    function: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    instruction_offset: Option<usize>,
+}
+
+impl Diagnostic {
+    fn new(error: &BinaryReaderError, func: Option<(u32, &FunctionBody<'_>)>) -> Diagnostic {
+        Diagnostic {
+            kind: error.kind().as_str(),
+            message: error.message().to_string(),
+            offset: error.offset(),
+            function: func.map(|(index, _)| index),
+            instruction_offset: func.map(|(_, body)| error.offset() - body.range().start),
+        }
+    }
+
+    fn to_error(&self) -> anyhow::Error {
+        let error = anyhow!("{} (at offset 0x{:x})", self.message, self.offset);
+        match self.function {
+            Some(index) => error.context(format!("func {index} failed to validate")),
+            None => error,
+        }
+    }
+}
+
+/// Parses a comma-separated list of features such as `all,-simd`, as accepted
+/// by `--features`.
+pub fn parse_features(arg: &str) -> Result<WasmFeatures> {
+    let mut ret = WasmFeatures::default();
 
     for part in arg.split(',').map(|s| s.trim()).filter(|s| !s.is_empty()) {
         let (enable, part) = if let Some(part) = part.strip_prefix("-") {
@@ -129,32 +244,83 @@ fn parse_features(arg: &str) -> Result<WasmFeatures> {
         };
         match part {
             "all" => {
-                for (name, accessor) in FEATURES {
-                    // don't count this under "all" for now.
-                    if *name == "deterministic" {
-                        continue;
-                    }
-
-                    *accessor(&mut ret) = enable;
+                for name in WasmFeatures::names() {
+                    *ret.get_mut(name).unwrap() = enable;
                 }
             }
 
             name => {
-                let (_, accessor) = FEATURES.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+                let flag = ret.get_mut(name).ok_or_else(|| {
                     anyhow!(
                         "unknown feature `{}`\nValid features: {}",
                         name,
-                        FEATURES
-                            .iter()
-                            .map(|(name, _)| *name)
-                            .collect::<Vec<_>>()
-                            .join(", "),
+                        WasmFeatures::names().collect::<Vec<_>>().join(", "),
                     )
                 })?;
-                *accessor(&mut ret) = enable;
+                *flag = enable;
             }
         }
     }
 
     Ok(ret)
 }
+
+fn parse_limits(contents: &str) -> Result<ValidatorLimits> {
+    let mut ret = ValidatorLimits::default();
+
+    enum Limit {
+        Count(fn(&mut ValidatorLimits) -> &mut usize),
+        Pages(fn(&mut ValidatorLimits) -> &mut u64),
+    }
+
+    const LIMITS: &[(&str, Limit)] = &[
+        ("types", Limit::Count(|l| &mut l.types)),
+        ("functions", Limit::Count(|l| &mut l.functions)),
+        ("function-size", Limit::Count(|l| &mut l.function_size)),
+        ("function-locals", Limit::Count(|l| &mut l.function_locals)),
+        ("exports", Limit::Count(|l| &mut l.exports)),
+        ("globals", Limit::Count(|l| &mut l.globals)),
+        ("element-segments", Limit::Count(|l| &mut l.element_segments)),
+        ("data-segments", Limit::Count(|l| &mut l.data_segments)),
+        ("tables", Limit::Count(|l| &mut l.tables)),
+        ("table-entries", Limit::Count(|l| &mut l.table_entries)),
+        ("memories", Limit::Count(|l| &mut l.memories)),
+        ("memory32-pages", Limit::Pages(|l| &mut l.memory32_pages)),
+        ("memory64-pages", Limit::Pages(|l| &mut l.memory64_pages)),
+        ("tags", Limit::Count(|l| &mut l.tags)),
+        ("modules", Limit::Count(|l| &mut l.modules)),
+        ("components", Limit::Count(|l| &mut l.components)),
+        ("instances", Limit::Count(|l| &mut l.instances)),
+        ("values", Limit::Count(|l| &mut l.values)),
+    ];
+
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (name, value) = line
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `name = value` on line {}", i + 1))?;
+        let (name, value) = (name.trim(), value.trim());
+        let (_, limit) = LIMITS.iter().find(|(n, _)| *n == name).ok_or_else(|| {
+            anyhow!(
+                "unknown limit `{}` on line {}\nValid limits: {}",
+                name,
+                i + 1,
+                LIMITS
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+        })?;
+        let context = || format!("invalid value for limit `{name}` on line {}", i + 1);
+        match limit {
+            Limit::Count(accessor) => *accessor(&mut ret) = value.parse().with_context(context)?,
+            Limit::Pages(accessor) => *accessor(&mut ret) = value.parse().with_context(context)?,
+        }
+    }
+
+    Ok(ret)
+}
@@ -0,0 +1,32 @@
+;; RUN: component dedupe % -t
+
+(component
+  (core module
+    (func (export "f") (result i32) i32.const 1)
+  )
+  (core module
+    (func (export "f") (result i32) i32.const 2)
+  )
+  (core module
+    (func (export "f") (result i32) i32.const 1)
+  )
+  (core instance (instantiate 0))
+  (core instance (instantiate 1))
+  (core instance (instantiate 2))
+
+  (component
+    (core module)
+    (core module)
+    (core instance (instantiate 1))
+  )
+  (component
+    (core module)
+    (core module)
+    (core instance (instantiate 1))
+  )
+  (instance (instantiate 0))
+  (instance (instantiate 1))
+  (alias outer 0 1 (component))
+  (export "c" (component 2))
+  (export "a" (core module 2))
+)
@@ -0,0 +1,35 @@
+;; RUN: component size %
+
+(component $app
+  (import "log" (func $log (param "msg" string)))
+
+  (core module $libc
+    (memory (export "memory") 1)
+    (func (export "realloc") (param i32 i32 i32 i32) (result i32)
+      i32.const 0)
+  )
+  (core instance $libc (instantiate $libc))
+
+  (core func $log-lowered (canon lower (func $log)
+    (memory $libc "memory") (realloc (func $libc "realloc"))))
+
+  (core module $main
+    (import "env" "log" (func $log (param i32 i32)))
+    (func (export "run")
+      i32.const 0
+      i32.const 5
+      call $log)
+    (data (i32.const 0) "hello")
+  )
+  (core instance $main (instantiate $main
+    (with "env" (instance (export "log" (func $log-lowered))))))
+
+  (component $inner
+    (type $r (resource (rep i32)))
+    (core func $new (canon resource.new $r))
+    (core func $drop (canon resource.drop $r))
+  )
+  (instance (instantiate $inner))
+
+  (func (export "run") (canon lift (core func $main "run")))
+)
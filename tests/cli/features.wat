@@ -0,0 +1,27 @@
+;; RUN: features --target all %
+
+(module
+  (import "" "g" (global (mut i32)))
+  (memory i64 1)
+  (table 1 externref)
+  (func (param i32) (result i32)
+    local.get 0)
+  (func (param i32 i32) (result i32 i32)
+    local.get 0
+    local.get 1
+    block (param i32 i32) (result i32 i32)
+    end)
+  (func (result f64)
+    (local v128)
+    f64.const 1
+    f64.const 2
+    f64.add)
+  (func (param i32) (result i32)
+    local.get 0
+    i32.extend8_s)
+  (func (param i32)
+    i64.const 0
+    local.get 0
+    i64.const 0
+    memory.fill)
+)
@@ -0,0 +1,29 @@
+;; RUN: print --fold %
+
+(module
+  (tag $e (param i32))
+  (func $f (param i32) (result i32)
+    block $caught (result i32)
+      try_table (result i32) (catch $e $caught)
+        local.get 0
+        i32.const 1
+        i32.add
+      end
+      i32.const 2
+      i32.mul
+      return
+    end
+    i32.const 3
+    i32.add
+  )
+  (func $g (param exnref)
+    block $l (result exnref)
+      try_table (catch_all_ref $l)
+        local.get 0
+        throw_ref
+      end
+      unreachable
+    end
+    throw_ref
+  )
+)
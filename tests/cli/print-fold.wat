@@ -0,0 +1,53 @@
+;; RUN: print --fold %
+
+(module
+  (type $t (func (param i32) (result i32)))
+  (import "" "f" (func $imported (param i32 i32) (result i32)))
+  (tag $e (param i32))
+  (memory 1)
+  (func $add (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add)
+  (func $blocks (param i32) (result i32)
+    block (result i32)
+      local.get 0
+      local.get 0
+      call $imported
+      local.get 0
+      br_if 0
+      drop
+      local.get 0
+      if (result i32)
+        i32.const 1
+      else
+        i32.const 2
+      end
+    end
+    loop
+    end
+    local.get 0
+    i32.load offset=4
+    i32.add)
+  (func $multi (result i32 i32)
+    i32.const 1
+    i32.const 2)
+  (func $partial (result i32) (local i32)
+    call $multi
+    i32.add
+    local.tee 0
+    return)
+  (func $throw (param i32)
+    try
+      local.get 0
+      throw $e
+    catch $e
+      drop
+    catch_all
+    end)
+  (func $call-indirect (param i32) (result i32)
+    local.get 0
+    i32.const 0
+    call_indirect (type $t))
+  (table 1 funcref)
+)
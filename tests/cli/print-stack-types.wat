@@ -0,0 +1,30 @@
+;; RUN: print --stack-types %
+
+(module
+  (type $t (func (param i32) (result i32 i64)))
+  (func (param i32) (result f64)
+    (local i64)
+    local.get 0
+    block $b (param i32) (result f64)
+      drop
+      f64.const 1
+      local.get 0
+      br_if $b
+      unreachable
+      i32.add
+      drop
+      f64.const 2
+    end
+    local.get 0
+    local.get 0
+    if (type $t)
+      i64.const 1
+    else
+      drop
+      i32.const 2
+      local.get 1
+    end
+    drop
+    drop
+  )
+)
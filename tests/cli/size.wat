@@ -0,0 +1,26 @@
+;; RUN: size %
+
+(module $m
+  (import "env" "log" (func $log (param i32)))
+  (memory 1)
+  (global $g (mut i32) (i32.const 0))
+  (func $helper (param i32) (result i32)
+    local.get 0
+    i32.const 1
+    i32.add)
+  (func $shared (result i32)
+    i32.const 7
+    call $helper)
+  (func $a (export "a")
+    call $shared
+    call $log)
+  (func $b (export "b")
+    call $shared
+    drop
+    global.get $g
+    call $log)
+  (func $dead
+    i32.const 1
+    call $log)
+  (data (i32.const 0) "hello world")
+)
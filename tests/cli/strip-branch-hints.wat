@@ -0,0 +1,13 @@
+;; RUN: strip % | print
+
+(module
+  (func (param i32)
+    local.get 0
+    (@metadata.code.branch_hint "\00")
+    if
+      unreachable
+    end
+  )
+
+  (@custom "some-custom" "hello")
+)
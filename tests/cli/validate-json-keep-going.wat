@@ -0,0 +1,7 @@
+;; FAIL: validate --json --keep-going %
+
+(module
+  (func (result i32) i64.const 0)
+  (func)
+  (func call 10)
+)
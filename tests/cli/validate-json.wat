@@ -0,0 +1,6 @@
+;; RUN: validate --json --keep-going %
+
+(module
+  (func (result i32) i32.const 0)
+  (func (export "f"))
+)
@@ -0,0 +1,5 @@
+;; FAIL: validate % --limits tests/cli/validate-limits.txt
+
+(module
+  (func (local i32 i32 i32))
+)
@@ -0,0 +1,8 @@
+;; RUN: validate % --limits tests/cli/validate-limits.txt
+
+(module
+  (memory 1)
+  (func (local i32 i32))
+  (func)
+  (export "f" (func 0))
+)
@@ -0,0 +1,38 @@
+(module
+  (import "" "" (func))
+  (func $a (param i32) (result i32)
+    local.get 0
+    (@metadata.code.branch_hint "\01")
+    if (result i32)
+      i32.const 1
+    else
+      i32.const 2
+    end
+  )
+  (func $b (param i32)
+    block
+      local.get 0
+      (@metadata.code.branch_hint "\00")
+      br_if 0
+      (@metadata.code.branch_hint "\01")
+      (if (local.get 0) (then nop))
+      (@metadata.code.branch_hint "\00")
+      (br_if 0 (local.get 0))
+    end
+  )
+)
+
+(assert_malformed
+  (module quote
+    "(func (@metadata.code.branch_hint \"\\01\") nop)")
+  "branch hint must be followed by an `if` or `br_if` instruction")
+
+(assert_malformed
+  (module quote
+    "(func i32.const 0 (@metadata.code.branch_hint \"\\02\") if end)")
+  "invalid value for branch hint")
+
+(assert_malformed
+  (module quote
+    "(func i32.const 0 (@metadata.code.branch_hint \"\\01\"))")
+  "branch hint must be followed by an `if` or `br_if` instruction")
@@ -38,6 +38,15 @@
   (type $A22b (result $A7))
   (type $A22c (result (error $A8)))
   (type $A22d (result $A9 (error $A10)))
+
+  (type $A23a (future))
+  (type $A23b (future $A6))
+  (type $A23c (future (list u8)))
+
+  (type $A24a (stream))
+  (type $A24b (stream $A2))
+  (type $A24c (stream (end $A13)))
+  (type $A24d (stream $A2 (end (result))))
 )
 
 (assert_invalid
@@ -126,6 +135,12 @@
 (assert_invalid
   (component (type (tuple 0)))
   "index out of bounds")
+(assert_invalid
+  (component (type (future 0)))
+  "index out of bounds")
+(assert_invalid
+  (component (type (stream 0 (end 1))))
+  "index out of bounds")
 
 (assert_invalid
   (component (type (record (field "a-B-c-D" string) (field "A-b-C-d" u8))))
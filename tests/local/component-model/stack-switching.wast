@@ -0,0 +1,15 @@
+(component
+  (core type (func (param contref) (result nullcontref)))
+  (core type (module
+    (type (func (param (ref null cont))))
+    (import "" "f" (func (type 0)))
+  ))
+)
+
+(assert_malformed
+  (component quote "(core type (cont 0))")
+  "continuation types are not supported in components")
+
+(assert_malformed
+  (component quote "(core type (module (type (func)) (type (cont 0))))")
+  "continuation types are not supported in components")
@@ -0,0 +1,35 @@
+(module
+  (@custom "first" (before first) "a")
+  (@custom "after-type" (after type) "\00\01")
+  (@custom "before-code" (before code) "b")
+  (@custom "after-code" (after code) "c")
+  (@custom "after-data" (after data) "d")
+  (@custom "last" "e" "f")
+  (@producers
+    (language "wat" "1.0")
+    (sdk "x" "2.0")
+  )
+  (func $f (@name "the f"))
+  (memory 1)
+  (data "hi")
+)
+
+(module
+  (@custom "before-import" (before import) "")
+  (@custom "after-global" (after global) "x")
+  (@custom "after-last" (after last) "y")
+  (import "" "" (func))
+  (global i32 (i32.const 0))
+)
+
+;; an explicit `name` section replaces the generated one
+(module
+  (@custom "name" "\00\02\01m")
+  (func $f)
+)
+
+;; an invalid `name` section is printed as-is
+(module
+  (@custom "name" (after func) "\ff")
+  (func $f)
+)
@@ -1,95 +1,175 @@
 (module
   (tag $a (param i32))
+  (tag $b)
 
   (func
     (; empty try_table ;)
     try_table
     end
 
-    (; try_table with result  ;)
+    (; try_table with result ;)
     try_table (result i32)
       i32.const 0
     end
     drop
 
-    (; try_table can catches ;)
-    try_table (catch $a 0)
-    end
-
-    try_table (catch $a 0) (catch $a 0)
-    end
-
-    try_table (catch_all 0)
-    end
-
-    try_table (catch $a 0) (catch_all 0)
-    end
-
-    try_table (catch $a 0) (catch $a 0) (catch_all 0)
-    end
-
-    (; try_table can have results and catches ;)
-    try_table (result i32) (catch $a 0)
+    (; try_table can have catches, resolved relative to the enclosing block ;)
+    block $l (result i32)
+      try_table (catch $a $l)
+      end
       i32.const 0
     end
     drop
 
-    try_table (result i32) (catch $a 0) (catch $a 0)
+    block $l (result i32)
+      try_table (catch $a $l) (catch $a 0)
+      end
       i32.const 0
     end
     drop
 
-    try_table (result i32) (catch_all 0)
-      i32.const 0
+    block $l
+      try_table (catch_all $l) (catch $b 0)
+      end
     end
-    drop
 
-    try_table (result i32) (catch $a 0) (catch_all 0)
-      i32.const 0
+    (; catch_all may come before other clauses ;)
+    block $l
+      try_table (catch_all 0) (catch $b 0) (catch_all 0)
+      end
     end
-    drop
 
-    try_table (result i32) (catch $a 0) (catch $a 0) (catch_all 0)
-      i32.const 0
+    (; try_table can have results and catches ;)
+    block $l (result i32)
+      try_table (result i32) (catch $a $l) (catch_all 1)
+        i32.const 0
+      end
     end
     drop
 
     (; mixes of catch, catch_ref, catch_all, and catch_all_ref ;)
-
-    try_table (catch_ref $a 0)
-    end
-
-    try_table (catch_ref $a 0) (catch_ref $a 0)
-    end
-
-    try_table (catch $a 0) (catch_ref $a 0)
-    end
-
-    try_table (catch_ref $a 0) (catch_ref $a 0)
-    end
-
-    try_table (catch_ref $a 0) (catch $a 0)
-    end
-
-    try_table (catch_all 0)
-    end
-
-    try_table (catch_all_ref 0)
-    end
-
-    try_table (catch_ref $a 0) (catch_all_ref 0)
+    block $l (result i32 exnref)
+      try_table (catch_ref $a $l)
+      end
+      unreachable
     end
+    drop
+    drop
 
-    try_table (catch_ref $a 0) (catch_ref $a 0) (catch_all_ref 0)
+    block $l (result exnref)
+      try_table (catch_ref $b $l) (catch_all_ref $l)
+      end
+      unreachable
     end
+    drop
 
-    try_table (catch $a 0) (catch_ref $a 0) (catch_all_ref 0)
+    block $l (result (ref exn))
+      try_table (catch_all_ref $l) (catch_ref $b 0)
+      end
+      unreachable
     end
+    drop
+  )
 
-    try_table (catch_ref $a 0) (catch_ref $a 0) (catch_all_ref 0)
+  (func (param exnref)
+    (; rethrowing a caught exception ;)
+    block $l (result exnref)
+      try_table (catch_all_ref $l)
+        local.get 0
+        throw_ref
+      end
+      unreachable
     end
+    throw_ref
+  )
 
-    try_table (catch_ref $a 0) (catch $a 0) (catch_all_ref 0)
-    end
+  (func
+    (; folded try_table ;)
+    (drop
+      (block $l (result i32)
+        (try_table (result i32) (catch $a $l) (catch_all 1)
+          (i32.const 0))))
+    (try_table
+      (try_table (catch_all 1)))
   )
 )
+
+(assert_invalid
+  (module
+    (tag $a (param i32))
+    (func
+      block $l
+        try_table (catch $a $l)
+        end
+      end
+    )
+  )
+  "type mismatch")
+
+(assert_invalid
+  (module
+    (tag $a (param i32))
+    (func
+      block $l (result i32)
+        try_table (catch_ref $a $l)
+        end
+        unreachable
+      end
+      drop
+    )
+  )
+  "type mismatch")
+
+(assert_invalid
+  (module
+    (func
+      block $l (result i32)
+        try_table (catch_all $l)
+        end
+        unreachable
+      end
+      drop
+    )
+  )
+  "type mismatch")
+
+(assert_invalid
+  (module
+    (func
+      block $l (result i32)
+        try_table (catch_all_ref $l)
+        end
+        unreachable
+      end
+      drop
+    )
+  )
+  "type mismatch")
+
+(assert_invalid
+  (module
+    (func
+      try_table (catch_all 1)
+      end
+    )
+  )
+  "unknown label")
+
+(assert_invalid
+  (module
+    (func
+      try_table
+      catch_all
+      end
+    )
+  )
+  "catch_all found outside of a `try` block")
+
+(assert_invalid
+  (module
+    (func (param externref)
+      local.get 0
+      throw_ref
+    )
+  )
+  "type mismatch")
@@ -0,0 +1,148 @@
+;; --enable-gc
+
+(module
+  (type $s (struct (field i32) (field (mut i64)) (field (mut i8))))
+  (type $a (array (mut i32)))
+  (type $p (array (mut i16)))
+  (type $r (array (mut funcref)))
+  (type $imm (array i32))
+
+  (data $d "\00\01\02\03")
+  (elem $e func $f)
+
+  (global (ref $s) (struct.new $s (i32.const 1) (i64.const 2) (i32.const 3)))
+  (global (ref $a) (array.new_fixed $a 2 (i32.const 1) (i32.const 2)))
+  (global anyref (any.convert_extern (ref.null extern)))
+  (global externref (extern.convert_any (ref.null any)))
+
+  (func $f)
+
+  (func (param $x (ref null $s)) (result i32 i64 i32 i32)
+    (struct.set $s 1 (local.get $x) (i64.const 0))
+    (struct.set $s 2 (local.get $x) (i32.const 0))
+    (struct.get $s 0 (local.get $x))
+    (struct.get $s 1 (local.get $x))
+    (struct.get_s $s 2 (local.get $x))
+    (struct.get_u $s 2 (local.get $x))
+  )
+
+  (func (result (ref $s))
+    struct.new_default $s
+  )
+
+  (func (param $x (ref null $a)) (param $y (ref null $p)) (result i32)
+    (drop (array.new $a (i32.const 0) (i32.const 10)))
+    (drop (array.new_default $p (i32.const 10)))
+    (drop (array.new_data $p $d (i32.const 0) (i32.const 2)))
+    (drop (array.new_elem $r $e (i32.const 0) (i32.const 1)))
+    (array.set $a (local.get $x) (i32.const 0) (i32.const 1))
+    (array.fill $a (local.get $x) (i32.const 0) (i32.const 1) (i32.const 2))
+    (array.copy $a $a
+      (local.get $x) (i32.const 0) (local.get $x) (i32.const 1) (i32.const 2))
+    (array.init_data $p $d (local.get $y) (i32.const 0) (i32.const 0) (i32.const 1))
+    (array.init_elem $r $e
+      (array.new_default $r (i32.const 1)) (i32.const 0) (i32.const 0) (i32.const 1))
+    (drop (array.get_s $p (local.get $y) (i32.const 0)))
+    (drop (array.get_u $p (local.get $y) (i32.const 0)))
+    (drop (array.len (local.get $x)))
+    (array.get $a (local.get $x) (i32.const 0))
+  )
+
+  (func (param $x anyref) (param $y eqref) (result i32)
+    (drop (ref.eq (local.get $y) (ref.i31 (i32.const 0))))
+    (drop (ref.test (ref $s) (local.get $x)))
+    (drop (ref.test (ref null $s) (local.get $x)))
+    (drop (ref.cast (ref i31) (local.get $x)))
+    (drop (ref.cast i31ref (local.get $x)))
+    i32.const 0
+  )
+
+  (func (param $x anyref) (result anyref)
+    (block $fail (result anyref)
+      (block $ok (result (ref $s))
+        (br_on_cast $ok anyref (ref $s) (local.get $x))
+        (br_on_cast_fail $fail anyref (ref $s))
+      )
+    )
+  )
+)
+
+(assert_invalid
+  (module
+    (type $s (struct (field i32)))
+    (func (param (ref $s))
+      (struct.set $s 0 (local.get 0) (i32.const 0))
+    )
+  )
+  "field is immutable"
+)
+
+(assert_invalid
+  (module
+    (type $a (array i32))
+    (func (param (ref $a))
+      (array.set $a (local.get 0) (i32.const 0) (i32.const 0))
+    )
+  )
+  "array is immutable"
+)
+
+(assert_invalid
+  (module
+    (type $s (struct (field (ref any))))
+    (func (drop (struct.new_default $s)))
+  )
+  "field type is not defaultable"
+)
+
+(assert_invalid
+  (module
+    (type $s (struct (field i8)))
+    (func (param (ref $s)) (result i32)
+      (struct.get $s 0 (local.get 0))
+    )
+  )
+  "can't use struct.get on packed storage types"
+)
+
+(assert_invalid
+  (module
+    (type $a (array i32))
+    (func (param (ref $a)) (result i32)
+      (array.get_u $a (local.get 0) (i32.const 0))
+    )
+  )
+  "can only use array.get_s and array.get_u on packed storage types"
+)
+
+(assert_invalid
+  (module
+    (type $a (array (mut anyref)))
+    (data $d "")
+    (func (drop (array.new_data $a $d (i32.const 0) (i32.const 0))))
+  )
+  "array type is not numeric or vector"
+)
+
+(assert_invalid
+  (module
+    (type $s (struct))
+    (func (param funcref) (result i32)
+      (ref.test (ref $s) (local.get 0))
+    )
+  )
+  "type mismatch"
+)
+
+(assert_invalid
+  (module
+    (type $s (struct))
+    (func (param (ref $s)) (result anyref)
+      (block $l (result (ref $s))
+        (br_on_cast $l (ref $s) anyref (local.get 0))
+        (return)
+      )
+    )
+  )
+  "type mismatch"
+)
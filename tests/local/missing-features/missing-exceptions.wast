@@ -10,3 +10,12 @@
     (import "" "" (tag))
     )
   "exceptions proposal not enabled")
+
+(assert_invalid
+  (module
+    (func
+      try_table
+      end
+    )
+  )
+  "exceptions support is not enabled")
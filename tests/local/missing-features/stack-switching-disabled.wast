@@ -0,0 +1,6 @@
+(assert_invalid
+  (module
+    (type $ft (func))
+    (type (cont $ft)))
+  "stack switching support is not enabled")
+
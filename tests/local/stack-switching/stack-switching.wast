@@ -0,0 +1,118 @@
+(module
+  (type $ft (func (param i32) (result i32)))
+  (type $ct (cont $ft))
+  (type $ft0 (func (result i32)))
+  (type $ct0 (cont $ft0))
+  (rec
+    (type $fs (func (param (ref null $cs)) (result i32)))
+    (type $cs (cont $fs)))
+
+  (tag $yield (param i32) (result i32))
+  (tag $swap (result i32))
+  (tag $exn (param i32))
+
+  (global (mut contref) (ref.null cont))
+  (global (mut nullcontref) (ref.null nocont))
+  (global (mut (ref null $ct)) (ref.null $ct))
+
+  (elem declare func $f $g)
+
+  (func $f (param i32) (result i32)
+    local.get 0
+    suspend $yield)
+
+  (func $g (type $fs)
+    local.get 0
+    switch $cs $swap
+    drop
+    i32.const 0)
+
+  (func (export "resume") (result i32)
+    (block $on_yield (result i32 (ref $ct))
+      i32.const 1
+      ref.func $f
+      cont.new $ct
+      resume $ct (on $yield $on_yield)
+      return)
+    drop)
+
+  (func (export "bind") (result (ref $ct0))
+    i32.const 2
+    ref.func $f
+    cont.new $ct
+    cont.bind $ct $ct0)
+
+  (func (export "resume-throw") (result i32)
+    i32.const 3
+    ref.func $f
+    cont.new $ct
+    resume_throw $ct $exn)
+
+  (func (export "switch") (result i32)
+    ref.null $cs
+    ref.func $g
+    cont.new $cs
+    resume $cs (on $swap switch))
+)
+
+(assert_invalid
+  (module
+    (type $s (struct))
+    (type (cont $s)))
+  "continuation type must refer to a function type")
+
+(assert_invalid
+  (module
+    (type $ft (func))
+    (func
+      ref.null $ft
+      cont.new $ft
+      drop))
+  "expected continuation type")
+
+(assert_invalid
+  (module
+    (type $ft (func (param i32) (result i32)))
+    (type $ct (cont $ft))
+    (type $ft0 (func (param i32 i32) (result i32)))
+    (type $ct0 (cont $ft0))
+    (func (param (ref $ct))
+      local.get 0
+      cont.bind $ct $ct0
+      drop))
+  "more parameters than argument type")
+
+(assert_invalid
+  (module
+    (type $ft (func (result i32)))
+    (type $ct (cont $ft))
+    (tag $t (param i64) (result i32))
+    (func (param (ref $ct)) (result i32)
+      (block $l (result i32 (ref $ct))
+        local.get 0
+        resume $ct (on $t $l)
+        return)
+      drop))
+  "handler label does not match tag parameters")
+
+(assert_invalid
+  (module
+    (type $ft (func (result i32)))
+    (type $ct (cont $ft))
+    (tag $t (result i32))
+    (func (param (ref $ct)) (result i32)
+      (block $l (result i32)
+        local.get 0
+        resume $ct (on $t $l)
+        return)))
+  "handler label must end with a continuation reference")
+
+(assert_invalid
+  (module
+    (type $ft (func (result i32)))
+    (type $ct (cont $ft))
+    (tag $t (param i32) (result i32))
+    (func (param (ref $ct)) (result i32)
+      local.get 0
+      resume $ct (on $t switch)))
+  "switch tag must not have parameters")
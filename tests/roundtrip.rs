@@ -3,7 +3,8 @@
 //!
 //! * For `*.wast` files, parses them and runs as many directives as we can.
 //! * For `*.wast` files, parse them with `wat`, parse that with `wasmparser`,
-//!   and make sure `wasmprinter` + `wat` produces the same bytes.
+//!   and make sure `wasmprinter` + `wat` produces the same bytes, as does
+//!   re-encoding core modules and components with `wasm-encoder`.
 //! * For `*.wasm`, assert they're either valid or invalid depending on their
 //!   path name,
 //!
@@ -168,28 +169,7 @@ fn skip_test(test: &Path, contents: &[u8]) -> bool {
 }
 
 fn skip_validation(test: &Path) -> bool {
-    let broken = &[
-        "gc/gc-array.wat",
-        "gc/gc-struct.wat",
-        "/proposals/gc/array.wast",
-        "/proposals/gc/array_copy.wast",
-        "/proposals/gc/array_fill.wast",
-        "/proposals/gc/array_init_data.wast",
-        "/proposals/gc/array_init_elem.wast",
-        "/proposals/gc/binary-gc.wast",
-        "/proposals/gc/br_on_cast.wast",
-        "/proposals/gc/br_on_cast_fail.wast",
-        "/proposals/gc/extern.wast",
-        "/proposals/gc/global.wast",
-        "/proposals/gc/ref_cast.wast",
-        "/proposals/gc/ref_eq.wast",
-        "/proposals/gc/ref_test.wast",
-        "/proposals/gc/struct.wast",
-        "/proposals/gc/type-subtyping.wast",
-        "/exnref/exnref.wast",
-        "/exnref/throw_ref.wast",
-        "/exnref/try_table.wast",
-    ];
+    let broken = &["gc/gc-array.wat", "gc/gc-struct.wat"];
     let test_path = test.to_str().unwrap().replace("\\", "/"); // for windows paths
     if broken.iter().any(|x| test_path.contains(x)) {
         return true;
@@ -252,6 +232,51 @@ impl TestState {
             self.bump_ntests();
             self.binary_compare(&binary2, contents)
                 .context("failed to compare original `wat` with roundtrip `wat`")?;
+
+            // The folded form of the text format should assemble to the same
+            // bytes as well.
+            let mut printer = wasmprinter::Printer::new();
+            printer.fold_instructions(true);
+            let folded = printer
+                .print(contents)
+                .context("failed to print folded wasm")?;
+            let binary3 = wat::parse_str(&folded)
+                .context("failed to parse folded `wat` from `wasmprinter`")?;
+            self.bump_ntests();
+            self.binary_compare(&binary3, contents)
+                .context("failed to compare original `wat` with folded roundtrip `wat`")?;
+
+            // Re-encoding a core module or component with `wasm-encoder`
+            // should produce the same binary too. This compares the printed
+            // form of the binaries since `wasm-encoder` doesn't always pick the
+            // same shorthand encodings of reference types as the text format.
+            let reencoded = if Parser::is_core_wasm(contents) {
+                use wasm_encoder::reencode::{Reencode, RoundtripReencoder};
+
+                let mut module = wasm_encoder::Module::new();
+                RoundtripReencoder
+                    .parse_core_module(&mut module, Parser::new(0), contents)
+                    .context("failed to re-encode module")?;
+                Some(module.finish())
+            } else if Parser::is_component(contents) {
+                use wasm_encoder::reencode::{ReencodeComponent, RoundtripReencoder};
+
+                let mut component = wasm_encoder::Component::new();
+                RoundtripReencoder
+                    .parse_component(&mut component, Parser::new(0), contents)
+                    .context("failed to re-encode component")?;
+                Some(component.finish())
+            } else {
+                None
+            };
+            if let Some(reencoded) = reencoded {
+                let reencoded = wasmprinter::print_bytes(reencoded)
+                    .context("failed to print re-encoded binary")?;
+                self.bump_ntests();
+                if reencoded != string {
+                    bail!("re-encoded binary differs from the original");
+                }
+            }
         }
 
         // Test that the `wasmprinter`-printed bytes have "pretty" whitespace
@@ -441,6 +466,14 @@ impl TestState {
     fn test_wasm_valid(&self, test: &Path, contents: &[u8]) -> Result<()> {
         self.wasmparser_validator_for(test).validate_all(contents)?;
         self.bump_ntests();
+
+        // The features detected as required should be enough to validate the
+        // input on their own.
+        let required = wasmparser::required_features(contents)?;
+        Validator::new_with_features(required.features)
+            .validate_all(contents)
+            .context("failed to validate with only the required features")?;
+        self.bump_ntests();
         Ok(())
     }
 
@@ -625,6 +658,7 @@ impl TestState {
             memory_control: true,
             gc: true,
             component_model_values: true,
+            stack_switching: true,
         };
         for part in test.iter().filter_map(|t| t.to_str()) {
             match part {
@@ -650,6 +684,7 @@ impl TestState {
                     features.function_references = false;
                     features.gc = false;
                     features.component_model_values = false;
+                    features.stack_switching = false;
                 }
                 "floats-disabled.wast" => features.floats = false,
                 "threads" => {
@@ -674,6 +709,10 @@ impl TestState {
                     features.function_references = true;
                     features.gc = true;
                 }
+                "stack-switching" => {
+                    features.function_references = true;
+                    features.stack_switching = true;
+                }
                 _ => {}
             }
         }